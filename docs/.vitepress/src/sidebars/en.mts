@@ -117,6 +117,7 @@ const items = [
                             { text: "Overview", link: "/en/RobustMQ-MQTT/Security/Authentication" },
                             { text: "Password", link: "/en/RobustMQ-MQTT/Security/Authentication-Password" },
                             { text: "JWT", link: "/en/RobustMQ-MQTT/Security/Authentication-JWT" },
+                            { text: "SCRAM", link: "/en/RobustMQ-MQTT/Security/Authentication-SCRAM" },
                         ]
                     },
                     { text: "Authorization", link: "/en/RobustMQ-MQTT/Security/Authorization" },
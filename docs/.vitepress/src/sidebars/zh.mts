@@ -117,6 +117,7 @@ const items = [
                             { text: "概览", link: "/zh/RobustMQ-MQTT/Security/Authentication" },
                             { text: "Password", link: "/zh/RobustMQ-MQTT/Security/Authentication-Password" },
                             { text: "JWT", link: "/zh/RobustMQ-MQTT/Security/Authentication-JWT" },
+                            { text: "SCRAM", link: "/zh/RobustMQ-MQTT/Security/Authentication-SCRAM" },
                         ]
                     },
                     { text: "授权", link: "/zh/RobustMQ-MQTT/Security/Authorization" },
@@ -560,6 +560,46 @@ pub async fn delete_schema_bind<T>(&self, request: &T) -> Result<String, HttpCli
             .await
     }
 
+    /// Get rule list
+    pub async fn get_rule_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.get_with_params(&api_path(CLUSTER_RULE_LIST_PATH), request)
+            .await
+    }
+
+    /// Create rule
+    pub async fn create_rule<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(CLUSTER_RULE_CREATE_PATH), request)
+            .await
+    }
+
+    /// Update rule
+    pub async fn update_rule<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(CLUSTER_RULE_UPDATE_PATH), request)
+            .await
+    }
+
+    /// Delete rule
+    pub async fn delete_rule<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(CLUSTER_RULE_DELETE_PATH), request)
+            .await
+    }
+
     /// Get system alarm list
     pub async fn get_system_alarm_list<T, R>(
         &self,
@@ -23,27 +23,35 @@
     config_cassandra::CassandraConnectorConfig,
     config_clickhouse::ClickHouseConnectorConfig,
     config_elasticsearch::ElasticsearchConnectorConfig,
+    config_file_tail::FileTailConnectorConfig,
     config_greptimedb::GreptimeDBConnectorConfig,
     config_influxdb::InfluxDBConnectorConfig,
     config_kafka::KafkaConnectorConfig,
+    config_kafka_source::KafkaSourceConnectorConfig,
     config_local_file::LocalFileConnectorConfig,
     config_mongodb::MongoDBConnectorConfig,
     config_mqtt::MqttBridgeConnectorConfig,
+    config_mqtt_source::MqttBridgeSourceConnectorConfig,
     config_mysql::MySQLConnectorConfig,
     config_opentsdb::OpenTSDBConnectorConfig,
     config_postgres::PostgresConnectorConfig,
+    config_postgres_cdc::PostgresCDCConnectorConfig,
     config_pulsar::PulsarConnectorConfig,
     config_rabbitmq::RabbitMQConnectorConfig,
+    config_rabbitmq_source::RabbitMQSourceConnectorConfig,
     config_redis::RedisConnectorConfig,
+    config_redis_stream::RedisStreamConnectorConfig,
     config_s3::S3ConnectorConfig,
     config_webhook::WebhookConnectorConfig,
     connector_type::{
         CONNECTOR_TYPE_CASSANDRA, CONNECTOR_TYPE_CLICKHOUSE, CONNECTOR_TYPE_ELASTICSEARCH,
-        CONNECTOR_TYPE_FILE, CONNECTOR_TYPE_GREPTIMEDB, CONNECTOR_TYPE_INFLUXDB,
-        CONNECTOR_TYPE_KAFKA, CONNECTOR_TYPE_MONGODB, CONNECTOR_TYPE_MQTT_BRIDGE,
+        CONNECTOR_TYPE_FILE, CONNECTOR_TYPE_FILE_TAIL, CONNECTOR_TYPE_GREPTIMEDB,
+        CONNECTOR_TYPE_INFLUXDB, CONNECTOR_TYPE_KAFKA, CONNECTOR_TYPE_KAFKA_SOURCE,
+        CONNECTOR_TYPE_MONGODB, CONNECTOR_TYPE_MQTT_BRIDGE, CONNECTOR_TYPE_MQTT_BRIDGE_SOURCE,
         CONNECTOR_TYPE_MYSQL, CONNECTOR_TYPE_OPENTSDB, CONNECTOR_TYPE_POSTGRES,
-        CONNECTOR_TYPE_PULSAR, CONNECTOR_TYPE_RABBITMQ, CONNECTOR_TYPE_REDIS, CONNECTOR_TYPE_S3,
-        CONNECTOR_TYPE_WEBHOOK,
+        CONNECTOR_TYPE_POSTGRES_CDC, CONNECTOR_TYPE_PULSAR, CONNECTOR_TYPE_RABBITMQ,
+        CONNECTOR_TYPE_RABBITMQ_SOURCE, CONNECTOR_TYPE_REDIS, CONNECTOR_TYPE_REDIS_STREAM,
+        CONNECTOR_TYPE_S3, CONNECTOR_TYPE_WEBHOOK,
     },
     rule::ETLRule,
     status::MQTTStatus,
@@ -217,7 +225,8 @@ fn validate_connector_type(connector_type: &str) -> Result<(), validator::Valida
     let mut err = validator::ValidationError::new("invalid_connector_type");
     err.message = Some(std::borrow::Cow::from(
         "Connector type must be kafka, pulsar, rabbitmq, greptime, postgres, mysql, mongodb, \
-         elasticsearch, redis, webhook, opentsdb, mqtt, clickhouse, influxdb, cassandra, s3 or file",
+         elasticsearch, redis, webhook, opentsdb, mqtt, clickhouse, influxdb, cassandra, s3, file, \
+         kafka_source, mqtt_source, postgres_cdc, redis_stream, rabbitmq_source or file_tail",
     ));
     Err(err)
 }
@@ -456,6 +465,36 @@ fn parse_connector_type(type_str: &str, config: &str) -> Result<ConnectorType, C
             c.validate()?;
             ConnectorType::S3(c)
         }
+        CONNECTOR_TYPE_KAFKA_SOURCE => {
+            let c: KafkaSourceConnectorConfig = serde_json::from_str(config)?;
+            c.validate()?;
+            ConnectorType::KafkaSource(c)
+        }
+        CONNECTOR_TYPE_MQTT_BRIDGE_SOURCE => {
+            let c: MqttBridgeSourceConnectorConfig = serde_json::from_str(config)?;
+            c.validate()?;
+            ConnectorType::MqttBridgeSource(c)
+        }
+        CONNECTOR_TYPE_POSTGRES_CDC => {
+            let c: PostgresCDCConnectorConfig = serde_json::from_str(config)?;
+            c.validate()?;
+            ConnectorType::PostgresCDC(c)
+        }
+        CONNECTOR_TYPE_REDIS_STREAM => {
+            let c: RedisStreamConnectorConfig = serde_json::from_str(config)?;
+            c.validate()?;
+            ConnectorType::RedisStream(c)
+        }
+        CONNECTOR_TYPE_RABBITMQ_SOURCE => {
+            let c: RabbitMQSourceConnectorConfig = serde_json::from_str(config)?;
+            c.validate()?;
+            ConnectorType::RabbitMQSource(c)
+        }
+        CONNECTOR_TYPE_FILE_TAIL => {
+            let c: FileTailConnectorConfig = serde_json::from_str(config)?;
+            c.validate()?;
+            ConnectorType::FileTail(c)
+        }
         _ => return Err(CommonError::IneligibleConnectorType(type_str.to_string())),
     };
     Ok(connector_type)
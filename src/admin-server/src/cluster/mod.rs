@@ -31,7 +31,9 @@
 pub mod message;
 pub mod node;
 pub mod offset;
+pub mod rule;
 pub mod schema;
+pub mod schema_registry;
 pub mod share_group;
 pub mod tenant;
 pub mod topic;
@@ -16,6 +16,7 @@
 use axum::extract::State;
 use broker_core::cluster::ClusterStorage;
 use common_base::http_response::{error_response, success_response};
+use network_server::common::tls_cert::reload_tls_cert;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use validator::Validate;
@@ -58,3 +59,14 @@ pub async fn node_leave(
         Err(e) => error_response(e.to_string()),
     }
 }
+
+/// Reload the TLS certificate, key and OCSP response of this node's listeners
+/// from the configured files. New handshakes use the new certificate; open
+/// connections are not affected. The files are also watched, so this is only
+/// needed to pick up a rotation before the next check.
+pub async fn node_tls_reload() -> String {
+    match reload_tls_cert() {
+        Ok(()) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::extract::{Query, State};
+use common_base::http_response::{error_response, success_response};
+use common_base::tools::now_second;
+use metadata_struct::rule::{Rule, RuleAction};
+use mqtt_broker::{core::error::MqttBrokerError, storage::rule::RuleStorage};
+use rule_engine::sql::SqlRule;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    state::HttpState,
+    tool::extractor::ValidatedJson,
+    tool::{
+        query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+        PageReplyData,
+    },
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RuleListReq {
+    pub tenant: Option<String>,
+    pub name: Option<String>,
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RuleActionReq {
+    // "republish" or "connector"
+    pub action_type: String,
+
+    #[serde(default)]
+    pub topic: String,
+
+    #[serde(default)]
+    pub payload: Option<String>,
+
+    #[serde(default)]
+    pub connector_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Validate)]
+pub struct CreateRuleReq {
+    #[validate(length(min = 1, max = 128, message = "Tenant length must be between 1-128"))]
+    pub tenant: String,
+
+    #[validate(length(min = 1, max = 128, message = "Rule name length must be between 1-128"))]
+    pub rule_name: String,
+
+    #[validate(length(min = 1, max = 65536, message = "SQL length must be between 1-65536"))]
+    pub sql: String,
+
+    #[validate(length(min = 1, message = "At least one action is required"))]
+    pub actions: Vec<RuleActionReq>,
+
+    #[serde(default)]
+    #[validate(length(max = 500, message = "Description length cannot exceed 500"))]
+    pub desc: String,
+
+    #[serde(default = "default_enable")]
+    pub enable: bool,
+}
+
+fn default_enable() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Validate)]
+pub struct DeleteRuleReq {
+    #[validate(length(min = 1, max = 128, message = "Tenant length must be between 1-128"))]
+    pub tenant: String,
+
+    #[validate(length(min = 1, max = 128, message = "Rule name length must be between 1-128"))]
+    pub rule_name: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RuleListRow {
+    pub tenant: String,
+    pub rule_name: String,
+    pub sql: String,
+    pub actions: Vec<RuleActionReq>,
+    pub desc: String,
+    pub enable: bool,
+    pub create_time: u64,
+    pub update_time: u64,
+}
+
+impl Queryable for RuleListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "tenant" => Some(self.tenant.clone()),
+            "rule_name" => Some(self.rule_name.clone()),
+            "enable" => Some(self.enable.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn rule_list(
+    State(state): State<Arc<HttpState>>,
+    Query(params): Query<RuleListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let rule_storage = RuleStorage::new(state.client_pool.clone());
+    let rules = match rule_storage.list(params.tenant, params.name).await {
+        Ok(rules) => rules,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let rows: Vec<RuleListRow> = rules
+        .into_iter()
+        .map(|rule| RuleListRow {
+            tenant: rule.tenant,
+            rule_name: rule.name,
+            sql: rule.sql,
+            actions: rule.actions.iter().map(action_to_req).collect(),
+            desc: rule.desc,
+            enable: rule.enable,
+            create_time: rule.create_time,
+            update_time: rule.update_time,
+        })
+        .collect();
+
+    let filtered = apply_filters(rows, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+pub async fn rule_create(
+    State(state): State<Arc<HttpState>>,
+    ValidatedJson(params): ValidatedJson<CreateRuleReq>,
+) -> String {
+    if let Err(e) = rule_save(state, params, false).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+pub async fn rule_update(
+    State(state): State<Arc<HttpState>>,
+    ValidatedJson(params): ValidatedJson<CreateRuleReq>,
+) -> String {
+    if let Err(e) = rule_save(state, params, true).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+pub async fn rule_delete(
+    State(state): State<Arc<HttpState>>,
+    ValidatedJson(params): ValidatedJson<DeleteRuleReq>,
+) -> String {
+    let rule_storage = RuleStorage::new(state.client_pool.clone());
+    if let Err(e) = rule_storage.delete(&params.tenant, &params.rule_name).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+async fn rule_save(
+    state: Arc<HttpState>,
+    req: CreateRuleReq,
+    update: bool,
+) -> Result<(), MqttBrokerError> {
+    SqlRule::compile(&req.sql)?;
+    let actions = req
+        .actions
+        .iter()
+        .map(action_from_req)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let rule_storage = RuleStorage::new(state.client_pool.clone());
+    let now = now_second();
+    let create_time = if update {
+        rule_storage
+            .list(Some(req.tenant.clone()), Some(req.rule_name.clone()))
+            .await?
+            .first()
+            .map(|rule| rule.create_time)
+            .unwrap_or(now)
+    } else {
+        now
+    };
+
+    let rule = Rule {
+        tenant: req.tenant,
+        name: req.rule_name,
+        sql: req.sql,
+        actions,
+        desc: req.desc,
+        enable: req.enable,
+        create_time,
+        update_time: now,
+    };
+    if update {
+        rule_storage.update(&rule).await?;
+    } else {
+        rule_storage.create(&rule).await?;
+    }
+    Ok(())
+}
+
+fn action_from_req(action: &RuleActionReq) -> Result<RuleAction, MqttBrokerError> {
+    match action.action_type.as_str() {
+        "republish" if !action.topic.is_empty() => Ok(RuleAction::Republish {
+            topic: action.topic.clone(),
+            payload: action.payload.clone(),
+        }),
+        "connector" if !action.connector_name.is_empty() => Ok(RuleAction::Connector {
+            connector_name: action.connector_name.clone(),
+        }),
+        "republish" => Err(MqttBrokerError::CommonError(
+            "republish action requires a topic".to_string(),
+        )),
+        "connector" => Err(MqttBrokerError::CommonError(
+            "connector action requires a connector_name".to_string(),
+        )),
+        other => Err(MqttBrokerError::CommonError(format!(
+            "invalid rule action type {}, must be republish or connector",
+            other
+        ))),
+    }
+}
+
+fn action_to_req(action: &RuleAction) -> RuleActionReq {
+    match action {
+        RuleAction::Republish { topic, payload } => RuleActionReq {
+            action_type: "republish".to_string(),
+            topic: topic.clone(),
+            payload: payload.clone(),
+            connector_name: String::new(),
+        },
+        RuleAction::Connector { connector_name } => RuleActionReq {
+            action_type: "connector".to_string(),
+            topic: String::new(),
+            payload: None,
+            connector_name: connector_name.clone(),
+        },
+    }
+}
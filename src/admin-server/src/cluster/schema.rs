@@ -14,8 +14,9 @@
 
 use axum::extract::{Query, State};
 use common_base::http_response::{error_response, success_response};
-use metadata_struct::schema::{SchemaData, SchemaType};
+use metadata_struct::schema::{SchemaData, SchemaResourceBind, SchemaType};
 use mqtt_broker::{core::error::MqttBrokerError, storage::schema::SchemaStorage};
+use schema_register::compatibility::parse_schema;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use validator::Validate;
@@ -55,7 +56,11 @@ pub struct CreateSchemaReq {
     #[validate(custom(function = "validate_schema_type"))]
     pub schema_type: String,
 
-    #[validate(length(min = 1, max = 8192, message = "Schema length must be between 1-8192"))]
+    #[validate(length(
+        min = 1,
+        max = 1048576,
+        message = "Schema length must be between 1-1048576"
+    ))]
     pub schema: String,
 
     #[validate(length(max = 500, message = "Description length cannot exceed 500"))]
@@ -120,6 +125,16 @@ pub struct CreateSchemaBindReq {
         message = "Resource name length must be between 1-256"
     ))]
     pub resource_name: String,
+
+    #[serde(default)]
+    #[validate(length(max = 256, message = "Message name length cannot exceed 256"))]
+    pub message_name: String,
+
+    #[serde(default)]
+    pub strict_unknown_fields: bool,
+
+    #[serde(default)]
+    pub strict_required_fields: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Validate)]
@@ -239,6 +254,8 @@ pub async fn schema_create_inner(
         _ => return Err(MqttBrokerError::InvalidSchemaType(req.schema_type.clone())),
     };
 
+    parse_schema(&schema_type, &req.schema)?;
+
     let schema_data = SchemaData {
         tenant: req.tenant.clone(),
         name: req.schema_name.clone(),
@@ -326,11 +343,16 @@ pub async fn schema_bind_create(
     State(state): State<Arc<HttpState>>,
     ValidatedJson(params): ValidatedJson<CreateSchemaBindReq>,
 ) -> String {
+    let bind = SchemaResourceBind {
+        tenant: params.tenant,
+        schema_name: params.schema_name,
+        resource_name: params.resource_name,
+        message_name: params.message_name,
+        strict_unknown_fields: params.strict_unknown_fields,
+        strict_required_fields: params.strict_required_fields,
+    };
     let schema_storage = SchemaStorage::new(state.client_pool.clone());
-    if let Err(e) = schema_storage
-        .create_bind(&params.tenant, &params.schema_name, &params.resource_name)
-        .await
-    {
+    if let Err(e) = schema_storage.create_bind(&bind).await {
         return error_response(e.to_string());
     }
     success_response("success")
@@ -0,0 +1,631 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Confluent Schema Registry compatible endpoints, so Kafka clients using
+//! Confluent serializers can point `schema.registry.url` at
+//! `http://<admin>/api/schema-registry`. Unlike the rest of the admin API these
+//! handlers answer with Confluent's plain JSON bodies and error codes.
+
+use crate::state::HttpState;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use metadata_struct::schema::{SchemaCompatibility, SchemaType, SchemaVersion};
+use metadata_struct::tenant::DEFAULT_TENANT;
+use mqtt_broker::storage::schema::SchemaStorage;
+use protocol::meta::meta_service_common::ListSchemaVersionRequest;
+use schema_register::compatibility::{check_compatibility, is_same_schema, parse_schema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+const SUBJECT_NOT_FOUND: u32 = 40401;
+const VERSION_NOT_FOUND: u32 = 40402;
+const SCHEMA_NOT_FOUND: u32 = 40403;
+const SUBJECT_LEVEL_COMPATIBILITY_NOT_CONFIGURED: u32 = 40408;
+const INCOMPATIBLE_SCHEMA: u32 = 409;
+const INVALID_SCHEMA: u32 = 42201;
+const INVALID_VERSION: u32 = 42202;
+const INVALID_COMPATIBILITY_LEVEL: u32 = 42203;
+const STORE_ERROR: u32 = 50001;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct RegistryQuery {
+    pub tenant: Option<String>,
+    pub verbose: Option<bool>,
+    #[serde(rename = "defaultToGlobal")]
+    pub default_to_global: Option<bool>,
+}
+
+impl RegistryQuery {
+    fn tenant(&self) -> &str {
+        self.tenant.as_deref().unwrap_or(DEFAULT_TENANT)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegisterSchemaReq {
+    pub schema: String,
+    #[serde(rename = "schemaType")]
+    pub schema_type: Option<String>,
+}
+
+impl RegisterSchemaReq {
+    fn schema_type(&self) -> Result<SchemaType, Response> {
+        // Confluent clients leave the type out for Avro.
+        SchemaType::from_str(self.schema_type.as_deref().unwrap_or("AVRO"))
+            .map_err(|e| registry_error(StatusCode::UNPROCESSABLE_ENTITY, INVALID_SCHEMA, e))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CompatibilityConfigReq {
+    pub compatibility: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SchemaVersionResp {
+    pub subject: String,
+    pub id: u32,
+    pub version: u32,
+    #[serde(rename = "schemaType", skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<String>,
+    pub schema: String,
+}
+
+impl From<SchemaVersion> for SchemaVersionResp {
+    fn from(version: SchemaVersion) -> Self {
+        SchemaVersionResp {
+            subject: version.subject,
+            id: version.id,
+            version: version.version,
+            schema_type: confluent_schema_type(&version.schema_type),
+            schema: version.schema,
+        }
+    }
+}
+
+// Confluent leaves `schemaType` out for Avro, the original format.
+fn confluent_schema_type(schema_type: &SchemaType) -> Option<String> {
+    match schema_type {
+        SchemaType::AVRO => None,
+        other => Some(other.to_string().to_uppercase()),
+    }
+}
+
+fn registry_response<T: Serialize>(status: StatusCode, body: T) -> Response {
+    (status, [(header::CONTENT_TYPE, CONTENT_TYPE)], Json(body)).into_response()
+}
+
+fn registry_ok<T: Serialize>(body: T) -> Response {
+    registry_response(StatusCode::OK, body)
+}
+
+fn registry_error(status: StatusCode, error_code: u32, message: impl ToString) -> Response {
+    registry_response(
+        status,
+        json!({ "error_code": error_code, "message": message.to_string() }),
+    )
+}
+
+fn store_error(message: impl ToString) -> Response {
+    registry_error(StatusCode::INTERNAL_SERVER_ERROR, STORE_ERROR, message)
+}
+
+fn subject_not_found(subject: &str) -> Response {
+    registry_error(
+        StatusCode::NOT_FOUND,
+        SUBJECT_NOT_FOUND,
+        format!("Subject '{subject}' not found."),
+    )
+}
+
+fn parse_compatibility(level: &str) -> Result<SchemaCompatibility, Response> {
+    SchemaCompatibility::from_str(level).map_err(|e| {
+        registry_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            INVALID_COMPATIBILITY_LEVEL,
+            e,
+        )
+    })
+}
+
+async fn list_versions(
+    state: &Arc<HttpState>,
+    request: ListSchemaVersionRequest,
+) -> Result<Vec<SchemaVersion>, Response> {
+    let storage = SchemaStorage::new(state.client_pool.clone());
+    let mut versions = storage.list_versions(request).await.map_err(store_error)?;
+    versions.sort_by_key(|v| v.version);
+    Ok(versions)
+}
+
+async fn subject_versions(
+    state: &Arc<HttpState>,
+    tenant: &str,
+    subject: &str,
+) -> Result<Vec<SchemaVersion>, Response> {
+    let versions = list_versions(
+        state,
+        ListSchemaVersionRequest {
+            tenant: tenant.to_string(),
+            subject: subject.to_string(),
+            ..Default::default()
+        },
+    )
+    .await?;
+    if versions.is_empty() {
+        return Err(subject_not_found(subject));
+    }
+    Ok(versions)
+}
+
+// `version` is a positive number, or `latest` / `-1` for the newest version.
+fn find_version(
+    versions: Vec<SchemaVersion>,
+    subject: &str,
+    version: &str,
+) -> Result<SchemaVersion, Response> {
+    let found = if version == "latest" || version == "-1" {
+        versions.into_iter().last()
+    } else {
+        let number = version
+            .parse::<u32>()
+            .ok()
+            .filter(|v| *v > 0)
+            .ok_or_else(|| {
+                registry_error(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    INVALID_VERSION,
+                    format!(
+                        "The specified version '{version}' is not a valid version id. \
+                     Allowed values are between [1, 2^31-1] and the string \"latest\""
+                    ),
+                )
+            })?;
+        versions.into_iter().find(|v| v.version == number)
+    };
+    found.ok_or_else(|| {
+        registry_error(
+            StatusCode::NOT_FOUND,
+            VERSION_NOT_FOUND,
+            format!("Version {version} not found for subject '{subject}'."),
+        )
+    })
+}
+
+async fn compatibility_level(
+    state: &Arc<HttpState>,
+    tenant: &str,
+    subject: Option<&str>,
+) -> Result<Option<SchemaCompatibility>, Response> {
+    SchemaStorage::new(state.client_pool.clone())
+        .get_compatibility(tenant, subject)
+        .await
+        .map_err(store_error)
+}
+
+async fn effective_compatibility(
+    state: &Arc<HttpState>,
+    tenant: &str,
+    subject: &str,
+) -> Result<SchemaCompatibility, Response> {
+    if let Some(level) = compatibility_level(state, tenant, Some(subject)).await? {
+        return Ok(level);
+    }
+    Ok(compatibility_level(state, tenant, None)
+        .await?
+        .unwrap_or_default())
+}
+
+pub async fn subject_list(
+    State(state): State<Arc<HttpState>>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    let versions = match list_versions(
+        &state,
+        ListSchemaVersionRequest {
+            tenant: params.tenant().to_string(),
+            ..Default::default()
+        },
+    )
+    .await
+    {
+        Ok(versions) => versions,
+        Err(resp) => return resp,
+    };
+    let subjects: BTreeSet<String> = versions.into_iter().map(|v| v.subject).collect();
+    registry_ok(subjects)
+}
+
+pub async fn subject_version_list(
+    State(state): State<Arc<HttpState>>,
+    Path(subject): Path<String>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    match subject_versions(&state, params.tenant(), &subject).await {
+        Ok(versions) => registry_ok(versions.iter().map(|v| v.version).collect::<Vec<_>>()),
+        Err(resp) => resp,
+    }
+}
+
+pub async fn subject_version_get(
+    State(state): State<Arc<HttpState>>,
+    Path((subject, version)): Path<(String, String)>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    let result = match subject_versions(&state, params.tenant(), &subject).await {
+        Ok(versions) => find_version(versions, &subject, &version),
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok(version) => registry_ok(SchemaVersionResp::from(version)),
+        Err(resp) => resp,
+    }
+}
+
+pub async fn subject_version_schema(
+    State(state): State<Arc<HttpState>>,
+    Path((subject, version)): Path<(String, String)>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    let result = match subject_versions(&state, params.tenant(), &subject).await {
+        Ok(versions) => find_version(versions, &subject, &version),
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok(version) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, CONTENT_TYPE)],
+            version.schema,
+        )
+            .into_response(),
+        Err(resp) => resp,
+    }
+}
+
+pub async fn subject_version_register(
+    State(state): State<Arc<HttpState>>,
+    Path(subject): Path<String>,
+    Query(params): Query<RegistryQuery>,
+    Json(req): Json<RegisterSchemaReq>,
+) -> Response {
+    let tenant = params.tenant();
+    let schema_type = match req.schema_type() {
+        Ok(schema_type) => schema_type,
+        Err(resp) => return resp,
+    };
+    if let Err(e) = parse_schema(&schema_type, &req.schema) {
+        return registry_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            INVALID_SCHEMA,
+            format!("Invalid schema: {e}"),
+        );
+    }
+
+    // Checked here as well as in meta-service so incompatible schemas get
+    // Confluent's 409 rather than a store error.
+    let previous = match list_versions(
+        &state,
+        ListSchemaVersionRequest {
+            tenant: tenant.to_string(),
+            subject: subject.clone(),
+            ..Default::default()
+        },
+    )
+    .await
+    {
+        Ok(versions) => versions,
+        Err(resp) => return resp,
+    };
+    if let Some(existing) = previous.iter().find(|v| {
+        v.schema_type == schema_type && is_same_schema(&schema_type, &v.schema, &req.schema)
+    }) {
+        return registry_ok(json!({ "id": existing.id }));
+    }
+    let level = match effective_compatibility(&state, tenant, &subject).await {
+        Ok(level) => level,
+        Err(resp) => return resp,
+    };
+    match check_compatibility(&schema_type, &req.schema, &previous, level) {
+        Ok(errors) if errors.is_empty() => {}
+        Ok(errors) => {
+            return registry_error(
+                StatusCode::CONFLICT,
+                INCOMPATIBLE_SCHEMA,
+                format!(
+                    "Schema being registered is incompatible with an earlier schema for subject \
+                     '{subject}': {}",
+                    errors.join("; ")
+                ),
+            )
+        }
+        Err(e) => return registry_error(StatusCode::UNPROCESSABLE_ENTITY, INVALID_SCHEMA, e),
+    }
+
+    let storage = SchemaStorage::new(state.client_pool.clone());
+    match storage
+        .register_version(tenant, &subject, &schema_type.to_string(), &req.schema)
+        .await
+    {
+        Ok(version) => registry_ok(json!({ "id": version.id })),
+        Err(e) => store_error(e),
+    }
+}
+
+/// Looks up which version of the subject, if any, holds the posted schema.
+pub async fn subject_lookup(
+    State(state): State<Arc<HttpState>>,
+    Path(subject): Path<String>,
+    Query(params): Query<RegistryQuery>,
+    Json(req): Json<RegisterSchemaReq>,
+) -> Response {
+    let schema_type = match req.schema_type() {
+        Ok(schema_type) => schema_type,
+        Err(resp) => return resp,
+    };
+    let versions = match subject_versions(&state, params.tenant(), &subject).await {
+        Ok(versions) => versions,
+        Err(resp) => return resp,
+    };
+    match versions.into_iter().find(|v| {
+        v.schema_type == schema_type && is_same_schema(&schema_type, &v.schema, &req.schema)
+    }) {
+        Some(version) => registry_ok(SchemaVersionResp::from(version)),
+        None => registry_error(StatusCode::NOT_FOUND, SCHEMA_NOT_FOUND, "Schema not found"),
+    }
+}
+
+pub async fn subject_delete(
+    State(state): State<Arc<HttpState>>,
+    Path(subject): Path<String>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    let tenant = params.tenant();
+    if let Err(resp) = subject_versions(&state, tenant, &subject).await {
+        return resp;
+    }
+    let storage = SchemaStorage::new(state.client_pool.clone());
+    match storage.delete_version(tenant, &subject, 0).await {
+        Ok(versions) => registry_ok(versions),
+        Err(e) => store_error(e),
+    }
+}
+
+pub async fn subject_version_delete(
+    State(state): State<Arc<HttpState>>,
+    Path((subject, version)): Path<(String, String)>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    let tenant = params.tenant();
+    let result = match subject_versions(&state, tenant, &subject).await {
+        Ok(versions) => find_version(versions, &subject, &version),
+        Err(resp) => return resp,
+    };
+    let version = match result {
+        Ok(version) => version,
+        Err(resp) => return resp,
+    };
+    let storage = SchemaStorage::new(state.client_pool.clone());
+    match storage
+        .delete_version(tenant, &subject, version.version)
+        .await
+    {
+        Ok(_) => registry_ok(version.version),
+        Err(e) => store_error(e),
+    }
+}
+
+pub async fn schema_by_id(State(state): State<Arc<HttpState>>, Path(id): Path<u32>) -> Response {
+    let versions = match list_versions(
+        &state,
+        ListSchemaVersionRequest {
+            id,
+            ..Default::default()
+        },
+    )
+    .await
+    {
+        Ok(versions) => versions,
+        Err(resp) => return resp,
+    };
+    match versions.into_iter().next() {
+        Some(version) => {
+            let mut body = json!({ "schema": version.schema });
+            if let Some(schema_type) = confluent_schema_type(&version.schema_type) {
+                body["schemaType"] = json!(schema_type);
+            }
+            registry_ok(body)
+        }
+        None => registry_error(
+            StatusCode::NOT_FOUND,
+            SCHEMA_NOT_FOUND,
+            format!("Schema {id} not found"),
+        ),
+    }
+}
+
+pub async fn schema_id_versions(
+    State(state): State<Arc<HttpState>>,
+    Path(id): Path<u32>,
+) -> Response {
+    let versions = match list_versions(
+        &state,
+        ListSchemaVersionRequest {
+            id,
+            ..Default::default()
+        },
+    )
+    .await
+    {
+        Ok(versions) => versions,
+        Err(resp) => return resp,
+    };
+    if versions.is_empty() {
+        return registry_error(
+            StatusCode::NOT_FOUND,
+            SCHEMA_NOT_FOUND,
+            format!("Schema {id} not found"),
+        );
+    }
+    let body: Vec<_> = versions
+        .iter()
+        .map(|v| json!({ "subject": v.subject, "version": v.version }))
+        .collect();
+    registry_ok(body)
+}
+
+pub async fn schema_types() -> Response {
+    registry_ok(["JSON", "PROTOBUF", "AVRO"])
+}
+
+/// Tests the posted schema against one version of the subject, in the
+/// direction(s) of the subject's compatibility level.
+pub async fn compatibility_check(
+    State(state): State<Arc<HttpState>>,
+    Path((subject, version)): Path<(String, String)>,
+    Query(params): Query<RegistryQuery>,
+    Json(req): Json<RegisterSchemaReq>,
+) -> Response {
+    let tenant = params.tenant();
+    let schema_type = match req.schema_type() {
+        Ok(schema_type) => schema_type,
+        Err(resp) => return resp,
+    };
+    if let Err(e) = parse_schema(&schema_type, &req.schema) {
+        return registry_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            INVALID_SCHEMA,
+            format!("Invalid schema: {e}"),
+        );
+    }
+    let result = match subject_versions(&state, tenant, &subject).await {
+        Ok(versions) => find_version(versions, &subject, &version),
+        Err(resp) => return resp,
+    };
+    let previous = match result {
+        Ok(version) => version,
+        Err(resp) => return resp,
+    };
+    let level = match effective_compatibility(&state, tenant, &subject).await {
+        Ok(level) => level,
+        Err(resp) => return resp,
+    };
+
+    match check_compatibility(&schema_type, &req.schema, &[previous], level) {
+        Ok(errors) => {
+            let mut body = json!({ "is_compatible": errors.is_empty() });
+            if params.verbose.unwrap_or(false) {
+                body["messages"] = json!(errors);
+            }
+            registry_ok(body)
+        }
+        Err(e) => registry_error(StatusCode::UNPROCESSABLE_ENTITY, INVALID_SCHEMA, e),
+    }
+}
+
+pub async fn config_get(
+    State(state): State<Arc<HttpState>>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    match compatibility_level(&state, params.tenant(), None).await {
+        Ok(level) => {
+            registry_ok(json!({ "compatibilityLevel": level.unwrap_or_default().to_string() }))
+        }
+        Err(resp) => resp,
+    }
+}
+
+pub async fn config_set(
+    State(state): State<Arc<HttpState>>,
+    Query(params): Query<RegistryQuery>,
+    Json(req): Json<CompatibilityConfigReq>,
+) -> Response {
+    set_compatibility(&state, params.tenant(), None, &req.compatibility).await
+}
+
+pub async fn subject_config_get(
+    State(state): State<Arc<HttpState>>,
+    Path(subject): Path<String>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    let tenant = params.tenant();
+    let level = match compatibility_level(&state, tenant, Some(&subject)).await {
+        Ok(Some(level)) => level,
+        Ok(None) if params.default_to_global.unwrap_or(false) => {
+            match compatibility_level(&state, tenant, None).await {
+                Ok(level) => level.unwrap_or_default(),
+                Err(resp) => return resp,
+            }
+        }
+        Ok(None) => {
+            return registry_error(
+                StatusCode::NOT_FOUND,
+                SUBJECT_LEVEL_COMPATIBILITY_NOT_CONFIGURED,
+                format!("Subject '{subject}' does not have subject-level compatibility configured"),
+            )
+        }
+        Err(resp) => return resp,
+    };
+    registry_ok(json!({ "compatibilityLevel": level.to_string() }))
+}
+
+pub async fn subject_config_set(
+    State(state): State<Arc<HttpState>>,
+    Path(subject): Path<String>,
+    Query(params): Query<RegistryQuery>,
+    Json(req): Json<CompatibilityConfigReq>,
+) -> Response {
+    set_compatibility(&state, params.tenant(), Some(&subject), &req.compatibility).await
+}
+
+pub async fn subject_config_delete(
+    State(state): State<Arc<HttpState>>,
+    Path(subject): Path<String>,
+    Query(params): Query<RegistryQuery>,
+) -> Response {
+    let tenant = params.tenant();
+    let level = match compatibility_level(&state, tenant, Some(&subject)).await {
+        Ok(Some(level)) => level,
+        Ok(None) => return subject_not_found(&subject),
+        Err(resp) => return resp,
+    };
+    let storage = SchemaStorage::new(state.client_pool.clone());
+    match storage.delete_compatibility(tenant, Some(&subject)).await {
+        Ok(()) => registry_ok(json!({ "compatibilityLevel": level.to_string() })),
+        Err(e) => store_error(e),
+    }
+}
+
+async fn set_compatibility(
+    state: &Arc<HttpState>,
+    tenant: &str,
+    subject: Option<&str>,
+    level: &str,
+) -> Response {
+    let level = match parse_compatibility(level) {
+        Ok(level) => level,
+        Err(resp) => return resp,
+    };
+    let storage = SchemaStorage::new(state.client_pool.clone());
+    match storage.set_compatibility(tenant, subject, level).await {
+        Ok(()) => registry_ok(json!({ "compatibility": level.to_string() })),
+        Err(e) => store_error(e),
+    }
+}
@@ -150,6 +150,7 @@ pub async fn user_create(
         salt: None,
         is_superuser: params.is_superuser,
         create_time: now_second(),
+        scram_credentials: Vec::new(),
     };
 
     let user_storage = UserStorage::new(state.client_pool.clone());
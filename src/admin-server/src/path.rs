@@ -34,6 +34,7 @@
 
 // Cluster Node management
 pub const CLUSTER_NODE_LEAVE_PATH: &str = "/cluster/node/leave";
+pub const CLUSTER_NODE_TLS_RELOAD_PATH: &str = "/cluster/node/tls/reload";
 
 // Cluster Topic API paths
 pub const CLUSTER_TOPIC_LIST_PATH: &str = "/cluster/topic/list";
@@ -70,6 +71,30 @@
 pub const CLUSTER_SCHEMA_BIND_CREATE_PATH: &str = "/cluster/schema-bind/create";
 pub const CLUSTER_SCHEMA_BIND_DELETE_PATH: &str = "/cluster/schema-bind/delete";
 
+// Cluster Rule API paths
+pub const CLUSTER_RULE_LIST_PATH: &str = "/cluster/rule/list";
+pub const CLUSTER_RULE_CREATE_PATH: &str = "/cluster/rule/create";
+pub const CLUSTER_RULE_UPDATE_PATH: &str = "/cluster/rule/update";
+pub const CLUSTER_RULE_DELETE_PATH: &str = "/cluster/rule/delete";
+
+// Confluent-compatible Schema Registry API paths
+pub const SCHEMA_REGISTRY_SUBJECTS_PATH: &str = "/schema-registry/subjects";
+pub const SCHEMA_REGISTRY_SUBJECT_PATH: &str = "/schema-registry/subjects/{subject}";
+pub const SCHEMA_REGISTRY_SUBJECT_VERSIONS_PATH: &str =
+    "/schema-registry/subjects/{subject}/versions";
+pub const SCHEMA_REGISTRY_SUBJECT_VERSION_PATH: &str =
+    "/schema-registry/subjects/{subject}/versions/{version}";
+pub const SCHEMA_REGISTRY_SUBJECT_VERSION_SCHEMA_PATH: &str =
+    "/schema-registry/subjects/{subject}/versions/{version}/schema";
+pub const SCHEMA_REGISTRY_SCHEMA_ID_PATH: &str = "/schema-registry/schemas/ids/{id}";
+pub const SCHEMA_REGISTRY_SCHEMA_ID_VERSIONS_PATH: &str =
+    "/schema-registry/schemas/ids/{id}/versions";
+pub const SCHEMA_REGISTRY_SCHEMA_TYPES_PATH: &str = "/schema-registry/schemas/types";
+pub const SCHEMA_REGISTRY_COMPATIBILITY_PATH: &str =
+    "/schema-registry/compatibility/subjects/{subject}/versions/{version}";
+pub const SCHEMA_REGISTRY_CONFIG_PATH: &str = "/schema-registry/config";
+pub const SCHEMA_REGISTRY_SUBJECT_CONFIG_PATH: &str = "/schema-registry/config/{subject}";
+
 // Cluster User API paths
 pub const CLUSTER_USER_LIST_PATH: &str = "/cluster/user/list";
 pub const CLUSTER_USER_CREATE_PATH: &str = "/cluster/user/create";
@@ -166,6 +191,10 @@ fn test_api_path() {
         assert_eq!(api_path(MQTT_OVERVIEW_PATH), "/api/mqtt/overview");
         assert_eq!(api_path(CLUSTER_CONFIG_GET_PATH), "/api/cluster/config/get");
         assert_eq!(api_path(CLUSTER_USER_LIST_PATH), "/api/cluster/user/list");
+        assert_eq!(
+            api_path(SCHEMA_REGISTRY_SUBJECTS_PATH),
+            "/api/schema-registry/subjects"
+        );
     }
 
     #[test]
@@ -28,11 +28,19 @@
         connector::{connector_create, connector_delete, connector_detail, connector_list},
         health::{health_cluster, health_node, health_ready},
         message::{read_message, send_message},
-        node::node_leave,
+        node::{node_leave, node_tls_reload},
+        rule::{rule_create, rule_delete, rule_list, rule_update},
         schema::{
             schema_bind_create, schema_bind_delete, schema_bind_list, schema_create, schema_delete,
             schema_list,
         },
+        schema_registry::{
+            compatibility_check, config_get, config_set, schema_by_id, schema_id_versions,
+            schema_types, subject_config_delete, subject_config_get, subject_config_set,
+            subject_delete, subject_list, subject_lookup, subject_version_delete,
+            subject_version_get, subject_version_list, subject_version_register,
+            subject_version_schema,
+        },
         share_group::{share_group_detail, share_group_list},
         tenant::{tenant_create, tenant_delete, tenant_list, tenant_update},
         topic::{topic_create, topic_delete, topic_detail, topic_list},
@@ -140,6 +148,7 @@ fn api_route(&self) -> Router<Arc<HttpState>> {
             .merge(self.mqtt_route())
             .merge(self.mq9_route())
             .merge(self.kafka_route())
+            .merge(self.schema_registry_route())
             .merge(self.engine_route())
     }
 
@@ -150,6 +159,7 @@ fn common_route(&self) -> Router<Arc<HttpState>> {
             .route(CLUSTER_CONFIG_GET_PATH, get(cluster_config_get))
             // node
             .route(CLUSTER_NODE_LEAVE_PATH, post(node_leave))
+            .route(CLUSTER_NODE_TLS_RELOAD_PATH, post(node_tls_reload))
             // tenant
             .route(TENANT_LIST_PATH, get(tenant_list))
             .route(TENANT_CREATE_PATH, post(tenant_create))
@@ -219,6 +229,11 @@ fn cluster_resource_route(&self) -> Router<Arc<HttpState>> {
             .route(CLUSTER_SCHEMA_BIND_LIST_PATH, get(schema_bind_list))
             .route(CLUSTER_SCHEMA_BIND_CREATE_PATH, post(schema_bind_create))
             .route(CLUSTER_SCHEMA_BIND_DELETE_PATH, post(schema_bind_delete))
+            // rule
+            .route(CLUSTER_RULE_LIST_PATH, get(rule_list))
+            .route(CLUSTER_RULE_CREATE_PATH, post(rule_create))
+            .route(CLUSTER_RULE_UPDATE_PATH, post(rule_update))
+            .route(CLUSTER_RULE_DELETE_PATH, post(rule_delete))
             // user
             .route(CLUSTER_USER_LIST_PATH, get(user_list))
             .route(CLUSTER_USER_CREATE_PATH, post(user_create))
@@ -273,6 +288,48 @@ fn mq9_route(&self) -> Router<Arc<HttpState>> {
     fn kafka_route(&self) -> Router<Arc<HttpState>> {
         Router::new()
     }
+
+    fn schema_registry_route(&self) -> Router<Arc<HttpState>> {
+        Router::new()
+            // subjects
+            .route(SCHEMA_REGISTRY_SUBJECTS_PATH, get(subject_list))
+            .route(
+                SCHEMA_REGISTRY_SUBJECT_PATH,
+                post(subject_lookup).delete(subject_delete),
+            )
+            .route(
+                SCHEMA_REGISTRY_SUBJECT_VERSIONS_PATH,
+                get(subject_version_list).post(subject_version_register),
+            )
+            .route(
+                SCHEMA_REGISTRY_SUBJECT_VERSION_PATH,
+                get(subject_version_get).delete(subject_version_delete),
+            )
+            .route(
+                SCHEMA_REGISTRY_SUBJECT_VERSION_SCHEMA_PATH,
+                get(subject_version_schema),
+            )
+            // schemas
+            .route(SCHEMA_REGISTRY_SCHEMA_ID_PATH, get(schema_by_id))
+            .route(
+                SCHEMA_REGISTRY_SCHEMA_ID_VERSIONS_PATH,
+                get(schema_id_versions),
+            )
+            .route(SCHEMA_REGISTRY_SCHEMA_TYPES_PATH, get(schema_types))
+            // compatibility
+            .route(
+                SCHEMA_REGISTRY_COMPATIBILITY_PATH,
+                post(compatibility_check),
+            )
+            // config
+            .route(SCHEMA_REGISTRY_CONFIG_PATH, get(config_get).put(config_set))
+            .route(
+                SCHEMA_REGISTRY_SUBJECT_CONFIG_PATH,
+                get(subject_config_get)
+                    .put(subject_config_set)
+                    .delete(subject_config_delete),
+            )
+    }
 }
 
 /// Public health endpoints served at `/health/*` (no `/api` prefix, no auth).
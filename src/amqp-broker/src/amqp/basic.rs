@@ -21,18 +21,23 @@
 use amq_protocol::protocol::confirm;
 use amq_protocol::protocol::confirm::SelectOk as ConfirmSelectOk;
 use amq_protocol::protocol::AMQPClass;
-use amq_protocol::types::{AMQPValue, FieldTable};
+use amq_protocol::types::{AMQPValue, FieldArray, FieldTable};
+use delay_message::manager::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::storage::record::{StorageRecord, StorageRecordProtocolDataAmqp};
+use schema_register::schema::SchemaRegisterManager;
 use storage_adapter::driver::StorageDriverManager;
 use tracing::{error, warn};
 
+use crate::amqp::channel::channel_error_close;
 use crate::amqp::consume::{
     cancel_channel_consumers, cancel_connection_consumers, process_cancel, process_consume,
     process_get,
 };
 use crate::amqp::route;
-use crate::core::cache::{AmqpCacheManager, PendingPublish, UnackedEntry};
+use crate::core::cache::{AmqpCacheManager, PendingPublish, Settlement, TxSettle, UnackedEntry};
+use crate::core::dead_letter::dead_letter_stored;
+use crate::core::queue_args::{logical_queue_name, DeathReason};
 use crate::core::recovery::requeue_message;
 use crate::core::unacked_index;
 use crate::push::AmqpPushManager;
@@ -65,7 +70,7 @@ pub(crate) fn properties_to_protocol_data(
             .as_ref()
             .map(|table| route::field_table_to_map(table).into_iter().collect())
             .unwrap_or_default(),
-        redelivered: false,
+        ..Default::default()
     }
 }
 
@@ -92,11 +97,12 @@ pub(crate) fn properties_from_protocol_data(
     if let Some(v) = &amqp.content_encoding {
         properties = properties.with_content_encoding(v.as_str().into());
     }
-    if !amqp.headers.is_empty() {
+    if !amqp.headers.is_empty() || !amqp.x_death.is_empty() {
         let mut table = FieldTable::default();
         for (k, v) in &amqp.headers {
             table.insert(k.as_str().into(), AMQPValue::LongString(v.as_str().into()));
         }
+        insert_x_death(&mut table, amqp);
         properties = properties.with_headers(table);
     }
     if let Some(v) = amqp.delivery_mode {
@@ -135,11 +141,76 @@ pub(crate) fn properties_from_protocol_data(
     properties
 }
 
+/// Renders the stored `x-death` history the way RabbitMQ sends it: an
+/// array of tables, most recent first, plus the `x-first-death-*` headers
+/// naming the oldest death.
+fn insert_x_death(table: &mut FieldTable, amqp: &StorageRecordProtocolDataAmqp) {
+    let Some(first) = amqp.x_death.last() else {
+        return;
+    };
+    let mut deaths = FieldArray::default();
+    for death in &amqp.x_death {
+        let mut entry = FieldTable::default();
+        entry.insert("count".into(), AMQPValue::LongLongInt(death.count as i64));
+        entry.insert(
+            "reason".into(),
+            AMQPValue::LongString(death.reason.as_str().into()),
+        );
+        entry.insert(
+            "queue".into(),
+            AMQPValue::LongString(death.queue.as_str().into()),
+        );
+        entry.insert("time".into(), AMQPValue::Timestamp(death.time));
+        entry.insert(
+            "exchange".into(),
+            AMQPValue::LongString(death.exchange.as_str().into()),
+        );
+        let mut routing_keys = FieldArray::default();
+        for key in &death.routing_keys {
+            routing_keys.push(AMQPValue::LongString(key.as_str().into()));
+        }
+        entry.insert("routing-keys".into(), AMQPValue::FieldArray(routing_keys));
+        deaths.push(AMQPValue::FieldTable(entry));
+    }
+    table.insert("x-death".into(), AMQPValue::FieldArray(deaths));
+    table.insert(
+        "x-first-death-reason".into(),
+        AMQPValue::LongString(first.reason.as_str().into()),
+    );
+    table.insert(
+        "x-first-death-queue".into(),
+        AMQPValue::LongString(first.queue.as_str().into()),
+    );
+    table.insert(
+        "x-first-death-exchange".into(),
+        AMQPValue::LongString(first.exchange.as_str().into()),
+    );
+}
+
+/// The exchange and routing key reported on Basic.Deliver/GetOk: those the
+/// message was published (or dead-lettered) with, falling back to the
+/// default exchange and its queue's name for records stored before they
+/// were kept.
+pub(crate) fn delivery_route(record: &StorageRecord, store: &str) -> (String, String) {
+    match record
+        .protocol_data
+        .as_ref()
+        .and_then(|pd| pd.amqp.as_ref())
+        .filter(|amqp| !amqp.routing_key.is_empty())
+    {
+        Some(amqp) => (amqp.exchange.clone(), amqp.routing_key.clone()),
+        None => (String::new(), logical_queue_name(store).to_string()),
+    }
+}
+
+#[derive(Clone)]
 pub(crate) struct BasicCtx {
     pub storage_driver_manager: Arc<StorageDriverManager>,
     pub amqp_cache: Arc<AmqpCacheManager>,
     pub client_pool: Arc<ClientPool>,
     pub push_manager: Arc<AmqpPushManager>,
+    pub delay_message_manager: Arc<DelayMessageManager>,
+    pub schema_manager: Arc<SchemaRegisterManager>,
 }
 
 pub(crate) async fn process_basic_full(
@@ -160,10 +231,10 @@ pub(crate) async fn process_basic_full(
             .await
         }
         AMQPMethod::Ack(ack) => {
-            process_settle(
-                Some(ack.delivery_tag),
+            settle_or_buffer(
+                ack.delivery_tag,
                 ack.multiple,
-                false,
+                Settlement::Ack,
                 connection_id,
                 channel_id,
                 ctx,
@@ -172,10 +243,10 @@ pub(crate) async fn process_basic_full(
             None
         }
         AMQPMethod::Nack(nack) => {
-            process_settle(
-                Some(nack.delivery_tag),
+            settle_or_buffer(
+                nack.delivery_tag,
                 nack.multiple,
-                nack.requeue,
+                Settlement::rejected(nack.requeue),
                 connection_id,
                 channel_id,
                 ctx,
@@ -184,10 +255,10 @@ pub(crate) async fn process_basic_full(
             None
         }
         AMQPMethod::Reject(reject) => {
-            process_settle(
-                Some(reject.delivery_tag),
+            settle_or_buffer(
+                reject.delivery_tag,
                 false,
-                reject.requeue,
+                Settlement::rejected(reject.requeue),
                 connection_id,
                 channel_id,
                 ctx,
@@ -196,12 +267,28 @@ pub(crate) async fn process_basic_full(
             None
         }
         AMQPMethod::RecoverAsync(recover) => {
-            process_settle(None, false, true, connection_id, channel_id, ctx).await;
+            process_settle(
+                None,
+                false,
+                Settlement::Requeue,
+                connection_id,
+                channel_id,
+                ctx,
+            )
+            .await;
             let _ = recover.requeue;
             None
         }
         AMQPMethod::Recover(recover) => {
-            process_settle(None, false, true, connection_id, channel_id, ctx).await;
+            process_settle(
+                None,
+                false,
+                Settlement::Requeue,
+                connection_id,
+                channel_id,
+                ctx,
+            )
+            .await;
             let _ = recover.requeue;
             Some(vec![AMQPFrame::Method(
                 channel_id,
@@ -249,10 +336,47 @@ pub(crate) async fn process_basic_full(
     }
 }
 
-async fn process_settle(
+/// Ack/Nack/Reject take effect immediately unless the channel is in a
+/// transaction, in which case they wait for Tx.Commit.
+async fn settle_or_buffer(
+    delivery_tag: u64,
+    multiple: bool,
+    settlement: Settlement,
+    connection_id: u64,
+    channel_id: u16,
+    ctx: &BasicCtx,
+) {
+    let in_tx = ctx
+        .amqp_cache
+        .get_channel(connection_id, channel_id)
+        .is_some_and(|channel| channel.tx_mode.load(Ordering::SeqCst));
+    if in_tx {
+        ctx.amqp_cache.buffer_tx_settle(
+            connection_id,
+            channel_id,
+            TxSettle {
+                delivery_tag,
+                multiple,
+                settlement,
+            },
+        );
+        return;
+    }
+    process_settle(
+        Some(delivery_tag),
+        multiple,
+        settlement,
+        connection_id,
+        channel_id,
+        ctx,
+    )
+    .await;
+}
+
+pub(crate) async fn process_settle(
     delivery_tag: Option<u64>,
     multiple: bool,
-    requeue: bool,
+    settlement: Settlement,
     connection_id: u64,
     channel_id: u16,
     ctx: &BasicCtx,
@@ -277,7 +401,7 @@ async fn process_settle(
             .remove(&(connection_id, channel_id, *tag));
     }
 
-    if requeue {
+    if settlement == Settlement::Requeue {
         for (_, entry) in &settled {
             if let Err(e) = requeue_message(
                 &ctx.storage_driver_manager,
@@ -297,6 +421,26 @@ async fn process_settle(
         return;
     }
 
+    if settlement == Settlement::Reject {
+        for (_, entry) in &settled {
+            if let Err(e) = dead_letter_stored(
+                &ctx.storage_driver_manager,
+                &ctx.amqp_cache,
+                &entry.tenant,
+                &entry.queue,
+                entry.offset,
+                DeathReason::Rejected,
+            )
+            .await
+            {
+                error!(
+                    "AMQP: failed to dead-letter rejected message from {}: {}",
+                    entry.queue, e
+                );
+            }
+        }
+    }
+
     let mut by_queue: HashMap<(String, String), Vec<u64>> = HashMap::new();
     for (_, entry) in &settled {
         by_queue
@@ -326,7 +470,15 @@ async fn process_settle(
 }
 
 pub(crate) async fn requeue_channel(connection_id: u64, channel_id: u16, ctx: &BasicCtx) {
-    process_settle(None, false, true, connection_id, channel_id, ctx).await;
+    process_settle(
+        None,
+        false,
+        Settlement::Requeue,
+        connection_id,
+        channel_id,
+        ctx,
+    )
+    .await;
     cancel_channel_consumers(connection_id, channel_id, ctx).await;
 }
 
@@ -410,6 +562,16 @@ fn process_confirm_select(
     ctx: &BasicCtx,
 ) -> Option<AMQPFrame> {
     if let Some(channel) = ctx.amqp_cache.get_channel(connection_id, channel_id) {
+        // A transactional channel can't also be put in confirm mode.
+        if channel.tx_mode.load(Ordering::SeqCst) {
+            return Some(channel_error_close(
+                channel_id,
+                406,
+                "PRECONDITION_FAILED",
+                85,
+                10,
+            ));
+        }
         channel.confirm_mode.store(true, Ordering::SeqCst);
     }
     Some(AMQPFrame::Method(
@@ -28,6 +28,9 @@
 use crate::core::cache::AmqpCacheManager;
 use crate::core::connection::{AmqpConnection, AmqpConnectionState};
 
+pub(crate) const SASL_MECHANISM_PLAIN: &str = "PLAIN";
+pub(crate) const SASL_MECHANISM_EXTERNAL: &str = "EXTERNAL";
+
 /// Handles the Connection class. StartOk/Open/Close/CloseOk carry the
 /// login+tenant handshake and connection lifecycle; everything else is a
 /// plain protocol ack handled by `process_connection` below.
@@ -35,15 +38,17 @@ pub(crate) async fn process_connection_full(
     channel_id: u16,
     method: &AMQPMethod,
     connection_id: u64,
+    cert_user: Option<&str>,
     amqp_cache: &Arc<AmqpCacheManager>,
     security_manager: &Arc<SecurityManager>,
 ) -> Option<AMQPFrame> {
     match method {
         AMQPMethod::StartOk(start_ok) => {
-            process_connection_start_ok(start_ok, connection_id, amqp_cache)
+            process_connection_start_ok(start_ok, connection_id, cert_user, amqp_cache)
         }
         AMQPMethod::Open(open) => {
-            process_connection_open(open, connection_id, amqp_cache, security_manager).await
+            process_connection_open(open, connection_id, cert_user, amqp_cache, security_manager)
+                .await
         }
         AMQPMethod::Close(_) => process_connection_close(connection_id, amqp_cache),
         AMQPMethod::CloseOk(_) => process_connection_close_ok(connection_id, amqp_cache),
@@ -55,7 +60,7 @@ pub(crate) async fn process_connection_full(
 }
 
 /// Parses a SASL PLAIN response per RFC 4616: [authzid] NUL authcid NUL passwd.
-fn parse_sasl_plain(response: &[u8]) -> Option<(String, String)> {
+pub(crate) fn parse_sasl_plain(response: &[u8]) -> Option<(String, String)> {
     let parts: Vec<&[u8]> = response.split(|b| *b == 0).collect();
     if parts.len() != 3 {
         return None;
@@ -67,12 +72,19 @@ fn parse_sasl_plain(response: &[u8]) -> Option<(String, String)> {
 
 /// Captures the SASL PLAIN credentials for verification once Open reveals
 /// which tenant (vhost) they should be checked against, then replies Tune.
+/// EXTERNAL logs in as the user the client certificate maps to.
 fn process_connection_start_ok(
     start_ok: &StartOk,
     connection_id: u64,
+    cert_user: Option<&str>,
     amqp_cache: &Arc<AmqpCacheManager>,
 ) -> Option<AMQPFrame> {
-    let Some((username, password)) = parse_sasl_plain(start_ok.response.as_bytes()) else {
+    let login = if start_ok.mechanism.as_str() == SASL_MECHANISM_EXTERNAL {
+        cert_user.map(|username| (username.to_string(), String::new()))
+    } else {
+        parse_sasl_plain(start_ok.response.as_bytes())
+    };
+    let Some((username, password)) = login else {
         warn!(
             connection_id,
             "AMQP Connection.StartOk: unsupported SASL response format"
@@ -86,6 +98,7 @@ fn process_connection_start_ok(
 async fn process_connection_open(
     open: &Open,
     connection_id: u64,
+    cert_user: Option<&str>,
     amqp_cache: &Arc<AmqpCacheManager>,
     security_manager: &Arc<SecurityManager>,
 ) -> Option<AMQPFrame> {
@@ -98,7 +111,8 @@ async fn process_connection_open(
     let login = amqp_cache.take_pending_login(connection_id);
     let authenticated = match &login {
         Some((username, password)) => {
-            password_check_by_login(security_manager, &tenant, username, password)
+            cert_user == Some(username.as_str())
+                || password_check_by_login(security_manager, &tenant, username, password)
         }
         None => false,
     };
@@ -172,14 +186,20 @@ fn process_connection_tune_ok(
     None
 }
 
-pub fn process_protocol_header() -> Option<AMQPFrame> {
+/// EXTERNAL is offered only to clients whose certificate maps to a user.
+pub fn process_protocol_header(offer_external: bool) -> Option<AMQPFrame> {
+    let mechanisms = if offer_external {
+        format!("{} {}", SASL_MECHANISM_PLAIN, SASL_MECHANISM_EXTERNAL)
+    } else {
+        SASL_MECHANISM_PLAIN.to_string()
+    };
     Some(AMQPFrame::Method(
         0,
         AMQPClass::Connection(AMQPMethod::Start(Start {
             version_major: 0,
             version_minor: 9,
             server_properties: FieldTable::default(),
-            mechanisms: LongString::from("PLAIN"),
+            mechanisms: LongString::from(mechanisms),
             locales: LongString::from("en_US"),
         })),
     ))
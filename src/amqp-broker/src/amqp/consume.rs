@@ -25,14 +25,14 @@
 use protocol::broker::broker::FetchAmqpQueueMessageRequest;
 use tracing::error;
 
-use crate::amqp::basic::{properties_from_record, BasicCtx};
+use crate::amqp::basic::{delivery_route, properties_from_record, BasicCtx};
 use crate::amqp::channel::channel_error_close;
 use crate::amqp::queue;
 use crate::core::cache::UnackedEntry;
 use crate::core::consume_group::{add_consume_member, remove_consume_member};
 use crate::core::frame::build_basic_content_frames;
 use crate::push;
-use crate::storage::offset::OffsetStorage;
+use crate::storage::offset::{ClaimedMessage, OffsetStorage};
 
 pub(crate) async fn process_consume(
     channel_id: u16,
@@ -235,60 +235,7 @@ pub(crate) async fn process_get(
 ) -> Option<Vec<AMQPFrame>> {
     let tenant = ctx.amqp_cache.tenant_for(connection_id);
 
-    let Some(topic) = queue::declare_amqp_queue(&ctx.storage_driver_manager, &tenant, queue).await
-    else {
-        error!("AMQP Basic.Get: queue {} is not available", queue);
-        return get_internal_error(channel_id);
-    };
-    let Some(shard_name) = topic.storage_name_list.get(&0).cloned() else {
-        error!("AMQP Basic.Get: queue {} has no shard", queue);
-        return get_internal_error(channel_id);
-    };
-
-    let leader_broker_id = match push::resolve_queue_leader(
-        &ctx.client_pool,
-        &ctx.storage_driver_manager.broker_cache,
-        &tenant,
-        queue,
-    )
-    .await
-    {
-        Ok(id) => id,
-        Err(e) => {
-            error!(
-                "AMQP Basic.Get: failed to resolve leader for {}: {}",
-                queue, e
-            );
-            return get_internal_error(channel_id);
-        }
-    };
-
-    let claimed = if push::is_self(leader_broker_id) {
-        claim_locally(
-            ctx,
-            &tenant,
-            queue,
-            &shard_name,
-            no_ack,
-            connection_id,
-            channel_id,
-        )
-        .await
-    } else {
-        claim_via_leader(
-            ctx,
-            leader_broker_id,
-            &tenant,
-            queue,
-            &shard_name,
-            no_ack,
-            connection_id,
-            channel_id,
-        )
-        .await
-    };
-
-    let (record, msg_offset, index_offset) = match claimed {
+    let claimed = match claim_next(ctx, &tenant, queue, no_ack, connection_id, channel_id).await {
         Ok(Some(v)) => v,
         Ok(None) => return get_empty(channel_id),
         Err(e) => {
@@ -304,19 +251,21 @@ pub(crate) async fn process_get(
         .unwrap_or(1);
 
     if !no_ack {
-        if let Some(index_offset) = index_offset {
+        if let Some(index_offset) = claimed.index_offset {
             ctx.amqp_cache.unacked().insert(
                 (connection_id, channel_id, delivery_tag),
                 UnackedEntry {
                     tenant: tenant.clone(),
-                    queue: queue.to_string(),
-                    offset: msg_offset,
+                    queue: claimed.store.clone(),
+                    offset: claimed.offset,
                     index_offset,
                 },
             );
         }
     }
 
+    let record = claimed.record;
+    let (exchange, routing_key) = delivery_route(&record, &claimed.store);
     let redelivered = record
         .protocol_data
         .as_ref()
@@ -330,8 +279,8 @@ pub(crate) async fn process_get(
         AMQPClass::Basic(AMQPMethod::GetOk(GetOk {
             delivery_tag,
             redelivered,
-            exchange: "".into(),
-            routing_key: queue.into(),
+            exchange: exchange.into(),
+            routing_key: routing_key.into(),
             message_count: 0,
         })),
     );
@@ -344,16 +293,59 @@ pub(crate) async fn process_get(
     ))
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Claims `queue`'s next ready message for `connection_id`/`channel_id`,
+/// from local storage when this node leads the queue or through its leader
+/// otherwise. Shared by Basic.Get and AMQP 1.0 receiver links.
+pub(crate) async fn claim_next(
+    ctx: &BasicCtx,
+    tenant: &str,
+    queue: &str,
+    no_ack: bool,
+    connection_id: u64,
+    channel_id: u16,
+) -> Result<Option<ClaimedMessage>, CommonError> {
+    if queue::declare_amqp_queue(&ctx.storage_driver_manager, tenant, queue)
+        .await
+        .is_none()
+    {
+        return Err(CommonError::CommonError(format!(
+            "queue {} is not available",
+            queue
+        )));
+    }
+
+    let leader_broker_id = push::resolve_queue_leader(
+        &ctx.client_pool,
+        &ctx.storage_driver_manager.broker_cache,
+        tenant,
+        queue,
+    )
+    .await?;
+
+    if push::is_self(leader_broker_id) {
+        claim_locally(ctx, tenant, queue, no_ack, connection_id, channel_id).await
+    } else {
+        claim_via_leader(
+            ctx,
+            leader_broker_id,
+            tenant,
+            queue,
+            no_ack,
+            connection_id,
+            channel_id,
+        )
+        .await
+    }
+}
+
 async fn claim_locally(
     ctx: &BasicCtx,
     tenant: &str,
     queue: &str,
-    shard_name: &str,
     no_ack: bool,
     connection_id: u64,
     channel_id: u16,
-) -> Result<Option<(StorageRecord, u64, Option<u64>)>, CommonError> {
+) -> Result<Option<ClaimedMessage>, CommonError> {
     let offset_storage = OffsetStorage::new(
         ctx.storage_driver_manager
             .engine_storage_handler
@@ -364,9 +356,9 @@ async fn claim_locally(
         .claim_and_track(
             &ctx.push_manager,
             &ctx.storage_driver_manager,
+            &ctx.amqp_cache,
             tenant,
             queue,
-            shard_name,
             no_ack,
             connection_id,
             channel_id,
@@ -381,11 +373,10 @@ async fn claim_via_leader(
     leader_broker_id: u64,
     tenant: &str,
     queue: &str,
-    shard_name: &str,
     no_ack: bool,
     connection_id: u64,
     channel_id: u16,
-) -> Result<Option<(StorageRecord, u64, Option<u64>)>, CommonError> {
+) -> Result<Option<ClaimedMessage>, CommonError> {
     let Some(node) = ctx
         .storage_driver_manager
         .broker_cache
@@ -403,7 +394,6 @@ async fn claim_via_leader(
     let request = FetchAmqpQueueMessageRequest {
         tenant: tenant.to_string(),
         queue: queue.to_string(),
-        shard_name: shard_name.to_string(),
         connect_id: connection_id,
         channel_id: channel_id as u32,
         no_ack,
@@ -413,6 +403,10 @@ async fn claim_via_leader(
     if !reply.has_message {
         return Ok(None);
     }
-    let record = StorageRecord::decode(&reply.record)?;
-    Ok(Some((record, reply.offset, reply.index_offset)))
+    Ok(Some(ClaimedMessage {
+        record: StorageRecord::decode(&reply.record)?,
+        store: reply.store,
+        offset: reply.offset,
+        index_offset: reply.index_offset,
+    }))
 }
@@ -27,6 +27,7 @@
 use crate::amqp::channel::channel_error_close;
 use crate::amqp::route;
 use crate::core::cache::AmqpCacheManager;
+use crate::core::delayed::{ARG_DELAYED_TYPE, DELAYED_EXCHANGE_TYPE};
 use crate::storage::binding::BindingStorage;
 use crate::storage::exchange::ExchangeStorage;
 
@@ -109,7 +110,30 @@ async fn process_exchange_declare(
         };
     }
 
-    let exchange_type =
+    let arguments = route::field_table_to_map(&declare.arguments);
+    let delayed = declare.kind.as_str() == DELAYED_EXCHANGE_TYPE;
+    let exchange_type = if delayed {
+        // An x-delayed-message exchange routes as its x-delayed-type once a
+        // message's delay is up; without a valid one there's nothing to
+        // route by.
+        let Some(exchange_type) = arguments
+            .get(ARG_DELAYED_TYPE)
+            .and_then(|kind| AmqpExchangeType::from_str_opt(kind))
+        else {
+            warn!(
+                "AMQP Exchange.Declare: {} requires a valid {} argument for {}",
+                DELAYED_EXCHANGE_TYPE, ARG_DELAYED_TYPE, exchange_name
+            );
+            return Some(channel_error_close(
+                channel_id,
+                406,
+                "PRECONDITION_FAILED",
+                40,
+                10,
+            ));
+        };
+        exchange_type
+    } else {
         AmqpExchangeType::from_str_opt(declare.kind.as_str()).unwrap_or_else(|| {
             warn!(
             "AMQP Exchange.Declare: unrecognized exchange type '{}' for {}, defaulting to direct",
@@ -117,8 +141,8 @@ async fn process_exchange_declare(
             exchange_name
         );
             AmqpExchangeType::Direct
-        });
-    let arguments = route::field_table_to_map(&declare.arguments);
+        })
+    };
 
     let exchange = AmqpExchange::new(
         &tenant,
@@ -128,7 +152,8 @@ async fn process_exchange_declare(
         declare.auto_delete,
         declare.internal,
         arguments,
-    );
+    )
+    .with_delayed(delayed);
     let storage = ExchangeStorage::new(
         storage_driver_manager
             .engine_storage_handler
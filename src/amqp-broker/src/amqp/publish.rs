@@ -12,21 +12,74 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use amq_protocol::frame::{AMQPContentHeader, AMQPFrame};
 use amq_protocol::protocol::basic::{AMQPMethod, Ack, Nack, Return};
 use amq_protocol::protocol::AMQPClass;
 use common_base::error::common::CommonError;
+use common_base::tools::now_millis;
+use common_config::broker::broker_config;
+use metadata_struct::adapter::adapter_read_config::AdapterWriteRespRow;
 use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
-use metadata_struct::storage::record::{StorageRecordProtocolData, StorageRecordProtocolDataAmqp};
+use metadata_struct::storage::record::{
+    StorageRecord, StorageRecordProtocolData, StorageRecordProtocolDataAmqp,
+};
+use schema_register::schema::dead_letter_record;
 use storage_adapter::driver::StorageDriverManager;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use crate::amqp::basic::{properties_from_protocol_data, properties_to_protocol_data, BasicCtx};
 use crate::amqp::{queue, route};
-use crate::core::cache::PendingPublish;
+use crate::core::cache::{AmqpCacheManager, PendingPublish};
+use crate::core::dead_letter::{cached_ready_count, dead_letter, queue_arguments};
+use crate::core::delayed::{delay_ms, schedule_delayed};
 use crate::core::frame::build_basic_content_frames;
+use crate::core::queue_args::{expire_at_ms, priority_store_name, DeathReason, Overflow};
+
+/// A message on its way into a queue: its body plus the properties stored
+/// alongside it.
+#[derive(Clone)]
+pub(crate) struct QueueMessage {
+    pub(crate) body: Vec<u8>,
+    pub(crate) properties: StorageRecordProtocolDataAmqp,
+}
+
+impl QueueMessage {
+    fn from_pending(pending: &PendingPublish) -> Self {
+        let mut properties = pending.properties.clone();
+        properties.exchange = pending.exchange.clone();
+        properties.routing_key = pending.routing_key.clone();
+        QueueMessage {
+            body: pending.body.clone(),
+            properties,
+        }
+    }
+
+    pub(crate) fn from_record(record: &StorageRecord) -> Self {
+        QueueMessage {
+            body: record.data.to_vec(),
+            properties: record
+                .protocol_data
+                .as_ref()
+                .and_then(|pd| pd.amqp.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+pub(crate) struct EnqueueOutcome {
+    /// False if a storage write failed.
+    pub(crate) written: bool,
+    /// Offsets written, per storage topic (the queue or a priority store).
+    pub(crate) offsets: HashMap<String, Vec<u64>>,
+    /// Messages a full `reject-publish(-dlx)` queue turned away.
+    pub(crate) rejected: Vec<QueueMessage>,
+    /// Whether the queue's overflow mode wants `rejected` dead-lettered.
+    pub(crate) dead_letter_rejected: bool,
+}
 
 /// Content Header frame: carries body_size for the Basic.Publish that preceded
 /// it. A zero-length body means the message is already complete.
@@ -55,7 +108,7 @@ pub(crate) async fn process_content_header_full(
     };
     if complete {
         if let Some((_, pending)) = ctx.amqp_cache.pending_publish().remove(&key) {
-            return finalize_publish(channel_id, pending, ctx).await;
+            return finalize_publish(connection_id, channel_id, pending, ctx).await;
         }
     }
     None
@@ -79,7 +132,7 @@ pub(crate) async fn process_content_body_full(
     };
     if complete {
         if let Some((_, pending)) = ctx.amqp_cache.pending_publish().remove(&key) {
-            return finalize_publish(channel_id, pending, ctx).await;
+            return finalize_publish(connection_id, channel_id, pending, ctx).await;
         }
     }
     None
@@ -90,12 +143,218 @@ pub(crate) async fn process_content_body_full(
 /// named exchanges are routed via `route::resolve_queues`, which follows
 /// their type (direct/fanout/topic/headers) and bindings, including
 /// exchange-to-exchange chains. Unroutable `mandatory` publishes are
-/// returned to the publisher via Basic.Return.
+/// returned to the publisher via Basic.Return, as are those whose body
+/// doesn't match a schema bound to one of their queues. On a Tx.Select channel the
+/// message is only buffered; `commit_publishes` writes it on Tx.Commit.
+/// Publishes to an `x-delayed-message` exchange with a positive `x-delay`
+/// are handed to the delay queue instead and routed when they fall due, so
+/// `mandatory` can't be honored for them.
 pub(crate) async fn finalize_publish(
+    connection_id: u64,
     channel_id: u16,
     pending: PendingPublish,
     ctx: &BasicCtx,
 ) -> Option<Vec<AMQPFrame>> {
+    let in_tx = ctx
+        .amqp_cache
+        .get_channel(connection_id, channel_id)
+        .is_some_and(|channel| channel.tx_mode.load(Ordering::SeqCst));
+    if in_tx {
+        ctx.amqp_cache
+            .buffer_tx_publish(connection_id, channel_id, pending);
+        return None;
+    }
+
+    let frames = match route_publish(&pending, ctx).await {
+        PublishOutcome::Dropped => return None,
+        // Unroutable: still "handled" from the publisher's point of view, so
+        // a Confirm-mode publisher gets acked even though there's no queue.
+        PublishOutcome::Unroutable => {
+            let mut frames = confirm_frames(channel_id, pending.confirm_seqno, true);
+            frames.extend(unroutable_frames(channel_id, &pending));
+            frames
+        }
+        PublishOutcome::Invalid => {
+            let mut frames = confirm_frames(channel_id, pending.confirm_seqno, false);
+            frames.extend(invalid_frames(channel_id, &pending));
+            frames
+        }
+        PublishOutcome::Stored(ok) => confirm_frames(channel_id, pending.confirm_seqno, ok),
+    };
+    (!frames.is_empty()).then_some(frames)
+}
+
+/// Where a single publish ended up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PublishOutcome {
+    /// Ignored outright: an empty routing key on the default exchange.
+    Dropped,
+    /// No queue is bound to take it.
+    Unroutable,
+    /// Its body doesn't match a schema bound to one of its queues, so no
+    /// queue got it.
+    Invalid,
+    /// Written to every matching queue (or the delay queue); false if any
+    /// write failed.
+    Stored(bool),
+}
+
+/// Routes one publish and writes it, outside of any transaction. Shared by
+/// Basic.Publish and AMQP 1.0 transfers.
+pub(crate) async fn route_publish(pending: &PendingPublish, ctx: &BasicCtx) -> PublishOutcome {
+    if let Some(delay) = publish_delay(pending, ctx) {
+        return PublishOutcome::Stored(schedule_publish(pending, delay, ctx).await.is_some());
+    }
+
+    let Some(queues) = resolve_publish_queues(pending, ctx) else {
+        return PublishOutcome::Dropped;
+    };
+    if queues.is_empty() {
+        return PublishOutcome::Unroutable;
+    }
+    if !check_payload_schema(pending, &queues, ctx).await {
+        return PublishOutcome::Invalid;
+    }
+
+    let mut all_ok = true;
+    for queue_name in &queues {
+        let ok = publish_to_queue(
+            ctx,
+            &pending.tenant,
+            queue_name,
+            vec![QueueMessage::from_pending(pending)],
+        )
+        .await;
+        all_ok &= ok;
+    }
+    PublishOutcome::Stored(all_ok)
+}
+
+/// Tx.Commit: routes every publish buffered by the transaction, then writes
+/// each target queue's messages as one batch and schedules the delayed ones.
+/// Returns the Basic.Return frames owed for unroutable `mandatory` publishes,
+/// or None if any write failed. A failed commit leaves nothing behind: the
+/// offsets already written are deleted and the delayed publishes already
+/// scheduled are cancelled.
+pub(crate) async fn commit_publishes(
+    channel_id: u16,
+    publishes: Vec<PendingPublish>,
+    ctx: &BasicCtx,
+) -> Option<Vec<AMQPFrame>> {
+    let mut returns = Vec::new();
+    let mut batches: HashMap<(String, String), Vec<QueueMessage>> = HashMap::new();
+    let mut delayed = Vec::new();
+    for pending in &publishes {
+        if let Some(delay) = publish_delay(pending, ctx) {
+            delayed.push((pending, delay));
+            continue;
+        }
+        let Some(queues) = resolve_publish_queues(pending, ctx) else {
+            continue;
+        };
+        if queues.is_empty() {
+            returns.extend(unroutable_frames(channel_id, pending));
+            continue;
+        }
+        if !check_payload_schema(pending, &queues, ctx).await {
+            returns.extend(invalid_frames(channel_id, pending));
+            continue;
+        }
+        for queue_name in queues {
+            batches
+                .entry((pending.tenant.clone(), queue_name))
+                .or_default()
+                .push(QueueMessage::from_pending(pending));
+        }
+    }
+
+    let mut outcomes = Vec::new();
+    let mut scheduled = Vec::new();
+    let mut all_written = true;
+    for ((tenant, queue_name), messages) in batches {
+        let outcome = enqueue(
+            &ctx.storage_driver_manager,
+            &ctx.amqp_cache,
+            &tenant,
+            &queue_name,
+            messages,
+        )
+        .await;
+        all_written &= outcome.written;
+        outcomes.push((tenant, queue_name, outcome));
+        if !all_written {
+            break;
+        }
+    }
+    if all_written {
+        for (pending, delay) in delayed {
+            match schedule_publish(pending, delay, ctx).await {
+                Some(id) => scheduled.push(id),
+                None => {
+                    all_written = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    if !all_written {
+        rollback_commit(ctx, &outcomes, &scheduled).await;
+        return None;
+    }
+
+    // Messages a full queue refuses aren't a commit failure: transactional
+    // publishes have no per-message nack, so they are just dropped (or
+    // dead-lettered), as RabbitMQ does.
+    for (tenant, queue_name, outcome) in outcomes {
+        dead_letter_rejected(
+            &ctx.storage_driver_manager,
+            &ctx.amqp_cache,
+            &tenant,
+            &queue_name,
+            outcome,
+        )
+        .await;
+    }
+    Some(returns)
+}
+
+/// Undoes what a failed Tx.Commit already wrote. Storage is append-only, so
+/// the written offsets are deleted, the same way an ack removes a message.
+pub(crate) async fn rollback_commit(
+    ctx: &BasicCtx,
+    outcomes: &[(String, String, EnqueueOutcome)],
+    scheduled: &[String],
+) {
+    for (tenant, _, outcome) in outcomes {
+        for (store, offsets) in &outcome.offsets {
+            if let Err(e) = ctx
+                .storage_driver_manager
+                .delete_by_offsets(tenant, store, offsets)
+                .await
+            {
+                error!(
+                    "AMQP Tx.Commit failed to roll back {} messages written to {}: {}",
+                    offsets.len(),
+                    store,
+                    e
+                );
+            }
+        }
+    }
+    for id in scheduled {
+        if let Err(e) = ctx.delay_message_manager.cancel(id).await {
+            error!(
+                "AMQP Tx.Commit failed to cancel delayed publish {}: {}",
+                id, e
+            );
+        }
+    }
+}
+
+/// The queues a publish lands in, or None when it is dropped outright (an
+/// empty routing key on the default exchange).
+fn resolve_publish_queues(pending: &PendingPublish, ctx: &BasicCtx) -> Option<Vec<String>> {
     if pending.exchange.is_empty() && pending.routing_key.is_empty() {
         tracing::warn!("AMQP Basic.Publish with empty routing key ignored on the default exchange");
         return None;
@@ -112,36 +371,109 @@ pub(crate) async fn finalize_publish(
             &pending.headers,
         )
     };
+    Some(queues)
+}
 
-    if queues.is_empty() {
-        // Unroutable: still "handled" from the publisher's point of view, so
-        // a Confirm-mode publisher gets acked even though there's no queue.
-        let mut frames = confirm_frames(channel_id, pending.confirm_seqno, true);
-        if pending.mandatory {
-            frames.extend(build_basic_return_frames(channel_id, &pending));
-        } else {
-            debug!(
-                "AMQP Basic.Publish unroutable (exchange={}, routing_key={}), dropped",
-                pending.exchange, pending.routing_key
+/// The `x-delay` of a publish to an `x-delayed-message` exchange, if it
+/// asks to be held back at all.
+fn publish_delay(pending: &PendingPublish, ctx: &BasicCtx) -> Option<u64> {
+    if pending.exchange.is_empty() {
+        return None;
+    }
+    let exchange = ctx
+        .amqp_cache
+        .get_exchange(&pending.tenant, &pending.exchange)?;
+    if !exchange.delayed {
+        return None;
+    }
+    delay_ms(&pending.headers)
+}
+
+/// The delay-queue id of the scheduled publish, or None if scheduling failed.
+async fn schedule_publish(pending: &PendingPublish, delay: u64, ctx: &BasicCtx) -> Option<String> {
+    match schedule_delayed(
+        &ctx.delay_message_manager,
+        &pending.tenant,
+        &pending.exchange,
+        delay,
+        QueueMessage::from_pending(pending),
+    )
+    .await
+    {
+        Ok(id) => Some(id),
+        Err(e) => {
+            error!(
+                "AMQP delayed publish to exchange {} failed: {}",
+                pending.exchange, e
             );
+            None
         }
-        return (!frames.is_empty()).then_some(frames);
     }
+}
 
-    let mut all_ok = true;
-    for queue_name in &queues {
-        let ok = write_to_queue(
-            &ctx.storage_driver_manager,
-            &pending.tenant,
-            queue_name,
-            pending.body.clone(),
-            &pending.properties,
-        )
-        .await;
-        all_ok &= ok;
+/// Checks the body against the schemas bound to each queue it was routed
+/// to. A rejected body goes to the dead-letter topic, if one is configured.
+async fn check_payload_schema(pending: &PendingPublish, queues: &[String], ctx: &BasicCtx) -> bool {
+    for queue_name in queues {
+        let Err(e) =
+            ctx.schema_manager
+                .validate_payload(&pending.tenant, queue_name, &pending.body, false)
+        else {
+            continue;
+        };
+        debug!(
+            "AMQP publish to queue {} rejected by schema: {}",
+            queue_name, e
+        );
+
+        let dead_letter_topic = &broker_config().amqp_runtime.schema_dead_letter_topic;
+        if !dead_letter_topic.is_empty() {
+            let record = dead_letter_record(
+                dead_letter_topic,
+                "amqp",
+                queue_name,
+                &e.to_string(),
+                &pending.body,
+            );
+            if let Err(err) = ctx
+                .storage_driver_manager
+                .write(&pending.tenant, dead_letter_topic, &[record], 1)
+                .await
+            {
+                warn!(
+                    "AMQP publish failed to write an invalid message for queue {} to dead-letter topic {}: {}",
+                    queue_name, dead_letter_topic, err
+                );
+            }
+        }
+        return false;
     }
-    let frames = confirm_frames(channel_id, pending.confirm_seqno, all_ok);
-    (!frames.is_empty()).then_some(frames)
+    true
+}
+
+fn unroutable_frames(channel_id: u16, pending: &PendingPublish) -> Vec<AMQPFrame> {
+    if pending.mandatory {
+        return build_basic_return_frames(channel_id, pending, 312, "NO_ROUTE");
+    }
+    debug!(
+        "AMQP Basic.Publish unroutable (exchange={}, routing_key={}), dropped",
+        pending.exchange, pending.routing_key
+    );
+    Vec::new()
+}
+
+// Returned like an unroutable message when `mandatory`, since it reached no
+// queue; otherwise only a Confirm-mode Nack tells the publisher.
+fn invalid_frames(channel_id: u16, pending: &PendingPublish) -> Vec<AMQPFrame> {
+    if !pending.mandatory {
+        return Vec::new();
+    }
+    build_basic_return_frames(
+        channel_id,
+        pending,
+        406,
+        "PRECONDITION_FAILED - message does not match the bound schema",
+    )
 }
 
 /// Builds the Confirm-mode Basic.Ack/Basic.Nack for one publish, or nothing
@@ -171,24 +503,147 @@ fn confirm_frames(channel_id: u16, confirm_seqno: Option<u64>, ok: bool) -> Vec<
     vec![frame]
 }
 
-async fn write_to_queue(
-    sdm: &Arc<StorageDriverManager>,
+/// Publishes to one queue, dead-lettering anything its overflow mode turns
+/// away. True only if every message was written.
+async fn publish_to_queue(
+    ctx: &BasicCtx,
     tenant: &str,
     queue_name: &str,
-    body: Vec<u8>,
-    properties: &StorageRecordProtocolDataAmqp,
+    messages: Vec<QueueMessage>,
 ) -> bool {
-    let record = AdapterWriteRecord::new(queue_name.to_string(), body).with_protocol_data(Some(
-        StorageRecordProtocolData {
-            amqp: Some(properties.clone()),
-            ..Default::default()
+    let outcome = enqueue(
+        &ctx.storage_driver_manager,
+        &ctx.amqp_cache,
+        tenant,
+        queue_name,
+        messages,
+    )
+    .await;
+    let ok = outcome.written && outcome.rejected.is_empty();
+    dead_letter_rejected(
+        &ctx.storage_driver_manager,
+        &ctx.amqp_cache,
+        tenant,
+        queue_name,
+        outcome,
+    )
+    .await;
+    ok
+}
+
+/// Dead-letters whatever a `reject-publish-dlx` queue turned away.
+pub(crate) async fn dead_letter_rejected(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<AmqpCacheManager>,
+    tenant: &str,
+    queue_name: &str,
+    outcome: EnqueueOutcome,
+) {
+    if !outcome.dead_letter_rejected {
+        return;
+    }
+    for message in outcome.rejected {
+        dead_letter(sdm, cache, tenant, queue_name, message, DeathReason::MaxLen).await;
+    }
+}
+
+/// Writes messages into `queue_name` under that queue's arguments:
+/// `x-max-priority` picks each message's priority store, `x-message-ttl`
+/// (with the message's own `expiration`) stamps its expiry, and once
+/// `x-max-length` ready messages are queued the `reject-publish` overflow
+/// modes turn the rest away. `drop-head` accepts everything and, once the
+/// queue is over its limit, asks the recovery scanner to trim its head.
+pub(crate) async fn enqueue(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<AmqpCacheManager>,
+    tenant: &str,
+    queue_name: &str,
+    messages: Vec<QueueMessage>,
+) -> EnqueueOutcome {
+    let args = queue_arguments(cache, tenant, queue_name);
+    let rejects = matches!(
+        args.overflow,
+        Overflow::RejectPublish | Overflow::RejectPublishDlx
+    );
+    let ready = match args.max_length {
+        Some(_) => match cached_ready_count(sdm, cache, tenant, queue_name, &args).await {
+            Ok(ready) => Some(ready),
+            Err(e) => {
+                warn!(
+                    "AMQP queue {}: failed to read its length, x-max-length not applied: {}",
+                    queue_name, e
+                );
+                None
+            }
         },
-    ));
-    match sdm
-        .write(tenant, queue_name, std::slice::from_ref(&record), 1)
-        .await
-    {
-        Ok(_) => true,
+        None => None,
+    };
+    let room = match (args.max_length, ready) {
+        (Some(max_length), Some(ready)) if rejects => {
+            Some(max_length.saturating_sub(ready) as usize)
+        }
+        _ => None,
+    };
+
+    let now_ms = now_millis() as u64;
+    let mut rejected = Vec::new();
+    let mut by_store: HashMap<String, Vec<AdapterWriteRecord>> = HashMap::new();
+    for (idx, mut message) in messages.into_iter().enumerate() {
+        if room.is_some_and(|room| idx >= room) {
+            rejected.push(message);
+            continue;
+        }
+        let properties = &mut message.properties;
+        properties.expire_at_ms = expire_at_ms(
+            now_ms,
+            properties.expiration.as_deref(),
+            args.message_ttl_ms,
+        );
+        let store = priority_store_name(queue_name, args.max_priority, properties.priority);
+        let record = AdapterWriteRecord::new(store.clone(), message.body).with_protocol_data(Some(
+            StorageRecordProtocolData {
+                amqp: Some(message.properties),
+                ..Default::default()
+            },
+        ));
+        by_store.entry(store).or_default().push(record);
+    }
+
+    let mut written = true;
+    let mut offsets = HashMap::new();
+    for (store, records) in by_store {
+        match write_to_queue(sdm, tenant, &store, &records).await {
+            Some(store_offsets) => {
+                offsets.insert(store, store_offsets);
+            }
+            None => written = false,
+        }
+    }
+
+    if let Some(max_length) = args.max_length {
+        let added = offsets.values().map(|o| o.len() as u64).sum();
+        let length = cache.add_queue_length(tenant, queue_name, added);
+        if args.overflow == Overflow::DropHead && length.is_some_and(|length| length > max_length) {
+            cache.request_trim(tenant, queue_name);
+        }
+    }
+    EnqueueOutcome {
+        written,
+        offsets,
+        rejected,
+        dead_letter_rejected: args.overflow == Overflow::RejectPublishDlx,
+    }
+}
+
+/// The offsets the records were written at, or None if the write failed.
+async fn write_to_queue(
+    sdm: &Arc<StorageDriverManager>,
+    tenant: &str,
+    queue_name: &str,
+    records: &[AdapterWriteRecord],
+) -> Option<Vec<u64>> {
+    match sdm.write(tenant, queue_name, records, 1).await {
+        Ok(rows) => Some(written_offsets(&rows)),
         Err(CommonError::TopicNotFoundInBrokerCache(_, _)) => {
             // Published to a queue that was never explicitly declared (common
             // with the default exchange): declare it on the fly, then retry.
@@ -196,14 +651,14 @@ async fn write_to_queue(
                 .await
                 .is_some()
             {
-                match sdm.write(tenant, queue_name, &[record], 1).await {
-                    Ok(_) => true,
+                match sdm.write(tenant, queue_name, records, 1).await {
+                    Ok(rows) => Some(written_offsets(&rows)),
                     Err(e) => {
                         error!(
                             "AMQP Basic.Publish retry write failed for {}: {}",
                             queue_name, e
                         );
-                        false
+                        None
                     }
                 }
             } else {
@@ -211,24 +666,36 @@ async fn write_to_queue(
                     "AMQP Basic.Publish dropped: queue {} does not exist and could not be created",
                     queue_name
                 );
-                false
+                None
             }
         }
         Err(e) => {
             error!("AMQP Basic.Publish write failed for {}: {}", queue_name, e);
-            false
+            None
         }
     }
 }
 
-/// Builds an unroutable `mandatory` publish's reply to its publisher, per
+fn written_offsets(rows: &[AdapterWriteRespRow]) -> Vec<u64> {
+    rows.iter()
+        .filter(|row| !row.is_error())
+        .map(|row| row.offset)
+        .collect()
+}
+
+/// Builds a returned `mandatory` publish's reply to its publisher, per
 /// spec: Basic.Return followed by the message's own content header and body.
-fn build_basic_return_frames(channel_id: u16, pending: &PendingPublish) -> Vec<AMQPFrame> {
+fn build_basic_return_frames(
+    channel_id: u16,
+    pending: &PendingPublish,
+    reply_code: u16,
+    reply_text: &str,
+) -> Vec<AMQPFrame> {
     let return_frame = AMQPFrame::Method(
         channel_id,
         AMQPClass::Basic(AMQPMethod::Return(Return {
-            reply_code: 312,
-            reply_text: "NO_ROUTE".into(),
+            reply_code,
+            reply_text: reply_text.into(),
             exchange: pending.exchange.clone().into(),
             routing_key: pending.routing_key.clone().into(),
         })),
@@ -21,6 +21,7 @@
     PurgeOk, Unbind, UnbindOk,
 };
 use amq_protocol::protocol::AMQPClass;
+use common_base::error::common::CommonError;
 use common_base::uuid::unique_id;
 use common_config::broker::broker_config;
 use common_config::storage::StorageType;
@@ -34,6 +35,8 @@
 use crate::amqp::channel::channel_error_close;
 use crate::amqp::route;
 use crate::core::cache::AmqpCacheManager;
+use crate::core::dead_letter::queue_arguments;
+use crate::core::queue_args::{store_names, QueueArguments};
 use crate::storage::binding::BindingStorage;
 use crate::storage::queue::QueueStorage;
 
@@ -127,14 +130,31 @@ async fn process_queue_declare(
                 channel_id,
                 AMQPClass::Queue(AMQPMethod::DeclareOk(QueueDeclareOk {
                     queue: queue_name.clone().into(),
-                    message_count: queue_message_count(storage_driver_manager, &tenant, &queue_name)
-                        .await as u32,
+                    message_count: queue_message_count(
+                        storage_driver_manager,
+                        amqp_cache,
+                        &tenant,
+                        &queue_name,
+                    )
+                    .await as u32,
                     consumer_count: consumer_count(storage_driver_manager, &tenant, &queue_name),
                 })),
             ))
         };
     }
 
+    let arguments = route::field_table_to_map(&declare.arguments);
+    if let Err(e) = QueueArguments::parse(&arguments) {
+        warn!("AMQP Queue.Declare rejected for {}: {}", queue_name, e);
+        return Some(channel_error_close(
+            channel_id,
+            406,
+            "PRECONDITION_FAILED",
+            50,
+            10,
+        ));
+    }
+
     // The physical message shard: needed whether or not the queue's own
     // declare metadata is durable — something has to hold its messages
     // while it's alive.
@@ -152,7 +172,6 @@ async fn process_queue_declare(
         ));
     }
 
-    let arguments = route::field_table_to_map(&declare.arguments);
     let amqp_queue = AmqpQueue::new(
         &tenant,
         &queue_name,
@@ -189,14 +208,20 @@ async fn process_queue_declare(
         channel_id,
         AMQPClass::Queue(AMQPMethod::DeclareOk(QueueDeclareOk {
             queue: queue_name.clone().into(),
-            message_count: queue_message_count(storage_driver_manager, &tenant, &queue_name).await
-                as u32,
+            message_count: queue_message_count(
+                storage_driver_manager,
+                amqp_cache,
+                &tenant,
+                &queue_name,
+            )
+            .await as u32,
             consumer_count: consumer_count(storage_driver_manager, &tenant, &queue_name),
         })),
     ))
 }
 
-/// Sum of unconsumed messages across all of a queue's storage shards.
+/// Sum of unconsumed messages across all of a queue's storage shards,
+/// priority stores included.
 ///
 /// Uses `high_watermark` (the next offset to be written, exclusive) rather
 /// than `end_offset` (the last written offset, inclusive — see
@@ -205,23 +230,48 @@ async fn process_queue_declare(
 /// exactly one message.
 async fn queue_message_count(
     storage_driver_manager: &Arc<StorageDriverManager>,
+    amqp_cache: &Arc<AmqpCacheManager>,
     tenant: &str,
     queue_name: &str,
 ) -> u64 {
-    storage_driver_manager
-        .list_storage_resource(tenant, queue_name)
-        .await
-        .map(|resources| {
-            resources
-                .values()
-                .map(|d| {
-                    d.offset
-                        .high_watermark
-                        .saturating_sub(d.offset.start_offset)
-                })
-                .sum()
+    let mut count = 0;
+    for store in existing_stores(storage_driver_manager, amqp_cache, tenant, queue_name) {
+        count += storage_driver_manager
+            .list_storage_resource(tenant, &store)
+            .await
+            .map(|resources| {
+                resources
+                    .values()
+                    .map(|d| {
+                        d.offset
+                            .high_watermark
+                            .saturating_sub(d.offset.start_offset)
+                    })
+                    .sum::<u64>()
+            })
+            .unwrap_or(0);
+    }
+    count
+}
+
+/// The storage topics currently holding a queue's messages: the queue's own
+/// and whichever of its `x-max-priority` stores have been written to.
+fn existing_stores(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    amqp_cache: &Arc<AmqpCacheManager>,
+    tenant: &str,
+    queue_name: &str,
+) -> Vec<String> {
+    let max_priority = queue_arguments(amqp_cache, tenant, queue_name).max_priority;
+    store_names(queue_name, max_priority)
+        .into_iter()
+        .filter(|store| {
+            storage_driver_manager
+                .broker_cache
+                .get_topic_by_name(tenant, store)
+                .is_some()
         })
-        .unwrap_or(0)
+        .collect()
 }
 
 /// Cluster-wide count of this queue's shared-group members (i.e. active
@@ -247,7 +297,8 @@ async fn process_queue_delete(
 ) -> Option<AMQPFrame> {
     let queue_name = delete.queue.to_string();
     let tenant = amqp_cache.tenant_for(connection_id);
-    let message_count = queue_message_count(storage_driver_manager, &tenant, &queue_name).await;
+    let message_count =
+        queue_message_count(storage_driver_manager, amqp_cache, &tenant, &queue_name).await;
 
     if delete.if_empty && message_count > 0 {
         return Some(channel_error_close(
@@ -268,30 +319,7 @@ async fn process_queue_delete(
         ));
     }
 
-    // Tear down the underlying message shard *before* deleting the queue's
-    // metadata: delete_storage_resource resolves which shards to remove via
-    // the topic lookup (build_driver -> broker_cache.get_topic_by_name),
-    // which depends on that same metadata still being registered. Doing
-    // this after the metadata delete makes the topic lookup fail, so the
-    // shard (and its messages) is silently never removed -- a later
-    // redeclare of the same queue name then resurrects the old messages.
-    if let Err(e) = storage_driver_manager
-        .delete_storage_resource(&tenant, &queue_name)
-        .await
-    {
-        warn!(
-            "AMQP Queue.Delete: failed to remove underlying storage for {}: {}",
-            queue_name, e
-        );
-    }
-
-    let storage = QueueStorage::new(
-        storage_driver_manager
-            .engine_storage_handler
-            .client_pool
-            .clone(),
-    );
-    if let Err(e) = storage.delete_queue(&tenant, &queue_name).await {
+    if let Err(e) = delete_queue(storage_driver_manager, amqp_cache, &tenant, &queue_name).await {
         warn!("AMQP Queue.Delete failed for {}: {}", queue_name, e);
         return Some(channel_error_close(
             channel_id,
@@ -301,7 +329,6 @@ async fn process_queue_delete(
             40,
         ));
     }
-    amqp_cache.remove_queue(&tenant, &queue_name);
 
     if delete.nowait {
         return None;
@@ -314,6 +341,44 @@ async fn process_queue_delete(
     ))
 }
 
+/// Removes a queue: its messages (priority stores included) and then its
+/// metadata. Shared by Queue.Delete and the `x-expires` sweep.
+pub(crate) async fn delete_queue(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    amqp_cache: &Arc<AmqpCacheManager>,
+    tenant: &str,
+    queue_name: &str,
+) -> Result<(), CommonError> {
+    // Tear down the underlying message shard *before* deleting the queue's
+    // metadata: delete_storage_resource resolves which shards to remove via
+    // the topic lookup (build_driver -> broker_cache.get_topic_by_name),
+    // which depends on that same metadata still being registered. Doing
+    // this after the metadata delete makes the topic lookup fail, so the
+    // shard (and its messages) is silently never removed -- a later
+    // redeclare of the same queue name then resurrects the old messages.
+    for store in existing_stores(storage_driver_manager, amqp_cache, tenant, queue_name) {
+        if let Err(e) = storage_driver_manager
+            .delete_storage_resource(tenant, &store)
+            .await
+        {
+            warn!(
+                "AMQP Queue.Delete: failed to remove underlying storage for {}: {}",
+                store, e
+            );
+        }
+    }
+
+    let storage = QueueStorage::new(
+        storage_driver_manager
+            .engine_storage_handler
+            .client_pool
+            .clone(),
+    );
+    storage.delete_queue(tenant, queue_name).await?;
+    amqp_cache.remove_queue(tenant, queue_name);
+    Ok(())
+}
+
 async fn process_queue_bind(
     channel_id: u16,
     bind: &Bind,
@@ -429,13 +494,45 @@ async fn process_queue_purge(
     let queue_name = purge.queue.to_string();
     let tenant = amqp_cache.tenant_for(connection_id);
 
-    let resources = match storage_driver_manager
-        .list_storage_resource(&tenant, &queue_name)
-        .await
-    {
-        Ok(resources) => resources,
-        Err(e) => {
-            warn!("AMQP Queue.Purge: failed to inspect {}: {}", queue_name, e);
+    let mut message_count: u64 = 0;
+    for store in existing_stores(storage_driver_manager, amqp_cache, &tenant, &queue_name) {
+        let resources = match storage_driver_manager
+            .list_storage_resource(&tenant, &store)
+            .await
+        {
+            Ok(resources) => resources,
+            Err(e) => {
+                warn!("AMQP Queue.Purge: failed to inspect {}: {}", store, e);
+                return Some(channel_error_close(
+                    channel_id,
+                    541,
+                    "INTERNAL_ERROR",
+                    50,
+                    30,
+                ));
+            }
+        };
+        // delete_records_before deletes offset < target (exclusive), so the
+        // target must be high_watermark (next-offset-to-write), not end_offset
+        // (the last written offset, inclusive) — using end_offset would leave
+        // the newest message in each shard behind.
+        let targets: HashMap<u32, u64> = resources
+            .iter()
+            .map(|(partition, detail)| (*partition, detail.offset.high_watermark))
+            .collect();
+        message_count += resources
+            .values()
+            .map(|d| {
+                d.offset
+                    .high_watermark
+                    .saturating_sub(d.offset.start_offset)
+            })
+            .sum::<u64>();
+        if let Err(e) = storage_driver_manager
+            .delete_records_before(&tenant, &store, &targets)
+            .await
+        {
+            warn!("AMQP Queue.Purge failed for {}: {}", store, e);
             return Some(channel_error_close(
                 channel_id,
                 541,
@@ -444,35 +541,6 @@ async fn process_queue_purge(
                 30,
             ));
         }
-    };
-    // delete_records_before deletes offset < target (exclusive), so the
-    // target must be high_watermark (next-offset-to-write), not end_offset
-    // (the last written offset, inclusive) — using end_offset would leave
-    // the newest message in each shard behind.
-    let targets: HashMap<u32, u64> = resources
-        .iter()
-        .map(|(partition, detail)| (*partition, detail.offset.high_watermark))
-        .collect();
-    let message_count: u64 = resources
-        .values()
-        .map(|d| {
-            d.offset
-                .high_watermark
-                .saturating_sub(d.offset.start_offset)
-        })
-        .sum();
-    if let Err(e) = storage_driver_manager
-        .delete_records_before(&tenant, &queue_name, &targets)
-        .await
-    {
-        warn!("AMQP Queue.Purge failed for {}: {}", queue_name, e);
-        return Some(channel_error_close(
-            channel_id,
-            541,
-            "INTERNAL_ERROR",
-            50,
-            30,
-        ));
     }
 
     if purge.nowait {
@@ -19,6 +19,7 @@
 use metadata_struct::amqp::exchange::AmqpExchangeType;
 
 use crate::core::cache::AmqpCacheManager;
+use crate::core::queue_args::ARG_ALTERNATE_EXCHANGE;
 
 /// Renders an AMQP field value as a plain string for routing comparisons
 /// (headers-exchange matching, binding arguments). Strings render without
@@ -157,6 +158,7 @@ fn resolve_inner(
     let Some(exchange) = cache.get_exchange(tenant, exchange_name) else {
         return;
     };
+    let matched_before = queues.len();
     for binding in cache.list_bindings_by_source(tenant, exchange_name) {
         if !binding_matches(&exchange.exchange_type, &binding, routing_key, headers) {
             continue;
@@ -174,6 +176,21 @@ fn resolve_inner(
             ),
         }
     }
+    // Nothing matched: hand the message to the exchange's alternate
+    // exchange, if it has one (which may in turn have its own).
+    if queues.len() == matched_before {
+        if let Some(alternate) = exchange.arguments.get(ARG_ALTERNATE_EXCHANGE) {
+            resolve_inner(
+                cache,
+                tenant,
+                alternate,
+                routing_key,
+                headers,
+                visited,
+                queues,
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -354,4 +371,53 @@ fn resolve_queues_cyclic_exchange_binding_does_not_hang() {
         let queues = resolve_queues(&cache, "t1", "a.ex", "x", &HashMap::new());
         assert!(queues.is_empty());
     }
+
+    #[test]
+    fn resolve_queues_unmatched_falls_back_to_alternate_exchange() {
+        use metadata_struct::amqp::exchange::AmqpExchange;
+
+        let cache = AmqpCacheManager::new();
+        cache.set_exchange(AmqpExchange::new(
+            "t1",
+            "orders.ex",
+            AmqpExchangeType::Direct,
+            true,
+            false,
+            false,
+            HashMap::from([(
+                ARG_ALTERNATE_EXCHANGE.to_string(),
+                "unrouted.ex".to_string(),
+            )]),
+        ));
+        cache.set_exchange(AmqpExchange::new(
+            "t1",
+            "unrouted.ex",
+            AmqpExchangeType::Fanout,
+            true,
+            false,
+            false,
+            HashMap::new(),
+        ));
+        cache.set_binding(AmqpBinding::new(
+            "t1",
+            "orders.ex",
+            "orders",
+            AmqpBindingDestinationType::Queue,
+            "order.created",
+            HashMap::new(),
+        ));
+        cache.set_binding(AmqpBinding::new(
+            "t1",
+            "unrouted.ex",
+            "unrouted",
+            AmqpBindingDestinationType::Queue,
+            "",
+            HashMap::new(),
+        ));
+
+        let routed = resolve_queues(&cache, "t1", "orders.ex", "order.created", &HashMap::new());
+        assert_eq!(routed, vec!["orders".to_string()]);
+        let fallback = resolve_queues(&cache, "t1", "orders.ex", "order.deleted", &HashMap::new());
+        assert_eq!(fallback, vec!["unrouted".to_string()]);
+    }
 }
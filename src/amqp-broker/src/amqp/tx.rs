@@ -12,39 +12,321 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::Ordering;
+
 use amq_protocol::frame::AMQPFrame;
 use amq_protocol::protocol::tx::{AMQPMethod, CommitOk, RollbackOk, SelectOk};
 use amq_protocol::protocol::AMQPClass;
+use tracing::error;
+
+use crate::amqp::basic::{process_settle, BasicCtx};
+use crate::amqp::channel::channel_error_close;
+use crate::amqp::publish::commit_publishes;
 
-// Select/Commit/Rollback are all synchronous and require an Ok reply. We don't
-// buffer publishes/acks for real transactional semantics, so every message is
-// already committed immediately — Commit/Rollback are acked as no-ops.
-pub fn process_tx(channel_id: u16, method: &AMQPMethod) -> Option<AMQPFrame> {
+// Select/Commit/Rollback are all synchronous and require an Ok reply. After
+// Select, the channel's publishes and acks are buffered in the cache rather
+// than applied (see `publish::finalize_publish` and `basic::process_basic_full`);
+// Commit applies the buffer, Rollback discards it. Either way the channel stays
+// transactional — a new transaction starts immediately.
+pub(crate) async fn process_tx(
+    channel_id: u16,
+    method: &AMQPMethod,
+    connection_id: u64,
+    ctx: &BasicCtx,
+) -> Option<Vec<AMQPFrame>> {
     match method {
-        AMQPMethod::Select(_) => process_select(channel_id),
-        AMQPMethod::Commit(_) => process_commit(channel_id),
-        AMQPMethod::Rollback(_) => process_rollback(channel_id),
+        AMQPMethod::Select(_) => process_select(channel_id, connection_id, ctx).map(|f| vec![f]),
+        AMQPMethod::Commit(_) => process_commit(channel_id, connection_id, ctx).await,
+        AMQPMethod::Rollback(_) => {
+            process_rollback(channel_id, connection_id, ctx).map(|f| vec![f])
+        }
         _ => None,
     }
 }
 
-fn process_select(channel_id: u16) -> Option<AMQPFrame> {
+fn process_select(channel_id: u16, connection_id: u64, ctx: &BasicCtx) -> Option<AMQPFrame> {
+    if let Some(channel) = ctx.amqp_cache.get_channel(connection_id, channel_id) {
+        // Publisher confirms and transactions are mutually exclusive.
+        if channel.confirm_mode.load(Ordering::SeqCst) {
+            return Some(channel_error_close(
+                channel_id,
+                406,
+                "PRECONDITION_FAILED",
+                90,
+                10,
+            ));
+        }
+        channel.tx_mode.store(true, Ordering::SeqCst);
+    }
     Some(AMQPFrame::Method(
         channel_id,
         AMQPClass::Tx(AMQPMethod::SelectOk(SelectOk {})),
     ))
 }
 
-fn process_commit(channel_id: u16) -> Option<AMQPFrame> {
-    Some(AMQPFrame::Method(
+/// Writes the buffered publishes first and settles the buffered acks only
+/// once every write succeeded. A failed write rolls back the writes that
+/// went through (see `commit_publishes`) and closes the channel, which
+/// requeues everything still unacked on it.
+async fn process_commit(
+    channel_id: u16,
+    connection_id: u64,
+    ctx: &BasicCtx,
+) -> Option<Vec<AMQPFrame>> {
+    if !is_transactional(channel_id, connection_id, ctx) {
+        return Some(vec![channel_error_close(
+            channel_id,
+            406,
+            "PRECONDITION_FAILED",
+            90,
+            20,
+        )]);
+    }
+    let buffer = ctx.amqp_cache.take_tx_buffer(connection_id, channel_id);
+
+    let Some(mut frames) = commit_publishes(channel_id, buffer.publishes, ctx).await else {
+        error!(
+            "AMQP Tx.Commit failed to write buffered publishes on channel {}",
+            channel_id
+        );
+        return Some(vec![channel_error_close(
+            channel_id,
+            541,
+            "INTERNAL_ERROR",
+            90,
+            20,
+        )]);
+    };
+
+    for settle in buffer.settles {
+        process_settle(
+            Some(settle.delivery_tag),
+            settle.multiple,
+            settle.settlement,
+            connection_id,
+            channel_id,
+            ctx,
+        )
+        .await;
+    }
+
+    // Basic.Return for unroutable mandatory publishes goes out ahead of
+    // CommitOk, as RabbitMQ does.
+    frames.push(AMQPFrame::Method(
         channel_id,
         AMQPClass::Tx(AMQPMethod::CommitOk(CommitOk {})),
-    ))
+    ));
+    Some(frames)
 }
 
-fn process_rollback(channel_id: u16) -> Option<AMQPFrame> {
+fn process_rollback(channel_id: u16, connection_id: u64, ctx: &BasicCtx) -> Option<AMQPFrame> {
+    if !is_transactional(channel_id, connection_id, ctx) {
+        return Some(channel_error_close(
+            channel_id,
+            406,
+            "PRECONDITION_FAILED",
+            90,
+            30,
+        ));
+    }
+    ctx.amqp_cache.take_tx_buffer(connection_id, channel_id);
     Some(AMQPFrame::Method(
         channel_id,
         AMQPClass::Tx(AMQPMethod::RollbackOk(RollbackOk {})),
     ))
 }
+
+fn is_transactional(channel_id: u16, connection_id: u64, ctx: &BasicCtx) -> bool {
+    ctx.amqp_cache
+        .get_channel(connection_id, channel_id)
+        .is_some_and(|channel| channel.tx_mode.load(Ordering::SeqCst))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amqp::basic::process_basic_full;
+    use crate::amqp::publish::{enqueue, finalize_publish, rollback_commit, QueueMessage};
+    use crate::core::cache::{AmqpCacheManager, PendingPublish, UnackedEntry};
+    use crate::core::connection::AmqpChannel;
+    use crate::push::AmqpPushManager;
+    use amq_protocol::protocol::basic::{self, Ack};
+    use amq_protocol::protocol::channel::AMQPMethod as ChannelMethod;
+    use amq_protocol::protocol::tx::{Commit, Rollback, Select};
+    use common_config::config::DelayMessageConfig;
+    use delay_message::manager::DelayMessageManager;
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::adapter::adapter_read_config::AdapterReadConfig;
+    use metadata_struct::tenant::DEFAULT_TENANT;
+    use schema_register::schema::SchemaRegisterManager;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use storage_adapter::storage::{test_add_topic, test_build_storage_driver_manager};
+
+    const CONNECTION_ID: u64 = 1;
+    const CHANNEL_ID: u16 = 1;
+    const QUEUE: &str = "tx.queue";
+
+    async fn test_ctx() -> BasicCtx {
+        let storage_driver_manager = test_build_storage_driver_manager().await.unwrap();
+        test_add_topic(&storage_driver_manager, QUEUE);
+        let client_pool = Arc::new(ClientPool::new(8));
+        let delay_message_manager = DelayMessageManager::new(
+            client_pool.clone(),
+            storage_driver_manager.clone(),
+            1,
+            DelayMessageConfig::default(),
+        )
+        .await
+        .unwrap();
+        let amqp_cache = Arc::new(AmqpCacheManager::new());
+        amqp_cache.set_channel(AmqpChannel::new(CONNECTION_ID, CHANNEL_ID));
+        BasicCtx {
+            storage_driver_manager,
+            amqp_cache,
+            client_pool,
+            push_manager: Arc::new(AmqpPushManager::new()),
+            delay_message_manager: Arc::new(delay_message_manager),
+            schema_manager: Arc::new(SchemaRegisterManager::new()),
+        }
+    }
+
+    async fn tx(ctx: &BasicCtx, method: AMQPMethod) -> Vec<AMQPFrame> {
+        process_tx(CHANNEL_ID, &method, CONNECTION_ID, ctx)
+            .await
+            .unwrap()
+    }
+
+    async fn publish(ctx: &BasicCtx, body: &str) {
+        let pending = PendingPublish {
+            tenant: DEFAULT_TENANT.to_string(),
+            routing_key: QUEUE.to_string(),
+            exchange: String::new(),
+            mandatory: false,
+            headers: HashMap::new(),
+            properties: Default::default(),
+            body_size: Some(body.len() as u64),
+            body: body.as_bytes().to_vec(),
+            confirm_seqno: None,
+        };
+        let frames = finalize_publish(CONNECTION_ID, CHANNEL_ID, pending, ctx).await;
+        assert!(frames.is_none());
+    }
+
+    async fn queue_bodies(ctx: &BasicCtx) -> Vec<String> {
+        let topic = ctx
+            .storage_driver_manager
+            .broker_cache
+            .get_topic_by_name(DEFAULT_TENANT, QUEUE)
+            .unwrap();
+        let mut offsets = HashMap::new();
+        offsets.insert(topic.storage_name_list.get(&0).cloned().unwrap(), 0);
+        ctx.storage_driver_manager
+            .read_by_offset(DEFAULT_TENANT, QUEUE, &offsets, &AdapterReadConfig::new())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| String::from_utf8_lossy(&record.data).to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rollback_discards_buffered_publishes() {
+        let ctx = test_ctx().await;
+        tx(&ctx, AMQPMethod::Select(Select {})).await;
+        publish(&ctx, "one").await;
+        publish(&ctx, "two").await;
+
+        let frames = tx(&ctx, AMQPMethod::Rollback(Rollback {})).await;
+        assert!(matches!(
+            frames.as_slice(),
+            [AMQPFrame::Method(
+                _,
+                AMQPClass::Tx(AMQPMethod::RollbackOk(_))
+            )]
+        ));
+        assert!(queue_bodies(&ctx).await.is_empty());
+
+        // The channel stays transactional and the next commit is empty.
+        tx(&ctx, AMQPMethod::Commit(Commit {})).await;
+        assert!(queue_bodies(&ctx).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn commit_applies_buffered_publishes_and_acks() {
+        let ctx = test_ctx().await;
+        ctx.amqp_cache.unacked().insert(
+            (CONNECTION_ID, CHANNEL_ID, 1),
+            UnackedEntry {
+                tenant: DEFAULT_TENANT.to_string(),
+                queue: QUEUE.to_string(),
+                offset: 100,
+                index_offset: 100,
+            },
+        );
+        tx(&ctx, AMQPMethod::Select(Select {})).await;
+        publish(&ctx, "one").await;
+        process_basic_full(
+            CHANNEL_ID,
+            &basic::AMQPMethod::Ack(Ack {
+                delivery_tag: 1,
+                multiple: false,
+            }),
+            CONNECTION_ID,
+            &ctx,
+        )
+        .await;
+        assert!(queue_bodies(&ctx).await.is_empty());
+        assert_eq!(ctx.amqp_cache.unacked_count(CONNECTION_ID, CHANNEL_ID), 1);
+
+        let frames = tx(&ctx, AMQPMethod::Commit(Commit {})).await;
+        assert!(matches!(
+            frames.as_slice(),
+            [AMQPFrame::Method(_, AMQPClass::Tx(AMQPMethod::CommitOk(_)))]
+        ));
+        assert_eq!(queue_bodies(&ctx).await, vec!["one".to_string()]);
+        assert_eq!(ctx.amqp_cache.unacked_count(CONNECTION_ID, CHANNEL_ID), 0);
+    }
+
+    #[tokio::test]
+    async fn rollback_commit_deletes_written_messages() {
+        let ctx = test_ctx().await;
+        let message = QueueMessage {
+            body: b"one".to_vec(),
+            properties: Default::default(),
+        };
+        let outcome = enqueue(
+            &ctx.storage_driver_manager,
+            &ctx.amqp_cache,
+            DEFAULT_TENANT,
+            QUEUE,
+            vec![message.clone(), message],
+        )
+        .await;
+        assert!(outcome.written);
+        assert_eq!(queue_bodies(&ctx).await.len(), 2);
+
+        let outcomes = vec![(DEFAULT_TENANT.to_string(), QUEUE.to_string(), outcome)];
+        rollback_commit(&ctx, &outcomes, &[]).await;
+        assert!(queue_bodies(&ctx).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn select_on_confirm_channel_is_rejected() {
+        let ctx = test_ctx().await;
+        let channel = ctx
+            .amqp_cache
+            .get_channel(CONNECTION_ID, CHANNEL_ID)
+            .unwrap();
+        channel.confirm_mode.store(true, Ordering::SeqCst);
+
+        let frames = tx(&ctx, AMQPMethod::Select(Select {})).await;
+        match frames.as_slice() {
+            [AMQPFrame::Method(_, AMQPClass::Channel(ChannelMethod::Close(close)))] => {
+                assert_eq!(close.reply_code, 406);
+            }
+            _ => panic!("expected Channel.Close"),
+        }
+        assert!(!channel.tx_mode.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// What an AMQP 1.0 link's source/target address names, in RabbitMQ's v2
+/// address format:
+///
+/// - `/queues/{queue}`: a queue, via the default exchange
+/// - `/exchanges/{exchange}`: an exchange, with each message's `subject` as
+///   its routing key
+/// - `/exchanges/{exchange}/{routing-key}`: an exchange with a fixed routing key
+///
+/// A bare `{queue}` (as Azure Service Bus and Artemis clients use) is
+/// treated as `/queues/{queue}`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Amqp1Address {
+    Queue(String),
+    Exchange {
+        exchange: String,
+        routing_key: Option<String>,
+    },
+}
+
+impl Amqp1Address {
+    pub fn parse(address: &str) -> Option<Self> {
+        if address.is_empty() {
+            return None;
+        }
+        let Some(path) = address.strip_prefix('/') else {
+            return Some(Amqp1Address::Queue(address.to_string()));
+        };
+
+        let mut parts = path.splitn(3, '/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("queues"), Some(queue), None) if !queue.is_empty() => {
+                Some(Amqp1Address::Queue(queue.to_string()))
+            }
+            (Some("exchanges"), Some(exchange), routing_key) if !exchange.is_empty() => {
+                Some(Amqp1Address::Exchange {
+                    exchange: exchange.to_string(),
+                    routing_key: routing_key.map(|key| key.to_string()),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The exchange and routing key a message sent to this address is
+    /// published with; `subject` is the message's own subject, used when an
+    /// exchange address doesn't fix the routing key.
+    pub fn publish_route(&self, subject: Option<&str>) -> (String, String) {
+        match self {
+            Amqp1Address::Queue(queue) => (String::new(), queue.clone()),
+            Amqp1Address::Exchange {
+                exchange,
+                routing_key,
+            } => (
+                exchange.clone(),
+                routing_key
+                    .clone()
+                    .or_else(|| subject.map(|s| s.to_string()))
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_queue_and_exchange_addresses() {
+        assert_eq!(
+            Amqp1Address::parse("/queues/orders"),
+            Some(Amqp1Address::Queue("orders".to_string()))
+        );
+        assert_eq!(
+            Amqp1Address::parse("orders"),
+            Some(Amqp1Address::Queue("orders".to_string()))
+        );
+        assert_eq!(
+            Amqp1Address::parse("/exchanges/amq.topic"),
+            Some(Amqp1Address::Exchange {
+                exchange: "amq.topic".to_string(),
+                routing_key: None,
+            })
+        );
+        // The routing key keeps any further slashes.
+        assert_eq!(
+            Amqp1Address::parse("/exchanges/amq.topic/a/b"),
+            Some(Amqp1Address::Exchange {
+                exchange: "amq.topic".to_string(),
+                routing_key: Some("a/b".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_or_empty_addresses() {
+        assert_eq!(Amqp1Address::parse(""), None);
+        assert_eq!(Amqp1Address::parse("/queues/"), None);
+        assert_eq!(Amqp1Address::parse("/queues/a/b"), None);
+        assert_eq!(Amqp1Address::parse("/exchanges/"), None);
+        assert_eq!(Amqp1Address::parse("/topics/t"), None);
+    }
+
+    #[test]
+    fn publish_route_prefers_the_fixed_routing_key() {
+        let queue = Amqp1Address::Queue("q".to_string());
+        assert_eq!(
+            queue.publish_route(Some("ignored")),
+            (String::new(), "q".to_string())
+        );
+
+        let by_subject = Amqp1Address::parse("/exchanges/x").unwrap();
+        assert_eq!(
+            by_subject.publish_route(Some("rk")),
+            ("x".to_string(), "rk".to_string())
+        );
+        assert_eq!(
+            by_subject.publish_route(None),
+            ("x".to_string(), String::new())
+        );
+
+        let fixed = Amqp1Address::parse("/exchanges/x/fixed").unwrap();
+        assert_eq!(
+            fixed.publish_route(Some("rk")),
+            ("x".to_string(), "fixed".to_string())
+        );
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_config::broker::broker_config;
+use common_security::login::password::password_check_by_login;
+use common_security::login::x509::peer_cert_username;
+use metadata_struct::tenant::DEFAULT_TENANT;
+use protocol::amqp1::codec::MAX_FRAME_SIZE;
+use protocol::amqp1::frame::{
+    Amqp1Frame, Amqp1ProtocolHeader, Begin, Close, End, ErrorCondition, Open, Performative,
+    SaslInit, SaslMechanisms, SaslOutcome, SaslPerformative,
+};
+use tracing::{debug, warn};
+
+use crate::amqp::basic;
+use crate::amqp::connection::{parse_sasl_plain, SASL_MECHANISM_EXTERNAL, SASL_MECHANISM_PLAIN};
+use crate::amqp1::session::{Amqp1Session, SESSION_WINDOW};
+use crate::amqp1::{write_frames, Amqp1Ctx, ERR_UNAUTHORIZED};
+use crate::core::connection::{AmqpConnection, AmqpConnectionState};
+
+const SASL_CODE_OK: u8 = 0;
+const SASL_CODE_AUTH: u8 = 1;
+
+/// The tenant a `vhost:{name}` hostname (in SASL Init or Open) names, as
+/// RabbitMQ reads it. Any other hostname is just the server's DNS name.
+pub(crate) fn tenant_from_hostname(hostname: Option<&str>) -> Option<String> {
+    hostname
+        .and_then(|h| h.strip_prefix("vhost:"))
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+}
+
+/// The user a verified client certificate maps to, if any.
+fn cert_user(connection_id: u64, ctx: &Amqp1Ctx) -> Option<String> {
+    ctx.connection_manager
+        .get_connect(connection_id)
+        .and_then(|connection| peer_cert_username(connection.peer_cert.as_ref()))
+}
+
+/// The SASL header starts authentication with PLAIN, plus EXTERNAL when the
+/// client certificate maps to a user. A client that skips SASL and sends the
+/// plain AMQP header is answered with the SASL header instead (spec 5.3.1),
+/// telling it SASL is required; the AMQP header is only echoed once SASL has
+/// succeeded.
+pub(crate) fn process_header(
+    header: Amqp1ProtocolHeader,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    match header {
+        Amqp1ProtocolHeader::Sasl => {
+            ctx.basic
+                .amqp_cache
+                .set_connection(AmqpConnection::new(connection_id));
+            let mut mechanisms = vec![SASL_MECHANISM_PLAIN.to_string()];
+            if cert_user(connection_id, ctx).is_some() {
+                mechanisms.push(SASL_MECHANISM_EXTERNAL.to_string());
+            }
+            vec![
+                Amqp1Frame::Header(Amqp1ProtocolHeader::Sasl),
+                Amqp1Frame::Sasl(SaslPerformative::Mechanisms(SaslMechanisms { mechanisms })),
+            ]
+        }
+        Amqp1ProtocolHeader::Amqp => {
+            let authenticated = ctx
+                .basic
+                .amqp_cache
+                .get_connection(connection_id)
+                .is_some_and(|conn| !conn.username.is_empty());
+            if authenticated {
+                vec![Amqp1Frame::Header(Amqp1ProtocolHeader::Amqp)]
+            } else {
+                warn!(
+                    connection_id,
+                    "AMQP 1.0 client sent the AMQP header without authenticating via SASL"
+                );
+                vec![Amqp1Frame::Header(Amqp1ProtocolHeader::Sasl)]
+            }
+        }
+    }
+}
+
+/// Verifies SASL PLAIN credentials against the tenant named by the Init's
+/// hostname, or the default tenant. They are kept until Open, which may
+/// name a different tenant to check them against. EXTERNAL logs in as the
+/// user the client certificate maps to.
+pub(crate) fn process_sasl_init(
+    init: &SaslInit,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    let cert_user = cert_user(connection_id, ctx);
+    let login = match init.mechanism.as_str() {
+        SASL_MECHANISM_PLAIN => {
+            parse_sasl_plain(init.initial_response.as_deref().unwrap_or_default())
+        }
+        SASL_MECHANISM_EXTERNAL => cert_user.clone().map(|username| (username, String::new())),
+        _ => None,
+    };
+    let tenant = tenant_from_hostname(init.hostname.as_deref())
+        .unwrap_or_else(|| DEFAULT_TENANT.to_string());
+
+    let code = match login {
+        Some((username, password))
+            if cert_user.as_deref() == Some(username.as_str())
+                || password_check_by_login(
+                    &ctx.security_manager,
+                    &tenant,
+                    &username,
+                    &password,
+                ) =>
+        {
+            let mut conn = ctx
+                .basic
+                .amqp_cache
+                .get_connection(connection_id)
+                .unwrap_or_else(|| AmqpConnection::new(connection_id));
+            conn.tenant = tenant;
+            conn.username = username.clone();
+            ctx.basic.amqp_cache.set_connection(conn);
+            ctx.basic
+                .amqp_cache
+                .set_pending_login(connection_id, username, password);
+            SASL_CODE_OK
+        }
+        _ => {
+            warn!(
+                connection_id,
+                "AMQP 1.0 SASL authentication failed (mechanism={}) for vhost={}",
+                init.mechanism,
+                tenant
+            );
+            SASL_CODE_AUTH
+        }
+    };
+    vec![Amqp1Frame::Sasl(SaslPerformative::Outcome(SaslOutcome {
+        code,
+        additional_data: None,
+    }))]
+}
+
+/// Open names the tenant as `vhost:{name}` in its hostname, falling back to
+/// the one SASL authenticated against. A client whose credentials don't
+/// hold for that tenant gets its Open answered and the connection closed
+/// straight away, as the spec requires.
+pub(crate) fn process_open(open: &Open, connection_id: u64, ctx: &Amqp1Ctx) -> Vec<Amqp1Frame> {
+    let cache = &ctx.basic.amqp_cache;
+    let mut conn = cache
+        .get_connection(connection_id)
+        .unwrap_or_else(|| AmqpConnection::new(connection_id));
+    let tenant = tenant_from_hostname(open.hostname.as_deref())
+        .or_else(|| (!conn.tenant.is_empty()).then(|| conn.tenant.clone()))
+        .unwrap_or_else(|| DEFAULT_TENANT.to_string());
+
+    let authenticated = match cache.take_pending_login(connection_id) {
+        Some((username, password)) => {
+            tenant == conn.tenant
+                || cert_user(connection_id, ctx).as_deref() == Some(username.as_str())
+                || password_check_by_login(&ctx.security_manager, &tenant, &username, &password)
+        }
+        None => false,
+    };
+
+    let reply = Amqp1Frame::amqp(
+        0,
+        Performative::Open(Open {
+            container_id: format!("robustmq-{}", broker_config().broker_id),
+            max_frame_size: Some(MAX_FRAME_SIZE),
+            channel_max: Some(u16::MAX),
+            ..Default::default()
+        }),
+    );
+    if !authenticated {
+        warn!(
+            connection_id,
+            "AMQP 1.0 Open authentication failed for vhost={}", tenant
+        );
+        return vec![
+            reply,
+            close_frame(ERR_UNAUTHORIZED, "authentication failed"),
+        ];
+    }
+
+    conn.tenant = tenant;
+    conn.state = AmqpConnectionState::Open;
+    conn.frame_max = open
+        .max_frame_size
+        .map_or(MAX_FRAME_SIZE, |max| max.min(MAX_FRAME_SIZE));
+    conn.channel_max = open.channel_max.unwrap_or(u16::MAX);
+    conn.heartbeat = (open.idle_time_out.unwrap_or(0) / 1000) as u16;
+    cache.set_connection(conn);
+
+    if let Some(idle_time_out) = open.idle_time_out.filter(|ms| *ms > 0) {
+        spawn_heartbeat(ctx.clone(), connection_id, idle_time_out);
+    }
+    vec![reply]
+}
+
+/// A client that set an idle time-out closes the connection once it has
+/// heard nothing for that long, so an empty frame is sent at half of it.
+fn spawn_heartbeat(ctx: Amqp1Ctx, connection_id: u64, idle_time_out_ms: u32) {
+    let interval = Duration::from_millis((idle_time_out_ms / 2).max(1) as u64);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            if ctx.basic.amqp_cache.get_connection(connection_id).is_none() {
+                break;
+            }
+            if let Err(e) = write_frames(
+                &ctx.connection_manager,
+                connection_id,
+                vec![Amqp1Frame::Heartbeat],
+            )
+            .await
+            {
+                debug!(connection_id, "AMQP 1.0 heartbeat stopped: {}", e);
+                break;
+            }
+        }
+    });
+}
+
+pub(crate) fn is_open(connection_id: u64, ctx: &Amqp1Ctx) -> bool {
+    ctx.basic
+        .amqp_cache
+        .get_connection(connection_id)
+        .is_some_and(|conn| conn.state == AmqpConnectionState::Open)
+}
+
+/// Answers the client's Begin on the same channel. The broker never begins
+/// sessions itself, so a Begin that names a remote channel is ignored.
+pub(crate) fn process_begin(
+    channel: u16,
+    begin: &Begin,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    if begin.remote_channel.is_some() {
+        return Vec::new();
+    }
+    ctx.basic
+        .amqp_cache
+        .set_amqp1_session(Arc::new(Amqp1Session::new(
+            connection_id,
+            channel,
+            begin.next_outgoing_id,
+        )));
+    vec![Amqp1Frame::amqp(
+        channel,
+        Performative::Begin(Begin {
+            remote_channel: Some(channel),
+            next_outgoing_id: 0,
+            incoming_window: SESSION_WINDOW,
+            outgoing_window: SESSION_WINDOW,
+            handle_max: None,
+        }),
+    )]
+}
+
+/// Ending a session stops its links and returns its unsettled deliveries
+/// to their queues.
+pub(crate) async fn process_end(
+    channel: u16,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    ctx.basic
+        .amqp_cache
+        .remove_amqp1_session(connection_id, channel);
+    basic::requeue_channel(connection_id, channel, &ctx.basic).await;
+    vec![Amqp1Frame::amqp(channel, Performative::End(End::default()))]
+}
+
+pub(crate) async fn process_close(connection_id: u64, ctx: &Amqp1Ctx) -> Vec<Amqp1Frame> {
+    ctx.basic.amqp_cache.remove_connection(connection_id);
+    basic::requeue_connection(connection_id, &ctx.basic).await;
+    vec![Amqp1Frame::amqp(0, Performative::Close(Close::default()))]
+}
+
+pub(crate) fn close_frame(condition: &str, description: &str) -> Amqp1Frame {
+    Amqp1Frame::amqp(
+        0,
+        Performative::Close(Close {
+            error: Some(ErrorCondition::new(condition, description)),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tenant_from_hostname_reads_vhost_prefix_only() {
+        assert_eq!(
+            tenant_from_hostname(Some("vhost:orders")),
+            Some("orders".to_string())
+        );
+        assert_eq!(tenant_from_hostname(Some("vhost:")), None);
+        assert_eq!(tenant_from_hostname(Some("broker.example.com")), None);
+        assert_eq!(tenant_from_hostname(None), None);
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use common_base::error::common::CommonError;
+use protocol::amqp1::codec::{FRAME_HEADER_SIZE, MAX_FRAME_SIZE};
+use protocol::amqp1::frame::{Amqp1Frame, Performative, Transfer};
+use protocol::amqp1::types::encode_value;
+use tokio::time::sleep;
+use tracing::{debug, error, warn};
+
+use crate::amqp::consume::claim_next;
+use crate::amqp1::message;
+use crate::amqp1::session::{Amqp1Link, Amqp1Session};
+use crate::amqp1::{write_frames, Amqp1Ctx};
+use crate::core::cache::UnackedEntry;
+use crate::core::recovery::requeue_message;
+use crate::push::common::{adaptive_sleep, IDLE_SLEEP_MS};
+use crate::storage::offset::ClaimedMessage;
+
+/// Feeds a client receiver link from `queue` while it has credit, claiming
+/// messages the way Basic.Get does. Deliveries that aren't pre-settled are
+/// tracked as unacked under the session's channel and their delivery-id, so
+/// dispositions settle them through the same path as Basic.Ack. Stops when
+/// the link detaches or the connection goes away.
+pub(crate) fn spawn_delivery(
+    ctx: Amqp1Ctx,
+    session: Arc<Amqp1Session>,
+    link: Arc<Amqp1Link>,
+    tenant: String,
+    queue: String,
+) {
+    tokio::spawn(async move {
+        let connection_id = session.connection_id;
+        loop {
+            if link.detached.load(Ordering::SeqCst)
+                || ctx.connection_manager.get_connect(connection_id).is_none()
+            {
+                break;
+            }
+            if link.credit.load(Ordering::SeqCst) == 0 {
+                sleep(Duration::from_millis(IDLE_SLEEP_MS)).await;
+                continue;
+            }
+
+            let mut delivered = 0;
+            let mut exhausted = false;
+            while link.credit.load(Ordering::SeqCst) > 0 && !link.detached.load(Ordering::SeqCst) {
+                match claim_next(
+                    &ctx.basic,
+                    &tenant,
+                    &queue,
+                    link.pre_settled,
+                    connection_id,
+                    session.channel,
+                )
+                .await
+                {
+                    Ok(Some(claimed)) => {
+                        if let Err(e) = deliver(&ctx, &session, &link, &tenant, claimed).await {
+                            debug!("AMQP 1.0 delivery to link '{}' failed: {}", link.name, e);
+                            break;
+                        }
+                        delivered += 1;
+                    }
+                    Ok(None) => {
+                        exhausted = true;
+                        break;
+                    }
+                    Err(e) => {
+                        warn!(
+                            "AMQP 1.0 link '{}' failed to claim from {}: {}",
+                            link.name, queue, e
+                        );
+                        break;
+                    }
+                }
+            }
+
+            if exhausted && link.drain.load(Ordering::SeqCst) {
+                if let Err(e) = drain(&ctx, &session, &link).await {
+                    debug!("AMQP 1.0 drain of link '{}' failed: {}", link.name, e);
+                }
+            }
+            adaptive_sleep(delivered).await;
+        }
+    });
+}
+
+async fn deliver(
+    ctx: &Amqp1Ctx,
+    session: &Amqp1Session,
+    link: &Amqp1Link,
+    tenant: &str,
+    claimed: ClaimedMessage,
+) -> Result<(), CommonError> {
+    let connection_id = session.connection_id;
+    let payload = message::from_record(&claimed.record, &claimed.store)
+        .encode()
+        .freeze();
+    let max_frame_size = ctx
+        .basic
+        .amqp_cache
+        .get_connection(connection_id)
+        .map(|conn| conn.frame_max)
+        .filter(|max| *max > 0)
+        .unwrap_or(MAX_FRAME_SIZE);
+
+    let mut ids = session.outgoing.lock().await;
+    let delivery_id = ids.next_delivery_id;
+    let transfer = Transfer {
+        handle: link.handle,
+        delivery_id: Some(delivery_id),
+        delivery_tag: Some(delivery_id.to_be_bytes().to_vec()),
+        message_format: Some(0),
+        settled: Some(link.pre_settled),
+        ..Default::default()
+    };
+    let frames = split_transfer(session.channel, transfer, payload, max_frame_size);
+    let frame_count = frames.len() as u32;
+
+    // Tracked before the write so a disposition racing back finds it.
+    let unacked_key = (connection_id, session.channel, delivery_id as u64);
+    if let Some(index_offset) = claimed.index_offset {
+        ctx.basic.amqp_cache.unacked().insert(
+            unacked_key,
+            UnackedEntry {
+                tenant: tenant.to_string(),
+                queue: claimed.store.clone(),
+                offset: claimed.offset,
+                index_offset,
+            },
+        );
+    }
+
+    if let Err(e) = write_frames(&ctx.connection_manager, connection_id, frames).await {
+        drop(ids);
+        ctx.basic.amqp_cache.unacked().remove(&unacked_key);
+        if let Some(index_offset) = claimed.index_offset {
+            if let Err(requeue_err) = requeue_message(
+                &ctx.basic.storage_driver_manager,
+                tenant,
+                &claimed.store,
+                claimed.offset,
+                index_offset,
+            )
+            .await
+            {
+                error!(
+                    "AMQP 1.0: failed to requeue undelivered message from {}: {}",
+                    claimed.store, requeue_err
+                );
+            }
+        }
+        return Err(e);
+    }
+
+    ids.next_delivery_id = delivery_id.wrapping_add(1);
+    ids.next_transfer_id = ids.next_transfer_id.wrapping_add(frame_count);
+    drop(ids);
+
+    let _ = link
+        .credit
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |c| c.checked_sub(1));
+    link.delivery_count.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// With the queue empty, a draining link uses up its remaining credit by
+/// advancing the delivery count, and says so in a flow (spec 2.6.7).
+async fn drain(
+    ctx: &Amqp1Ctx,
+    session: &Amqp1Session,
+    link: &Amqp1Link,
+) -> Result<(), CommonError> {
+    let ids = session.outgoing.lock().await;
+    let credit = link.credit.swap(0, Ordering::SeqCst);
+    link.delivery_count.fetch_add(credit, Ordering::SeqCst);
+    let flow = session.flow(ids.next_transfer_id, Some(link));
+    link.drain.store(false, Ordering::SeqCst);
+    write_frames(
+        &ctx.connection_manager,
+        session.connection_id,
+        vec![Amqp1Frame::amqp(session.channel, Performative::Flow(flow))],
+    )
+    .await
+}
+
+/// Splits one delivery into as many transfer frames as `max_frame_size`
+/// requires, all but the last flagged `more`. Only the first carries the
+/// delivery-id and tag.
+pub(crate) fn split_transfer(
+    channel: u16,
+    transfer: Transfer,
+    payload: Bytes,
+    max_frame_size: u32,
+) -> Vec<Amqp1Frame> {
+    let mut probe = transfer.clone();
+    probe.more = true;
+    let mut encoded = BytesMut::new();
+    encode_value(&Performative::Transfer(probe).encode(), &mut encoded);
+    let chunk = (max_frame_size as usize)
+        .saturating_sub(FRAME_HEADER_SIZE + encoded.len())
+        .max(1);
+
+    if payload.len() <= chunk {
+        return vec![Amqp1Frame::Amqp {
+            channel,
+            performative: Performative::Transfer(transfer),
+            payload,
+        }];
+    }
+
+    let mut frames = Vec::with_capacity(payload.len().div_ceil(chunk));
+    let mut offset = 0;
+    while offset < payload.len() {
+        let end = (offset + chunk).min(payload.len());
+        let mut part = if offset == 0 {
+            transfer.clone()
+        } else {
+            Transfer {
+                handle: transfer.handle,
+                settled: transfer.settled,
+                ..Default::default()
+            }
+        };
+        part.more = end < payload.len();
+        frames.push(Amqp1Frame::Amqp {
+            channel,
+            performative: Performative::Transfer(part),
+            payload: payload.slice(offset..end),
+        });
+        offset = end;
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::amqp1::codec::Amqp1Codec;
+
+    fn transfer() -> Transfer {
+        Transfer {
+            handle: 1,
+            delivery_id: Some(7),
+            delivery_tag: Some(vec![0, 0, 0, 7]),
+            message_format: Some(0),
+            settled: Some(false),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn small_delivery_is_one_frame() {
+        let frames = split_transfer(0, transfer(), Bytes::from_static(b"hello"), 512);
+        assert_eq!(frames.len(), 1);
+        let Amqp1Frame::Amqp {
+            performative: Performative::Transfer(t),
+            ..
+        } = &frames[0]
+        else {
+            panic!("expected a transfer");
+        };
+        assert!(!t.more);
+        assert_eq!(t.delivery_id, Some(7));
+    }
+
+    #[test]
+    fn large_delivery_is_split_within_max_frame_size() {
+        let payload = Bytes::from(vec![9u8; 2000]);
+        let frames = split_transfer(3, transfer(), payload.clone(), 512);
+        assert!(frames.len() > 1);
+
+        let mut codec = Amqp1Codec::new();
+        let mut reassembled = Vec::new();
+        for (i, frame) in frames.iter().enumerate() {
+            let mut buf = BytesMut::new();
+            codec.encode_data(frame.clone(), &mut buf).unwrap();
+            assert!(buf.len() <= 512);
+
+            let Amqp1Frame::Amqp {
+                channel,
+                performative: Performative::Transfer(t),
+                payload,
+            } = frame
+            else {
+                panic!("expected a transfer");
+            };
+            assert_eq!(*channel, 3);
+            assert_eq!(t.more, i + 1 < frames.len());
+            assert_eq!(t.delivery_id.is_some(), i == 0);
+            reassembled.extend_from_slice(payload);
+        }
+        assert_eq!(reassembled, payload.to_vec());
+    }
+}
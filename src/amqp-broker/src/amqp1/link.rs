@@ -0,0 +1,310 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use protocol::amqp1::frame::{
+    Amqp1Frame, Attach, Detach, ErrorCondition, Flow, Performative, Role, SND_SETTLE_MODE_SETTLED,
+    SND_SETTLE_MODE_UNSETTLED,
+};
+use tracing::warn;
+
+use crate::amqp1::address::Amqp1Address;
+use crate::amqp1::delivery::spawn_delivery;
+use crate::amqp1::session::{Amqp1Link, Amqp1Session, LINK_CREDIT};
+use crate::amqp1::{
+    Amqp1Ctx, ERR_INVALID_FIELD, ERR_NOT_FOUND, ERR_NOT_IMPLEMENTED, MAX_MESSAGE_SIZE,
+};
+
+/// Attaches a link. A client sender publishes to its target address and is
+/// granted `LINK_CREDIT` up front, with `MAX_MESSAGE_SIZE` as its
+/// `max-message-size`; a client receiver consumes from the
+/// queue its source names, fed by a delivery task as it grants credit.
+/// Links to addresses that don't resolve are refused: attached with no
+/// terminus and detached with an error, as the spec describes.
+pub(crate) async fn process_attach(
+    channel: u16,
+    attach: &Attach,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    let Some(session) = ctx
+        .basic
+        .amqp_cache
+        .get_amqp1_session(connection_id, channel)
+    else {
+        warn!(
+            connection_id,
+            "AMQP 1.0 Attach on channel {} without a session", channel
+        );
+        return Vec::new();
+    };
+    let tenant = ctx.basic.amqp_cache.tenant_for(connection_id);
+
+    match attach.role {
+        // The client sends, so the broker's end receives.
+        Role::Sender => {
+            let address = attach.target.as_ref().and_then(|t| t.address.as_deref());
+            let address = match resolve_publish_address(address, attach, &tenant, ctx) {
+                Ok(address) => address,
+                Err(error) => return refuse(channel, attach, Role::Receiver, error),
+            };
+            let link = Arc::new(Amqp1Link::new(
+                attach.name.clone(),
+                attach.handle,
+                Role::Receiver,
+                address,
+                false,
+            ));
+            link.credit.store(LINK_CREDIT, Ordering::SeqCst);
+            link.delivery_count
+                .store(attach.initial_delivery_count.unwrap_or(0), Ordering::SeqCst);
+            session.links.insert(attach.handle, link.clone());
+
+            let reply = Attach {
+                role: Role::Receiver,
+                snd_settle_mode: attach.snd_settle_mode,
+                source: attach.source.clone(),
+                target: attach.target.clone(),
+                max_message_size: Some(MAX_MESSAGE_SIZE),
+                ..link_attach(attach)
+            };
+            let next_outgoing_id = session.outgoing.lock().await.next_transfer_id;
+            vec![
+                Amqp1Frame::amqp(channel, Performative::Attach(reply)),
+                Amqp1Frame::amqp(
+                    channel,
+                    Performative::Flow(session.flow(next_outgoing_id, Some(&link))),
+                ),
+            ]
+        }
+        // The client receives, so the broker's end sends.
+        Role::Receiver => {
+            let address = attach.source.as_ref().and_then(|s| s.address.as_deref());
+            let queue = match resolve_consume_queue(address, attach, &tenant, ctx) {
+                Ok(queue) => queue,
+                Err(error) => return refuse(channel, attach, Role::Sender, error),
+            };
+            let pre_settled = attach.snd_settle_mode == SND_SETTLE_MODE_SETTLED;
+            let link = Arc::new(Amqp1Link::new(
+                attach.name.clone(),
+                attach.handle,
+                Role::Sender,
+                Amqp1Address::Queue(queue.clone()),
+                pre_settled,
+            ));
+            session.links.insert(attach.handle, link.clone());
+            spawn_delivery(ctx.clone(), session.clone(), link, tenant, queue);
+
+            let reply = Attach {
+                role: Role::Sender,
+                snd_settle_mode: if pre_settled {
+                    SND_SETTLE_MODE_SETTLED
+                } else {
+                    SND_SETTLE_MODE_UNSETTLED
+                },
+                source: attach.source.clone(),
+                target: attach.target.clone(),
+                initial_delivery_count: Some(0),
+                ..link_attach(attach)
+            };
+            vec![Amqp1Frame::amqp(channel, Performative::Attach(reply))]
+        }
+    }
+}
+
+fn resolve_publish_address(
+    address: Option<&str>,
+    attach: &Attach,
+    tenant: &str,
+    ctx: &Amqp1Ctx,
+) -> Result<Amqp1Address, ErrorCondition> {
+    if attach.target.as_ref().is_some_and(|t| t.dynamic) {
+        return Err(ErrorCondition::new(
+            ERR_NOT_IMPLEMENTED,
+            "dynamic targets are not supported",
+        ));
+    }
+    let address = parse_address(address)?;
+    let cache = &ctx.basic.amqp_cache;
+    match &address {
+        Amqp1Address::Queue(queue) if cache.get_queue(tenant, queue).is_none() => {
+            Err(ErrorCondition::new(
+                ERR_NOT_FOUND,
+                format!("no queue '{queue}' in vhost '{tenant}'"),
+            ))
+        }
+        Amqp1Address::Exchange { exchange, .. }
+            if cache.get_exchange(tenant, exchange).is_none() =>
+        {
+            Err(ErrorCondition::new(
+                ERR_NOT_FOUND,
+                format!("no exchange '{exchange}' in vhost '{tenant}'"),
+            ))
+        }
+        _ => Ok(address),
+    }
+}
+
+/// Receiver links consume from queues only; consuming from an exchange
+/// would need a queue declared on the client's behalf.
+fn resolve_consume_queue(
+    address: Option<&str>,
+    attach: &Attach,
+    tenant: &str,
+    ctx: &Amqp1Ctx,
+) -> Result<String, ErrorCondition> {
+    if attach.source.as_ref().is_some_and(|s| s.dynamic) {
+        return Err(ErrorCondition::new(
+            ERR_NOT_IMPLEMENTED,
+            "dynamic sources are not supported",
+        ));
+    }
+    match parse_address(address)? {
+        Amqp1Address::Queue(queue) => {
+            if ctx.basic.amqp_cache.get_queue(tenant, &queue).is_none() {
+                return Err(ErrorCondition::new(
+                    ERR_NOT_FOUND,
+                    format!("no queue '{queue}' in vhost '{tenant}'"),
+                ));
+            }
+            Ok(queue)
+        }
+        Amqp1Address::Exchange { .. } => Err(ErrorCondition::new(
+            ERR_NOT_IMPLEMENTED,
+            "consuming from an exchange address is not supported; consume from a queue",
+        )),
+    }
+}
+
+fn parse_address(address: Option<&str>) -> Result<Amqp1Address, ErrorCondition> {
+    address.and_then(Amqp1Address::parse).ok_or_else(|| {
+        ErrorCondition::new(
+            ERR_INVALID_FIELD,
+            format!("unsupported address {:?}", address.unwrap_or_default()),
+        )
+    })
+}
+
+/// The fields the broker's Attach always echoes from the client's.
+fn link_attach(attach: &Attach) -> Attach {
+    Attach {
+        name: attach.name.clone(),
+        handle: attach.handle,
+        ..Default::default()
+    }
+}
+
+fn refuse(channel: u16, attach: &Attach, role: Role, error: ErrorCondition) -> Vec<Amqp1Frame> {
+    warn!(
+        "AMQP 1.0 link '{}' refused: {}",
+        attach.name,
+        error.description.as_deref().unwrap_or(&error.condition)
+    );
+    let reply = Attach {
+        role,
+        source: if role == Role::Sender {
+            None
+        } else {
+            attach.source.clone()
+        },
+        target: if role == Role::Receiver {
+            None
+        } else {
+            attach.target.clone()
+        },
+        ..link_attach(attach)
+    };
+    vec![
+        Amqp1Frame::amqp(channel, Performative::Attach(reply)),
+        Amqp1Frame::amqp(
+            channel,
+            Performative::Detach(Detach {
+                handle: attach.handle,
+                closed: true,
+                error: Some(error),
+            }),
+        ),
+    ]
+}
+
+/// Detaching stops the link's delivery task. Deliveries it sent stay
+/// unsettled on the session until settled there or the session ends.
+pub(crate) fn process_detach(
+    channel: u16,
+    detach: &Detach,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    if let Some(session) = ctx
+        .basic
+        .amqp_cache
+        .get_amqp1_session(connection_id, channel)
+    {
+        if let Some((_, link)) = session.links.remove(&detach.handle) {
+            link.detached.store(true, Ordering::SeqCst);
+        }
+    }
+    vec![Amqp1Frame::amqp(
+        channel,
+        Performative::Detach(Detach {
+            handle: detach.handle,
+            closed: detach.closed,
+            error: None,
+        }),
+    )]
+}
+
+/// A flow about one of the broker's sender links sets its credit and drain
+/// mode; the delivery task picks both up. Flows asking for an echo are
+/// answered with the broker's own state.
+pub(crate) async fn process_flow(
+    channel: u16,
+    flow: &Flow,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    let Some(session) = ctx
+        .basic
+        .amqp_cache
+        .get_amqp1_session(connection_id, channel)
+    else {
+        return Vec::new();
+    };
+    let link = flow
+        .handle
+        .and_then(|handle| session.links.get(&handle).map(|l| l.clone()));
+    if let Some(link) = &link {
+        if link.role == Role::Sender {
+            link.apply_receiver_flow(flow);
+        }
+    }
+    if !flow.echo {
+        return Vec::new();
+    }
+    echo_flow(channel, &session, link.as_deref()).await
+}
+
+async fn echo_flow(
+    channel: u16,
+    session: &Amqp1Session,
+    link: Option<&Amqp1Link>,
+) -> Vec<Amqp1Frame> {
+    let next_outgoing_id = session.outgoing.lock().await.next_transfer_id;
+    vec![Amqp1Frame::amqp(
+        channel,
+        Performative::Flow(session.flow(next_outgoing_id, link)),
+    )]
+}
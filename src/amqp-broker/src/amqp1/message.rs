@@ -0,0 +1,183 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use metadata_struct::storage::record::{StorageRecord, StorageRecordProtocolDataAmqp};
+use protocol::amqp1::message::{Amqp1Message, MessageBody, MessageHeader, MessageProperties};
+use protocol::amqp1::types::Amqp1Value;
+
+use crate::amqp::basic::delivery_route;
+
+const ANNOTATION_EXCHANGE: &str = "x-exchange";
+const ANNOTATION_ROUTING_KEY: &str = "x-routing-key";
+
+/// Maps an AMQP 1.0 message's header and properties onto the 0-9-1
+/// properties stored alongside it, so either protocol can consume it.
+/// Application properties become headers, rendered as text like every other
+/// stored header.
+pub(crate) fn to_protocol_data(message: &Amqp1Message) -> StorageRecordProtocolDataAmqp {
+    let mut data = StorageRecordProtocolDataAmqp::default();
+    if let Some(header) = &message.header {
+        data.delivery_mode = Some(if header.durable { 2 } else { 1 });
+        data.priority = header.priority;
+        data.expiration = header.ttl.map(|ttl| ttl.to_string());
+    }
+    if let Some(properties) = &message.properties {
+        data.message_id = properties
+            .message_id
+            .as_ref()
+            .map(|id| id.to_display_string());
+        data.user_id = properties
+            .user_id
+            .as_ref()
+            .and_then(|id| String::from_utf8(id.clone()).ok());
+        data.reply_to = properties.reply_to.clone();
+        data.correlation_id = properties
+            .correlation_id
+            .as_ref()
+            .map(|id| id.to_display_string());
+        data.content_type = properties.content_type.clone();
+        data.content_encoding = properties.content_encoding.clone();
+        data.timestamp = properties
+            .creation_time
+            .and_then(|ms| u64::try_from(ms / 1000).ok());
+    }
+    data.headers = message
+        .application_properties
+        .iter()
+        .map(|(k, v)| (k.clone(), v.to_display_string()))
+        .collect();
+    data
+}
+
+/// Builds the AMQP 1.0 message delivered for a stored record. The exchange
+/// and routing key it was published with travel as the `x-exchange` and
+/// `x-routing-key` message annotations, as RabbitMQ sends them, and the
+/// routing key doubles as the subject.
+pub(crate) fn from_record(record: &StorageRecord, store: &str) -> Amqp1Message {
+    let amqp = record
+        .protocol_data
+        .as_ref()
+        .and_then(|pd| pd.amqp.clone())
+        .unwrap_or_default();
+    let (exchange, routing_key) = delivery_route(record, store);
+
+    let header = MessageHeader {
+        durable: amqp.delivery_mode == Some(2),
+        priority: amqp.priority,
+        ttl: amqp.expiration.as_deref().and_then(|e| e.parse().ok()),
+        first_acquirer: !amqp.redelivered,
+        delivery_count: amqp.redelivered as u32,
+    };
+    let properties = MessageProperties {
+        message_id: amqp.message_id.clone().map(Amqp1Value::String),
+        user_id: amqp.user_id.as_ref().map(|id| id.as_bytes().to_vec()),
+        subject: (!routing_key.is_empty()).then(|| routing_key.clone()),
+        reply_to: amqp.reply_to.clone(),
+        correlation_id: amqp.correlation_id.clone().map(Amqp1Value::String),
+        content_type: amqp.content_type.clone(),
+        content_encoding: amqp.content_encoding.clone(),
+        creation_time: amqp.timestamp.map(|secs| secs as i64 * 1000),
+        ..Default::default()
+    };
+
+    Amqp1Message {
+        header: Some(header),
+        message_annotations: vec![
+            (
+                Amqp1Value::symbol(ANNOTATION_EXCHANGE),
+                Amqp1Value::String(exchange),
+            ),
+            (
+                Amqp1Value::symbol(ANNOTATION_ROUTING_KEY),
+                Amqp1Value::String(routing_key),
+            ),
+        ],
+        properties: Some(properties),
+        application_properties: amqp
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), Amqp1Value::String(v.clone())))
+            .collect(),
+        body: MessageBody::Data(vec![record.data.to_vec()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::storage::record::{StorageRecordMetadata, StorageRecordProtocolData};
+
+    #[test]
+    fn to_protocol_data_maps_header_and_properties() {
+        let message = Amqp1Message {
+            header: Some(MessageHeader {
+                durable: true,
+                priority: Some(5),
+                ttl: Some(1500),
+                ..Default::default()
+            }),
+            properties: Some(MessageProperties {
+                message_id: Some(Amqp1Value::Ulong(42)),
+                content_type: Some("text/plain".to_string()),
+                creation_time: Some(1_700_000_000_123),
+                ..Default::default()
+            }),
+            application_properties: vec![("k".to_string(), Amqp1Value::Int(7))],
+            ..Default::default()
+        };
+
+        let data = to_protocol_data(&message);
+        assert_eq!(data.delivery_mode, Some(2));
+        assert_eq!(data.priority, Some(5));
+        assert_eq!(data.expiration.as_deref(), Some("1500"));
+        assert_eq!(data.message_id.as_deref(), Some("42"));
+        assert_eq!(data.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(data.timestamp, Some(1_700_000_000));
+        assert_eq!(data.headers, vec![("k".to_string(), "7".to_string())]);
+    }
+
+    #[test]
+    fn from_record_carries_route_and_redelivery() {
+        let record = StorageRecord {
+            metadata: StorageRecordMetadata::build(0, "shard".to_string(), 0),
+            protocol_data: Some(StorageRecordProtocolData {
+                amqp: Some(StorageRecordProtocolDataAmqp {
+                    exchange: "x".to_string(),
+                    routing_key: "rk".to_string(),
+                    redelivered: true,
+                    timestamp: Some(10),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            data: bytes::Bytes::from_static(b"hello"),
+        };
+
+        let message = from_record(&record, "q");
+        let header = message.header.clone().unwrap();
+        assert!(!header.first_acquirer);
+        assert_eq!(header.delivery_count, 1);
+        let properties = message.properties.clone().unwrap();
+        assert_eq!(properties.subject.as_deref(), Some("rk"));
+        assert_eq!(properties.creation_time, Some(10_000));
+        assert_eq!(
+            message.message_annotations[0],
+            (
+                Amqp1Value::symbol(ANNOTATION_EXCHANGE),
+                Amqp1Value::String("x".to_string())
+            )
+        );
+        assert_eq!(message.body_bytes(), b"hello".to_vec());
+    }
+}
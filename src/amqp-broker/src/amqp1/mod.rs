@@ -0,0 +1,76 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! AMQP 1.0 front-end. Clients reach it on the AMQP listener, whose codec
+//! switches to AMQP 1.0 on seeing its protocol header, and their links
+//! publish to and consume from the same exchanges and queues as AMQP 0-9-1.
+
+pub mod address;
+pub mod connection;
+pub mod delivery;
+pub mod link;
+pub mod message;
+pub mod session;
+pub mod transfer;
+
+use std::sync::Arc;
+
+use common_base::error::ResultCommonError;
+use common_security::manager::SecurityManager;
+use network_server::common::connection_manager::ConnectionManager;
+use protocol::amqp1::frame::Amqp1Frame;
+use protocol::robust::{
+    Amqp1WrapperExtend, RobustMQPacket, RobustMQPacketWrapper, RobustMQProtocol,
+    RobustMQWrapperExtend,
+};
+
+use crate::amqp::basic::BasicCtx;
+
+pub(crate) const ERR_INTERNAL: &str = "amqp:internal-error";
+pub(crate) const ERR_NOT_FOUND: &str = "amqp:not-found";
+pub(crate) const ERR_NOT_IMPLEMENTED: &str = "amqp:not-implemented";
+pub(crate) const ERR_INVALID_FIELD: &str = "amqp:invalid-field";
+pub(crate) const ERR_DECODE: &str = "amqp:decode-error";
+pub(crate) const ERR_PRECONDITION_FAILED: &str = "amqp:precondition-failed";
+pub(crate) const ERR_UNAUTHORIZED: &str = "amqp:unauthorized-access";
+pub(crate) const ERR_UNATTACHED_HANDLE: &str = "amqp:session:unattached-handle";
+pub(crate) const ERR_MESSAGE_SIZE_EXCEEDED: &str = "amqp:link:message-size-exceeded";
+
+/// Largest message a client sender link may transfer, advertised as the
+/// link's `max-message-size` on attach (RabbitMQ's default).
+pub(crate) const MAX_MESSAGE_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Clone)]
+pub(crate) struct Amqp1Ctx {
+    pub basic: BasicCtx,
+    pub security_manager: Arc<SecurityManager>,
+    // Deliveries and heartbeats are written from their own tasks rather
+    // than as replies to a client frame.
+    pub connection_manager: Arc<ConnectionManager>,
+}
+
+pub(crate) async fn write_frames(
+    connection_manager: &Arc<ConnectionManager>,
+    connection_id: u64,
+    frames: Vec<Amqp1Frame>,
+) -> ResultCommonError {
+    let wrapper = RobustMQPacketWrapper {
+        protocol: RobustMQProtocol::AMQP1,
+        extend: RobustMQWrapperExtend::AMQP1(Amqp1WrapperExtend {}),
+        packet: RobustMQPacket::AMQP1(frames),
+    };
+    connection_manager
+        .write_tcp_frame(connection_id, wrapper)
+        .await
+}
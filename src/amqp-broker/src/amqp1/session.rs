@@ -0,0 +1,212 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use dashmap::DashMap;
+use protocol::amqp1::frame::{Flow, Role};
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::amqp1::address::Amqp1Address;
+
+/// Credit granted to a client's sender link, topped back up once it has
+/// used half of it.
+pub(crate) const LINK_CREDIT: u32 = 256;
+
+/// The session window advertised to clients. It is never shrunk, so it
+/// doesn't limit them; link credit is the only flow control applied.
+pub(crate) const SESSION_WINDOW: u32 = 65_535;
+
+/// The ids stamped on outgoing transfers. Held locked while a delivery is
+/// numbered *and* written, because clients require delivery-ids to arrive
+/// in order.
+#[derive(Default)]
+pub(crate) struct OutgoingIds {
+    pub(crate) next_transfer_id: u32,
+    pub(crate) next_delivery_id: u32,
+}
+
+/// One AMQP 1.0 session. The broker answers each Begin on the same channel
+/// number the client used, so one number identifies it in both directions.
+/// Runtime-only, like `AmqpConnection`.
+pub(crate) struct Amqp1Session {
+    pub(crate) connection_id: u64,
+    pub(crate) channel: u16,
+    pub(crate) outgoing: AsyncMutex<OutgoingIds>,
+    // The transfer-id expected on the client's next transfer frame.
+    pub(crate) next_incoming_id: AtomicU32,
+    // Keyed by the client's handle, which the broker reuses for its own end.
+    pub(crate) links: DashMap<u32, Arc<Amqp1Link>>,
+}
+
+impl Amqp1Session {
+    pub(crate) fn new(connection_id: u64, channel: u16, next_incoming_id: u32) -> Self {
+        Amqp1Session {
+            connection_id,
+            channel,
+            outgoing: AsyncMutex::new(OutgoingIds::default()),
+            next_incoming_id: AtomicU32::new(next_incoming_id),
+            links: DashMap::with_capacity(2),
+        }
+    }
+
+    /// A session-level flow carrying this session's current windows, with
+    /// `link`'s state when it is about one link.
+    pub(crate) fn flow(&self, next_outgoing_id: u32, link: Option<&Amqp1Link>) -> Flow {
+        let mut flow = Flow {
+            next_incoming_id: Some(self.next_incoming_id.load(Ordering::SeqCst)),
+            incoming_window: SESSION_WINDOW,
+            next_outgoing_id,
+            outgoing_window: SESSION_WINDOW,
+            ..Default::default()
+        };
+        if let Some(link) = link {
+            flow.handle = Some(link.handle);
+            flow.delivery_count = Some(link.delivery_count.load(Ordering::SeqCst));
+            flow.link_credit = Some(link.credit.load(Ordering::SeqCst));
+            flow.drain = link.drain.load(Ordering::SeqCst);
+        }
+        flow
+    }
+
+    /// Stops every link's delivery loop; called when the session or its
+    /// connection ends.
+    pub(crate) fn close_links(&self) {
+        for link in self.links.iter() {
+            link.detached.store(true, Ordering::SeqCst);
+        }
+        self.links.clear();
+    }
+}
+
+/// A message arriving over several transfer frames, assembled until the
+/// frame without `more` completes it.
+#[derive(Default)]
+pub(crate) struct PartialDelivery {
+    pub(crate) delivery_id: Option<u32>,
+    pub(crate) settled: bool,
+    pub(crate) payload: Vec<u8>,
+}
+
+/// One link. `role` is the broker's end of it: `Receiver` for a client
+/// publishing to `address`, `Sender` for a client consuming from it.
+pub(crate) struct Amqp1Link {
+    pub(crate) name: String,
+    pub(crate) handle: u32,
+    pub(crate) role: Role,
+    pub(crate) address: Amqp1Address,
+    // Sender links only: the client asked for deliveries pre-settled, so
+    // they are consumed with no_ack.
+    pub(crate) pre_settled: bool,
+    pub(crate) credit: AtomicU32,
+    pub(crate) delivery_count: AtomicU32,
+    pub(crate) drain: AtomicBool,
+    pub(crate) detached: AtomicBool,
+    pub(crate) partial: Mutex<Option<PartialDelivery>>,
+}
+
+impl Amqp1Link {
+    pub(crate) fn new(
+        name: String,
+        handle: u32,
+        role: Role,
+        address: Amqp1Address,
+        pre_settled: bool,
+    ) -> Self {
+        Amqp1Link {
+            name,
+            handle,
+            role,
+            address,
+            pre_settled,
+            credit: AtomicU32::new(0),
+            delivery_count: AtomicU32::new(0),
+            drain: AtomicBool::new(false),
+            detached: AtomicBool::new(false),
+            partial: Mutex::new(None),
+        }
+    }
+
+    /// Applies a client's flow to the broker's sender link: the credit it
+    /// grants is counted from the client's view of the delivery count
+    /// (spec 2.6.7), so deliveries still in flight are taken off it.
+    pub(crate) fn apply_receiver_flow(&self, flow: &Flow) {
+        let delivery_count = self.delivery_count.load(Ordering::SeqCst);
+        let credit = flow
+            .delivery_count
+            .unwrap_or(0)
+            .wrapping_add(flow.link_credit.unwrap_or(0))
+            .wrapping_sub(delivery_count);
+        // A stale flow (one that predates deliveries the client has not seen
+        // yet) can't take the count below zero.
+        let credit = if credit > flow.link_credit.unwrap_or(0) {
+            0
+        } else {
+            credit
+        };
+        self.credit.store(credit, Ordering::SeqCst);
+        self.drain.store(flow.drain, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sender_link() -> Amqp1Link {
+        Amqp1Link::new(
+            "l".to_string(),
+            0,
+            Role::Sender,
+            Amqp1Address::Queue("q".to_string()),
+            false,
+        )
+    }
+
+    #[test]
+    fn receiver_flow_discounts_deliveries_in_flight() {
+        let link = sender_link();
+        link.delivery_count.store(10, Ordering::SeqCst);
+
+        // The client has seen 8 of the 10 deliveries and grants 5 more.
+        link.apply_receiver_flow(&Flow {
+            delivery_count: Some(8),
+            link_credit: Some(5),
+            drain: true,
+            ..Default::default()
+        });
+        assert_eq!(link.credit.load(Ordering::SeqCst), 3);
+        assert!(link.drain.load(Ordering::SeqCst));
+
+        // Fewer credits than deliveries in flight leave none.
+        link.apply_receiver_flow(&Flow {
+            delivery_count: Some(2),
+            link_credit: Some(5),
+            ..Default::default()
+        });
+        assert_eq!(link.credit.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn close_links_stops_every_link() {
+        let session = Amqp1Session::new(1, 0, 0);
+        let link = Arc::new(sender_link());
+        session.links.insert(0, link.clone());
+
+        session.close_links();
+        assert!(link.detached.load(Ordering::SeqCst));
+        assert!(session.links.is_empty());
+    }
+}
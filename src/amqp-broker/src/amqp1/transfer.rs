@@ -0,0 +1,355 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::Ordering;
+
+use protocol::amqp1::frame::{
+    Amqp1Frame, DeliveryState, Detach, Disposition, ErrorCondition, Performative, Role, Transfer,
+};
+use protocol::amqp1::message::Amqp1Message;
+use tracing::warn;
+
+use crate::amqp::basic::process_settle;
+use crate::amqp::publish::{route_publish, PublishOutcome};
+use crate::amqp1::message::to_protocol_data;
+use crate::amqp1::session::{Amqp1Link, Amqp1Session, LINK_CREDIT};
+use crate::amqp1::{
+    Amqp1Ctx, ERR_DECODE, ERR_INTERNAL, ERR_MESSAGE_SIZE_EXCEEDED, ERR_PRECONDITION_FAILED,
+    ERR_UNATTACHED_HANDLE, MAX_MESSAGE_SIZE,
+};
+use crate::core::cache::{PendingPublish, Settlement};
+
+/// A transfer on a client sender link. Frames flagged `more` are collected
+/// until the delivery is complete, which is then published like a
+/// Basic.Publish to the link's address. An unsettled delivery is answered
+/// with its outcome: accepted once stored, released when no queue took it,
+/// rejected if it couldn't be decoded or stored. Credit is topped back up
+/// once half of it has been used. A delivery growing past `MAX_MESSAGE_SIZE`
+/// is dropped and its link detached with `message-size-exceeded`.
+pub(crate) async fn process_transfer(
+    channel: u16,
+    transfer: &Transfer,
+    payload: &[u8],
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    let Some(session) = ctx
+        .basic
+        .amqp_cache
+        .get_amqp1_session(connection_id, channel)
+    else {
+        return Vec::new();
+    };
+    session.next_incoming_id.fetch_add(1, Ordering::SeqCst);
+
+    let link = session
+        .links
+        .get(&transfer.handle)
+        .map(|l| l.clone())
+        .filter(|l| l.role == Role::Receiver);
+    let Some(link) = link else {
+        return vec![Amqp1Frame::amqp(
+            channel,
+            Performative::Detach(Detach {
+                handle: transfer.handle,
+                closed: true,
+                error: Some(ErrorCondition::new(
+                    ERR_UNATTACHED_HANDLE,
+                    format!("no sender link attached on handle {}", transfer.handle),
+                )),
+            }),
+        )];
+    };
+
+    let delivery = {
+        let mut partial = link.partial.lock().unwrap();
+        if transfer.aborted {
+            *partial = None;
+            return Vec::new();
+        }
+        let entry = partial.get_or_insert_with(Default::default);
+        if exceeds_max_message_size(entry.payload.len(), payload.len()) {
+            *partial = None;
+            return detach_oversized(channel, &session, &link);
+        }
+        if entry.delivery_id.is_none() {
+            entry.delivery_id = transfer.delivery_id;
+        }
+        entry.settled |= transfer.settled.unwrap_or(false);
+        entry.payload.extend_from_slice(payload);
+        if transfer.more {
+            return Vec::new();
+        }
+        partial.take().unwrap_or_default()
+    };
+
+    link.delivery_count.fetch_add(1, Ordering::SeqCst);
+    let remaining = link
+        .credit
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |c| c.checked_sub(1))
+        .map_or(0, |c| c - 1);
+
+    let state = publish_delivery(&delivery.payload, &link, connection_id, ctx).await;
+
+    let mut frames = Vec::new();
+    if !delivery.settled {
+        if let Some(delivery_id) = delivery.delivery_id {
+            frames.push(Amqp1Frame::amqp(
+                channel,
+                Performative::Disposition(Disposition {
+                    role: Role::Receiver,
+                    first: delivery_id,
+                    last: None,
+                    settled: true,
+                    state: Some(state),
+                }),
+            ));
+        }
+    }
+    if remaining < LINK_CREDIT / 2 {
+        link.credit.store(LINK_CREDIT, Ordering::SeqCst);
+        let next_outgoing_id = session.outgoing.lock().await.next_transfer_id;
+        frames.push(Amqp1Frame::amqp(
+            channel,
+            Performative::Flow(session.flow(next_outgoing_id, Some(&link))),
+        ));
+    }
+    frames
+}
+
+fn exceeds_max_message_size(received: usize, incoming: usize) -> bool {
+    (received + incoming) as u64 > MAX_MESSAGE_SIZE
+}
+
+/// Closes a sender link whose delivery went over `MAX_MESSAGE_SIZE`; the
+/// client has to attach again to keep publishing.
+fn detach_oversized(channel: u16, session: &Amqp1Session, link: &Amqp1Link) -> Vec<Amqp1Frame> {
+    warn!(
+        "AMQP 1.0 link '{}' detached: delivery larger than {} bytes",
+        link.name, MAX_MESSAGE_SIZE
+    );
+    session.links.remove(&link.handle);
+    link.detached.store(true, Ordering::SeqCst);
+    vec![Amqp1Frame::amqp(
+        channel,
+        Performative::Detach(Detach {
+            handle: link.handle,
+            closed: true,
+            error: Some(ErrorCondition::new(
+                ERR_MESSAGE_SIZE_EXCEEDED,
+                format!("message exceeds the link's max-message-size of {MAX_MESSAGE_SIZE} bytes"),
+            )),
+        }),
+    )]
+}
+
+async fn publish_delivery(
+    payload: &[u8],
+    link: &Amqp1Link,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> DeliveryState {
+    let message = match Amqp1Message::decode(payload) {
+        Ok(message) => message,
+        Err(e) => {
+            warn!(
+                "AMQP 1.0 link '{}' received an undecodable message: {}",
+                link.name, e
+            );
+            return DeliveryState::Rejected(Some(ErrorCondition::new(ERR_DECODE, e.to_string())));
+        }
+    };
+
+    let subject = message
+        .properties
+        .as_ref()
+        .and_then(|p| p.subject.as_deref());
+    let (exchange, routing_key) = link.address.publish_route(subject);
+    let properties = to_protocol_data(&message);
+    let body = message.body_bytes();
+    let pending = PendingPublish {
+        tenant: ctx.basic.amqp_cache.tenant_for(connection_id),
+        routing_key,
+        exchange,
+        mandatory: false,
+        headers: properties.headers.iter().cloned().collect(),
+        properties,
+        body_size: Some(body.len() as u64),
+        body,
+        confirm_seqno: None,
+    };
+
+    match route_publish(&pending, &ctx.basic).await {
+        PublishOutcome::Stored(true) => DeliveryState::Accepted,
+        PublishOutcome::Stored(false) => DeliveryState::Rejected(Some(ErrorCondition::new(
+            ERR_INTERNAL,
+            "failed to store message",
+        ))),
+        PublishOutcome::Invalid => DeliveryState::Rejected(Some(ErrorCondition::new(
+            ERR_PRECONDITION_FAILED,
+            "message does not match the schema bound to its queue",
+        ))),
+        PublishOutcome::Unroutable | PublishOutcome::Dropped => DeliveryState::Released,
+    }
+}
+
+/// How the client's outcome for one of the broker's deliveries settles it:
+/// accepted acks it, rejected dead-letters it, released requeues it, and
+/// modified requeues it unless the client marked it undeliverable here.
+/// `None` for the non-terminal `received` state.
+fn settlement_for(state: Option<&DeliveryState>) -> Option<Settlement> {
+    match state {
+        None | Some(DeliveryState::Accepted) => Some(Settlement::Ack),
+        Some(DeliveryState::Rejected(_)) => Some(Settlement::Reject),
+        Some(DeliveryState::Modified {
+            undeliverable_here: true,
+            ..
+        }) => Some(Settlement::Reject),
+        Some(DeliveryState::Released) | Some(DeliveryState::Modified { .. }) => {
+            Some(Settlement::Requeue)
+        }
+        Some(DeliveryState::Received { .. }) => None,
+    }
+}
+
+/// A client receiver settling the broker's deliveries `first..=last`. If
+/// the client hasn't settled them itself, the broker confirms the outcome.
+pub(crate) async fn process_disposition(
+    channel: u16,
+    disposition: &Disposition,
+    connection_id: u64,
+    ctx: &Amqp1Ctx,
+) -> Vec<Amqp1Frame> {
+    // Dispositions from the client's sending side concern deliveries the
+    // broker has already settled.
+    if disposition.role != Role::Receiver {
+        return Vec::new();
+    }
+    let Some(settlement) = settlement_for(disposition.state.as_ref()) else {
+        return Vec::new();
+    };
+
+    let last = disposition.last.unwrap_or(disposition.first);
+    let mut delivery_id = disposition.first;
+    loop {
+        process_settle(
+            Some(delivery_id as u64),
+            false,
+            settlement,
+            connection_id,
+            channel,
+            &ctx.basic,
+        )
+        .await;
+        if delivery_id == last {
+            break;
+        }
+        delivery_id = delivery_id.wrapping_add(1);
+    }
+
+    if disposition.settled {
+        return Vec::new();
+    }
+    vec![Amqp1Frame::amqp(
+        channel,
+        Performative::Disposition(Disposition {
+            role: Role::Sender,
+            first: disposition.first,
+            last: disposition.last,
+            settled: true,
+            state: disposition.state.clone(),
+        }),
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn outcomes_map_to_settlements() {
+        assert_eq!(settlement_for(None), Some(Settlement::Ack));
+        assert_eq!(
+            settlement_for(Some(&DeliveryState::Accepted)),
+            Some(Settlement::Ack)
+        );
+        assert_eq!(
+            settlement_for(Some(&DeliveryState::Rejected(None))),
+            Some(Settlement::Reject)
+        );
+        assert_eq!(
+            settlement_for(Some(&DeliveryState::Released)),
+            Some(Settlement::Requeue)
+        );
+        assert_eq!(
+            settlement_for(Some(&DeliveryState::Modified {
+                delivery_failed: true,
+                undeliverable_here: false,
+            })),
+            Some(Settlement::Requeue)
+        );
+        assert_eq!(
+            settlement_for(Some(&DeliveryState::Modified {
+                delivery_failed: true,
+                undeliverable_here: true,
+            })),
+            Some(Settlement::Reject)
+        );
+        assert_eq!(
+            settlement_for(Some(&DeliveryState::Received {
+                section_number: 0,
+                section_offset: 0,
+            })),
+            None
+        );
+    }
+
+    #[test]
+    fn oversized_delivery_detaches_its_link() {
+        assert!(!exceeds_max_message_size(
+            MAX_MESSAGE_SIZE as usize - 10,
+            10
+        ));
+        assert!(exceeds_max_message_size(MAX_MESSAGE_SIZE as usize - 10, 11));
+
+        let session = Amqp1Session::new(1, 0, 0);
+        let link = Arc::new(Amqp1Link::new(
+            "sender".to_string(),
+            3,
+            Role::Receiver,
+            crate::amqp1::address::Amqp1Address::Queue("q".to_string()),
+            false,
+        ));
+        session.links.insert(3, link.clone());
+
+        let frames = detach_oversized(0, &session, &link);
+        assert!(session.links.is_empty());
+        assert!(link.detached.load(Ordering::SeqCst));
+        match frames.as_slice() {
+            [Amqp1Frame::Amqp {
+                performative: Performative::Detach(detach),
+                ..
+            }] => {
+                assert_eq!(detach.handle, 3);
+                assert!(detach.closed);
+                assert_eq!(
+                    detach.error.as_ref().map(|e| e.condition.as_str()),
+                    Some(ERR_MESSAGE_SIZE_EXCEEDED)
+                );
+            }
+            _ => panic!("expected a Detach"),
+        }
+    }
+}
@@ -21,6 +21,7 @@
 use common_base::task::TaskSupervisor;
 use common_config::broker::broker_config;
 use common_security::manager::SecurityManager;
+use delay_message::manager::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
 use network_server::common::channel::RequestChannel;
 use network_server::common::connection_manager::ConnectionManager;
@@ -43,6 +44,7 @@ pub struct AmqpBrokerServerParams {
     pub amqp_cache: Arc<AmqpCacheManager>,
     pub security_manager: Arc<SecurityManager>,
     pub push_manager: Arc<AmqpPushManager>,
+    pub delay_message_manager: Arc<DelayMessageManager>,
 }
 
 pub struct AmqpBrokerServer {
@@ -60,6 +62,8 @@ pub fn new(params: AmqpBrokerServerParams) -> Self {
         let recovery_scanner = AmqpRecoveryScanner::new(
             params.client_pool.clone(),
             params.storage_driver_manager.clone(),
+            params.amqp_cache.clone(),
+            params.push_manager.clone(),
         );
         let push_watcher_params = PushWatcherParams {
             connection_manager: params.connection_manager.clone(),
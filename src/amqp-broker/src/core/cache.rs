@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use dashmap::DashMap;
 use metadata_struct::amqp::binding::AmqpBinding;
@@ -20,7 +21,9 @@
 use metadata_struct::amqp::queue::AmqpQueue;
 use metadata_struct::storage::record::StorageRecordProtocolDataAmqp;
 use metadata_struct::tenant::DEFAULT_TENANT;
+use tokio::sync::Notify;
 
+use crate::amqp1::session::Amqp1Session;
 use crate::core::connection::{AmqpChannel, AmqpConnection};
 
 #[derive(Clone)]
@@ -45,6 +48,42 @@ pub(crate) struct PendingPublish {
     pub(crate) confirm_seqno: Option<u64>,
 }
 
+/// What a Basic.Ack/Nack/Reject does with the deliveries it settles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Settlement {
+    Ack,
+    Requeue,
+    /// Nack/Reject without requeue: the message is dead-lettered.
+    Reject,
+}
+
+impl Settlement {
+    pub(crate) fn rejected(requeue: bool) -> Self {
+        if requeue {
+            Settlement::Requeue
+        } else {
+            Settlement::Reject
+        }
+    }
+}
+
+/// A Basic.Ack/Nack/Reject received on a Tx.Select channel, held until
+/// Tx.Commit settles it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TxSettle {
+    pub(crate) delivery_tag: u64,
+    pub(crate) multiple: bool,
+    pub(crate) settlement: Settlement,
+}
+
+/// Everything a Tx.Select channel has done since its last Tx.Commit or
+/// Tx.Rollback, in arrival order.
+#[derive(Default)]
+pub(crate) struct TxBuffer {
+    pub(crate) publishes: Vec<PendingPublish>,
+    pub(crate) settles: Vec<TxSettle>,
+}
+
 /// Local record of what a `Basic.Consume` targets, so Cancel/channel-close/
 /// connection-close know what to deregister without a meta-service round trip.
 #[derive(Clone)]
@@ -63,6 +102,16 @@ pub struct AmqpCacheManager {
     pending_publish: DashMap<(u64, u16), PendingPublish>,
     unacked: DashMap<(u64, u16, u64), UnackedEntry>,
     consumers: DashMap<(u64, u16, String), ConsumerRegistration>,
+    tx_buffers: DashMap<(u64, u16), TxBuffer>,
+    // AMQP 1.0 sessions, by (connection, channel).
+    amqp1_sessions: DashMap<(u64, u16), Arc<Amqp1Session>>,
+    // Ready-message counts of `x-max-length` queues: the count and when it
+    // was last read from storage (ms). Publishes add to it in between.
+    queue_lengths: DashMap<String, (u64, u64)>,
+    // `drop-head` queues that went over `x-max-length`, waiting for the
+    // recovery scanner to trim them.
+    trim_requests: DashMap<String, (String, String)>,
+    trim_notify: Notify,
 }
 
 impl AmqpCacheManager {
@@ -77,6 +126,11 @@ pub fn new() -> Self {
             pending_publish: DashMap::with_capacity(8),
             unacked: DashMap::with_capacity(8),
             consumers: DashMap::with_capacity(8),
+            tx_buffers: DashMap::with_capacity(8),
+            amqp1_sessions: DashMap::with_capacity(8),
+            queue_lengths: DashMap::with_capacity(8),
+            trim_requests: DashMap::with_capacity(8),
+            trim_notify: Notify::new(),
         }
     }
 
@@ -88,6 +142,36 @@ pub(crate) fn unacked(&self) -> &DashMap<(u64, u16, u64), UnackedEntry> {
         &self.unacked
     }
 
+    pub(crate) fn buffer_tx_publish(
+        &self,
+        connection_id: u64,
+        channel_id: u16,
+        pending: PendingPublish,
+    ) {
+        self.tx_buffers
+            .entry((connection_id, channel_id))
+            .or_default()
+            .publishes
+            .push(pending);
+    }
+
+    pub(crate) fn buffer_tx_settle(&self, connection_id: u64, channel_id: u16, settle: TxSettle) {
+        self.tx_buffers
+            .entry((connection_id, channel_id))
+            .or_default()
+            .settles
+            .push(settle);
+    }
+
+    /// Removes and returns the channel's current transaction; Tx.Commit
+    /// applies it, Tx.Rollback just drops it.
+    pub(crate) fn take_tx_buffer(&self, connection_id: u64, channel_id: u16) -> TxBuffer {
+        self.tx_buffers
+            .remove(&(connection_id, channel_id))
+            .map(|(_, v)| v)
+            .unwrap_or_default()
+    }
+
     /// Count of not-yet-acked deliveries outstanding on one channel, used to
     /// enforce Basic.Qos prefetch_count against push delivery.
     pub(crate) fn unacked_count(&self, connection_id: u64, channel_id: u16) -> usize {
@@ -195,8 +279,10 @@ pub fn set_queue(&self, queue: AmqpQueue) {
     }
 
     pub fn remove_queue(&self, tenant: &str, queue_name: &str) {
-        self.queues
-            .remove(&Self::tenant_name_key(tenant, queue_name));
+        let key = Self::tenant_name_key(tenant, queue_name);
+        self.queues.remove(&key);
+        self.queue_lengths.remove(&key);
+        self.trim_requests.remove(&key);
     }
 
     pub fn get_queue(&self, tenant: &str, queue_name: &str) -> Option<AmqpQueue> {
@@ -205,6 +291,13 @@ pub fn get_queue(&self, tenant: &str, queue_name: &str) -> Option<AmqpQueue> {
             .map(|q| q.clone())
     }
 
+    pub fn list_queues(&self) -> Vec<AmqpQueue> {
+        self.queues
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
     pub fn list_queues_by_tenant(&self, tenant: &str) -> Vec<AmqpQueue> {
         let prefix = format!("{}/", tenant);
         self.queues
@@ -264,6 +357,15 @@ pub fn remove_connection(&self, connection_id: u64) {
         self.channels
             .retain(|(conn_id, _), _| *conn_id != connection_id);
         self.pending_logins.remove(&connection_id);
+        self.tx_buffers
+            .retain(|(conn_id, _), _| *conn_id != connection_id);
+        self.amqp1_sessions.retain(|(conn_id, _), session| {
+            if *conn_id != connection_id {
+                return true;
+            }
+            session.close_links();
+            false
+        });
     }
 
     pub fn set_pending_login(&self, connection_id: u64, username: String, password: String) {
@@ -286,6 +388,8 @@ pub fn set_channel(&self, channel: AmqpChannel) {
 
     pub fn remove_channel(&self, connection_id: u64, channel_id: u16) {
         self.channels.remove(&(connection_id, channel_id));
+        // An uncommitted transaction dies with its channel.
+        self.tx_buffers.remove(&(connection_id, channel_id));
     }
 
     pub fn get_channel(&self, connection_id: u64, channel_id: u16) -> Option<AmqpChannel> {
@@ -293,6 +397,81 @@ pub fn get_channel(&self, connection_id: u64, channel_id: u16) -> Option<AmqpCha
             .get(&(connection_id, channel_id))
             .map(|c| c.clone())
     }
+
+    pub(crate) fn set_amqp1_session(&self, session: Arc<Amqp1Session>) {
+        self.amqp1_sessions
+            .insert((session.connection_id, session.channel), session);
+    }
+
+    pub(crate) fn get_amqp1_session(
+        &self,
+        connection_id: u64,
+        channel: u16,
+    ) -> Option<Arc<Amqp1Session>> {
+        self.amqp1_sessions
+            .get(&(connection_id, channel))
+            .map(|s| s.clone())
+    }
+
+    pub(crate) fn remove_amqp1_session(&self, connection_id: u64, channel: u16) {
+        if let Some((_, session)) = self.amqp1_sessions.remove(&(connection_id, channel)) {
+            session.close_links();
+        }
+    }
+
+    /// The queue's cached ready count, if it was read from storage less than
+    /// `max_age_ms` ago.
+    pub(crate) fn cached_queue_length(
+        &self,
+        tenant: &str,
+        queue_name: &str,
+        max_age_ms: u64,
+        now_ms: u64,
+    ) -> Option<u64> {
+        self.queue_lengths
+            .get(&Self::tenant_name_key(tenant, queue_name))
+            .filter(|entry| now_ms.saturating_sub(entry.1) < max_age_ms)
+            .map(|entry| entry.0)
+    }
+
+    pub(crate) fn set_queue_length(&self, tenant: &str, queue_name: &str, ready: u64, now_ms: u64) {
+        self.queue_lengths
+            .insert(Self::tenant_name_key(tenant, queue_name), (ready, now_ms));
+    }
+
+    /// Counts `added` newly written messages; returns the new count, or
+    /// None if the queue's length isn't cached.
+    pub(crate) fn add_queue_length(
+        &self,
+        tenant: &str,
+        queue_name: &str,
+        added: u64,
+    ) -> Option<u64> {
+        self.queue_lengths
+            .get_mut(&Self::tenant_name_key(tenant, queue_name))
+            .map(|mut entry| {
+                entry.0 = entry.0.saturating_add(added);
+                entry.0
+            })
+    }
+
+    pub(crate) fn request_trim(&self, tenant: &str, queue_name: &str) {
+        self.trim_requests.insert(
+            Self::tenant_name_key(tenant, queue_name),
+            (tenant.to_string(), queue_name.to_string()),
+        );
+        self.trim_notify.notify_one();
+    }
+
+    /// Waits for `request_trim`, then hands over every (tenant, queue)
+    /// requested so far.
+    pub(crate) async fn next_trim_requests(&self) -> Vec<(String, String)> {
+        self.trim_notify.notified().await;
+        let keys: Vec<String> = self.trim_requests.iter().map(|e| e.key().clone()).collect();
+        keys.into_iter()
+            .filter_map(|key| self.trim_requests.remove(&key).map(|(_, v)| v))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -407,6 +586,30 @@ fn unacked_count_is_scoped_to_one_channel() {
         assert_eq!(cache.unacked_count(9, 9), 0);
     }
 
+    #[test]
+    fn tx_buffer_keeps_order_and_dies_with_its_channel() {
+        let cache = AmqpCacheManager::new();
+        let settle = |delivery_tag: u64| TxSettle {
+            delivery_tag,
+            multiple: false,
+            settlement: Settlement::Ack,
+        };
+        cache.buffer_tx_settle(1, 1, settle(2));
+        cache.buffer_tx_settle(1, 1, settle(1));
+        cache.buffer_tx_settle(1, 2, settle(7));
+        cache.buffer_tx_settle(2, 1, settle(9));
+
+        let buffer = cache.take_tx_buffer(1, 1);
+        assert_eq!(buffer.settles, vec![settle(2), settle(1)]);
+        assert!(buffer.publishes.is_empty());
+        assert!(cache.take_tx_buffer(1, 1).settles.is_empty());
+
+        cache.remove_channel(1, 2);
+        assert!(cache.take_tx_buffer(1, 2).settles.is_empty());
+        cache.remove_connection(2);
+        assert!(cache.take_tx_buffer(2, 1).settles.is_empty());
+    }
+
     #[test]
     fn connection_and_channel_lifecycle() {
         let cache = AmqpCacheManager::new();
@@ -427,4 +630,30 @@ fn connection_and_channel_lifecycle() {
         assert!(cache.get_channel(1, 2).is_none());
         assert!(cache.get_channel(2, 1).is_some());
     }
+
+    #[test]
+    fn queue_length_is_cached_until_it_goes_stale() {
+        let cache = AmqpCacheManager::new();
+        assert_eq!(cache.add_queue_length("t1", "q1", 3), None);
+
+        cache.set_queue_length("t1", "q1", 5, 1_000);
+        assert_eq!(cache.add_queue_length("t1", "q1", 2), Some(7));
+        assert_eq!(cache.cached_queue_length("t1", "q1", 1_000, 1_999), Some(7));
+        assert_eq!(cache.cached_queue_length("t1", "q1", 1_000, 2_000), None);
+        assert_eq!(cache.cached_queue_length("t2", "q1", 1_000, 1_500), None);
+
+        cache.remove_queue("t1", "q1");
+        assert_eq!(cache.cached_queue_length("t1", "q1", 1_000, 1_500), None);
+    }
+
+    #[tokio::test]
+    async fn trim_requests_are_handed_over_once() {
+        let cache = AmqpCacheManager::new();
+        cache.request_trim("t1", "q1");
+        cache.request_trim("t1", "q1");
+
+        let requests = cache.next_trim_requests().await;
+        assert_eq!(requests, vec![("t1".to_string(), "q1".to_string())]);
+        assert!(cache.trim_requests.is_empty());
+    }
 }
@@ -79,6 +79,10 @@ pub struct AmqpChannel {
     // that number once its write to storage resolves.
     pub confirm_mode: Arc<AtomicBool>,
     pub next_publish_seqno: Arc<AtomicU64>,
+    // Tx.Select state: once set, publishes and acks on this channel are held
+    // in the cache's tx buffer until Tx.Commit applies them or Tx.Rollback
+    // drops them. Mutually exclusive with `confirm_mode`, as in RabbitMQ.
+    pub tx_mode: Arc<AtomicBool>,
     // Basic.Qos prefetch_count; 0 means unlimited (spec default).
     pub prefetch_count: Arc<AtomicU32>,
     // Channel.Flow: false pauses Basic.Deliver push to this channel until a
@@ -96,6 +100,7 @@ pub fn new(connection_id: u64, channel_id: u16) -> Self {
             next_delivery_tag: Arc::new(AtomicU64::new(1)),
             confirm_mode: Arc::new(AtomicBool::new(false)),
             next_publish_seqno: Arc::new(AtomicU64::new(1)),
+            tx_mode: Arc::new(AtomicBool::new(false)),
             prefetch_count: Arc::new(AtomicU32::new(0)),
             flow_active: Arc::new(AtomicBool::new(true)),
         }
@@ -112,6 +117,7 @@ fn new_channel_starts_open_unconfirmed_unlimited_and_flowing() {
         let channel = AmqpChannel::new(1, 2);
         assert_eq!(channel.state, AmqpChannelState::Open);
         assert!(!channel.confirm_mode.load(Ordering::SeqCst));
+        assert!(!channel.tx_mode.load(Ordering::SeqCst));
         assert_eq!(channel.next_publish_seqno.load(Ordering::SeqCst), 1);
         assert_eq!(channel.prefetch_count.load(Ordering::SeqCst), 0);
         assert!(channel.flow_active.load(Ordering::SeqCst));
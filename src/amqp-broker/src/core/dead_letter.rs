@@ -0,0 +1,210 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use common_base::tools::{now_millis, now_second};
+use metadata_struct::adapter::adapter_read_config::AdapterReadConfig;
+use storage_adapter::driver::StorageDriverManager;
+use tracing::{debug, warn};
+
+use crate::amqp::publish::{enqueue, QueueMessage};
+use crate::amqp::route;
+use crate::core::cache::AmqpCacheManager;
+use crate::core::queue_args::{
+    dead_letter_would_cycle, logical_queue_name, record_death, store_names, DeathReason,
+    QueueArguments,
+};
+use crate::storage::offset::OffsetStorage;
+
+/// How long a queue's cached ready count is trusted before it is read from
+/// storage again.
+const QUEUE_LENGTH_CACHE_TTL_MS: u64 = 1_000;
+
+/// The enforced arguments of a declared queue; a queue that was only ever
+/// published to (never declared) has none.
+pub(crate) fn queue_arguments(
+    cache: &AmqpCacheManager,
+    tenant: &str,
+    queue: &str,
+) -> QueueArguments {
+    cache
+        .get_queue(tenant, queue)
+        .map(|q| QueueArguments::from_arguments(&q.arguments))
+        .unwrap_or_default()
+}
+
+/// Re-publishes a message leaving `queue` for `reason` to the queue's
+/// `x-dead-letter-exchange`, under `x-dead-letter-routing-key` (else its
+/// original routing key), with the death added to its `x-death` header. The
+/// caller still removes the original. Without a dead-letter exchange the
+/// message is simply dropped, as in RabbitMQ.
+pub(crate) async fn dead_letter(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<AmqpCacheManager>,
+    tenant: &str,
+    queue: &str,
+    mut message: QueueMessage,
+    reason: DeathReason,
+) {
+    let args = queue_arguments(cache, tenant, queue);
+    let Some(exchange) = args.dead_letter_exchange else {
+        debug!(
+            "AMQP queue {} dropped a message ({}): no dead-letter exchange",
+            queue,
+            reason.as_str()
+        );
+        return;
+    };
+
+    let properties = &mut message.properties;
+    let routing_key = args
+        .dead_letter_routing_key
+        .unwrap_or_else(|| properties.routing_key.clone());
+    record_death(properties, queue, reason, now_second());
+    // The per-message TTL is stripped so the message doesn't expire again
+    // the moment it lands in the dead-letter queue.
+    properties.expiration = None;
+    properties.expire_at_ms = 0;
+    properties.redelivered = false;
+    properties.exchange = exchange.clone();
+    properties.routing_key = routing_key.clone();
+
+    let headers: HashMap<String, String> = properties.headers.iter().cloned().collect();
+    let targets = route::resolve_queues(cache, tenant, &exchange, &routing_key, &headers);
+    if targets.is_empty() {
+        debug!(
+            "AMQP dead letter from {} unroutable (exchange={}, routing_key={}), dropped",
+            queue, exchange, routing_key
+        );
+    }
+    for target in targets {
+        if dead_letter_would_cycle(&message.properties, &target) {
+            warn!(
+                "AMQP dead letter from {} to {} would cycle, dropped",
+                queue, target
+            );
+            continue;
+        }
+        // A full target simply turns the dead letter away: dead letters are
+        // never dead-lettered again on overflow.
+        let outcome = enqueue(sdm, cache, tenant, &target, vec![message.clone()]).await;
+        if !outcome.written || !outcome.rejected.is_empty() {
+            warn!(
+                "AMQP dead letter from {} was not accepted by {}",
+                queue, target
+            );
+        }
+    }
+}
+
+/// Dead-letters the message at `offset` of a queue's storage topic `store`
+/// (the queue itself or one of its priority stores).
+pub(crate) async fn dead_letter_stored(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<AmqpCacheManager>,
+    tenant: &str,
+    store: &str,
+    offset: u64,
+    reason: DeathReason,
+) -> Result<(), CommonError> {
+    let Some(shard_name) = sdm
+        .broker_cache
+        .get_topic_by_name(tenant, store)
+        .and_then(|topic| topic.storage_name_list.get(&0).cloned())
+    else {
+        return Ok(());
+    };
+    let mut offsets = HashMap::new();
+    offsets.insert(shard_name, offset);
+    let records = sdm
+        .read_by_offset(tenant, store, &offsets, &AdapterReadConfig::new())
+        .await?;
+    // read_by_offset returns from `offset` onward; anything else means the
+    // message is already gone.
+    let Some(record) = records
+        .into_iter()
+        .next()
+        .filter(|r| r.metadata.offset == offset)
+    else {
+        return Ok(());
+    };
+    dead_letter(
+        sdm,
+        cache,
+        tenant,
+        logical_queue_name(store),
+        QueueMessage::from_record(&record),
+        reason,
+    )
+    .await;
+    Ok(())
+}
+
+/// Messages waiting in `queue` across its priority stores: everything past
+/// the committed claim cursor. Unacked deliveries don't count, matching
+/// RabbitMQ's `x-max-length`.
+pub(crate) async fn ready_count(
+    sdm: &Arc<StorageDriverManager>,
+    tenant: &str,
+    queue: &str,
+    args: &QueueArguments,
+) -> Result<u64, CommonError> {
+    let offset_storage = OffsetStorage::new(sdm.engine_storage_handler.client_pool.clone());
+    let mut ready = 0;
+    for store in store_names(queue, args.max_priority) {
+        let Some(shard_name) = sdm
+            .broker_cache
+            .get_topic_by_name(tenant, &store)
+            .and_then(|topic| topic.storage_name_list.get(&0).cloned())
+        else {
+            continue;
+        };
+        let resources = sdm.list_storage_resource(tenant, &store).await?;
+        let Some(detail) = resources.get(&0) else {
+            continue;
+        };
+        let committed = offset_storage
+            .read_committed_offset(tenant, &store, &shard_name)
+            .await?;
+        ready += detail
+            .offset
+            .high_watermark
+            .saturating_sub(committed.max(detail.offset.start_offset));
+    }
+    Ok(ready)
+}
+
+/// `ready_count`, read from storage at most once per
+/// `QUEUE_LENGTH_CACHE_TTL_MS`. In between, publishes through this node add
+/// to the cached count (`AmqpCacheManager::add_queue_length`), so a busy
+/// `x-max-length` queue doesn't cost a storage read per publish.
+pub(crate) async fn cached_ready_count(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &AmqpCacheManager,
+    tenant: &str,
+    queue: &str,
+    args: &QueueArguments,
+) -> Result<u64, CommonError> {
+    let now_ms = now_millis() as u64;
+    if let Some(ready) = cache.cached_queue_length(tenant, queue, QUEUE_LENGTH_CACHE_TTL_MS, now_ms)
+    {
+        return Ok(ready);
+    }
+    let ready = ready_count(sdm, tenant, queue, args).await?;
+    cache.set_queue_length(tenant, queue, ready, now_ms);
+    Ok(ready)
+}
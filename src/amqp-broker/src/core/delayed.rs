@@ -0,0 +1,199 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `x-delayed-message` exchange. A publish carrying a positive `x-delay`
+//! header (milliseconds) is handed to the shared `DelayMessageManager`
+//! instead of being routed; when it falls due, `AmqpDelayedRouter` routes it
+//! through the exchange's bindings as they stand at that moment. Scheduled
+//! messages are persisted by the delay-message crate, so they survive a
+//! broker restart.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use common_base::tools::now_second;
+use delay_message::manager::DelayMessageManager;
+use delay_message::router::DelayMessageRouter;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::storage::record::{StorageRecord, StorageRecordProtocolData};
+use storage_adapter::driver::StorageDriverManager;
+use tracing::{debug, warn};
+
+use crate::amqp::publish::{dead_letter_rejected, enqueue, QueueMessage};
+use crate::amqp::route;
+use crate::core::cache::AmqpCacheManager;
+
+pub const DELAYED_EXCHANGE_TYPE: &str = "x-delayed-message";
+pub const ARG_DELAYED_TYPE: &str = "x-delayed-type";
+pub const HEADER_DELAY: &str = "x-delay";
+
+/// Delay-message targets under this prefix are AMQP exchanges, routed by
+/// `AmqpDelayedRouter`.
+pub const DELAYED_TARGET_PREFIX: &str = "$amqp-delayed/";
+
+/// The publish's `x-delay` in milliseconds, if it asks to be delayed at
+/// all; zero, negative or unparsable values route immediately.
+pub(crate) fn delay_ms(headers: &HashMap<String, String>) -> Option<u64> {
+    headers
+        .get(HEADER_DELAY)
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|ms| *ms > 0)
+        .map(|ms| ms as u64)
+}
+
+fn delayed_target(exchange: &str) -> String {
+    format!("{DELAYED_TARGET_PREFIX}{exchange}")
+}
+
+/// Schedules `message` to be routed through `exchange` after `delay_ms`,
+/// returning the id `DelayMessageManager::cancel` takes. The delay queue
+/// works in whole seconds, so delays are rounded up.
+pub(crate) async fn schedule_delayed(
+    delay_message_manager: &Arc<DelayMessageManager>,
+    tenant: &str,
+    exchange: &str,
+    delay_ms: u64,
+    message: QueueMessage,
+) -> Result<String, CommonError> {
+    let target = delayed_target(exchange);
+    let target_timestamp = now_second() + delay_ms.div_ceil(1000);
+    let record = AdapterWriteRecord::new(target.clone(), message.body).with_protocol_data(Some(
+        StorageRecordProtocolData {
+            amqp: Some(message.properties),
+            ..Default::default()
+        },
+    ));
+    let id = delay_message_manager
+        .send(tenant, &target, target_timestamp, record)
+        .await?;
+    debug!(
+        "AMQP delayed publish scheduled: exchange={}, delay_ms={}, id={}",
+        exchange, delay_ms, id
+    );
+    Ok(id)
+}
+
+pub struct AmqpDelayedRouter {
+    storage_driver_manager: Arc<StorageDriverManager>,
+    amqp_cache: Arc<AmqpCacheManager>,
+}
+
+impl AmqpDelayedRouter {
+    pub fn new(
+        storage_driver_manager: Arc<StorageDriverManager>,
+        amqp_cache: Arc<AmqpCacheManager>,
+    ) -> Self {
+        AmqpDelayedRouter {
+            storage_driver_manager,
+            amqp_cache,
+        }
+    }
+}
+
+#[async_trait]
+impl DelayMessageRouter for AmqpDelayedRouter {
+    async fn deliver(
+        &self,
+        tenant: &str,
+        target: &str,
+        record: &StorageRecord,
+    ) -> Result<(), CommonError> {
+        let exchange = target.strip_prefix(DELAYED_TARGET_PREFIX).unwrap_or(target);
+        if self.amqp_cache.get_exchange(tenant, exchange).is_none() {
+            warn!(
+                "AMQP delayed message dropped: exchange {} no longer exists",
+                exchange
+            );
+            return Ok(());
+        }
+
+        let message = QueueMessage::from_record(record);
+        let headers: HashMap<String, String> = message.properties.headers.iter().cloned().collect();
+        let queues = route::resolve_queues(
+            &self.amqp_cache,
+            tenant,
+            exchange,
+            &message.properties.routing_key,
+            &headers,
+        );
+        if queues.is_empty() {
+            debug!(
+                "AMQP delayed message unroutable (exchange={}, routing_key={}), dropped",
+                exchange, message.properties.routing_key
+            );
+            return Ok(());
+        }
+
+        let mut failed = Vec::new();
+        for queue in queues {
+            let outcome = enqueue(
+                &self.storage_driver_manager,
+                &self.amqp_cache,
+                tenant,
+                &queue,
+                vec![message.clone()],
+            )
+            .await;
+            if !outcome.written {
+                failed.push(queue.clone());
+            }
+            dead_letter_rejected(
+                &self.storage_driver_manager,
+                &self.amqp_cache,
+                tenant,
+                &queue,
+                outcome,
+            )
+            .await;
+        }
+        if !failed.is_empty() {
+            return Err(CommonError::CommonError(format!(
+                "AMQP delayed message from {} could not be written to {}",
+                exchange,
+                failed.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(delay: &str) -> HashMap<String, String> {
+        HashMap::from([(HEADER_DELAY.to_string(), delay.to_string())])
+    }
+
+    #[test]
+    fn delay_ms_only_for_positive_delays() {
+        assert_eq!(delay_ms(&headers("1500")), Some(1500));
+        assert_eq!(delay_ms(&headers("0")), None);
+        assert_eq!(delay_ms(&headers("-100")), None);
+        assert_eq!(delay_ms(&headers("soon")), None);
+        assert_eq!(delay_ms(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn delayed_target_is_under_router_prefix() {
+        let target = delayed_target("orders.delayed");
+        assert!(target.starts_with(DELAYED_TARGET_PREFIX));
+        assert_eq!(
+            target.strip_prefix(DELAYED_TARGET_PREFIX),
+            Some("orders.delayed")
+        );
+    }
+}
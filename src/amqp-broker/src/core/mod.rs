@@ -15,7 +15,10 @@
 pub mod cache;
 pub mod connection;
 pub mod consume_group;
+pub mod dead_letter;
+pub mod delayed;
 pub mod frame;
 pub mod keep_alive;
+pub mod queue_args;
 pub mod recovery;
 pub mod unacked_index;
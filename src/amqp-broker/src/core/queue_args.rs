@@ -0,0 +1,339 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use metadata_struct::storage::record::{StorageRecordAmqpDeath, StorageRecordProtocolDataAmqp};
+
+pub(crate) const ARG_MESSAGE_TTL: &str = "x-message-ttl";
+pub(crate) const ARG_EXPIRES: &str = "x-expires";
+pub(crate) const ARG_MAX_LENGTH: &str = "x-max-length";
+pub(crate) const ARG_OVERFLOW: &str = "x-overflow";
+pub(crate) const ARG_DEAD_LETTER_EXCHANGE: &str = "x-dead-letter-exchange";
+pub(crate) const ARG_DEAD_LETTER_ROUTING_KEY: &str = "x-dead-letter-routing-key";
+pub(crate) const ARG_MAX_PRIORITY: &str = "x-max-priority";
+pub(crate) const ARG_ALTERNATE_EXCHANGE: &str = "alternate-exchange";
+
+// A priority queue keeps each non-zero priority level in its own storage
+// topic, "{queue}$priority.{level}"; level 0 stays in the queue's own topic.
+const PRIORITY_STORE_SEPARATOR: &str = "$priority.";
+
+/// What `x-overflow` does once `x-max-length` ready messages are queued.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum Overflow {
+    /// Accept the publish and drop (or dead-letter) the oldest message.
+    #[default]
+    DropHead,
+    /// Refuse the publish; a Confirm-mode publisher gets a Basic.Nack.
+    RejectPublish,
+    /// As RejectPublish, and dead-letter the refused message.
+    RejectPublishDlx,
+}
+
+/// The declare arguments RobustMQ enforces, parsed out of
+/// `AmqpQueue.arguments` (already flattened to strings by
+/// `route::field_table_to_map`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct QueueArguments {
+    pub(crate) message_ttl_ms: Option<u64>,
+    pub(crate) expires_ms: Option<u64>,
+    pub(crate) max_length: Option<u64>,
+    pub(crate) overflow: Overflow,
+    pub(crate) dead_letter_exchange: Option<String>,
+    pub(crate) dead_letter_routing_key: Option<String>,
+    /// 0 when the queue is not a priority queue.
+    pub(crate) max_priority: u8,
+}
+
+impl QueueArguments {
+    /// Strict parse for Queue.Declare: the error names the offending
+    /// argument, reported as 406 PRECONDITION_FAILED.
+    pub(crate) fn parse(arguments: &HashMap<String, String>) -> Result<Self, String> {
+        let number = |name: &str| -> Result<Option<u64>, String> {
+            arguments
+                .get(name)
+                .map(|v| {
+                    v.parse::<u64>()
+                        .map_err(|_| format!("invalid arg '{}' for queue: {}", name, v))
+                })
+                .transpose()
+        };
+
+        let expires_ms = number(ARG_EXPIRES)?;
+        if expires_ms == Some(0) {
+            return Err(format!("invalid arg '{}' for queue: 0", ARG_EXPIRES));
+        }
+        let overflow = match arguments.get(ARG_OVERFLOW).map(String::as_str) {
+            None | Some("drop-head") => Overflow::DropHead,
+            Some("reject-publish") => Overflow::RejectPublish,
+            Some("reject-publish-dlx") => Overflow::RejectPublishDlx,
+            Some(other) => {
+                return Err(format!(
+                    "invalid arg '{}' for queue: {}",
+                    ARG_OVERFLOW, other
+                ))
+            }
+        };
+        let max_priority = match number(ARG_MAX_PRIORITY)? {
+            None => 0,
+            Some(n) => u8::try_from(n)
+                .map_err(|_| format!("invalid arg '{}' for queue: {}", ARG_MAX_PRIORITY, n))?,
+        };
+        let dead_letter_routing_key = arguments.get(ARG_DEAD_LETTER_ROUTING_KEY).cloned();
+        let dead_letter_exchange = arguments.get(ARG_DEAD_LETTER_EXCHANGE).cloned();
+        if dead_letter_routing_key.is_some() && dead_letter_exchange.is_none() {
+            return Err(format!(
+                "invalid arg '{}' for queue: requires '{}'",
+                ARG_DEAD_LETTER_ROUTING_KEY, ARG_DEAD_LETTER_EXCHANGE
+            ));
+        }
+
+        Ok(QueueArguments {
+            message_ttl_ms: number(ARG_MESSAGE_TTL)?,
+            expires_ms,
+            max_length: number(ARG_MAX_LENGTH)?,
+            overflow,
+            dead_letter_exchange,
+            dead_letter_routing_key,
+            max_priority,
+        })
+    }
+
+    /// For queues that are already declared: anything unparseable was
+    /// rejected at declare time, so falling back to "no arguments" only
+    /// affects queues declared before these were enforced.
+    pub(crate) fn from_arguments(arguments: &HashMap<String, String>) -> Self {
+        Self::parse(arguments).unwrap_or_default()
+    }
+
+    /// Whether the queue's head needs periodic attention (expiry, length
+    /// trimming or auto-delete) even while nothing consumes from it.
+    pub(crate) fn needs_sweep(&self) -> bool {
+        self.message_ttl_ms.is_some() || self.max_length.is_some() || self.expires_ms.is_some()
+    }
+}
+
+/// The storage topic holding `queue`'s messages of `priority` (clamped to
+/// `max_priority`).
+pub(crate) fn priority_store_name(queue: &str, max_priority: u8, priority: Option<u8>) -> String {
+    match priority.unwrap_or(0).min(max_priority) {
+        0 => queue.to_string(),
+        level => format!("{}{}{}", queue, PRIORITY_STORE_SEPARATOR, level),
+    }
+}
+
+/// Every storage topic a queue may use, highest priority first — the order
+/// messages are claimed in.
+pub(crate) fn store_names(queue: &str, max_priority: u8) -> Vec<String> {
+    (0..=max_priority)
+        .rev()
+        .map(|level| priority_store_name(queue, max_priority, Some(level)))
+        .collect()
+}
+
+/// The queue a storage topic belongs to (the inverse of `priority_store_name`).
+pub(crate) fn logical_queue_name(store: &str) -> &str {
+    match store.rfind(PRIORITY_STORE_SEPARATOR) {
+        Some(idx) => &store[..idx],
+        None => store,
+    }
+}
+
+/// Unix ms at which a message published now expires, from the lower of its
+/// own `expiration` property (ms, as a string) and the queue's
+/// `x-message-ttl`; 0 if neither applies.
+pub(crate) fn expire_at_ms(
+    now_ms: u64,
+    expiration: Option<&str>,
+    queue_ttl_ms: Option<u64>,
+) -> u64 {
+    let message_ttl = expiration.and_then(|v| v.parse::<u64>().ok());
+    match (message_ttl, queue_ttl_ms) {
+        (Some(a), Some(b)) => now_ms.saturating_add(a.min(b)),
+        (Some(ttl), None) | (None, Some(ttl)) => now_ms.saturating_add(ttl),
+        (None, None) => 0,
+    }
+}
+
+pub(crate) fn is_expired(properties: &StorageRecordProtocolDataAmqp, now_ms: u64) -> bool {
+    properties.expire_at_ms > 0 && properties.expire_at_ms < now_ms
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum DeathReason {
+    Rejected,
+    Expired,
+    MaxLen,
+}
+
+impl DeathReason {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            DeathReason::Rejected => "rejected",
+            DeathReason::Expired => "expired",
+            DeathReason::MaxLen => "maxlen",
+        }
+    }
+}
+
+/// Adds a death out of `queue` to the message's `x-death` history. As in
+/// RabbitMQ, a repeat (queue, reason) pair bumps that entry's count and moves
+/// it to the front rather than adding a new one.
+pub(crate) fn record_death(
+    properties: &mut StorageRecordProtocolDataAmqp,
+    queue: &str,
+    reason: DeathReason,
+    now_secs: u64,
+) {
+    let reason = reason.as_str();
+    let previous = properties
+        .x_death
+        .iter()
+        .position(|d| d.queue == queue && d.reason == reason)
+        .map(|idx| properties.x_death.remove(idx));
+    let count = previous.map(|d| d.count).unwrap_or(0) + 1;
+    properties.x_death.insert(
+        0,
+        StorageRecordAmqpDeath {
+            queue: queue.to_string(),
+            reason: reason.to_string(),
+            exchange: properties.exchange.clone(),
+            routing_keys: vec![properties.routing_key.clone()],
+            count,
+            time: now_secs,
+        },
+    );
+}
+
+/// A dead-letter route back into a queue the message already died in is a
+/// cycle, and is dropped — unless a consumer rejected it somewhere along the
+/// way, which is how retry topologies deliberately loop.
+pub(crate) fn dead_letter_would_cycle(
+    properties: &StorageRecordProtocolDataAmqp,
+    target_queue: &str,
+) -> bool {
+    let revisits = properties.x_death.iter().any(|d| d.queue == target_queue);
+    let rejected = properties
+        .x_death
+        .iter()
+        .any(|d| d.reason == DeathReason::Rejected.as_str());
+    revisits && !rejected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_reads_every_enforced_argument() {
+        let parsed = QueueArguments::parse(&args(&[
+            (ARG_MESSAGE_TTL, "5000"),
+            (ARG_EXPIRES, "60000"),
+            (ARG_MAX_LENGTH, "10"),
+            (ARG_OVERFLOW, "reject-publish-dlx"),
+            (ARG_DEAD_LETTER_EXCHANGE, "dlx"),
+            (ARG_DEAD_LETTER_ROUTING_KEY, "retry"),
+            (ARG_MAX_PRIORITY, "5"),
+        ]))
+        .unwrap();
+        assert_eq!(parsed.message_ttl_ms, Some(5000));
+        assert_eq!(parsed.expires_ms, Some(60000));
+        assert_eq!(parsed.max_length, Some(10));
+        assert_eq!(parsed.overflow, Overflow::RejectPublishDlx);
+        assert_eq!(parsed.dead_letter_exchange.as_deref(), Some("dlx"));
+        assert_eq!(parsed.dead_letter_routing_key.as_deref(), Some("retry"));
+        assert_eq!(parsed.max_priority, 5);
+        assert!(parsed.needs_sweep());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_values() {
+        assert!(QueueArguments::parse(&args(&[(ARG_MESSAGE_TTL, "-1")])).is_err());
+        assert!(QueueArguments::parse(&args(&[(ARG_EXPIRES, "0")])).is_err());
+        assert!(QueueArguments::parse(&args(&[(ARG_OVERFLOW, "drop-tail")])).is_err());
+        assert!(QueueArguments::parse(&args(&[(ARG_MAX_PRIORITY, "256")])).is_err());
+        assert!(QueueArguments::parse(&args(&[(ARG_DEAD_LETTER_ROUTING_KEY, "k")])).is_err());
+        assert_eq!(
+            QueueArguments::from_arguments(&args(&[(ARG_MAX_LENGTH, "lots")])),
+            QueueArguments::default()
+        );
+    }
+
+    #[test]
+    fn priority_stores_round_trip_and_clamp() {
+        assert_eq!(priority_store_name("q", 0, Some(9)), "q");
+        assert_eq!(priority_store_name("q", 5, None), "q");
+        assert_eq!(priority_store_name("q", 5, Some(9)), "q$priority.5");
+        assert_eq!(logical_queue_name("q$priority.5"), "q");
+        assert_eq!(logical_queue_name("q"), "q");
+        assert_eq!(
+            store_names("q", 2),
+            vec!["q$priority.2", "q$priority.1", "q"]
+        );
+    }
+
+    #[test]
+    fn expiry_takes_the_lower_ttl() {
+        assert_eq!(expire_at_ms(1000, None, None), 0);
+        assert_eq!(expire_at_ms(1000, Some("500"), None), 1500);
+        assert_eq!(expire_at_ms(1000, Some("500"), Some(200)), 1200);
+        assert_eq!(expire_at_ms(1000, Some("soon"), Some(200)), 1200);
+
+        let properties = StorageRecordProtocolDataAmqp {
+            expire_at_ms: 1200,
+            ..Default::default()
+        };
+        assert!(!is_expired(&properties, 1200));
+        assert!(is_expired(&properties, 1201));
+        assert!(!is_expired(&StorageRecordProtocolDataAmqp::default(), 1201));
+    }
+
+    #[test]
+    fn record_death_counts_repeats_most_recent_first() {
+        let mut properties = StorageRecordProtocolDataAmqp {
+            exchange: "orders".to_string(),
+            routing_key: "created".to_string(),
+            ..Default::default()
+        };
+        record_death(&mut properties, "work", DeathReason::Rejected, 10);
+        record_death(&mut properties, "retry", DeathReason::Expired, 20);
+        record_death(&mut properties, "work", DeathReason::Rejected, 30);
+
+        assert_eq!(properties.x_death.len(), 2);
+        assert_eq!(properties.x_death[0].queue, "work");
+        assert_eq!(properties.x_death[0].count, 2);
+        assert_eq!(properties.x_death[0].time, 30);
+        assert_eq!(properties.x_death[0].routing_keys, vec!["created"]);
+        assert_eq!(properties.x_death[1].queue, "retry");
+        assert_eq!(properties.x_death[1].count, 1);
+    }
+
+    #[test]
+    fn dead_letter_cycles_are_dropped_unless_rejected() {
+        let mut properties = StorageRecordProtocolDataAmqp::default();
+        record_death(&mut properties, "q", DeathReason::Expired, 1);
+        assert!(dead_letter_would_cycle(&properties, "q"));
+        assert!(!dead_letter_would_cycle(&properties, "other"));
+
+        record_death(&mut properties, "work", DeathReason::Rejected, 2);
+        assert!(!dead_letter_would_cycle(&properties, "q"));
+    }
+}
@@ -18,19 +18,29 @@
 use broker_core::cluster::ClusterStorage;
 use common_base::error::common::CommonError;
 use common_base::error::ResultCommonError;
-use common_base::tools::{loop_select_ticket, now_second};
+use common_base::tools::{loop_select_ticket, now_millis, now_second};
 use common_config::broker::broker_config;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::adapter::adapter_read_config::AdapterReadConfig;
 use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::amqp::queue::AmqpQueue;
 use metadata_struct::meta::status::MetaStatus;
+use metadata_struct::storage::record::StorageRecord;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::broadcast;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
+use crate::amqp::publish::QueueMessage;
+use crate::amqp::queue::delete_queue;
+use crate::core::cache::AmqpCacheManager;
+use crate::core::dead_letter::{dead_letter, ready_count};
+use crate::core::queue_args::{is_expired, store_names, DeathReason, Overflow, QueueArguments};
 use crate::core::unacked_index;
+use crate::push::{self, AmqpPushManager};
+use crate::storage::offset::OffsetStorage;
 
 const SCAN_INTERVAL_MS: u64 = 60_000;
+const SWEEP_INTERVAL_MS: u64 = 1_000;
 const STALE_THRESHOLD_SECS: u64 = 300;
 const METADATA_SHARD_NAME: &str = "metadata_0";
 
@@ -81,26 +91,277 @@ pub(crate) async fn requeue_message(
     unacked_index::delete_entry(sdm, index_offset).await
 }
 
+/// Requeues stale unacked deliveries cluster-wide (on the meta leader) and,
+/// on every node, enforces the declare arguments of the queues it leads:
+/// expiring messages at the head, trimming over-length queues and deleting
+/// unused `x-expires` queues.
 #[derive(Clone)]
 pub struct AmqpRecoveryScanner {
     client_pool: Arc<ClientPool>,
     storage_driver_manager: Arc<StorageDriverManager>,
+    amqp_cache: Arc<AmqpCacheManager>,
+    push_manager: Arc<AmqpPushManager>,
 }
 
 impl AmqpRecoveryScanner {
     pub fn new(
         client_pool: Arc<ClientPool>,
         storage_driver_manager: Arc<StorageDriverManager>,
+        amqp_cache: Arc<AmqpCacheManager>,
+        push_manager: Arc<AmqpPushManager>,
     ) -> Self {
         AmqpRecoveryScanner {
             client_pool,
             storage_driver_manager,
+            amqp_cache,
+            push_manager,
         }
     }
 
     pub async fn start(&self, stop_send: &broadcast::Sender<bool>) {
-        let ac_fn = async || -> ResultCommonError { self.tick().await };
-        loop_select_ticket(ac_fn, SCAN_INTERVAL_MS, stop_send).await;
+        let scan_fn = async || -> ResultCommonError { self.tick().await };
+        let sweep_fn = async || -> ResultCommonError { self.sweep_queues().await };
+        tokio::join!(
+            loop_select_ticket(scan_fn, SCAN_INTERVAL_MS, stop_send),
+            loop_select_ticket(sweep_fn, SWEEP_INTERVAL_MS, stop_send),
+            self.trim_on_request(stop_send),
+        );
+    }
+
+    async fn sweep_queues(&self) -> ResultCommonError {
+        for queue in self.amqp_cache.list_queues() {
+            self.sweep_if_leader(&queue).await;
+        }
+        Ok(())
+    }
+
+    /// Sweeps the `drop-head` queues a publish pushed over `x-max-length`
+    /// right away instead of waiting for the next sweep. Publishes that
+    /// arrive on another node than the queue's leader are still trimmed by
+    /// the leader's periodic sweep.
+    async fn trim_on_request(&self, stop_send: &broadcast::Sender<bool>) {
+        let mut stop_recv = stop_send.subscribe();
+        loop {
+            tokio::select! {
+                val = stop_recv.recv() => {
+                    if let Ok(true) = val {
+                        break;
+                    }
+                }
+                requests = self.amqp_cache.next_trim_requests() => {
+                    for (tenant, queue_name) in requests {
+                        if let Some(queue) = self.amqp_cache.get_queue(&tenant, &queue_name) {
+                            self.sweep_if_leader(&queue).await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn sweep_if_leader(&self, queue: &AmqpQueue) {
+        let args = QueueArguments::from_arguments(&queue.arguments);
+        if !args.needs_sweep() {
+            return;
+        }
+        let leader = match push::resolve_queue_leader(
+            &self.client_pool,
+            &self.storage_driver_manager.broker_cache,
+            &queue.tenant,
+            &queue.queue_name,
+        )
+        .await
+        {
+            Ok(leader) => leader,
+            Err(e) => {
+                warn!(
+                    "AMQP queue sweep: failed to resolve leader for {}: {}",
+                    queue.queue_name, e
+                );
+                return;
+            }
+        };
+        if !push::is_self(leader) {
+            return;
+        }
+        if let Err(e) = self.sweep_queue(queue, &args).await {
+            error!(
+                "AMQP queue sweep: failed to enforce arguments of {}: {}",
+                queue.queue_name, e
+            );
+        }
+    }
+
+    async fn sweep_queue(&self, queue: &AmqpQueue, args: &QueueArguments) -> ResultCommonError {
+        let tenant = &queue.tenant;
+        let name = &queue.queue_name;
+        if let Some(expires_ms) = args.expires_ms {
+            if self.expire_if_unused(queue, expires_ms).await? {
+                return Ok(());
+            }
+        }
+
+        let offset_storage = OffsetStorage::new(self.client_pool.clone());
+        let stores: Vec<(String, String)> = store_names(name, args.max_priority)
+            .into_iter()
+            .filter_map(|store| {
+                let shard_name = self
+                    .storage_driver_manager
+                    .broker_cache
+                    .get_topic_by_name(tenant, &store)
+                    .and_then(|topic| topic.storage_name_list.get(&0).cloned())?;
+                Some((store, shard_name))
+            })
+            .collect();
+
+        if args.message_ttl_ms.is_some() {
+            // Only the head of each store is checked, as in RabbitMQ; a
+            // per-message expiration on a queue without `x-message-ttl` is
+            // enforced when the message reaches the head for delivery.
+            for (store, shard_name) in &stores {
+                while let Some((record, seen)) = offset_storage
+                    .peek_next_record(
+                        &self.push_manager,
+                        &self.storage_driver_manager,
+                        tenant,
+                        store,
+                        shard_name,
+                    )
+                    .await?
+                {
+                    let message = QueueMessage::from_record(&record);
+                    if !is_expired(&message.properties, now_millis() as u64) {
+                        break;
+                    }
+                    self.remove_head(
+                        &offset_storage,
+                        tenant,
+                        name,
+                        store,
+                        shard_name,
+                        seen,
+                        &record,
+                        DeathReason::Expired,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        if let (Some(max_length), Overflow::DropHead) = (args.max_length, args.overflow) {
+            let read_at = now_millis() as u64;
+            let ready = ready_count(&self.storage_driver_manager, tenant, name, args).await?;
+            let mut excess = ready.saturating_sub(max_length);
+            let mut trimmed = 0;
+            // The oldest messages go first, lowest priority before highest.
+            for (store, shard_name) in stores.iter().rev() {
+                while excess > 0 {
+                    let Some((record, seen)) = offset_storage
+                        .peek_next_record(
+                            &self.push_manager,
+                            &self.storage_driver_manager,
+                            tenant,
+                            store,
+                            shard_name,
+                        )
+                        .await?
+                    else {
+                        break;
+                    };
+                    if self
+                        .remove_head(
+                            &offset_storage,
+                            tenant,
+                            name,
+                            store,
+                            shard_name,
+                            seen,
+                            &record,
+                            DeathReason::MaxLen,
+                        )
+                        .await?
+                    {
+                        excess -= 1;
+                        trimmed += 1;
+                    }
+                }
+            }
+            self.amqp_cache
+                .set_queue_length(tenant, name, ready - trimmed, read_at);
+        }
+        Ok(())
+    }
+
+    /// Claims the head message `record` (read at cursor `seen`) away from
+    /// consumers, dead-letters it and deletes it. False if a consumer
+    /// claimed it first.
+    #[allow(clippy::too_many_arguments)]
+    async fn remove_head(
+        &self,
+        offset_storage: &OffsetStorage,
+        tenant: &str,
+        queue: &str,
+        store: &str,
+        shard_name: &str,
+        seen: u64,
+        record: &StorageRecord,
+        reason: DeathReason,
+    ) -> Result<bool, CommonError> {
+        let offset = record.metadata.offset;
+        if !offset_storage
+            .advance_cursor(&self.push_manager, tenant, store, shard_name, seen, offset)
+            .await?
+        {
+            return Ok(false);
+        }
+        dead_letter(
+            &self.storage_driver_manager,
+            &self.amqp_cache,
+            tenant,
+            queue,
+            QueueMessage::from_record(record),
+            reason,
+        )
+        .await;
+        self.storage_driver_manager
+            .delete_by_offsets(tenant, store, &[offset])
+            .await?;
+        Ok(true)
+    }
+
+    /// Deletes `queue` once it has gone `expires_ms` without consumers or
+    /// a `Basic.Get`.
+    async fn expire_if_unused(
+        &self,
+        queue: &AmqpQueue,
+        expires_ms: u64,
+    ) -> Result<bool, CommonError> {
+        let tenant = &queue.tenant;
+        let name = &queue.queue_name;
+        let now = now_millis() as u64;
+        let in_use = !self
+            .storage_driver_manager
+            .broker_cache
+            .get_share_group_members(tenant, name)
+            .is_empty();
+        if in_use {
+            self.push_manager.note_use(tenant, name);
+            return Ok(false);
+        }
+        let last_use = self
+            .push_manager
+            .last_use_or(tenant, name, now)
+            .max(queue.create_time * 1000);
+        if now.saturating_sub(last_use) < expires_ms {
+            return Ok(false);
+        }
+        info!(
+            "AMQP queue {} unused for {}ms, deleting (x-expires)",
+            name, expires_ms
+        );
+        delete_queue(&self.storage_driver_manager, &self.amqp_cache, tenant, name).await?;
+        self.push_manager.forget_queue(tenant, name);
+        Ok(true)
     }
 
     async fn tick(&self) -> ResultCommonError {
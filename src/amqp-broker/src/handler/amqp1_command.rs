@@ -0,0 +1,186 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common_security::manager::SecurityManager;
+use delay_message::manager::DelayMessageManager;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::connection::NetworkConnection;
+use network_server::command::{ArcCommandAdapter, Command};
+use network_server::common::connection_manager::ConnectionManager;
+use network_server::common::packet::ResponsePackage;
+use protocol::amqp1::frame::{Amqp1Frame, Performative, SaslPerformative};
+use protocol::robust::{RobustMQPacket, RobustMQProtocol};
+use schema_register::schema::SchemaRegisterManager;
+use storage_adapter::driver::StorageDriverManager;
+use tracing::{debug, warn};
+
+use crate::amqp::basic::BasicCtx;
+use crate::amqp1::{connection, link, transfer, Amqp1Ctx, ERR_UNAUTHORIZED};
+use crate::core::cache::AmqpCacheManager;
+use crate::push::AmqpPushManager;
+
+pub struct Amqp1CommandParams {
+    pub storage_driver_manager: Arc<StorageDriverManager>,
+    pub amqp_cache: Arc<AmqpCacheManager>,
+    pub security_manager: Arc<SecurityManager>,
+    pub client_pool: Arc<ClientPool>,
+    pub push_manager: Arc<AmqpPushManager>,
+    pub delay_message_manager: Arc<DelayMessageManager>,
+    pub connection_manager: Arc<ConnectionManager>,
+    pub schema_manager: Arc<SchemaRegisterManager>,
+}
+
+pub fn create_amqp1_command_with_state(params: Amqp1CommandParams) -> ArcCommandAdapter {
+    Arc::new(Box::new(Amqp1HandlerCommand::new(params)))
+}
+
+/// Serves AMQP 1.0 connections, which share the AMQP listener and state
+/// with `AmqpHandlerCommand`.
+#[derive(Clone)]
+pub struct Amqp1HandlerCommand {
+    ctx: Amqp1Ctx,
+}
+
+impl Amqp1HandlerCommand {
+    pub fn new(params: Amqp1CommandParams) -> Self {
+        Amqp1HandlerCommand {
+            ctx: Amqp1Ctx {
+                basic: BasicCtx {
+                    storage_driver_manager: params.storage_driver_manager,
+                    amqp_cache: params.amqp_cache,
+                    client_pool: params.client_pool,
+                    push_manager: params.push_manager,
+                    delay_message_manager: params.delay_message_manager,
+                    schema_manager: params.schema_manager,
+                },
+                security_manager: params.security_manager,
+                connection_manager: params.connection_manager,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Command for Amqp1HandlerCommand {
+    async fn apply(
+        &self,
+        tcp_connection: &NetworkConnection,
+        _addr: &SocketAddr,
+        packet: &RobustMQPacket,
+    ) -> Option<ResponsePackage> {
+        match packet {
+            RobustMQPacket::AMQP1(frames) => {
+                let connection_id = tcp_connection.connection_id;
+                // As with 0-9-1, each request carries exactly one wire frame.
+                let Some(frame) = frames.first() else {
+                    warn!("Amqp1HandlerCommand received an empty AMQP 1.0 packet");
+                    return None;
+                };
+                let resp_frames = self.process_frame(frame, connection_id).await;
+                if resp_frames.is_empty() {
+                    debug!("AMQP 1.0 frame has no response: {}", frame);
+                    return None;
+                }
+                Some(ResponsePackage {
+                    connection_id,
+                    packet: RobustMQPacket::AMQP1(resp_frames),
+                })
+            }
+            _ => {
+                warn!("Amqp1HandlerCommand received non-AMQP 1.0 packet");
+                None
+            }
+        }
+    }
+}
+
+impl Amqp1HandlerCommand {
+    async fn process_frame(&self, frame: &Amqp1Frame, connection_id: u64) -> Vec<Amqp1Frame> {
+        let ctx = &self.ctx;
+        match frame {
+            Amqp1Frame::Header(header) => {
+                ctx.connection_manager
+                    .set_connect_protocol(connection_id, RobustMQProtocol::AMQP1);
+                connection::process_header(*header, connection_id, ctx)
+            }
+            Amqp1Frame::Heartbeat => Vec::new(),
+            Amqp1Frame::Sasl(SaslPerformative::Init(init)) => {
+                connection::process_sasl_init(init, connection_id, ctx)
+            }
+            Amqp1Frame::Sasl(other) => {
+                warn!("AMQP 1.0 unexpected SASL frame from client: {:?}", other);
+                Vec::new()
+            }
+            Amqp1Frame::Amqp {
+                channel,
+                performative,
+                payload,
+            } => {
+                self.process_performative(*channel, performative, payload, connection_id)
+                    .await
+            }
+        }
+    }
+
+    async fn process_performative(
+        &self,
+        channel: u16,
+        performative: &Performative,
+        payload: &[u8],
+        connection_id: u64,
+    ) -> Vec<Amqp1Frame> {
+        let ctx = &self.ctx;
+        match performative {
+            Performative::Open(open) => return connection::process_open(open, connection_id, ctx),
+            Performative::Close(_) => return connection::process_close(connection_id, ctx).await,
+            _ => {}
+        }
+        if !connection::is_open(connection_id, ctx) {
+            warn!(
+                connection_id,
+                "AMQP 1.0 {} before the connection was opened",
+                performative.name()
+            );
+            return vec![connection::close_frame(
+                ERR_UNAUTHORIZED,
+                "connection is not open",
+            )];
+        }
+
+        match performative {
+            Performative::Begin(begin) => {
+                connection::process_begin(channel, begin, connection_id, ctx)
+            }
+            Performative::End(_) => connection::process_end(channel, connection_id, ctx).await,
+            Performative::Attach(attach) => {
+                link::process_attach(channel, attach, connection_id, ctx).await
+            }
+            Performative::Detach(detach) => {
+                link::process_detach(channel, detach, connection_id, ctx)
+            }
+            Performative::Flow(flow) => link::process_flow(channel, flow, connection_id, ctx).await,
+            Performative::Transfer(t) => {
+                transfer::process_transfer(channel, t, payload, connection_id, ctx).await
+            }
+            Performative::Disposition(disposition) => {
+                transfer::process_disposition(channel, disposition, connection_id, ctx).await
+            }
+            Performative::Open(_) | Performative::Close(_) => unreachable!("handled above"),
+        }
+    }
+}
@@ -20,12 +20,15 @@
 use amq_protocol::protocol::connection::AMQPMethod as ConnMethod;
 use amq_protocol::protocol::AMQPClass;
 use async_trait::async_trait;
+use common_security::login::x509::peer_cert_username;
 use common_security::manager::SecurityManager;
+use delay_message::manager::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::connection::NetworkConnection;
 use network_server::command::{ArcCommandAdapter, Command};
 use network_server::common::packet::ResponsePackage;
 use protocol::robust::RobustMQPacket;
+use schema_register::schema::SchemaRegisterManager;
 use std::net::SocketAddr;
 use storage_adapter::driver::StorageDriverManager;
 use tracing::{debug, warn};
@@ -36,12 +39,15 @@
 use crate::core::connection::AmqpConnection;
 use crate::push::AmqpPushManager;
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_command_with_state(
     storage_driver_manager: Arc<StorageDriverManager>,
     amqp_cache: Arc<AmqpCacheManager>,
     security_manager: Arc<SecurityManager>,
     client_pool: Arc<ClientPool>,
     push_manager: Arc<AmqpPushManager>,
+    delay_message_manager: Arc<DelayMessageManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
 ) -> ArcCommandAdapter {
     Arc::new(Box::new(AmqpHandlerCommand::new(
         storage_driver_manager,
@@ -49,6 +55,8 @@ pub fn create_command_with_state(
         security_manager,
         client_pool,
         push_manager,
+        delay_message_manager,
+        schema_manager,
     )))
 }
 
@@ -59,15 +67,20 @@ pub struct AmqpHandlerCommand {
     security_manager: Arc<SecurityManager>,
     client_pool: Arc<ClientPool>,
     push_manager: Arc<AmqpPushManager>,
+    delay_message_manager: Arc<DelayMessageManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
 }
 
 impl AmqpHandlerCommand {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         storage_driver_manager: Arc<StorageDriverManager>,
         amqp_cache: Arc<AmqpCacheManager>,
         security_manager: Arc<SecurityManager>,
         client_pool: Arc<ClientPool>,
         push_manager: Arc<AmqpPushManager>,
+        delay_message_manager: Arc<DelayMessageManager>,
+        schema_manager: Arc<SchemaRegisterManager>,
     ) -> Self {
         AmqpHandlerCommand {
             storage_driver_manager,
@@ -75,6 +88,8 @@ pub fn new(
             security_manager,
             client_pool,
             push_manager,
+            delay_message_manager,
+            schema_manager,
         }
     }
 
@@ -84,6 +99,8 @@ fn basic_ctx(&self) -> BasicCtx {
             amqp_cache: self.amqp_cache.clone(),
             client_pool: self.client_pool.clone(),
             push_manager: self.push_manager.clone(),
+            delay_message_manager: self.delay_message_manager.clone(),
+            schema_manager: self.schema_manager.clone(),
         }
     }
 }
@@ -105,7 +122,10 @@ async fn apply(
                     warn!("AmqpHandlerCommand received an empty AMQP packet");
                     return None;
                 };
-                let resp_frames = self.process_frame(frame, connection_id).await;
+                let cert_user = peer_cert_username(tcp_connection.peer_cert.as_ref());
+                let resp_frames = self
+                    .process_frame(frame, connection_id, cert_user.as_deref())
+                    .await;
                 resp_frames.map(|frames| ResponsePackage {
                     connection_id,
                     packet: RobustMQPacket::AMQP(frames),
@@ -120,15 +140,21 @@ async fn apply(
 }
 
 impl AmqpHandlerCommand {
-    async fn process_frame(&self, frame: &AMQPFrame, connection_id: u64) -> Option<Vec<AMQPFrame>> {
+    async fn process_frame(
+        &self,
+        frame: &AMQPFrame,
+        connection_id: u64,
+        cert_user: Option<&str>,
+    ) -> Option<Vec<AMQPFrame>> {
         let result = match frame {
             AMQPFrame::Method(channel_id, class) => {
-                self.process_method(*channel_id, class, connection_id).await
+                self.process_method(*channel_id, class, connection_id, cert_user)
+                    .await
             }
             AMQPFrame::ProtocolHeader(_) => {
                 self.amqp_cache
                     .set_connection(AmqpConnection::new(connection_id));
-                connection::process_protocol_header().map(|f| vec![f])
+                connection::process_protocol_header(cert_user.is_some()).map(|f| vec![f])
             }
             AMQPFrame::Heartbeat(channel_id) => {
                 connection::process_heartbeat(*channel_id).map(|f| vec![f])
@@ -164,6 +190,7 @@ async fn process_method(
         channel_id: u16,
         class: &AMQPClass,
         connection_id: u64,
+        cert_user: Option<&str>,
     ) -> Option<Vec<AMQPFrame>> {
         let result = match class {
             AMQPClass::Connection(method) => {
@@ -172,6 +199,7 @@ async fn process_method(
                     channel_id,
                     method,
                     connection_id,
+                    cert_user,
                     &self.amqp_cache,
                     &self.security_manager,
                 )
@@ -216,7 +244,9 @@ async fn process_method(
                 basic::process_basic_full(channel_id, method, connection_id, &self.basic_ctx())
                     .await
             }
-            AMQPClass::Tx(method) => tx::process_tx(channel_id, method).map(|f| vec![f]),
+            AMQPClass::Tx(method) => {
+                tx::process_tx(channel_id, method, connection_id, &self.basic_ctx()).await
+            }
             // access.request is deprecated and permissions are configured on the
             // broker side, but real RabbitMQ still acks it unconditionally rather
             // than leaving the client hanging, so we match that behavior.
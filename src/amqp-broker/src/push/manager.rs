@@ -15,9 +15,12 @@
 use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 
+use common_base::tools::now_millis;
 use dashmap::DashMap;
 use tokio::sync::broadcast;
 
+use crate::core::queue_args::logical_queue_name;
+
 /// Marks a shard's cursor as not yet seeded from the last committed offset.
 pub(crate) const UNSEEDED: u64 = u64::MAX;
 
@@ -25,18 +28,24 @@
 /// queues this node currently leads.
 #[derive(Default)]
 pub struct AmqpPushManager {
-    // "{tenant}#{queue}#{shard}" -> next offset to read
+    // "{tenant}#{queue}#{store}#{shard}" -> next offset to read
     cursors: DashMap<String, Arc<AtomicU64>>,
     // "{tenant}#{queue}" -> that queue's push task's stop channel
     running: DashMap<String, broadcast::Sender<bool>>,
+    // "{tenant}#{queue}" -> unix ms the queue last had a consumer or a
+    // Basic.Get, for `x-expires`
+    last_use: DashMap<String, u64>,
 }
 
 fn queue_key(tenant: &str, queue: &str) -> String {
     format!("{tenant}#{queue}")
 }
 
-fn shard_key(tenant: &str, queue: &str, shard_name: &str) -> String {
-    format!("{tenant}#{queue}#{shard_name}")
+/// Keyed by the owning queue too, so a priority store's cursor is dropped
+/// along with its queue's.
+fn shard_key(tenant: &str, store: &str, shard_name: &str) -> String {
+    let queue = logical_queue_name(store);
+    format!("{tenant}#{queue}#{store}#{shard_name}")
 }
 
 impl AmqpPushManager {
@@ -44,13 +53,34 @@ pub fn new() -> Self {
         Self::default()
     }
 
-    pub(crate) fn cursor(&self, tenant: &str, queue: &str, shard_name: &str) -> Arc<AtomicU64> {
+    pub(crate) fn cursor(&self, tenant: &str, store: &str, shard_name: &str) -> Arc<AtomicU64> {
         self.cursors
-            .entry(shard_key(tenant, queue, shard_name))
+            .entry(shard_key(tenant, store, shard_name))
             .or_insert_with(|| Arc::new(AtomicU64::new(UNSEEDED)))
             .clone()
     }
 
+    pub(crate) fn note_use(&self, tenant: &str, queue: &str) {
+        self.last_use
+            .insert(queue_key(tenant, queue), now_millis() as u64);
+    }
+
+    /// When the queue was last used, first recording `now_ms` if this node
+    /// has no record of it (it only just became leader, or restarted).
+    pub(crate) fn last_use_or(&self, tenant: &str, queue: &str, now_ms: u64) -> u64 {
+        *self
+            .last_use
+            .entry(queue_key(tenant, queue))
+            .or_insert(now_ms)
+    }
+
+    pub(crate) fn forget_queue(&self, tenant: &str, queue: &str) {
+        let prefix = queue_key(tenant, queue);
+        self.cursors
+            .retain(|k, _| !k.starts_with(&format!("{prefix}#")));
+        self.last_use.remove(&prefix);
+    }
+
     pub fn is_running(&self, tenant: &str, queue: &str) -> bool {
         self.running.contains_key(&queue_key(tenant, queue))
     }
@@ -35,14 +35,14 @@
 use tokio::sync::broadcast;
 use tracing::{error, warn};
 
-use crate::amqp::basic::properties_from_record;
+use crate::amqp::basic::{delivery_route, properties_from_record};
 use crate::amqp::queue::declare_amqp_queue;
 use crate::core::cache::{AmqpCacheManager, UnackedEntry};
 use crate::core::frame::build_basic_content_frames;
 use crate::core::unacked_index;
 use crate::push::common::{adaptive_sleep, should_stop};
 use crate::push::manager::AmqpPushManager;
-use crate::storage::offset::OffsetStorage;
+use crate::storage::offset::{ClaimedMessage, OffsetStorage};
 
 pub struct AmqpQueuePushParams {
     pub connection_manager: Arc<ConnectionManager>,
@@ -102,18 +102,16 @@ async fn send_messages(&mut self) -> Result<usize, CommonError> {
             return Ok(0);
         }
 
-        let Some(topic) = declare_amqp_queue(
+        if declare_amqp_queue(
             &self.params.storage_driver_manager,
             &self.params.tenant,
             &self.params.queue,
         )
         .await
-        else {
-            return Ok(0);
-        };
-        let Some(shard_name) = topic.storage_name_list.get(&0).cloned() else {
+        .is_none()
+        {
             return Ok(0);
-        };
+        }
 
         let offset_storage = OffsetStorage::new(
             self.params
@@ -135,29 +133,26 @@ async fn send_messages(&mut self) -> Result<usize, CommonError> {
                 break;
             }
             let claimed = offset_storage
-                .claim_next_record(
+                .claim_live_message(
                     &self.params.push_manager,
                     &self.params.storage_driver_manager,
+                    &self.params.amqp_cache,
                     &self.params.tenant,
                     &self.params.queue,
-                    &shard_name,
                 )
                 .await?;
-            let Some((record, msg_offset)) = claimed else {
+            let Some(claimed) = claimed else {
                 break;
             };
 
             let start_idx = self.round_robin as usize % members.len();
             self.round_robin = self.round_robin.wrapping_add(1);
-            if self
-                .deliver(&members, start_idx, &record, msg_offset)
-                .await?
-            {
+            if self.deliver(&members, start_idx, &claimed).await? {
                 pushed += 1;
             } else {
                 warn!(
-                    "AMQP queue [{}/{}]: claimed offset {} but no consumer could take it",
-                    self.params.tenant, self.params.queue, msg_offset
+                    "AMQP queue [{}/{}]: claimed offset {} of {} but no consumer could take it",
+                    self.params.tenant, self.params.queue, claimed.offset, claimed.store
                 );
             }
         }
@@ -207,8 +202,7 @@ async fn deliver(
         &self,
         members: &[ShareGroupMember],
         start_idx: usize,
-        record: &StorageRecord,
-        msg_offset: u64,
+        claimed: &ClaimedMessage,
     ) -> Result<bool, CommonError> {
         let self_broker_id = broker_config().broker_id;
 
@@ -228,8 +222,8 @@ async fn deliver(
                     unacked_index::write_entry(
                         &self.params.storage_driver_manager,
                         &self.params.tenant,
-                        &self.params.queue,
-                        msg_offset,
+                        &claimed.store,
+                        claimed.offset,
                         member.connect_id,
                         detail.channel_id,
                         member.broker_id,
@@ -239,10 +233,10 @@ async fn deliver(
             };
 
             let delivered = if member.broker_id == self_broker_id {
-                self.deliver_local(member, detail, record, msg_offset, index_offset)
+                self.deliver_local(member, detail, claimed, index_offset)
                     .await
             } else {
-                self.deliver_remote(member, detail, record, msg_offset, index_offset)
+                self.deliver_remote(member, detail, claimed, index_offset)
                     .await
             };
 
@@ -291,8 +285,7 @@ async fn deliver_local(
         &self,
         member: &ShareGroupMember,
         detail: &metadata_struct::mqtt::share_group::ShareGroupParamsAmqp,
-        record: &StorageRecord,
-        msg_offset: u64,
+        claimed: &ClaimedMessage,
         index_offset: Option<u64>,
     ) -> Result<bool, CommonError> {
         deliver_to_local_connection(
@@ -302,9 +295,9 @@ async fn deliver_local(
             detail.channel_id,
             &detail.consumer_tag,
             &self.params.tenant,
-            &self.params.queue,
-            record,
-            msg_offset,
+            &claimed.store,
+            &claimed.record,
+            claimed.offset,
             index_offset,
         )
         .await
@@ -314,8 +307,7 @@ async fn deliver_remote(
         &self,
         member: &ShareGroupMember,
         detail: &metadata_struct::mqtt::share_group::ShareGroupParamsAmqp,
-        record: &StorageRecord,
-        msg_offset: u64,
+        claimed: &ClaimedMessage,
         index_offset: Option<u64>,
     ) -> Result<bool, CommonError> {
         let Some(node) = self
@@ -332,13 +324,13 @@ async fn deliver_remote(
 
         let request = SendShareGroupMessageRequest {
             connect_id: member.connect_id,
-            record: record.encode()?,
+            record: claimed.record.encode()?,
             detail: Some(Detail::Amqp(AmqpShareGroupDetail {
                 channel_id: detail.channel_id as u32,
                 consumer_tag: detail.consumer_tag.clone(),
                 tenant: self.params.tenant.clone(),
-                queue: self.params.queue.clone(),
-                offset: msg_offset,
+                queue: claimed.store.clone(),
+                offset: claimed.offset,
                 index_offset,
             })),
         };
@@ -358,6 +350,7 @@ async fn deliver_remote(
 
 /// Writes a Basic.Deliver to a local consumer connection; shared by the
 /// leader's own local delivery and the `SendShareGroupMessage` gRPC handler.
+/// `store` is the storage topic the message was claimed from.
 #[allow(clippy::too_many_arguments)]
 pub async fn deliver_to_local_connection(
     connection_manager: &Arc<ConnectionManager>,
@@ -366,7 +359,7 @@ pub async fn deliver_to_local_connection(
     channel_id: u16,
     consumer_tag: &str,
     tenant: &str,
-    queue: &str,
+    store: &str,
     record: &StorageRecord,
     msg_offset: u64,
     index_offset: Option<u64>,
@@ -377,14 +370,15 @@ pub async fn deliver_to_local_connection(
 
     let delivery_tag = channel.next_delivery_tag.fetch_add(1, Ordering::SeqCst);
     let body = record.data.to_vec();
+    let (exchange, routing_key) = delivery_route(record, store);
     let deliver_frame = amq_protocol::frame::AMQPFrame::Method(
         channel_id,
         AMQPClass::Basic(AMQPMethod::Deliver(Deliver {
             consumer_tag: consumer_tag.into(),
             delivery_tag,
             redelivered: false,
-            exchange: "".into(),
-            routing_key: queue.into(),
+            exchange: exchange.into(),
+            routing_key: routing_key.into(),
         })),
     );
     let frames = build_basic_content_frames(
@@ -409,7 +403,7 @@ pub async fn deliver_to_local_connection(
             (connect_id, channel_id, delivery_tag),
             UnackedEntry {
                 tenant: tenant.to_string(),
-                queue: queue.to_string(),
+                queue: store.to_string(),
                 offset: msg_offset,
                 index_offset,
             },
@@ -17,6 +17,7 @@
 use std::sync::Arc;
 
 use common_base::error::common::CommonError;
+use common_base::tools::now_millis;
 use common_config::broker::broker_config;
 use grpc_clients::meta::common::call::{get_offset_data, save_offset_data};
 use grpc_clients::pool::ClientPool;
@@ -27,9 +28,23 @@
 };
 use storage_adapter::driver::StorageDriverManager;
 
+use crate::amqp::publish::QueueMessage;
+use crate::core::cache::AmqpCacheManager;
+use crate::core::dead_letter::{dead_letter, queue_arguments};
+use crate::core::queue_args::{is_expired, store_names, DeathReason};
 use crate::core::unacked_index;
 use crate::push::manager::{AmqpPushManager, UNSEEDED};
 
+/// A message claimed from a queue. `store` is the storage topic it was
+/// read from: the queue itself, or one of its priority stores.
+pub struct ClaimedMessage {
+    pub record: StorageRecord,
+    pub store: String,
+    pub offset: u64,
+    /// Set unless the claim was no_ack.
+    pub index_offset: Option<u64>,
+}
+
 pub struct OffsetStorage {
     client_pool: Arc<ClientPool>,
 }
@@ -91,87 +106,178 @@ async fn persist_offset(
         Ok(())
     }
 
-    /// Claims the next message for a queue this node leads, via an in-memory
-    /// cursor (seeded from the last committed offset on first use). Shared
-    /// by `Basic.Get` and `Basic.Consume`'s push loop; unacked-table
-    /// bookkeeping is left to the caller.
-    pub async fn claim_next_record(
+    /// The record at this store's claim cursor, without claiming it, plus
+    /// the cursor value it was read at (for `advance_cursor`). The cursor is
+    /// seeded from the last committed offset on first use.
+    pub async fn peek_next_record(
         &self,
         push_manager: &AmqpPushManager,
         sdm: &Arc<StorageDriverManager>,
         tenant: &str,
-        queue: &str,
+        store: &str,
         shard_name: &str,
     ) -> Result<Option<(StorageRecord, u64)>, CommonError> {
-        let cursor = push_manager.cursor(tenant, queue, shard_name);
+        let cursor = push_manager.cursor(tenant, store, shard_name);
         let mut current = cursor.load(Ordering::SeqCst);
         if current == UNSEEDED {
-            current = self
-                .read_committed_offset(tenant, queue, shard_name)
+            let committed = self
+                .read_committed_offset(tenant, store, shard_name)
                 .await?;
-            cursor.store(current, Ordering::SeqCst);
+            current = match cursor.compare_exchange(
+                UNSEEDED,
+                committed,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => committed,
+                Err(seeded) => seeded,
+            };
         }
 
         let read_config = AdapterReadConfig::new();
         let mut offsets = HashMap::new();
         offsets.insert(shard_name.to_string(), current);
         let records = sdm
-            .read_by_offset(tenant, queue, &offsets, &read_config)
+            .read_by_offset(tenant, store, &offsets, &read_config)
             .await?;
-        let Some(record) = records.into_iter().next() else {
-            return Ok(None);
-        };
+        Ok(records.into_iter().next().map(|record| (record, current)))
+    }
 
-        let msg_offset = record.metadata.offset;
+    /// Claims `msg_offset` (read by `peek_next_record` at cursor `seen`) by
+    /// moving the cursor past it and committing that. False if someone else
+    /// (the push loop, a `Basic.Get` or the queue sweep) moved the cursor
+    /// first.
+    pub async fn advance_cursor(
+        &self,
+        push_manager: &AmqpPushManager,
+        tenant: &str,
+        store: &str,
+        shard_name: &str,
+        seen: u64,
+        msg_offset: u64,
+    ) -> Result<bool, CommonError> {
         let new_offset = msg_offset + 1;
-        cursor.store(new_offset, Ordering::SeqCst);
-        self.persist_offset(tenant, queue, shard_name, new_offset)
+        let cursor = push_manager.cursor(tenant, store, shard_name);
+        if cursor
+            .compare_exchange(seen, new_offset, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(false);
+        }
+        self.persist_offset(tenant, store, shard_name, new_offset)
             .await?;
+        Ok(true)
+    }
+
+    /// Claims the next message of one storage topic of a queue this node
+    /// leads, via an in-memory cursor; unacked-table bookkeeping is left to
+    /// the caller.
+    pub async fn claim_next_record(
+        &self,
+        push_manager: &AmqpPushManager,
+        sdm: &Arc<StorageDriverManager>,
+        tenant: &str,
+        store: &str,
+        shard_name: &str,
+    ) -> Result<Option<(StorageRecord, u64)>, CommonError> {
+        loop {
+            let Some((record, seen)) = self
+                .peek_next_record(push_manager, sdm, tenant, store, shard_name)
+                .await?
+            else {
+                return Ok(None);
+            };
+            let msg_offset = record.metadata.offset;
+            if self
+                .advance_cursor(push_manager, tenant, store, shard_name, seen, msg_offset)
+                .await?
+            {
+                return Ok(Some((record, msg_offset)));
+            }
+        }
+    }
 
-        Ok(Some((record, msg_offset)))
+    /// Claims the next live message of `queue`: its priority stores highest
+    /// first, then the queue itself. Expired messages met on the way are
+    /// dead-lettered and removed. Shared by `Basic.Get` and `Basic.Consume`'s
+    /// push loop.
+    pub async fn claim_live_message(
+        &self,
+        push_manager: &AmqpPushManager,
+        sdm: &Arc<StorageDriverManager>,
+        cache: &Arc<AmqpCacheManager>,
+        tenant: &str,
+        queue: &str,
+    ) -> Result<Option<ClaimedMessage>, CommonError> {
+        push_manager.note_use(tenant, queue);
+        let args = queue_arguments(cache, tenant, queue);
+        for store in store_names(queue, args.max_priority) {
+            let Some(shard_name) = sdm
+                .broker_cache
+                .get_topic_by_name(tenant, &store)
+                .and_then(|topic| topic.storage_name_list.get(&0).cloned())
+            else {
+                continue;
+            };
+            while let Some((record, offset)) = self
+                .claim_next_record(push_manager, sdm, tenant, &store, &shard_name)
+                .await?
+            {
+                let message = QueueMessage::from_record(&record);
+                if !is_expired(&message.properties, now_millis() as u64) {
+                    return Ok(Some(ClaimedMessage {
+                        record,
+                        store,
+                        offset,
+                        index_offset: None,
+                    }));
+                }
+                dead_letter(sdm, cache, tenant, queue, message, DeathReason::Expired).await;
+                sdm.delete_by_offsets(tenant, &store, &[offset]).await?;
+            }
+        }
+        Ok(None)
     }
 
-    /// `claim_next_record` plus, for `no_ack == false`, an unacked-index
+    /// `claim_live_message` plus, for `no_ack == false`, an unacked-index
     /// write in the same step. Used by `Basic.Get`; Consume's push loop
-    /// calls `claim_next_record` directly since it may retry against a
+    /// calls `claim_live_message` directly since it may retry against a
     /// different member.
     #[allow(clippy::too_many_arguments)]
     pub async fn claim_and_track(
         &self,
         push_manager: &AmqpPushManager,
         sdm: &Arc<StorageDriverManager>,
+        cache: &Arc<AmqpCacheManager>,
         tenant: &str,
         queue: &str,
-        shard_name: &str,
         no_ack: bool,
         connection_id: u64,
         channel_id: u16,
         broker_id: u64,
-    ) -> Result<Option<(StorageRecord, u64, Option<u64>)>, CommonError> {
-        let Some((record, msg_offset)) = self
-            .claim_next_record(push_manager, sdm, tenant, queue, shard_name)
+    ) -> Result<Option<ClaimedMessage>, CommonError> {
+        let Some(mut claimed) = self
+            .claim_live_message(push_manager, sdm, cache, tenant, queue)
             .await?
         else {
             return Ok(None);
         };
 
-        let index_offset = if no_ack {
-            None
-        } else {
-            Some(
+        if !no_ack {
+            claimed.index_offset = Some(
                 unacked_index::write_entry(
                     sdm,
                     tenant,
-                    queue,
-                    msg_offset,
+                    &claimed.store,
+                    claimed.offset,
                     connection_id,
                     channel_id,
                     broker_id,
                 )
                 .await?,
-            )
-        };
+            );
+        }
 
-        Ok(Some((record, msg_offset, index_offset)))
+        Ok(Some(claimed))
     }
 }
@@ -58,6 +58,15 @@ pub async fn try_init_default_tenant(
     Ok(())
 }
 
+/// Config of `tenant_name`, falling back to the defaults while the tenant is
+/// not in the cache.
+pub fn get_tenant_config(broker_cache: &NodeCacheManager, tenant_name: &str) -> TenantConfig {
+    broker_cache
+        .get_tenant(tenant_name)
+        .map(|tenant| tenant.config)
+        .unwrap_or_default()
+}
+
 pub struct TenantStorage {
     client_pool: Arc<ClientPool>,
 }
@@ -14,10 +14,12 @@
 
 use amqp_broker::broker::{AmqpBrokerServer, AmqpBrokerServerParams};
 use amqp_broker::core::cache::AmqpCacheManager;
+use amqp_broker::core::delayed::{AmqpDelayedRouter, DELAYED_TARGET_PREFIX};
 use amqp_broker::push::AmqpPushManager;
 use broker_core::cache::NodeCacheManager;
 use common_base::{role::is_broker_node, task::TaskSupervisor};
 use common_security::manager::SecurityManager;
+use delay_message::manager::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
 use network_server::common::channel::RequestChannel;
 use network_server::common::connection_manager::ConnectionManager;
@@ -39,9 +41,19 @@ pub struct AmqpBuildParams {
     pub storage_driver_manager: Arc<StorageDriverManager>,
     pub amqp_cache: Arc<AmqpCacheManager>,
     pub security_manager: Arc<SecurityManager>,
+    pub delay_message_manager: Arc<DelayMessageManager>,
 }
 
 pub fn build_amqp_params(p: AmqpBuildParams) -> AmqpBrokerServerParams {
+    // Delayed-exchange messages come back from the delay queue through this
+    // router, which routes them via the exchange's current bindings.
+    p.delay_message_manager.register_router(
+        DELAYED_TARGET_PREFIX,
+        Arc::new(AmqpDelayedRouter::new(
+            p.storage_driver_manager.clone(),
+            p.amqp_cache.clone(),
+        )),
+    );
     AmqpBrokerServerParams {
         connection_manager: p.connection_manager,
         client_pool: p.client_pool,
@@ -54,6 +66,7 @@ pub fn build_amqp_params(p: AmqpBuildParams) -> AmqpBrokerServerParams {
         amqp_cache: p.amqp_cache,
         security_manager: p.security_manager,
         push_manager: Arc::new(AmqpPushManager::new()),
+        delay_message_manager: p.delay_message_manager,
     }
 }
 
@@ -16,7 +16,8 @@
 use amqp_broker::broker::AmqpBrokerServerParams;
 use amqp_broker::push::queue::deliver_to_local_connection;
 use amqp_broker::storage::offset::OffsetStorage;
-use kafka_broker::core::cache::KafkaCacheManager;
+use kafka_broker::broker::KafkaBrokerServerParams;
+use kafka_broker::kafka::produce::process_forwarded_produce;
 use metadata_struct::storage::record::StorageRecord;
 use mqtt_broker::{
     broker::MqttBrokerServerParams, core::inner::send_last_will_message_by_req,
@@ -26,11 +27,12 @@
 use nats_broker::push::nats_fanout::send_packet;
 use protocol::broker::broker::{
     broker_service_server::BrokerService, send_share_group_message_request::Detail,
-    FetchAmqpQueueMessageReply, FetchAmqpQueueMessageRequest, GetQosDataByClientIdReply,
-    GetQosDataByClientIdRequest, GetShardSegmentDeleteStatusReply,
-    GetShardSegmentDeleteStatusRequest, QueryReplicaLeoReply, QueryReplicaLeoRequest,
-    SendLastWillMessageReply, SendLastWillMessageRequest, SendShareGroupMessageReply,
-    SendShareGroupMessageRequest, ShardSegmentDeleteStatus, UpdateCacheReply, UpdateCacheRequest,
+    FetchAmqpQueueMessageReply, FetchAmqpQueueMessageRequest, ForwardKafkaProduceReply,
+    ForwardKafkaProduceRequest, GetQosDataByClientIdReply, GetQosDataByClientIdRequest,
+    GetShardSegmentDeleteStatusReply, GetShardSegmentDeleteStatusRequest, QueryReplicaLeoReply,
+    QueryReplicaLeoRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
+    SendShareGroupMessageReply, SendShareGroupMessageRequest, ShardSegmentDeleteStatus,
+    UpdateCacheReply, UpdateCacheRequest,
 };
 use std::sync::Arc;
 use storage_engine::core::delete::{segment_already_delete, shard_already_delete};
@@ -44,7 +46,7 @@ pub struct GrpcBrokerService {
     mqtt_params: MqttBrokerServerParams,
     nats_params: NatsBrokerServerParams,
     storage_params: StorageEngineParams,
-    kafka_cache: Arc<KafkaCacheManager>,
+    kafka_params: KafkaBrokerServerParams,
     amqp_params: AmqpBrokerServerParams,
 }
 
@@ -53,14 +55,14 @@ pub fn new(
         mqtt_params: MqttBrokerServerParams,
         nats_params: NatsBrokerServerParams,
         storage_params: StorageEngineParams,
-        kafka_cache: Arc<KafkaCacheManager>,
+        kafka_params: KafkaBrokerServerParams,
         amqp_params: AmqpBrokerServerParams,
     ) -> Self {
         GrpcBrokerService {
             mqtt_params,
             nats_params,
             storage_params,
-            kafka_cache,
+            kafka_params,
             amqp_params,
         }
     }
@@ -78,7 +80,7 @@ async fn update_cache(
                 &self.mqtt_params,
                 &self.nats_params,
                 &self.storage_params,
-                &self.kafka_cache,
+                &self.kafka_params.kafka_cache,
                 &self.amqp_params.amqp_cache,
                 record,
             )
@@ -226,9 +228,9 @@ async fn fetch_amqp_queue_message(
             .claim_and_track(
                 &self.amqp_params.push_manager,
                 &self.amqp_params.storage_driver_manager,
+                &self.amqp_params.amqp_cache,
                 &req.tenant,
                 &req.queue,
-                &req.shard_name,
                 req.no_ack,
                 req.connect_id,
                 req.channel_id as u16,
@@ -237,22 +239,48 @@ async fn fetch_amqp_queue_message(
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
-        let Some((record, offset, index_offset)) = claimed else {
+        let Some(claimed) = claimed else {
             return Ok(Response::new(FetchAmqpQueueMessageReply {
                 has_message: false,
                 record: Vec::new(),
                 offset: 0,
                 index_offset: None,
+                store: String::new(),
             }));
         };
 
         Ok(Response::new(FetchAmqpQueueMessageReply {
             has_message: true,
-            record: record
+            record: claimed
+                .record
                 .encode()
                 .map_err(|e| Status::internal(e.to_string()))?,
-            offset,
-            index_offset,
+            offset: claimed.offset,
+            index_offset: claimed.index_offset,
+            store: claimed.store,
+        }))
+    }
+
+    async fn forward_kafka_produce(
+        &self,
+        request: Request<ForwardKafkaProduceRequest>,
+    ) -> Result<Response<ForwardKafkaProduceReply>, Status> {
+        let req = request.into_inner();
+        let response = process_forwarded_produce(
+            &self.kafka_params.storage_driver_manager,
+            &self.kafka_params.kafka_cache,
+            &self.kafka_params.txn_coordinator,
+            &self.mqtt_params.schema_manager,
+            &self.mqtt_params.rule_manager,
+            req.api_version as i16,
+            &req.request,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(ForwardKafkaProduceReply {
+            has_response: response.is_some(),
+            response: response.unwrap_or_default(),
         }))
     }
 
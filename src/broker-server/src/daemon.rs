@@ -185,6 +185,7 @@ pub async fn start_background_services(
         // connector
         let message_storage = self.mqtt_params.storage_driver_manager.clone();
         let connector_manager = self.mqtt_params.connector_manager.clone();
+        let schema_manager = self.mqtt_params.schema_manager.clone();
         let client_pool = self.client_pool.clone();
         let task_supervisor = self.task_supervisor.clone();
         self.server_runtime.spawn(async move {
@@ -192,6 +193,7 @@ pub async fn start_background_services(
                 &client_pool,
                 &message_storage,
                 &connector_manager,
+                &schema_manager,
                 &task_supervisor,
                 &stop,
             )
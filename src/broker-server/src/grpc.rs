@@ -20,7 +20,7 @@
 use common_base::tools::now_millis;
 use common_config::broker::broker_config;
 use common_metrics::grpc::{extract_grpc_status_code, parse_grpc_path, record_grpc_request};
-use kafka_broker::core::cache::KafkaCacheManager;
+use kafka_broker::broker::KafkaBrokerServerParams;
 use meta_service::server::service_amqp::GrpcAmqpService;
 use meta_service::server::service_common::GrpcPlacementService;
 use meta_service::server::service_engine::GrpcEngineService;
@@ -40,7 +40,6 @@
 use protocol::meta::meta_service_mqtt::mqtt_service_server::MqttServiceServer;
 use protocol::meta::meta_service_nats::nats_service_server::NatsServiceServer;
 use std::pin::Pin;
-use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use storage_engine::StorageEngineParams;
@@ -55,7 +54,7 @@ pub async fn start_grpc_server(
     mqtt_params: MqttBrokerServerParams,
     nats_params: NatsBrokerServerParams,
     engine_params: StorageEngineParams,
-    kafka_cache: Arc<KafkaCacheManager>,
+    kafka_params: KafkaBrokerServerParams,
     amqp_params: AmqpBrokerServerParams,
     grpc_port: u32,
 ) -> Result<(), CommonError> {
@@ -80,7 +79,7 @@ pub async fn start_grpc_server(
                 mqtt_params.clone(),
                 nats_params.clone(),
                 engine_params.clone(),
-                kafka_cache,
+                kafka_params,
                 amqp_params,
             ))
             .max_decoding_message_size(grpc_max_decoding_message_size),
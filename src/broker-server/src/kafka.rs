@@ -17,6 +17,7 @@
 use grpc_clients::pool::ClientPool;
 use kafka_broker::broker::{KafkaBrokerServer, KafkaBrokerServerParams};
 use kafka_broker::core::cache::KafkaCacheManager;
+use kafka_broker::core::txn_coordinator::TransactionCoordinator;
 use network_server::common::channel::RequestChannel;
 use network_server::common::connection_manager::ConnectionManager;
 use rate_limit::global::GlobalRateLimiterManager;
@@ -47,6 +48,10 @@ pub fn build_kafka_params(p: KafkaBuildParams) -> KafkaBrokerServerParams {
         task_supervisor: p.task_supervisor,
         stop_sx: p.stop_sx,
         request_channel: p.shared_request_channel,
+        txn_coordinator: Arc::new(TransactionCoordinator::new(
+            p.storage_driver_manager.clone(),
+            p.kafka_cache.clone(),
+        )),
         storage_driver_manager: p.storage_driver_manager,
         kafka_cache: p.kafka_cache,
     }
@@ -371,6 +371,7 @@ fn init_protocol_params(
             storage_driver_manager: storage_driver_manager.clone(),
             amqp_cache,
             security_manager: security_manager.clone(),
+            delay_message_manager: delay_message_manager.clone(),
         });
         let nats_params = nats::build_nats_params(nats::NatsBuildParams {
             connection_manager: base.connection_manager.clone(),
@@ -531,6 +532,9 @@ fn create_command_registry(
             self.kafka_params.storage_driver_manager.clone(),
             self.broker_cache.clone(),
             self.kafka_params.kafka_cache.clone(),
+            self.kafka_params.txn_coordinator.clone(),
+            self.mqtt_params.schema_manager.clone(),
+            self.mqtt_params.rule_manager.clone(),
         ));
         let amqp_cmd = Some(amqp_broker::handler::command::create_command_with_state(
             self.amqp_params.storage_driver_manager.clone(),
@@ -538,7 +542,23 @@ fn create_command_registry(
             self.amqp_params.security_manager.clone(),
             self.amqp_params.client_pool.clone(),
             self.amqp_params.push_manager.clone(),
+            self.amqp_params.delay_message_manager.clone(),
+            self.mqtt_params.schema_manager.clone(),
         ));
+        let amqp1_cmd = Some(
+            amqp_broker::handler::amqp1_command::create_amqp1_command_with_state(
+                amqp_broker::handler::amqp1_command::Amqp1CommandParams {
+                    storage_driver_manager: self.amqp_params.storage_driver_manager.clone(),
+                    amqp_cache: self.amqp_params.amqp_cache.clone(),
+                    security_manager: self.amqp_params.security_manager.clone(),
+                    client_pool: self.amqp_params.client_pool.clone(),
+                    push_manager: self.amqp_params.push_manager.clone(),
+                    delay_message_manager: self.amqp_params.delay_message_manager.clone(),
+                    connection_manager: self.connection_manager.clone(),
+                    schema_manager: self.mqtt_params.schema_manager.clone(),
+                },
+            ),
+        );
         let nats_cmd = Some(nats_broker::handler::command::create_command(
             self.connection_manager.clone(),
             self.nats_params.cache_manager.clone(),
@@ -547,12 +567,15 @@ fn create_command_registry(
             self.nats_params.client_pool.clone(),
             self.nats_params.security_manager.clone(),
             self.nats_params.delay_message_manager.clone(),
+            self.mqtt_params.schema_manager.clone(),
+            self.mqtt_params.rule_manager.clone(),
         ));
 
         CommandRegistry {
             mqtt: mqtt_cmd,
             kafka: kafka_cmd,
             amqp: amqp_cmd,
+            amqp1: amqp1_cmd,
             nats: nats_cmd,
             storage_engine: None,
         }
@@ -43,23 +43,28 @@
 use mqtt_broker::core::tool::ResultMqttBrokerError;
 use mqtt_broker::storage::auto_subscribe::AutoSubscribeStorage;
 use mqtt_broker::storage::connector::ConnectorStorage;
+use mqtt_broker::storage::rule::RuleStorage;
 use mqtt_broker::storage::schema::SchemaStorage;
 use mqtt_broker::storage::topic_rewrite::TopicRewriteStorage;
 use nats_broker::core::cache::NatsCacheManager;
 use nats_broker::push::NatsSubscribeManager;
 use nats_broker::storage::agent::Mq9AgentStorage;
+use nats_broker::storage::consumer::NatsConsumerStorage;
 use nats_broker::storage::mail::Mq9MailStorage;
+use nats_broker::storage::stream::NatsStreamStorage;
 use nats_broker::storage::subscribe::NatsSubscribeStorage;
+use protocol::meta::meta_service_common::ListSchemaVersionRequest;
 use protocol::meta::meta_service_kafka::{
     ListKafkaDelegationTokenRequest, ListKafkaQuotaRequest, ListScramCredentialRequest,
 };
+use rule_engine::manager::RuleEngineManager;
 use schema_register::schema::SchemaRegisterManager;
 use std::sync::Arc;
 use storage_engine::core::cache::StorageCacheManager;
 use storage_engine::core::error::StorageEngineError;
 use storage_engine::core::segment::{list_segment_metas, list_segments};
 use storage_engine::core::shard::list_shards;
-use tracing::info;
+use tracing::{info, warn};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn load_metadata_cache(
@@ -69,6 +74,7 @@ pub async fn load_metadata_cache(
     client_pool: &Arc<ClientPool>,
     connector_manager: &Arc<ConnectorManager>,
     schema_manager: &Arc<SchemaRegisterManager>,
+    rule_manager: &Arc<RuleEngineManager>,
     security_manager: &Arc<SecurityManager>,
     kafka_cache: &Arc<KafkaCacheManager>,
     amqp_cache: &Arc<AmqpCacheManager>,
@@ -79,6 +85,7 @@ pub async fn load_metadata_cache(
         client_pool,
         connector_manager,
         schema_manager,
+        rule_manager,
     )
     .await?;
 
@@ -179,6 +186,7 @@ async fn load_common_cache(
     client_pool: &Arc<ClientPool>,
     connector_manager: &Arc<ConnectorManager>,
     schema_manager: &Arc<SchemaRegisterManager>,
+    rule_manager: &Arc<RuleEngineManager>,
 ) -> ResultMqttBrokerError {
     let cluster_storage = ClusterStorage::new(client_pool.clone());
     let nodes = cluster_storage
@@ -210,6 +218,11 @@ async fn load_common_cache(
         .map_err(|e| MqttBrokerError::CommonError(format!("Failed to load connectors: {}", e)))?;
     for connector in connectors.iter() {
         connector_manager.add_connector(connector);
+        rule_manager.add_connector_topic(
+            &connector.tenant,
+            &connector.connector_name,
+            &connector.topic_name,
+        );
     }
 
     let schema_storage = SchemaStorage::new(client_pool.clone());
@@ -230,6 +243,29 @@ async fn load_common_cache(
         schema_manager.add_bind(schema);
     }
 
+    let schema_storage = SchemaStorage::new(client_pool.clone());
+    let schema_versions = schema_storage
+        .list_versions(ListSchemaVersionRequest::default())
+        .await
+        .map_err(|e| {
+            MqttBrokerError::CommonError(format!("Failed to load schema versions: {}", e))
+        })?;
+    for schema_version in schema_versions {
+        schema_manager.add_schema_version(schema_version);
+    }
+
+    let rule_storage = RuleStorage::new(client_pool.clone());
+    let rules = rule_storage
+        .list(None, None)
+        .await
+        .map_err(|e| MqttBrokerError::CommonError(format!("Failed to load rules: {}", e)))?;
+    for rule in rules {
+        let name = rule.name.clone();
+        if let Err(e) = rule_manager.add_rule(rule) {
+            warn!("Failed to load rule {}: {}", name, e);
+        }
+    }
+
     let tenant_storage = TenantStorage::new(client_pool.clone());
     let tenants = tenant_storage
         .list_all()
@@ -391,9 +427,23 @@ pub async fn load_nats_cache(
         cache_manager.add_agent(agent);
     }
 
+    let stream_storage = NatsStreamStorage::new(client_pool.clone());
+    let streams = stream_storage.list("").await?;
+    let stream_count = streams.len();
+    for stream in streams {
+        cache_manager.add_stream(stream);
+    }
+
+    let consumer_storage = NatsConsumerStorage::new(client_pool.clone());
+    let consumers = consumer_storage.list("", "").await?;
+    let consumer_count = consumers.len();
+    for consumer in consumers {
+        cache_manager.add_consumer(consumer);
+    }
+
     info!(
-        "NATS cache loaded: subscribes={}, mails={}, agents={}",
-        subscribe_count, mail_count, agent_count
+        "NATS cache loaded: subscribes={}, mails={}, agents={}, streams={}, consumers={}",
+        subscribe_count, mail_count, agent_count, stream_count, consumer_count
     );
     Ok(())
 }
@@ -32,6 +32,7 @@
 use node_call::NodeCallManager;
 use rate_limit::global::GlobalRateLimiterManager;
 use rocksdb_engine::{metrics::mqtt::MQTTMetricsCache, rocksdb::RocksDBEngine};
+use rule_engine::manager::RuleEngineManager;
 use schema_register::schema::SchemaRegisterManager;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
@@ -120,6 +121,7 @@ pub(crate) async fn build_broker_mqtt_params(
     let connector_manager = Arc::new(ConnectorManager::new());
     let metrics_cache_manager = Arc::new(MQTTMetricsCache::new(rocksdb_engine_handler.clone()));
     let schema_manager = Arc::new(SchemaRegisterManager::new());
+    let rule_manager = Arc::new(RuleEngineManager::new());
     let push_manager = Arc::new(PushManager::new(
         cache_manager.clone(),
         storage_driver_manager.clone(),
@@ -143,6 +145,7 @@ pub(crate) async fn build_broker_mqtt_params(
         security_manager,
         delay_message_manager,
         schema_manager,
+        rule_manager,
         metrics_cache_manager,
         rocksdb_engine_handler,
         node_cache: broker_cache,
@@ -30,7 +30,7 @@ pub fn start_grpc_server(&self) {
         let mqtt_params = self.mqtt_params.clone();
         let nats_params = self.nats_params.clone();
         let engine_params = self.engine_params.clone();
-        let kafka_cache = self.kafka_params.kafka_cache.clone();
+        let kafka_params = self.kafka_params.clone();
         let amqp_params = self.amqp_params.clone();
         let grpc_port = self.config.grpc_port;
         self.server_runtime.spawn(Box::pin(async move {
@@ -39,7 +39,7 @@ pub fn start_grpc_server(&self) {
                 mqtt_params,
                 nats_params,
                 engine_params,
-                kafka_cache,
+                kafka_params,
                 amqp_params,
                 grpc_port,
             )
@@ -130,6 +130,7 @@ pub fn start_load_cache(&self) {
         let client_pool = self.client_pool.clone();
         let connector_manager = self.mqtt_params.connector_manager.clone();
         let schema_manager = self.mqtt_params.schema_manager.clone();
+        let rule_manager = self.mqtt_params.rule_manager.clone();
         let security_manager = self.mqtt_params.security_manager.clone();
         let kafka_cache = self.kafka_params.kafka_cache.clone();
         let amqp_cache = self.amqp_params.amqp_cache.clone();
@@ -141,6 +142,7 @@ pub fn start_load_cache(&self) {
                 &client_pool,
                 &connector_manager,
                 &schema_manager,
+                &rule_manager,
                 &security_manager,
                 &kafka_cache,
                 &amqp_cache,
@@ -32,7 +32,8 @@
 use metadata_struct::mqtt::share_group::{ShareGroup, ShareGroupMember, ShareGroupParams};
 use metadata_struct::nats::subscribe::NatsSubscribe;
 use metadata_struct::resource_config::ResourceConfig;
-use metadata_struct::schema::{SchemaData, SchemaResourceBind};
+use metadata_struct::rule::Rule;
+use metadata_struct::schema::{SchemaData, SchemaResourceBind, SchemaVersion};
 use metadata_struct::tenant::Tenant;
 use metadata_struct::topic::Topic;
 use mqtt_broker::core::topic::{create_topic_by_mqtt, delete_topic_by_mqtt};
@@ -78,7 +79,7 @@ pub async fn update_cache(
             }
         }
 
-        // Cluster — Node, Config, Tenant, User, Acl, Blacklist, Group, ShareGroupMember, Connector, Schema, Topic
+        // Cluster — Node, Config, Tenant, User, Acl, Blacklist, Group, ShareGroupMember, Connector, Schema, Rule, Topic
         BrokerUpdateCacheResourceType::ClusterResourceConfig
         | BrokerUpdateCacheResourceType::Node
         | BrokerUpdateCacheResourceType::Tenant
@@ -90,6 +91,8 @@ pub async fn update_cache(
         | BrokerUpdateCacheResourceType::Connector
         | BrokerUpdateCacheResourceType::Schema
         | BrokerUpdateCacheResourceType::SchemaResource
+        | BrokerUpdateCacheResourceType::SchemaVersion
+        | BrokerUpdateCacheResourceType::Rule
         | BrokerUpdateCacheResourceType::GroupOffset
         | BrokerUpdateCacheResourceType::Topic => {
             if let Err(e) = update_cluster_cache_metadata(mqtt_params, nats_params, record).await {
@@ -172,6 +175,8 @@ pub async fn update_cache(
 
         // NATS / MQ9
         BrokerUpdateCacheResourceType::NatsSubscribe
+        | BrokerUpdateCacheResourceType::NatsStream
+        | BrokerUpdateCacheResourceType::NatsConsumer
         | BrokerUpdateCacheResourceType::Mq9Mail
         | BrokerUpdateCacheResourceType::Mq9Agent => {
             if let Err(e) = update_nats_cache_metadata(
@@ -357,11 +362,19 @@ pub async fn update_cluster_cache_metadata(
             match record.action_type() {
                 BrokerUpdateCacheActionType::Create | BrokerUpdateCacheActionType::Update => {
                     mqtt_params.connector_manager.add_connector(&connector);
+                    mqtt_params.rule_manager.add_connector_topic(
+                        &connector.tenant,
+                        &connector.connector_name,
+                        &connector.topic_name,
+                    );
                 }
                 BrokerUpdateCacheActionType::Delete => {
                     mqtt_params
                         .connector_manager
                         .remove_connector(&connector.connector_name);
+                    mqtt_params
+                        .rule_manager
+                        .remove_connector_topic(&connector.tenant, &connector.connector_name);
                 }
             }
         }
@@ -392,6 +405,38 @@ pub async fn update_cluster_cache_metadata(
             }
         }
 
+        BrokerUpdateCacheResourceType::SchemaVersion => {
+            let schema_version: SchemaVersion = serialize::deserialize(&record.data)?;
+            match record.action_type() {
+                BrokerUpdateCacheActionType::Create | BrokerUpdateCacheActionType::Update => {
+                    mqtt_params
+                        .schema_manager
+                        .add_schema_version(schema_version);
+                }
+                BrokerUpdateCacheActionType::Delete => {
+                    mqtt_params.schema_manager.remove_schema_version(
+                        &schema_version.tenant,
+                        &schema_version.subject,
+                        schema_version.version,
+                    );
+                }
+            }
+        }
+
+        BrokerUpdateCacheResourceType::Rule => {
+            let rule: Rule = serialize::deserialize(&record.data)?;
+            match record.action_type() {
+                BrokerUpdateCacheActionType::Create | BrokerUpdateCacheActionType::Update => {
+                    mqtt_params.rule_manager.add_rule(rule)?;
+                }
+                BrokerUpdateCacheActionType::Delete => {
+                    mqtt_params
+                        .rule_manager
+                        .remove_rule(&rule.tenant, &rule.name);
+                }
+            }
+        }
+
         BrokerUpdateCacheResourceType::GroupOffset => match record.action_type() {
             BrokerUpdateCacheActionType::Create => {}
             BrokerUpdateCacheActionType::Update => {
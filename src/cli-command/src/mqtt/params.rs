@@ -474,6 +474,12 @@ pub struct BindSchemaArgs {
     pub schema_name: String,
     #[arg(short, long, required = true)]
     pub resource_name: String,
+    #[arg(short, long, default_value = "")]
+    pub message_name: String,
+    #[arg(long, default_value_t = false)]
+    pub strict_unknown_fields: bool,
+    #[arg(long, default_value_t = false)]
+    pub strict_required_fields: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -673,6 +679,9 @@ pub fn process_schema_args(args: SchemaArgs) -> MqttActionType {
                 tenant: arg.tenant,
                 schema_name: arg.schema_name,
                 resource_name: arg.resource_name,
+                message_name: arg.message_name,
+                strict_unknown_fields: arg.strict_unknown_fields,
+                strict_required_fields: arg.strict_required_fields,
             })
         }
         SchemaActionType::Unbind(arg) => {
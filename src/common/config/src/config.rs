@@ -34,20 +34,21 @@
     default_mqtt_slow_subscribe, default_mqtt_system_monitor, default_mqtt_tcp_port,
     default_mqtt_tls_port, default_mqtt_websocket_port, default_mqtt_websockets_port,
     default_network, default_offline_message_enable, default_offline_message_expire_ms,
-    default_offline_message_max_num, default_queue_size, default_raft_write_timeout_sec,
-    default_receive_max, default_roles, default_runtime, default_schema_echo_log,
-    default_schema_enable, default_schema_failed_operation, default_schema_log_level,
-    default_schema_strategy, default_session_expiry_interval, default_slow_subscribe_delay_type,
-    default_slow_subscribe_record_time, default_storage_expire_scan_task_num,
-    default_storage_io_thread_num, default_storage_isr_maintain_interval_ms,
-    default_storage_max_segment_size, default_storage_metadata_reconcile_interval_ms,
-    default_storage_num_replica_fetchers, default_storage_offset_enable_cache,
-    default_storage_replica_fetch_backoff_ms, default_storage_replica_fetch_max_wait_ms,
-    default_storage_replica_fetch_min_bytes, default_storage_replica_lag_time_max_ms,
-    default_storage_tcp_port, default_system_monitor_cpu_watermark,
-    default_system_monitor_memory_watermark, default_system_monitor_topic_interval_ms,
-    default_tls_cert, default_tls_key, default_topic_alias_max, default_topic_partition_num,
-    default_topic_replica_num,
+    default_offline_message_max_num, default_proxy_protocol_timeout_ms, default_queue_size,
+    default_raft_write_timeout_sec, default_receive_max, default_roles, default_runtime,
+    default_schema_echo_log, default_schema_enable, default_schema_failed_operation,
+    default_schema_log_level, default_schema_strategy, default_session_expiry_interval,
+    default_slow_subscribe_delay_type, default_slow_subscribe_record_time,
+    default_storage_expire_scan_task_num, default_storage_io_thread_num,
+    default_storage_isr_maintain_interval_ms, default_storage_max_segment_size,
+    default_storage_metadata_reconcile_interval_ms, default_storage_num_replica_fetchers,
+    default_storage_offset_enable_cache, default_storage_replica_fetch_backoff_ms,
+    default_storage_replica_fetch_max_wait_ms, default_storage_replica_fetch_min_bytes,
+    default_storage_replica_lag_time_max_ms, default_storage_tcp_port,
+    default_system_monitor_cpu_watermark, default_system_monitor_memory_watermark,
+    default_system_monitor_topic_interval_ms, default_tls_cert, default_tls_client_auth,
+    default_tls_key, default_tls_reload_interval_sec, default_topic_alias_max,
+    default_topic_partition_num, default_topic_replica_num,
 };
 use crate::common::default_log;
 use crate::common::Log;
@@ -275,6 +276,54 @@ pub struct Network {
 
     #[serde(default = "default_tls_key")]
     pub tls_key: String,
+
+    // Client certificates: "none", "optional" (verified if presented) or "required".
+    #[serde(default = "default_tls_client_auth")]
+    pub tls_client_auth: String,
+
+    // CA bundle client certificates must chain to.
+    #[serde(default)]
+    pub tls_ca_cert: String,
+
+    // PEM CRL files checked against the client's end-entity certificate; OCSP
+    // is not checked for client certificates.
+    #[serde(default)]
+    pub tls_crls: Vec<String>,
+
+    // DER OCSP response stapled to the server certificate in the handshake.
+    #[serde(default)]
+    pub tls_ocsp_response: String,
+
+    // Use the client certificate's "cn" or "san" as the username / client id.
+    #[serde(default)]
+    pub tls_peer_cert_as_username: String,
+
+    #[serde(default)]
+    pub tls_peer_cert_as_client_id: String,
+
+    // How often the certificate files are checked for changes; 0 turns the check off.
+    #[serde(default = "default_tls_reload_interval_sec")]
+    pub tls_reload_interval_sec: u64,
+
+    // TCP and TLS listeners that sit behind a load balancer sending PROXY protocol
+    // headers. WebSocket and QUIC listeners don't read them.
+    #[serde(default)]
+    pub proxy_protocol: Vec<ProxyProtocolListener>,
+
+    #[serde(default = "default_proxy_protocol_timeout_ms")]
+    pub proxy_protocol_timeout_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct ProxyProtocolListener {
+    // The port of the TCP or TLS listener, e.g. 1883. WebSocket and QUIC ports
+    // are not supported.
+    pub port: u32,
+
+    // Sources (CIDRs or addresses) that must start with a PROXY header. Other
+    // sources connect directly and keep their own address. Empty trusts no source.
+    #[serde(default)]
+    pub trusted_cidrs: Vec<String>,
 }
 
 impl Default for Network {
@@ -758,6 +807,10 @@ pub struct KafkaRuntime {
     pub sasl: KafkaSasl,
     #[serde(default = "default_auto_create_topics_enable")]
     pub auto_create_topics_enable: bool,
+    /// Topic that payloads rejected by a bound schema are written to, with the
+    /// rejection reason in the record headers. Empty drops them.
+    #[serde(default)]
+    pub schema_dead_letter_topic: String,
 }
 
 impl Default for KafkaRuntime {
@@ -769,6 +822,7 @@ fn default() -> Self {
             max_describe_topic_partitions: default_kafka_max_describe_topic_partitions(),
             sasl: KafkaSasl::default(),
             auto_create_topics_enable: default_auto_create_topics_enable(),
+            schema_dead_letter_topic: String::new(),
         }
     }
 }
@@ -810,12 +864,17 @@ fn default_amqp_tcp_port() -> u32 {
 pub struct AmqpRuntime {
     #[serde(default = "default_amqp_tcp_port")]
     pub tcp_port: u32,
+    /// Topic that payloads rejected by a bound schema are written to, with the
+    /// rejection reason in the record headers. Empty drops them.
+    #[serde(default)]
+    pub schema_dead_letter_topic: String,
 }
 
 impl Default for AmqpRuntime {
     fn default() -> Self {
         AmqpRuntime {
             tcp_port: default_amqp_tcp_port(),
+            schema_dead_letter_topic: String::new(),
         }
     }
 }
@@ -900,6 +959,10 @@ pub struct NatsRuntime {
     pub push_queue_thread_num: usize,
     #[serde(default = "default_nats_mq9_mailbox_ttl")]
     pub mq9_mailbox_default_ttl: u64,
+    /// Topic that payloads rejected by a bound schema are written to, with the
+    /// rejection reason in the record headers. Empty drops them.
+    #[serde(default)]
+    pub schema_dead_letter_topic: String,
 }
 
 impl Default for NatsRuntime {
@@ -918,6 +981,7 @@ fn default() -> Self {
             push_thread_num: default_nats_push_thread_num(),
             push_queue_thread_num: default_nats_push_queue_thread_num(),
             mq9_mailbox_default_ttl: default_nats_mq9_mailbox_ttl(),
+            schema_dead_letter_topic: String::new(),
         }
     }
 }
@@ -72,6 +72,15 @@ pub fn default_network() -> Network {
         queue_size: 5000,
         tls_cert: "./config/certs/cert.pem".to_string(),
         tls_key: "./config/certs/key.pem".to_string(),
+        tls_client_auth: default_tls_client_auth(),
+        tls_ca_cert: String::new(),
+        tls_crls: Vec::new(),
+        tls_ocsp_response: String::new(),
+        tls_peer_cert_as_username: String::new(),
+        tls_peer_cert_as_client_id: String::new(),
+        tls_reload_interval_sec: default_tls_reload_interval_sec(),
+        proxy_protocol: Vec::new(),
+        proxy_protocol_timeout_ms: default_proxy_protocol_timeout_ms(),
     }
 }
 
@@ -207,6 +216,15 @@ pub fn default_tls_cert() -> String {
 pub fn default_tls_key() -> String {
     "./config/certs/key.pem".to_string()
 }
+pub fn default_tls_client_auth() -> String {
+    "none".to_string()
+}
+pub fn default_tls_reload_interval_sec() -> u64 {
+    60
+}
+pub fn default_proxy_protocol_timeout_ms() -> u64 {
+    3000
+}
 pub fn default_channels_per_address() -> usize {
     4
 }
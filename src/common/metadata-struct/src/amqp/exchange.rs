@@ -73,6 +73,10 @@ pub struct AmqpExchange {
     /// Declare-time arguments (AMQP FieldTable), e.g. "alternate-exchange".
     pub arguments: HashMap<String, String>,
     pub create_time: u64,
+    /// An `x-delayed-message` exchange: holds each publish for its `x-delay`
+    /// header before routing it as `exchange_type` (its `x-delayed-type`).
+    #[serde(default)]
+    pub delayed: bool,
 }
 
 impl AmqpExchange {
@@ -95,9 +99,15 @@ pub fn new(
             internal,
             arguments,
             create_time: now_second(),
+            delayed: false,
         }
     }
 
+    pub fn with_delayed(mut self, delayed: bool) -> Self {
+        self.delayed = delayed;
+        self
+    }
+
     pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
         Ok(serde_json::to_vec(&self)?)
     }
@@ -127,6 +137,24 @@ fn test_encode_decode() {
         assert_eq!(exchange, decoded);
     }
 
+    #[test]
+    fn test_delayed_defaults_to_false_for_stored_exchanges() {
+        let exchange = AmqpExchange::new(
+            "t1",
+            "delayed.exchange",
+            AmqpExchangeType::Direct,
+            true,
+            false,
+            false,
+            HashMap::new(),
+        );
+        let mut value: serde_json::Value =
+            serde_json::from_slice(&exchange.with_delayed(true).encode().unwrap()).unwrap();
+        value.as_object_mut().unwrap().remove("delayed");
+        let decoded = AmqpExchange::decode(&serde_json::to_vec(&value).unwrap()).unwrap();
+        assert!(!decoded.delayed);
+    }
+
     #[test]
     fn test_exchange_type_round_trip() {
         for t in [
@@ -23,6 +23,31 @@ pub struct SecurityUser {
     pub salt: Option<String>,
     pub is_superuser: bool,
     pub create_time: u64,
+    /// SCRAM credentials derived when the user was created, one per
+    /// mechanism, so SCRAM works without keeping the password around.
+    pub scram_credentials: Vec<ScramCredential>,
+}
+
+/// RFC 5802 server-side credentials for one SCRAM mechanism.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ScramCredential {
+    /// E.g. `SCRAM-SHA-256`.
+    pub mechanism: String,
+    pub salt: Vec<u8>,
+    pub iterations: u32,
+    pub stored_key: Vec<u8>,
+    pub server_key: Vec<u8>,
+}
+
+// A user as stored before `scram_credentials` existed.
+#[derive(Deserialize)]
+struct LegacySecurityUser {
+    tenant: String,
+    username: String,
+    password: String,
+    salt: Option<String>,
+    is_superuser: bool,
+    create_time: u64,
 }
 
 impl SecurityUser {
@@ -30,7 +55,73 @@ pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
         serialize::serialize(self)
     }
 
+    /// Also reads users stored before SCRAM credentials were added; they
+    /// come back without any.
     pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
-        serialize::deserialize(data)
+        serialize::deserialize(data).or_else(|e| {
+            let legacy: LegacySecurityUser = serialize::deserialize(data).map_err(|_| e)?;
+            Ok(SecurityUser {
+                tenant: legacy.tenant,
+                username: legacy.username,
+                password: legacy.password,
+                salt: legacy.salt,
+                is_superuser: legacy.is_superuser,
+                create_time: legacy.create_time,
+                scram_credentials: Vec::new(),
+            })
+        })
+    }
+
+    pub fn scram_credential(&self, mechanism: &str) -> Option<&ScramCredential> {
+        self.scram_credentials
+            .iter()
+            .find(|c| c.mechanism == mechanism)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn users_stored_before_scram_credentials_still_decode() {
+        #[derive(Serialize)]
+        struct Legacy<'a> {
+            tenant: &'a str,
+            username: &'a str,
+            password: &'a str,
+            salt: Option<String>,
+            is_superuser: bool,
+            create_time: u64,
+        }
+        let data = serialize::serialize(&Legacy {
+            tenant: "default",
+            username: "alice",
+            password: "pencil",
+            salt: None,
+            is_superuser: true,
+            create_time: 7,
+        })
+        .unwrap();
+
+        let user = SecurityUser::decode(&data).unwrap();
+        assert_eq!(user.username, "alice");
+        assert!(user.is_superuser);
+        assert!(user.scram_credentials.is_empty());
+
+        let current = SecurityUser {
+            scram_credentials: vec![ScramCredential {
+                mechanism: "SCRAM-SHA-256".to_string(),
+                salt: vec![1, 2],
+                iterations: 4096,
+                stored_key: vec![3],
+                server_key: vec![4],
+            }],
+            ..user
+        };
+        let decoded = SecurityUser::decode(&current.encode().unwrap()).unwrap();
+        assert_eq!(decoded, current);
+        assert!(decoded.scram_credential("SCRAM-SHA-256").is_some());
+        assert!(decoded.scram_credential("SCRAM-SHA-512").is_none());
     }
 }
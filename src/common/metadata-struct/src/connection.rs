@@ -48,6 +48,40 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
+/// The identity in a client certificate the TLS handshake verified.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct PeerCertIdentity {
+    pub common_name: Option<String>,
+    // DNS names, e-mail addresses and URIs from the subjectAltName extension, in order.
+    pub subject_alt_names: Vec<String>,
+}
+
+/// The TLS details a load balancer that terminated TLS passed on in its
+/// PROXY protocol v2 header.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct ProxySslInfo {
+    pub version: Option<String>,
+    pub cipher: Option<String>,
+    pub sig_alg: Option<String>,
+    pub key_alg: Option<String>,
+    // Common name of the client certificate, if the client sent one.
+    pub client_cn: Option<String>,
+    // The client sent a certificate and the load balancer verified it.
+    pub client_cert_verified: bool,
+}
+
+/// What a load balancer's PROXY protocol header said about a connection.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProxyInfo {
+    // The load balancer's own address; `NetworkConnection::addr` is the client's.
+    pub proxy_addr: SocketAddr,
+    // The address the client connected to on the load balancer.
+    pub destination_addr: Option<SocketAddr>,
+    pub alpn: Option<String>,
+    pub authority: Option<String>,
+    pub ssl: Option<ProxySslInfo>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct NetworkConnection {
     pub connection_type: NetworkConnectionType,
@@ -57,6 +91,10 @@ pub struct NetworkConnection {
     pub last_heartbeat_time: u64,
     pub create_time: u64,
     pub mark_close: u64,
+    #[serde(default)]
+    pub peer_cert: Option<PeerCertIdentity>,
+    #[serde(default)]
+    pub proxy: Option<ProxyInfo>,
     #[serde(skip_serializing, skip_deserializing)]
     pub connection_stop_sx: Option<mpsc::Sender<bool>>,
 }
@@ -77,6 +115,8 @@ pub fn new(
             create_time: now_second(),
             connection_stop_sx,
             mark_close: 0,
+            peer_cert: None,
+            proxy: None,
         }
     }
 
@@ -84,6 +124,14 @@ pub fn connection_id(&self) -> u64 {
         self.connection_id
     }
 
+    pub fn set_peer_cert(&mut self, peer_cert: Option<PeerCertIdentity>) {
+        self.peer_cert = peer_cert;
+    }
+
+    pub fn set_proxy(&mut self, proxy: Option<ProxyInfo>) {
+        self.proxy = proxy;
+    }
+
     pub fn set_protocol(&mut self, protocol: RobustMQProtocol) {
         self.protocol = Some(protocol);
     }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::DEFAULT_SINK_OFFSET_TABLE;
 use common_base::error::common::CommonError;
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +28,10 @@ fn default_timeout_secs() -> u64 {
     15
 }
 
+fn default_offset_table() -> String {
+    DEFAULT_SINK_OFFSET_TABLE.to_string()
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct CassandraConnectorConfig {
     pub nodes: Vec<String>,
@@ -47,6 +52,11 @@ pub struct CassandraConnectorConfig {
 
     #[serde(default = "default_timeout_secs")]
     pub timeout_secs: u64,
+
+    #[serde(default)]
+    pub enable_exactly_once: bool,
+    #[serde(default = "default_offset_table")]
+    pub offset_table: String,
 }
 
 impl Default for CassandraConnectorConfig {
@@ -60,6 +70,8 @@ fn default() -> Self {
             password: String::new(),
             replication_factor: default_replication_factor(),
             timeout_secs: default_timeout_secs(),
+            enable_exactly_once: false,
+            offset_table: default_offset_table(),
         }
     }
 }
@@ -104,6 +116,17 @@ pub fn validate(&self) -> Result<(), CommonError> {
             ));
         }
 
+        if self.offset_table.is_empty()
+            || !self
+                .offset_table
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(CommonError::CommonError(
+                "offset_table can only contain letters, numbers and underscores".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
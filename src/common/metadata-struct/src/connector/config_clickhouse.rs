@@ -32,6 +32,9 @@ pub struct ClickHouseConnectorConfig {
 
     #[serde(default = "default_timeout_secs")]
     pub timeout_secs: u64,
+
+    #[serde(default)]
+    pub enable_exactly_once: bool,
 }
 
 fn default_pool_size() -> u32 {
@@ -52,6 +55,7 @@ fn default() -> Self {
             password: String::new(),
             pool_size: default_pool_size(),
             timeout_secs: default_timeout_secs(),
+            enable_exactly_once: false,
         }
     }
 }
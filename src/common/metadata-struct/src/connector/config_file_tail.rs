@@ -0,0 +1,94 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::connector::source::SourceStartPosition;
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FileTailConnectorConfig {
+    pub local_file_path: String,
+
+    #[serde(default)]
+    pub start_position: SourceStartPosition,
+
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_poll_interval_ms() -> u64 {
+    500
+}
+
+impl Default for FileTailConnectorConfig {
+    fn default() -> Self {
+        Self {
+            local_file_path: String::new(),
+            start_position: SourceStartPosition::default(),
+            batch_size: default_batch_size(),
+            poll_interval_ms: default_poll_interval_ms(),
+        }
+    }
+}
+
+impl FileTailConnectorConfig {
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if self.local_file_path.is_empty() {
+            return Err(CommonError::CommonError(
+                "local_file_path cannot be empty".to_string(),
+            ));
+        }
+
+        if self.local_file_path.len() > 4096 {
+            return Err(CommonError::CommonError(
+                "local_file_path length cannot exceed 4096 characters".to_string(),
+            ));
+        }
+
+        let path = Path::new(&self.local_file_path);
+        if !path.is_absolute() {
+            return Err(CommonError::CommonError(
+                "local_file_path must be an absolute path".to_string(),
+            ));
+        }
+
+        if path.components().any(|c| c.as_os_str() == "..") {
+            return Err(CommonError::CommonError(
+                "local_file_path cannot contain '..' directory traversal".to_string(),
+            ));
+        }
+
+        if self.batch_size == 0 || self.batch_size > 10000 {
+            return Err(CommonError::CommonError(
+                "batch_size must be between 1 and 10000".to_string(),
+            ));
+        }
+
+        if self.poll_interval_ms == 0 || self.poll_interval_ms > 60000 {
+            return Err(CommonError::CommonError(
+                "poll_interval_ms must be between 1 and 60000".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::connector::source::SourceStartPosition;
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KafkaSourceConnectorConfig {
+    pub bootstrap_servers: String,
+    pub topic: String,
+
+    #[serde(default = "default_group_id")]
+    pub group_id: String,
+
+    #[serde(default)]
+    pub start_position: SourceStartPosition,
+
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    #[serde(default = "default_poll_timeout_ms")]
+    pub poll_timeout_ms: u64,
+}
+
+fn default_group_id() -> String {
+    "robustmq-connector".to_string()
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_poll_timeout_ms() -> u64 {
+    1000
+}
+
+impl Default for KafkaSourceConnectorConfig {
+    fn default() -> Self {
+        Self {
+            bootstrap_servers: String::new(),
+            topic: String::new(),
+            group_id: default_group_id(),
+            start_position: SourceStartPosition::default(),
+            batch_size: default_batch_size(),
+            poll_timeout_ms: default_poll_timeout_ms(),
+        }
+    }
+}
+
+impl KafkaSourceConnectorConfig {
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if self.bootstrap_servers.is_empty() {
+            return Err(CommonError::CommonError(
+                "bootstrap_servers cannot be empty".to_string(),
+            ));
+        }
+
+        if self.bootstrap_servers.len() > 1024 {
+            return Err(CommonError::CommonError(
+                "bootstrap_servers length cannot exceed 1024 characters".to_string(),
+            ));
+        }
+
+        if self.topic.is_empty() {
+            return Err(CommonError::CommonError(
+                "topic cannot be empty".to_string(),
+            ));
+        }
+
+        if self.topic.len() > 256 {
+            return Err(CommonError::CommonError(
+                "topic length cannot exceed 256 characters".to_string(),
+            ));
+        }
+
+        if self.group_id.is_empty() || self.group_id.len() > 256 {
+            return Err(CommonError::CommonError(
+                "group_id length must be between 1 and 256 characters".to_string(),
+            ));
+        }
+
+        if self.batch_size == 0 || self.batch_size > 10000 {
+            return Err(CommonError::CommonError(
+                "batch_size must be between 1 and 10000".to_string(),
+            ));
+        }
+
+        if self.poll_timeout_ms == 0 || self.poll_timeout_ms > 60000 {
+            return Err(CommonError::CommonError(
+                "poll_timeout_ms must be between 1 and 60000".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
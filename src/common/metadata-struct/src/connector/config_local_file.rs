@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::output::{
+    default_roll_interval_secs, default_roll_size_mb, validate_output, OutputCompression,
+    OutputFormat, PartitionBy,
+};
 use common_base::error::common::CommonError;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -26,19 +30,47 @@ pub enum RotationStrategy {
     Daily,
 }
 
-#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+/// With `format` other than `json`, `local_file_path` names the output
+/// directory (its parent) and file name prefix (its stem); rolled files are
+/// written next to it instead of appending to it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct LocalFileConnectorConfig {
     pub local_file_path: String,
     #[serde(default)]
     pub rotation_strategy: RotationStrategy,
     #[serde(default = "default_max_size_gb")]
     pub max_size_gb: u64,
+    #[serde(default)]
+    pub format: OutputFormat,
+    #[serde(default)]
+    pub compression: OutputCompression,
+    #[serde(default)]
+    pub partition_by: PartitionBy,
+    #[serde(default = "default_roll_size_mb")]
+    pub roll_size_mb: u64,
+    #[serde(default = "default_roll_interval_secs")]
+    pub roll_interval_secs: u64,
 }
 
 fn default_max_size_gb() -> u64 {
     1
 }
 
+impl Default for LocalFileConnectorConfig {
+    fn default() -> Self {
+        Self {
+            local_file_path: String::new(),
+            rotation_strategy: RotationStrategy::default(),
+            max_size_gb: default_max_size_gb(),
+            format: OutputFormat::default(),
+            compression: OutputCompression::default(),
+            partition_by: PartitionBy::default(),
+            roll_size_mb: default_roll_size_mb(),
+            roll_interval_secs: default_roll_interval_secs(),
+        }
+    }
+}
+
 impl LocalFileConnectorConfig {
     pub fn validate(&self) -> Result<(), CommonError> {
         if self.local_file_path.is_empty() {
@@ -99,6 +131,13 @@ pub fn validate(&self) -> Result<(), CommonError> {
             ));
         }
 
+        validate_output(
+            &self.format,
+            &self.compression,
+            self.roll_size_mb,
+            self.roll_interval_secs,
+        )?;
+
         if let Some(parent) = path.parent() {
             if parent.exists() {
                 let metadata = std::fs::metadata(parent).map_err(|e| {
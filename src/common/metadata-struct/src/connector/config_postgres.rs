@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::DEFAULT_SINK_OFFSET_TABLE;
 use common_base::error::common::CommonError;
 use serde::{Deserialize, Serialize};
 
@@ -53,6 +54,8 @@ fn default() -> Self {
             enable_batch_insert: None,
             enable_upsert: None,
             conflict_columns: None,
+            enable_exactly_once: None,
+            offset_table: None,
             connect_timeout_secs: default_connect_timeout_secs(),
             acquire_timeout_secs: default_acquire_timeout_secs(),
             idle_timeout_secs: default_idle_timeout_secs(),
@@ -76,6 +79,8 @@ pub struct PostgresConnectorConfig {
     pub enable_batch_insert: Option<bool>,
     pub enable_upsert: Option<bool>,
     pub conflict_columns: Option<String>,
+    pub enable_exactly_once: Option<bool>,
+    pub offset_table: Option<String>,
 
     #[serde(default = "default_connect_timeout_secs")]
     pub connect_timeout_secs: u64,
@@ -112,6 +117,16 @@ pub fn is_upsert_enabled(&self) -> bool {
         self.enable_upsert.unwrap_or(false)
     }
 
+    pub fn is_exactly_once_enabled(&self) -> bool {
+        self.enable_exactly_once.unwrap_or(false)
+    }
+
+    pub fn offset_table(&self) -> &str {
+        self.offset_table
+            .as_deref()
+            .unwrap_or(DEFAULT_SINK_OFFSET_TABLE)
+    }
+
     pub fn validate(&self) -> Result<(), CommonError> {
         if self.host.is_empty() {
             return Err(CommonError::CommonError("host cannot be empty".to_string()));
@@ -211,6 +226,23 @@ pub fn validate(&self) -> Result<(), CommonError> {
             }
         }
 
+        if let Some(offset_table) = &self.offset_table {
+            if offset_table.is_empty() || offset_table.len() > 256 {
+                return Err(CommonError::CommonError(
+                    "offset_table length must be between 1 and 256 characters".to_string(),
+                ));
+            }
+            if !offset_table
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+            {
+                return Err(CommonError::CommonError(
+                    "offset_table can only contain letters, numbers, underscores and dots"
+                        .to_string(),
+                ));
+            }
+        }
+
         if self.connect_timeout_secs == 0 || self.connect_timeout_secs > 300 {
             return Err(CommonError::CommonError(
                 "connect_timeout_secs must be between 1 and 300 seconds".to_string(),
@@ -0,0 +1,147 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PostgresCDCConnectorConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub database: String,
+    pub username: String,
+    pub password: String,
+    pub slot_name: String,
+
+    #[serde(default = "default_plugin")]
+    pub plugin: String,
+
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+}
+
+fn default_port() -> u16 {
+    5432
+}
+
+fn default_plugin() -> String {
+    "test_decoding".to_string()
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+impl Default for PostgresCDCConnectorConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: default_port(),
+            database: String::new(),
+            username: String::new(),
+            password: String::new(),
+            slot_name: String::new(),
+            plugin: default_plugin(),
+            batch_size: default_batch_size(),
+            poll_interval_ms: default_poll_interval_ms(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+        }
+    }
+}
+
+impl PostgresCDCConnectorConfig {
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if self.host.is_empty() {
+            return Err(CommonError::CommonError("host cannot be empty".to_string()));
+        }
+
+        if self.port == 0 {
+            return Err(CommonError::CommonError("port cannot be 0".to_string()));
+        }
+
+        if self.database.is_empty() {
+            return Err(CommonError::CommonError(
+                "database cannot be empty".to_string(),
+            ));
+        }
+
+        if self.username.is_empty() {
+            return Err(CommonError::CommonError(
+                "username cannot be empty".to_string(),
+            ));
+        }
+
+        // Slot and plugin names are interpolated into the replication
+        // functions, keep them to plain identifiers.
+        for (field, value) in [("slot_name", &self.slot_name), ("plugin", &self.plugin)] {
+            if value.is_empty() || value.len() > 63 {
+                return Err(CommonError::CommonError(format!(
+                    "{} length must be between 1 and 63 characters",
+                    field
+                )));
+            }
+            if !value
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            {
+                return Err(CommonError::CommonError(format!(
+                    "{} may only contain lowercase letters, digits and underscores",
+                    field
+                )));
+            }
+        }
+
+        if self.batch_size == 0 || self.batch_size > 10000 {
+            return Err(CommonError::CommonError(
+                "batch_size must be between 1 and 10000".to_string(),
+            ));
+        }
+
+        if self.poll_interval_ms == 0 || self.poll_interval_ms > 60000 {
+            return Err(CommonError::CommonError(
+                "poll_interval_ms must be between 1 and 60000".to_string(),
+            ));
+        }
+
+        if self.connect_timeout_secs == 0 || self.connect_timeout_secs > 300 {
+            return Err(CommonError::CommonError(
+                "connect_timeout_secs must be between 1 and 300".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn connection_url(&self) -> String {
+        format!(
+            "postgres://{}:{}@{}:{}/{}",
+            self.username, self.password, self.host, self.port, self.database
+        )
+    }
+}
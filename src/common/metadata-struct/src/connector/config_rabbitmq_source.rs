@@ -0,0 +1,161 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RabbitMQSourceConnectorConfig {
+    pub server: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    #[serde(default = "default_virtual_host")]
+    pub virtual_host: String,
+    pub queue: String,
+    #[serde(default)]
+    pub enable_tls: bool,
+
+    #[serde(default = "default_connection_timeout_secs")]
+    pub connection_timeout_secs: u64,
+    #[serde(default = "default_heartbeat_secs")]
+    pub heartbeat_secs: u16,
+
+    #[serde(default = "default_prefetch_count")]
+    pub prefetch_count: u16,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_batch_wait_ms")]
+    pub batch_wait_ms: u64,
+}
+
+fn default_port() -> u16 {
+    5672
+}
+
+fn default_virtual_host() -> String {
+    "/".to_string()
+}
+
+fn default_connection_timeout_secs() -> u64 {
+    30
+}
+
+fn default_heartbeat_secs() -> u16 {
+    60
+}
+
+fn default_prefetch_count() -> u16 {
+    200
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_batch_wait_ms() -> u64 {
+    500
+}
+
+impl Default for RabbitMQSourceConnectorConfig {
+    fn default() -> Self {
+        Self {
+            server: String::new(),
+            port: default_port(),
+            username: String::new(),
+            password: String::new(),
+            virtual_host: default_virtual_host(),
+            queue: String::new(),
+            enable_tls: false,
+            connection_timeout_secs: default_connection_timeout_secs(),
+            heartbeat_secs: default_heartbeat_secs(),
+            prefetch_count: default_prefetch_count(),
+            batch_size: default_batch_size(),
+            batch_wait_ms: default_batch_wait_ms(),
+        }
+    }
+}
+
+impl RabbitMQSourceConnectorConfig {
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if self.server.is_empty() {
+            return Err(CommonError::CommonError(
+                "server cannot be empty".to_string(),
+            ));
+        }
+
+        if self.server.len() > 512 {
+            return Err(CommonError::CommonError(
+                "server length cannot exceed 512 characters".to_string(),
+            ));
+        }
+
+        if self.port == 0 {
+            return Err(CommonError::CommonError(
+                "port must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.username.is_empty() {
+            return Err(CommonError::CommonError(
+                "username cannot be empty".to_string(),
+            ));
+        }
+
+        if self.queue.is_empty() {
+            return Err(CommonError::CommonError(
+                "queue cannot be empty".to_string(),
+            ));
+        }
+
+        if self.queue.len() > 256 {
+            return Err(CommonError::CommonError(
+                "queue length cannot exceed 256 characters".to_string(),
+            ));
+        }
+
+        if self.connection_timeout_secs == 0 || self.connection_timeout_secs > 300 {
+            return Err(CommonError::CommonError(
+                "connection_timeout_secs must be between 1 and 300 seconds".to_string(),
+            ));
+        }
+
+        if self.heartbeat_secs > 300 {
+            return Err(CommonError::CommonError(
+                "heartbeat_secs cannot exceed 300 seconds".to_string(),
+            ));
+        }
+
+        if self.batch_size == 0 || self.batch_size > 10000 {
+            return Err(CommonError::CommonError(
+                "batch_size must be between 1 and 10000".to_string(),
+            ));
+        }
+
+        if (self.prefetch_count as usize) < self.batch_size {
+            return Err(CommonError::CommonError(
+                "prefetch_count cannot be smaller than batch_size".to_string(),
+            ));
+        }
+
+        if self.batch_wait_ms == 0 || self.batch_wait_ms > 60000 {
+            return Err(CommonError::CommonError(
+                "batch_wait_ms must be between 1 and 60000".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::connector::source::SourceStartPosition;
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RedisStreamConnectorConfig {
+    pub server: String,
+    pub stream_key: String,
+    #[serde(default)]
+    pub database: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub tls_enabled: bool,
+    #[serde(default)]
+    pub start_position: SourceStartPosition,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_block_ms")]
+    pub block_ms: u64,
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_block_ms() -> u64 {
+    1000
+}
+
+fn default_connect_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for RedisStreamConnectorConfig {
+    fn default() -> Self {
+        Self {
+            server: String::new(),
+            stream_key: String::new(),
+            database: 0,
+            username: None,
+            password: None,
+            tls_enabled: false,
+            start_position: SourceStartPosition::default(),
+            batch_size: default_batch_size(),
+            block_ms: default_block_ms(),
+            connect_timeout_ms: default_connect_timeout_ms(),
+        }
+    }
+}
+
+impl RedisStreamConnectorConfig {
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if self.server.is_empty() {
+            return Err(CommonError::CommonError(
+                "server cannot be empty".to_string(),
+            ));
+        }
+
+        if self.server.len() > 1024 {
+            return Err(CommonError::CommonError(
+                "server length cannot exceed 1024 characters".to_string(),
+            ));
+        }
+
+        if self.stream_key.is_empty() {
+            return Err(CommonError::CommonError(
+                "stream_key cannot be empty".to_string(),
+            ));
+        }
+
+        if self.stream_key.len() > 1024 {
+            return Err(CommonError::CommonError(
+                "stream_key length cannot exceed 1024 characters".to_string(),
+            ));
+        }
+
+        if self.database > 15 {
+            return Err(CommonError::CommonError(
+                "database must be between 0 and 15".to_string(),
+            ));
+        }
+
+        if self.batch_size == 0 || self.batch_size > 10000 {
+            return Err(CommonError::CommonError(
+                "batch_size must be between 1 and 10000".to_string(),
+            ));
+        }
+
+        if self.block_ms == 0 || self.block_ms > 60000 {
+            return Err(CommonError::CommonError(
+                "block_ms must be between 1 and 60000".to_string(),
+            ));
+        }
+
+        if self.connect_timeout_ms == 0 {
+            return Err(CommonError::CommonError(
+                "connect_timeout_ms must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::output::{
+    default_roll_interval_secs, default_roll_size_mb, validate_output, OutputCompression,
+    OutputFormat, PartitionBy,
+};
 use common_base::error::common::CommonError;
 use serde::{Deserialize, Serialize};
 
@@ -40,6 +44,21 @@ pub struct S3ConnectorConfig {
 
     #[serde(default = "default_file_extension")]
     pub file_extension: String,
+
+    #[serde(default)]
+    pub format: OutputFormat,
+
+    #[serde(default)]
+    pub compression: OutputCompression,
+
+    #[serde(default)]
+    pub partition_by: PartitionBy,
+
+    #[serde(default = "default_roll_size_mb")]
+    pub roll_size_mb: u64,
+
+    #[serde(default = "default_roll_interval_secs")]
+    pub roll_interval_secs: u64,
 }
 
 fn default_object_key_prefix() -> String {
@@ -62,6 +81,11 @@ fn default() -> Self {
             root: String::new(),
             object_key_prefix: default_object_key_prefix(),
             file_extension: default_file_extension(),
+            format: OutputFormat::default(),
+            compression: OutputCompression::default(),
+            partition_by: PartitionBy::default(),
+            roll_size_mb: default_roll_size_mb(),
+            roll_interval_secs: default_roll_interval_secs(),
         }
     }
 }
@@ -149,6 +173,13 @@ pub fn validate(&self) -> Result<(), CommonError> {
             ));
         }
 
+        validate_output(
+            &self.format,
+            &self.compression,
+            self.roll_size_mb,
+            self.roll_interval_secs,
+        )?;
+
         Ok(())
     }
 }
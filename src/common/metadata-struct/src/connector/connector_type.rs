@@ -19,14 +19,17 @@
 
 use crate::connector::{
     config_cassandra::CassandraConnectorConfig, config_clickhouse::ClickHouseConnectorConfig,
-    config_elasticsearch::ElasticsearchConnectorConfig,
+    config_elasticsearch::ElasticsearchConnectorConfig, config_file_tail::FileTailConnectorConfig,
     config_greptimedb::GreptimeDBConnectorConfig, config_influxdb::InfluxDBConnectorConfig,
-    config_kafka::KafkaConnectorConfig, config_local_file::LocalFileConnectorConfig,
-    config_mongodb::MongoDBConnectorConfig, config_mqtt::MqttBridgeConnectorConfig,
+    config_kafka::KafkaConnectorConfig, config_kafka_source::KafkaSourceConnectorConfig,
+    config_local_file::LocalFileConnectorConfig, config_mongodb::MongoDBConnectorConfig,
+    config_mqtt::MqttBridgeConnectorConfig, config_mqtt_source::MqttBridgeSourceConnectorConfig,
     config_mysql::MySQLConnectorConfig, config_opentsdb::OpenTSDBConnectorConfig,
-    config_postgres::PostgresConnectorConfig, config_pulsar::PulsarConnectorConfig,
-    config_rabbitmq::RabbitMQConnectorConfig, config_redis::RedisConnectorConfig,
-    config_s3::S3ConnectorConfig, config_webhook::WebhookConnectorConfig,
+    config_postgres::PostgresConnectorConfig, config_postgres_cdc::PostgresCDCConnectorConfig,
+    config_pulsar::PulsarConnectorConfig, config_rabbitmq::RabbitMQConnectorConfig,
+    config_rabbitmq_source::RabbitMQSourceConnectorConfig, config_redis::RedisConnectorConfig,
+    config_redis_stream::RedisStreamConnectorConfig, config_s3::S3ConnectorConfig,
+    config_webhook::WebhookConnectorConfig,
 };
 
 pub const CONNECTOR_TYPE_FILE: &str = "file";
@@ -46,6 +49,12 @@
 pub const CONNECTOR_TYPE_INFLUXDB: &str = "influxdb";
 pub const CONNECTOR_TYPE_CASSANDRA: &str = "cassandra";
 pub const CONNECTOR_TYPE_S3: &str = "s3";
+pub const CONNECTOR_TYPE_KAFKA_SOURCE: &str = "kafka_source";
+pub const CONNECTOR_TYPE_MQTT_BRIDGE_SOURCE: &str = "mqtt_source";
+pub const CONNECTOR_TYPE_POSTGRES_CDC: &str = "postgres_cdc";
+pub const CONNECTOR_TYPE_REDIS_STREAM: &str = "redis_stream";
+pub const CONNECTOR_TYPE_RABBITMQ_SOURCE: &str = "rabbitmq_source";
+pub const CONNECTOR_TYPE_FILE_TAIL: &str = "file_tail";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum ConnectorType {
@@ -66,6 +75,12 @@ pub enum ConnectorType {
     InfluxDB(InfluxDBConnectorConfig),
     Cassandra(CassandraConnectorConfig),
     S3(S3ConnectorConfig),
+    KafkaSource(KafkaSourceConnectorConfig),
+    MqttBridgeSource(MqttBridgeSourceConnectorConfig),
+    PostgresCDC(PostgresCDCConnectorConfig),
+    RedisStream(RedisStreamConnectorConfig),
+    RabbitMQSource(RabbitMQSourceConnectorConfig),
+    FileTail(FileTailConnectorConfig),
 }
 
 impl Default for ConnectorType {
@@ -94,8 +109,28 @@ pub fn as_str(&self) -> &'static str {
             ConnectorType::InfluxDB(_) => CONNECTOR_TYPE_INFLUXDB,
             ConnectorType::Cassandra(_) => CONNECTOR_TYPE_CASSANDRA,
             ConnectorType::S3(_) => CONNECTOR_TYPE_S3,
+            ConnectorType::KafkaSource(_) => CONNECTOR_TYPE_KAFKA_SOURCE,
+            ConnectorType::MqttBridgeSource(_) => CONNECTOR_TYPE_MQTT_BRIDGE_SOURCE,
+            ConnectorType::PostgresCDC(_) => CONNECTOR_TYPE_POSTGRES_CDC,
+            ConnectorType::RedisStream(_) => CONNECTOR_TYPE_REDIS_STREAM,
+            ConnectorType::RabbitMQSource(_) => CONNECTOR_TYPE_RABBITMQ_SOURCE,
+            ConnectorType::FileTail(_) => CONNECTOR_TYPE_FILE_TAIL,
         }
     }
+
+    /// Source connectors read from an external system and write into the
+    /// connector's topic, every other type reads the topic and sinks it out.
+    pub fn is_source(&self) -> bool {
+        matches!(
+            self,
+            ConnectorType::KafkaSource(_)
+                | ConnectorType::MqttBridgeSource(_)
+                | ConnectorType::PostgresCDC(_)
+                | ConnectorType::RedisStream(_)
+                | ConnectorType::RabbitMQSource(_)
+                | ConnectorType::FileTail(_)
+        )
+    }
 }
 
 impl Display for ConnectorType {
@@ -150,6 +185,28 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             Ok(ConnectorType::Cassandra(CassandraConnectorConfig::default()))
         } else if s.eq_ignore_ascii_case(CONNECTOR_TYPE_S3) {
             Ok(ConnectorType::S3(S3ConnectorConfig::default()))
+        } else if s.eq_ignore_ascii_case(CONNECTOR_TYPE_KAFKA_SOURCE) {
+            Ok(ConnectorType::KafkaSource(
+                KafkaSourceConnectorConfig::default(),
+            ))
+        } else if s.eq_ignore_ascii_case(CONNECTOR_TYPE_MQTT_BRIDGE_SOURCE) {
+            Ok(ConnectorType::MqttBridgeSource(
+                MqttBridgeSourceConnectorConfig::default(),
+            ))
+        } else if s.eq_ignore_ascii_case(CONNECTOR_TYPE_POSTGRES_CDC) {
+            Ok(ConnectorType::PostgresCDC(
+                PostgresCDCConnectorConfig::default(),
+            ))
+        } else if s.eq_ignore_ascii_case(CONNECTOR_TYPE_REDIS_STREAM) {
+            Ok(ConnectorType::RedisStream(
+                RedisStreamConnectorConfig::default(),
+            ))
+        } else if s.eq_ignore_ascii_case(CONNECTOR_TYPE_RABBITMQ_SOURCE) {
+            Ok(ConnectorType::RabbitMQSource(
+                RabbitMQSourceConnectorConfig::default(),
+            ))
+        } else if s.eq_ignore_ascii_case(CONNECTOR_TYPE_FILE_TAIL) {
+            Ok(ConnectorType::FileTail(FileTailConnectorConfig::default()))
         } else {
             Err(CommonError::IneligibleConnectorType(s.to_string()))
         }
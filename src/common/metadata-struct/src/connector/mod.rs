@@ -20,26 +20,38 @@
 pub mod config_cassandra;
 pub mod config_clickhouse;
 pub mod config_elasticsearch;
+pub mod config_file_tail;
 pub mod config_greptimedb;
 pub mod config_influxdb;
 pub mod config_kafka;
+pub mod config_kafka_source;
 pub mod config_local_file;
 pub mod config_mongodb;
 pub mod config_mqtt;
+pub mod config_mqtt_source;
 pub mod config_mysql;
 pub mod config_opentsdb;
 pub mod config_postgres;
+pub mod config_postgres_cdc;
 pub mod config_pulsar;
 pub mod config_rabbitmq;
+pub mod config_rabbitmq_source;
 pub mod config_redis;
+pub mod config_redis_stream;
 pub mod config_s3;
 pub mod config_webhook;
 pub mod connector_type;
+pub mod output;
 pub mod rule;
+pub mod source;
 pub mod status;
 
 pub use connector_type::ConnectorType;
 
+/// Table the database sinks keep their per-shard offsets in when exactly-once
+/// delivery is enabled.
+pub const DEFAULT_SINK_OFFSET_TABLE: &str = "robustmq_connector_offsets";
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
 pub struct MQTTConnector {
     pub tenant: String,
@@ -0,0 +1,105 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// File format written by the S3 and local file connectors. `Json` keeps the
+/// original per-connector layout; the other formats are rolled into larger
+/// files and require JSON payloads.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Jsonl,
+    Parquet,
+    Avro,
+}
+
+impl OutputFormat {
+    pub fn is_rolling(&self) -> bool {
+        *self != OutputFormat::Json
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Parquet => "parquet",
+            OutputFormat::Avro => "avro",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Hive-style time partitioning of output files, based on the record time in UTC.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionBy {
+    #[default]
+    None,
+    Hourly,
+    Daily,
+}
+
+pub fn default_roll_size_mb() -> u64 {
+    128
+}
+
+pub fn default_roll_interval_secs() -> u64 {
+    300
+}
+
+pub fn validate_output(
+    format: &OutputFormat,
+    compression: &OutputCompression,
+    roll_size_mb: u64,
+    roll_interval_secs: u64,
+) -> Result<(), CommonError> {
+    if !format.is_rolling() {
+        if *compression != OutputCompression::None {
+            return Err(CommonError::CommonError(
+                "compression requires format jsonl, parquet or avro".to_string(),
+            ));
+        }
+        return Ok(());
+    }
+
+    if roll_size_mb == 0 || roll_size_mb > 4096 {
+        return Err(CommonError::CommonError(
+            "roll_size_mb must be between 1 and 4096".to_string(),
+        ));
+    }
+
+    if roll_interval_secs == 0 || roll_interval_secs > 86400 {
+        return Err(CommonError::CommonError(
+            "roll_interval_secs must be between 1 and 86400".to_string(),
+        ));
+    }
+
+    Ok(())
+}
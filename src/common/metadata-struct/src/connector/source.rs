@@ -0,0 +1,58 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Where a source connector starts reading when it has no stored offset yet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceStartPosition {
+    #[default]
+    Latest,
+    Earliest,
+}
+
+/// Resume position of a source connector. The map is keyed by the upstream
+/// partition (Kafka partition, stream key, file path, replication slot) and
+/// holds the last position that was written into RobustMQ.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct SourceOffset {
+    pub offsets: HashMap<String, String>,
+    pub update_time: u64,
+}
+
+impl SourceOffset {
+    pub fn get(&self, partition: &str) -> Option<&str> {
+        self.offsets.get(partition).map(|s| s.as_str())
+    }
+
+    pub fn set(&mut self, partition: impl Into<String>, offset: impl Into<String>) {
+        self.offsets.insert(partition.into(), offset.into());
+    }
+
+    pub fn encode(&self) -> Result<String, CommonError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn decode(data: &str) -> Result<Self, CommonError> {
+        Ok(serde_json::from_str(data)?)
+    }
+}
+
+/// Meta-service KV key under which a source connector's offset is stored.
+pub fn source_offset_key(tenant: &str, connector_name: &str) -> String {
+    format!("/connector/source_offset/{}/{}", tenant, connector_name)
+}
@@ -15,3 +15,5 @@
 pub mod delegation_token;
 pub mod quota;
 pub mod scram;
+pub mod share_group;
+pub mod transaction;
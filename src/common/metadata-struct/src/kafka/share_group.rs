@@ -0,0 +1,59 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+// Delivery states of a persisted state batch, numbered as in KIP-932. Acquired
+// records are never persisted: after a restart they are simply available again.
+pub const SHARE_DELIVERY_STATE_AVAILABLE: i8 = 0;
+pub const SHARE_DELIVERY_STATE_ACKNOWLEDGED: i8 = 2;
+pub const SHARE_DELIVERY_STATE_ARCHIVED: i8 = 4;
+
+// A run of consecutive in-flight offsets sharing one delivery state and count.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KafkaShareStateBatch {
+    pub first_offset: i64,
+    pub last_offset: i64,
+    pub delivery_state: i8,
+    pub delivery_count: i16,
+}
+
+// Durable state of one share-partition (a topic partition as seen by one share
+// group). Every offset below `start_offset` is done with; offsets in
+// [start_offset, end_offset) are in flight and described by `state_batches`;
+// nothing at or after `end_offset` has been handed out yet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KafkaSharePartitionState {
+    pub tenant: String,
+    pub group_id: String,
+    pub topic: String,
+    pub partition: i32,
+    pub start_offset: i64,
+    pub end_offset: i64,
+    // Bumped whenever the offsets are reset administratively, so a write based
+    // on the old state can be told apart from one based on the new.
+    pub state_epoch: i32,
+    pub state_batches: Vec<KafkaShareStateBatch>,
+}
+
+impl KafkaSharePartitionState {
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        Ok(serde_json::to_vec(&self)?)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        Ok(serde_json::from_slice(data)?)
+    }
+}
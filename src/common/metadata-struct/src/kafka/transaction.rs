@@ -0,0 +1,195 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+use crate::adapter::adapter_offset::AdapterCommitOffset;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KafkaTransactionState {
+    #[default]
+    Empty,
+    Ongoing,
+    PrepareCommit,
+    PrepareAbort,
+    CompleteCommit,
+    CompleteAbort,
+}
+
+impl KafkaTransactionState {
+    // Names as reported by DescribeTransactions/ListTransactions.
+    pub fn name(&self) -> &'static str {
+        match self {
+            KafkaTransactionState::Empty => "Empty",
+            KafkaTransactionState::Ongoing => "Ongoing",
+            KafkaTransactionState::PrepareCommit => "PrepareCommit",
+            KafkaTransactionState::PrepareAbort => "PrepareAbort",
+            KafkaTransactionState::CompleteCommit => "CompleteCommit",
+            KafkaTransactionState::CompleteAbort => "CompleteAbort",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            KafkaTransactionState::Empty,
+            KafkaTransactionState::Ongoing,
+            KafkaTransactionState::PrepareCommit,
+            KafkaTransactionState::PrepareAbort,
+            KafkaTransactionState::CompleteCommit,
+            KafkaTransactionState::CompleteAbort,
+        ]
+        .into_iter()
+        .find(|s| s.name() == name)
+    }
+
+    // A transaction whose records may still be undecided: it holds back the
+    // last stable offset of every partition it touched.
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            KafkaTransactionState::Ongoing
+                | KafkaTransactionState::PrepareCommit
+                | KafkaTransactionState::PrepareAbort
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KafkaTxnPartition {
+    pub topic: String,
+    pub partition: i32,
+    pub shard_name: String,
+    // High watermark when the partition joined the transaction: a lower bound
+    // on the offset of its first transactional record.
+    pub first_offset: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KafkaTxnOffset {
+    pub group_id: String,
+    pub offset: AdapterCommitOffset,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KafkaTransaction {
+    pub transactional_id: String,
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+    pub timeout_ms: i32,
+    pub state: KafkaTransactionState,
+    pub partitions: Vec<KafkaTxnPartition>,
+    pub groups: Vec<String>,
+    // TxnOffsetCommit offsets, applied to the groups only when the
+    // transaction commits.
+    pub pending_offsets: Vec<KafkaTxnOffset>,
+    pub start_time_ms: u64,
+    pub update_time_ms: u64,
+    // Bumped on every write to the transaction log. The log topic has several
+    // partitions, so recovery keeps the highest version per transactional id
+    // rather than relying on read order.
+    pub version: u64,
+}
+
+impl KafkaTransaction {
+    pub fn has_partition(&self, topic: &str, partition: i32) -> bool {
+        self.partitions
+            .iter()
+            .any(|p| p.topic == topic && p.partition == partition)
+    }
+}
+
+// Offset range [first_offset, last_offset] of one shard written by an aborted
+// transaction; last_offset is the offset of its abort marker.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KafkaAbortedTxn {
+    pub shard_name: String,
+    pub producer_id: i64,
+    pub first_offset: i64,
+    pub last_offset: i64,
+}
+
+// Offset of the commit marker a transaction wrote to one shard.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KafkaTxnMarker {
+    pub shard_name: String,
+    pub producer_id: i64,
+    pub offset: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum KafkaTransactionLogRecord {
+    Transaction(KafkaTransaction),
+    Aborted(Vec<KafkaAbortedTxn>),
+    Committed(Vec<KafkaTxnMarker>),
+}
+
+impl KafkaTransactionLogRecord {
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        Ok(serde_json::to_vec(&self)?)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        Ok(serde_json::from_slice(data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_names_round_trip() {
+        for state in [
+            KafkaTransactionState::Empty,
+            KafkaTransactionState::Ongoing,
+            KafkaTransactionState::PrepareCommit,
+            KafkaTransactionState::PrepareAbort,
+            KafkaTransactionState::CompleteCommit,
+            KafkaTransactionState::CompleteAbort,
+        ] {
+            assert_eq!(KafkaTransactionState::from_name(state.name()), Some(state));
+        }
+        assert_eq!(KafkaTransactionState::from_name("Dead"), None);
+    }
+
+    #[test]
+    fn log_record_round_trips() {
+        let txn = KafkaTransaction {
+            transactional_id: "tx-1".to_string(),
+            producer_id: 7,
+            producer_epoch: 2,
+            state: KafkaTransactionState::Ongoing,
+            partitions: vec![KafkaTxnPartition {
+                topic: "orders".to_string(),
+                partition: 0,
+                shard_name: "orders-0".to_string(),
+                first_offset: 42,
+            }],
+            version: 3,
+            ..Default::default()
+        };
+        let record = KafkaTransactionLogRecord::Transaction(txn);
+        let decoded = KafkaTransactionLogRecord::decode(&record.encode().unwrap()).unwrap();
+        assert_eq!(decoded, record);
+
+        let record = KafkaTransactionLogRecord::Committed(vec![KafkaTxnMarker {
+            shard_name: "orders-0".to_string(),
+            producer_id: 7,
+            offset: 57,
+        }]);
+        let decoded = KafkaTransactionLogRecord::decode(&record.encode().unwrap()).unwrap();
+        assert_eq!(decoded, record);
+    }
+}
@@ -25,6 +25,7 @@
 pub mod mqtt;
 pub mod nats;
 pub mod resource_config;
+pub mod rule;
 pub mod schema;
 pub mod storage;
 pub mod tenant;
@@ -0,0 +1,185 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::{error::common::CommonError, utils::serialize};
+use serde::{Deserialize, Serialize};
+
+/// Consumer-group name under which a consumer's ack floor is committed.
+const NATS_CONSUMER_GROUP_PREFIX: &str = "$nats.js.";
+
+/// Where a new consumer starts in its stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NatsDeliverPolicy {
+    #[default]
+    All,
+    Last,
+    New,
+    ByStartSequence,
+    ByStartTime,
+    LastPerSubject,
+}
+
+impl NatsDeliverPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NatsDeliverPolicy::All => "all",
+            NatsDeliverPolicy::Last => "last",
+            NatsDeliverPolicy::New => "new",
+            NatsDeliverPolicy::ByStartSequence => "by_start_sequence",
+            NatsDeliverPolicy::ByStartTime => "by_start_time",
+            NatsDeliverPolicy::LastPerSubject => "last_per_subject",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "" | "all" => Some(NatsDeliverPolicy::All),
+            "last" => Some(NatsDeliverPolicy::Last),
+            "new" => Some(NatsDeliverPolicy::New),
+            "by_start_sequence" => Some(NatsDeliverPolicy::ByStartSequence),
+            "by_start_time" => Some(NatsDeliverPolicy::ByStartTime),
+            "last_per_subject" => Some(NatsDeliverPolicy::LastPerSubject),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NatsAckPolicy {
+    /// Messages are considered acknowledged once delivered.
+    None,
+    /// Acknowledging a message acknowledges every earlier one too.
+    All,
+    /// Every message must be acknowledged on its own.
+    #[default]
+    Explicit,
+}
+
+impl NatsAckPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NatsAckPolicy::None => "none",
+            NatsAckPolicy::All => "all",
+            NatsAckPolicy::Explicit => "explicit",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "none" => Some(NatsAckPolicy::None),
+            "all" => Some(NatsAckPolicy::All),
+            "" | "explicit" => Some(NatsAckPolicy::Explicit),
+            _ => None,
+        }
+    }
+}
+
+/// A JetStream consumer. Durations (`ack_wait`, `idle_heartbeat`, `backoff`)
+/// are in nanoseconds as on the wire; `max_deliver`, `max_waiting` and
+/// `max_ack_pending` use `-1` for unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NatsConsumer {
+    pub tenant: String,
+    pub stream_name: String,
+    pub name: String,
+    pub durable: bool,
+    pub description: String,
+    /// Empty for pull consumers.
+    pub deliver_subject: String,
+    pub deliver_policy: NatsDeliverPolicy,
+    pub opt_start_seq: u64,
+    /// Start time in seconds for `ByStartTime`.
+    pub opt_start_time: u64,
+    pub ack_policy: NatsAckPolicy,
+    pub ack_wait: u64,
+    pub max_deliver: i64,
+    pub filter_subjects: Vec<String>,
+    pub max_waiting: i64,
+    pub max_ack_pending: i64,
+    pub idle_heartbeat: u64,
+    pub flow_control: bool,
+    pub backoff: Vec<u64>,
+    /// Deliver only headers, with the payload size in `Nats-Msg-Size`.
+    pub headers_only: bool,
+    /// Pause deadline in seconds, `0` when not paused.
+    pub pause_until: u64,
+    /// First stream sequence the consumer delivers, resolved from the
+    /// deliver policy when the consumer is created.
+    pub start_seq: u64,
+    pub create_time: u64,
+}
+
+impl NatsConsumer {
+    pub fn is_pull(&self) -> bool {
+        self.deliver_subject.is_empty()
+    }
+
+    pub fn group_name(&self) -> String {
+        NatsConsumer::build_group_name(&self.stream_name, &self.name)
+    }
+
+    pub fn build_group_name(stream_name: &str, consumer_name: &str) -> String {
+        format!(
+            "{}{}.{}",
+            NATS_CONSUMER_GROUP_PREFIX, stream_name, consumer_name
+        )
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        serialize::serialize(self)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        serialize::deserialize(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let consumer = NatsConsumer {
+            tenant: "default".to_string(),
+            stream_name: "ORDERS".to_string(),
+            name: "worker".to_string(),
+            durable: true,
+            deliver_policy: NatsDeliverPolicy::ByStartSequence,
+            opt_start_seq: 10,
+            ack_wait: 30_000_000_000,
+            max_ack_pending: 1000,
+            filter_subjects: vec!["orders.eu.>".to_string()],
+            start_seq: 10,
+            ..Default::default()
+        };
+        let decoded = NatsConsumer::decode(&consumer.encode().unwrap()).unwrap();
+        assert_eq!(consumer, decoded);
+        assert!(decoded.is_pull());
+        assert_eq!(decoded.group_name(), "$nats.js.ORDERS.worker");
+    }
+
+    #[test]
+    fn test_parse_policies() {
+        assert_eq!(NatsDeliverPolicy::parse(""), Some(NatsDeliverPolicy::All));
+        assert_eq!(
+            NatsDeliverPolicy::parse("by_start_time"),
+            Some(NatsDeliverPolicy::ByStartTime)
+        );
+        assert!(NatsDeliverPolicy::parse("oldest").is_none());
+        assert_eq!(NatsAckPolicy::parse(""), Some(NatsAckPolicy::Explicit));
+        assert_eq!(NatsAckPolicy::parse("all"), Some(NatsAckPolicy::All));
+        assert_eq!(NatsAckPolicy::None.as_str(), "none");
+    }
+}
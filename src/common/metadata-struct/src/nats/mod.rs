@@ -12,5 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod consumer;
+pub mod stream;
 pub mod subscribe;
 pub mod subscriber;
@@ -0,0 +1,202 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::{error::common::CommonError, utils::serialize};
+use serde::{Deserialize, Serialize};
+
+/// Inner topic that backs the messages of a JetStream stream.
+const NATS_STREAM_TOPIC_PREFIX: &str = "$sys.inner.nats.stream.";
+
+/// When messages may be removed from a stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NatsStreamRetention {
+    /// Kept until a `max_*` limit is reached.
+    #[default]
+    Limits,
+    /// Kept while at least one consumer has not acknowledged it.
+    Interest,
+    /// Removed as soon as any consumer acknowledges it.
+    WorkQueue,
+}
+
+impl NatsStreamRetention {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NatsStreamRetention::Limits => "limits",
+            NatsStreamRetention::Interest => "interest",
+            NatsStreamRetention::WorkQueue => "workqueue",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "" | "limits" => Some(NatsStreamRetention::Limits),
+            "interest" => Some(NatsStreamRetention::Interest),
+            "workqueue" => Some(NatsStreamRetention::WorkQueue),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NatsStreamStorage {
+    #[default]
+    File,
+    Memory,
+}
+
+impl NatsStreamStorage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NatsStreamStorage::File => "file",
+            NatsStreamStorage::Memory => "memory",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "" | "file" => Some(NatsStreamStorage::File),
+            "memory" => Some(NatsStreamStorage::Memory),
+            _ => None,
+        }
+    }
+}
+
+/// What to do when a publish would exceed one of the stream limits.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NatsStreamDiscard {
+    /// Drop the oldest messages to make room.
+    #[default]
+    Old,
+    /// Reject the new message.
+    New,
+}
+
+impl NatsStreamDiscard {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NatsStreamDiscard::Old => "old",
+            NatsStreamDiscard::New => "new",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "" | "old" => Some(NatsStreamDiscard::Old),
+            "new" => Some(NatsStreamDiscard::New),
+            _ => None,
+        }
+    }
+}
+
+/// A JetStream stream. Limits use the NATS conventions: `-1` (or `0` for
+/// `max_age`) means unlimited, and `max_age` / `duplicate_window` are in
+/// nanoseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NatsStream {
+    pub tenant: String,
+    pub name: String,
+    pub description: String,
+    pub subjects: Vec<String>,
+    pub retention: NatsStreamRetention,
+    pub storage: NatsStreamStorage,
+    pub discard: NatsStreamDiscard,
+    pub max_msgs: i64,
+    pub max_bytes: i64,
+    pub max_age: u64,
+    pub max_msgs_per_subject: i64,
+    pub max_msg_size: i64,
+    pub num_replicas: u32,
+    pub duplicate_window: u64,
+    pub deny_delete: bool,
+    pub deny_purge: bool,
+    pub allow_rollup_hdrs: bool,
+    /// Whether messages may set their own expiry with `Nats-TTL`.
+    pub allow_msg_ttl: bool,
+    /// Whether `$JS.API.DIRECT.GET` answers reads of this stream.
+    pub allow_direct: bool,
+    pub create_time: u64,
+}
+
+impl NatsStream {
+    pub fn topic_name(&self) -> String {
+        NatsStream::build_topic_name(&self.name)
+    }
+
+    pub fn build_topic_name(stream_name: &str) -> String {
+        format!("{}{}", NATS_STREAM_TOPIC_PREFIX, stream_name)
+    }
+
+    /// Retention in seconds for the backing topic; `None` when unlimited.
+    pub fn max_age_sec(&self) -> Option<u64> {
+        if self.max_age == 0 {
+            None
+        } else {
+            Some(self.max_age.div_ceil(1_000_000_000))
+        }
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        serialize::serialize(self)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        serialize::deserialize(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let stream = NatsStream {
+            tenant: "default".to_string(),
+            name: "ORDERS".to_string(),
+            subjects: vec!["orders.>".to_string()],
+            retention: NatsStreamRetention::WorkQueue,
+            max_msgs: 100,
+            max_bytes: -1,
+            max_age: 1_500_000_000,
+            ..Default::default()
+        };
+        let decoded = NatsStream::decode(&stream.encode().unwrap()).unwrap();
+        assert_eq!(stream, decoded);
+        assert_eq!(decoded.topic_name(), "$sys.inner.nats.stream.ORDERS");
+        assert_eq!(decoded.max_age_sec(), Some(2));
+    }
+
+    #[test]
+    fn test_parse_policies() {
+        assert_eq!(
+            NatsStreamRetention::parse("interest"),
+            Some(NatsStreamRetention::Interest)
+        );
+        assert_eq!(
+            NatsStreamRetention::parse(""),
+            Some(NatsStreamRetention::Limits)
+        );
+        assert!(NatsStreamRetention::parse("forever").is_none());
+        assert_eq!(
+            NatsStreamStorage::parse("memory"),
+            Some(NatsStreamStorage::Memory)
+        );
+        assert_eq!(
+            NatsStreamDiscard::parse("new"),
+            Some(NatsStreamDiscard::New)
+        );
+        assert_eq!(NatsStreamDiscard::New.as_str(), "new");
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::{error::common::CommonError, utils::serialize};
+use serde::{Deserialize, Serialize};
+
+/// A standalone rule: messages published on topics matching the FROM clause of
+/// `sql` and passing its WHERE clause are sent on by `actions`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Rule {
+    pub tenant: String,
+    pub name: String,
+    pub sql: String,
+    pub actions: Vec<RuleAction>,
+    pub desc: String,
+    pub enable: bool,
+    pub create_time: u64,
+    pub update_time: u64,
+}
+
+impl Rule {
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        serialize::serialize(self)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        serialize::deserialize(data)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum RuleAction {
+    /// Writes to `topic`. `topic` and `payload` may hold `${name}` placeholders
+    /// for the selected fields; without a payload template the selected
+    /// fields are sent as a JSON object.
+    Republish {
+        topic: String,
+        payload: Option<String>,
+    },
+    /// Writes the selected fields, as a JSON object, to the topic the
+    /// connector reads from.
+    Connector { connector_name: String },
+}
@@ -15,6 +15,7 @@
 use common_base::{error::common::CommonError, utils::serialize};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use std::str::FromStr;
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct SchemaData {
@@ -35,11 +36,46 @@ pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
     }
 }
 
+/// One registered version of a subject. `SchemaData` named after the subject
+/// always holds its latest version, so binds and validation keep working on it.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SchemaVersion {
+    pub tenant: String,
+    pub subject: String,
+    pub version: u32,
+    // Cluster-wide id, shared by every subject that registers the same schema.
+    // This is the id Confluent serializers write after the magic byte.
+    pub id: u32,
+    pub schema_type: SchemaType,
+    pub schema: String,
+    pub create_time: u64,
+}
+
+impl SchemaVersion {
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        serialize::serialize(self)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        serialize::deserialize(data)
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct SchemaResourceBind {
     pub tenant: String,
     pub schema_name: String,
     pub resource_name: String,
+    /// Protobuf message the resource's payloads are checked against. Empty
+    /// for the first message of the schema's main file.
+    #[serde(default)]
+    pub message_name: String,
+    /// Reject protobuf payloads with fields the message doesn't declare.
+    #[serde(default)]
+    pub strict_unknown_fields: bool,
+    /// Reject protobuf payloads missing a `required` field.
+    #[serde(default)]
+    pub strict_required_fields: bool,
 }
 
 impl SchemaResourceBind {
@@ -68,3 +104,111 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         }
     }
 }
+
+impl FromStr for SchemaType {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SchemaType::JSON),
+            "protobuf" => Ok(SchemaType::PROTOBUF),
+            "avro" => Ok(SchemaType::AVRO),
+            _ => Err(CommonError::CommonError(format!(
+                "invalid schema type {s}, expected json, avro or protobuf"
+            ))),
+        }
+    }
+}
+
+/// Which earlier versions a new version of a subject must stay compatible
+/// with. The `*Transitive` levels check every earlier version, the others
+/// only the latest one.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum SchemaCompatibility {
+    None,
+    // Readers using the new schema can read data written with the old one.
+    #[default]
+    Backward,
+    BackwardTransitive,
+    // Readers using the old schema can read data written with the new one.
+    Forward,
+    ForwardTransitive,
+    // Both backward and forward.
+    Full,
+    FullTransitive,
+}
+
+impl SchemaCompatibility {
+    pub fn is_backward(&self) -> bool {
+        matches!(
+            self,
+            SchemaCompatibility::Backward
+                | SchemaCompatibility::BackwardTransitive
+                | SchemaCompatibility::Full
+                | SchemaCompatibility::FullTransitive
+        )
+    }
+
+    pub fn is_forward(&self) -> bool {
+        matches!(
+            self,
+            SchemaCompatibility::Forward
+                | SchemaCompatibility::ForwardTransitive
+                | SchemaCompatibility::Full
+                | SchemaCompatibility::FullTransitive
+        )
+    }
+
+    pub fn is_transitive(&self) -> bool {
+        matches!(
+            self,
+            SchemaCompatibility::BackwardTransitive
+                | SchemaCompatibility::ForwardTransitive
+                | SchemaCompatibility::FullTransitive
+        )
+    }
+}
+
+impl Display for SchemaCompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SchemaCompatibility::None => "NONE",
+            SchemaCompatibility::Backward => "BACKWARD",
+            SchemaCompatibility::BackwardTransitive => "BACKWARD_TRANSITIVE",
+            SchemaCompatibility::Forward => "FORWARD",
+            SchemaCompatibility::ForwardTransitive => "FORWARD_TRANSITIVE",
+            SchemaCompatibility::Full => "FULL",
+            SchemaCompatibility::FullTransitive => "FULL_TRANSITIVE",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for SchemaCompatibility {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "NONE" => Ok(SchemaCompatibility::None),
+            "BACKWARD" => Ok(SchemaCompatibility::Backward),
+            "BACKWARD_TRANSITIVE" => Ok(SchemaCompatibility::BackwardTransitive),
+            "FORWARD" => Ok(SchemaCompatibility::Forward),
+            "FORWARD_TRANSITIVE" => Ok(SchemaCompatibility::ForwardTransitive),
+            "FULL" => Ok(SchemaCompatibility::Full),
+            "FULL_TRANSITIVE" => Ok(SchemaCompatibility::FullTransitive),
+            _ => Err(CommonError::CommonError(format!(
+                "invalid compatibility level {s}"
+            ))),
+        }
+    }
+}
+
+/// Resource config key the compatibility level of a subject is stored under,
+/// or the tenant-wide default when `subject` is `None`.
+pub fn schema_compatibility_resource(tenant: &str, subject: Option<&str>) -> Vec<String> {
+    let mut resource = vec!["schema_compatibility".to_string(), tenant.to_string()];
+    if let Some(subject) = subject {
+        resource.push(subject.to_string());
+    }
+    resource
+}
@@ -182,6 +182,7 @@ pub struct StorageRecordProtocolData {
     pub nats: Option<StorageRecordProtocolDataNats>,
     pub mq9: Option<StorageRecordProtocolDataMq9>,
     pub amqp: Option<StorageRecordProtocolDataAmqp>,
+    pub kafka: Option<StorageRecordProtocolDataKafka>,
 }
 
 impl StorageRecordProtocolData {}
@@ -210,6 +211,18 @@ pub struct StorageRecordProtocolDataMq9 {
     pub reply_to: Option<String>,
 }
 
+/// Kafka record-batch producer metadata, kept so Fetch can rebuild
+/// transactional batches and commit/abort control markers.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct StorageRecordProtocolDataKafka {
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+    pub transactional: bool,
+    pub control: bool,
+    /// Codec the record is served in (Kafka's wire value, 0 = none).
+    pub compression: i8,
+}
+
 /// AMQP's `AMQPProperties`, carried opaquely through storage so Basic.Deliver
 /// and Basic.GetOk can reconstruct the original properties on redelivery
 /// instead of always sending an empty set.
@@ -233,4 +246,26 @@ pub struct StorageRecordProtocolDataAmqp {
     // (Nack/Reject/Recover, connection cleanup, or crash recovery), so a
     // later Get/Consume can report AMQP's `redelivered` flag correctly.
     pub redelivered: bool,
+    // The exchange and routing key the message was published with: reported
+    // on Basic.Deliver/GetOk and reused when the message is dead-lettered.
+    pub exchange: String,
+    pub routing_key: String,
+    // Unix time (ms) after which the message is expired, from the lower of its
+    // `expiration` property and its queue's `x-message-ttl`; 0 never expires.
+    pub expire_at_ms: u64,
+    // The `x-death` header: one entry per (queue, reason) the message was
+    // dead-lettered from, most recent first.
+    pub x_death: Vec<StorageRecordAmqpDeath>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct StorageRecordAmqpDeath {
+    pub queue: String,
+    // "rejected", "expired" or "maxlen", as in RabbitMQ.
+    pub reason: String,
+    pub exchange: String,
+    pub routing_keys: Vec<String>,
+    pub count: u64,
+    // Unix seconds of the most recent death, sent as an AMQP timestamp.
+    pub time: u64,
 }
@@ -21,6 +21,7 @@
 pub mod mqtt;
 pub mod network;
 pub mod rocksdb;
+pub mod rule;
 pub mod storage_engine;
 
 /// Pre-register all static-label gauge metrics to 0 so that they appear in
@@ -0,0 +1,51 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{counter_metric_get, counter_metric_inc, register_counter_metric};
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+pub struct RuleFailureLabel {
+    pub tenant: String,
+    pub rule_name: String,
+    // evaluate, action, init_topic or write
+    pub stage: String,
+}
+
+register_counter_metric!(
+    RULE_FAILURES,
+    "rule_failures",
+    "Total number of messages a rule failed to evaluate, render or write",
+    RuleFailureLabel
+);
+
+pub fn record_rule_failure(tenant: &str, rule_name: &str, stage: &str) {
+    let label = RuleFailureLabel {
+        tenant: tenant.to_string(),
+        rule_name: rule_name.to_string(),
+        stage: stage.to_string(),
+    };
+    counter_metric_inc!(RULE_FAILURES, label);
+}
+
+pub fn get_rule_failure(tenant: &str, rule_name: &str, stage: &str) -> u64 {
+    let label = RuleFailureLabel {
+        tenant: tenant.to_string(),
+        rule_name: rule_name.to_string(),
+        stage: stage.to_string(),
+    };
+    let mut result = 0u64;
+    counter_metric_get!(RULE_FAILURES, label, result);
+    result
+}
@@ -37,6 +37,7 @@ pub struct CommandRegistry {
     pub mqtt: Option<ArcCommandAdapter>,
     pub kafka: Option<ArcCommandAdapter>,
     pub amqp: Option<ArcCommandAdapter>,
+    pub amqp1: Option<ArcCommandAdapter>,
     pub nats: Option<ArcCommandAdapter>,
     pub storage_engine: Option<ArcCommandAdapter>,
 }
@@ -47,6 +48,7 @@ pub fn get(&self, packet: &RobustMQPacket) -> Option<&ArcCommandAdapter> {
             RobustMQPacket::MQTT(_) => self.mqtt.as_ref(),
             RobustMQPacket::KAFKA(_) => self.kafka.as_ref(),
             RobustMQPacket::AMQP(_) => self.amqp.as_ref(),
+            RobustMQPacket::AMQP1(_) => self.amqp1.as_ref(),
             RobustMQPacket::NATS(_) => self.nats.as_ref(),
             RobustMQPacket::StorageEngine(_) => self.storage_engine.as_ref(),
         }
@@ -0,0 +1,163 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::tls_acceptor::load_certs;
+use common_base::error::common::CommonError;
+use common_config::config::Network;
+use metadata_struct::connection::PeerCertIdentity;
+use rustls_pemfile::crls;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::rustls::pki_types::{CertificateDer, CertificateRevocationListDer};
+use tokio_rustls::rustls::server::danger::ClientCertVerifier;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::RootCertStore;
+use x509_parser::extensions::GeneralName;
+use x509_parser::parse_x509_certificate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientAuthMode {
+    None,
+    // A certificate is verified if the client sends one, but it may send none.
+    Optional,
+    Required,
+}
+
+impl ClientAuthMode {
+    pub fn parse(mode: &str) -> Result<Self, CommonError> {
+        match mode.to_lowercase().as_str() {
+            "" | "none" => Ok(ClientAuthMode::None),
+            "optional" => Ok(ClientAuthMode::Optional),
+            "required" => Ok(ClientAuthMode::Required),
+            _ => Err(CommonError::CommonError(format!(
+                "invalid tls_client_auth {mode}, expected none, optional or required"
+            ))),
+        }
+    }
+}
+
+/// The verifier client certificates are checked with, or `None` when the
+/// listener does not ask for them.
+#[allow(clippy::result_large_err)]
+pub fn build_client_cert_verifier(
+    conf: &Network,
+) -> Result<Option<Arc<dyn ClientCertVerifier>>, CommonError> {
+    let mode = ClientAuthMode::parse(&conf.tls_client_auth)?;
+    if mode == ClientAuthMode::None {
+        return Ok(None);
+    }
+    if conf.tls_ca_cert.is_empty() {
+        return Err(CommonError::CommonError(format!(
+            "tls_client_auth is {}, but no tls_ca_cert is configured",
+            conf.tls_client_auth
+        )));
+    }
+
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(Path::new(&conf.tls_ca_cert))? {
+        roots.add(cert)?;
+    }
+
+    let mut builder = WebPkiClientVerifier::builder(Arc::new(roots));
+    if !conf.tls_crls.is_empty() {
+        let mut revocations = Vec::new();
+        for path in &conf.tls_crls {
+            revocations.extend(load_crls(Path::new(path))?);
+        }
+        // CA bundles rarely come with CRLs for their intermediates.
+        builder = builder
+            .with_crls(revocations)
+            .only_check_end_entity_revocation();
+    }
+    if mode == ClientAuthMode::Optional {
+        builder = builder.allow_unauthenticated();
+    }
+    let verifier = builder
+        .build()
+        .map_err(|e| CommonError::CommonError(format!("invalid client CA bundle: {e}")))?;
+    Ok(Some(verifier))
+}
+
+fn load_crls(path: &Path) -> std::io::Result<Vec<CertificateRevocationListDer<'static>>> {
+    crls(&mut BufReader::new(File::open(path)?)).collect()
+}
+
+/// Reads the CN and subjectAltNames from the end-entity certificate of a
+/// verified chain. `None` when the client sent no certificate.
+pub fn peer_cert_identity(certs: Option<&[CertificateDer<'_>]>) -> Option<PeerCertIdentity> {
+    let (_, cert) = parse_x509_certificate(certs?.first()?.as_ref()).ok()?;
+
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(|cn| cn.to_string());
+
+    let mut subject_alt_names = Vec::new();
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::DNSName(name)
+                | GeneralName::RFC822Name(name)
+                | GeneralName::URI(name) => subject_alt_names.push(name.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    Some(PeerCertIdentity {
+        common_name,
+        subject_alt_names,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_config::default::default_network;
+
+    #[test]
+    fn client_auth_mode_parse() {
+        assert_eq!(ClientAuthMode::parse("").unwrap(), ClientAuthMode::None);
+        assert_eq!(
+            ClientAuthMode::parse("Required").unwrap(),
+            ClientAuthMode::Required
+        );
+        assert!(ClientAuthMode::parse("sometimes").is_err());
+    }
+
+    #[test]
+    fn client_auth_needs_a_ca_bundle() {
+        let mut conf = default_network();
+        assert!(build_client_cert_verifier(&conf).unwrap().is_none());
+
+        conf.tls_client_auth = "required".to_string();
+        assert!(build_client_cert_verifier(&conf).is_err());
+    }
+
+    #[test]
+    fn identity_from_certificate() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../config/certs/cert.pem");
+        let certs = load_certs(&path).unwrap();
+
+        let identity = peer_cert_identity(Some(certs.as_slice())).unwrap();
+        assert_eq!(identity.common_name, None);
+        assert_eq!(identity.subject_alt_names, vec!["localhost".to_string()]);
+
+        assert!(peer_cert_identity(None).is_none());
+    }
+}
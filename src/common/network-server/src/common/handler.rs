@@ -33,7 +33,7 @@
 use protocol::codec::{RobustMQCodec, RobustMQCodecWrapper};
 use protocol::mqtt::codec::MqttPacketWrapper;
 use protocol::robust::{
-    AmqpWrapperExtend, KafkaWrapperExtend, NatsWrapperExtend, RobustMQPacket,
+    Amqp1WrapperExtend, AmqpWrapperExtend, KafkaWrapperExtend, NatsWrapperExtend, RobustMQPacket,
     RobustMQPacketWrapper, RobustMQWrapperExtend, StorageEngineWrapperExtend,
 };
 use std::sync::Arc;
@@ -221,6 +221,11 @@ async fn write_response(
                 extend: RobustMQWrapperExtend::AMQP(AmqpWrapperExtend {}),
                 packet: RobustMQPacket::AMQP(packet),
             },
+            RobustMQPacket::AMQP1(packet) => RobustMQPacketWrapper {
+                protocol: protocol.clone(),
+                extend: RobustMQWrapperExtend::AMQP1(Amqp1WrapperExtend {}),
+                packet: RobustMQPacket::AMQP1(packet),
+            },
             RobustMQPacket::StorageEngine(packet) => RobustMQPacketWrapper {
                 protocol: protocol.clone(),
                 extend: RobustMQWrapperExtend::StorageEngine(StorageEngineWrapperExtend {}),
@@ -289,6 +294,10 @@ async fn write_websocket_response(
         for frame in frames {
             codec.encode_data(RobustMQCodecWrapper::AMQP(frame), &mut response_buf)?;
         }
+    } else if let RobustMQPacket::AMQP1(frames) = packet_wrapper.packet.clone() {
+        for frame in frames {
+            codec.encode_data(RobustMQCodecWrapper::AMQP1(frame), &mut response_buf)?;
+        }
     } else {
         let codec_wrapper = match packet_wrapper.packet.clone() {
             RobustMQPacket::MQTT(pkg) => RobustMQCodecWrapper::MQTT(MqttPacketWrapper {
@@ -297,7 +306,7 @@ async fn write_websocket_response(
             }),
             RobustMQPacket::StorageEngine(pkg) => RobustMQCodecWrapper::StorageEngine(pkg),
             RobustMQPacket::KAFKA(pkg) => RobustMQCodecWrapper::KAFKA(pkg),
-            RobustMQPacket::AMQP(_) => unreachable!("handled above"),
+            RobustMQPacket::AMQP(_) | RobustMQPacket::AMQP1(_) => unreachable!("handled above"),
             RobustMQPacket::NATS(pkt) => RobustMQCodecWrapper::NATS(pkt),
         };
 
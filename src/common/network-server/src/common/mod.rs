@@ -13,11 +13,14 @@
 // limitations under the License.
 
 pub mod channel;
+pub mod client_cert;
 pub mod connection_manager;
 pub mod handler;
 pub mod metric;
 pub mod packet;
+pub mod proxy_protocol;
 pub mod tcp_acceptor;
 pub mod tls_acceptor;
+pub mod tls_cert;
 pub mod tool;
 pub mod write;
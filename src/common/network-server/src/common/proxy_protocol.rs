@@ -0,0 +1,452 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! PROXY protocol v1 and v2 headers, as sent by HAProxy, AWS NLB and other
+//! load balancers ahead of the client's own bytes.
+//! See <https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt>.
+
+use common_base::error::common::CommonError;
+use common_config::broker::broker_config;
+use common_config::config::ProxyProtocolListener;
+use ipnet::IpNet;
+use metadata_struct::connection::{ProxyInfo, ProxySslInfo};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::timeout;
+use tracing::warn;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+// "PROXY UNKNOWN" + two IPv6 addresses and ports, with CRLF.
+const V1_MAX_LEN: usize = 107;
+const V2_SIGNATURE: [u8; 12] = [
+    0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A,
+];
+
+const PP2_TYPE_ALPN: u8 = 0x01;
+const PP2_TYPE_AUTHORITY: u8 = 0x02;
+const PP2_TYPE_SSL: u8 = 0x20;
+const PP2_SUBTYPE_SSL_VERSION: u8 = 0x21;
+const PP2_SUBTYPE_SSL_CN: u8 = 0x22;
+const PP2_SUBTYPE_SSL_CIPHER: u8 = 0x23;
+const PP2_SUBTYPE_SSL_SIG_ALG: u8 = 0x24;
+const PP2_SUBTYPE_SSL_KEY_ALG: u8 = 0x25;
+const PP2_CLIENT_CERT_CONN: u8 = 0x02;
+
+/// A parsed header. `source` is `None` for v1 `UNKNOWN` and v2 `LOCAL`
+/// headers (e.g. health checks), where the connection is the proxy's own.
+#[derive(Debug, Default, PartialEq)]
+pub struct ProxyHeader {
+    pub source: Option<SocketAddr>,
+    pub destination: Option<SocketAddr>,
+    pub alpn: Option<String>,
+    pub authority: Option<String>,
+    pub ssl: Option<ProxySslInfo>,
+}
+
+/// The PROXY protocol settings of the listener on `port`, if it has any.
+pub fn proxy_protocol_listener(port: u16) -> Option<ProxyProtocolListener> {
+    broker_config()
+        .broker_network
+        .proxy_protocol
+        .iter()
+        .find(|listener| listener.port == port as u32)
+        .cloned()
+}
+
+/// PROXY protocol is read on TCP and TLS listeners only. A WebSocket or QUIC
+/// port listed in `proxy_protocol` would otherwise be ignored silently.
+pub fn warn_proxy_protocol_unsupported(port: u32, listener: &str) {
+    if u16::try_from(port)
+        .ok()
+        .and_then(proxy_protocol_listener)
+        .is_some()
+    {
+        warn!(
+            "proxy_protocol lists port {}, but {} listeners don't read PROXY headers; clients keep the load balancer's address",
+            port, listener
+        );
+    }
+}
+
+/// Whether `ip` may (and so must) send a PROXY header on this listener. A
+/// header rewrites the client address and can vouch for a client certificate,
+/// so only sources listed in `trusted_cidrs` are trusted; an empty list trusts
+/// none.
+pub fn is_trusted_proxy(listener: &ProxyProtocolListener, ip: IpAddr) -> bool {
+    listener.trusted_cidrs.iter().any(|cidr| {
+        IpNet::from_str(cidr)
+            .map(|net| net.contains(&ip))
+            .unwrap_or_else(|_| IpAddr::from_str(cidr).is_ok_and(|addr| addr == ip))
+    })
+}
+
+/// Reads the PROXY header a trusted source starts its connection with, and
+/// returns the client's address with what the header said about it. Sources
+/// the listener doesn't trust, and listeners without PROXY protocol, keep
+/// the socket address.
+pub async fn accept_proxy_header<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    addr: SocketAddr,
+    listener: Option<&ProxyProtocolListener>,
+) -> Result<(SocketAddr, Option<ProxyInfo>), CommonError> {
+    let Some(listener) = listener else {
+        return Ok((addr, None));
+    };
+    if !is_trusted_proxy(listener, addr.ip()) {
+        return Ok((addr, None));
+    }
+
+    let wait = Duration::from_millis(broker_config().broker_network.proxy_protocol_timeout_ms);
+    let header = timeout(wait, read_proxy_header(stream))
+        .await
+        .map_err(|_| {
+            CommonError::CommonError(format!("no PROXY protocol header from {addr} in time"))
+        })??;
+
+    let Some(source) = header.source else {
+        return Ok((addr, None));
+    };
+    Ok((
+        source,
+        Some(ProxyInfo {
+            proxy_addr: addr,
+            destination_addr: header.destination,
+            alpn: header.alpn,
+            authority: header.authority,
+            ssl: header.ssl,
+        }),
+    ))
+}
+
+/// Reads exactly one v1 or v2 header, leaving the client's bytes that follow
+/// it in the stream.
+pub async fn read_proxy_header<S: AsyncRead + Unpin>(
+    stream: &mut S,
+) -> Result<ProxyHeader, CommonError> {
+    let mut prefix = [0u8; 16];
+    stream.read_exact(&mut prefix[..V1_PREFIX.len()]).await?;
+
+    if &prefix[..V1_PREFIX.len()] == V1_PREFIX {
+        let mut line = prefix[..V1_PREFIX.len()].to_vec();
+        while !line.ends_with(b"\r\n") {
+            if line.len() >= V1_MAX_LEN {
+                return Err(proxy_error("PROXY v1 header is too long"));
+            }
+            line.push(stream.read_u8().await?);
+        }
+        return parse_v1(&line);
+    }
+
+    stream.read_exact(&mut prefix[V1_PREFIX.len()..]).await?;
+    if prefix[..12] != V2_SIGNATURE {
+        return Err(proxy_error("connection did not start with a PROXY header"));
+    }
+    let len = u16::from_be_bytes([prefix[14], prefix[15]]) as usize;
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body).await?;
+    parse_v2(&prefix, &body)
+}
+
+/// `PROXY TCP4 192.0.2.1 198.51.100.1 56324 1883\r\n`
+#[allow(clippy::result_large_err)]
+pub fn parse_v1(line: &[u8]) -> Result<ProxyHeader, CommonError> {
+    let line = std::str::from_utf8(line)
+        .ok()
+        .and_then(|l| l.strip_suffix("\r\n"))
+        .ok_or_else(|| proxy_error("PROXY v1 header is not a CRLF-terminated line"))?;
+    let fields: Vec<&str> = line.split(' ').collect();
+
+    match fields.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(ProxyHeader::default()),
+        ["PROXY", family @ ("TCP4" | "TCP6"), src, dst, src_port, dst_port] => {
+            let ip = |s: &str| -> Result<IpAddr, CommonError> {
+                let ip = IpAddr::from_str(s)
+                    .map_err(|_| proxy_error(&format!("invalid PROXY v1 address {s}")))?;
+                if ip.is_ipv4() != (*family == "TCP4") {
+                    return Err(proxy_error(&format!("{s} is not a {family} address")));
+                }
+                Ok(ip)
+            };
+            let port = |s: &str| {
+                u16::from_str(s).map_err(|_| proxy_error(&format!("invalid PROXY v1 port {s}")))
+            };
+            Ok(ProxyHeader {
+                source: Some(SocketAddr::new(ip(src)?, port(src_port)?)),
+                destination: Some(SocketAddr::new(ip(dst)?, port(dst_port)?)),
+                ..Default::default()
+            })
+        }
+        _ => Err(proxy_error(&format!("malformed PROXY v1 header {line}"))),
+    }
+}
+
+/// The 16-byte fixed part and the variable part (addresses, then TLVs).
+#[allow(clippy::result_large_err)]
+pub fn parse_v2(prefix: &[u8; 16], body: &[u8]) -> Result<ProxyHeader, CommonError> {
+    let version = prefix[12] >> 4;
+    let command = prefix[12] & 0x0F;
+    if version != 2 {
+        return Err(proxy_error(&format!("unsupported PROXY version {version}")));
+    }
+    match command {
+        // LOCAL: the proxy's own connection; the addresses, if any, are ignored.
+        0x0 => return Ok(ProxyHeader::default()),
+        0x1 => {}
+        _ => return Err(proxy_error(&format!("unknown PROXY v2 command {command}"))),
+    }
+
+    let (source, destination, addr_len) = match prefix[13] >> 4 {
+        // AF_INET
+        0x1 => {
+            let addrs = body
+                .get(..12)
+                .ok_or_else(|| proxy_error("PROXY v2 IPv4 addresses are truncated"))?;
+            let src = Ipv4Addr::new(addrs[0], addrs[1], addrs[2], addrs[3]);
+            let dst = Ipv4Addr::new(addrs[4], addrs[5], addrs[6], addrs[7]);
+            (
+                Some(SocketAddr::new(IpAddr::V4(src), be_u16(&addrs[8..10]))),
+                Some(SocketAddr::new(IpAddr::V4(dst), be_u16(&addrs[10..12]))),
+                12,
+            )
+        }
+        // AF_INET6
+        0x2 => {
+            let addrs = body
+                .get(..36)
+                .ok_or_else(|| proxy_error("PROXY v2 IPv6 addresses are truncated"))?;
+            let src: [u8; 16] = addrs[..16].try_into().unwrap();
+            let dst: [u8; 16] = addrs[16..32].try_into().unwrap();
+            (
+                Some(SocketAddr::new(
+                    IpAddr::V6(Ipv6Addr::from(src)),
+                    be_u16(&addrs[32..34]),
+                )),
+                Some(SocketAddr::new(
+                    IpAddr::V6(Ipv6Addr::from(dst)),
+                    be_u16(&addrs[34..36]),
+                )),
+                36,
+            )
+        }
+        // AF_UNSPEC and AF_UNIX carry no address the broker can use.
+        0x0 => (None, None, 0),
+        0x3 => (None, None, 216.min(body.len())),
+        family => {
+            return Err(proxy_error(&format!(
+                "unknown PROXY v2 address family {family}"
+            )))
+        }
+    };
+
+    let mut header = ProxyHeader {
+        source,
+        destination,
+        ..Default::default()
+    };
+    for (kind, value) in tlvs(&body[addr_len..])? {
+        match kind {
+            PP2_TYPE_ALPN => header.alpn = Some(lossy(value)),
+            PP2_TYPE_AUTHORITY => header.authority = Some(lossy(value)),
+            PP2_TYPE_SSL => header.ssl = Some(parse_ssl_tlv(value)?),
+            _ => {}
+        }
+    }
+    Ok(header)
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_ssl_tlv(value: &[u8]) -> Result<ProxySslInfo, CommonError> {
+    if value.len() < 5 {
+        return Err(proxy_error("PROXY v2 SSL TLV is truncated"));
+    }
+    let client = value[0];
+    let verify = u32::from_be_bytes([value[1], value[2], value[3], value[4]]);
+    let mut ssl = ProxySslInfo {
+        client_cert_verified: client & PP2_CLIENT_CERT_CONN != 0 && verify == 0,
+        ..Default::default()
+    };
+    for (kind, sub) in tlvs(&value[5..])? {
+        match kind {
+            PP2_SUBTYPE_SSL_VERSION => ssl.version = Some(lossy(sub)),
+            PP2_SUBTYPE_SSL_CN => ssl.client_cn = Some(lossy(sub)),
+            PP2_SUBTYPE_SSL_CIPHER => ssl.cipher = Some(lossy(sub)),
+            PP2_SUBTYPE_SSL_SIG_ALG => ssl.sig_alg = Some(lossy(sub)),
+            PP2_SUBTYPE_SSL_KEY_ALG => ssl.key_alg = Some(lossy(sub)),
+            _ => {}
+        }
+    }
+    Ok(ssl)
+}
+
+#[allow(clippy::result_large_err)]
+fn tlvs(mut data: &[u8]) -> Result<Vec<(u8, &[u8])>, CommonError> {
+    let mut tlvs = Vec::new();
+    while !data.is_empty() {
+        if data.len() < 3 {
+            return Err(proxy_error("PROXY v2 TLV is truncated"));
+        }
+        let len = be_u16(&data[1..3]) as usize;
+        let value = data
+            .get(3..3 + len)
+            .ok_or_else(|| proxy_error("PROXY v2 TLV is truncated"))?;
+        tlvs.push((data[0], value));
+        data = &data[3 + len..];
+    }
+    Ok(tlvs)
+}
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+fn proxy_error(message: &str) -> CommonError {
+    CommonError::CommonError(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v2_header(command: u8, family: u8, body: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.push(0x20 | command);
+        header.push(family);
+        header.extend_from_slice(&(body.len() as u16).to_be_bytes());
+        header.extend_from_slice(body);
+        header
+    }
+
+    fn tlv(kind: u8, value: &[u8]) -> Vec<u8> {
+        let mut tlv = vec![kind];
+        tlv.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        tlv.extend_from_slice(value);
+        tlv
+    }
+
+    #[tokio::test]
+    async fn v1_header_is_consumed_exactly() {
+        let data = b"PROXY TCP4 192.0.2.10 198.51.100.1 56324 1883\r\n\x10\x0c";
+        let mut stream = &data[..];
+        let header = read_proxy_header(&mut stream).await.unwrap();
+        assert_eq!(
+            header.source,
+            Some(SocketAddr::from_str("192.0.2.10:56324").unwrap())
+        );
+        assert_eq!(
+            header.destination,
+            Some(SocketAddr::from_str("198.51.100.1:1883").unwrap())
+        );
+        // The MQTT CONNECT that follows is left for the codec.
+        assert_eq!(stream, b"\x10\x0c");
+    }
+
+    #[test]
+    fn v1_variants() {
+        let header = parse_v1(b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 9092\r\n").unwrap();
+        assert_eq!(
+            header.source,
+            Some(SocketAddr::from_str("[2001:db8::1]:4000").unwrap())
+        );
+        assert_eq!(
+            parse_v1(b"PROXY UNKNOWN\r\n").unwrap(),
+            ProxyHeader::default()
+        );
+        assert!(parse_v1(b"PROXY TCP4 2001:db8::1 192.0.2.1 1 2\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 192.0.2.1 192.0.2.2 1\r\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn v2_ipv4_with_tls_tlvs() {
+        let mut body = vec![192, 0, 2, 10, 198, 51, 100, 1];
+        body.extend_from_slice(&56324u16.to_be_bytes());
+        body.extend_from_slice(&8883u16.to_be_bytes());
+        body.extend(tlv(PP2_TYPE_ALPN, b"mqtt"));
+        let mut ssl = vec![0x01 | PP2_CLIENT_CERT_CONN, 0, 0, 0, 0];
+        ssl.extend(tlv(PP2_SUBTYPE_SSL_VERSION, b"TLSv1.3"));
+        ssl.extend(tlv(PP2_SUBTYPE_SSL_CN, b"sensor-0042"));
+        body.extend(tlv(PP2_TYPE_SSL, &ssl));
+
+        let data = v2_header(0x1, 0x11, &body);
+        let header = read_proxy_header(&mut &data[..]).await.unwrap();
+        assert_eq!(
+            header.source,
+            Some(SocketAddr::from_str("192.0.2.10:56324").unwrap())
+        );
+        assert_eq!(header.alpn.as_deref(), Some("mqtt"));
+        let ssl = header.ssl.unwrap();
+        assert_eq!(ssl.version.as_deref(), Some("TLSv1.3"));
+        assert_eq!(ssl.client_cn.as_deref(), Some("sensor-0042"));
+        assert!(ssl.client_cert_verified);
+    }
+
+    #[tokio::test]
+    async fn v2_local_and_garbage() {
+        let data = v2_header(0x0, 0x00, &[]);
+        assert_eq!(
+            read_proxy_header(&mut &data[..]).await.unwrap(),
+            ProxyHeader::default()
+        );
+
+        let connect = b"\x10\x0c\x00\x04MQTT\x04\x02\x00\x3c\x00\x00";
+        assert!(read_proxy_header(&mut &connect[..]).await.is_err());
+    }
+
+    #[test]
+    fn trusted_sources() {
+        let listener = ProxyProtocolListener {
+            port: 1883,
+            trusted_cidrs: vec!["10.0.0.0/8".to_string(), "192.0.2.7".to_string()],
+        };
+        assert!(is_trusted_proxy(&listener, "10.1.2.3".parse().unwrap()));
+        assert!(is_trusted_proxy(&listener, "192.0.2.7".parse().unwrap()));
+        assert!(!is_trusted_proxy(&listener, "192.0.2.8".parse().unwrap()));
+
+        let none = ProxyProtocolListener {
+            port: 1883,
+            trusted_cidrs: Vec::new(),
+        };
+        assert!(!is_trusted_proxy(&none, "203.0.113.5".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn untrusted_source_cannot_claim_a_certificate() {
+        let mut body = vec![192, 0, 2, 10, 198, 51, 100, 1];
+        body.extend_from_slice(&56324u16.to_be_bytes());
+        body.extend_from_slice(&1883u16.to_be_bytes());
+        let mut ssl = vec![0x01 | PP2_CLIENT_CERT_CONN, 0, 0, 0, 0];
+        ssl.extend(tlv(PP2_SUBTYPE_SSL_CN, b"admin"));
+        body.extend(tlv(PP2_TYPE_SSL, &ssl));
+        let data = v2_header(0x1, 0x11, &body);
+
+        let addr = SocketAddr::from_str("203.0.113.5:40000").unwrap();
+        let listener = ProxyProtocolListener {
+            port: 1883,
+            trusted_cidrs: Vec::new(),
+        };
+        let mut stream = &data[..];
+        let (client, proxy) = accept_proxy_header(&mut stream, addr, Some(&listener))
+            .await
+            .unwrap();
+        assert_eq!(client, addr);
+        assert!(proxy.is_none());
+        // The header is left for the protocol codec, which rejects it.
+        assert_eq!(stream.len(), data.len());
+    }
+}
@@ -14,13 +14,14 @@
 
 use crate::common::channel::RequestChannel;
 use crate::common::connection_manager::ConnectionManager;
+use crate::common::proxy_protocol::{accept_proxy_header, proxy_protocol_listener};
 use crate::common::tool::{check_connection_limit, read_packet};
 use crate::protocol::nats::send_nats_info;
 use broker_core::cache::NodeCacheManager;
 use common_base::task::TaskSupervisor;
 use common_metrics::mqtt::packets::record_received_error_metrics;
 use futures_util::StreamExt;
-use metadata_struct::connection::{NetworkConnection, NetworkConnectionType};
+use metadata_struct::connection::{NetworkConnection, NetworkConnectionType, PeerCertIdentity};
 use protocol::codec::{RobustMQCodec, RobustMQCodecWrapper};
 use protocol::robust::{RobustMQPacket, RobustMQProtocol};
 use rate_limit::global::GlobalRateLimiterManager;
@@ -30,7 +31,7 @@
 use tokio::sync::mpsc::{self, Receiver};
 use tokio::{io, select};
 use tokio_util::codec::{FramedRead, FramedWrite};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 pub struct TcpAcceptorContext {
     pub accept_thread_num: usize,
@@ -57,6 +58,11 @@ pub async fn acceptor_process(ctx: TcpAcceptorContext) {
         let row_codec = ctx.codec.clone();
         let row_broker_cache = ctx.broker_cache.clone();
         let row_global_limit_manager = ctx.global_limit_manager.clone();
+        let proxy_listener = ctx
+            .listener
+            .local_addr()
+            .ok()
+            .and_then(|local| proxy_protocol_listener(local.port()));
         let task_name = format!("{:?}-{}-acceptor-{}", ctx.protocol, ctx.network_type, index);
         ctx.task_supervisor.spawn(task_name, async move {
             debug!(
@@ -90,59 +96,92 @@ pub async fn acceptor_process(ctx: TcpAcceptorContext) {
 
                     val = listener.accept()=>{
                         match val{
-                            Ok((stream, addr)) => {
+                            Ok((mut stream, addr)) => {
                                 debug!("Accept {} connection:{:?}", network_type, addr);
-                                // check connection
-                                if check_connection_limit(&row_global_limit_manager, &row_broker_cache, &connection_manager, &addr).await{
-                                    continue;
-                                }
+                                let proxy_listener = proxy_listener.clone();
+                                let network_type = network_type.clone();
+                                let protocol = protocol.clone();
+                                let row_codec = row_codec.clone();
+                                let row_broker_cache = row_broker_cache.clone();
+                                let row_global_limit_manager = row_global_limit_manager.clone();
+                                let connection_manager = connection_manager.clone();
+                                let request_channel = request_channel.clone();
+                                // everything that waits on the peer runs off the accept loop, so a
+                                // peer that connects and stays silent can't hold up other accepts
+                                tokio::spawn(Box::pin(async move {
+                                    // behind a load balancer, the client is the one named in the PROXY header
+                                    let (addr, proxy) = match accept_proxy_header(&mut stream, addr, proxy_listener.as_ref()).await {
+                                        Ok(val) => val,
+                                        Err(e) => {
+                                            warn!("{} connection from {} closed, invalid PROXY protocol header: {}", network_type, addr, e);
+                                            return;
+                                        }
+                                    };
 
-                                // create stream
-                                let (r_stream, w_stream) = io::split(stream);
-                                let conn_codec = match protocol {
-                                    RobustMQProtocol::MQTT3
-                                    | RobustMQProtocol::MQTT4
-                                    | RobustMQProtocol::MQTT5 => row_codec.clone(),
-                                    _ => RobustMQCodec::new_with_protocol(protocol.clone()),
-                                };
+                                    // check connection
+                                    if check_connection_limit(&row_global_limit_manager, &row_broker_cache, &connection_manager, &addr).await{
+                                        return;
+                                    }
 
-                                let read_frame_stream = FramedRead::new(r_stream, conn_codec.clone());
-                                let write_frame_stream = FramedWrite::new(w_stream, conn_codec);
+                                    // create stream
+                                    let (r_stream, w_stream) = io::split(stream);
+                                    let conn_codec = match protocol {
+                                        RobustMQProtocol::MQTT3
+                                        | RobustMQProtocol::MQTT4
+                                        | RobustMQProtocol::MQTT5 => row_codec.clone(),
+                                        _ => RobustMQCodec::new_with_protocol(protocol.clone()),
+                                    };
 
-                                // create connection
-                                let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                let connection = NetworkConnection::new(
-                                    NetworkConnectionType::Tcp,
-                                    addr,
-                                    Some(connection_stop_sx.clone())
-                                );
+                                    let read_frame_stream = FramedRead::new(r_stream, conn_codec.clone());
+                                    let write_frame_stream = FramedWrite::new(w_stream, conn_codec);
 
-                                connection_manager.add_connection(connection.clone());
-                                connection_manager.add_tcp_write(connection.connection_id, write_frame_stream);
+                                    // create connection
+                                    let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
+                                    let mut connection = NetworkConnection::new(
+                                        NetworkConnectionType::Tcp,
+                                        addr,
+                                        Some(connection_stop_sx.clone())
+                                    );
+                                    // a certificate the load balancer verified identifies the client as if
+                                    // it had been presented to the broker's own TLS listener; `proxy` is only
+                                    // set for sources listed in trusted_cidrs
+                                    if let Some(ssl) = proxy.as_ref().and_then(|p| p.ssl.as_ref()) {
+                                        if ssl.client_cert_verified && ssl.client_cn.is_some() {
+                                            connection.set_peer_cert(Some(PeerCertIdentity {
+                                                common_name: ssl.client_cn.clone(),
+                                                subject_alt_names: Vec::new(),
+                                            }));
+                                        }
+                                    }
+                                    connection.set_proxy(proxy);
 
-                                match protocol {
-                                    RobustMQProtocol::MQTT3
-                                    | RobustMQProtocol::MQTT4
-                                    | RobustMQProtocol::MQTT5 => {}
-                                    _ => connection_manager
-                                        .set_connect_protocol(connection.connection_id, protocol.clone()),
-                                }
+                                    connection_manager.add_connection(connection.clone());
+                                    connection_manager.add_tcp_write(connection.connection_id, write_frame_stream);
 
-                                // nats special logic
-                                if protocol.is_nats() {
-                                    send_nats_info(&row_broker_cache, connection.connection_id, &connection_manager,&network_type, &addr).await;
-                                }
+                                    match protocol {
+                                        RobustMQProtocol::MQTT3
+                                        | RobustMQProtocol::MQTT4
+                                        | RobustMQProtocol::MQTT5 => {}
+                                        _ => connection_manager
+                                            .set_connect_protocol(connection.connection_id, protocol.clone()),
+                                    }
 
-                                // process connection
-                                read_frame_process(
-                                    row_broker_cache.clone(),
-                                    read_frame_stream,
-                                    connection.connection_id(),
-                                    connection_manager.clone(),
-                                    request_channel.clone(),
-                                    connection_stop_rx,
-                                    network_type.clone(),
-                                );
+                                    // nats special logic
+                                    if protocol.is_nats() {
+                                        send_nats_info(&row_broker_cache, connection.connection_id, &connection_manager,&network_type, &addr).await;
+                                    }
+
+                                    // process connection
+                                    read_frame_process(
+                                        row_broker_cache.clone(),
+                                        read_frame_stream,
+                                        connection.connection_id(),
+                                        connection_manager.clone(),
+                                        request_channel.clone(),
+                                        connection_stop_rx,
+                                        network_type.clone(),
+                                    );
+                                }));
                             }
                             Err(e) => {
                                 error!("{} accept failed to create connection with error message :{:?}", network_type, e);
@@ -209,6 +248,9 @@ fn read_frame_process(
                                     RobustMQCodecWrapper::AMQP(pk) => {
                                         read_packet(RobustMQPacket::AMQP(vec![pk]), &request_channel, &connection, &network_type).await;
                                     }
+                                    RobustMQCodecWrapper::AMQP1(pk) => {
+                                        read_packet(RobustMQPacket::AMQP1(vec![pk]), &request_channel, &connection, &network_type).await;
+                                    }
                                     RobustMQCodecWrapper::StorageEngine(pk) => {
                                         read_packet(RobustMQPacket::StorageEngine(pk), &request_channel, &connection, &network_type).await;
                                     }
@@ -13,7 +13,10 @@
 // limitations under the License.
 
 use crate::common::channel::RequestChannel;
+use crate::common::client_cert::{build_client_cert_verifier, peer_cert_identity};
 use crate::common::connection_manager::ConnectionManager;
+use crate::common::proxy_protocol::{accept_proxy_header, proxy_protocol_listener};
+use crate::common::tls_cert::tls_cert_resolver;
 use crate::common::tool::{check_connection_limit, read_packet};
 use crate::protocol::nats::send_nats_info;
 use broker_core::cache::NodeCacheManager;
@@ -40,7 +43,7 @@
 use tokio_rustls::rustls::ServerConfig;
 use tokio_rustls::TlsAcceptor;
 use tokio_util::codec::{FramedRead, FramedWrite};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 pub struct TlsAcceptorContext {
     pub accept_thread_num: usize,
@@ -80,6 +83,11 @@ pub async fn acceptor_tls_process(ctx: TlsAcceptorContext) -> ResultCommonError
         let row_codec = ctx.codec.clone();
         let row_broker_cache = ctx.broker_cache.clone();
         let row_global_limit_manager = ctx.global_limit_manager.clone();
+        let proxy_listener = ctx
+            .listener
+            .local_addr()
+            .ok()
+            .and_then(|local| proxy_protocol_listener(local.port()));
         let task_name = format!(
             "{:?}-{}-tls-acceptor-{}",
             ctx.protocol, ctx.network_type, index
@@ -115,38 +123,62 @@ pub async fn acceptor_tls_process(ctx: TlsAcceptorContext) -> ResultCommonError
                     }
                     val = listener.accept()=>{
                         match val{
-                            Ok((stream, addr)) => {
+                            Ok((mut stream, addr)) => {
                                 debug!("Accept {} tls connection:{:?}", network_type, addr);
-                                let stream = match raw_tls_acceptor.accept(stream).await{
-                                    Ok(da) => da,
-                                    Err(e) => {
-                                        error!("{} Accepter failed to read Stream with error message :{e:?}", network_type);
-                                        continue;
-                                    }
-                                };
+                                let raw_tls_acceptor = raw_tls_acceptor.clone();
+                                let proxy_listener = proxy_listener.clone();
+                                let network_type = network_type.clone();
+                                let protocol = protocol.clone();
+                                let row_codec = row_codec.clone();
+                                let row_broker_cache = row_broker_cache.clone();
+                                let row_global_limit_manager = row_global_limit_manager.clone();
+                                let connection_manager = connection_manager.clone();
+                                let request_channel = request_channel.clone();
+                                // everything that waits on the peer runs off the accept loop, so a
+                                // peer that connects and stays silent can't hold up other accepts
+                                tokio::spawn(Box::pin(async move {
+                                    // the PROXY header comes before the TLS handshake
+                                    let (addr, proxy) = match accept_proxy_header(&mut stream, addr, proxy_listener.as_ref()).await {
+                                        Ok(val) => val,
+                                        Err(e) => {
+                                            warn!("{} connection from {} closed, invalid PROXY protocol header: {}", network_type, addr, e);
+                                            return;
+                                        }
+                                    };
+                                    let stream = match raw_tls_acceptor.accept(stream).await{
+                                        Ok(da) => da,
+                                        Err(e) => {
+                                            error!("{} Accepter failed to read Stream with error message :{e:?}", network_type);
+                                            return;
+                                        }
+                                    };
 
-                                let (r_stream, w_stream) = tokio::io::split(stream);
-                                let read_frame_stream = FramedRead::new(r_stream, row_codec.clone());
-                                let write_frame_stream = FramedWrite::new(w_stream, row_codec.clone());
+                                    let peer_cert = peer_cert_identity(stream.get_ref().1.peer_certificates());
+                                    let (r_stream, w_stream) = tokio::io::split(stream);
+                                    let read_frame_stream = FramedRead::new(r_stream, row_codec.clone());
+                                    let write_frame_stream = FramedWrite::new(w_stream, row_codec.clone());
 
-                                if check_connection_limit(&row_global_limit_manager, &row_broker_cache, &connection_manager, &addr).await{
-                                    continue;
-                                }
+                                    if check_connection_limit(&row_global_limit_manager, &row_broker_cache, &connection_manager, &addr).await{
+                                        return;
+                                    }
 
-                                let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                let connection = NetworkConnection::new(
-                                    NetworkConnectionType::Tls,
-                                    addr,
-                                    Some(connection_stop_sx.clone())
-                                );
-                                connection_manager.add_connection(connection.clone());
-                                connection_manager.add_tcp_tls_write(connection.connection_id, write_frame_stream);
+                                    let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
+                                    let mut connection = NetworkConnection::new(
+                                        NetworkConnectionType::Tls,
+                                        addr,
+                                        Some(connection_stop_sx.clone())
+                                    );
+                                    connection.set_peer_cert(peer_cert);
+                                    connection.set_proxy(proxy);
+                                    connection_manager.add_connection(connection.clone());
+                                    connection_manager.add_tcp_tls_write(connection.connection_id, write_frame_stream);
 
-                                if protocol.is_nats() {
-                                    send_nats_info(&row_broker_cache, connection.connection_id, &connection_manager,&network_type, &addr).await;
-                                }
+                                    if protocol.is_nats() {
+                                        send_nats_info(&row_broker_cache, connection.connection_id, &connection_manager,&network_type, &addr).await;
+                                    }
 
-                                read_tls_frame_process(row_broker_cache.clone(), connection_manager.clone(),read_frame_stream, connection, request_channel.clone(), connection_stop_rx, network_type.clone());
+                                    read_tls_frame_process(row_broker_cache.clone(), connection_manager.clone(),read_frame_stream, connection, request_channel.clone(), connection_stop_rx, network_type.clone());
+                                }));
                             }
                             Err(e) => {
                                 error!("{} accept failed to create connection with error message :{:?}", network_type, e);
@@ -211,6 +243,9 @@ pub(crate) fn read_tls_frame_process(
                                     }
                                     RobustMQCodecWrapper::AMQP(pk) => {
                                         read_packet(RobustMQPacket::AMQP(vec![pk]), &request_channel, &connection, &network_type).await;
+                                    }
+                                    RobustMQCodecWrapper::AMQP1(pk) => {
+                                        read_packet(RobustMQPacket::AMQP1(vec![pk]), &request_channel, &connection, &network_type).await;
                                     }
                                      RobustMQCodecWrapper::StorageEngine(pk) => {
                                         read_packet(RobustMQPacket::StorageEngine(pk), &request_channel, &connection, &network_type).await;
@@ -244,10 +279,10 @@ pub(crate) fn read_tls_frame_process(
 #[allow(clippy::result_large_err)]
 fn create_tls_accept() -> Result<TlsAcceptor, CommonError> {
     let conf = broker_config();
-    let certs = load_certs(Path::new(&conf.broker_network.tls_cert))?;
-    let key = load_key(Path::new(&conf.broker_network.tls_key))?;
-    let config = ServerConfig::builder()
-        .with_no_client_auth()
-        .with_single_cert(certs, key)?;
+    let builder = match build_client_cert_verifier(&conf.broker_network)? {
+        Some(verifier) => ServerConfig::builder().with_client_cert_verifier(verifier),
+        None => ServerConfig::builder().with_no_client_auth(),
+    };
+    let config = builder.with_cert_resolver(tls_cert_resolver()?);
     Ok(TlsAcceptor::from(Arc::new(config)))
 }
@@ -0,0 +1,221 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::tls_acceptor::{load_certs, load_key};
+use arc_swap::ArcSwap;
+use common_base::error::common::CommonError;
+use common_config::broker::broker_config;
+use common_config::config::Network;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep;
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tracing::{info, warn};
+
+static TLS_CERT_RESOLVER: OnceLock<Arc<ReloadableCertResolver>> = OnceLock::new();
+
+/// Serves the broker certificate to TLS handshakes. A reload swaps in the new
+/// certificate for the handshakes that follow; established connections keep
+/// the session they negotiated.
+#[derive(Debug)]
+pub struct ReloadableCertResolver {
+    current: ArcSwap<CertifiedKey>,
+    // Modification times of the files `current` was loaded from.
+    loaded_stamps: Mutex<Vec<Option<SystemTime>>>,
+}
+
+impl ReloadableCertResolver {
+    #[allow(clippy::result_large_err)]
+    pub fn load(conf: &Network) -> Result<Self, CommonError> {
+        let stamps = file_stamps(conf);
+        Ok(ReloadableCertResolver {
+            current: ArcSwap::from_pointee(load_certified_key(conf)?),
+            loaded_stamps: Mutex::new(stamps),
+        })
+    }
+
+    /// Loads the certificate, key and OCSP response again. If any of them
+    /// can't be loaded the current certificate stays in use.
+    #[allow(clippy::result_large_err)]
+    pub fn reload(&self, conf: &Network) -> Result<(), CommonError> {
+        let stamps = file_stamps(conf);
+        let certified_key = load_certified_key(conf)?;
+        self.current.store(Arc::new(certified_key));
+        *self.loaded_stamps.lock().unwrap() = stamps;
+        Ok(())
+    }
+
+    /// Reloads if any of the files changed since they were last loaded.
+    #[allow(clippy::result_large_err)]
+    pub fn reload_if_changed(&self, conf: &Network) -> Result<bool, CommonError> {
+        if file_stamps(conf) == *self.loaded_stamps.lock().unwrap() {
+            return Ok(false);
+        }
+        self.reload(conf)?;
+        Ok(true)
+    }
+
+    pub fn current(&self) -> Arc<CertifiedKey> {
+        self.current.load_full()
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+/// The resolver all TLS listeners (TCP, WebSocket and QUIC) share. The first
+/// call loads the certificate and starts watching its files.
+#[allow(clippy::result_large_err)]
+pub fn tls_cert_resolver() -> Result<Arc<ReloadableCertResolver>, CommonError> {
+    if let Some(resolver) = TLS_CERT_RESOLVER.get() {
+        return Ok(resolver.clone());
+    }
+
+    let loaded = Arc::new(ReloadableCertResolver::load(
+        &broker_config().broker_network,
+    )?);
+    let mut created = false;
+    let resolver = TLS_CERT_RESOLVER
+        .get_or_init(|| {
+            created = true;
+            loaded
+        })
+        .clone();
+    if created {
+        spawn_tls_cert_watcher(resolver.clone());
+    }
+    Ok(resolver)
+}
+
+/// Reloads the certificate of every TLS listener, e.g. from the admin API.
+#[allow(clippy::result_large_err)]
+pub fn reload_tls_cert() -> Result<(), CommonError> {
+    let Some(resolver) = TLS_CERT_RESOLVER.get() else {
+        return Err(CommonError::CommonError(
+            "no TLS listener is running on this node".to_string(),
+        ));
+    };
+    resolver.reload(&broker_config().broker_network)?;
+    info!("TLS certificate reloaded");
+    Ok(())
+}
+
+fn spawn_tls_cert_watcher(resolver: Arc<ReloadableCertResolver>) {
+    let interval = broker_config().broker_network.tls_reload_interval_sec;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_secs(interval)).await;
+            match resolver.reload_if_changed(&broker_config().broker_network) {
+                Ok(true) => info!("TLS certificate files changed, certificate reloaded"),
+                Ok(false) => {}
+                Err(e) => warn!(
+                    "TLS certificate files changed but could not be loaded, \
+                     keeping the current certificate: {}",
+                    e
+                ),
+            }
+        }
+    });
+}
+
+#[allow(clippy::result_large_err)]
+fn load_certified_key(conf: &Network) -> Result<CertifiedKey, CommonError> {
+    let certs = load_certs(Path::new(&conf.tls_cert))?;
+    let key = load_key(Path::new(&conf.tls_key))?;
+    let provider = CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(ring::default_provider()));
+
+    // Also rejects a key that doesn't belong to the certificate, which is
+    // what a reload sees when it catches a rotation half-way.
+    let mut certified_key = CertifiedKey::from_der(certs, key, &provider)?;
+    if !conf.tls_ocsp_response.is_empty() {
+        certified_key.ocsp = Some(fs::read(&conf.tls_ocsp_response)?);
+    }
+    Ok(certified_key)
+}
+
+fn file_stamps(conf: &Network) -> Vec<Option<SystemTime>> {
+    [&conf.tls_cert, &conf.tls_key, &conf.tls_ocsp_response]
+        .into_iter()
+        .filter(|path| !path.is_empty())
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_config::default::default_network;
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    fn certs_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../config/certs")
+    }
+
+    fn test_network(dir: &Path) -> Network {
+        let mut conf = default_network();
+        conf.tls_cert = dir.join("cert.pem").to_string_lossy().to_string();
+        conf.tls_key = dir.join("key.pem").to_string_lossy().to_string();
+        conf
+    }
+
+    #[test]
+    fn reload_when_files_change() {
+        let dir = std::env::temp_dir().join(format!("robustmq-tls-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(certs_dir().join("cert.pem"), dir.join("cert.pem")).unwrap();
+        fs::copy(certs_dir().join("key.pem"), dir.join("key.pem")).unwrap();
+        let conf = test_network(&dir);
+
+        let resolver = ReloadableCertResolver::load(&conf).unwrap();
+        let before = resolver.current();
+        assert!(!resolver.reload_if_changed(&conf).unwrap());
+
+        File::options()
+            .write(true)
+            .open(dir.join("cert.pem"))
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert!(resolver.reload_if_changed(&conf).unwrap());
+        assert!(!Arc::ptr_eq(&before, &resolver.current()));
+        assert!(!resolver.reload_if_changed(&conf).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_reload_keeps_current_certificate() {
+        let mut conf = test_network(&certs_dir());
+        let resolver = ReloadableCertResolver::load(&conf).unwrap();
+        let before = resolver.current();
+
+        // The CA certificate does not match the server key.
+        conf.tls_cert = certs_dir().join("ca.pem").to_string_lossy().to_string();
+        assert!(resolver.reload(&conf).is_err());
+        assert!(Arc::ptr_eq(&before, &resolver.current()));
+    }
+}
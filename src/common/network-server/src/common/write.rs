@@ -14,7 +14,6 @@
 
 use super::connection_manager::ConnectionManager;
 use crate::common::tool::is_ignore_print;
-use amq_protocol::frame::AMQPFrame;
 use axum::extract::ws::Message;
 use common_base::error::{common::CommonError, ResultCommonError};
 use common_base::network::broker_not_available;
@@ -54,10 +53,19 @@ pub async fn write_tcp_frame(
         }
 
         // AMQP replies can be several wire frames (e.g. GetOk + header +
-        // body); write them under one lock acquisition so a concurrent
-        // writer to the same connection can't interleave frames in between.
+        // body, or a split AMQP 1.0 transfer); write them under one lock
+        // acquisition so a concurrent writer to the same connection can't
+        // interleave frames in between.
         match packet_wrapper.packet {
             RobustMQPacket::AMQP(frames) => {
+                let frames = frames.into_iter().map(RobustMQCodecWrapper::AMQP).collect();
+                self.write_tcp_frames_atomic(connection_id, frames).await
+            }
+            RobustMQPacket::AMQP1(frames) => {
+                let frames = frames
+                    .into_iter()
+                    .map(RobustMQCodecWrapper::AMQP1)
+                    .collect();
                 self.write_tcp_frames_atomic(connection_id, frames).await
             }
             RobustMQPacket::MQTT(pack) => {
@@ -93,6 +101,14 @@ pub async fn write_quic_frame(
 
         match packet_wrapper.packet {
             RobustMQPacket::AMQP(frames) => {
+                let frames = frames.into_iter().map(RobustMQCodecWrapper::AMQP).collect();
+                self.write_quic_frames_atomic(connection_id, frames).await
+            }
+            RobustMQPacket::AMQP1(frames) => {
+                let frames = frames
+                    .into_iter()
+                    .map(RobustMQCodecWrapper::AMQP1)
+                    .collect();
                 self.write_quic_frames_atomic(connection_id, frames).await
             }
             RobustMQPacket::MQTT(pack) => {
@@ -156,7 +172,7 @@ async fn write_websocket_frame0(&self, connection_id: u64, resp: Message) -> Res
     async fn write_tcp_frames_atomic(
         &self,
         connection_id: u64,
-        frames: Vec<AMQPFrame>,
+        frames: Vec<RobustMQCodecWrapper>,
     ) -> ResultCommonError {
         if let Some(connection) = self.get_connect(connection_id) {
             if connection.connection_type == NetworkConnectionType::Tls {
@@ -170,7 +186,7 @@ async fn write_tcp_frames_atomic(
             .map(|entry| entry.value().clone())
             .ok_or_else(|| {
                 debug!(
-                    "Write to tcp skipped: connection {} not found, amqp frame batch",
+                    "Write to tcp skipped: connection {} not found, frame batch",
                     connection_id
                 );
                 CommonError::NotObtainAvailableConnection("tcp".to_string(), connection_id)
@@ -179,11 +195,9 @@ async fn write_tcp_frames_atomic(
         let mut stream = writer.lock().await;
         for frame in frames {
             let write_start = now_millis();
-            let result = tokio::time::timeout(
-                Duration::from_secs(WRITE_TIMEOUT_SECS),
-                stream.send(RobustMQCodecWrapper::AMQP(frame)),
-            )
-            .await;
+            let result =
+                tokio::time::timeout(Duration::from_secs(WRITE_TIMEOUT_SECS), stream.send(frame))
+                    .await;
             metrics_write_client_ms(
                 &NetworkConnectionType::Tcp,
                 now_millis().saturating_sub(write_start) as f64,
@@ -221,7 +235,7 @@ async fn write_tcp_frames_atomic(
     async fn write_tls_frames_atomic(
         &self,
         connection_id: u64,
-        frames: Vec<AMQPFrame>,
+        frames: Vec<RobustMQCodecWrapper>,
     ) -> ResultCommonError {
         let writer = self
             .tcp_tls_write_list
@@ -229,7 +243,7 @@ async fn write_tls_frames_atomic(
             .map(|entry| entry.value().clone())
             .ok_or_else(|| {
                 debug!(
-                    "Write to tls skipped: connection {} not found, amqp frame batch",
+                    "Write to tls skipped: connection {} not found, frame batch",
                     connection_id
                 );
                 CommonError::NotObtainAvailableConnection("tls".to_string(), connection_id)
@@ -238,11 +252,9 @@ async fn write_tls_frames_atomic(
         let mut stream = writer.lock().await;
         for frame in frames {
             let write_start = now_millis();
-            let result = tokio::time::timeout(
-                Duration::from_secs(WRITE_TIMEOUT_SECS),
-                stream.send(RobustMQCodecWrapper::AMQP(frame)),
-            )
-            .await;
+            let result =
+                tokio::time::timeout(Duration::from_secs(WRITE_TIMEOUT_SECS), stream.send(frame))
+                    .await;
             metrics_write_client_ms(
                 &NetworkConnectionType::Tls,
                 now_millis().saturating_sub(write_start) as f64,
@@ -280,7 +292,7 @@ async fn write_tls_frames_atomic(
     async fn write_quic_frames_atomic(
         &self,
         connection_id: u64,
-        frames: Vec<AMQPFrame>,
+        frames: Vec<RobustMQCodecWrapper>,
     ) -> ResultCommonError {
         let writer = self
             .quic_write_list
@@ -288,7 +300,7 @@ async fn write_quic_frames_atomic(
             .map(|entry| entry.value().clone())
             .ok_or_else(|| {
                 debug!(
-                    "Write to quic skipped: connection {} not found, amqp frame batch",
+                    "Write to quic skipped: connection {} not found, frame batch",
                     connection_id
                 );
                 CommonError::NotObtainAvailableConnection("quic".to_string(), connection_id)
@@ -297,11 +309,9 @@ async fn write_quic_frames_atomic(
         let mut stream = writer.lock().await;
         for frame in frames {
             let write_start = now_millis();
-            let result = tokio::time::timeout(
-                Duration::from_secs(WRITE_TIMEOUT_SECS),
-                stream.send(RobustMQCodecWrapper::AMQP(frame)),
-            )
-            .await;
+            let result =
+                tokio::time::timeout(Duration::from_secs(WRITE_TIMEOUT_SECS), stream.send(frame))
+                    .await;
             metrics_write_client_ms(
                 &NetworkConnectionType::QUIC,
                 now_millis().saturating_sub(write_start) as f64,
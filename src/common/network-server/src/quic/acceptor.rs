@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::common::channel::RequestChannel;
+use crate::common::client_cert::peer_cert_identity;
 use crate::common::connection_manager::ConnectionManager;
 use crate::common::tool::{check_connection_limit, read_packet};
 use crate::quic::stream::{QuicFramedReadStream, QuicFramedWriteStream};
@@ -27,6 +28,7 @@
 use tokio::select;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc::{self, Receiver};
+use tokio_rustls::rustls::pki_types::CertificateDer;
 use tracing::{debug, error};
 
 #[allow(clippy::too_many_arguments)]
@@ -71,6 +73,10 @@ pub(crate) async fn acceptor_process(
                                 Ok(connection) => {
                                     debug!("Accept {} connection:{:?}", network_type, connection.remote_address());
                                     let client_addr = connection.remote_address();
+                                    let peer_cert = connection
+                                        .peer_identity()
+                                        .and_then(|identity| identity.downcast::<Vec<CertificateDer<'static>>>().ok())
+                                        .and_then(|certs| peer_cert_identity(Some(certs.as_slice())));
                                     match connection.accept_bi().await {
                                         Ok((w_stream, r_stream)) => {
                                             let codec_write = QuicFramedWriteStream::new(w_stream, row_codec.clone());
@@ -81,11 +87,12 @@ pub(crate) async fn acceptor_process(
                                             }
 
                                             let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                            let connection = NetworkConnection::new(
+                                            let mut connection = NetworkConnection::new(
                                                 NetworkConnectionType::QUIC,
                                                 client_addr,
                                                 Some(connection_stop_sx.clone())
                                             );
+                                            connection.set_peer_cert(peer_cert);
 
                                             connection_manager.add_connection(connection.clone());
                                             connection_manager.add_mqtt_quic_write(connection.connection_id, codec_write);
@@ -163,6 +170,9 @@ fn read_frame_process(
                                     RobustMQCodecWrapper::AMQP(p) => {
                                         read_packet(RobustMQPacket::AMQP(vec![p]), &request_channel, &connection, &network_type).await;
                                     }
+                                    RobustMQCodecWrapper::AMQP1(p) => {
+                                        read_packet(RobustMQPacket::AMQP1(vec![p]), &request_channel, &connection, &network_type).await;
+                                    }
                                     RobustMQCodecWrapper::StorageEngine(p) => {
                                         read_packet(RobustMQPacket::StorageEngine(p), &request_channel, &connection, &network_type).await;
                                     }
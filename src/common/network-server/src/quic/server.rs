@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::tls_acceptor::{load_certs, load_key};
+use crate::common::client_cert::build_client_cert_verifier;
+use crate::common::proxy_protocol::warn_proxy_protocol_unsupported;
+use crate::common::tls_cert::tls_cert_resolver;
 use crate::context::ServerContext;
 use crate::quic::acceptor::acceptor_process;
 use common_base::error::common::CommonError;
@@ -20,10 +22,11 @@
 use common_config::broker::broker_config;
 use metadata_struct::connection::NetworkConnectionType;
 use protocol::codec::RobustMQCodec;
+use quinn::crypto::rustls::QuicServerConfig;
 use quinn::{Endpoint, ServerConfig};
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
-use std::path::Path;
 use std::sync::Arc;
+use tokio_rustls::rustls;
 use tracing::info;
 
 pub struct QuicServer {
@@ -37,6 +40,7 @@ pub fn new(name: String, context: ServerContext) -> Self {
     }
 
     pub async fn start(&self, port: u32) -> ResultCommonError {
+        warn_proxy_protocol_unsupported(port, "QUIC");
         let config = self.build_config()?;
         let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), port as u16));
         let server = Endpoint::server(config, addr)?;
@@ -67,10 +71,17 @@ pub async fn stop(&self) {}
 
     #[allow(clippy::result_large_err)]
     fn build_config(&self) -> Result<ServerConfig, CommonError> {
-        let conf = broker_config();
-        let certs = load_certs(Path::new(&conf.broker_network.tls_cert))?;
-        let key = load_key(Path::new(&conf.broker_network.tls_key))?;
-        let config = ServerConfig::with_single_cert(certs, key)?;
-        Ok(config)
+        // QUIC runs TLS 1.3 only; 0-RTT is accepted as quinn does by default.
+        let builder =
+            rustls::ServerConfig::builder_with_protocol_versions(&[&rustls::version::TLS13]);
+        let builder = match build_client_cert_verifier(&broker_config().broker_network)? {
+            Some(verifier) => builder.with_client_cert_verifier(verifier),
+            None => builder.with_no_client_auth(),
+        };
+        let mut tls_config = builder.with_cert_resolver(tls_cert_resolver()?);
+        tls_config.max_early_data_size = u32::MAX;
+        let crypto = QuicServerConfig::try_from(tls_config)
+            .map_err(|e| CommonError::CommonError(e.to_string()))?;
+        Ok(ServerConfig::with_crypto(Arc::new(crypto)))
     }
 }
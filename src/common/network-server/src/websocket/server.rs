@@ -13,31 +13,41 @@
 // limitations under the License.
 
 use crate::common::channel::RequestChannel;
+use crate::common::client_cert::{build_client_cert_verifier, peer_cert_identity};
 use crate::common::connection_manager::ConnectionManager;
 use crate::common::packet::RequestPackage;
+use crate::common::proxy_protocol::warn_proxy_protocol_unsupported;
+use crate::common::tls_cert::tls_cert_resolver;
 use crate::common::tool::check_connection_limit;
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
+use axum::middleware::AddExtension;
 use axum::response::Response;
 use axum::routing::get;
-use axum::Router;
+use axum::{Extension, Router};
 use axum_extra::headers::UserAgent;
 use axum_extra::TypedHeader;
-use axum_server::tls_rustls::RustlsConfig;
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
 use broker_core::cache::NodeCacheManager;
 use bytes::{BufMut, BytesMut};
 use common_base::error::ResultCommonError;
 use common_config::broker::broker_config;
+use futures::future::BoxFuture;
 use futures_util::stream::StreamExt;
-use metadata_struct::connection::{NetworkConnection, NetworkConnectionType};
+use metadata_struct::connection::{NetworkConnection, NetworkConnectionType, PeerCertIdentity};
 use protocol::codec::{RobustMQCodec, RobustMQCodecWrapper};
 use protocol::robust::{RobustMQPacket, RobustMQProtocol};
 use rate_limit::global::GlobalRateLimiterManager;
+use std::io;
 use std::net::SocketAddr;
-use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::select;
 use tokio::sync::broadcast;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::server::TlsStream;
+use tower::Layer;
 use tracing::{debug, error, info, warn};
 
 pub const ROUTE_ROOT: &str = "/mqtt";
@@ -66,6 +76,7 @@ pub fn new(state: WebSocketServerState) -> Self {
 
     pub async fn start_ws(&self) -> ResultCommonError {
         let ip: SocketAddr = format!("0.0.0.0:{}", self.state.ws_port).parse()?;
+        warn_proxy_protocol_unsupported(self.state.ws_port, "WebSocket");
         let app = routes_v1(self.state.clone());
 
         info!(
@@ -80,26 +91,57 @@ pub async fn start_ws(&self) -> ResultCommonError {
 
     pub async fn start_wss(&self) -> ResultCommonError {
         let ip: SocketAddr = format!("0.0.0.0:{}", self.state.wss_port).parse()?;
+        warn_proxy_protocol_unsupported(self.state.wss_port, "WebSocket");
         let app = routes_v1(self.state.clone());
 
-        let config = broker_config();
-        let tls_config = RustlsConfig::from_pem_file(
-            PathBuf::from(config.broker_network.tls_cert.clone()),
-            PathBuf::from(config.broker_network.tls_key.clone()),
-        )
-        .await?;
+        let builder = match build_client_cert_verifier(&broker_config().broker_network)? {
+            Some(verifier) => ServerConfig::builder().with_client_cert_verifier(verifier),
+            None => ServerConfig::builder().with_no_client_auth(),
+        };
+        let mut server_config = builder.with_cert_resolver(tls_cert_resolver()?);
+        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        let tls_config = RustlsConfig::from_config(Arc::new(server_config));
 
         info!(
             "{:?} WebSocket TLS Server start success. addr:{}",
             self.state.protocol, ip
         );
-        axum_server::bind_rustls(ip, tls_config)
+        axum_server::bind(ip)
+            .acceptor(PeerCertAcceptor {
+                inner: RustlsAcceptor::new(tls_config),
+            })
             .serve(app.into_make_service_with_connect_info::<SocketAddr>())
             .await?;
         Ok(())
     }
 }
 
+/// Runs the TLS handshake and hands the client certificate's identity to the
+/// handler of every request on the connection.
+#[derive(Clone)]
+struct PeerCertAcceptor {
+    inner: RustlsAcceptor,
+}
+
+impl<I, S> Accept<I, S> for PeerCertAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = TlsStream<I>;
+    type Service = AddExtension<S, Option<PeerCertIdentity>>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let handshake = self.inner.accept(stream, service);
+        Box::pin(async move {
+            let (stream, service) = handshake.await?;
+            let peer_cert = peer_cert_identity(stream.get_ref().1.peer_certificates());
+            Ok((stream, Extension(peer_cert).layer(service)))
+        })
+    }
+}
+
 fn routes_v1(state: WebSocketServerState) -> Router {
     let mqtt_ws = Router::new().route(ROUTE_ROOT, get(ws_handler));
     let app = Router::new().merge(mqtt_ws);
@@ -111,7 +153,9 @@ async fn ws_handler(
     State(state): State<WebSocketServerState>,
     user_agent: Option<TypedHeader<UserAgent>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    peer_cert: Option<Extension<Option<PeerCertIdentity>>>,
 ) -> Response {
+    let peer_cert = peer_cert.and_then(|Extension(peer_cert)| peer_cert);
     let user_agent = if let Some(TypedHeader(user_agent)) = user_agent {
         user_agent.to_string()
     } else {
@@ -124,6 +168,7 @@ async fn ws_handler(
             handle_socket(
                 socket,
                 addr,
+                peer_cert,
                 state.connection_manager.clone(),
                 state.request_channel.clone(),
                 state.global_limit_manager.clone(),
@@ -133,9 +178,11 @@ async fn ws_handler(
         })
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_socket(
     socket: WebSocket,
     addr: SocketAddr,
+    peer_cert: Option<PeerCertIdentity>,
     connection_manager: Arc<ConnectionManager>,
     request_channel: Arc<RequestChannel>,
     global_limit_manager: Arc<GlobalRateLimiterManager>,
@@ -143,7 +190,8 @@ async fn handle_socket(
     stop_sx: broadcast::Sender<bool>,
 ) {
     let (sender, mut receiver) = socket.split();
-    let connection = NetworkConnection::new(NetworkConnectionType::WebSocket, addr, None);
+    let mut connection = NetworkConnection::new(NetworkConnectionType::WebSocket, addr, None);
+    connection.set_peer_cert(peer_cert);
     let connection_id = connection.connection_id;
     connection_manager.add_websocket_write(connection_id, sender);
     connection_manager.add_connection(connection);
@@ -182,6 +230,7 @@ async fn handle_socket(
                                         RobustMQCodecWrapper::MQTT(pkg) => RobustMQPacket::MQTT(pkg.packet),
                                         RobustMQCodecWrapper::KAFKA(pkg) => RobustMQPacket::KAFKA(pkg),
                                         RobustMQCodecWrapper::AMQP(pkg) => RobustMQPacket::AMQP(vec![pkg]),
+                                        RobustMQCodecWrapper::AMQP1(pkg) => RobustMQPacket::AMQP1(vec![pkg]),
                                         RobustMQCodecWrapper::StorageEngine(pkg) => RobustMQPacket::StorageEngine(pkg),
                                         RobustMQCodecWrapper::NATS(pkt) => RobustMQPacket::NATS(pkt),
                                     };
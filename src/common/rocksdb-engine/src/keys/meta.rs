@@ -249,6 +249,46 @@ pub fn storage_key_mqtt_schema_prefix() -> String {
     format!("{}mqtt/schema/", PREFIX_META)
 }
 
+// MQTT: schema versions, by subject. Versions are zero-padded so a prefix
+// scan returns them in order.
+#[inline]
+pub fn storage_key_mqtt_schema_version(tenant: &str, subject: &str, version: u32) -> String {
+    format!(
+        "{}mqtt/schema_version/{}/{}/{:010}",
+        PREFIX_META, tenant, subject, version
+    )
+}
+
+#[inline]
+pub fn storage_key_mqtt_schema_version_subject_prefix(tenant: &str, subject: &str) -> String {
+    format!("{}mqtt/schema_version/{}/{}/", PREFIX_META, tenant, subject)
+}
+
+#[inline]
+pub fn storage_key_mqtt_schema_version_tenant_prefix(tenant: &str) -> String {
+    format!("{}mqtt/schema_version/{}/", PREFIX_META, tenant)
+}
+
+#[inline]
+pub fn storage_key_mqtt_schema_version_prefix() -> String {
+    format!("{}mqtt/schema_version/", PREFIX_META)
+}
+
+/// Last version number handed out in a subject; deleted versions are not reused.
+#[inline]
+pub fn storage_key_mqtt_schema_version_seq(tenant: &str, subject: &str) -> String {
+    format!(
+        "{}mqtt/schema_version_seq/{}/{}",
+        PREFIX_META, tenant, subject
+    )
+}
+
+/// Last schema id handed out, cluster-wide.
+#[inline]
+pub fn storage_key_mqtt_schema_id_seq() -> String {
+    format!("{}mqtt/schema_id_seq", PREFIX_META)
+}
+
 // MQTT: schema bindings.
 #[inline]
 pub fn storage_key_mqtt_schema_bind(
@@ -283,6 +323,22 @@ pub fn storage_key_mqtt_schema_bind_prefix() -> String {
     format!("{}mqtt/schema_bind/", PREFIX_META)
 }
 
+// Rules, shared by every protocol.
+#[inline]
+pub fn storage_key_rule(tenant: &str, rule_name: &str) -> String {
+    format!("{}rule/{}/{}", PREFIX_META, tenant, rule_name)
+}
+
+#[inline]
+pub fn storage_key_rule_tenant_prefix(tenant: &str) -> String {
+    format!("{}rule/{}/", PREFIX_META, tenant)
+}
+
+#[inline]
+pub fn storage_key_rule_prefix() -> String {
+    format!("{}rule/", PREFIX_META)
+}
+
 // MQTT: ACLs.
 #[inline]
 pub fn storage_key_mqtt_acl(tenant: &str, name: &str) -> String {
@@ -388,6 +444,46 @@ pub fn storage_key_nats_subscribe_prefix() -> String {
     format!("{}nats/subscribe/", PREFIX_META)
 }
 
+// NATS: JetStream streams.
+#[inline]
+pub fn storage_key_nats_stream(tenant: &str, stream_name: &str) -> String {
+    format!("{}nats/stream/{}/{}", PREFIX_META, tenant, stream_name)
+}
+
+#[inline]
+pub fn storage_key_nats_stream_tenant_prefix(tenant: &str) -> String {
+    format!("{}nats/stream/{}/", PREFIX_META, tenant)
+}
+
+#[inline]
+pub fn storage_key_nats_stream_prefix() -> String {
+    format!("{}nats/stream/", PREFIX_META)
+}
+
+// NATS: JetStream consumers.
+#[inline]
+pub fn storage_key_nats_consumer(tenant: &str, stream_name: &str, consumer_name: &str) -> String {
+    format!(
+        "{}nats/consumer/{}/{}/{}",
+        PREFIX_META, tenant, stream_name, consumer_name
+    )
+}
+
+#[inline]
+pub fn storage_key_nats_consumer_stream_prefix(tenant: &str, stream_name: &str) -> String {
+    format!("{}nats/consumer/{}/{}/", PREFIX_META, tenant, stream_name)
+}
+
+#[inline]
+pub fn storage_key_nats_consumer_tenant_prefix(tenant: &str) -> String {
+    format!("{}nats/consumer/{}/", PREFIX_META, tenant)
+}
+
+#[inline]
+pub fn storage_key_nats_consumer_prefix() -> String {
+    format!("{}nats/consumer/", PREFIX_META)
+}
+
 // MQ9: mailboxes.
 #[inline]
 pub fn storage_key_mq9_mail(tenant: &str, mail_address: &str) -> String {
@@ -473,6 +569,33 @@ pub fn storage_key_kafka_scram_tenant_prefix(tenant: &str) -> String {
     format!("{}kafka/scram/{}/", PREFIX_META, tenant)
 }
 
+// Kafka: share-partition state, one entry per (group, topic, partition).
+#[inline]
+pub fn storage_key_kafka_share_partition(
+    tenant: &str,
+    group_id: &str,
+    topic: &str,
+    partition: i32,
+) -> String {
+    format!(
+        "{}kafka/share_partition/{}/{}/{}/{}",
+        PREFIX_META, tenant, group_id, topic, partition
+    )
+}
+
+#[inline]
+pub fn storage_key_kafka_share_partition_group_prefix(tenant: &str, group_id: &str) -> String {
+    format!(
+        "{}kafka/share_partition/{}/{}/",
+        PREFIX_META, tenant, group_id
+    )
+}
+
+#[inline]
+pub fn storage_key_kafka_share_partition_tenant_prefix(tenant: &str) -> String {
+    format!("{}kafka/share_partition/{}/", PREFIX_META, tenant)
+}
+
 // AMQP: exchanges.
 #[inline]
 pub fn storage_key_amqp_exchange(tenant: &str, exchange_name: &str) -> String {
@@ -0,0 +1,161 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::login::scram::{ScramHash, ScramMethod};
+use crate::manager::SecurityManager;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// What an enhanced authentication method wants done after one client message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnhancedAuthStep {
+    /// Send this challenge to the client and wait for its next message.
+    Continue(Vec<u8>),
+    /// The client proved it is `username`. `data`, if any, goes back to the
+    /// client with the final packet (e.g. the SCRAM server signature).
+    Success {
+        username: String,
+        data: Option<Vec<u8>>,
+    },
+    /// The exchange is over and the client is not authenticated.
+    Failed(String),
+}
+
+/// One in-progress exchange. Each call consumes the client's next message.
+#[async_trait]
+pub trait EnhancedAuthSession: Send + Sync {
+    async fn step(
+        &mut self,
+        security_manager: &SecurityManager,
+        tenant: &str,
+        data: &[u8],
+    ) -> EnhancedAuthStep;
+}
+
+/// A challenge/response authentication method, keyed by the name clients put
+/// in the Authentication-Method property.
+pub trait EnhancedAuthMethod: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn start(&self) -> Box<dyn EnhancedAuthSession>;
+}
+
+/// The enhanced authentication methods the broker accepts. SCRAM-SHA-256 and
+/// SCRAM-SHA-512 are built in; others can be registered at startup.
+#[derive(Clone)]
+pub struct EnhancedAuthRegistry {
+    methods: Arc<DashMap<String, Arc<dyn EnhancedAuthMethod>>>,
+}
+
+impl Default for EnhancedAuthRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnhancedAuthRegistry {
+    pub fn new() -> Self {
+        let registry = EnhancedAuthRegistry {
+            methods: Arc::new(DashMap::with_capacity(4)),
+        };
+        registry.register(Arc::new(ScramMethod::new(ScramHash::Sha256)));
+        registry.register(Arc::new(ScramMethod::new(ScramHash::Sha512)));
+        registry
+    }
+
+    /// Adds a method, replacing any registered under the same name.
+    pub fn register(&self, method: Arc<dyn EnhancedAuthMethod>) {
+        self.methods.insert(method.name().to_string(), method);
+    }
+
+    pub fn unregister(&self, name: &str) {
+        self.methods.remove(name);
+    }
+
+    pub fn is_supported(&self, name: &str) -> bool {
+        self.methods.contains_key(name)
+    }
+
+    /// Starts a new exchange for `name`, or `None` if no such method is registered.
+    pub fn start(&self, name: &str) -> Option<Box<dyn EnhancedAuthSession>> {
+        self.methods.get(name).map(|method| method.start())
+    }
+
+    pub fn method_names(&self) -> Vec<String> {
+        self.methods.iter().map(|m| m.key().clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticToken;
+
+    struct StaticTokenSession;
+
+    #[async_trait]
+    impl EnhancedAuthSession for StaticTokenSession {
+        async fn step(&mut self, _: &SecurityManager, _: &str, data: &[u8]) -> EnhancedAuthStep {
+            if data == b"let-me-in" {
+                EnhancedAuthStep::Success {
+                    username: "token-user".to_string(),
+                    data: None,
+                }
+            } else {
+                EnhancedAuthStep::Failed("bad token".to_string())
+            }
+        }
+    }
+
+    impl EnhancedAuthMethod for StaticToken {
+        fn name(&self) -> &str {
+            "STATIC-TOKEN"
+        }
+
+        fn start(&self) -> Box<dyn EnhancedAuthSession> {
+            Box::new(StaticTokenSession)
+        }
+    }
+
+    #[test]
+    fn scram_is_built_in() {
+        let registry = EnhancedAuthRegistry::new();
+        assert!(registry.is_supported("SCRAM-SHA-256"));
+        assert!(registry.is_supported("SCRAM-SHA-512"));
+        assert!(registry.start("KERBEROS").is_none());
+    }
+
+    #[tokio::test]
+    async fn custom_methods_can_be_registered() {
+        let registry = EnhancedAuthRegistry::new();
+        registry.register(Arc::new(StaticToken));
+
+        let security_manager = SecurityManager::new();
+        let mut session = registry.start("STATIC-TOKEN").unwrap();
+        assert_eq!(
+            session
+                .step(&security_manager, "default", b"let-me-in")
+                .await,
+            EnhancedAuthStep::Success {
+                username: "token-user".to_string(),
+                data: None,
+            }
+        );
+
+        registry.unregister("STATIC-TOKEN");
+        assert!(!registry.is_supported("STATIC-TOKEN"));
+    }
+}
@@ -172,6 +172,7 @@ async fn apply(&self) -> Result<bool, CommonError> {
             salt: None,
             is_superuser: claims.is_superuser.unwrap_or(false),
             create_time: now_second(),
+            scram_credentials: Vec::new(),
         };
         self.cache_manager.add_user(user);
 
@@ -14,9 +14,12 @@
 
 use std::str::FromStr;
 
+pub mod enhanced;
 // pub mod jwt;
 pub mod password;
+pub mod scram;
 pub mod super_user;
+pub mod x509;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LoginType {
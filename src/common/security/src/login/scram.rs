@@ -0,0 +1,623 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::login::enhanced::{EnhancedAuthMethod, EnhancedAuthSession, EnhancedAuthStep};
+use crate::manager::SecurityManager;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use common_base::error::common::CommonError;
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use metadata_struct::auth::user::ScramCredential;
+use rand::distributions::Alphanumeric;
+use rand::{Rng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+use std::sync::{Arc, OnceLock};
+use subtle::ConstantTimeEq;
+use tracing::debug;
+
+pub const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+pub const SCRAM_SHA_512: &str = "SCRAM-SHA-512";
+
+const SCRAM_ITERATIONS: u32 = 4096;
+const SALT_LEN: usize = 16;
+const SERVER_NONCE_LEN: usize = 24;
+
+// The only reason a failed exchange gives the client, whatever went wrong.
+const AUTH_FAILED: &str = "authentication failed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScramHash {
+    Sha256,
+    Sha512,
+}
+
+impl ScramHash {
+    pub fn mechanism(&self) -> &'static str {
+        match self {
+            ScramHash::Sha256 => SCRAM_SHA_256,
+            ScramHash::Sha512 => SCRAM_SHA_512,
+        }
+    }
+
+    fn key_len(&self) -> usize {
+        match self {
+            ScramHash::Sha256 => 32,
+            ScramHash::Sha512 => 64,
+        }
+    }
+
+    fn hmac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            ScramHash::Sha256 => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+            ScramHash::Sha512 => {
+                let mut mac =
+                    Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+
+    fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            ScramHash::Sha256 => Sha256::digest(data).to_vec(),
+            ScramHash::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    fn salted_password(&self, password: &str, salt: &[u8], iterations: u32) -> Vec<u8> {
+        match self {
+            ScramHash::Sha256 => {
+                pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(password.as_bytes(), salt, iterations)
+                    .to_vec()
+            }
+            ScramHash::Sha512 => {
+                pbkdf2::pbkdf2_hmac_array::<Sha512, 64>(password.as_bytes(), salt, iterations)
+                    .to_vec()
+            }
+        }
+    }
+}
+
+/// RFC 5802 StoredKey and ServerKey for one password, salt and iteration count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScramKeys {
+    pub stored_key: Vec<u8>,
+    pub server_key: Vec<u8>,
+}
+
+impl ScramKeys {
+    pub fn derive(hash: ScramHash, password: &str, salt: &[u8], iterations: u32) -> Self {
+        let salted = hash.salted_password(password, salt, iterations);
+        let client_key = hash.hmac(&salted, b"Client Key");
+        ScramKeys {
+            stored_key: hash.digest(&client_key),
+            server_key: hash.hmac(&salted, b"Server Key"),
+        }
+    }
+}
+
+/// Derives a user's stored credential for `hash` with a fresh random salt.
+pub fn scram_credential(hash: ScramHash, password: &str) -> ScramCredential {
+    let mut salt = vec![0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let keys = ScramKeys::derive(hash, password, &salt, SCRAM_ITERATIONS);
+    ScramCredential {
+        mechanism: hash.mechanism().to_string(),
+        salt,
+        iterations: SCRAM_ITERATIONS,
+        stored_key: keys.stored_key,
+        server_key: keys.server_key,
+    }
+}
+
+/// The credentials stored with a new user, one per built-in mechanism.
+/// PBKDF2 is deliberately slow, so it runs on the blocking pool.
+pub async fn scram_credentials(password: &str) -> Result<Vec<ScramCredential>, CommonError> {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || {
+        [ScramHash::Sha256, ScramHash::Sha512]
+            .into_iter()
+            .map(|hash| scram_credential(hash, &password))
+            .collect()
+    })
+    .await
+    .map_err(|e| CommonError::CommonError(format!("failed to derive SCRAM credentials: {e}")))
+}
+
+/// SCRAM (RFC 5802) as an enhanced authentication method, checked against the
+/// user store. Users carry credentials derived when they were created. Users
+/// without them (created earlier, or loaded from an external source) can
+/// still use SCRAM if their password is stored in plain: credentials are then
+/// derived on first use and kept in memory. Users stored with a salted hash
+/// and no credentials cannot use SCRAM.
+///
+/// Unknown users get a challenge like everyone else, with a salt derived
+/// from their name (RFC 5802 section 5.1), and fail at the proof, so the
+/// exchange doesn't tell whether a user exists.
+pub struct ScramMethod {
+    hash: ScramHash,
+    derived: Arc<DerivedCredentials>,
+}
+
+// (tenant, username) -> digest of the password the credential was derived
+// from, and the credential. A changed password is derived again.
+type DerivedCredentials = DashMap<(String, String), (Vec<u8>, ScramCredential)>;
+
+impl ScramMethod {
+    pub fn new(hash: ScramHash) -> Self {
+        ScramMethod {
+            hash,
+            derived: Arc::new(DashMap::with_capacity(8)),
+        }
+    }
+}
+
+impl EnhancedAuthMethod for ScramMethod {
+    fn name(&self) -> &str {
+        self.hash.mechanism()
+    }
+
+    fn start(&self) -> Box<dyn EnhancedAuthSession> {
+        Box::new(ScramSession::AwaitingClientFirst {
+            hash: self.hash,
+            derived: self.derived.clone(),
+        })
+    }
+}
+
+enum ScramSession {
+    AwaitingClientFirst {
+        hash: ScramHash,
+        derived: Arc<DerivedCredentials>,
+    },
+    AwaitingClientFinal {
+        hash: ScramHash,
+        username: String,
+        keys: ScramKeys,
+        client_first_bare: String,
+        server_first: String,
+        combined_nonce: String,
+    },
+    Finished,
+}
+
+#[async_trait]
+impl EnhancedAuthSession for ScramSession {
+    async fn step(
+        &mut self,
+        security_manager: &SecurityManager,
+        tenant: &str,
+        data: &[u8],
+    ) -> EnhancedAuthStep {
+        match std::mem::replace(self, ScramSession::Finished) {
+            ScramSession::AwaitingClientFirst { hash, derived } => {
+                let parsed = match parse_client_first(data) {
+                    Ok(parsed) => parsed,
+                    Err(e) => return failed(&e),
+                };
+                let credential = match user_credential(
+                    security_manager,
+                    &derived,
+                    tenant,
+                    &parsed.username,
+                    hash,
+                )
+                .await
+                {
+                    Some(credential) => credential,
+                    None => unknown_user_credential(hash, tenant, &parsed.username),
+                };
+
+                let server_nonce: String = rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(SERVER_NONCE_LEN)
+                    .map(char::from)
+                    .collect();
+                let combined_nonce = format!("{}{}", parsed.client_nonce, server_nonce);
+                let server_first = format!(
+                    "r={},s={},i={}",
+                    combined_nonce,
+                    BASE64.encode(&credential.salt),
+                    credential.iterations
+                );
+
+                *self = ScramSession::AwaitingClientFinal {
+                    hash,
+                    username: parsed.username,
+                    keys: ScramKeys {
+                        stored_key: credential.stored_key,
+                        server_key: credential.server_key,
+                    },
+                    client_first_bare: parsed.bare,
+                    server_first: server_first.clone(),
+                    combined_nonce,
+                };
+                EnhancedAuthStep::Continue(server_first.into_bytes())
+            }
+            ScramSession::AwaitingClientFinal {
+                hash,
+                username,
+                keys,
+                client_first_bare,
+                server_first,
+                combined_nonce,
+            } => match verify_client_final(
+                hash,
+                &keys,
+                &client_first_bare,
+                &server_first,
+                &combined_nonce,
+                data,
+            ) {
+                Ok(server_final) => EnhancedAuthStep::Success {
+                    username,
+                    data: Some(server_final.into_bytes()),
+                },
+                Err(e) => failed(&e),
+            },
+            ScramSession::Finished => failed("SCRAM exchange already finished"),
+        }
+    }
+}
+
+fn failed(reason: &str) -> EnhancedAuthStep {
+    debug!("SCRAM authentication failed: {}", reason);
+    EnhancedAuthStep::Failed(AUTH_FAILED.to_string())
+}
+
+/// The user's credential for `hash`: the stored one, else one derived from a
+/// plain password (once, off the async executor). None for unknown users and
+/// for users whose password is only stored hashed.
+async fn user_credential(
+    security_manager: &SecurityManager,
+    derived: &DerivedCredentials,
+    tenant: &str,
+    username: &str,
+    hash: ScramHash,
+) -> Option<ScramCredential> {
+    let user = security_manager
+        .metadata
+        .user_info
+        .get(tenant)?
+        .get(username)?
+        .clone();
+    if let Some(credential) = user.scram_credential(hash.mechanism()) {
+        return Some(credential.clone());
+    }
+    if user.salt.is_some() {
+        return None;
+    }
+
+    let key = (tenant.to_string(), username.to_string());
+    let password_digest = Sha256::digest(user.password.as_bytes()).to_vec();
+    if let Some(entry) = derived.get(&key) {
+        if entry.0 == password_digest {
+            return Some(entry.1.clone());
+        }
+    }
+    let credential = tokio::task::spawn_blocking(move || scram_credential(hash, &user.password))
+        .await
+        .ok()?;
+    derived.insert(key, (password_digest, credential.clone()));
+    Some(credential)
+}
+
+// Key for unknown users' salts: random per process, so the salts can't be
+// computed (and compared) from outside.
+fn unknown_user_salt_key() -> &'static [u8; 32] {
+    static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    })
+}
+
+/// A credential no proof matches, with the same salt every time for the
+/// same name so repeated attempts look like those against a real user.
+fn unknown_user_credential(hash: ScramHash, tenant: &str, username: &str) -> ScramCredential {
+    let name = format!("{tenant}/{username}");
+    let mut salt = hash.hmac(unknown_user_salt_key(), name.as_bytes());
+    salt.truncate(SALT_LEN);
+    let mut stored_key = vec![0u8; hash.key_len()];
+    let mut server_key = vec![0u8; hash.key_len()];
+    rand::thread_rng().fill_bytes(&mut stored_key);
+    rand::thread_rng().fill_bytes(&mut server_key);
+    ScramCredential {
+        mechanism: hash.mechanism().to_string(),
+        salt,
+        iterations: SCRAM_ITERATIONS,
+        stored_key,
+        server_key,
+    }
+}
+
+struct ParsedClientFirst {
+    username: String,
+    client_nonce: String,
+    bare: String,
+}
+
+// client-first-message = gs2-header + client-first-message-bare. Only the
+// "no channel binding, no authzid" header ("n,,") is accepted.
+fn parse_client_first(bytes: &[u8]) -> Result<ParsedClientFirst, String> {
+    let text = std::str::from_utf8(bytes).map_err(|_| "client-first is not UTF-8".to_string())?;
+    let bare = text
+        .strip_prefix("n,,")
+        .ok_or_else(|| "unsupported gs2 header".to_string())?;
+
+    let mut username = None;
+    let mut client_nonce = None;
+    for field in bare.split(',') {
+        if let Some(v) = field.strip_prefix("n=") {
+            // SCRAM username escaping: =2C -> ',', =3D -> '='.
+            username = Some(v.replace("=2C", ",").replace("=3D", "="));
+        } else if let Some(v) = field.strip_prefix("r=") {
+            client_nonce = Some(v.to_string());
+        }
+    }
+
+    Ok(ParsedClientFirst {
+        username: username.ok_or_else(|| "missing username in client-first".to_string())?,
+        client_nonce: client_nonce.ok_or_else(|| "missing nonce in client-first".to_string())?,
+        bare: bare.to_string(),
+    })
+}
+
+// Checks the client-final proof and returns the server-final message
+// (v=<ServerSignature>) on success.
+fn verify_client_final(
+    hash: ScramHash,
+    keys: &ScramKeys,
+    client_first_bare: &str,
+    server_first: &str,
+    combined_nonce: &str,
+    client_final: &[u8],
+) -> Result<String, String> {
+    let text =
+        std::str::from_utf8(client_final).map_err(|_| "client-final is not UTF-8".to_string())?;
+
+    let mut channel_binding = None;
+    let mut nonce = None;
+    let mut proof_b64 = None;
+    for field in text.split(',') {
+        if let Some(v) = field.strip_prefix("c=") {
+            channel_binding = Some(v);
+        } else if let Some(v) = field.strip_prefix("r=") {
+            nonce = Some(v);
+        } else if let Some(v) = field.strip_prefix("p=") {
+            proof_b64 = Some(v);
+        }
+    }
+
+    // "biws" is base64("n,,"), the only channel binding accepted.
+    if channel_binding != Some("biws") {
+        return Err("unsupported channel binding".to_string());
+    }
+    if nonce != Some(combined_nonce) {
+        return Err("nonce mismatch".to_string());
+    }
+    let proof = BASE64
+        .decode(proof_b64.ok_or_else(|| "missing client proof".to_string())?)
+        .map_err(|_| "client proof is not valid base64".to_string())?;
+
+    let client_final_without_proof = text
+        .rsplit_once(",p=")
+        .map(|(head, _)| head)
+        .ok_or_else(|| "malformed client-final".to_string())?;
+    let auth_message = format!(
+        "{},{},{}",
+        client_first_bare, server_first, client_final_without_proof
+    );
+
+    let client_signature = hash.hmac(&keys.stored_key, auth_message.as_bytes());
+    if proof.len() != client_signature.len() {
+        return Err("client proof has wrong length".to_string());
+    }
+    // ClientKey = ClientProof XOR ClientSignature; the password is right iff
+    // H(ClientKey) == StoredKey. Compared in constant time so the response
+    // time says nothing about how much of the proof was right.
+    let client_key: Vec<u8> = proof
+        .iter()
+        .zip(client_signature.iter())
+        .map(|(a, b)| a ^ b)
+        .collect();
+    if !bool::from(hash.digest(&client_key).ct_eq(&keys.stored_key)) {
+        return Err("wrong client proof".to_string());
+    }
+
+    let server_signature = hash.hmac(&keys.server_key, auth_message.as_bytes());
+    Ok(format!("v={}", BASE64.encode(server_signature)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::auth::user::SecurityUser;
+
+    fn manager_with_user(
+        password: &str,
+        salt: Option<String>,
+        scram_credentials: Vec<ScramCredential>,
+    ) -> SecurityManager {
+        let manager = SecurityManager::new();
+        manager.metadata.add_user(SecurityUser {
+            tenant: "default".to_string(),
+            username: "alice".to_string(),
+            password: password.to_string(),
+            salt,
+            is_superuser: false,
+            create_time: 0,
+            scram_credentials,
+        });
+        manager
+    }
+
+    // What a client does with server-first: returns client-final.
+    fn client_final(
+        hash: ScramHash,
+        password: &str,
+        client_first_bare: &str,
+        server_first: &str,
+    ) -> String {
+        let mut nonce = "";
+        let mut salt = Vec::new();
+        let mut iterations = 0;
+        for field in server_first.split(',') {
+            if let Some(v) = field.strip_prefix("r=") {
+                nonce = v;
+            } else if let Some(v) = field.strip_prefix("s=") {
+                salt = BASE64.decode(v).unwrap();
+            } else if let Some(v) = field.strip_prefix("i=") {
+                iterations = v.parse().unwrap();
+            }
+        }
+        let without_proof = format!("c=biws,r={}", nonce);
+        let auth_message = format!("{},{},{}", client_first_bare, server_first, without_proof);
+
+        let salted = hash.salted_password(password, &salt, iterations);
+        let client_key = hash.hmac(&salted, b"Client Key");
+        let stored_key = hash.digest(&client_key);
+        let signature = hash.hmac(&stored_key, auth_message.as_bytes());
+        let proof: Vec<u8> = client_key
+            .iter()
+            .zip(signature.iter())
+            .map(|(a, b)| a ^ b)
+            .collect();
+        format!("{},p={}", without_proof, BASE64.encode(proof))
+    }
+
+    async fn server_first(
+        session: &mut Box<dyn EnhancedAuthSession>,
+        manager: &SecurityManager,
+    ) -> String {
+        match session
+            .step(manager, "default", b"n,,n=alice,r=abc123")
+            .await
+        {
+            EnhancedAuthStep::Continue(data) => String::from_utf8(data).unwrap(),
+            other => panic!("expected a challenge, got {:?}", other),
+        }
+    }
+
+    async fn run_exchange(
+        hash: ScramHash,
+        manager: &SecurityManager,
+        presented: &str,
+    ) -> EnhancedAuthStep {
+        let mut session = ScramMethod::new(hash).start();
+        let server_first = server_first(&mut session, manager).await;
+        assert!(server_first.starts_with("r=abc123"));
+
+        let client_final = client_final(hash, presented, "n=alice,r=abc123", &server_first);
+        session
+            .step(manager, "default", client_final.as_bytes())
+            .await
+    }
+
+    fn salt_of(server_first: &str) -> &str {
+        server_first
+            .split(',')
+            .find_map(|field| field.strip_prefix("s="))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn scram_sha256_round_trip_succeeds() {
+        let manager = manager_with_user("pencil", None, Vec::new());
+        let EnhancedAuthStep::Success { username, data } =
+            run_exchange(ScramHash::Sha256, &manager, "pencil").await
+        else {
+            panic!("expected success");
+        };
+        assert_eq!(username, "alice");
+        assert!(String::from_utf8(data.unwrap()).unwrap().starts_with("v="));
+    }
+
+    #[tokio::test]
+    async fn scram_sha512_round_trip_succeeds() {
+        let manager = manager_with_user("pencil", None, Vec::new());
+        assert!(matches!(
+            run_exchange(ScramHash::Sha512, &manager, "pencil").await,
+            EnhancedAuthStep::Success { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn stored_credentials_work_without_the_password() {
+        let credentials = scram_credentials("pencil").await.unwrap();
+        let manager = manager_with_user("5e8ff9bf55", Some("salt".to_string()), credentials);
+        assert!(matches!(
+            run_exchange(ScramHash::Sha256, &manager, "pencil").await,
+            EnhancedAuthStep::Success { .. }
+        ));
+        assert!(matches!(
+            run_exchange(ScramHash::Sha512, &manager, "pencil").await,
+            EnhancedAuthStep::Success { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn derived_credentials_keep_their_salt() {
+        let method = ScramMethod::new(ScramHash::Sha256);
+        let manager = manager_with_user("pencil", None, Vec::new());
+        let first = server_first(&mut method.start(), &manager).await;
+        let second = server_first(&mut method.start(), &manager).await;
+        assert_eq!(salt_of(&first), salt_of(&second));
+    }
+
+    #[tokio::test]
+    async fn wrong_password_is_rejected() {
+        let manager = manager_with_user("pencil", None, Vec::new());
+        assert_eq!(
+            run_exchange(ScramHash::Sha256, &manager, "crayon").await,
+            EnhancedAuthStep::Failed(AUTH_FAILED.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_or_hashed_users_get_a_challenge_and_then_fail() {
+        let unknown = SecurityManager::new();
+        let hashed = manager_with_user("5e8ff9bf55", Some("salt".to_string()), Vec::new());
+        for manager in [&unknown, &hashed] {
+            let method = ScramMethod::new(ScramHash::Sha256);
+            let first = server_first(&mut method.start(), manager).await;
+            let second = server_first(&mut method.start(), manager).await;
+            assert_eq!(salt_of(&first), salt_of(&second));
+
+            assert_eq!(
+                run_exchange(ScramHash::Sha256, manager, "pencil").await,
+                EnhancedAuthStep::Failed(AUTH_FAILED.to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_messages_fail_with_the_generic_reason() {
+        let manager = manager_with_user("pencil", None, Vec::new());
+        let mut session = ScramMethod::new(ScramHash::Sha256).start();
+        assert_eq!(
+            session.step(&manager, "default", b"y,,n=alice,r=abc").await,
+            EnhancedAuthStep::Failed(AUTH_FAILED.to_string())
+        );
+    }
+}
@@ -31,6 +31,7 @@ pub async fn try_init_system_user(client_pool: &Arc<ClientPool>) -> ResultCommon
         salt: None,
         is_superuser: true,
         create_time: now_second(),
+        scram_credentials: Vec::new(),
     };
     let user_storage = UserStorage::new(client_pool.clone());
     let res = user_storage
@@ -0,0 +1,75 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_config::broker::broker_config;
+use metadata_struct::connection::PeerCertIdentity;
+
+/// The user a verified client certificate logs in as, when
+/// `tls_peer_cert_as_username` is set. The handshake already proved the
+/// client holds the key, so no password is checked for it; ACLs and
+/// blacklists still apply to the user.
+pub fn peer_cert_username(peer_cert: Option<&PeerCertIdentity>) -> Option<String> {
+    peer_cert_field(
+        peer_cert?,
+        &broker_config().broker_network.tls_peer_cert_as_username,
+    )
+}
+
+/// The client id a verified client certificate connects as, when
+/// `tls_peer_cert_as_client_id` is set.
+pub fn peer_cert_client_id(peer_cert: Option<&PeerCertIdentity>) -> Option<String> {
+    peer_cert_field(
+        peer_cert?,
+        &broker_config().broker_network.tls_peer_cert_as_client_id,
+    )
+}
+
+/// "cn" is the subject's common name, "san" the first subjectAltName.
+fn peer_cert_field(peer_cert: &PeerCertIdentity, field: &str) -> Option<String> {
+    let value = match field.to_lowercase().as_str() {
+        "cn" => peer_cert.common_name.clone(),
+        "san" => peer_cert.subject_alt_names.first().cloned(),
+        _ => None,
+    };
+    value.filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_cert_field_test() {
+        let peer_cert = PeerCertIdentity {
+            common_name: Some("sensor-0042".to_string()),
+            subject_alt_names: vec![
+                "sensor-0042.devices.example.com".to_string(),
+                "ops@example.com".to_string(),
+            ],
+        };
+        assert_eq!(
+            peer_cert_field(&peer_cert, "cn"),
+            Some("sensor-0042".to_string())
+        );
+        assert_eq!(
+            peer_cert_field(&peer_cert, "SAN"),
+            Some("sensor-0042.devices.example.com".to_string())
+        );
+        assert_eq!(peer_cert_field(&peer_cert, ""), None);
+
+        let anonymous = PeerCertIdentity::default();
+        assert_eq!(peer_cert_field(&anonymous, "cn"), None);
+        assert_eq!(peer_cert_field(&anonymous, "san"), None);
+    }
+}
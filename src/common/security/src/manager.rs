@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::login::enhanced::EnhancedAuthRegistry;
 use crate::metadata::SecurityMetadata;
 use crate::third::build_storage_driver;
 use crate::third::storage_trait::AuthStorageAdapter;
@@ -30,6 +31,7 @@
 pub struct SecurityManager {
     storage_drivers: Arc<DashMap<String, ArcAuthStorageAdapter>>,
     pub metadata: SecurityMetadata,
+    pub enhanced_auth: EnhancedAuthRegistry,
 }
 
 impl SecurityManager {
@@ -37,6 +39,7 @@ pub fn new() -> SecurityManager {
         SecurityManager {
             storage_drivers: Arc::new(DashMap::new()),
             metadata: SecurityMetadata::new(),
+            enhanced_auth: EnhancedAuthRegistry::new(),
         }
     }
 
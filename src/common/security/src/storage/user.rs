@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::login::scram::scram_credentials;
 use common_base::error::common::CommonError;
 use common_base::error::ResultCommonError;
 use common_config::broker::broker_config;
@@ -31,7 +32,12 @@ pub fn new(client_pool: Arc<ClientPool>) -> Self {
         UserStorage { client_pool }
     }
 
-    pub async fn save_user(&self, user_info: SecurityUser) -> ResultCommonError {
+    /// Users with a plain password get their SCRAM credentials derived here,
+    /// while the password is at hand.
+    pub async fn save_user(&self, mut user_info: SecurityUser) -> ResultCommonError {
+        if user_info.salt.is_none() && user_info.scram_credentials.is_empty() {
+            user_info.scram_credentials = scram_credentials(&user_info.password).await?;
+        }
         let config = broker_config();
         let request = CreateUserRequest {
             tenant: user_info.tenant.clone(),
@@ -268,6 +268,7 @@ async fn read_all_user(&self) -> Result<Vec<SecurityUser>, CommonError> {
                 salt: Self::parse_string(map.get("salt")),
                 is_superuser: Self::parse_bool_like(map.get("is_superuser")),
                 create_time: Self::parse_created_to_seconds(map.get("created")),
+                scram_credentials: Vec::new(),
             });
         }
         Ok(users)
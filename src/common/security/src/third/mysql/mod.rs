@@ -132,6 +132,7 @@ async fn read_all_user(&self) -> Result<Vec<SecurityUser>, CommonError> {
                 salt,
                 is_superuser: is_superuser == 1,
                 create_time: Self::parse_created_to_seconds(created),
+                scram_credentials: Vec::new(),
             });
         }
         Ok(results)
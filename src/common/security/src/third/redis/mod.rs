@@ -141,6 +141,7 @@ async fn read_all_user(&self) -> Result<Vec<SecurityUser>, CommonError> {
                         },
                         is_superuser: redis_user.is_superuser == 1,
                         create_time: redis_user.created.unwrap_or_else(now_second),
+                        scram_credentials: Vec::new(),
                     });
                 }
                 Err(e) => {
@@ -14,13 +14,14 @@
 
 use async_trait::async_trait;
 use common_base::error::common::CommonError;
+use common_base::tools::now_second;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::{
     connector::config_cassandra::CassandraConnectorConfig, connector::MQTTConnector,
     storage::record::StorageRecord,
 };
-use rule_engine::apply_rule_engine;
 use scylla::{Session, SessionBuilder};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use storage_adapter::driver::StorageDriverManager;
@@ -30,7 +31,7 @@
 use super::{
     core::{BridgePluginReadConfig, BridgePluginThread},
     failure::FailureRecordInfo,
-    loops::run_connector_loop,
+    loops::{next_shard_offsets, record_position_key, run_connector_loop},
     manager::ConnectorManager,
     traits::ConnectorSink,
 };
@@ -60,6 +61,57 @@ fn build_insert_cql(&self) -> String {
             self.config.keyspace, self.config.table
         )
     }
+
+    fn offset_table_name(&self) -> String {
+        format!("{}.{}", self.config.keyspace, self.config.offset_table)
+    }
+
+    async fn create_offset_table(&self, session: &Session) -> Result<(), CommonError> {
+        let cql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (connector_name text, shard_name text, next_offset bigint, update_time bigint, PRIMARY KEY (connector_name, shard_name))",
+            self.offset_table_name()
+        );
+        session.query_unpaged(cql, ()).await.map_err(|e| {
+            CommonError::CommonError(format!("Failed to create Cassandra offset table: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Offsets are written after the rows they cover. The rows themselves are
+    /// keyed by shard and offset, so a batch replayed after a crash between the
+    /// two writes overwrites the same rows instead of adding new ones.
+    async fn store_offsets(
+        &self,
+        session: &Session,
+        records: &[StorageRecord],
+    ) -> Result<(), CommonError> {
+        let cql = format!(
+            "INSERT INTO {} (connector_name, shard_name, next_offset, update_time) VALUES (?, ?, ?, ?)",
+            self.offset_table_name()
+        );
+        let prepared = session.prepare(cql).await.map_err(|e| {
+            CommonError::CommonError(format!("Failed to prepare CQL statement: {}", e))
+        })?;
+
+        let now = now_second() as i64;
+        for (shard_name, next_offset) in next_shard_offsets(records) {
+            session
+                .execute_unpaged(
+                    &prepared,
+                    (
+                        &self.connector.connector_name,
+                        &shard_name,
+                        next_offset as i64,
+                        now,
+                    ),
+                )
+                .await
+                .map_err(|e| {
+                    CommonError::CommonError(format!("Failed to store connector offset: {}", e))
+                })?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -88,6 +140,10 @@ async fn init_sink(&self) -> Result<Self::SinkResource, CommonError> {
             ))
         })?;
 
+        if self.config.enable_exactly_once {
+            self.create_offset_table(&session).await?;
+        }
+
         debug!(
             "Connected to Cassandra: nodes={:?}, keyspace={}, table={}",
             known_nodes, self.config.keyspace, self.config.table
@@ -110,36 +166,64 @@ async fn send_batch(
             CommonError::CommonError(format!("Failed to prepare CQL statement: {}", e))
         })?;
 
-        let mut fail_messages = Vec::new();
         for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-            let payload = String::from_utf8_lossy(&processed_data).to_string();
-            let key = record.metadata.key.clone().unwrap_or_default();
+            let payload = String::from_utf8_lossy(&record.data).to_string();
+            let msgid = if self.config.enable_exactly_once {
+                record_position_key(record).into_bytes()
+            } else {
+                record.metadata.key.clone().unwrap_or_default().to_vec()
+            };
             let timestamp = record.metadata.create_t as i64;
 
             session
-                .execute_unpaged(&prepared, (key.as_ref(), "", 0i32, &payload, timestamp))
+                .execute_unpaged(&prepared, (msgid.as_slice(), "", 0i32, &payload, timestamp))
                 .await
                 .map_err(|e| {
                     CommonError::CommonError(format!("Failed to execute CQL insert: {}", e))
                 })?;
         }
 
-        Ok(fail_messages)
+        if self.config.enable_exactly_once {
+            self.store_offsets(session, records).await?;
+        }
+
+        Ok(vec![])
+    }
+
+    async fn committed_offsets(
+        &self,
+        session: &mut Session,
+    ) -> Result<Option<HashMap<String, u64>>, CommonError> {
+        if !self.config.enable_exactly_once {
+            return Ok(None);
+        }
+
+        let cql = format!(
+            "SELECT shard_name, next_offset FROM {} WHERE connector_name = ?",
+            self.offset_table_name()
+        );
+        let result = session
+            .query_unpaged(cql, (&self.connector.connector_name,))
+            .await
+            .map_err(|e| {
+                CommonError::CommonError(format!(
+                    "Failed to load stored offsets from Cassandra: {}",
+                    e
+                ))
+            })?
+            .into_rows_result()
+            .map_err(|e| CommonError::CommonError(e.to_string()))?;
+
+        let mut offsets = HashMap::new();
+        for row in result
+            .rows::<(String, i64)>()
+            .map_err(|e| CommonError::CommonError(e.to_string()))?
+        {
+            let (shard_name, next_offset) =
+                row.map_err(|e| CommonError::CommonError(e.to_string()))?;
+            offsets.insert(shard_name, next_offset as u64);
+        }
+        Ok(Some(offsets))
     }
 }
 
@@ -182,6 +266,7 @@ pub fn start_cassandra_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
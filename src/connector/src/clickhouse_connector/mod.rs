@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use async_trait::async_trait;
+use clickhouse::sql::Identifier;
 use clickhouse::Client;
 use clickhouse::Row;
 use common_base::error::common::CommonError;
@@ -21,8 +22,8 @@
     connector::config_clickhouse::ClickHouseConnectorConfig, connector::MQTTConnector,
     storage::record::StorageRecord,
 };
-use rule_engine::apply_rule_engine;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
@@ -54,6 +55,35 @@ struct MqttMessageRow {
     timestamp: u64,
 }
 
+/// Row schema used when `enable_exactly_once` is set. Every row carries its
+/// source position, so a replayed batch collapses onto the rows already
+/// written and the connector resumes from the highest stored offset:
+/// ```sql
+/// CREATE TABLE mqtt_messages (
+///     data String,
+///     key String,
+///     timestamp UInt64,
+///     shard String,
+///     offset UInt64
+/// ) ENGINE = ReplacingMergeTree()
+/// ORDER BY (shard, offset);
+/// ```
+/// The table must only be written by this connector.
+#[derive(Row, Serialize)]
+struct MqttMessageOffsetRow {
+    data: String,
+    key: String,
+    timestamp: u64,
+    shard: String,
+    offset: u64,
+}
+
+#[derive(Row, Deserialize)]
+struct ShardOffsetRow {
+    shard: String,
+    next_offset: u64,
+}
+
 pub struct ClickHouseBridgePlugin {
     connector: MQTTConnector,
     config: ClickHouseConnectorConfig,
@@ -118,6 +148,34 @@ async fn send_batch(
             return Ok(vec![]);
         }
 
+        if self.config.enable_exactly_once {
+            let mut insert = client
+                .insert::<MqttMessageOffsetRow>(&self.config.table)
+                .await
+                .map_err(|e| {
+                    CommonError::CommonError(format!("Failed to prepare ClickHouse insert: {}", e))
+                })?;
+
+            for record in records {
+                let row = MqttMessageOffsetRow {
+                    data: String::from_utf8_lossy(&record.data).to_string(),
+                    key: record_key(record),
+                    timestamp: record.metadata.create_t,
+                    shard: record.metadata.shard.clone(),
+                    offset: record.metadata.offset,
+                };
+
+                insert.write(&row).await.map_err(|e| {
+                    CommonError::CommonError(format!("Failed to write row to ClickHouse: {}", e))
+                })?;
+            }
+
+            insert.end().await.map_err(|e| {
+                CommonError::CommonError(format!("Failed to flush ClickHouse insert: {}", e))
+            })?;
+            return Ok(vec![]);
+        }
+
         let mut insert = client
             .insert::<MqttMessageRow>(&self.config.table)
             .await
@@ -125,31 +183,10 @@ async fn send_batch(
                 CommonError::CommonError(format!("Failed to prepare ClickHouse insert: {}", e))
             })?;
 
-        let mut fail_messages = Vec::new();
         for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
             let row = MqttMessageRow {
-                data: String::from_utf8_lossy(&processed_data).to_string(),
-                key: record
-                    .metadata
-                    .key
-                    .as_deref()
-                    .map(|k| String::from_utf8_lossy(k).into_owned())
-                    .unwrap_or_default(),
+                data: String::from_utf8_lossy(&record.data).to_string(),
+                key: record_key(record),
                 timestamp: record.metadata.create_t,
             };
 
@@ -157,16 +194,48 @@ async fn send_batch(
                 CommonError::CommonError(format!("Failed to write row to ClickHouse: {}", e))
             })?;
         }
-        if fail_messages.len() == records.len() {
-            return Ok(fail_messages);
-        }
 
         insert.end().await.map_err(|e| {
             CommonError::CommonError(format!("Failed to flush ClickHouse insert: {}", e))
         })?;
 
-        Ok(fail_messages)
+        Ok(vec![])
     }
+
+    async fn committed_offsets(
+        &self,
+        client: &mut Client,
+    ) -> Result<Option<HashMap<String, u64>>, CommonError> {
+        if !self.config.enable_exactly_once {
+            return Ok(None);
+        }
+
+        let rows = client
+            .query("SELECT shard, max(offset) + 1 AS next_offset FROM ? GROUP BY shard")
+            .bind(Identifier(&self.config.table))
+            .fetch_all::<ShardOffsetRow>()
+            .await
+            .map_err(|e| {
+                CommonError::CommonError(format!(
+                    "Failed to load stored offsets from ClickHouse: {}",
+                    e
+                ))
+            })?;
+        Ok(Some(
+            rows.into_iter()
+                .map(|row| (row.shard, row.next_offset))
+                .collect(),
+        ))
+    }
+}
+
+fn record_key(record: &StorageRecord) -> String {
+    record
+        .metadata
+        .key
+        .as_deref()
+        .map(|k| String::from_utf8_lossy(k).into_owned())
+        .unwrap_or_default()
 }
 
 pub fn start_clickhouse_connector(
@@ -208,6 +277,7 @@ pub fn start_clickhouse_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -17,8 +17,9 @@
 use common_config::broker::broker_config;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::connector::{
-    status::MQTTStatus, ConnectorType, FailureHandlingStrategy, MQTTConnector,
+    rule::ETLRule, status::MQTTStatus, ConnectorType, FailureHandlingStrategy, MQTTConnector,
 };
+use schema_register::schema::SchemaRegisterManager;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::{
@@ -29,13 +30,17 @@
 
 use super::{
     cassandra::start_cassandra_connector, clickhouse_connector::start_clickhouse_connector,
-    elasticsearch::start_elasticsearch_connector, file::start_local_file_connector,
-    greptimedb::start_greptimedb_connector, influxdb_connector::start_influxdb_connector,
+    elasticsearch::start_elasticsearch_connector, file::source::start_file_tail_connector,
+    file::start_local_file_connector, greptimedb::start_greptimedb_connector,
+    influxdb_connector::start_influxdb_connector, kafka::source::start_kafka_source_connector,
     kafka::start_kafka_connector, manager::ConnectorManager, mongodb::start_mongodb_connector,
+    mqtt_bridge::source::start_mqtt_bridge_source_connector,
     mqtt_bridge::start_mqtt_bridge_connector, mysql::start_mysql_connector,
-    opentsdb::start_opentsdb_connector, postgres::start_postgres_connector,
-    pulsar::start_pulsar_connector, rabbitmq::start_rabbitmq_connector,
-    redis::start_redis_connector, s3::start_s3_connector, webhook::start_webhook_connector,
+    opentsdb::start_opentsdb_connector, postgres::source::start_postgres_cdc_connector,
+    postgres::start_postgres_connector, pulsar::start_pulsar_connector,
+    rabbitmq::source::start_rabbitmq_source_connector, rabbitmq::start_rabbitmq_connector,
+    redis::source::start_redis_stream_connector, redis::start_redis_connector,
+    s3::start_s3_connector, webhook::start_webhook_connector,
 };
 use crate::storage::connector::ConnectorStorage;
 
@@ -45,6 +50,7 @@ pub struct BridgePluginReadConfig {
     pub topic_name: String,
     pub record_num: u64,
     pub strategy: FailureHandlingStrategy,
+    pub etl_rule: ETLRule,
 }
 
 #[derive(Clone)]
@@ -61,6 +67,7 @@ pub(crate) async fn start_connector_thread(
     client_pool: Arc<ClientPool>,
     storage_driver_manager: Arc<StorageDriverManager>,
     connector_manager: Arc<ConnectorManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
     stop_send: broadcast::Sender<bool>,
 ) {
     let ac_fn = async || -> ResultCommonError {
@@ -68,6 +75,7 @@ pub(crate) async fn start_connector_thread(
         start_connectors(
             &storage_driver_manager,
             &connector_manager,
+            &schema_manager,
             &client_pool,
             current_broker_id,
         );
@@ -81,6 +89,7 @@ pub(crate) async fn start_connector_thread(
 fn start_connectors(
     storage_driver_manager: &Arc<StorageDriverManager>,
     connector_manager: &Arc<ConnectorManager>,
+    schema_manager: &Arc<SchemaRegisterManager>,
     client_pool: &Arc<ClientPool>,
     current_broker_id: u64,
 ) {
@@ -115,6 +124,7 @@ fn start_connectors(
             client_pool.clone(),
             connector_manager.clone(),
             storage_driver_manager.clone(),
+            schema_manager.clone(),
             raw.clone(),
             thread,
             stop_rx,
@@ -159,6 +169,7 @@ fn start_thread(
     client_pool: Arc<ClientPool>,
     connector_manager: Arc<ConnectorManager>,
     storage_driver_manager: Arc<StorageDriverManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
     connector: MQTTConnector,
     thread: BridgePluginThread,
     stop_rx: Receiver<bool>,
@@ -177,6 +188,7 @@ fn start_thread(
                 client_pool,
                 connector_manager,
                 storage_driver_manager,
+                schema_manager,
                 connector,
                 thread,
                 stop_rx,
@@ -334,6 +346,67 @@ fn start_thread(
         }
         ConnectorType::S3(_) => {
             start_s3_connector(
+                client_pool,
+                connector_manager,
+                storage_driver_manager,
+                schema_manager,
+                connector,
+                thread,
+                stop_rx,
+            );
+        }
+        ConnectorType::KafkaSource(_) => {
+            start_kafka_source_connector(
+                client_pool,
+                connector_manager,
+                storage_driver_manager,
+                connector,
+                thread,
+                stop_rx,
+            );
+        }
+        ConnectorType::MqttBridgeSource(_) => {
+            start_mqtt_bridge_source_connector(
+                client_pool,
+                connector_manager,
+                storage_driver_manager,
+                connector,
+                thread,
+                stop_rx,
+            );
+        }
+        ConnectorType::PostgresCDC(_) => {
+            start_postgres_cdc_connector(
+                client_pool,
+                connector_manager,
+                storage_driver_manager,
+                connector,
+                thread,
+                stop_rx,
+            );
+        }
+        ConnectorType::RedisStream(_) => {
+            start_redis_stream_connector(
+                client_pool,
+                connector_manager,
+                storage_driver_manager,
+                connector,
+                thread,
+                stop_rx,
+            );
+        }
+        ConnectorType::RabbitMQSource(_) => {
+            start_rabbitmq_source_connector(
+                client_pool,
+                connector_manager,
+                storage_driver_manager,
+                connector,
+                thread,
+                stop_rx,
+            );
+        }
+        ConnectorType::FileTail(_) => {
+            start_file_tail_connector(
                 client_pool,
                 connector_manager,
                 storage_driver_manager,
@@ -386,6 +459,7 @@ fn test_bridge_plugin_read_config_creation() {
             topic_name: "test_topic".to_string(),
             record_num: 100,
             strategy: FailureHandlingStrategy::Discard,
+            etl_rule: ETLRule::default(),
         };
 
         assert_eq!(config.topic_name, "test_topic");
@@ -418,6 +492,7 @@ async fn test_start_connector_thread() {
                 client_pool,
                 storage_driver_manager,
                 connector_manager,
+                Arc::new(SchemaRegisterManager::new()),
                 stop_send,
             )
             .await;
@@ -26,7 +26,6 @@
     connector::{config_elasticsearch::ElasticsearchConnectorConfig, MQTTConnector},
     storage::record::StorageRecord,
 };
-use rule_engine::apply_rule_engine;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
@@ -97,16 +96,14 @@ async fn create_client(&self) -> Result<Elasticsearch, CommonError> {
         Ok(Elasticsearch::new(transport))
     }
 
-    #[allow(clippy::result_large_err)]
-    async fn record_to_json(&self, record: &StorageRecord) -> Result<Value, CommonError> {
-        let processed_data = apply_rule_engine(&self.connector.etl_rule, &record.data).await?;
-        let payload_str = String::from_utf8_lossy(&processed_data).to_string();
+    fn record_to_json(&self, record: &StorageRecord) -> Value {
+        let payload_str = String::from_utf8_lossy(&record.data).to_string();
 
         let mut doc = json!({
             "key": record.metadata.key,
             "timestamp": record.metadata.create_t,
             "payload": payload_str,
-            "data": processed_data,
+            "data": record.data,
         });
 
         if let Some(headers) = &record.metadata.header {
@@ -119,7 +116,7 @@ async fn record_to_json(&self, record: &StorageRecord) -> Result<Value, CommonEr
             }
         }
 
-        Ok(doc)
+        doc
     }
 }
 
@@ -146,37 +143,13 @@ async fn send_batch(
         }
 
         let mut body_parts: Vec<JsonBody<_>> = Vec::new();
-        let mut fail_messages = Vec::new();
-
         for record in records {
-            let doc = match self.record_to_json(record).await {
-                Ok(d) => d,
-                Err(e) => {
-                    error!(
-                        "Failed to convert record to JSON: {}. Record will be skipped.",
-                        e
-                    );
-                    fail_messages.push(FailureRecordInfo {
-                        tenant: self.connector.tenant.clone(),
-                        connector_name: self.connector.connector_name.clone(),
-                        connector_type: self.connector.connector_type.to_string(),
-                        source_topic: self.connector.topic_name.clone(),
-                        error_message: e.to_string(),
-                        records: vec![record.clone()],
-                    });
-                    continue;
-                }
-            };
-
+            let doc = self.record_to_json(record);
             let action = json!({"index": {}});
             body_parts.push(JsonBody::new(action));
             body_parts.push(JsonBody::new(doc));
         }
 
-        if body_parts.is_empty() {
-            return Ok(fail_messages);
-        }
-
         let response = client
             .bulk(BulkParts::Index(&self.config.index))
             .body(body_parts)
@@ -187,7 +160,7 @@ async fn send_batch(
             })?;
 
         if response.status_code().is_success() {
-            Ok(fail_messages)
+            Ok(vec![])
         } else {
             let error_text = response
                 .text()
@@ -240,6 +213,7 @@ pub fn start_elasticsearch_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -15,19 +15,22 @@
 use super::core::{BridgePluginReadConfig, BridgePluginThread};
 use super::loops::run_connector_loop;
 use super::manager::ConnectorManager;
+use super::output::{RollingOutput, RollingOutputConfig};
 use super::traits::ConnectorSink;
 use crate::failure::FailureRecordInfo;
 use async_trait::async_trait;
-use bytes::Bytes;
 use chrono::{DateTime, Local, Timelike};
 use common_base::error::common::CommonError;
+use common_base::tools::now_millis;
+use common_base::uuid::unique_id;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::connector::config_local_file::RotationStrategy;
 use metadata_struct::storage::record::StorageRecord;
 use metadata_struct::{
     connector::config_local_file::LocalFileConnectorConfig, connector::MQTTConnector,
 };
-use rule_engine::apply_rule_engine;
+use schema_register::schema::SchemaRegisterManager;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
@@ -35,7 +38,9 @@
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::sync::mpsc::Receiver;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
+
+pub mod source;
 
 pub struct FileWriter {
     writer: BufWriter<File>,
@@ -164,14 +169,25 @@ async fn shutdown(mut self) -> Result<(), CommonError> {
     }
 }
 
+/// The line writer is used for the `json` format, the rolling output for the
+/// other formats.
+pub struct FileSink {
+    writer: Option<FileWriter>,
+    output: Option<RollingOutput>,
+}
+
 pub struct FileBridgePlugin {
     connector: MQTTConnector,
     config: LocalFileConnectorConfig,
+    schema_manager: Arc<SchemaRegisterManager>,
 }
 
 impl FileBridgePlugin {
     #[allow(clippy::result_large_err)]
-    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+    pub fn new(
+        connector: MQTTConnector,
+        schema_manager: Arc<SchemaRegisterManager>,
+    ) -> Result<Self, CommonError> {
         let config = match &connector.connector_type {
             metadata_struct::connector::ConnectorType::LocalFile(config) => config.clone(),
             _ => {
@@ -180,19 +196,56 @@ pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
                 ));
             }
         };
-        Ok(FileBridgePlugin { connector, config })
+        Ok(FileBridgePlugin {
+            connector,
+            config,
+            schema_manager,
+        })
+    }
+
+    /// Rolled files go to `<dir>/<partition>/<stem>-<millis>-<id>.<ext>`, where
+    /// `dir` and `stem` come from `local_file_path`.
+    fn rolled_file_path(&self, partition: &str, extension: &str) -> (PathBuf, String) {
+        let original_path = Path::new(&self.config.local_file_path);
+        let parent = original_path.parent().unwrap_or_else(|| Path::new("."));
+        let stem = original_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("file");
+        (
+            parent.join(partition),
+            format!("{}-{}-{}.{}", stem, now_millis(), unique_id(), extension),
+        )
     }
 
-    async fn process_data(&self, data: &Bytes) -> Result<Bytes, CommonError> {
-        let processed_data = apply_rule_engine(&self.connector.etl_rule, data).await?;
-        // let data = String::from_utf8_lossy(&processed_data);
-        Ok(processed_data)
+    /// Writes rolled files in order, stopping at the first failure so the file
+    /// is retried on the next call. Each file is written under a temporary name
+    /// first, so readers never see a partial file.
+    async fn write_ready(&self, sink: &mut FileSink) -> Result<(), CommonError> {
+        let Some(output) = sink.output.as_mut() else {
+            return Ok(());
+        };
+        while let Some(file) = output.next_ready() {
+            let (dir, file_name) = self.rolled_file_path(&file.partition, file.extension);
+            tokio::fs::create_dir_all(&dir).await?;
+            let path = dir.join(&file_name);
+            let tmp_path = dir.join(format!(".{}.tmp", file_name));
+            tokio::fs::write(&tmp_path, &file.data).await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+            debug!(
+                "File connector wrote {} records to {}",
+                file.record_count,
+                path.display()
+            );
+            output.complete_ready();
+        }
+        Ok(())
     }
 }
 
 #[async_trait]
 impl ConnectorSink for FileBridgePlugin {
-    type SinkResource = FileWriter;
+    type SinkResource = FileSink;
 
     async fn validate(&self) -> Result<(), CommonError> {
         let file_path = Path::new(&self.config.local_file_path);
@@ -208,39 +261,94 @@ async fn validate(&self) -> Result<(), CommonError> {
     }
 
     async fn init_sink(&self) -> Result<Self::SinkResource, CommonError> {
-        FileWriter::new(self.config.clone()).await
+        if !self.config.format.is_rolling() {
+            return Ok(FileSink {
+                writer: Some(FileWriter::new(self.config.clone()).await?),
+                output: None,
+            });
+        }
+
+        let output = RollingOutput::new(
+            RollingOutputConfig {
+                tenant: self.connector.tenant.clone(),
+                topic_name: self.connector.topic_name.clone(),
+                format: self.config.format.clone(),
+                compression: self.config.compression.clone(),
+                partition_by: self.config.partition_by.clone(),
+                roll_size_mb: self.config.roll_size_mb,
+                roll_interval_secs: self.config.roll_interval_secs,
+            },
+            self.schema_manager.clone(),
+        );
+        Ok(FileSink {
+            writer: None,
+            output: Some(output),
+        })
     }
 
     async fn send_batch(
         &self,
         records: &[StorageRecord],
-        writer: &mut FileWriter,
+        sink: &mut FileSink,
     ) -> Result<Vec<FailureRecordInfo>, CommonError> {
-        let mut fail_messages = Vec::new();
-        for record in records {
-            let data = match self.process_data(&record.data).await {
-                Ok(data) => data,
-                Err(e) => {
-                    fail_messages.push(FailureRecordInfo {
-                        tenant: self.connector.tenant.clone(),
-                        connector_name: self.connector.connector_name.clone(),
-                        connector_type: self.connector.connector_type.to_string(),
-                        source_topic: self.connector.topic_name.clone(),
-                        error_message: e.to_string(),
-                        records: vec![record.clone()],
-                    });
-                    continue;
-                }
-            };
-            writer.write(data.as_ref()).await?;
-            writer.write(b"\n").await?;
+        if let Some(writer) = sink.writer.as_mut() {
+            for record in records {
+                writer.write(record.data.as_ref()).await?;
+                writer.write(b"\n").await?;
+            }
+            writer.flush().await?;
+            return Ok(vec![]);
+        }
+
+        // Leftover files go first: an error here is retried with the same
+        // batch, which has not been buffered yet.
+        self.write_ready(sink).await?;
+
+        let output = sink.output.as_mut().expect("rolling output is set");
+        let fail_messages = output
+            .append(records)
+            .into_iter()
+            .map(|(record, reason)| FailureRecordInfo {
+                tenant: self.connector.tenant.clone(),
+                connector_name: self.connector.connector_name.clone(),
+                connector_type: self.connector.connector_type.to_string(),
+                source_topic: self.connector.topic_name.clone(),
+                error_message: reason,
+                records: vec![record],
+            })
+            .collect();
+        output.roll(false)?;
+        if let Err(e) = self.write_ready(sink).await {
+            warn!(
+                "File connector '{}' failed to write rolled file, will retry: {}",
+                self.connector.connector_name, e
+            );
         }
-        writer.flush().await?;
         Ok(fail_messages)
     }
 
-    async fn cleanup_sink(&self, writer: FileWriter) -> Result<(), CommonError> {
-        writer.shutdown().await?;
+    fn buffers_records(&self) -> bool {
+        self.config.format.is_rolling()
+    }
+
+    async fn flush_buffered(&self, sink: &mut FileSink, force: bool) -> Result<(), CommonError> {
+        if let Some(output) = sink.output.as_mut() {
+            output.roll(force)?;
+        }
+        self.write_ready(sink).await
+    }
+
+    fn take_flushed_offsets(&self, sink: &mut FileSink) -> HashMap<String, u64> {
+        sink.output
+            .as_mut()
+            .map(|output| output.take_committable_offsets())
+            .unwrap_or_default()
+    }
+
+    async fn cleanup_sink(&self, sink: FileSink) -> Result<(), CommonError> {
+        if let Some(writer) = sink.writer {
+            writer.shutdown().await?;
+        }
         Ok(())
     }
 }
@@ -249,6 +357,7 @@ pub fn start_local_file_connector(
     client_pool: Arc<ClientPool>,
     connector_manager: Arc<ConnectorManager>,
     storage_driver_manager: Arc<StorageDriverManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
     connector: MQTTConnector,
     thread: BridgePluginThread,
     stop_recv: Receiver<bool>,
@@ -256,7 +365,7 @@ pub fn start_local_file_connector(
     tokio::spawn(Box::pin(async move {
         let connector_name = connector.connector_name.clone();
         let connector_type = connector.connector_type.to_string();
-        let bridge = match FileBridgePlugin::new(connector.clone()) {
+        let bridge = match FileBridgePlugin::new(connector.clone(), schema_manager) {
             Ok(bridge) => bridge,
             Err(e) => {
                 error!(
@@ -284,6 +393,7 @@ pub fn start_local_file_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -312,7 +422,8 @@ mod tests {
         storage::record::{StorageRecord, StorageRecordMetadata},
         tenant::DEFAULT_TENANT,
     };
-    use std::{fs, path::PathBuf};
+    use schema_register::schema::SchemaRegisterManager;
+    use std::{fs, path::PathBuf, sync::Arc};
     use tempfile::tempdir;
     use tokio::{fs::File, io::AsyncReadExt};
 
@@ -341,24 +452,28 @@ async fn file_bridge_plugin_test() {
             rotation_strategy:
                 metadata_struct::connector::config_local_file::RotationStrategy::None,
             max_size_gb: 1,
+            ..Default::default()
         };
 
         // create such file
         fs::create_dir_all(dir_path).unwrap();
         File::create(config.local_file_path.clone()).await.unwrap();
 
-        let file_bridge_plugin = FileBridgePlugin::new(MQTTConnector {
-            connector_name: "test-file".to_string(),
-            connector_type: ConnectorType::LocalFile(config.clone()),
-            failure_strategy: FailureHandlingStrategy::Discard,
-            tenant: DEFAULT_TENANT.to_string(),
-            topic_name: unique_id(),
-            status: Default::default(),
-            etl_rule: ETLRule::default(),
-            broker_id: None,
-            create_time: now_second(),
-            update_time: now_second(),
-        })
+        let file_bridge_plugin = FileBridgePlugin::new(
+            MQTTConnector {
+                connector_name: "test-file".to_string(),
+                connector_type: ConnectorType::LocalFile(config.clone()),
+                failure_strategy: FailureHandlingStrategy::Discard,
+                tenant: DEFAULT_TENANT.to_string(),
+                topic_name: unique_id(),
+                status: Default::default(),
+                etl_rule: ETLRule::default(),
+                broker_id: None,
+                create_time: now_second(),
+                update_time: now_second(),
+            },
+            Arc::new(SchemaRegisterManager::new()),
+        )
         .unwrap();
 
         let mut writer = file_bridge_plugin.init_sink().await.unwrap();
@@ -0,0 +1,350 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::{BridgePluginReadConfig, BridgePluginThread};
+use crate::loops::run_source_connector_loop;
+use crate::manager::ConnectorManager;
+use crate::traits::{ConnectorSource, SourceRecord};
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::connector::{
+    config_file_tail::FileTailConnectorConfig,
+    source::{SourceOffset, SourceStartPosition},
+    ConnectorType, MQTTConnector,
+};
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use storage_adapter::driver::StorageDriverManager;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+use tokio::sync::mpsc::Receiver;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+pub struct FileTailSource {
+    connector: MQTTConnector,
+    config: FileTailConnectorConfig,
+}
+
+/// Byte position of the next unread line.
+pub struct FileTailPosition {
+    position: u64,
+}
+
+impl FileTailSource {
+    #[allow(clippy::result_large_err)]
+    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+        let config = match &connector.connector_type {
+            ConnectorType::FileTail(config) => config.clone(),
+            _ => {
+                return Err(CommonError::CommonError(
+                    "invalid connector type for file tail source".to_string(),
+                ));
+            }
+        };
+        Ok(FileTailSource { connector, config })
+    }
+
+    async fn file_len(&self) -> Option<u64> {
+        fs::metadata(&self.config.local_file_path)
+            .await
+            .ok()
+            .map(|m| m.len())
+    }
+}
+
+#[async_trait]
+impl ConnectorSource for FileTailSource {
+    type SourceResource = FileTailPosition;
+
+    async fn validate(&self) -> Result<(), CommonError> {
+        self.config.validate()
+    }
+
+    async fn init_source(&self, offset: &SourceOffset) -> Result<FileTailPosition, CommonError> {
+        let stored = offset
+            .get(&self.config.local_file_path)
+            .and_then(|p| p.parse::<u64>().ok());
+        let position = match (stored, &self.config.start_position) {
+            (Some(position), _) => position,
+            (None, SourceStartPosition::Earliest) => 0,
+            (None, SourceStartPosition::Latest) => self.file_len().await.unwrap_or(0),
+        };
+        info!(
+            "Tailing file {} from byte {}",
+            self.config.local_file_path, position
+        );
+        Ok(FileTailPosition { position })
+    }
+
+    async fn read_batch(
+        &self,
+        tail: &mut FileTailPosition,
+    ) -> Result<Vec<SourceRecord>, CommonError> {
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+        let Some(len) = self.file_len().await else {
+            sleep(poll_interval).await;
+            return Ok(Vec::new());
+        };
+
+        // The file shrank, it was truncated or replaced by rotation.
+        if len < tail.position {
+            info!(
+                "File {} shrank from {} to {} bytes, reading from the start",
+                self.config.local_file_path, tail.position, len
+            );
+            tail.position = 0;
+        }
+
+        if len == tail.position {
+            sleep(poll_interval).await;
+            return Ok(Vec::new());
+        }
+
+        let mut file = File::open(&self.config.local_file_path).await?;
+        file.seek(SeekFrom::Start(tail.position)).await?;
+        let mut reader = BufReader::new(file);
+
+        let mut records = Vec::new();
+        let mut line = Vec::new();
+        while records.len() < self.config.batch_size {
+            line.clear();
+            let n = reader.read_until(b'\n', &mut line).await?;
+            // Stop at EOF or at a line that is still being written.
+            if n == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            tail.position += n as u64;
+
+            let data = line.trim_ascii_end();
+            if data.is_empty() {
+                continue;
+            }
+            records.push(SourceRecord {
+                partition: self.config.local_file_path.clone(),
+                offset: tail.position.to_string(),
+                record: AdapterWriteRecord::new(self.connector.topic_name.as_str(), data.to_vec()),
+            });
+        }
+
+        if records.is_empty() {
+            sleep(poll_interval).await;
+        }
+        Ok(records)
+    }
+}
+
+pub fn start_file_tail_connector(
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    storage_driver_manager: Arc<StorageDriverManager>,
+    connector: MQTTConnector,
+    thread: BridgePluginThread,
+    stop_recv: Receiver<bool>,
+) {
+    tokio::spawn(Box::pin(async move {
+        let connector_name = connector.connector_name.clone();
+        let connector_type = connector.connector_type.to_string();
+        let source = match FileTailSource::new(connector.clone()) {
+            Ok(source) => source,
+            Err(e) => {
+                error!(
+                    "Invalid connector config type for FileTail connector, connector_name='{}', connector_type='{}', error={}",
+                    connector_name, connector_type, e
+                );
+                return;
+            }
+        };
+
+        connector_manager.add_connector_thread(
+            &connector.tenant,
+            &connector.connector_name,
+            thread,
+        );
+
+        if let Err(e) = run_source_connector_loop(
+            &source,
+            &client_pool,
+            &connector_manager,
+            &storage_driver_manager,
+            connector.connector_name.clone(),
+            BridgePluginReadConfig {
+                tenant: connector.tenant,
+                topic_name: connector.topic_name,
+                record_num: source.config.batch_size as u64,
+                strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
+            },
+            stop_recv,
+        )
+        .await
+        {
+            connector_manager.remove_connector_thread(&connector.connector_name);
+            error!(
+                "Failed to start FileTailSource, connector_name='{}', connector_type='{}', error={:?}",
+                connector_name, connector_type, e
+            );
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_base::uuid::unique_id;
+    use metadata_struct::tenant::DEFAULT_TENANT;
+    use tempfile::tempdir;
+    use tokio::io::AsyncWriteExt;
+
+    fn file_tail_source(path: &str, start_position: SourceStartPosition) -> FileTailSource {
+        FileTailSource::new(MQTTConnector {
+            connector_name: "test-file-tail".to_string(),
+            connector_type: ConnectorType::FileTail(FileTailConnectorConfig {
+                local_file_path: path.to_string(),
+                start_position,
+                batch_size: 2,
+                poll_interval_ms: 1,
+            }),
+            tenant: DEFAULT_TENANT.to_string(),
+            topic_name: "t1".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    async fn write_file(path: &str, data: &[u8]) {
+        let mut file = File::create(path).await.unwrap();
+        file.write_all(data).await.unwrap();
+        file.flush().await.unwrap();
+    }
+
+    fn lines(batch: &[SourceRecord]) -> Vec<&[u8]> {
+        batch.iter().map(|r| r.record.data.as_ref()).collect()
+    }
+
+    #[tokio::test]
+    async fn file_tail_source_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(unique_id()).to_str().unwrap().to_string();
+        let source = file_tail_source(&path, SourceStartPosition::Earliest);
+
+        let mut file = File::create(&path).await.unwrap();
+        file.write_all(b"a\nbb\nccc\ndd").await.unwrap();
+        file.flush().await.unwrap();
+
+        let mut tail = source.init_source(&SourceOffset::default()).await.unwrap();
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].record.data.as_ref(), b"a");
+        assert_eq!(batch[1].offset, "5");
+
+        // The trailing "dd" has no newline yet and is not consumed.
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].record.data.as_ref(), b"ccc");
+        assert!(source.read_batch(&mut tail).await.unwrap().is_empty());
+
+        file.write_all(b"d\n").await.unwrap();
+        file.flush().await.unwrap();
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(batch[0].record.data.as_ref(), b"ddd");
+
+        // Resume from a stored offset.
+        let mut offset = SourceOffset::default();
+        offset.set(path.as_str(), "5");
+        let mut tail = source.init_source(&offset).await.unwrap();
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(batch[0].record.data.as_ref(), b"ccc");
+
+        // Truncation starts over from the beginning.
+        File::create(&path)
+            .await
+            .unwrap()
+            .write_all(b"x\n")
+            .await
+            .unwrap();
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].record.data.as_ref(), b"x");
+    }
+
+    #[tokio::test]
+    async fn file_tail_resumes_after_stored_offset() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(unique_id()).to_str().unwrap().to_string();
+        write_file(&path, b"a\nbb\nccc\n").await;
+
+        // Without a stored offset "latest" skips what is already in the file.
+        let source = file_tail_source(&path, SourceStartPosition::Latest);
+        let mut tail = source.init_source(&SourceOffset::default()).await.unwrap();
+        assert!(source.read_batch(&mut tail).await.unwrap().is_empty());
+
+        // The stored offset of a batch is the byte after its last line and
+        // wins over the start position.
+        let earliest = file_tail_source(&path, SourceStartPosition::Earliest);
+        let mut tail = earliest
+            .init_source(&SourceOffset::default())
+            .await
+            .unwrap();
+        let mut offset = SourceOffset::default();
+        for record in earliest.read_batch(&mut tail).await.unwrap() {
+            offset.set(record.partition, record.offset);
+        }
+        let offset = SourceOffset::decode(&offset.encode().unwrap()).unwrap();
+        assert_eq!(offset.get(&path), Some("5"));
+
+        let mut tail = source.init_source(&offset).await.unwrap();
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(lines(&batch), vec![&b"ccc"[..]]);
+    }
+
+    #[tokio::test]
+    async fn file_tail_reads_truncated_file_from_start() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(unique_id()).to_str().unwrap().to_string();
+        write_file(&path, b"a\nbb\n").await;
+
+        let source = file_tail_source(&path, SourceStartPosition::Earliest);
+        let mut tail = source.init_source(&SourceOffset::default()).await.unwrap();
+        assert_eq!(source.read_batch(&mut tail).await.unwrap().len(), 2);
+
+        write_file(&path, b"x\n").await;
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(lines(&batch), vec![&b"x"[..]]);
+        assert_eq!(batch[0].offset, "2");
+    }
+
+    #[tokio::test]
+    async fn file_tail_follows_rotated_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(unique_id()).to_str().unwrap().to_string();
+        write_file(&path, b"a\nbb\nccc\n").await;
+
+        let source = file_tail_source(&path, SourceStartPosition::Earliest);
+        let mut tail = source.init_source(&SourceOffset::default()).await.unwrap();
+        assert_eq!(source.read_batch(&mut tail).await.unwrap().len(), 2);
+
+        // While the file is moved away there is nothing to read.
+        fs::rename(&path, format!("{}.1", path)).await.unwrap();
+        assert!(source.read_batch(&mut tail).await.unwrap().is_empty());
+
+        write_file(&path, b"new\n").await;
+        let batch = source.read_batch(&mut tail).await.unwrap();
+        assert_eq!(lines(&batch), vec![&b"new"[..]]);
+        assert_eq!(batch[0].offset, "4");
+    }
+}
@@ -21,7 +21,6 @@
     storage::record::StorageRecord,
 };
 
-use rule_engine::apply_rule_engine;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::error;
@@ -74,33 +73,8 @@ async fn send_batch(
         records: &[StorageRecord],
         sender: &mut sender::Sender,
     ) -> Result<Vec<FailureRecordInfo>, CommonError> {
-        let mut processed_records = Vec::with_capacity(records.len());
-        let mut fail_messages = Vec::new();
-        for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-            let mut processed_record = record.clone();
-            processed_record.data = processed_data;
-            processed_records.push(processed_record);
-        }
-        if processed_records.is_empty() {
-            return Ok(fail_messages);
-        }
-        sender.send_batch(&processed_records).await?;
-        Ok(fail_messages)
+        sender.send_batch(records).await?;
+        Ok(vec![])
     }
 }
 
@@ -142,6 +116,7 @@ pub fn start_greptimedb_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -21,7 +21,6 @@
     storage::record::StorageRecord,
 };
 use reqwest::Client;
-use rule_engine::apply_rule_engine;
 use std::sync::Arc;
 use std::time::Duration;
 use storage_adapter::driver::StorageDriverManager;
@@ -65,13 +64,9 @@ fn build_client(&self) -> Result<Client, CommonError> {
 
     /// Convert a StorageRecord to InfluxDB Line Protocol format:
     /// `measurement,tag1=val1 field1="strval",field2=42i timestamp`
-    fn record_to_line_protocol(
-        &self,
-        record: &StorageRecord,
-        processed_data: &bytes::Bytes,
-    ) -> String {
+    fn record_to_line_protocol(&self, record: &StorageRecord) -> String {
         let measurement = &self.config.measurement;
-        let payload_str = String::from_utf8_lossy(processed_data);
+        let payload_str = String::from_utf8_lossy(&record.data);
 
         let mut tags = String::new();
         if let Some(key) = &record.metadata.key {
@@ -127,27 +122,8 @@ async fn send_batch(
         }
 
         let mut lines: Vec<String> = Vec::with_capacity(records.len());
-        let mut fail_messages = Vec::new();
         for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-            lines.push(self.record_to_line_protocol(record, &processed_data));
-        }
-        if lines.is_empty() {
-            return Ok(fail_messages);
+            lines.push(self.record_to_line_protocol(record));
         }
         let body = lines.join("\n");
 
@@ -167,7 +143,7 @@ async fn send_batch(
         })?;
 
         if response.status().is_success() || response.status().as_u16() == 204 {
-            Ok(fail_messages)
+            Ok(vec![])
         } else {
             let status = response.status();
             let error_text = response
@@ -221,6 +197,7 @@ pub fn start_influxdb_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
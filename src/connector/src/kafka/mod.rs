@@ -29,11 +29,12 @@
     storage::record::StorageRecord,
 };
 use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
-use rule_engine::apply_rule_engine;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::error;
 
+pub mod source;
+
 pub struct KafkaBridgePlugin {
     connector: MQTTConnector,
     config: KafkaConnectorConfig,
@@ -101,37 +102,10 @@ async fn send_batch(
     ) -> Result<Vec<FailureRecordInfo>, CommonError> {
         use futures::future::join_all;
 
-        let mut processed_records = Vec::with_capacity(records.len());
-        let mut fail_messages = Vec::new();
-        for record in records {
-            match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                Ok(data) => {
-                    let mut processed_record = record.clone();
-                    processed_record.data = data;
-                    processed_records.push(processed_record);
-                }
-                Err(e) => {
-                    tracing::error!("Failed to apply rule before Kafka send: {}", e);
-                    fail_messages.push(FailureRecordInfo {
-                        tenant: self.connector.tenant.clone(),
-                        connector_name: self.connector.connector_name.clone(),
-                        connector_type: self.connector.connector_type.to_string(),
-                        source_topic: self.connector.topic_name.clone(),
-                        error_message: e.to_string(),
-                        records: vec![record.clone()],
-                    });
-                }
-            }
-        }
-
-        if processed_records.is_empty() {
-            return Ok(fail_messages);
-        }
-
         let mut serialized_data = Vec::with_capacity(records.len());
         let mut keys = Vec::with_capacity(records.len());
 
-        for record in &processed_records {
+        for record in records {
             let data = serde_json::to_string(record)?;
             serialized_data.push(data);
 
@@ -163,7 +137,7 @@ async fn send_batch(
             ));
         }
 
-        Ok(fail_messages)
+        Ok(vec![])
     }
 
     async fn cleanup_sink(&self, producer: FutureProducer) -> Result<(), CommonError> {
@@ -216,6 +190,7 @@ pub fn start_kafka_connector(
                 topic_name: connector.topic_name.clone(),
                 record_num: 100,
                 strategy: connector.failure_strategy.clone(),
+                etl_rule: connector.etl_rule.clone(),
             },
             stop_recv,
         )
@@ -0,0 +1,286 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::{BridgePluginReadConfig, BridgePluginThread};
+use crate::loops::run_source_connector_loop;
+use crate::manager::ConnectorManager;
+use crate::traits::{ConnectorSource, SourceRecord};
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::adapter_record::{AdapterWriteRecord, RecordHeader};
+use metadata_struct::connector::{
+    config_kafka_source::KafkaSourceConnectorConfig,
+    source::{SourceOffset, SourceStartPosition},
+    ConnectorType, MQTTConnector,
+};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::{BorrowedMessage, Headers};
+use rdkafka::{Message, Offset, TopicPartitionList};
+use std::sync::Arc;
+use std::time::Duration;
+use storage_adapter::driver::StorageDriverManager;
+use tokio::select;
+use tokio::sync::mpsc::Receiver;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+pub struct KafkaSource {
+    connector: MQTTConnector,
+    config: KafkaSourceConnectorConfig,
+}
+
+/// The stored offset is the last record written into RobustMQ, so reading
+/// resumes right after it. Without a usable stored offset the configured
+/// start position applies.
+fn start_offset(stored: Option<&str>, start_position: &SourceStartPosition) -> Offset {
+    match (stored.and_then(|o| o.parse::<i64>().ok()), start_position) {
+        (Some(o), _) => Offset::Offset(o + 1),
+        (None, SourceStartPosition::Earliest) => Offset::Beginning,
+        (None, SourceStartPosition::Latest) => Offset::End,
+    }
+}
+
+impl KafkaSource {
+    #[allow(clippy::result_large_err)]
+    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+        let config = match &connector.connector_type {
+            ConnectorType::KafkaSource(config) => config.clone(),
+            _ => {
+                return Err(CommonError::CommonError(
+                    "invalid connector type for kafka source".to_string(),
+                ));
+            }
+        };
+        Ok(KafkaSource { connector, config })
+    }
+
+    /// Partitions are assigned explicitly rather than through a consumer group
+    /// rebalance: the connector scheduler already runs exactly one instance.
+    #[allow(clippy::result_large_err)]
+    fn build_assignment(
+        &self,
+        consumer: &StreamConsumer,
+        offset: &SourceOffset,
+    ) -> Result<TopicPartitionList, CommonError> {
+        let metadata =
+            consumer.fetch_metadata(Some(&self.config.topic), Duration::from_secs(10))?;
+        let topic = metadata
+            .topics()
+            .iter()
+            .find(|t| t.name() == self.config.topic)
+            .ok_or_else(|| {
+                CommonError::CommonError(format!("Kafka topic '{}' not found", self.config.topic))
+            })?;
+        if topic.partitions().is_empty() {
+            return Err(CommonError::CommonError(format!(
+                "Kafka topic '{}' has no partitions",
+                self.config.topic
+            )));
+        }
+
+        let mut assignment = TopicPartitionList::new();
+        for partition in topic.partitions() {
+            let start = start_offset(
+                offset.get(&partition.id().to_string()),
+                &self.config.start_position,
+            );
+            assignment.add_partition_offset(&self.config.topic, partition.id(), start)?;
+        }
+        Ok(assignment)
+    }
+
+    fn to_source_record(&self, msg: &BorrowedMessage<'_>) -> SourceRecord {
+        let mut record = AdapterWriteRecord::new(
+            self.connector.topic_name.as_str(),
+            msg.payload().unwrap_or_default().to_vec(),
+        );
+        if let Some(key) = msg.key() {
+            record = record.with_key(key.to_vec());
+        }
+        if let Some(headers) = msg.headers() {
+            let headers: Vec<RecordHeader> = headers
+                .iter()
+                .map(|h| RecordHeader {
+                    name: h.key.to_string(),
+                    value: String::from_utf8_lossy(h.value.unwrap_or_default()).into_owned(),
+                })
+                .collect();
+            if !headers.is_empty() {
+                record = record.with_header(headers);
+            }
+        }
+        SourceRecord {
+            partition: msg.partition().to_string(),
+            offset: msg.offset().to_string(),
+            record,
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectorSource for KafkaSource {
+    type SourceResource = StreamConsumer;
+
+    async fn validate(&self) -> Result<(), CommonError> {
+        self.config.validate()
+    }
+
+    async fn init_source(&self, offset: &SourceOffset) -> Result<StreamConsumer, CommonError> {
+        let mut client_config = rdkafka::ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", &self.config.bootstrap_servers)
+            .set("group.id", &self.config.group_id)
+            .set("enable.auto.commit", "false")
+            .set("enable.auto.offset.store", "false")
+            .set("enable.partition.eof", "false");
+
+        let consumer: StreamConsumer = client_config.create()?;
+        let assignment = self.build_assignment(&consumer, offset)?;
+        consumer.assign(&assignment)?;
+
+        info!(
+            "Kafka source consumer initialized: servers={}, topic={}, partitions={}",
+            self.config.bootstrap_servers,
+            self.config.topic,
+            assignment.count()
+        );
+        Ok(consumer)
+    }
+
+    async fn read_batch(
+        &self,
+        consumer: &mut StreamConsumer,
+    ) -> Result<Vec<SourceRecord>, CommonError> {
+        let deadline = sleep(Duration::from_millis(self.config.poll_timeout_ms));
+        tokio::pin!(deadline);
+
+        let mut records = Vec::new();
+        while records.len() < self.config.batch_size {
+            select! {
+                msg = consumer.recv() => {
+                    records.push(self.to_source_record(&msg?));
+                }
+                _ = &mut deadline => break,
+            }
+        }
+        Ok(records)
+    }
+}
+
+pub fn start_kafka_source_connector(
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    storage_driver_manager: Arc<StorageDriverManager>,
+    connector: MQTTConnector,
+    thread: BridgePluginThread,
+    stop_recv: Receiver<bool>,
+) {
+    tokio::spawn(Box::pin(async move {
+        let connector_name = connector.connector_name.clone();
+        let connector_type = connector.connector_type.to_string();
+        let source = match KafkaSource::new(connector.clone()) {
+            Ok(source) => source,
+            Err(e) => {
+                error!(
+                    "Invalid connector config type for Kafka source connector, connector_name='{}', connector_type='{}', error={}",
+                    connector_name, connector_type, e
+                );
+                return;
+            }
+        };
+
+        connector_manager.add_connector_thread(
+            &connector.tenant,
+            &connector.connector_name,
+            thread,
+        );
+
+        if let Err(e) = run_source_connector_loop(
+            &source,
+            &client_pool,
+            &connector_manager,
+            &storage_driver_manager,
+            connector.connector_name.clone(),
+            BridgePluginReadConfig {
+                tenant: connector.tenant,
+                topic_name: connector.topic_name,
+                record_num: source.config.batch_size as u64,
+                strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
+            },
+            stop_recv,
+        )
+        .await
+        {
+            connector_manager.remove_connector_thread(&connector.connector_name);
+            error!(
+                "Failed to start KafkaSource, connector_name='{}', connector_type='{}', error={:?}",
+                connector_name, connector_type, e
+            );
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_offset_resumes_after_stored_offset() {
+        for start_position in [SourceStartPosition::Earliest, SourceStartPosition::Latest] {
+            assert_eq!(
+                start_offset(Some("41"), &start_position),
+                Offset::Offset(42)
+            );
+        }
+    }
+
+    #[test]
+    fn start_offset_without_stored_offset_uses_start_position() {
+        assert_eq!(
+            start_offset(None, &SourceStartPosition::Earliest),
+            Offset::Beginning
+        );
+        assert_eq!(
+            start_offset(None, &SourceStartPosition::Latest),
+            Offset::End
+        );
+        assert_eq!(
+            start_offset(Some("not-a-number"), &SourceStartPosition::Earliest),
+            Offset::Beginning
+        );
+    }
+
+    #[test]
+    fn stored_offsets_round_trip_per_partition() {
+        let mut offset = SourceOffset::default();
+        offset.set("0", "41");
+        offset.set("1", "7");
+
+        let offset = SourceOffset::decode(&offset.encode().unwrap()).unwrap();
+        assert_eq!(
+            start_offset(offset.get("0"), &SourceStartPosition::Latest),
+            Offset::Offset(42)
+        );
+        assert_eq!(
+            start_offset(offset.get("1"), &SourceStartPosition::Latest),
+            Offset::Offset(8)
+        );
+        assert_eq!(
+            start_offset(offset.get("2"), &SourceStartPosition::Latest),
+            Offset::End
+        );
+    }
+}
@@ -16,6 +16,7 @@
 
 use common_base::task::{TaskKind, TaskSupervisor};
 use grpc_clients::pool::ClientPool;
+use schema_register::schema::SchemaRegisterManager;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::broadcast;
 
@@ -40,6 +41,7 @@
 pub mod mqtt_bridge;
 pub mod mysql;
 pub mod opentsdb;
+pub mod output;
 pub mod postgres;
 pub mod pulsar;
 pub mod rabbitmq;
@@ -53,6 +55,7 @@ pub async fn start_connector(
     client_pool: &Arc<ClientPool>,
     storage_driver_manager: &Arc<StorageDriverManager>,
     connector_manager: &Arc<ConnectorManager>,
+    schema_manager: &Arc<SchemaRegisterManager>,
     task_supervisor: &Arc<TaskSupervisor>,
     stop_send: &broadcast::Sender<bool>,
 ) {
@@ -61,6 +64,7 @@ pub async fn start_connector(
     let raw_connector_manager = connector_manager.clone();
     let raw_stop_send = stop_send.clone();
     let raw_client_poll = client_pool.clone();
+    let raw_schema_manager = schema_manager.clone();
     task_supervisor.spawn(
         TaskKind::ConnectorManager.to_string(),
         Box::pin(async move {
@@ -68,6 +72,7 @@ pub async fn start_connector(
                 raw_client_poll,
                 raw_message_storage,
                 raw_connector_manager,
+                raw_schema_manager,
                 raw_stop_send,
             )
             .await;
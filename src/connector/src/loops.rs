@@ -16,7 +16,9 @@
 use crate::failure::{failure_message_process, FailureRecordInfo};
 use crate::manager::ConnectorManager;
 use crate::storage::connector::ConnectorStorage;
-use crate::traits::ConnectorSink;
+use crate::storage::message::MessageStorage;
+use crate::storage::source_offset::SourceOffsetStorage;
+use crate::traits::{ConnectorSink, ConnectorSource, SourceRecord};
 use common_base::error::common::CommonError;
 use common_base::tools::{now_millis, now_second};
 use common_metrics::mqtt::connector::{
@@ -25,15 +27,22 @@
     record_connector_source_read_failure,
 };
 use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::connector::source::SourceOffset;
 use metadata_struct::connector::status::MQTTStatus;
 use metadata_struct::connector::FailureHandlingStrategy;
-use metadata_struct::storage::{adapter_read_config::AdapterReadConfig, record::StorageRecord};
+use metadata_struct::storage::{
+    adapter_read_config::AdapterReadConfig,
+    record::{StorageRecord, StorageRecordMetadata},
+};
+use rule_engine::apply_rule_engine;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use storage_adapter::consumer::GroupConsumer;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::{select, sync::mpsc, time::sleep};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 enum SendResultAction {
     Retry,
@@ -66,6 +75,7 @@ struct BatchCtx<'a> {
     tenant: &'a str,
     storage_driver_manager: &'a Arc<StorageDriverManager>,
     connector_manager: &'a Arc<ConnectorManager>,
+    buffers_records: bool,
 }
 
 pub async fn run_connector_loop<S: ConnectorSink>(
@@ -79,7 +89,6 @@ pub async fn run_connector_loop<S: ConnectorSink>(
 ) -> Result<(), CommonError> {
     sink.validate().await?;
 
-    let mut resource = Some(sink.init_sink().await?);
     let connector_tenant = config.tenant.clone();
     let connector_type = connector_manager
         .get_connector(&connector_name)
@@ -92,6 +101,7 @@ pub async fn run_connector_loop<S: ConnectorSink>(
         tenant: &connector_tenant,
         storage_driver_manager,
         connector_manager,
+        buffers_records: sink.buffers_records(),
     };
 
     let consumer = GroupConsumer::new_manual(storage_driver_manager.clone(), &connector_name);
@@ -100,6 +110,18 @@ pub async fn run_connector_loop<S: ConnectorSink>(
         max_size: 1024 * 1024 * 30,
     };
 
+    let mut raw_resource = sink.init_sink().await?;
+    if let Err(e) = restore_sink_offsets(sink, &mut raw_resource, &ctx, &consumer, &config).await {
+        if let Err(cleanup_err) = sink.cleanup_sink(raw_resource).await {
+            error!(
+                "Connector '{}' cleanup failed after restoring sink offsets, cleanup_error={}",
+                connector_name, cleanup_err
+            );
+        }
+        return Err(e);
+    }
+    let mut resource = Some(raw_resource);
+
     let mut run_result: Result<(), CommonError> = Ok(());
 
     'run: loop {
@@ -117,10 +139,37 @@ pub async fn run_connector_loop<S: ConnectorSink>(
                         connector_manager.report_heartbeat(&connector_tenant, &connector_name);
 
                         if data.is_empty() {
+                            if ctx.buffers_records {
+                                let raw_resource = resource
+                                    .as_mut()
+                                    .expect("sink resource must exist during connector loop");
+                                if let Err(e) = sink.flush_buffered(raw_resource, false).await {
+                                    warn!(
+                                        connector_name = ctx.connector_name,
+                                        "failed to write out buffered records: {}", e
+                                    );
+                                }
+                                if let Err(e) =
+                                    commit_flushed_offsets(sink, raw_resource, &ctx, &consumer, &config)
+                                        .await
+                                {
+                                    run_result = Err(e);
+                                    break 'run;
+                                }
+                            }
                             sleep(Duration::from_millis(100)).await;
                             continue;
                         }
 
+                        let data = match transform_batch(&ctx, &consumer, &config, data).await {
+                            Ok(data) if data.is_empty() => continue,
+                            Ok(data) => data,
+                            Err(e) => {
+                                run_result = Err(e);
+                                break 'run;
+                            }
+                        };
+
                         let start_time = now_millis();
                         let message_count = data.len() as u64;
                         let mut retry_times: u32 = 0;
@@ -179,6 +228,23 @@ pub async fn run_connector_loop<S: ConnectorSink>(
                                 }
                             }
                         }
+
+                        if ctx.buffers_records {
+                            if let Err(e) = commit_flushed_offsets(
+                                sink,
+                                resource
+                                    .as_mut()
+                                    .expect("sink resource must exist during connector loop"),
+                                &ctx,
+                                &consumer,
+                                &config,
+                            )
+                            .await
+                            {
+                                run_result = Err(e);
+                                break 'run;
+                            }
+                        }
                     }
                     Err(e) => {
                         match handle_read_error(client_pool, &ctx, &config.topic_name, e).await {
@@ -195,6 +261,27 @@ pub async fn run_connector_loop<S: ConnectorSink>(
         }
     }
 
+    if let Some(raw_resource) = resource.as_mut() {
+        if ctx.buffers_records {
+            let flushed = match sink.flush_buffered(raw_resource, true).await {
+                Ok(()) => {
+                    commit_flushed_offsets(sink, raw_resource, &ctx, &consumer, &config).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(flush_err) = flushed {
+                if run_result.is_ok() {
+                    run_result = Err(flush_err);
+                } else {
+                    error!(
+                        "Connector '{}' failed to write out buffered records after run error, error={}",
+                        connector_name, flush_err
+                    );
+                }
+            }
+        }
+    }
+
     if let Some(raw_resource) = resource.take() {
         if let Err(cleanup_err) = sink.cleanup_sink(raw_resource).await {
             if run_result.is_ok() {
@@ -211,6 +298,342 @@ pub async fn run_connector_loop<S: ConnectorSink>(
     run_result
 }
 
+/// Applies the ETL rule to a batch read from the topic. When no record is
+/// left for the sink, the batch is done here and its offsets are committed,
+/// so it is not read again.
+async fn transform_batch(
+    ctx: &BatchCtx<'_>,
+    consumer: &GroupConsumer,
+    config: &BridgePluginReadConfig,
+    data: Vec<StorageRecord>,
+) -> Result<Vec<StorageRecord>, CommonError> {
+    let data = apply_etl_rule(ctx, config, data).await;
+    if data.is_empty() {
+        // Every record failed the ETL rule and was handled by the failure
+        // strategy.
+        commit_consumer_offsets(ctx, consumer).await?;
+    }
+    Ok(data)
+}
+
+/// Runs the connector's ETL rule on each record. A record the rule fails on is
+/// handed to the failure strategy on its own, the rest of the batch goes on to
+/// the sink.
+async fn apply_etl_rule(
+    ctx: &BatchCtx<'_>,
+    config: &BridgePluginReadConfig,
+    data: Vec<StorageRecord>,
+) -> Vec<StorageRecord> {
+    if config.etl_rule.is_empty() {
+        return data;
+    }
+
+    let mut processed = Vec::with_capacity(data.len());
+    let mut fail_messages = Vec::new();
+    for mut record in data {
+        match apply_rule_engine(&config.etl_rule, &record.data).await {
+            Ok(value) => {
+                record.data = value;
+                processed.push(record);
+            }
+            Err(e) => {
+                error!(
+                    connector_name = ctx.connector_name,
+                    "failed to apply ETL rule to record at offset {}: {}",
+                    record.metadata.offset,
+                    e
+                );
+                fail_messages.push(FailureRecordInfo {
+                    tenant: ctx.tenant.to_string(),
+                    connector_name: ctx.connector_name.to_string(),
+                    connector_type: ctx.connector_type.to_string(),
+                    source_topic: config.topic_name.clone(),
+                    error_message: e.to_string(),
+                    records: vec![record],
+                });
+            }
+        }
+    }
+
+    report_etl_failures(ctx, config, &fail_messages).await;
+    processed
+}
+
+async fn report_etl_failures(
+    ctx: &BatchCtx<'_>,
+    config: &BridgePluginReadConfig,
+    fail_messages: &[FailureRecordInfo],
+) {
+    if fail_messages.is_empty() {
+        return;
+    }
+    update_last_active(
+        ctx.connector_manager,
+        ctx.tenant,
+        ctx.connector_name,
+        ctx.connector_type,
+        now_millis(),
+        fail_messages.len() as u64,
+        false,
+    );
+    process_fail_messages(ctx.storage_driver_manager, &config.strategy, fail_messages).await;
+}
+
+struct SourceBatch {
+    records: Vec<AdapterWriteRecord>,
+    offset: SourceOffset,
+    start_time: u128,
+    message_count: u64,
+}
+
+pub async fn run_source_connector_loop<S: ConnectorSource>(
+    source: &S,
+    client_pool: &Arc<ClientPool>,
+    connector_manager: &Arc<ConnectorManager>,
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    connector_name: String,
+    config: BridgePluginReadConfig,
+    mut stop_recv: mpsc::Receiver<bool>,
+) -> Result<(), CommonError> {
+    source.validate().await?;
+
+    let offset_storage = SourceOffsetStorage::new(client_pool.clone());
+    let mut offset = offset_storage.get(&config.tenant, &connector_name).await?;
+    let mut resource = source.init_source(&offset).await?;
+    let connector_tenant = config.tenant.clone();
+    let connector_type = connector_manager
+        .get_connector(&connector_name)
+        .map(|c| c.connector_type.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let ctx = BatchCtx {
+        connector_name: &connector_name,
+        connector_type: &connector_type,
+        tenant: &connector_tenant,
+        storage_driver_manager,
+        connector_manager,
+        buffers_records: false,
+    };
+
+    let message_storage = MessageStorage::new(storage_driver_manager.clone());
+    let mut pending: Option<SourceBatch> = None;
+    let mut run_result: Result<(), CommonError> = Ok(());
+
+    'run: loop {
+        select! {
+            val = stop_recv.recv() => {
+                match val {
+                    Some(true) | None => break,
+                    Some(false) => {}
+                }
+            },
+
+            val = source.read_batch(&mut resource), if pending.is_none() => {
+                match val {
+                    Ok(records) => {
+                        connector_manager.report_heartbeat(&connector_tenant, &connector_name);
+                        if records.is_empty() {
+                            continue;
+                        }
+                        pending = Some(build_source_batch(&ctx, &config, &offset, records).await);
+                    }
+                    Err(e) => {
+                        record_connector_source_read_failure(
+                            ctx.tenant,
+                            ctx.connector_type.to_string(),
+                            ctx.connector_name.to_string(),
+                        );
+                        error!(
+                            connector_name = ctx.connector_name,
+                            "failed to read from source, reconnecting: {}", e
+                        );
+                        sleep(Duration::from_millis(1000)).await;
+                        match source.init_source(&offset).await {
+                            Ok(new_resource) => {
+                                let old = std::mem::replace(&mut resource, new_resource);
+                                if let Err(e) = source.cleanup_source(old).await {
+                                    error!(
+                                        connector_name = ctx.connector_name,
+                                        "failed to clean up source connection: {}", e
+                                    );
+                                }
+                            }
+                            Err(e) => {
+                                error!(
+                                    connector_name = ctx.connector_name,
+                                    "failed to reconnect source: {}", e
+                                );
+                            }
+                        }
+                        continue;
+                    }
+                }
+            },
+
+            _ = sleep(Duration::from_millis(1000)), if pending.is_some() => {}
+        }
+
+        let Some(batch) = pending.take() else {
+            continue;
+        };
+
+        if !batch.records.is_empty() {
+            if let Err(e) = message_storage
+                .append_topic_message(&config.tenant, &config.topic_name, batch.records.clone())
+                .await
+            {
+                update_last_active(
+                    connector_manager,
+                    ctx.tenant,
+                    ctx.connector_name,
+                    ctx.connector_type,
+                    batch.start_time,
+                    batch.message_count,
+                    false,
+                );
+                match stop_connector(client_pool, connector_manager, &connector_name, &e).await {
+                    Ok(true) => {
+                        info!(
+                            connector_name = ctx.connector_name,
+                            "connector sealed and stopped, reason: {}", e
+                        );
+                        break 'run;
+                    }
+                    Ok(false) => {
+                        error!(
+                            connector_name = ctx.connector_name,
+                            topic_name = config.topic_name,
+                            "failed to write source batch, will retry: {}",
+                            e
+                        );
+                        // Keep the batch and retry it after the backoff.
+                        pending = Some(batch);
+                        continue;
+                    }
+                    Err(err) => {
+                        run_result = Err(err);
+                        break 'run;
+                    }
+                }
+            }
+        }
+
+        // At-least-once: the offset is only stored after the batch is in the
+        // topic, a crash in between replays the batch.
+        if let Err(e) = offset_storage
+            .save(&config.tenant, &connector_name, &batch.offset)
+            .await
+        {
+            record_connector_offset_commit_failure(
+                ctx.tenant,
+                ctx.connector_type.to_string(),
+                ctx.connector_name.to_string(),
+            );
+            error!(
+                connector_name = ctx.connector_name,
+                "failed to store source offset: {}", e
+            );
+        }
+        offset = batch.offset;
+
+        // The batch is stored, so a restart resumes after it even when the
+        // source was not acknowledged.
+        if let Err(e) = source.commit_batch(&mut resource, &offset).await {
+            error!(
+                connector_name = ctx.connector_name,
+                "failed to acknowledge source batch: {}", e
+            );
+            run_result = Err(e);
+            break 'run;
+        }
+
+        update_last_active(
+            connector_manager,
+            ctx.tenant,
+            ctx.connector_name,
+            ctx.connector_type,
+            batch.start_time,
+            batch.message_count,
+            true,
+        );
+    }
+
+    if let Err(cleanup_err) = source.cleanup_source(resource).await {
+        if run_result.is_ok() {
+            run_result = Err(cleanup_err);
+        } else {
+            error!(
+                "Connector '{}' cleanup failed after run error, cleanup_error={}",
+                connector_name, cleanup_err
+            );
+        }
+    }
+
+    run_result
+}
+
+/// Advances the offset over the read records and runs the ETL rule on them.
+/// Records the rule fails on go to the failure strategy, their offset is still
+/// advanced so they are not read again.
+async fn build_source_batch(
+    ctx: &BatchCtx<'_>,
+    config: &BridgePluginReadConfig,
+    offset: &SourceOffset,
+    records: Vec<SourceRecord>,
+) -> SourceBatch {
+    let start_time = now_millis();
+    let mut next_offset = offset.clone();
+    let mut write_records = Vec::with_capacity(records.len());
+    let mut fail_messages = Vec::new();
+
+    for SourceRecord {
+        partition,
+        offset,
+        mut record,
+    } in records
+    {
+        next_offset.set(partition.as_str(), offset);
+        if config.etl_rule.is_empty() {
+            write_records.push(record);
+            continue;
+        }
+
+        match apply_rule_engine(&config.etl_rule, &record.data).await {
+            Ok(value) => {
+                record.data = value;
+                write_records.push(record);
+            }
+            Err(e) => {
+                error!(
+                    connector_name = ctx.connector_name,
+                    "failed to apply ETL rule to source record from {}: {}", partition, e
+                );
+                fail_messages.push(FailureRecordInfo {
+                    tenant: ctx.tenant.to_string(),
+                    connector_name: ctx.connector_name.to_string(),
+                    connector_type: ctx.connector_type.to_string(),
+                    source_topic: config.topic_name.clone(),
+                    error_message: e.to_string(),
+                    records: vec![StorageRecord {
+                        metadata: StorageRecordMetadata::build(0, partition, 0)
+                            .with_key(record.key),
+                        protocol_data: record.protocol_data,
+                        data: record.data,
+                    }],
+                });
+            }
+        }
+    }
+
+    report_etl_failures(ctx, config, &fail_messages).await;
+    SourceBatch {
+        message_count: write_records.len() as u64,
+        records: write_records,
+        offset: next_offset,
+        start_time,
+    }
+}
+
 async fn handle_send_success(
     ctx: &BatchCtx<'_>,
     consumer: &GroupConsumer,
@@ -343,10 +766,88 @@ async fn process_fail_messages(
     }
 }
 
+/// Seeds the consumer from the offsets a sink stored with its data. Where the
+/// consumer group offset is further ahead, e.g. because a whole batch was
+/// dropped by the ETL rule or the failure strategy, the group offset wins.
+async fn restore_sink_offsets<S: ConnectorSink>(
+    sink: &S,
+    resource: &mut S::SinkResource,
+    ctx: &BatchCtx<'_>,
+    consumer: &GroupConsumer,
+    config: &BridgePluginReadConfig,
+) -> Result<(), CommonError> {
+    let Some(mut offsets) = sink.committed_offsets(resource).await? else {
+        return Ok(());
+    };
+
+    let committed = ctx
+        .storage_driver_manager
+        .get_offset_by_group(&config.tenant, ctx.connector_name)
+        .await?;
+    for group_offset in committed {
+        let offset = offsets.entry(group_offset.shard_name).or_insert(0);
+        *offset = (*offset).max(group_offset.offset);
+    }
+
+    if !offsets.is_empty() {
+        info!(
+            connector_name = ctx.connector_name,
+            "resuming from sink-side offsets: {:?}", offsets
+        );
+        consumer.set_current_offsets(&config.tenant, &config.topic_name, &offsets);
+    }
+    Ok(())
+}
+
+/// Next offset to read per shard once `records` are written, for sinks that
+/// store their position alongside the data.
+pub fn next_shard_offsets(records: &[StorageRecord]) -> HashMap<String, u64> {
+    let mut offsets = HashMap::new();
+    for record in records {
+        let next = offsets.entry(record.metadata.shard.clone()).or_insert(0);
+        *next = (*next).max(record.metadata.offset + 1);
+    }
+    offsets
+}
+
+/// Identifies a record by its position in the topic, so a replayed record
+/// lands on the row it was first written to.
+pub fn record_position_key(record: &StorageRecord) -> String {
+    format!("{}:{}", record.metadata.shard, record.metadata.offset)
+}
+
+/// Commits the offsets a buffering sink has written out since the last call.
+async fn commit_flushed_offsets<S: ConnectorSink>(
+    sink: &S,
+    resource: &mut S::SinkResource,
+    ctx: &BatchCtx<'_>,
+    consumer: &GroupConsumer,
+    config: &BridgePluginReadConfig,
+) -> Result<(), CommonError> {
+    let offsets = sink.take_flushed_offsets(resource);
+    consumer
+        .commit_shard_offsets(&config.tenant, &config.topic_name, &offsets)
+        .await
+        .inspect_err(|_| {
+            record_connector_offset_commit_failure(
+                ctx.tenant,
+                ctx.connector_type.to_string(),
+                ctx.connector_name.to_string(),
+            );
+        })
+}
+
+/// For sinks that buffer records, only the read position moves here; their
+/// offsets are committed once the records are written out.
 async fn commit_consumer_offsets(
     ctx: &BatchCtx<'_>,
     consumer: &GroupConsumer,
 ) -> Result<(), CommonError> {
+    if ctx.buffers_records {
+        consumer.advance();
+        return Ok(());
+    }
+
     consumer.commit().await.inspect_err(|_| {
         record_connector_offset_commit_failure(
             ctx.tenant,
@@ -422,3 +923,335 @@ pub fn update_last_active(
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::failure::DeadLetterRecord;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use metadata_struct::adapter::adapter_offset::AdapterCommitOffset;
+    use metadata_struct::connector::rule::{
+        DataDecodeType, DataEncodeType, DecodeDeleteParams, ETLOperator, ETLRule,
+        EncodeDeleteParams, KeepOnlyRuleParams,
+    };
+    use metadata_struct::connector::DeadMessageQueueStrategy;
+    use metadata_struct::tenant::DEFAULT_TENANT;
+    use storage_adapter::storage::{test_add_topic, test_build_storage_driver_manager};
+
+    const CONNECTOR_NAME: &str = "etl-connector";
+    const TOPIC: &str = "etl.source";
+    const DLQ_TOPIC: &str = "etl.dlq";
+
+    /// Keeps only `id` of a JSON object; anything that is not a JSON object
+    /// fails to decode.
+    fn keep_id_rule() -> ETLRule {
+        ETLRule {
+            decode_rule: Some(ETLOperator::Decode(DecodeDeleteParams {
+                data_type: DataDecodeType::JsonObject,
+                line_separator: None,
+                token_separator: None,
+                kv_separator: None,
+            })),
+            ops_rule_list: vec![ETLOperator::KeepOnly(KeepOnlyRuleParams {
+                keys: vec!["id".to_string()],
+            })],
+            encode_rule: Some(ETLOperator::Encode(EncodeDeleteParams {
+                data_type: DataEncodeType::JsonObject,
+                line_separator: None,
+                token_separator: None,
+                kv_separator: None,
+            })),
+        }
+    }
+
+    fn read_config(strategy: FailureHandlingStrategy) -> BridgePluginReadConfig {
+        BridgePluginReadConfig {
+            tenant: DEFAULT_TENANT.to_string(),
+            topic_name: TOPIC.to_string(),
+            record_num: 100,
+            strategy,
+            etl_rule: keep_id_rule(),
+        }
+    }
+
+    fn dlq_strategy() -> FailureHandlingStrategy {
+        FailureHandlingStrategy::DeadMessageQueue(DeadMessageQueueStrategy {
+            tenant: DEFAULT_TENANT.to_string(),
+            topic_name: DLQ_TOPIC.to_string(),
+            retry_total_times: 0,
+            wait_time_ms: 0,
+        })
+    }
+
+    fn record(offset: u64, data: &str) -> StorageRecord {
+        StorageRecord {
+            metadata: StorageRecordMetadata::build(offset, "shard".to_string(), 0),
+            protocol_data: None,
+            data: Bytes::from(data.to_string()),
+        }
+    }
+
+    fn batch_ctx<'a>(
+        storage_driver_manager: &'a Arc<StorageDriverManager>,
+        connector_manager: &'a Arc<ConnectorManager>,
+    ) -> BatchCtx<'a> {
+        BatchCtx {
+            connector_name: CONNECTOR_NAME,
+            connector_type: "test",
+            tenant: DEFAULT_TENANT,
+            storage_driver_manager,
+            connector_manager,
+            buffers_records: false,
+        }
+    }
+
+    /// A sink that reports a fixed stored position.
+    struct OffsetSink {
+        offsets: Option<HashMap<String, u64>>,
+    }
+
+    #[async_trait]
+    impl ConnectorSink for OffsetSink {
+        type SinkResource = ();
+
+        async fn validate(&self) -> Result<(), CommonError> {
+            Ok(())
+        }
+
+        async fn init_sink(&self) -> Result<(), CommonError> {
+            Ok(())
+        }
+
+        async fn send_batch(
+            &self,
+            _records: &[StorageRecord],
+            _resource: &mut (),
+        ) -> Result<Vec<FailureRecordInfo>, CommonError> {
+            Ok(Vec::new())
+        }
+
+        async fn committed_offsets(
+            &self,
+            _resource: &mut (),
+        ) -> Result<Option<HashMap<String, u64>>, CommonError> {
+            Ok(self.offsets.clone())
+        }
+    }
+
+    fn shard_name(storage_driver_manager: &Arc<StorageDriverManager>, topic: &str) -> String {
+        storage_driver_manager
+            .broker_cache
+            .get_topic_by_name(DEFAULT_TENANT, topic)
+            .and_then(|topic| topic.storage_name_list.get(&0).cloned())
+            .unwrap()
+    }
+
+    async fn read_topic(
+        storage_driver_manager: &Arc<StorageDriverManager>,
+        topic: &str,
+    ) -> Vec<StorageRecord> {
+        let mut offsets = HashMap::new();
+        offsets.insert(shard_name(storage_driver_manager, topic), 0);
+        storage_driver_manager
+            .read_by_offset(DEFAULT_TENANT, topic, &offsets, &AdapterReadConfig::new())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn etl_failures_go_to_failure_strategy() {
+        let storage_driver_manager = test_build_storage_driver_manager().await.unwrap();
+        test_add_topic(&storage_driver_manager, DLQ_TOPIC);
+        let connector_manager = Arc::new(ConnectorManager::new());
+        let ctx = batch_ctx(&storage_driver_manager, &connector_manager);
+
+        let data = vec![
+            record(0, r#"{"id":1,"name":"a"}"#),
+            record(1, "not json"),
+            record(2, r#"{"id":3,"name":"c"}"#),
+        ];
+        let processed = apply_etl_rule(&ctx, &read_config(dlq_strategy()), data).await;
+        assert_eq!(processed.len(), 2);
+
+        let dead_letters: Vec<DeadLetterRecord> = read_topic(&storage_driver_manager, DLQ_TOPIC)
+            .await
+            .iter()
+            .map(|r| serde_json::from_slice(&r.data).unwrap())
+            .collect();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].connector_name, CONNECTOR_NAME);
+        assert_eq!(dead_letters[0].source_topic, TOPIC);
+        assert_eq!(dead_letters[0].record.metadata.offset, 1);
+        assert_eq!(dead_letters[0].record.data, Bytes::from("not json"));
+    }
+
+    #[tokio::test]
+    async fn etl_drops_failed_records_and_transforms_the_rest() {
+        let storage_driver_manager = test_build_storage_driver_manager().await.unwrap();
+        let connector_manager = Arc::new(ConnectorManager::new());
+        let ctx = batch_ctx(&storage_driver_manager, &connector_manager);
+
+        let data = vec![
+            record(0, "[1,2]"),
+            record(1, r#"{"id":2,"name":"b"}"#),
+            record(2, "not json"),
+        ];
+        let processed =
+            apply_etl_rule(&ctx, &read_config(FailureHandlingStrategy::Discard), data).await;
+        assert_eq!(processed.len(), 1);
+        assert_eq!(processed[0].metadata.offset, 1);
+        let value: serde_json::Value = serde_json::from_slice(&processed[0].data).unwrap();
+        assert_eq!(value, serde_json::json!({ "id": 2 }));
+    }
+
+    #[tokio::test]
+    async fn batch_failing_etl_is_committed_and_not_read_again() {
+        let storage_driver_manager = test_build_storage_driver_manager().await.unwrap();
+        test_add_topic(&storage_driver_manager, TOPIC);
+        let connector_manager = Arc::new(ConnectorManager::new());
+        let ctx = batch_ctx(&storage_driver_manager, &connector_manager);
+        let config = read_config(FailureHandlingStrategy::Discard);
+
+        let records: Vec<AdapterWriteRecord> = (0..3)
+            .map(|i| AdapterWriteRecord::new(TOPIC, format!("broken {}", i)))
+            .collect();
+        storage_driver_manager
+            .write(DEFAULT_TENANT, TOPIC, &records, 1)
+            .await
+            .unwrap();
+        let shard = shard_name(&storage_driver_manager, TOPIC);
+        storage_driver_manager
+            .commit_group_offset(
+                DEFAULT_TENANT,
+                CONNECTOR_NAME,
+                &[AdapterCommitOffset {
+                    shard_name: shard.clone(),
+                    topic_name: TOPIC.to_string(),
+                    partition: 0,
+                    offset: 0,
+                }],
+            )
+            .await
+            .unwrap();
+
+        let consumer = GroupConsumer::new_manual(storage_driver_manager.clone(), CONNECTOR_NAME);
+        let read_config = AdapterReadConfig::new();
+        let data = consumer
+            .next_messages(DEFAULT_TENANT, TOPIC, &read_config)
+            .await
+            .unwrap();
+        assert_eq!(data.len(), 3);
+
+        let data = transform_batch(&ctx, &consumer, &config, data)
+            .await
+            .unwrap();
+        assert!(data.is_empty());
+
+        let committed = storage_driver_manager
+            .get_offset_by_group(DEFAULT_TENANT, CONNECTOR_NAME)
+            .await
+            .unwrap();
+        assert_eq!(committed.len(), 1);
+        assert_eq!(committed[0].shard_name, shard);
+        assert_eq!(committed[0].offset, 3);
+        assert!(consumer
+            .next_messages(DEFAULT_TENANT, TOPIC, &read_config)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Writes `count` records to the source topic and commits `group_offset`
+    /// for the connector, returning the topic's shard.
+    async fn seed_topic(
+        storage_driver_manager: &Arc<StorageDriverManager>,
+        count: u64,
+        group_offset: u64,
+    ) -> String {
+        test_add_topic(storage_driver_manager, TOPIC);
+        let records: Vec<AdapterWriteRecord> = (0..count)
+            .map(|i| AdapterWriteRecord::new(TOPIC, format!("record {}", i)))
+            .collect();
+        storage_driver_manager
+            .write(DEFAULT_TENANT, TOPIC, &records, 1)
+            .await
+            .unwrap();
+        let shard = shard_name(storage_driver_manager, TOPIC);
+        storage_driver_manager
+            .commit_group_offset(
+                DEFAULT_TENANT,
+                CONNECTOR_NAME,
+                &[AdapterCommitOffset {
+                    shard_name: shard.clone(),
+                    topic_name: TOPIC.to_string(),
+                    partition: 0,
+                    offset: group_offset,
+                }],
+            )
+            .await
+            .unwrap();
+        shard
+    }
+
+    /// Restores the consumer from `sink_offset` and returns the offset of the
+    /// first record it reads afterwards.
+    async fn first_offset_after_restore(group_offset: u64, sink_offset: Option<u64>) -> u64 {
+        let storage_driver_manager = test_build_storage_driver_manager().await.unwrap();
+        let connector_manager = Arc::new(ConnectorManager::new());
+        let shard = seed_topic(&storage_driver_manager, 8, group_offset).await;
+        let ctx = batch_ctx(&storage_driver_manager, &connector_manager);
+        let config = read_config(FailureHandlingStrategy::Discard);
+
+        let sink = OffsetSink {
+            offsets: sink_offset.map(|offset| HashMap::from([(shard, offset)])),
+        };
+        let consumer = GroupConsumer::new_manual(storage_driver_manager.clone(), CONNECTOR_NAME);
+        restore_sink_offsets(&sink, &mut (), &ctx, &consumer, &config)
+            .await
+            .unwrap();
+
+        let records = consumer
+            .next_messages(DEFAULT_TENANT, TOPIC, &AdapterReadConfig::new())
+            .await
+            .unwrap();
+        records[0].metadata.offset
+    }
+
+    #[tokio::test]
+    async fn restore_resumes_from_sink_offset_ahead_of_group() {
+        assert_eq!(first_offset_after_restore(2, Some(5)).await, 5);
+    }
+
+    #[tokio::test]
+    async fn restore_keeps_group_offset_ahead_of_sink() {
+        assert_eq!(first_offset_after_restore(6, Some(3)).await, 6);
+    }
+
+    #[tokio::test]
+    async fn restore_without_sink_offsets_uses_group_offset() {
+        assert_eq!(first_offset_after_restore(4, None).await, 4);
+    }
+
+    #[test]
+    fn position_key_is_deterministic() {
+        let first = record(7, "a");
+        let replayed = record(7, "b");
+        assert_eq!(record_position_key(&first), "shard:7");
+        assert_eq!(record_position_key(&first), record_position_key(&replayed));
+        assert_ne!(
+            record_position_key(&first),
+            record_position_key(&record(8, "a"))
+        );
+    }
+
+    #[test]
+    fn next_shard_offsets_follow_highest_offset() {
+        let mut other = record(3, "c");
+        other.metadata.shard = "other".to_string();
+        let offsets = next_shard_offsets(&[record(4, "a"), record(2, "b"), other]);
+        assert_eq!(offsets.len(), 2);
+        assert_eq!(offsets["shard"], 5);
+        assert_eq!(offsets["other"], 4);
+    }
+}
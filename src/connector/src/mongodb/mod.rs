@@ -26,7 +26,6 @@
     options::{ClientOptions, InsertManyOptions, WriteConcern},
     Client, Collection,
 };
-use rule_engine::apply_rule_engine;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::{debug, error, info, warn};
@@ -108,14 +107,11 @@ async fn create_client(&self) -> Result<Client, CommonError> {
     }
 
     #[allow(clippy::result_large_err)]
-    async fn record_to_document(&self, record: &StorageRecord) -> Result<Document, CommonError> {
-        let processed_data = apply_rule_engine(&self.connector.etl_rule, &record.data).await?;
-        let mut processed_record = record.clone();
-        processed_record.data = processed_data;
-        bson::to_document(&processed_record).map_err(|e| {
+    fn record_to_document(&self, record: &StorageRecord) -> Result<Document, CommonError> {
+        bson::to_document(record).map_err(|e| {
             CommonError::CommonError(format!(
                 "Failed to serialize record with key '{:?}' at timestamp {}: {}",
-                processed_record.metadata.key, processed_record.metadata.create_t, e
+                record.metadata.key, record.metadata.create_t, e
             ))
         })
     }
@@ -187,7 +183,7 @@ async fn send_batch(
         let mut fail_messages = Vec::new();
 
         for (idx, record) in records.iter().enumerate() {
-            match self.record_to_document(record).await {
+            match self.record_to_document(record) {
                 Ok(doc) => documents.push(doc),
                 Err(e) => {
                     warn!(
@@ -295,6 +291,7 @@ pub fn start_mongodb_connector(
                 topic_name: connector.topic_name,
                 record_num: batch_size,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -20,7 +20,6 @@
     connector::MQTTConnector, storage::record::StorageRecord,
 };
 use paho_mqtt as mqtt;
-use rule_engine::apply_rule_engine;
 use std::sync::Arc;
 use std::time::Duration;
 use storage_adapter::driver::StorageDriverManager;
@@ -35,6 +34,8 @@
     traits::ConnectorSink,
 };
 
+pub mod source;
+
 pub struct MqttBridgePlugin {
     connector: MQTTConnector,
     config: MqttBridgeConnectorConfig,
@@ -149,27 +150,11 @@ async fn send_batch(
             ));
         }
 
-        let mut fail_messages = Vec::new();
         for record in records {
             let topic = self.build_target_topic(record);
-            let payload = match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                Ok(data) => data,
-                Err(e) => {
-                    fail_messages.push(FailureRecordInfo {
-                        tenant: self.connector.tenant.clone(),
-                        connector_name: self.connector.connector_name.clone(),
-                        connector_type: self.connector.connector_type.to_string(),
-                        source_topic: self.connector.topic_name.clone(),
-                        error_message: e.to_string(),
-                        records: vec![record.clone()],
-                    });
-                    continue;
-                }
-            };
-
             let msg = mqtt::MessageBuilder::new()
                 .topic(&topic)
-                .payload(payload)
+                .payload(record.data.to_vec())
                 .qos(self.config.qos)
                 .retained(self.config.retain)
                 .finalize();
@@ -182,7 +167,7 @@ async fn send_batch(
             })?;
         }
 
-        Ok(fail_messages)
+        Ok(vec![])
     }
 }
 
@@ -225,6 +210,7 @@ pub fn start_mqtt_bridge_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
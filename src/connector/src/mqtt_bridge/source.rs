@@ -0,0 +1,371 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::{BridgePluginReadConfig, BridgePluginThread};
+use crate::loops::run_source_connector_loop;
+use crate::manager::ConnectorManager;
+use crate::traits::{ConnectorSource, SourceRecord};
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::connector::{
+    config_mqtt::MqttProtocolVersion, config_mqtt_source::MqttBridgeSourceConnectorConfig,
+    source::SourceOffset, ConnectorType, MQTTConnector,
+};
+use paho_mqtt as mqtt;
+use std::sync::Arc;
+use std::time::Duration;
+use storage_adapter::driver::StorageDriverManager;
+use tokio::sync::mpsc::{self, Receiver};
+use tokio::time::timeout;
+use tracing::{debug, error, warn};
+
+const READ_WAIT: Duration = Duration::from_secs(1);
+
+pub struct MqttBridgeSourceResource {
+    client: mqtt::AsyncClient,
+    // `None` is pushed by the connection-lost callback.
+    receiver: Receiver<Option<mqtt::Message>>,
+    disconnected: bool,
+}
+
+pub struct MqttBridgeSource {
+    connector: MQTTConnector,
+    config: MqttBridgeSourceConnectorConfig,
+}
+
+impl MqttBridgeSource {
+    #[allow(clippy::result_large_err)]
+    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+        let config = match &connector.connector_type {
+            ConnectorType::MqttBridgeSource(config) => config.clone(),
+            _ => {
+                return Err(CommonError::CommonError(
+                    "invalid connector type for mqtt bridge source".to_string(),
+                ));
+            }
+        };
+        Ok(MqttBridgeSource { connector, config })
+    }
+
+    /// The remote topic is kept as the record key, which is also what the MQTT
+    /// bridge sink uses to pick the target topic.
+    fn to_source_record(&self, msg: &mqtt::Message) -> SourceRecord {
+        SourceRecord {
+            partition: self.config.topic.clone(),
+            offset: now_second().to_string(),
+            record: AdapterWriteRecord::new(
+                self.connector.topic_name.as_str(),
+                msg.payload().to_vec(),
+            )
+            .with_key(msg.topic().as_bytes().to_vec()),
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectorSource for MqttBridgeSource {
+    type SourceResource = MqttBridgeSourceResource;
+
+    async fn validate(&self) -> Result<(), CommonError> {
+        self.config.validate()
+    }
+
+    // MQTT has no replayable offsets; delivery relies on the subscription QoS
+    // and a persistent session on the remote broker.
+    async fn init_source(
+        &self,
+        _offset: &SourceOffset,
+    ) -> Result<MqttBridgeSourceResource, CommonError> {
+        let client_id = if let Some(prefix) = &self.config.client_id_prefix {
+            format!("{}:{}", prefix, self.connector.connector_name)
+        } else {
+            format!("robustmq-source:{}", self.connector.connector_name)
+        };
+
+        let create_opts = mqtt::CreateOptionsBuilder::new()
+            .server_uri(&self.config.server)
+            .client_id(&client_id)
+            .finalize();
+
+        let client = mqtt::AsyncClient::new(create_opts).map_err(|e| {
+            CommonError::CommonError(format!("Failed to create MQTT client: {}", e))
+        })?;
+
+        let (sender, receiver) = mpsc::channel(self.config.batch_size.max(1) * 10);
+        let message_sender = sender.clone();
+        client.set_message_callback(move |_, msg| {
+            if let Some(msg) = msg {
+                let _ = message_sender.blocking_send(Some(msg));
+            }
+        });
+        client.set_connection_lost_callback(move |_| {
+            let _ = sender.blocking_send(None);
+        });
+
+        let conn_opts = {
+            let mut conn_builder = match self.config.protocol_version {
+                MqttProtocolVersion::V5 => mqtt::ConnectOptionsBuilder::new_v5(),
+                _ => mqtt::ConnectOptionsBuilder::new(),
+            };
+            conn_builder
+                .keep_alive_interval(Duration::from_secs(self.config.keepalive_secs))
+                .connect_timeout(Duration::from_secs(self.config.connect_timeout_secs))
+                .clean_session(false);
+
+            if let Some(username) = &self.config.username {
+                conn_builder.user_name(username);
+            }
+            if let Some(password) = &self.config.password {
+                conn_builder.password(password);
+            }
+
+            if self.config.enable_tls {
+                let ssl_opts = mqtt::SslOptionsBuilder::new().finalize();
+                conn_builder.ssl_options(ssl_opts);
+            }
+
+            conn_builder.finalize()
+        };
+
+        client.connect(conn_opts).await.map_err(|e| {
+            CommonError::CommonError(format!(
+                "Failed to connect to MQTT broker {}: {}",
+                self.config.server, e
+            ))
+        })?;
+
+        client
+            .subscribe(&self.config.topic, self.config.qos)
+            .await
+            .map_err(|e| {
+                CommonError::CommonError(format!(
+                    "Failed to subscribe to remote MQTT topic '{}': {}",
+                    self.config.topic, e
+                ))
+            })?;
+
+        debug!(
+            "Subscribed to remote MQTT broker: {} topic {} as {}",
+            self.config.server, self.config.topic, client_id
+        );
+
+        Ok(MqttBridgeSourceResource {
+            client,
+            receiver,
+            disconnected: false,
+        })
+    }
+
+    async fn read_batch(
+        &self,
+        resource: &mut MqttBridgeSourceResource,
+    ) -> Result<Vec<SourceRecord>, CommonError> {
+        let lost = || {
+            CommonError::CommonError(format!(
+                "Lost connection to remote MQTT broker {}",
+                self.config.server
+            ))
+        };
+        if resource.disconnected {
+            return Err(lost());
+        }
+
+        let first = match timeout(READ_WAIT, resource.receiver.recv()).await {
+            Ok(Some(Some(msg))) => msg,
+            Ok(Some(None)) | Ok(None) => return Err(lost()),
+            Err(_) => return Ok(vec![]),
+        };
+
+        let mut records = vec![self.to_source_record(&first)];
+        while records.len() < self.config.batch_size {
+            match resource.receiver.try_recv() {
+                Ok(Some(msg)) => records.push(self.to_source_record(&msg)),
+                // Hand over what was received; the next read reports the loss.
+                Ok(None) => {
+                    resource.disconnected = true;
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(records)
+    }
+
+    async fn cleanup_source(&self, resource: MqttBridgeSourceResource) -> Result<(), CommonError> {
+        if resource.client.is_connected() {
+            if let Err(e) = resource.client.disconnect(None).await {
+                warn!(
+                    "Failed to disconnect from remote MQTT broker {}: {}",
+                    self.config.server, e
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn start_mqtt_bridge_source_connector(
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    storage_driver_manager: Arc<StorageDriverManager>,
+    connector: MQTTConnector,
+    thread: BridgePluginThread,
+    stop_recv: Receiver<bool>,
+) {
+    tokio::spawn(Box::pin(async move {
+        let connector_name = connector.connector_name.clone();
+        let connector_type = connector.connector_type.to_string();
+        let source = match MqttBridgeSource::new(connector.clone()) {
+            Ok(source) => source,
+            Err(e) => {
+                error!(
+                    "Invalid connector config type for MqttBridgeSource connector, connector_name='{}', connector_type='{}', error={}",
+                    connector_name, connector_type, e
+                );
+                return;
+            }
+        };
+
+        connector_manager.add_connector_thread(
+            &connector.tenant,
+            &connector.connector_name,
+            thread,
+        );
+
+        if let Err(e) = run_source_connector_loop(
+            &source,
+            &client_pool,
+            &connector_manager,
+            &storage_driver_manager,
+            connector.connector_name.clone(),
+            BridgePluginReadConfig {
+                tenant: connector.tenant,
+                topic_name: connector.topic_name,
+                record_num: source.config.batch_size as u64,
+                strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
+            },
+            stop_recv,
+        )
+        .await
+        {
+            connector_manager.remove_connector_thread(&connector.connector_name);
+            error!(
+                "Failed to start MqttBridgeSource, connector_name='{}', connector_type='{}', error={:?}",
+                connector_name, connector_type, e
+            );
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::tenant::DEFAULT_TENANT;
+    use tokio::sync::mpsc::Sender;
+
+    fn source(batch_size: usize) -> MqttBridgeSource {
+        MqttBridgeSource::new(MQTTConnector {
+            connector_name: "test-mqtt-bridge-source".to_string(),
+            connector_type: ConnectorType::MqttBridgeSource(MqttBridgeSourceConnectorConfig {
+                server: "tcp://127.0.0.1:1883".to_string(),
+                topic: "sensors/#".to_string(),
+                batch_size,
+                ..Default::default()
+            }),
+            tenant: DEFAULT_TENANT.to_string(),
+            topic_name: "t1".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    /// A resource fed by hand instead of the client callbacks; the client is
+    /// never connected.
+    fn resource() -> (Sender<Option<mqtt::Message>>, MqttBridgeSourceResource) {
+        let client = mqtt::AsyncClient::new(
+            mqtt::CreateOptionsBuilder::new()
+                .server_uri("tcp://127.0.0.1:1883")
+                .client_id("test-mqtt-bridge-source")
+                .persistence(mqtt::PersistenceType::None)
+                .finalize(),
+        )
+        .unwrap();
+        let (sender, receiver) = mpsc::channel(16);
+        (
+            sender,
+            MqttBridgeSourceResource {
+                client,
+                receiver,
+                disconnected: false,
+            },
+        )
+    }
+
+    fn message(topic: &str, payload: &str) -> Option<mqtt::Message> {
+        Some(mqtt::Message::new(topic, payload, 1))
+    }
+
+    #[tokio::test]
+    async fn read_batch_keeps_remote_topic_as_key() {
+        let source = source(2);
+        let (sender, mut resource) = resource();
+        for (topic, payload) in [("sensors/a", "1"), ("sensors/b", "2"), ("sensors/c", "3")] {
+            sender.send(message(topic, payload)).await.unwrap();
+        }
+
+        let batch = source.read_batch(&mut resource).await.unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].partition, "sensors/#");
+        assert_eq!(batch[0].record.key.as_deref(), Some(&b"sensors/a"[..]));
+        assert_eq!(batch[1].record.data.as_ref(), b"2");
+
+        let batch = source.read_batch(&mut resource).await.unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].record.key.as_deref(), Some(&b"sensors/c"[..]));
+    }
+
+    #[tokio::test]
+    async fn connection_loss_hands_over_batch_then_fails_read() {
+        let source = source(10);
+        let (sender, mut resource) = resource();
+        sender.send(message("sensors/a", "1")).await.unwrap();
+        sender.send(None).await.unwrap();
+        sender.send(message("sensors/b", "2")).await.unwrap();
+
+        let batch = source.read_batch(&mut resource).await.unwrap();
+        assert_eq!(batch.len(), 1);
+        assert!(resource.disconnected);
+
+        // The loop reconnects on a read error, so nothing after the loss is
+        // handed over from this resource.
+        assert!(source.read_batch(&mut resource).await.is_err());
+        assert!(source.read_batch(&mut resource).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn connection_loss_before_any_message_fails_read() {
+        let source = source(10);
+        let (sender, mut resource) = resource();
+        sender.send(None).await.unwrap();
+        assert!(source.read_batch(&mut resource).await.is_err());
+
+        // The callbacks are gone once the client is dropped.
+        drop(sender);
+        assert!(source.read_batch(&mut resource).await.is_err());
+    }
+}
@@ -12,16 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use common_base::tools::now_second;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::{
     connector::config_mysql::MySQLConnectorConfig, connector::MQTTConnector,
     storage::record::StorageRecord,
 };
-use rule_engine::apply_rule_engine;
-use sqlx::{mysql::MySqlPoolOptions, MySql, Pool};
+use sqlx::{mysql::MySqlPoolOptions, MySql, MySqlConnection, Pool};
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::{error, warn};
@@ -31,7 +32,7 @@
 use super::{
     core::{BridgePluginReadConfig, BridgePluginThread},
     failure::FailureRecordInfo,
-    loops::run_connector_loop,
+    loops::{next_shard_offsets, run_connector_loop},
     manager::ConnectorManager,
     traits::ConnectorSink,
 };
@@ -65,7 +66,7 @@ async fn create_pool(&self) -> Result<Pool<MySql>, sqlx::Error> {
     async fn single_insert(
         &self,
         records: &[StorageRecord],
-        pool: &Pool<MySql>,
+        conn: &mut MySqlConnection,
     ) -> Result<(), CommonError> {
         for record in records {
             let payload = serde_json::to_string(record)?;
@@ -104,7 +105,7 @@ async fn single_insert(
                 .bind(record_key)
                 .bind(&payload)
                 .bind(record.metadata.create_t as i64)
-                .execute(pool)
+                .execute(&mut *conn)
                 .await?;
         }
 
@@ -114,7 +115,7 @@ async fn single_insert(
     async fn batch_insert(
         &self,
         records: &[StorageRecord],
-        pool: &Pool<MySql>,
+        conn: &mut MySqlConnection,
     ) -> Result<(), CommonError> {
         let mut values_placeholders = Vec::with_capacity(records.len());
         let mut bindings = Vec::with_capacity(records.len());
@@ -153,10 +154,59 @@ async fn batch_insert(
             query = query.bind(key).bind(payload).bind(timestamp);
         }
 
-        query.execute(pool).await?;
+        query.execute(&mut *conn).await?;
 
         Ok(())
     }
+
+    async fn insert_records(
+        &self,
+        records: &[StorageRecord],
+        conn: &mut MySqlConnection,
+    ) -> Result<(), CommonError> {
+        if self.config.is_batch_insert_enabled() {
+            if self.config.sql_template.is_some() {
+                warn!(
+                    "sql_template is not applied in batch mode; default batch INSERT will be used"
+                );
+            }
+            self.batch_insert(records, conn).await
+        } else {
+            self.single_insert(records, conn).await
+        }
+    }
+
+    async fn create_offset_table(&self, pool: &Pool<MySql>) -> Result<(), CommonError> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (connector_name VARCHAR(256) NOT NULL, shard_name VARCHAR(256) NOT NULL, next_offset BIGINT UNSIGNED NOT NULL, update_time BIGINT UNSIGNED NOT NULL, PRIMARY KEY (connector_name, shard_name)) ENGINE = InnoDB",
+            self.config.offset_table()
+        );
+        sqlx::query(&sql).execute(pool).await?;
+        Ok(())
+    }
+
+    /// Stores the batch position in the same transaction as its rows, so the
+    /// rows and the offset are either both written or both rolled back.
+    async fn store_offsets(
+        &self,
+        records: &[StorageRecord],
+        conn: &mut MySqlConnection,
+    ) -> Result<(), CommonError> {
+        let sql = format!(
+            "INSERT INTO {} (connector_name, shard_name, next_offset, update_time) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE next_offset = GREATEST(next_offset, VALUES(next_offset)), update_time = VALUES(update_time)",
+            self.config.offset_table()
+        );
+        for (shard, next_offset) in next_shard_offsets(records) {
+            sqlx::query(&sql)
+                .bind(&self.connector.connector_name)
+                .bind(&shard)
+                .bind(next_offset)
+                .bind(now_second())
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -169,6 +219,9 @@ async fn validate(&self) -> Result<(), CommonError> {
 
     async fn init_sink(&self) -> Result<Self::SinkResource, CommonError> {
         let pool = self.create_pool().await?;
+        if self.config.is_exactly_once_enabled() {
+            self.create_offset_table(&pool).await?;
+        }
         Ok(pool)
     }
 
@@ -180,42 +233,36 @@ async fn send_batch(
         if records.is_empty() {
             return Ok(vec![]);
         }
-        let mut processed_records = Vec::with_capacity(records.len());
-        let mut fail_messages = Vec::new();
-        for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-            let mut processed_record = record.clone();
-            processed_record.data = processed_data;
-            processed_records.push(processed_record);
-        }
-        if processed_records.is_empty() {
-            return Ok(fail_messages);
-        }
-        if self.config.is_batch_insert_enabled() {
-            if self.config.sql_template.is_some() {
-                warn!(
-                    "sql_template is not applied in batch mode; default batch INSERT will be used"
-                );
-            }
-            self.batch_insert(&processed_records, pool).await?;
+
+        if self.config.is_exactly_once_enabled() {
+            let mut tx = pool.begin().await?;
+            self.insert_records(records, &mut tx).await?;
+            self.store_offsets(records, &mut tx).await?;
+            tx.commit().await?;
         } else {
-            self.single_insert(&processed_records, pool).await?;
+            let mut conn = pool.acquire().await?;
+            self.insert_records(records, &mut conn).await?;
         }
-        Ok(fail_messages)
+        Ok(vec![])
+    }
+
+    async fn committed_offsets(
+        &self,
+        pool: &mut Pool<MySql>,
+    ) -> Result<Option<HashMap<String, u64>>, CommonError> {
+        if !self.config.is_exactly_once_enabled() {
+            return Ok(None);
+        }
+
+        let sql = format!(
+            "SELECT shard_name, next_offset FROM {} WHERE connector_name = ?",
+            self.config.offset_table()
+        );
+        let rows: Vec<(String, u64)> = sqlx::query_as(&sql)
+            .bind(&self.connector.connector_name)
+            .fetch_all(&*pool)
+            .await?;
+        Ok(Some(rows.into_iter().collect()))
     }
 
     async fn cleanup_sink(&self, pool: Pool<MySql>) -> Result<(), CommonError> {
@@ -262,6 +309,7 @@ pub fn start_mysql_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -20,7 +20,6 @@
     storage::record::StorageRecord,
 };
 use reqwest::Client;
-use rule_engine::apply_rule_engine;
 use serde_json::{json, Map, Value};
 use std::sync::Arc;
 use std::time::Duration;
@@ -64,9 +63,8 @@ fn build_client(&self) -> Result<Client, CommonError> {
     }
 
     #[allow(clippy::result_large_err)]
-    async fn record_to_data_point(&self, record: &StorageRecord) -> Result<Value, CommonError> {
-        let processed_data = apply_rule_engine(&self.connector.etl_rule, &record.data).await?;
-        let payload_str = String::from_utf8_lossy(&processed_data);
+    fn record_to_data_point(&self, record: &StorageRecord) -> Result<Value, CommonError> {
+        let payload_str = String::from_utf8_lossy(&record.data);
         let payload: Value = serde_json::from_str(&payload_str).map_err(|e| {
             CommonError::CommonError(format!("Failed to parse payload as JSON: {}", e))
         })?;
@@ -141,7 +139,7 @@ async fn send_batch(
         let mut data_points = Vec::new();
         let mut fail_messages = Vec::new();
         for record in records {
-            match self.record_to_data_point(record).await {
+            match self.record_to_data_point(record) {
                 Ok(dp) => data_points.push(dp),
                 Err(e) => {
                     error!(
@@ -237,6 +235,7 @@ pub fn start_opentsdb_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
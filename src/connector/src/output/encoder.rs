@@ -0,0 +1,161 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::schema::OutputSchema;
+use apache_avro::{types::Value as AvroValue, Codec, Writer as AvroWriter};
+use arrow_array::RecordBatch;
+use arrow_json::ReaderBuilder;
+use common_base::error::common::CommonError;
+use flate2::write::GzEncoder;
+use metadata_struct::connector::output::{OutputCompression, OutputFormat};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+use std::io::Write;
+
+/// Records of one output file, encoded as they arrive and turned into the
+/// final file bytes when the file is rolled.
+pub enum FileEncoder {
+    JsonLines(Vec<u8>),
+    Parquet {
+        schema: OutputSchema,
+        batches: Vec<RecordBatch>,
+    },
+    Avro {
+        schema: OutputSchema,
+        values: Vec<AvroValue>,
+    },
+}
+
+impl FileEncoder {
+    /// `schema` is only used by the columnar formats.
+    pub fn new(format: &OutputFormat, schema: Option<OutputSchema>) -> Self {
+        match (format, schema) {
+            (OutputFormat::Parquet, Some(schema)) => FileEncoder::Parquet {
+                schema,
+                batches: Vec::new(),
+            },
+            (OutputFormat::Avro, Some(schema)) => FileEncoder::Avro {
+                schema,
+                values: Vec::new(),
+            },
+            _ => FileEncoder::JsonLines(Vec::new()),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn append(&mut self, value: &Value) -> Result<(), CommonError> {
+        match self {
+            FileEncoder::JsonLines(buf) => {
+                serde_json::to_writer(&mut *buf, value)?;
+                buf.push(b'\n');
+            }
+            FileEncoder::Parquet { schema, batches } => {
+                // A decoder per record keeps a record that does not match the
+                // schema from leaving partial rows behind.
+                let mut decoder = ReaderBuilder::new(schema.arrow.clone())
+                    .build_decoder()
+                    .map_err(|e| CommonError::CommonError(e.to_string()))?;
+                decoder
+                    .serialize(std::slice::from_ref(value))
+                    .map_err(|e| {
+                        CommonError::CommonError(format!(
+                            "payload does not match the output schema: {}",
+                            e
+                        ))
+                    })?;
+                if let Some(batch) = decoder
+                    .flush()
+                    .map_err(|e| CommonError::CommonError(e.to_string()))?
+                {
+                    batches.push(batch);
+                }
+            }
+            FileEncoder::Avro { schema, values } => {
+                let avro = AvroValue::from(value.clone())
+                    .resolve(&schema.avro)
+                    .map_err(|e| {
+                        CommonError::CommonError(format!(
+                            "payload does not match the output schema: {}",
+                            e
+                        ))
+                    })?;
+                values.push(avro);
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn finish(self, compression: &OutputCompression) -> Result<Vec<u8>, CommonError> {
+        match self {
+            FileEncoder::JsonLines(buf) => match compression {
+                OutputCompression::None => Ok(buf),
+                OutputCompression::Gzip => {
+                    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(&buf)?;
+                    Ok(encoder.finish()?)
+                }
+                OutputCompression::Zstd => Ok(zstd::encode_all(buf.as_slice(), 0)?),
+            },
+            FileEncoder::Parquet { schema, batches } => {
+                let codec = match compression {
+                    OutputCompression::None => Compression::UNCOMPRESSED,
+                    OutputCompression::Gzip => Compression::GZIP(GzipLevel::default()),
+                    OutputCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+                };
+                let props = WriterProperties::builder().set_compression(codec).build();
+                let mut writer = ArrowWriter::try_new(Vec::new(), schema.arrow, Some(props))
+                    .map_err(|e| {
+                        CommonError::CommonError(format!("Failed to create Parquet writer: {}", e))
+                    })?;
+                for batch in batches.iter() {
+                    writer.write(batch).map_err(|e| {
+                        CommonError::CommonError(format!("Failed to write Parquet rows: {}", e))
+                    })?;
+                }
+                writer.into_inner().map_err(|e| {
+                    CommonError::CommonError(format!("Failed to finish Parquet file: {}", e))
+                })
+            }
+            FileEncoder::Avro { schema, values } => {
+                let codec = match compression {
+                    OutputCompression::None => Codec::Null,
+                    OutputCompression::Gzip => Codec::Deflate,
+                    OutputCompression::Zstd => Codec::Zstandard,
+                };
+                let mut writer = AvroWriter::with_codec(&schema.avro, Vec::new(), codec);
+                for value in values {
+                    writer.append(value).map_err(|e| {
+                        CommonError::CommonError(format!("Failed to write Avro record: {}", e))
+                    })?;
+                }
+                writer.into_inner().map_err(|e| {
+                    CommonError::CommonError(format!("Failed to finish Avro file: {}", e))
+                })
+            }
+        }
+    }
+}
+
+pub fn file_extension(format: &OutputFormat, compression: &OutputCompression) -> &'static str {
+    match (format, compression) {
+        (OutputFormat::Parquet, _) => "parquet",
+        (OutputFormat::Avro, _) => "avro",
+        (_, OutputCompression::Gzip) => "jsonl.gz",
+        (_, OutputCompression::Zstd) => "jsonl.zst",
+        (_, OutputCompression::None) => "jsonl",
+    }
+}
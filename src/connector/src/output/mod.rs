@@ -0,0 +1,366 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::DateTime;
+use common_base::error::common::CommonError;
+use metadata_struct::connector::output::{OutputCompression, OutputFormat, PartitionBy};
+use metadata_struct::storage::record::StorageRecord;
+use schema_register::schema::SchemaRegisterManager;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use self::encoder::{file_extension, FileEncoder};
+use self::schema::OutputSchema;
+
+pub mod encoder;
+pub mod schema;
+
+pub struct RollingOutputConfig {
+    pub tenant: String,
+    pub topic_name: String,
+    pub format: OutputFormat,
+    pub compression: OutputCompression,
+    pub partition_by: PartitionBy,
+    pub roll_size_mb: u64,
+    pub roll_interval_secs: u64,
+}
+
+/// A rolled file, ready to be written to the target store.
+pub struct FinishedFile {
+    /// Hive-style partition directory, empty when partitioning is off.
+    pub partition: String,
+    pub extension: &'static str,
+    pub data: Vec<u8>,
+    pub record_count: u64,
+    next_offsets: HashMap<String, u64>,
+}
+
+struct OpenFile {
+    encoder: FileEncoder,
+    opened_at: Instant,
+    payload_bytes: u64,
+    record_count: u64,
+    // shard -> lowest offset in this file
+    first_offsets: HashMap<String, u64>,
+    // shard -> offset after the highest one in this file
+    next_offsets: HashMap<String, u64>,
+}
+
+/// Buffers records into one open file per partition and rolls a file once it
+/// reaches `roll_size_mb` of payload or has been open for `roll_interval_secs`.
+/// Rolled files wait in a queue until the sink reports them written, and only
+/// then count towards the offsets handed back for committing.
+pub struct RollingOutput {
+    config: RollingOutputConfig,
+    schema_manager: Arc<SchemaRegisterManager>,
+    open_files: HashMap<String, OpenFile>,
+    ready: VecDeque<(FinishedFile, HashMap<String, u64>)>,
+    // shard -> next offset covered by written files
+    written: HashMap<String, u64>,
+    // shard -> offset last handed out by take_committable_offsets
+    committed: HashMap<String, u64>,
+}
+
+impl RollingOutput {
+    pub fn new(config: RollingOutputConfig, schema_manager: Arc<SchemaRegisterManager>) -> Self {
+        RollingOutput {
+            config,
+            schema_manager,
+            open_files: HashMap::new(),
+            ready: VecDeque::new(),
+            written: HashMap::new(),
+            committed: HashMap::new(),
+        }
+    }
+
+    /// Adds `records` to the open files. Returns the records that could not be
+    /// encoded together with the reason; those are left to the failure strategy
+    /// and do not hold back the committed offsets.
+    pub fn append(&mut self, records: &[StorageRecord]) -> Vec<(StorageRecord, String)> {
+        let failed = self.encode(records);
+        for (record, _) in failed.iter() {
+            let written = self
+                .written
+                .entry(record.metadata.shard.clone())
+                .or_insert(0);
+            *written = (*written).max(record.metadata.offset + 1);
+        }
+        failed
+    }
+
+    fn encode(&mut self, records: &[StorageRecord]) -> Vec<(StorageRecord, String)> {
+        let mut failed = Vec::new();
+        let mut by_partition: Vec<(String, Vec<(&StorageRecord, Value)>)> = Vec::new();
+        for record in records {
+            let value = match serde_json::from_slice::<Value>(&record.data) {
+                Ok(value) if value.is_object() => value,
+                Ok(_) => {
+                    failed.push((record.clone(), "payload is not a JSON object".to_string()));
+                    continue;
+                }
+                Err(e) => {
+                    failed.push((record.clone(), format!("payload is not valid JSON: {}", e)));
+                    continue;
+                }
+            };
+            let partition = partition_path(&self.config.partition_by, record.metadata.create_t);
+            match by_partition.iter_mut().find(|(p, _)| *p == partition) {
+                Some((_, values)) => values.push((record, value)),
+                None => by_partition.push((partition, vec![(record, value)])),
+            }
+        }
+
+        for (partition, values) in by_partition {
+            if !self.open_files.contains_key(&partition) {
+                let samples: Vec<&Value> = values.iter().map(|(_, v)| v).collect();
+                match self.open_file(&samples) {
+                    Ok(file) => {
+                        self.open_files.insert(partition.clone(), file);
+                    }
+                    Err(e) => {
+                        let reason = e.to_string();
+                        failed.extend(values.into_iter().map(|(r, _)| (r.clone(), reason.clone())));
+                        continue;
+                    }
+                }
+            }
+
+            let file = self
+                .open_files
+                .get_mut(&partition)
+                .expect("open file was just created");
+            for (record, value) in values {
+                if let Err(e) = file.encoder.append(&value) {
+                    failed.push((record.clone(), e.to_string()));
+                    continue;
+                }
+                let shard = &record.metadata.shard;
+                let offset = record.metadata.offset;
+                file.payload_bytes += record.data.len() as u64;
+                file.record_count += 1;
+                let first = file.first_offsets.entry(shard.clone()).or_insert(offset);
+                *first = (*first).min(offset);
+                let next = file.next_offsets.entry(shard.clone()).or_insert(0);
+                *next = (*next).max(offset + 1);
+            }
+        }
+        failed
+    }
+
+    /// Moves open files that are due, or all of them when `force` is set, to
+    /// the queue of files waiting to be written.
+    #[allow(clippy::result_large_err)]
+    pub fn roll(&mut self, force: bool) -> Result<(), CommonError> {
+        let roll_size = self.config.roll_size_mb * 1024 * 1024;
+        let roll_interval = Duration::from_secs(self.config.roll_interval_secs);
+        let due: Vec<String> = self
+            .open_files
+            .iter()
+            .filter(|(_, f)| {
+                force || f.payload_bytes >= roll_size || f.opened_at.elapsed() >= roll_interval
+            })
+            .map(|(p, _)| p.clone())
+            .collect();
+
+        for partition in due {
+            let file = self
+                .open_files
+                .remove(&partition)
+                .expect("due file is open");
+            if file.record_count == 0 {
+                continue;
+            }
+            let data = file.encoder.finish(&self.config.compression)?;
+            self.ready.push_back((
+                FinishedFile {
+                    partition,
+                    extension: file_extension(&self.config.format, &self.config.compression),
+                    data,
+                    record_count: file.record_count,
+                    next_offsets: file.next_offsets,
+                },
+                file.first_offsets,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Oldest rolled file that still has to be written.
+    pub fn next_ready(&self) -> Option<&FinishedFile> {
+        self.ready.front().map(|(file, _)| file)
+    }
+
+    /// Marks the file returned by `next_ready` as written.
+    pub fn complete_ready(&mut self) {
+        if let Some((file, _)) = self.ready.pop_front() {
+            for (shard, next) in file.next_offsets {
+                let written = self.written.entry(shard).or_insert(0);
+                *written = (*written).max(next);
+            }
+        }
+    }
+
+    /// Next offset per shard up to which every record has been written, for
+    /// shards that moved since the last call. A record still buffered in an
+    /// open or unwritten file holds its shard back, as files of different
+    /// partitions are rolled independently.
+    pub fn take_committable_offsets(&mut self) -> HashMap<String, u64> {
+        let mut offsets = HashMap::new();
+        for (shard, written) in self.written.iter() {
+            let pending = self
+                .open_files
+                .values()
+                .filter_map(|f| f.first_offsets.get(shard))
+                .chain(self.ready.iter().filter_map(|(_, first)| first.get(shard)))
+                .min()
+                .copied();
+            let next = pending.map_or(*written, |p| p.min(*written));
+            if self.committed.get(shard).is_none_or(|c| next > *c) {
+                self.committed.insert(shard.clone(), next);
+                offsets.insert(shard.clone(), next);
+            }
+        }
+        offsets
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn open_file(&self, samples: &[&Value]) -> Result<OpenFile, CommonError> {
+        let schema = match self.config.format {
+            OutputFormat::Parquet | OutputFormat::Avro => Some(self.resolve_schema(samples)?),
+            OutputFormat::Json | OutputFormat::Jsonl => None,
+        };
+        Ok(OpenFile {
+            encoder: FileEncoder::new(&self.config.format, schema),
+            opened_at: Instant::now(),
+            payload_bytes: 0,
+            record_count: 0,
+            first_offsets: HashMap::new(),
+            next_offsets: HashMap::new(),
+        })
+    }
+
+    /// Uses the schema bound to the topic in schema-register, falling back to
+    /// one inferred from the records the file is opened with.
+    #[allow(clippy::result_large_err)]
+    fn resolve_schema(&self, samples: &[&Value]) -> Result<OutputSchema, CommonError> {
+        for bound in self
+            .schema_manager
+            .get_bind_schema_by_resource(&self.config.tenant, &self.config.topic_name)
+        {
+            match OutputSchema::from_bound(&bound) {
+                Ok(Some(schema)) => return Ok(schema),
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "Bound schema '{}' of topic '{}' cannot be used for {} output: {}",
+                    bound.name, self.config.topic_name, self.config.format, e
+                ),
+            }
+        }
+        OutputSchema::infer(samples)
+    }
+}
+
+/// Hive-style partition directory for a record created at `create_t` seconds.
+pub fn partition_path(partition_by: &PartitionBy, create_t: u64) -> String {
+    let time = DateTime::from_timestamp(create_t as i64, 0).unwrap_or_default();
+    match partition_by {
+        PartitionBy::None => String::new(),
+        PartitionBy::Daily => time.format("dt=%Y-%m-%d").to_string(),
+        PartitionBy::Hourly => time.format("dt=%Y-%m-%d/hour=%H").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use metadata_struct::storage::record::StorageRecordMetadata;
+
+    fn record(shard: &str, offset: u64, create_t: u64, data: &str) -> StorageRecord {
+        let mut metadata = StorageRecordMetadata::build(offset, shard.to_string(), 0);
+        metadata.create_t = create_t;
+        StorageRecord {
+            metadata,
+            data: Bytes::from(data.to_string()),
+            protocol_data: None,
+        }
+    }
+
+    #[test]
+    fn partition_path_test() {
+        assert_eq!(partition_path(&PartitionBy::None, 1760792400), "");
+        assert_eq!(
+            partition_path(&PartitionBy::Daily, 1760792400),
+            "dt=2025-10-18"
+        );
+        assert_eq!(
+            partition_path(&PartitionBy::Hourly, 1760792400),
+            "dt=2025-10-18/hour=13"
+        );
+    }
+
+    #[test]
+    fn rolled_offsets_wait_for_other_partitions() {
+        let mut output = RollingOutput::new(
+            RollingOutputConfig {
+                tenant: "default".to_string(),
+                topic_name: "t1".to_string(),
+                format: OutputFormat::Parquet,
+                compression: OutputCompression::Zstd,
+                partition_by: PartitionBy::Hourly,
+                roll_size_mb: 1,
+                roll_interval_secs: 3600,
+            },
+            Arc::new(SchemaRegisterManager::new()),
+        );
+
+        let failed = output.append(&[
+            record("s1", 0, 1760792400, r#"{"id":1,"temp":20.5}"#),
+            record("s1", 1, 1760796000, r#"{"id":2,"temp":21.0}"#),
+            record("s1", 2, 1760792400, "not json"),
+        ]);
+        assert_eq!(failed.len(), 1);
+
+        output.roll(false).unwrap();
+        assert!(output.next_ready().is_none());
+
+        // Only the first hour is rolled and written, the record from the
+        // second hour keeps shard s1 at offset 1.
+        let second_hour = output.open_files.remove("dt=2025-10-18/hour=14").unwrap();
+        output.roll(true).unwrap();
+        output
+            .open_files
+            .insert("dt=2025-10-18/hour=14".to_string(), second_hour);
+        let file = output.next_ready().unwrap();
+        assert_eq!(file.partition, "dt=2025-10-18/hour=13");
+        assert_eq!(file.extension, "parquet");
+        assert_eq!(&file.data[..4], b"PAR1");
+        output.complete_ready();
+        assert_eq!(
+            output.take_committable_offsets(),
+            HashMap::from([("s1".to_string(), 1)])
+        );
+
+        output.roll(true).unwrap();
+        output.complete_ready();
+        assert_eq!(
+            output.take_committable_offsets(),
+            HashMap::from([("s1".to_string(), 3)])
+        );
+        assert!(output.take_committable_offsets().is_empty());
+    }
+}
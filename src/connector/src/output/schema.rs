@@ -0,0 +1,328 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use apache_avro::Schema as AvroSchema;
+use arrow_json::reader::infer_json_schema_from_iterator;
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit};
+use common_base::error::common::CommonError;
+use metadata_struct::schema::{SchemaData, SchemaType};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Schema of one output file, in the shape each columnar writer needs.
+#[derive(Clone)]
+pub struct OutputSchema {
+    pub arrow: SchemaRef,
+    pub avro: Arc<AvroSchema>,
+}
+
+impl OutputSchema {
+    /// Builds the schema from an Avro or JSON schema bound in schema-register.
+    /// Protobuf schemas have no JSON payload to map and return `None`.
+    #[allow(clippy::result_large_err)]
+    pub fn from_bound(schema: &SchemaData) -> Result<Option<Self>, CommonError> {
+        match schema.schema_type {
+            SchemaType::AVRO => {
+                let avro = AvroSchema::parse_str(&schema.schema).map_err(|e| {
+                    CommonError::CommonError(format!(
+                        "Failed to parse Avro schema '{}': {}",
+                        schema.name, e
+                    ))
+                })?;
+                let arrow = match avro_to_arrow(&avro)? {
+                    DataType::Struct(fields) => Schema::new(fields),
+                    _ => {
+                        return Err(CommonError::CommonError(format!(
+                            "Avro schema '{}' must be a record",
+                            schema.name
+                        )))
+                    }
+                };
+                Ok(Some(OutputSchema {
+                    arrow: Arc::new(arrow),
+                    avro: Arc::new(avro),
+                }))
+            }
+            SchemaType::JSON => {
+                let value: Value = serde_json::from_str(&schema.schema)?;
+                let arrow = match json_schema_to_arrow(&value)? {
+                    DataType::Struct(fields) => Schema::new(fields),
+                    _ => {
+                        return Err(CommonError::CommonError(format!(
+                            "JSON schema '{}' must describe an object with properties",
+                            schema.name
+                        )))
+                    }
+                };
+                Self::from_arrow(arrow).map(Some)
+            }
+            SchemaType::PROTOBUF => Ok(None),
+        }
+    }
+
+    /// Infers the schema from sample payloads, e.g. the records a file is
+    /// opened with. Later records that do not fit are reported as failures.
+    #[allow(clippy::result_large_err)]
+    pub fn infer(samples: &[&Value]) -> Result<Self, CommonError> {
+        let arrow = infer_json_schema_from_iterator(samples.iter().map(|v| Ok(*v)))
+            .map_err(|e| CommonError::CommonError(format!("Failed to infer schema: {}", e)))?;
+        Self::from_arrow(arrow)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn from_arrow(arrow: Schema) -> Result<Self, CommonError> {
+        let mut name_seq = 0;
+        let record = arrow_fields_to_avro("root", arrow.fields(), &mut name_seq);
+        let avro = AvroSchema::parse(&record)
+            .map_err(|e| CommonError::CommonError(format!("Failed to build Avro schema: {}", e)))?;
+        Ok(OutputSchema {
+            arrow: Arc::new(arrow),
+            avro: Arc::new(avro),
+        })
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn avro_to_arrow(schema: &AvroSchema) -> Result<DataType, CommonError> {
+    let data_type = match schema {
+        AvroSchema::Null => DataType::Null,
+        AvroSchema::Boolean => DataType::Boolean,
+        AvroSchema::Int | AvroSchema::TimeMillis => DataType::Int32,
+        AvroSchema::Long | AvroSchema::TimeMicros => DataType::Int64,
+        AvroSchema::Float => DataType::Float32,
+        AvroSchema::Double | AvroSchema::Decimal(_) | AvroSchema::BigDecimal => DataType::Float64,
+        AvroSchema::Bytes
+        | AvroSchema::Fixed(_)
+        | AvroSchema::String
+        | AvroSchema::Enum(_)
+        | AvroSchema::Uuid
+        | AvroSchema::Duration => DataType::Utf8,
+        AvroSchema::Date => DataType::Date32,
+        AvroSchema::TimestampMillis | AvroSchema::LocalTimestampMillis => {
+            DataType::Timestamp(TimeUnit::Millisecond, None)
+        }
+        AvroSchema::TimestampMicros | AvroSchema::LocalTimestampMicros => {
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        }
+        AvroSchema::TimestampNanos | AvroSchema::LocalTimestampNanos => {
+            DataType::Timestamp(TimeUnit::Nanosecond, None)
+        }
+        AvroSchema::Array(array) => DataType::List(Arc::new(avro_field("item", &array.items)?)),
+        AvroSchema::Map(map) => DataType::Map(
+            Arc::new(Field::new(
+                "entries",
+                DataType::Struct(Fields::from(vec![
+                    Field::new("keys", DataType::Utf8, false),
+                    avro_field("values", &map.types)?,
+                ])),
+                false,
+            )),
+            false,
+        ),
+        AvroSchema::Record(record) => DataType::Struct(
+            record
+                .fields
+                .iter()
+                .map(|field| avro_field(&field.name, &field.schema))
+                .collect::<Result<Vec<_>, _>>()?
+                .into(),
+        ),
+        AvroSchema::Union(union) => match non_null_variant(union.variants()) {
+            Some(inner) => avro_to_arrow(inner)?,
+            None => {
+                return Err(CommonError::CommonError(
+                    "only unions of null and one other type are supported".to_string(),
+                ))
+            }
+        },
+        AvroSchema::Ref { name } => {
+            return Err(CommonError::CommonError(format!(
+                "named type references are not supported: {}",
+                name
+            )))
+        }
+    };
+    Ok(data_type)
+}
+
+#[allow(clippy::result_large_err)]
+fn avro_field(name: &str, schema: &AvroSchema) -> Result<Field, CommonError> {
+    let nullable = match schema {
+        AvroSchema::Union(union) => union.is_nullable(),
+        AvroSchema::Null => true,
+        _ => false,
+    };
+    Ok(Field::new(name, avro_to_arrow(schema)?, nullable))
+}
+
+fn non_null_variant(variants: &[AvroSchema]) -> Option<&AvroSchema> {
+    let mut others = variants.iter().filter(|s| !matches!(s, AvroSchema::Null));
+    match (others.next(), others.next()) {
+        (Some(inner), None) => Some(inner),
+        _ => None,
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn json_schema_to_arrow(schema: &Value) -> Result<DataType, CommonError> {
+    let type_name = match schema.get("type") {
+        Some(Value::String(name)) => name.as_str(),
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(|n| n.as_str())
+            .find(|n| *n != "null")
+            .unwrap_or("null"),
+        _ => {
+            return Err(CommonError::CommonError(format!(
+                "JSON schema node has no type: {}",
+                schema
+            )))
+        }
+    };
+
+    let data_type = match type_name {
+        "string" => DataType::Utf8,
+        "integer" => DataType::Int64,
+        "number" => DataType::Float64,
+        "boolean" => DataType::Boolean,
+        "null" => DataType::Null,
+        "array" => {
+            let items = schema.get("items").ok_or_else(|| {
+                CommonError::CommonError("JSON schema array has no items".to_string())
+            })?;
+            DataType::List(Arc::new(Field::new(
+                "item",
+                json_schema_to_arrow(items)?,
+                true,
+            )))
+        }
+        "object" => {
+            let properties = schema
+                .get("properties")
+                .and_then(|p| p.as_object())
+                .ok_or_else(|| {
+                    CommonError::CommonError("JSON schema object has no properties".to_string())
+                })?;
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(|r| r.as_array())
+                .map(|r| r.iter().filter_map(|n| n.as_str()).collect())
+                .unwrap_or_default();
+            DataType::Struct(
+                properties
+                    .iter()
+                    .map(|(name, property)| {
+                        Ok(Field::new(
+                            name,
+                            json_schema_to_arrow(property)?,
+                            !required.contains(&name.as_str()),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, CommonError>>()?
+                    .into(),
+            )
+        }
+        other => {
+            return Err(CommonError::CommonError(format!(
+                "unsupported JSON schema type: {}",
+                other
+            )))
+        }
+    };
+    Ok(data_type)
+}
+
+/// Avro requires every record to have a unique name, nested records are
+/// named after their position.
+fn arrow_fields_to_avro(name: &str, fields: &Fields, name_seq: &mut u32) -> Value {
+    let fields: Vec<Value> = fields
+        .iter()
+        .map(|field| {
+            let field_type = arrow_type_to_avro(field.name(), field.data_type(), name_seq);
+            if field.is_nullable() && *field.data_type() != DataType::Null {
+                json!({"name": field.name(), "type": ["null", field_type], "default": null})
+            } else {
+                json!({"name": field.name(), "type": field_type})
+            }
+        })
+        .collect();
+    json!({"type": "record", "name": name, "fields": fields})
+}
+
+fn arrow_type_to_avro(name: &str, data_type: &DataType, name_seq: &mut u32) -> Value {
+    match data_type {
+        DataType::Null => json!("null"),
+        DataType::Boolean => json!("boolean"),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 => json!("int"),
+        DataType::Int64 | DataType::UInt8 | DataType::UInt16 | DataType::UInt32 => {
+            json!("long")
+        }
+        DataType::Float32 => json!("float"),
+        DataType::Float64 | DataType::UInt64 => json!("double"),
+        DataType::Date32 => json!({"type": "int", "logicalType": "date"}),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            json!({"type": "long", "logicalType": "timestamp-millis"})
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            json!({"type": "long", "logicalType": "timestamp-micros"})
+        }
+        DataType::List(item) | DataType::LargeList(item) => {
+            let items = arrow_type_to_avro(name, item.data_type(), name_seq);
+            let items = if item.is_nullable() && *item.data_type() != DataType::Null {
+                json!(["null", items])
+            } else {
+                items
+            };
+            json!({"type": "array", "items": items})
+        }
+        DataType::Struct(fields) => {
+            *name_seq += 1;
+            arrow_fields_to_avro(&format!("{}_{}", name, name_seq), fields, name_seq)
+        }
+        _ => json!("string"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_schema_maps_required_fields_as_non_nullable() {
+        let schema = SchemaData {
+            tenant: "default".to_string(),
+            name: "sensor".to_string(),
+            schema_type: SchemaType::JSON,
+            desc: String::new(),
+            schema: r#"{"type":"object","properties":{"id":{"type":"integer"},"name":{"type":"string"},"tags":{"type":"array","items":{"type":"string"}}},"required":["id"]}"#.to_string(),
+        };
+        let output = OutputSchema::from_bound(&schema).unwrap().unwrap();
+        let id = output.arrow.field_with_name("id").unwrap();
+        assert_eq!(id.data_type(), &DataType::Int64);
+        assert!(!id.is_nullable());
+        assert!(output.arrow.field_with_name("name").unwrap().is_nullable());
+        assert!(matches!(
+            output.arrow.field_with_name("tags").unwrap().data_type(),
+            DataType::List(_)
+        ));
+    }
+
+    #[test]
+    fn inferred_schema_round_trips_to_avro() {
+        let sample = json!({"id": 1, "temp": 21.5, "meta": {"site": "a"}});
+        let output = OutputSchema::infer(&[&sample]).unwrap();
+        assert_eq!(output.arrow.fields().len(), 3);
+        assert!(matches!(output.avro.as_ref(), AvroSchema::Record(_)));
+    }
+}
@@ -12,16 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use common_base::tools::now_second;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::{
     connector::config_postgres::PostgresConnectorConfig, connector::MQTTConnector,
     storage::record::StorageRecord,
 };
-use rule_engine::apply_rule_engine;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use sqlx::{postgres::PgPoolOptions, PgConnection, Pool, Postgres};
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::{error, warn};
@@ -31,11 +32,13 @@
 use super::{
     core::{BridgePluginReadConfig, BridgePluginThread},
     failure::FailureRecordInfo,
-    loops::run_connector_loop,
+    loops::{next_shard_offsets, run_connector_loop},
     manager::ConnectorManager,
     traits::ConnectorSink,
 };
 
+pub mod source;
+
 pub struct PostgresBridgePlugin {
     connector: MQTTConnector,
     config: PostgresConnectorConfig,
@@ -65,7 +68,7 @@ async fn create_pool(&self) -> Result<Pool<Postgres>, sqlx::Error> {
     async fn single_insert(
         &self,
         records: &[StorageRecord],
-        pool: &Pool<Postgres>,
+        conn: &mut PgConnection,
     ) -> Result<(), CommonError> {
         for record in records {
             let client_id = record
@@ -122,7 +125,7 @@ async fn single_insert(
                 .bind(timestamp)
                 .bind(&payload_str)
                 .bind(record.data.as_ref())
-                .execute(pool)
+                .execute(&mut *conn)
                 .await?;
         }
 
@@ -132,7 +135,7 @@ async fn single_insert(
     async fn batch_insert(
         &self,
         records: &[StorageRecord],
-        pool: &Pool<Postgres>,
+        conn: &mut PgConnection,
     ) -> Result<(), CommonError> {
         let mut value_placeholders = Vec::with_capacity(records.len());
         let mut param_index = 1;
@@ -208,8 +211,58 @@ async fn batch_insert(
                 .bind(data_vec[i].as_ref());
         }
 
-        query.execute(pool).await?;
+        query.execute(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    async fn insert_records(
+        &self,
+        records: &[StorageRecord],
+        conn: &mut PgConnection,
+    ) -> Result<(), CommonError> {
+        if self.config.is_batch_insert_enabled() {
+            if self.config.sql_template.is_some() {
+                warn!(
+                    "sql_template is not applied in batch mode; default batch INSERT will be used"
+                );
+            }
+            self.batch_insert(records, conn).await
+        } else {
+            self.single_insert(records, conn).await
+        }
+    }
 
+    async fn create_offset_table(&self, pool: &Pool<Postgres>) -> Result<(), CommonError> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (connector_name VARCHAR(256) NOT NULL, shard_name VARCHAR(256) NOT NULL, next_offset BIGINT NOT NULL, update_time BIGINT NOT NULL, PRIMARY KEY (connector_name, shard_name))",
+            self.config.offset_table()
+        );
+        sqlx::query(&sql).execute(pool).await?;
+        Ok(())
+    }
+
+    /// Stores the batch position in the same transaction as its rows, so the
+    /// rows and the offset are either both written or both rolled back.
+    async fn store_offsets(
+        &self,
+        records: &[StorageRecord],
+        conn: &mut PgConnection,
+    ) -> Result<(), CommonError> {
+        let offset_table = self.config.offset_table();
+        let sql = format!(
+            "INSERT INTO {0} (connector_name, shard_name, next_offset, update_time) VALUES ($1, $2, $3, $4) ON CONFLICT (connector_name, shard_name) DO UPDATE SET next_offset = GREATEST({0}.next_offset, EXCLUDED.next_offset), update_time = EXCLUDED.update_time",
+            offset_table
+        );
+        for (shard, next_offset) in next_shard_offsets(records) {
+            sqlx::query(&sql)
+                .bind(&self.connector.connector_name)
+                .bind(&shard)
+                .bind(next_offset as i64)
+                .bind(now_second() as i64)
+                .execute(&mut *conn)
+                .await?;
+        }
         Ok(())
     }
 }
@@ -224,6 +277,9 @@ async fn validate(&self) -> Result<(), CommonError> {
 
     async fn init_sink(&self) -> Result<Self::SinkResource, CommonError> {
         let pool = self.create_pool().await?;
+        if self.config.is_exactly_once_enabled() {
+            self.create_offset_table(&pool).await?;
+        }
         Ok(pool)
     }
 
@@ -236,44 +292,39 @@ async fn send_batch(
             return Ok(vec![]);
         }
 
-        let mut processed_records = Vec::with_capacity(records.len());
-        let mut fail_messages = Vec::new();
-        for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-            let mut processed_record = record.clone();
-            processed_record.data = processed_data;
-            processed_records.push(processed_record);
+        if self.config.is_exactly_once_enabled() {
+            let mut tx = pool.begin().await?;
+            self.insert_records(records, &mut tx).await?;
+            self.store_offsets(records, &mut tx).await?;
+            tx.commit().await?;
+        } else {
+            let mut conn = pool.acquire().await?;
+            self.insert_records(records, &mut conn).await?;
         }
+        Ok(vec![])
+    }
 
-        if processed_records.is_empty() {
-            return Ok(fail_messages);
+    async fn committed_offsets(
+        &self,
+        pool: &mut Pool<Postgres>,
+    ) -> Result<Option<HashMap<String, u64>>, CommonError> {
+        if !self.config.is_exactly_once_enabled() {
+            return Ok(None);
         }
 
-        if self.config.is_batch_insert_enabled() {
-            if self.config.sql_template.is_some() {
-                warn!(
-                    "sql_template is not applied in batch mode; default batch INSERT will be used"
-                );
-            }
-            self.batch_insert(&processed_records, pool).await?;
-        } else {
-            self.single_insert(&processed_records, pool).await?;
-        }
-        Ok(fail_messages)
+        let sql = format!(
+            "SELECT shard_name, next_offset FROM {} WHERE connector_name = $1",
+            self.config.offset_table()
+        );
+        let rows: Vec<(String, i64)> = sqlx::query_as(&sql)
+            .bind(&self.connector.connector_name)
+            .fetch_all(&*pool)
+            .await?;
+        Ok(Some(
+            rows.into_iter()
+                .map(|(shard, offset)| (shard, offset as u64))
+                .collect(),
+        ))
     }
 
     async fn cleanup_sink(&self, pool: Pool<Postgres>) -> Result<(), CommonError> {
@@ -320,6 +371,7 @@ pub fn start_postgres_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
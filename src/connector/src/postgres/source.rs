@@ -0,0 +1,352 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::{BridgePluginReadConfig, BridgePluginThread};
+use crate::loops::run_source_connector_loop;
+use crate::manager::ConnectorManager;
+use crate::traits::{ConnectorSource, SourceRecord};
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::connector::{
+    config_postgres_cdc::PostgresCDCConnectorConfig, source::SourceOffset, ConnectorType,
+    MQTTConnector,
+};
+use serde_json::json;
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use std::sync::Arc;
+use std::time::Duration;
+use storage_adapter::driver::StorageDriverManager;
+use tokio::sync::mpsc::Receiver;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+pub struct PostgresCDCResource {
+    pool: Pool<Postgres>,
+    committed_lsn: u64,
+}
+
+/// Reads changes from a logical replication slot through the SQL decoding
+/// functions. Changes are only peeked; the slot is advanced once the batch is
+/// in RobustMQ, so unacknowledged changes are decoded again after a restart.
+pub struct PostgresCDCSource {
+    connector: MQTTConnector,
+    config: PostgresCDCConnectorConfig,
+}
+
+impl PostgresCDCSource {
+    #[allow(clippy::result_large_err)]
+    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+        let config = match &connector.connector_type {
+            ConnectorType::PostgresCDC(config) => config.clone(),
+            _ => {
+                return Err(CommonError::CommonError(
+                    "invalid connector type for postgres cdc source".to_string(),
+                ));
+            }
+        };
+        Ok(PostgresCDCSource { connector, config })
+    }
+
+    async fn ensure_slot(&self, pool: &Pool<Postgres>) -> Result<(), CommonError> {
+        let exists: Option<i32> =
+            sqlx::query_scalar("SELECT 1 FROM pg_replication_slots WHERE slot_name = $1::name")
+                .bind(&self.config.slot_name)
+                .fetch_optional(pool)
+                .await?;
+        if exists.is_some() {
+            return Ok(());
+        }
+
+        sqlx::query("SELECT * FROM pg_create_logical_replication_slot($1::name, $2::name)")
+            .bind(&self.config.slot_name)
+            .bind(&self.config.plugin)
+            .execute(pool)
+            .await?;
+        info!(
+            "Created logical replication slot '{}' with plugin '{}'",
+            self.config.slot_name, self.config.plugin
+        );
+        Ok(())
+    }
+
+    async fn advance_slot(&self, pool: &Pool<Postgres>, lsn: &str) -> Result<(), CommonError> {
+        sqlx::query("SELECT * FROM pg_replication_slot_advance($1::name, $2::pg_lsn)")
+            .bind(&self.config.slot_name)
+            .bind(lsn)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Position the slot was last advanced to, zero when nothing is stored.
+    fn committed_lsn(&self, offset: &SourceOffset) -> u64 {
+        offset
+            .get(&self.config.slot_name)
+            .and_then(parse_lsn)
+            .unwrap_or_default()
+    }
+
+    /// Changes up to the stored offset may still be in the slot if the
+    /// previous advance did not complete; they are dropped here.
+    fn to_source_records(
+        &self,
+        rows: Vec<(String, String, String)>,
+        committed_lsn: u64,
+    ) -> Vec<SourceRecord> {
+        rows.into_iter()
+            .filter(|(lsn, _, _)| parse_lsn(lsn).is_some_and(|l| l > committed_lsn))
+            .map(|(lsn, xid, data)| {
+                let payload = json!({ "lsn": lsn, "xid": xid, "data": data });
+                SourceRecord {
+                    partition: self.config.slot_name.clone(),
+                    offset: lsn,
+                    record: AdapterWriteRecord::new(
+                        self.connector.topic_name.as_str(),
+                        payload.to_string(),
+                    )
+                    .with_key(xid),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses a textual LSN such as `16/B374D848` into its 64-bit position.
+fn parse_lsn(lsn: &str) -> Option<u64> {
+    let (high, low) = lsn.split_once('/')?;
+    let high = u64::from_str_radix(high, 16).ok()?;
+    let low = u64::from_str_radix(low, 16).ok()?;
+    Some((high << 32) | low)
+}
+
+fn format_lsn(lsn: u64) -> String {
+    format!("{:X}/{:X}", lsn >> 32, lsn & 0xFFFF_FFFF)
+}
+
+#[async_trait]
+impl ConnectorSource for PostgresCDCSource {
+    type SourceResource = PostgresCDCResource;
+
+    async fn validate(&self) -> Result<(), CommonError> {
+        self.config.validate()
+    }
+
+    async fn init_source(&self, offset: &SourceOffset) -> Result<PostgresCDCResource, CommonError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .acquire_timeout(Duration::from_secs(self.config.connect_timeout_secs))
+            .connect(&self.config.connection_url())
+            .await?;
+        self.ensure_slot(&pool).await?;
+
+        let committed_lsn = self.committed_lsn(offset);
+        info!(
+            "Postgres CDC source initialized: {}:{}/{} slot={}",
+            self.config.host, self.config.port, self.config.database, self.config.slot_name
+        );
+        Ok(PostgresCDCResource {
+            pool,
+            committed_lsn,
+        })
+    }
+
+    async fn read_batch(
+        &self,
+        resource: &mut PostgresCDCResource,
+    ) -> Result<Vec<SourceRecord>, CommonError> {
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT lsn::text, xid::text, data FROM pg_logical_slot_peek_changes($1::name, NULL, $2)",
+        )
+        .bind(&self.config.slot_name)
+        .bind(self.config.batch_size as i32)
+        .fetch_all(&resource.pool)
+        .await?;
+
+        let peeked = rows.len();
+        let records = self.to_source_records(rows, resource.committed_lsn);
+
+        if records.is_empty() {
+            if peeked > 0 {
+                // Everything peeked was already delivered; move the slot on
+                // or the same changes are peeked again on every read.
+                self.advance_slot(&resource.pool, &format_lsn(resource.committed_lsn))
+                    .await?;
+            }
+            sleep(Duration::from_millis(self.config.poll_interval_ms)).await;
+        }
+        Ok(records)
+    }
+
+    async fn commit_batch(
+        &self,
+        resource: &mut PostgresCDCResource,
+        offset: &SourceOffset,
+    ) -> Result<(), CommonError> {
+        let Some(lsn) = offset.get(&self.config.slot_name) else {
+            return Ok(());
+        };
+        self.advance_slot(&resource.pool, lsn).await?;
+        if let Some(lsn) = parse_lsn(lsn) {
+            resource.committed_lsn = lsn;
+        }
+        Ok(())
+    }
+
+    async fn cleanup_source(&self, resource: PostgresCDCResource) -> Result<(), CommonError> {
+        resource.pool.close().await;
+        Ok(())
+    }
+}
+
+pub fn start_postgres_cdc_connector(
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    storage_driver_manager: Arc<StorageDriverManager>,
+    connector: MQTTConnector,
+    thread: BridgePluginThread,
+    stop_recv: Receiver<bool>,
+) {
+    tokio::spawn(Box::pin(async move {
+        let connector_name = connector.connector_name.clone();
+        let connector_type = connector.connector_type.to_string();
+        let source = match PostgresCDCSource::new(connector.clone()) {
+            Ok(source) => source,
+            Err(e) => {
+                error!(
+                    "Invalid connector config type for PostgresCDC connector, connector_name='{}', connector_type='{}', error={}",
+                    connector_name, connector_type, e
+                );
+                return;
+            }
+        };
+
+        connector_manager.add_connector_thread(
+            &connector.tenant,
+            &connector.connector_name,
+            thread,
+        );
+
+        if let Err(e) = run_source_connector_loop(
+            &source,
+            &client_pool,
+            &connector_manager,
+            &storage_driver_manager,
+            connector.connector_name.clone(),
+            BridgePluginReadConfig {
+                tenant: connector.tenant,
+                topic_name: connector.topic_name,
+                record_num: source.config.batch_size as u64,
+                strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
+            },
+            stop_recv,
+        )
+        .await
+        {
+            connector_manager.remove_connector_thread(&connector.connector_name);
+            error!(
+                "Failed to start PostgresCDCSource, connector_name='{}', connector_type='{}', error={:?}",
+                connector_name, connector_type, e
+            );
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::tenant::DEFAULT_TENANT;
+
+    const SLOT: &str = "robustmq_slot";
+
+    fn source() -> PostgresCDCSource {
+        PostgresCDCSource::new(MQTTConnector {
+            connector_name: "test-postgres-cdc".to_string(),
+            connector_type: ConnectorType::PostgresCDC(PostgresCDCConnectorConfig {
+                slot_name: SLOT.to_string(),
+                ..Default::default()
+            }),
+            tenant: DEFAULT_TENANT.to_string(),
+            topic_name: "t1".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn row(lsn: &str, xid: &str, data: &str) -> (String, String, String) {
+        (lsn.to_string(), xid.to_string(), data.to_string())
+    }
+
+    #[test]
+    fn parse_lsn_test() {
+        assert_eq!(parse_lsn("0/0"), Some(0));
+        assert_eq!(parse_lsn("0/16B3748"), Some(0x16B3748));
+        assert_eq!(parse_lsn("16/B374D848"), Some((0x16 << 32) | 0xB374D848));
+        assert!(parse_lsn("1/2") < parse_lsn("2/0"));
+        assert_eq!(parse_lsn("invalid"), None);
+    }
+
+    #[test]
+    fn format_lsn_test() {
+        assert_eq!(format_lsn(0), "0/0");
+        assert_eq!(format_lsn(0x16B3748), "0/16B3748");
+        assert_eq!(format_lsn((0x16 << 32) | 0xB374D848), "16/B374D848");
+        assert_eq!(parse_lsn(&format_lsn(0x1_0000_0001)), Some(0x1_0000_0001));
+    }
+
+    #[test]
+    fn resume_skips_changes_up_to_stored_lsn() {
+        let source = source();
+        assert_eq!(source.committed_lsn(&SourceOffset::default()), 0);
+
+        let mut offset = SourceOffset::default();
+        offset.set(SLOT, "0/20");
+        let committed_lsn = source.committed_lsn(&offset);
+        assert_eq!(committed_lsn, 0x20);
+
+        let records = source.to_source_records(
+            vec![
+                row("0/10", "700", "BEGIN 700"),
+                row("0/20", "700", "COMMIT 700"),
+                row("0/30", "701", "BEGIN 701"),
+            ],
+            committed_lsn,
+        );
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].partition, SLOT);
+        assert_eq!(records[0].offset, "0/30");
+        assert_eq!(records[0].record.key.as_deref(), Some(&b"701"[..]));
+    }
+
+    #[test]
+    fn stored_offset_of_a_batch_is_its_last_lsn() {
+        let source = source();
+        let records = source.to_source_records(
+            vec![
+                row("0/10", "700", "BEGIN 700"),
+                row("1/0", "700", "COMMIT 700"),
+            ],
+            0,
+        );
+
+        let mut offset = SourceOffset::default();
+        for record in records {
+            offset.set(record.partition, record.offset);
+        }
+        let offset = SourceOffset::decode(&offset.encode().unwrap()).unwrap();
+        assert_eq!(source.committed_lsn(&offset), 1 << 32);
+    }
+}
@@ -29,7 +29,6 @@
     connector::config_pulsar::PulsarConnectorConfig, connector::MQTTConnector,
     storage::record::StorageRecord,
 };
-use rule_engine::apply_rule_engine;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::error;
@@ -75,28 +74,11 @@ async fn send_batch(
         records: &[StorageRecord],
         producer: &mut pulsar::producer::Producer<pulsar::TokioExecutor>,
     ) -> Result<Vec<FailureRecordInfo>, CommonError> {
-        let mut fail_messages = Vec::new();
         for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-            let mut write_record = convert_storage_record_to_adapter(record.clone());
-            write_record.data = processed_data;
+            let write_record = convert_storage_record_to_adapter(record.clone());
             producer.send_non_blocking(write_record).await?;
         }
-        Ok(fail_messages)
+        Ok(vec![])
     }
 }
 
@@ -138,6 +120,7 @@ pub fn start_pulsar_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
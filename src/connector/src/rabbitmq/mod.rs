@@ -25,7 +25,6 @@
     connector::config_rabbitmq::RabbitMQConnectorConfig, connector::MQTTConnector,
     storage::record::StorageRecord,
 };
-use rule_engine::apply_rule_engine;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::{debug, error, info, warn};
@@ -40,6 +39,8 @@
     traits::ConnectorSink,
 };
 
+pub mod source;
+
 pub struct RabbitMQBridgePlugin {
     connector: MQTTConnector,
     config: RabbitMQConnectorConfig,
@@ -165,26 +166,7 @@ async fn send_batch(
         let mut confirms = Vec::new();
 
         for (idx, record) in records.iter().enumerate() {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        warn!(
-                            "Failed to apply rule for record {}/{} (key: '{:?}'): {}",
-                            idx + 1,
-                            records.len(),
-                            record.metadata.key,
-                            e
-                        );
-                        failed_records.push((idx, e.to_string()));
-                        continue;
-                    }
-                };
-
-            let mut processed_record = record.clone();
-            processed_record.data = processed_data;
-
-            let data = match serde_json::to_string(&processed_record) {
+            let data = match serde_json::to_string(record) {
                 Ok(d) => d,
                 Err(e) => {
                     warn!(
@@ -364,6 +346,7 @@ pub fn start_rabbitmq_connector(
                 topic_name: connector.topic_name,
                 record_num: batch_size,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
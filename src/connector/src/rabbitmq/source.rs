@@ -0,0 +1,333 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::{BridgePluginReadConfig, BridgePluginThread};
+use crate::loops::run_source_connector_loop;
+use crate::manager::ConnectorManager;
+use crate::traits::{ConnectorSource, SourceRecord};
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use futures::StreamExt;
+use grpc_clients::pool::ClientPool;
+use lapin::message::Delivery;
+use lapin::options::{BasicAckOptions, BasicConsumeOptions, BasicQosOptions};
+use lapin::types::FieldTable;
+use lapin::{Channel, Connection, ConnectionProperties, Consumer};
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::connector::{
+    config_rabbitmq_source::RabbitMQSourceConnectorConfig, source::SourceOffset, ConnectorType,
+    MQTTConnector,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use storage_adapter::driver::StorageDriverManager;
+use tokio::select;
+use tokio::sync::mpsc::Receiver;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+pub struct RabbitMQSourceResource {
+    connection: Connection,
+    channel: Channel,
+    consumer: Consumer,
+}
+
+pub struct RabbitMQSource {
+    connector: MQTTConnector,
+    config: RabbitMQSourceConnectorConfig,
+}
+
+impl RabbitMQSource {
+    #[allow(clippy::result_large_err)]
+    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+        let config = match &connector.connector_type {
+            ConnectorType::RabbitMQSource(config) => config.clone(),
+            _ => {
+                return Err(CommonError::CommonError(
+                    "invalid connector type for rabbitmq source".to_string(),
+                ));
+            }
+        };
+        Ok(RabbitMQSource { connector, config })
+    }
+
+    fn build_connection_uri(&self) -> String {
+        let protocol = if self.config.enable_tls {
+            "amqps"
+        } else {
+            "amqp"
+        };
+        format!(
+            "{}://{}:{}@{}:{}/{}?heartbeat={}&connection_timeout={}",
+            protocol,
+            self.config.username,
+            self.config.password,
+            self.config.server,
+            self.config.port,
+            self.config.virtual_host,
+            self.config.heartbeat_secs,
+            self.config.connection_timeout_secs * 1000
+        )
+    }
+
+    /// The delivery tag is only meaningful on the channel that received it; it
+    /// is carried as the offset so `commit_batch` knows what to acknowledge.
+    fn to_source_record(&self, delivery: Delivery) -> SourceRecord {
+        let mut record = AdapterWriteRecord::new(self.connector.topic_name.as_str(), delivery.data);
+        if !delivery.routing_key.as_str().is_empty() {
+            record = record.with_key(delivery.routing_key.as_str().as_bytes().to_vec());
+        }
+        SourceRecord {
+            partition: self.config.queue.clone(),
+            offset: delivery.delivery_tag.to_string(),
+            record,
+        }
+    }
+
+    /// Delivery tag of the last record in the batch. Acking it with
+    /// `multiple` set covers every earlier delivery on the channel.
+    fn ack_tag(&self, offset: &SourceOffset) -> Option<u64> {
+        offset
+            .get(&self.config.queue)
+            .and_then(|tag| tag.parse::<u64>().ok())
+    }
+}
+
+#[async_trait]
+impl ConnectorSource for RabbitMQSource {
+    type SourceResource = RabbitMQSourceResource;
+
+    async fn validate(&self) -> Result<(), CommonError> {
+        self.config.validate()
+    }
+
+    // Messages are consumed with manual acks, so anything not yet committed is
+    // requeued by the broker when the channel closes; the stored offset is not
+    // needed to resume.
+    async fn init_source(
+        &self,
+        _offset: &SourceOffset,
+    ) -> Result<RabbitMQSourceResource, CommonError> {
+        info!(
+            "Initializing RabbitMQ consumer: {}:{}/{} queue: {} (prefetch: {})",
+            self.config.server,
+            self.config.port,
+            self.config.virtual_host,
+            self.config.queue,
+            self.config.prefetch_count
+        );
+
+        let uri = self.build_connection_uri();
+        let connection = Connection::connect(&uri, ConnectionProperties::default()).await?;
+        let channel = connection.create_channel().await?;
+        channel
+            .basic_qos(self.config.prefetch_count, BasicQosOptions::default())
+            .await?;
+
+        let consumer_tag = format!("robustmq-source:{}", self.connector.connector_name);
+        let consumer = channel
+            .basic_consume(
+                &self.config.queue,
+                &consumer_tag,
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+
+        Ok(RabbitMQSourceResource {
+            connection,
+            channel,
+            consumer,
+        })
+    }
+
+    async fn read_batch(
+        &self,
+        resource: &mut RabbitMQSourceResource,
+    ) -> Result<Vec<SourceRecord>, CommonError> {
+        let deadline = sleep(Duration::from_millis(self.config.batch_wait_ms));
+        tokio::pin!(deadline);
+
+        let mut records = Vec::new();
+        while records.len() < self.config.batch_size {
+            select! {
+                delivery = resource.consumer.next() => {
+                    match delivery {
+                        Some(delivery) => records.push(self.to_source_record(delivery?)),
+                        None if records.is_empty() => {
+                            return Err(CommonError::CommonError(format!(
+                                "RabbitMQ consumer for queue '{}' was closed",
+                                self.config.queue
+                            )));
+                        }
+                        None => break,
+                    }
+                }
+                _ = &mut deadline => break,
+            }
+        }
+        Ok(records)
+    }
+
+    async fn commit_batch(
+        &self,
+        resource: &mut RabbitMQSourceResource,
+        offset: &SourceOffset,
+    ) -> Result<(), CommonError> {
+        let Some(delivery_tag) = self.ack_tag(offset) else {
+            return Ok(());
+        };
+        resource
+            .channel
+            .basic_ack(delivery_tag, BasicAckOptions { multiple: true })
+            .await?;
+        Ok(())
+    }
+
+    async fn cleanup_source(&self, resource: RabbitMQSourceResource) -> Result<(), CommonError> {
+        if let Err(e) = resource.connection.close(200, "connector stopped").await {
+            warn!(
+                "Failed to close RabbitMQ connection {}:{}: {}",
+                self.config.server, self.config.port, e
+            );
+        }
+        Ok(())
+    }
+}
+
+pub fn start_rabbitmq_source_connector(
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    storage_driver_manager: Arc<StorageDriverManager>,
+    connector: MQTTConnector,
+    thread: BridgePluginThread,
+    stop_recv: Receiver<bool>,
+) {
+    tokio::spawn(Box::pin(async move {
+        let connector_name = connector.connector_name.clone();
+        let connector_type = connector.connector_type.to_string();
+        let source = match RabbitMQSource::new(connector.clone()) {
+            Ok(source) => source,
+            Err(e) => {
+                error!(
+                    "Invalid connector config type for RabbitMQSource connector, connector_name='{}', connector_type='{}', error={}",
+                    connector_name, connector_type, e
+                );
+                return;
+            }
+        };
+
+        connector_manager.add_connector_thread(
+            &connector.tenant,
+            &connector.connector_name,
+            thread,
+        );
+
+        if let Err(e) = run_source_connector_loop(
+            &source,
+            &client_pool,
+            &connector_manager,
+            &storage_driver_manager,
+            connector.connector_name.clone(),
+            BridgePluginReadConfig {
+                tenant: connector.tenant,
+                topic_name: connector.topic_name,
+                record_num: source.config.batch_size as u64,
+                strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
+            },
+            stop_recv,
+        )
+        .await
+        {
+            connector_manager.remove_connector_thread(&connector.connector_name);
+            error!(
+                "Failed to start RabbitMQSource, connector_name='{}', connector_type='{}', error={:?}",
+                connector_name, connector_type, e
+            );
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lapin::acker::Acker;
+    use lapin::BasicProperties;
+    use metadata_struct::tenant::DEFAULT_TENANT;
+
+    const QUEUE: &str = "orders";
+
+    fn source() -> RabbitMQSource {
+        RabbitMQSource::new(MQTTConnector {
+            connector_name: "test-rabbitmq-source".to_string(),
+            connector_type: ConnectorType::RabbitMQSource(RabbitMQSourceConnectorConfig {
+                server: "127.0.0.1".to_string(),
+                queue: QUEUE.to_string(),
+                ..Default::default()
+            }),
+            tenant: DEFAULT_TENANT.to_string(),
+            topic_name: "t1".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn delivery(delivery_tag: u64, routing_key: &str, data: &str) -> Delivery {
+        Delivery {
+            delivery_tag,
+            exchange: "".into(),
+            routing_key: routing_key.into(),
+            redelivered: false,
+            properties: BasicProperties::default(),
+            data: data.as_bytes().to_vec(),
+            acker: Acker::default(),
+        }
+    }
+
+    #[test]
+    fn records_carry_delivery_tag_as_offset() {
+        let source = source();
+
+        let record = source.to_source_record(delivery(3, "orders.created", "a"));
+        assert_eq!(record.partition, QUEUE);
+        assert_eq!(record.offset, "3");
+        assert_eq!(record.record.data.as_ref(), b"a");
+        assert_eq!(record.record.key.as_deref(), Some(&b"orders.created"[..]));
+
+        let record = source.to_source_record(delivery(4, "", "b"));
+        assert!(record.record.key.is_none());
+    }
+
+    #[test]
+    fn batch_is_acked_up_to_last_delivery_tag() {
+        let source = source();
+        let mut offset = SourceOffset::default();
+        for tag in [5, 6, 7] {
+            let record = source.to_source_record(delivery(tag, "", "m"));
+            offset.set(record.partition, record.offset);
+        }
+        assert_eq!(source.ack_tag(&offset), Some(7));
+    }
+
+    #[test]
+    fn nothing_is_acked_without_a_delivery_tag() {
+        let source = source();
+        assert_eq!(source.ack_tag(&SourceOffset::default()), None);
+
+        let mut offset = SourceOffset::default();
+        offset.set(QUEUE, "not-a-tag");
+        assert_eq!(source.ack_tag(&offset), None);
+    }
+}
@@ -24,7 +24,6 @@
 };
 use redis::aio::ConnectionManager;
 use redis::{Client, Cmd, RedisError};
-use rule_engine::apply_rule_engine;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
 use tracing::{error, info, warn};
@@ -38,6 +37,9 @@
     manager::ConnectorManager,
     traits::ConnectorSink,
 };
+
+pub mod source;
+
 pub struct RedisBridgePlugin {
     connector: MQTTConnector,
     config: RedisConnectorConfig,
@@ -131,11 +133,7 @@ fn build_redis_client(&self) -> Result<Client, RedisError> {
     }
 
     #[allow(clippy::result_large_err)]
-    fn render_command_template(
-        &self,
-        record: &StorageRecord,
-        processed_data: &bytes::Bytes,
-    ) -> Result<Vec<String>, CommonError> {
+    fn render_command_template(&self, record: &StorageRecord) -> Result<Vec<String>, CommonError> {
         let mut rendered = self.config.command_template.clone();
 
         let key_str = record
@@ -148,10 +146,7 @@ fn render_command_template(
         let mut replacements = HashMap::new();
         replacements.insert("client_id", key_str.clone());
         replacements.insert("topic", record.metadata.shard.clone());
-        replacements.insert(
-            "payload",
-            String::from_utf8_lossy(processed_data).to_string(),
-        );
+        replacements.insert("payload", String::from_utf8_lossy(&record.data).to_string());
         replacements.insert("timestamp", record.metadata.create_t.to_string());
         replacements.insert("key", key_str);
 
@@ -228,25 +223,7 @@ async fn send_batch(
         let mut fail_messages = Vec::new();
 
         for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        error!("Failed to apply rule before Redis send: {}", e);
-                        error_count += 1;
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-
-            let command_parts = match self.render_command_template(record, &processed_data) {
+            let command_parts = match self.render_command_template(record) {
                 Ok(parts) => parts,
                 Err(e) => {
                     error!("Failed to render command template: {}", e);
@@ -371,6 +348,7 @@ pub fn start_redis_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
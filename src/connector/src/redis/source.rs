@@ -0,0 +1,347 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::{BridgePluginReadConfig, BridgePluginThread};
+use crate::loops::run_source_connector_loop;
+use crate::manager::ConnectorManager;
+use crate::traits::{ConnectorSource, SourceRecord};
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::connector::{
+    config_redis_stream::RedisStreamConnectorConfig,
+    source::{SourceOffset, SourceStartPosition},
+    ConnectorType, MQTTConnector,
+};
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use redis::streams::{StreamId, StreamRangeReply, StreamReadOptions, StreamReadReply};
+use redis::{AsyncCommands, Client};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use storage_adapter::driver::StorageDriverManager;
+use tokio::sync::mpsc::Receiver;
+use tracing::{error, info};
+
+pub struct RedisStreamResource {
+    conn: ConnectionManager,
+    last_id: String,
+}
+
+pub struct RedisStreamSource {
+    connector: MQTTConnector,
+    config: RedisStreamConnectorConfig,
+}
+
+impl RedisStreamSource {
+    #[allow(clippy::result_large_err)]
+    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+        let config = match &connector.connector_type {
+            ConnectorType::RedisStream(config) => config.clone(),
+            _ => {
+                return Err(CommonError::CommonError(
+                    "invalid connector type for redis stream source".to_string(),
+                ));
+            }
+        };
+        Ok(RedisStreamSource { connector, config })
+    }
+
+    fn build_connection_url(&self) -> String {
+        let mut url = String::from(if self.config.tls_enabled {
+            "rediss://"
+        } else {
+            "redis://"
+        });
+        match (&self.config.username, &self.config.password) {
+            (Some(username), Some(password)) => {
+                url.push_str(&format!("{}:{}@", username, password));
+            }
+            (None, Some(password)) => {
+                url.push_str(&format!(":{}@", password));
+            }
+            _ => {}
+        }
+        url.push_str(&self.config.server);
+        url.push_str(&format!("/{}", self.config.database));
+        url
+    }
+
+    /// Resolves "latest" to the current last entry ID up front, so entries
+    /// added between two XREAD calls are not skipped the way `$` would.
+    async fn resolve_start_id(
+        &self,
+        conn: &mut ConnectionManager,
+        offset: &SourceOffset,
+    ) -> Result<String, CommonError> {
+        if let Some(id) = self.known_start_id(offset) {
+            return Ok(id);
+        }
+        let reply: StreamRangeReply = conn
+            .xrevrange_count(&self.config.stream_key, "+", "-", 1)
+            .await
+            .map_err(|e| {
+                CommonError::CommonError(format!(
+                    "Failed to read last entry of Redis stream '{}': {}",
+                    self.config.stream_key, e
+                ))
+            })?;
+        Ok(reply
+            .ids
+            .first()
+            .map(|entry| entry.id.clone())
+            .unwrap_or_else(|| "0-0".to_string()))
+    }
+
+    /// The start ID that needs no lookup: the stored entry ID, or the start of
+    /// the stream for "earliest". `None` means the last entry has to be read.
+    fn known_start_id(&self, offset: &SourceOffset) -> Option<String> {
+        if let Some(id) = offset.get(&self.config.stream_key) {
+            return Some(id.to_string());
+        }
+        match self.config.start_position {
+            SourceStartPosition::Earliest => Some("0-0".to_string()),
+            SourceStartPosition::Latest => None,
+        }
+    }
+
+    /// Stream entries are flat field/value maps; they are written as a JSON
+    /// object keyed by field name.
+    fn to_source_record(&self, entry: &StreamId) -> SourceRecord {
+        let mut fields = Map::new();
+        for (field, value) in entry.map.iter() {
+            let value = redis::from_redis_value_ref::<Vec<u8>>(value)
+                .map(|v| String::from_utf8_lossy(&v).into_owned())
+                .unwrap_or_default();
+            fields.insert(field.clone(), Value::String(value));
+        }
+        SourceRecord {
+            partition: self.config.stream_key.clone(),
+            offset: entry.id.clone(),
+            record: AdapterWriteRecord::new(
+                self.connector.topic_name.as_str(),
+                Value::Object(fields).to_string(),
+            )
+            .with_key(entry.id.clone()),
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectorSource for RedisStreamSource {
+    type SourceResource = RedisStreamResource;
+
+    async fn validate(&self) -> Result<(), CommonError> {
+        self.config.validate()
+    }
+
+    async fn init_source(&self, offset: &SourceOffset) -> Result<RedisStreamResource, CommonError> {
+        let client = Client::open(self.build_connection_url()).map_err(|e| {
+            CommonError::CommonError(format!("Failed to build Redis client: {}", e))
+        })?;
+
+        // XREAD BLOCK holds the response for up to block_ms, so the response
+        // timeout has to outlast it.
+        let manager_config = ConnectionManagerConfig::new()
+            .set_connection_timeout(Some(Duration::from_millis(self.config.connect_timeout_ms)))
+            .set_response_timeout(Some(Duration::from_millis(
+                self.config.block_ms + self.config.connect_timeout_ms,
+            )));
+        let mut conn = ConnectionManager::new_with_config(client, manager_config)
+            .await
+            .map_err(|e| {
+                CommonError::CommonError(format!(
+                    "Failed to create Redis connection manager: {}",
+                    e
+                ))
+            })?;
+
+        let last_id = self.resolve_start_id(&mut conn, offset).await?;
+        info!(
+            "Redis stream source initialized: server={}, stream={}, start_id={}",
+            self.config.server, self.config.stream_key, last_id
+        );
+        Ok(RedisStreamResource { conn, last_id })
+    }
+
+    async fn read_batch(
+        &self,
+        resource: &mut RedisStreamResource,
+    ) -> Result<Vec<SourceRecord>, CommonError> {
+        let options = StreamReadOptions::default()
+            .count(self.config.batch_size)
+            .block(self.config.block_ms as usize);
+        let reply: Option<StreamReadReply> = resource
+            .conn
+            .xread_options(&[&self.config.stream_key], &[&resource.last_id], &options)
+            .await
+            .map_err(|e| {
+                CommonError::CommonError(format!(
+                    "Failed to read Redis stream '{}': {}",
+                    self.config.stream_key, e
+                ))
+            })?;
+
+        let mut records = Vec::new();
+        for stream in reply.map(|r| r.keys).unwrap_or_default() {
+            for entry in stream.ids.iter() {
+                records.push(self.to_source_record(entry));
+            }
+        }
+        if let Some(last) = records.last() {
+            resource.last_id = last.offset.clone();
+        }
+        Ok(records)
+    }
+}
+
+pub fn start_redis_stream_connector(
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    storage_driver_manager: Arc<StorageDriverManager>,
+    connector: MQTTConnector,
+    thread: BridgePluginThread,
+    stop_recv: Receiver<bool>,
+) {
+    tokio::spawn(Box::pin(async move {
+        let connector_name = connector.connector_name.clone();
+        let connector_type = connector.connector_type.to_string();
+        let source = match RedisStreamSource::new(connector.clone()) {
+            Ok(source) => source,
+            Err(e) => {
+                error!(
+                    "Invalid connector config type for RedisStream connector, connector_name='{}', connector_type='{}', error={}",
+                    connector_name, connector_type, e
+                );
+                return;
+            }
+        };
+
+        connector_manager.add_connector_thread(
+            &connector.tenant,
+            &connector.connector_name,
+            thread,
+        );
+
+        if let Err(e) = run_source_connector_loop(
+            &source,
+            &client_pool,
+            &connector_manager,
+            &storage_driver_manager,
+            connector.connector_name.clone(),
+            BridgePluginReadConfig {
+                tenant: connector.tenant,
+                topic_name: connector.topic_name,
+                record_num: source.config.batch_size as u64,
+                strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
+            },
+            stop_recv,
+        )
+        .await
+        {
+            connector_manager.remove_connector_thread(&connector.connector_name);
+            error!(
+                "Failed to start RedisStreamSource, connector_name='{}', connector_type='{}', error={:?}",
+                connector_name, connector_type, e
+            );
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::tenant::DEFAULT_TENANT;
+    use std::collections::HashMap;
+
+    const STREAM_KEY: &str = "events";
+
+    fn source(start_position: SourceStartPosition) -> RedisStreamSource {
+        RedisStreamSource::new(MQTTConnector {
+            connector_name: "test-redis-stream".to_string(),
+            connector_type: ConnectorType::RedisStream(RedisStreamConnectorConfig {
+                server: "127.0.0.1:6379".to_string(),
+                stream_key: STREAM_KEY.to_string(),
+                start_position,
+                ..Default::default()
+            }),
+            tenant: DEFAULT_TENANT.to_string(),
+            topic_name: "t1".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn entry(id: &str, fields: &[(&str, &str)]) -> StreamId {
+        StreamId {
+            id: id.to_string(),
+            map: fields
+                .iter()
+                .map(|(field, value)| {
+                    (
+                        field.to_string(),
+                        redis::Value::BulkString(value.as_bytes().to_vec()),
+                    )
+                })
+                .collect::<HashMap<_, _>>(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stored_entry_id_is_resumed_from() {
+        let mut offset = SourceOffset::default();
+        offset.set(STREAM_KEY, "1700000000000-3");
+        for start_position in [SourceStartPosition::Earliest, SourceStartPosition::Latest] {
+            assert_eq!(
+                source(start_position).known_start_id(&offset).as_deref(),
+                Some("1700000000000-3")
+            );
+        }
+    }
+
+    #[test]
+    fn start_position_applies_without_stored_entry_id() {
+        let offset = SourceOffset::default();
+        assert_eq!(
+            source(SourceStartPosition::Earliest)
+                .known_start_id(&offset)
+                .as_deref(),
+            Some("0-0")
+        );
+        assert_eq!(
+            source(SourceStartPosition::Latest).known_start_id(&offset),
+            None
+        );
+    }
+
+    #[test]
+    fn entry_becomes_json_record_with_its_id_as_offset() {
+        let source = source(SourceStartPosition::Earliest);
+        let record = source.to_source_record(&entry("1-1", &[("id", "7"), ("name", "a")]));
+
+        assert_eq!(record.partition, STREAM_KEY);
+        assert_eq!(record.offset, "1-1");
+        assert_eq!(record.record.key.as_deref(), Some(&b"1-1"[..]));
+        let data: Value = serde_json::from_slice(&record.record.data).unwrap();
+        assert_eq!(data, serde_json::json!({"id": "7", "name": "a"}));
+
+        // The stored offset of a batch resumes after its last entry.
+        let mut offset = SourceOffset::default();
+        offset.set(record.partition, record.offset);
+        assert_eq!(source.known_start_id(&offset).as_deref(), Some("1-1"));
+    }
+}
@@ -21,18 +21,20 @@
     storage::record::StorageRecord,
 };
 use opendal::{services::S3, Operator};
-use rule_engine::apply_rule_engine;
+use schema_register::schema::SchemaRegisterManager;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
 use tokio::sync::mpsc::Receiver;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use super::{
     core::{BridgePluginReadConfig, BridgePluginThread},
     failure::FailureRecordInfo,
     loops::run_connector_loop,
     manager::ConnectorManager,
+    output::{RollingOutput, RollingOutputConfig},
     traits::ConnectorSink,
 };
 
@@ -46,14 +48,24 @@ struct S3MessageRecord {
     timestamp: u64,
 }
 
+pub struct S3Sink {
+    operator: Operator,
+    // Set for the rolling formats.
+    output: Option<RollingOutput>,
+}
+
 pub struct S3BridgePlugin {
     connector: MQTTConnector,
     config: S3ConnectorConfig,
+    schema_manager: Arc<SchemaRegisterManager>,
 }
 
 impl S3BridgePlugin {
     #[allow(clippy::result_large_err)]
-    pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
+    pub fn new(
+        connector: MQTTConnector,
+        schema_manager: Arc<SchemaRegisterManager>,
+    ) -> Result<Self, CommonError> {
         let config = match &connector.connector_type {
             metadata_struct::connector::ConnectorType::S3(config) => config.clone(),
             _ => {
@@ -62,7 +74,11 @@ pub fn new(connector: MQTTConnector) -> Result<Self, CommonError> {
                 ));
             }
         };
-        Ok(S3BridgePlugin { connector, config })
+        Ok(S3BridgePlugin {
+            connector,
+            config,
+            schema_manager,
+        })
     }
 
     fn normalize_prefix(prefix: &str) -> &str {
@@ -75,6 +91,40 @@ fn build_object_key(&self) -> String {
         format!("{}/{}-{}.{}", prefix, now_millis(), unique_id(), extension)
     }
 
+    fn build_rolled_object_key(&self, partition: &str, extension: &str) -> String {
+        let prefix = Self::normalize_prefix(&self.config.object_key_prefix);
+        if partition.is_empty() {
+            format!("{}/{}-{}.{}", prefix, now_millis(), unique_id(), extension)
+        } else {
+            format!(
+                "{}/{}/{}-{}.{}",
+                prefix,
+                partition,
+                now_millis(),
+                unique_id(),
+                extension
+            )
+        }
+    }
+
+    /// Uploads rolled files in order, stopping at the first failure so the
+    /// file is retried on the next call.
+    async fn upload_ready(&self, sink: &mut S3Sink) -> Result<(), CommonError> {
+        let Some(output) = sink.output.as_mut() else {
+            return Ok(());
+        };
+        while let Some(file) = output.next_ready() {
+            let object_key = self.build_rolled_object_key(&file.partition, file.extension);
+            sink.operator.write(&object_key, file.data.clone()).await?;
+            debug!(
+                "S3 connector uploaded {} records to {}",
+                file.record_count, object_key
+            );
+            output.complete_ready();
+        }
+        Ok(())
+    }
+
     #[allow(clippy::result_large_err)]
     fn build_operator(&self) -> Result<Operator, CommonError> {
         let mut builder = S3::default()
@@ -104,50 +154,74 @@ fn build_operator(&self) -> Result<Operator, CommonError> {
 
 #[async_trait]
 impl ConnectorSink for S3BridgePlugin {
-    type SinkResource = Operator;
+    type SinkResource = S3Sink;
 
     async fn validate(&self) -> Result<(), CommonError> {
         self.config.validate()
     }
 
     async fn init_sink(&self) -> Result<Self::SinkResource, CommonError> {
-        let op = self.build_operator()?;
+        let operator = self.build_operator()?;
+        let output = self.config.format.is_rolling().then(|| {
+            RollingOutput::new(
+                RollingOutputConfig {
+                    tenant: self.connector.tenant.clone(),
+                    topic_name: self.connector.topic_name.clone(),
+                    format: self.config.format.clone(),
+                    compression: self.config.compression.clone(),
+                    partition_by: self.config.partition_by.clone(),
+                    roll_size_mb: self.config.roll_size_mb,
+                    roll_interval_secs: self.config.roll_interval_secs,
+                },
+                self.schema_manager.clone(),
+            )
+        });
         debug!(
-            "S3 connector initialized: bucket={}, region={}, root={}",
-            self.config.bucket, self.config.region, self.config.root
+            "S3 connector initialized: bucket={}, region={}, root={}, format={}",
+            self.config.bucket, self.config.region, self.config.root, self.config.format
         );
-        Ok(op)
+        Ok(S3Sink { operator, output })
     }
 
     async fn send_batch(
         &self,
         records: &[StorageRecord],
-        operator: &mut Operator,
+        sink: &mut S3Sink,
     ) -> Result<Vec<FailureRecordInfo>, CommonError> {
         if records.is_empty() {
             return Ok(vec![]);
         }
 
+        if sink.output.is_some() {
+            // Leftover uploads go first: an error here is retried with the
+            // same batch, which has not been buffered yet.
+            self.upload_ready(sink).await?;
+
+            let output = sink.output.as_mut().expect("rolling output is set");
+            let fail_messages = output
+                .append(records)
+                .into_iter()
+                .map(|(record, reason)| FailureRecordInfo {
+                    tenant: self.connector.tenant.clone(),
+                    connector_name: self.connector.connector_name.clone(),
+                    connector_type: self.connector.connector_type.to_string(),
+                    source_topic: self.connector.topic_name.clone(),
+                    error_message: reason,
+                    records: vec![record],
+                })
+                .collect();
+            output.roll(false)?;
+            if let Err(e) = self.upload_ready(sink).await {
+                warn!(
+                    "S3 connector '{}' failed to upload rolled file, will retry: {}",
+                    self.connector.connector_name, e
+                );
+            }
+            return Ok(fail_messages);
+        }
+
         let mut payload: Vec<S3MessageRecord> = Vec::with_capacity(records.len());
-        let mut fail_messages = Vec::new();
         for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        tracing::error!("Failed to apply rule before S3 send: {}", e);
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-
             let headers = record.metadata.header.as_ref().map(|hs| {
                 hs.iter()
                     .map(|h| RecordHeader {
@@ -165,22 +239,36 @@ async fn send_batch(
                     .map(|k| String::from_utf8_lossy(k).into_owned()),
                 headers,
                 tags: record.metadata.tags.clone(),
-                data: processed_data.to_vec(),
+                data: record.data.to_vec(),
                 timestamp: record.metadata.create_t,
             });
         }
 
-        if payload.is_empty() {
-            return Ok(fail_messages);
-        }
-
         let object_key = self.build_object_key();
         let bytes = serde_json::to_vec(&payload).map_err(|e| {
             CommonError::CommonError(format!("Failed to serialize S3 payload to JSON: {}", e))
         })?;
-        operator.write(&object_key, bytes).await?;
+        sink.operator.write(&object_key, bytes).await?;
+
+        Ok(vec![])
+    }
+
+    fn buffers_records(&self) -> bool {
+        self.config.format.is_rolling()
+    }
+
+    async fn flush_buffered(&self, sink: &mut S3Sink, force: bool) -> Result<(), CommonError> {
+        if let Some(output) = sink.output.as_mut() {
+            output.roll(force)?;
+        }
+        self.upload_ready(sink).await
+    }
 
-        Ok(fail_messages)
+    fn take_flushed_offsets(&self, sink: &mut S3Sink) -> HashMap<String, u64> {
+        sink.output
+            .as_mut()
+            .map(|output| output.take_committable_offsets())
+            .unwrap_or_default()
     }
 }
 
@@ -188,6 +276,7 @@ pub fn start_s3_connector(
     client_pool: Arc<ClientPool>,
     connector_manager: Arc<ConnectorManager>,
     storage_driver_manager: Arc<StorageDriverManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
     connector: MQTTConnector,
     thread: BridgePluginThread,
     stop_recv: Receiver<bool>,
@@ -195,7 +284,7 @@ pub fn start_s3_connector(
     tokio::spawn(Box::pin(async move {
         let connector_name = connector.connector_name.clone();
         let connector_type = connector.connector_type.to_string();
-        let bridge = match S3BridgePlugin::new(connector.clone()) {
+        let bridge = match S3BridgePlugin::new(connector.clone(), schema_manager) {
             Ok(bridge) => bridge,
             Err(e) => {
                 error!(
@@ -222,6 +311,7 @@ pub fn start_s3_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -14,3 +14,4 @@
 
 pub mod connector;
 pub mod message;
+pub mod source_offset;
@@ -0,0 +1,68 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use common_base::tools::now_second;
+use common_config::broker::broker_config;
+use grpc_clients::{
+    meta::common::call::{kv_get, kv_set},
+    pool::ClientPool,
+};
+use metadata_struct::connector::source::{source_offset_key, SourceOffset};
+use protocol::meta::meta_service_common::{GetRequest, SetRequest};
+
+pub struct SourceOffsetStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl SourceOffsetStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        SourceOffsetStorage { client_pool }
+    }
+
+    pub async fn get(
+        &self,
+        tenant: &str,
+        connector_name: &str,
+    ) -> Result<SourceOffset, CommonError> {
+        let config = broker_config();
+        let request = GetRequest {
+            key: source_offset_key(tenant, connector_name),
+        };
+        let reply = kv_get(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        if reply.value.is_empty() {
+            return Ok(SourceOffset::default());
+        }
+        SourceOffset::decode(&reply.value)
+    }
+
+    pub async fn save(
+        &self,
+        tenant: &str,
+        connector_name: &str,
+        offset: &SourceOffset,
+    ) -> Result<(), CommonError> {
+        let config = broker_config();
+        let mut offset = offset.clone();
+        offset.update_time = now_second();
+        let request = SetRequest {
+            key: source_offset_key(tenant, connector_name),
+            value: offset.encode()?,
+        };
+        kv_set(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+}
@@ -14,7 +14,10 @@
 
 use async_trait::async_trait;
 use common_base::error::common::CommonError;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::connector::source::SourceOffset;
 use metadata_struct::storage::record::StorageRecord;
+use std::collections::HashMap;
 
 use crate::failure::FailureRecordInfo;
 
@@ -32,7 +35,80 @@ async fn send_batch(
         resource: &mut Self::SinkResource,
     ) -> Result<Vec<FailureRecordInfo>, CommonError>;
 
+    /// Next offset to read per shard, as stored by the sink alongside the data.
+    /// Sinks running in exactly-once mode return it so a restart resumes from
+    /// what was actually written instead of the consumer group offset.
+    async fn committed_offsets(
+        &self,
+        _resource: &mut Self::SinkResource,
+    ) -> Result<Option<HashMap<String, u64>>, CommonError> {
+        Ok(None)
+    }
+
+    /// Whether `send_batch` may keep records in memory after returning, e.g. to
+    /// roll them into larger files. The loop then only commits the offsets
+    /// reported by `take_flushed_offsets`.
+    fn buffers_records(&self) -> bool {
+        false
+    }
+
+    /// Writes out buffered records whose roll condition is met, or all of them
+    /// when `force` is set. Called while the topic is idle and before cleanup.
+    async fn flush_buffered(
+        &self,
+        _resource: &mut Self::SinkResource,
+        _force: bool,
+    ) -> Result<(), CommonError> {
+        Ok(())
+    }
+
+    /// Next offset to read per shard covering everything written out since the
+    /// last call.
+    fn take_flushed_offsets(&self, _resource: &mut Self::SinkResource) -> HashMap<String, u64> {
+        HashMap::new()
+    }
+
     async fn cleanup_sink(&self, _resource: Self::SinkResource) -> Result<(), CommonError> {
         Ok(())
     }
 }
+
+/// A record read from an external system together with the upstream position
+/// it was read at.
+pub struct SourceRecord {
+    pub partition: String,
+    pub offset: String,
+    pub record: AdapterWriteRecord,
+}
+
+#[async_trait]
+pub trait ConnectorSource: Send + Sync {
+    type SourceResource: Send;
+
+    async fn validate(&self) -> Result<(), CommonError>;
+
+    /// Connects to the external system and positions the reader right after
+    /// the stored offset, or at the configured start position if there is none.
+    async fn init_source(&self, offset: &SourceOffset)
+        -> Result<Self::SourceResource, CommonError>;
+
+    /// Returns the next batch, or an empty one after a bounded wait.
+    async fn read_batch(
+        &self,
+        resource: &mut Self::SourceResource,
+    ) -> Result<Vec<SourceRecord>, CommonError>;
+
+    /// Called once a batch is written to the topic and its offset is stored,
+    /// so the source can acknowledge it upstream.
+    async fn commit_batch(
+        &self,
+        _resource: &mut Self::SourceResource,
+        _offset: &SourceOffset,
+    ) -> Result<(), CommonError> {
+        Ok(())
+    }
+
+    async fn cleanup_source(&self, _resource: Self::SourceResource) -> Result<(), CommonError> {
+        Ok(())
+    }
+}
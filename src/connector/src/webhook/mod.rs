@@ -21,7 +21,6 @@
     storage::record::StorageRecord,
 };
 use reqwest::{Client, RequestBuilder};
-use rule_engine::apply_rule_engine;
 use serde_json::json;
 use std::sync::Arc;
 use std::time::Duration;
@@ -95,31 +94,10 @@ fn build_request(&self, client: &Client, body: String) -> RequestBuilder {
         req.body(body)
     }
 
-    async fn records_to_json(
-        &self,
-        records: &[StorageRecord],
-        fail_messages: &mut Vec<FailureRecordInfo>,
-    ) -> String {
+    fn records_to_json(&self, records: &[StorageRecord]) -> String {
         let mut items: Vec<serde_json::Value> = Vec::with_capacity(records.len());
         for record in records {
-            let processed_data =
-                match apply_rule_engine(&self.connector.etl_rule, &record.data).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        tracing::error!("Failed to apply rule before Webhook send: {}", e);
-                        fail_messages.push(FailureRecordInfo {
-                            tenant: self.connector.tenant.clone(),
-                            connector_name: self.connector.connector_name.clone(),
-                            connector_type: self.connector.connector_type.to_string(),
-                            source_topic: self.connector.topic_name.clone(),
-                            error_message: e.to_string(),
-                            records: vec![record.clone()],
-                        });
-                        continue;
-                    }
-                };
-
-            let payload = String::from_utf8_lossy(&processed_data).to_string();
+            let payload = String::from_utf8_lossy(&record.data).to_string();
             let mut item = json!({
                 "payload": payload,
                 "timestamp": record.metadata.create_t,
@@ -170,11 +148,7 @@ async fn send_batch(
             return Ok(vec![]);
         }
 
-        let mut fail_messages = Vec::new();
-        let body = self.records_to_json(records, &mut fail_messages).await;
-        if body.is_empty() {
-            return Ok(fail_messages);
-        }
+        let body = self.records_to_json(records);
         let request = self.build_request(client, body);
 
         let response = request
@@ -183,7 +157,7 @@ async fn send_batch(
             .map_err(|e| CommonError::CommonError(format!("Webhook HTTP request failed: {}", e)))?;
 
         if response.status().is_success() {
-            Ok(fail_messages)
+            Ok(vec![])
         } else {
             let status = response.status();
             let error_text = response
@@ -237,6 +211,7 @@ pub fn start_webhook_connector(
                 topic_name: connector.topic_name,
                 record_num: 100,
                 strategy: connector.failure_strategy,
+                etl_rule: connector.etl_rule,
             },
             stop_recv,
         )
@@ -16,3 +16,4 @@
 pub mod manager;
 pub mod pop;
 pub mod recover;
+pub mod router;
@@ -15,6 +15,7 @@
 use crate::delay::{
     delete_delay_index_info, delete_delay_message, save_delay_index_info, save_delay_message,
 };
+use crate::router::DelayMessageRouter;
 use crate::{pop::spawn_delay_message_pop_threads, recover::recover_delay_queue};
 use common_base::task::TaskSupervisor;
 use common_base::uuid::unique_id;
@@ -85,6 +86,8 @@ pub struct DelayMessageManager {
     /// unique_id → (shard_no, queue key).
     message_key_map: DashMap<String, (u32, delay_queue::Key)>,
     pub delay_message_config: DelayMessageConfig,
+    /// Target-name prefix → router delivering the targets under it.
+    routers: DashMap<String, Arc<dyn DelayMessageRouter>>,
 }
 
 impl DelayMessageManager {
@@ -103,6 +106,7 @@ pub async fn new(
             delay_queue_num,
             message_key_map: DashMap::new(),
             delay_message_config,
+            routers: DashMap::new(),
         };
         Ok(driver)
     }
@@ -112,6 +116,20 @@ pub(crate) fn register_shard_cmd_tx(&self, shard_no: u32, tx: ShardCmdTx) {
         self.shard_cmd_tx.insert(shard_no, tx);
     }
 
+    /// Routes due messages whose target starts with `prefix` through `router`
+    /// instead of writing them to a topic. Register before
+    /// `start_delay_message_manager_thread` so recovered messages find it.
+    pub fn register_router(&self, prefix: &str, router: Arc<dyn DelayMessageRouter>) {
+        self.routers.insert(prefix.to_string(), router);
+    }
+
+    pub(crate) fn router_for(&self, target: &str) -> Option<Arc<dyn DelayMessageRouter>> {
+        self.routers
+            .iter()
+            .find(|entry| target.starts_with(entry.key().as_str()))
+            .map(|entry| entry.value().clone())
+    }
+
     pub(crate) fn remove_message_key(&self, unique_id: &str) {
         self.message_key_map.remove(unique_id);
     }
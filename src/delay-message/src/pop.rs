@@ -14,6 +14,7 @@
 
 use crate::delay::{delete_delay_index_info, delete_delay_message};
 use crate::manager::{DelayMessageManager, ShardCmd, DELAY_MESSAGE_SAVE_MS};
+use crate::router::DelayMessageRouter;
 use broker_core::inner_topic::DELAY_QUEUE_MESSAGE_TOPIC;
 use common_base::error::common::CommonError;
 use common_base::task::{TaskKind, TaskSupervisor};
@@ -118,9 +119,11 @@ async fn run_shard_loop(
                 let storage = manager.storage_driver_manager.clone();
                 let retry_manager = manager.clone();
                 let config = manager.delay_message_config.clone();
+                let router = manager.router_for(&delay_message.target_topic_name);
                 tokio::spawn(async move {
                     if let Err(e) = delay_message_process(
                         &storage,
+                        router,
                         &delay_message,
                         now_second(),
                     )
@@ -163,6 +166,7 @@ async fn run_shard_loop(
 
 pub async fn delay_message_process(
     storage_driver_manager: &Arc<StorageDriverManager>,
+    router: Option<Arc<dyn DelayMessageRouter>>,
     delay_info: &DelayMessageIndexInfo,
     trigger_time: u64,
 ) -> Result<(), CommonError> {
@@ -173,7 +177,9 @@ pub async fn delay_message_process(
     // message — the caller re-enqueues it for retry. Deleting on failure here permanently
     // loses the message.
     let offset =
-        match send_delay_message_to_shard(storage_driver_manager, delay_info, trigger_time).await {
+        match send_delay_message_to_shard(storage_driver_manager, router, delay_info, trigger_time)
+            .await
+        {
             Ok(offset) => offset,
             Err(e) => {
                 record_delay_msg_deliver_fail();
@@ -185,7 +191,7 @@ pub async fn delay_message_process(
     record_delay_msg_deliver();
     record_delay_msg_deliver_duration(duration_ms);
     info!(
-        "Delay message processed successfully. unique_id={}, target_topic={}, offset={}, duration_ms={:.2}",
+        "Delay message processed successfully. unique_id={}, target_topic={}, offset={:?}, duration_ms={:.2}",
         delay_info.unique_id, delay_info.target_topic_name, offset, duration_ms
     );
 
@@ -195,11 +201,14 @@ pub async fn delay_message_process(
     Ok(())
 }
 
+/// Delivers the message to its target: written to the target topic (its
+/// offset there is returned), or handed to the target's router.
 async fn send_delay_message_to_shard(
     storage_driver_manager: &Arc<StorageDriverManager>,
+    router: Option<Arc<dyn DelayMessageRouter>>,
     delay_message: &DelayMessageIndexInfo,
     trigger_time: u64,
-) -> Result<u64, CommonError> {
+) -> Result<Option<u64>, CommonError> {
     // read data
     let results = storage_driver_manager
         .read_by_keys(
@@ -235,6 +244,17 @@ async fn send_delay_message_to_shard(
         )));
     };
 
+    if let Some(router) = router {
+        router
+            .deliver(
+                &delay_message.tenant,
+                &delay_message.target_topic_name,
+                &record,
+            )
+            .await?;
+        return Ok(None);
+    }
+
     let send_record = build_new_record(delay_message, &record, trigger_time);
 
     // send to target topic under the original tenant
@@ -263,7 +283,7 @@ async fn send_delay_message_to_shard(
         "Expired delay message sent successfully: delay queue -> {} (offset: {})",
         delay_message.target_topic_name, delay_message.offset
     );
-    Ok(write_resp.offset)
+    Ok(Some(write_resp.offset))
 }
 
 fn build_new_record(
@@ -388,7 +408,7 @@ async fn test_delay_message_process_returns_err_on_send_failure() {
             retry_count: 0,
         };
 
-        let result = delay_message_process(&storage, &delay_info, now_second()).await;
+        let result = delay_message_process(&storage, None, &delay_info, now_second()).await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
@@ -171,8 +171,14 @@ async fn handle_expired_delay_message(
 
     let manager = delay_message_manager.clone();
     tokio::spawn(async move {
-        if let Err(e) =
-            delay_message_process(&manager.storage_driver_manager, &delay_info, now_second()).await
+        let router = manager.router_for(&delay_info.target_topic_name);
+        if let Err(e) = delay_message_process(
+            &manager.storage_driver_manager,
+            router,
+            &delay_info,
+            now_second(),
+        )
+        .await
         {
             error!(
                 "Failed to send expired delay message (offset: {}): {:?}",
@@ -0,0 +1,36 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
+use metadata_struct::storage::record::StorageRecord;
+
+/// Delivers due delay messages whose target is not a storage topic.
+///
+/// By default a due message is written to its `target_topic_name`. A
+/// protocol that needs to do more at delivery time (e.g. route the message
+/// through an AMQP exchange) registers a router for a target-name prefix via
+/// `DelayMessageManager::register_router`, and schedules its messages with a
+/// target under that prefix.
+#[async_trait]
+pub trait DelayMessageRouter: Send + Sync {
+    /// `record` is the message as it was handed to `DelayMessageManager::send`.
+    /// An error leaves the message scheduled for a retry.
+    async fn deliver(
+        &self,
+        tenant: &str,
+        target: &str,
+        record: &StorageRecord,
+    ) -> Result<(), CommonError>;
+}
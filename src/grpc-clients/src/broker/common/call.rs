@@ -14,11 +14,11 @@
 
 use common_base::error::common::CommonError;
 use protocol::broker::broker::{
-    FetchAmqpQueueMessageReply, FetchAmqpQueueMessageRequest, GetQosDataByClientIdReply,
-    GetQosDataByClientIdRequest, GetShardSegmentDeleteStatusReply,
-    GetShardSegmentDeleteStatusRequest, QueryReplicaLeoReply, QueryReplicaLeoRequest,
-    SendLastWillMessageReply, SendLastWillMessageRequest, SendShareGroupMessageReply,
-    SendShareGroupMessageRequest, UpdateCacheReply, UpdateCacheRequest,
+    FetchAmqpQueueMessageReply, FetchAmqpQueueMessageRequest, ForwardKafkaProduceReply,
+    ForwardKafkaProduceRequest, GetQosDataByClientIdReply, GetQosDataByClientIdRequest,
+    GetShardSegmentDeleteStatusReply, GetShardSegmentDeleteStatusRequest, QueryReplicaLeoReply,
+    QueryReplicaLeoRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
+    SendShareGroupMessageReply, SendShareGroupMessageRequest, UpdateCacheReply, UpdateCacheRequest,
 };
 
 use crate::pool::ClientPool;
@@ -70,3 +70,9 @@ pub async fn $fn_name(
     FetchAmqpQueueMessageRequest,
     FetchAmqpQueueMessageReply
 );
+
+generate_broker_call!(
+    broker_forward_kafka_produce,
+    ForwardKafkaProduceRequest,
+    ForwardKafkaProduceReply
+);
@@ -15,10 +15,11 @@
 use crate::macros::impl_retriable_request;
 use protocol::broker::broker::{
     broker_service_client::BrokerServiceClient, FetchAmqpQueueMessageReply,
-    FetchAmqpQueueMessageRequest, GetQosDataByClientIdReply, GetQosDataByClientIdRequest,
-    GetShardSegmentDeleteStatusReply, GetShardSegmentDeleteStatusRequest, QueryReplicaLeoReply,
-    QueryReplicaLeoRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
-    SendShareGroupMessageReply, SendShareGroupMessageRequest, UpdateCacheReply, UpdateCacheRequest,
+    FetchAmqpQueueMessageRequest, ForwardKafkaProduceReply, ForwardKafkaProduceRequest,
+    GetQosDataByClientIdReply, GetQosDataByClientIdRequest, GetShardSegmentDeleteStatusReply,
+    GetShardSegmentDeleteStatusRequest, QueryReplicaLeoReply, QueryReplicaLeoRequest,
+    SendLastWillMessageReply, SendLastWillMessageRequest, SendShareGroupMessageReply,
+    SendShareGroupMessageRequest, UpdateCacheReply, UpdateCacheRequest,
 };
 use tonic::transport::Channel;
 
@@ -86,3 +87,12 @@
     "BrokerService",
     "FetchAmqpQueueMessage"
 );
+
+impl_retriable_request!(
+    ForwardKafkaProduceRequest,
+    BrokerServiceClient<Channel>,
+    ForwardKafkaProduceReply,
+    forward_kafka_produce,
+    "BrokerService",
+    "ForwardKafkaProduce"
+);
@@ -15,24 +15,26 @@
 use common_base::error::common::CommonError;
 use protocol::meta::meta_service_common::{
     AddShareGroupMemberReply, AddShareGroupMemberRequest, AppendReply, AppendRequest,
-    BindSchemaReply, BindSchemaRequest, ClusterStatusReply, ClusterStatusRequest,
-    CreateSchemaReply, CreateSchemaRequest, CreateShareGroupReply, CreateShareGroupRequest,
-    CreateTenantReply, CreateTenantRequest, DeleteOffsetDataReply, DeleteOffsetDataRequest,
-    DeleteReply, DeleteRequest, DeleteResourceConfigReply, DeleteResourceConfigRequest,
-    DeleteSchemaReply, DeleteSchemaRequest, DeleteShareGroupMemberReply,
-    DeleteShareGroupMemberRequest, DeleteShareGroupReply, DeleteShareGroupRequest,
-    DeleteTenantReply, DeleteTenantRequest, ExistsReply, ExistsRequest, GetOffsetDataReply,
-    GetOffsetDataRequest, GetPrefixReply, GetPrefixRequest, GetReply, GetRequest,
-    GetResourceConfigReply, GetResourceConfigRequest, HeartbeatReply, HeartbeatRequest,
+    BindSchemaReply, BindSchemaRequest, ClusterStatusReply, ClusterStatusRequest, CreateRuleReply,
+    CreateRuleRequest, CreateSchemaReply, CreateSchemaRequest, CreateShareGroupReply,
+    CreateShareGroupRequest, CreateTenantReply, CreateTenantRequest, DeleteOffsetDataReply,
+    DeleteOffsetDataRequest, DeleteReply, DeleteRequest, DeleteResourceConfigReply,
+    DeleteResourceConfigRequest, DeleteRuleReply, DeleteRuleRequest, DeleteSchemaReply,
+    DeleteSchemaRequest, DeleteSchemaVersionReply, DeleteSchemaVersionRequest,
+    DeleteShareGroupMemberReply, DeleteShareGroupMemberRequest, DeleteShareGroupReply,
+    DeleteShareGroupRequest, DeleteTenantReply, DeleteTenantRequest, ExistsReply, ExistsRequest,
+    GetOffsetDataReply, GetOffsetDataRequest, GetPrefixReply, GetPrefixRequest, GetReply,
+    GetRequest, GetResourceConfigReply, GetResourceConfigRequest, HeartbeatReply, HeartbeatRequest,
     JoinClusterReply, JoinClusterRequest, LeaveClusterReply, LeaveClusterRequest,
-    ListBindSchemaReply, ListBindSchemaRequest, ListSchemaReply, ListSchemaRequest,
-    ListShareGroupMemberReply, ListShareGroupMemberRequest, ListShareGroupReply,
-    ListShareGroupRequest, ListTenantReply, ListTenantRequest, NodeListReply, NodeListRequest,
-    RegisterNodeReply, RegisterNodeRequest, SaveOffsetDataReply, SaveOffsetDataRequest, SetReply,
-    SetRequest, SetResourceConfigReply, SetResourceConfigRequest, SnapshotReply, SnapshotRequest,
-    UnBindSchemaReply, UnBindSchemaRequest, UnRegisterNodeReply, UnRegisterNodeRequest,
-    UpdateSchemaReply, UpdateSchemaRequest, UpdateTenantReply, UpdateTenantRequest, VoteReply,
-    VoteRequest,
+    ListBindSchemaReply, ListBindSchemaRequest, ListRuleReply, ListRuleRequest, ListSchemaReply,
+    ListSchemaRequest, ListSchemaVersionReply, ListSchemaVersionRequest, ListShareGroupMemberReply,
+    ListShareGroupMemberRequest, ListShareGroupReply, ListShareGroupRequest, ListTenantReply,
+    ListTenantRequest, NodeListReply, NodeListRequest, RegisterNodeReply, RegisterNodeRequest,
+    RegisterSchemaVersionReply, RegisterSchemaVersionRequest, SaveOffsetDataReply,
+    SaveOffsetDataRequest, SetReply, SetRequest, SetResourceConfigReply, SetResourceConfigRequest,
+    SnapshotReply, SnapshotRequest, UnBindSchemaReply, UnBindSchemaRequest, UnRegisterNodeReply,
+    UnRegisterNodeRequest, UpdateRuleReply, UpdateRuleRequest, UpdateSchemaReply,
+    UpdateSchemaRequest, UpdateTenantReply, UpdateTenantRequest, VoteReply, VoteRequest,
 };
 
 use tonic::Streaming;
@@ -170,6 +172,40 @@ pub async fn $fn_name(
     UnBindSchema
 );
 
+generate_meta_service_call!(
+    list_schema_version,
+    ListSchemaVersionRequest,
+    Streaming<ListSchemaVersionReply>,
+    ListSchemaVersion
+);
+
+generate_meta_service_call!(
+    register_schema_version,
+    RegisterSchemaVersionRequest,
+    RegisterSchemaVersionReply,
+    RegisterSchemaVersion
+);
+
+generate_meta_service_call!(
+    delete_schema_version,
+    DeleteSchemaVersionRequest,
+    DeleteSchemaVersionReply,
+    DeleteSchemaVersion
+);
+
+generate_meta_service_call!(
+    list_rule,
+    ListRuleRequest,
+    Streaming<ListRuleReply>,
+    ListRule
+);
+
+generate_meta_service_call!(create_rule, CreateRuleRequest, CreateRuleReply, CreateRule);
+
+generate_meta_service_call!(update_rule, UpdateRuleRequest, UpdateRuleReply, UpdateRule);
+
+generate_meta_service_call!(delete_rule, DeleteRuleRequest, DeleteRuleReply, DeleteRule);
+
 generate_meta_service_call!(
     get_offset_data,
     GetOffsetDataRequest,
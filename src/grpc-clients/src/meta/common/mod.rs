@@ -15,24 +15,26 @@
 use protocol::meta::meta_service_common::meta_service_service_client::MetaServiceServiceClient;
 use protocol::meta::meta_service_common::{
     AddShareGroupMemberReply, AddShareGroupMemberRequest, AppendReply, AppendRequest,
-    BindSchemaReply, BindSchemaRequest, ClusterStatusReply, ClusterStatusRequest,
-    CreateSchemaReply, CreateSchemaRequest, CreateShareGroupReply, CreateShareGroupRequest,
-    CreateTenantReply, CreateTenantRequest, DeleteOffsetDataReply, DeleteOffsetDataRequest,
-    DeleteReply, DeleteRequest, DeleteResourceConfigReply, DeleteResourceConfigRequest,
-    DeleteSchemaReply, DeleteSchemaRequest, DeleteShareGroupMemberReply,
-    DeleteShareGroupMemberRequest, DeleteShareGroupReply, DeleteShareGroupRequest,
-    DeleteTenantReply, DeleteTenantRequest, ExistsReply, ExistsRequest, GetOffsetDataReply,
-    GetOffsetDataRequest, GetPrefixReply, GetPrefixRequest, GetReply, GetRequest,
-    GetResourceConfigReply, GetResourceConfigRequest, HeartbeatReply, HeartbeatRequest,
+    BindSchemaReply, BindSchemaRequest, ClusterStatusReply, ClusterStatusRequest, CreateRuleReply,
+    CreateRuleRequest, CreateSchemaReply, CreateSchemaRequest, CreateShareGroupReply,
+    CreateShareGroupRequest, CreateTenantReply, CreateTenantRequest, DeleteOffsetDataReply,
+    DeleteOffsetDataRequest, DeleteReply, DeleteRequest, DeleteResourceConfigReply,
+    DeleteResourceConfigRequest, DeleteRuleReply, DeleteRuleRequest, DeleteSchemaReply,
+    DeleteSchemaRequest, DeleteSchemaVersionReply, DeleteSchemaVersionRequest,
+    DeleteShareGroupMemberReply, DeleteShareGroupMemberRequest, DeleteShareGroupReply,
+    DeleteShareGroupRequest, DeleteTenantReply, DeleteTenantRequest, ExistsReply, ExistsRequest,
+    GetOffsetDataReply, GetOffsetDataRequest, GetPrefixReply, GetPrefixRequest, GetReply,
+    GetRequest, GetResourceConfigReply, GetResourceConfigRequest, HeartbeatReply, HeartbeatRequest,
     JoinClusterReply, JoinClusterRequest, LeaveClusterReply, LeaveClusterRequest,
-    ListBindSchemaReply, ListBindSchemaRequest, ListSchemaReply, ListSchemaRequest,
-    ListShareGroupMemberReply, ListShareGroupMemberRequest, ListShareGroupReply,
-    ListShareGroupRequest, ListTenantReply, ListTenantRequest, NodeListReply, NodeListRequest,
-    RegisterNodeReply, RegisterNodeRequest, SaveOffsetDataReply, SaveOffsetDataRequest, SetReply,
-    SetRequest, SetResourceConfigReply, SetResourceConfigRequest, SnapshotReply, SnapshotRequest,
-    UnBindSchemaReply, UnBindSchemaRequest, UnRegisterNodeReply, UnRegisterNodeRequest,
-    UpdateSchemaReply, UpdateSchemaRequest, UpdateTenantReply, UpdateTenantRequest, VoteReply,
-    VoteRequest,
+    ListBindSchemaReply, ListBindSchemaRequest, ListRuleReply, ListRuleRequest, ListSchemaReply,
+    ListSchemaRequest, ListSchemaVersionReply, ListSchemaVersionRequest, ListShareGroupMemberReply,
+    ListShareGroupMemberRequest, ListShareGroupReply, ListShareGroupRequest, ListTenantReply,
+    ListTenantRequest, NodeListReply, NodeListRequest, RegisterNodeReply, RegisterNodeRequest,
+    RegisterSchemaVersionReply, RegisterSchemaVersionRequest, SaveOffsetDataReply,
+    SaveOffsetDataRequest, SetReply, SetRequest, SetResourceConfigReply, SetResourceConfigRequest,
+    SnapshotReply, SnapshotRequest, UnBindSchemaReply, UnBindSchemaRequest, UnRegisterNodeReply,
+    UnRegisterNodeRequest, UpdateRuleReply, UpdateRuleRequest, UpdateSchemaReply,
+    UpdateSchemaRequest, UpdateTenantReply, UpdateTenantRequest, VoteReply, VoteRequest,
 };
 use tonic::transport::Channel;
 use tonic::Streaming;
@@ -261,6 +263,76 @@
     true
 );
 
+impl_retriable_request!(
+    ListSchemaVersionRequest,
+    MetaServiceServiceClient<Channel>,
+    Streaming<ListSchemaVersionReply>,
+    list_schema_version,
+    "PlacementService",
+    "ListSchemaVersion",
+    true
+);
+
+impl_retriable_request!(
+    RegisterSchemaVersionRequest,
+    MetaServiceServiceClient<Channel>,
+    RegisterSchemaVersionReply,
+    register_schema_version,
+    "PlacementService",
+    "RegisterSchemaVersion",
+    true
+);
+
+impl_retriable_request!(
+    DeleteSchemaVersionRequest,
+    MetaServiceServiceClient<Channel>,
+    DeleteSchemaVersionReply,
+    delete_schema_version,
+    "PlacementService",
+    "DeleteSchemaVersion",
+    true
+);
+
+impl_retriable_request!(
+    ListRuleRequest,
+    MetaServiceServiceClient<Channel>,
+    Streaming<ListRuleReply>,
+    list_rule,
+    "PlacementService",
+    "ListRule",
+    true
+);
+
+impl_retriable_request!(
+    CreateRuleRequest,
+    MetaServiceServiceClient<Channel>,
+    CreateRuleReply,
+    create_rule,
+    "PlacementService",
+    "CreateRule",
+    true
+);
+
+impl_retriable_request!(
+    UpdateRuleRequest,
+    MetaServiceServiceClient<Channel>,
+    UpdateRuleReply,
+    update_rule,
+    "PlacementService",
+    "UpdateRule",
+    true
+);
+
+impl_retriable_request!(
+    DeleteRuleRequest,
+    MetaServiceServiceClient<Channel>,
+    DeleteRuleReply,
+    delete_rule,
+    "PlacementService",
+    "DeleteRule",
+    true
+);
+
 impl_retriable_request!(
     SetRequest,
     MetaServiceServiceClient<Channel>,
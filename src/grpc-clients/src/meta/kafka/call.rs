@@ -15,12 +15,15 @@
 use common_base::error::common::CommonError;
 use protocol::meta::meta_service_kafka::{
     DeleteKafkaDelegationTokenReply, DeleteKafkaDelegationTokenRequest, DeleteKafkaQuotaReply,
-    DeleteKafkaQuotaRequest, DeleteScramCredentialReply, DeleteScramCredentialRequest,
-    GetCoordinatorLeaderReply, GetCoordinatorLeaderRequest, ListKafkaDelegationTokenReply,
-    ListKafkaDelegationTokenRequest, ListKafkaQuotaReply, ListKafkaQuotaRequest,
+    DeleteKafkaQuotaRequest, DeleteKafkaSharePartitionStateReply,
+    DeleteKafkaSharePartitionStateRequest, DeleteScramCredentialReply,
+    DeleteScramCredentialRequest, GetCoordinatorLeaderReply, GetCoordinatorLeaderRequest,
+    ListKafkaDelegationTokenReply, ListKafkaDelegationTokenRequest, ListKafkaQuotaReply,
+    ListKafkaQuotaRequest, ListKafkaSharePartitionStateReply, ListKafkaSharePartitionStateRequest,
     ListScramCredentialReply, ListScramCredentialRequest, SetKafkaDelegationTokenReply,
     SetKafkaDelegationTokenRequest, SetKafkaQuotaReply, SetKafkaQuotaRequest,
-    SetScramCredentialReply, SetScramCredentialRequest,
+    SetKafkaSharePartitionStateReply, SetKafkaSharePartitionStateRequest, SetScramCredentialReply,
+    SetScramCredentialRequest,
 };
 
 use crate::pool::ClientPool;
@@ -79,3 +82,18 @@ pub async fn $fn_name(
     ListScramCredentialRequest,
     ListScramCredentialReply
 );
+generate_kafka_service_call!(
+    set_kafka_share_partition_state,
+    SetKafkaSharePartitionStateRequest,
+    SetKafkaSharePartitionStateReply
+);
+generate_kafka_service_call!(
+    delete_kafka_share_partition_state,
+    DeleteKafkaSharePartitionStateRequest,
+    DeleteKafkaSharePartitionStateReply
+);
+generate_kafka_service_call!(
+    list_kafka_share_partition_state,
+    ListKafkaSharePartitionStateRequest,
+    ListKafkaSharePartitionStateReply
+);
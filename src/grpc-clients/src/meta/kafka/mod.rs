@@ -15,12 +15,15 @@
 use protocol::meta::meta_service_kafka::kafka_service_client::KafkaServiceClient;
 use protocol::meta::meta_service_kafka::{
     DeleteKafkaDelegationTokenReply, DeleteKafkaDelegationTokenRequest, DeleteKafkaQuotaReply,
-    DeleteKafkaQuotaRequest, DeleteScramCredentialReply, DeleteScramCredentialRequest,
-    GetCoordinatorLeaderReply, GetCoordinatorLeaderRequest, ListKafkaDelegationTokenReply,
-    ListKafkaDelegationTokenRequest, ListKafkaQuotaReply, ListKafkaQuotaRequest,
+    DeleteKafkaQuotaRequest, DeleteKafkaSharePartitionStateReply,
+    DeleteKafkaSharePartitionStateRequest, DeleteScramCredentialReply,
+    DeleteScramCredentialRequest, GetCoordinatorLeaderReply, GetCoordinatorLeaderRequest,
+    ListKafkaDelegationTokenReply, ListKafkaDelegationTokenRequest, ListKafkaQuotaReply,
+    ListKafkaQuotaRequest, ListKafkaSharePartitionStateReply, ListKafkaSharePartitionStateRequest,
     ListScramCredentialReply, ListScramCredentialRequest, SetKafkaDelegationTokenReply,
     SetKafkaDelegationTokenRequest, SetKafkaQuotaReply, SetKafkaQuotaRequest,
-    SetScramCredentialReply, SetScramCredentialRequest,
+    SetKafkaSharePartitionStateReply, SetKafkaSharePartitionStateRequest, SetScramCredentialReply,
+    SetScramCredentialRequest,
 };
 use tonic::transport::Channel;
 
@@ -127,3 +130,33 @@
     "ListScramCredential",
     true
 );
+
+impl_retriable_request!(
+    SetKafkaSharePartitionStateRequest,
+    KafkaServiceClient<Channel>,
+    SetKafkaSharePartitionStateReply,
+    set_kafka_share_partition_state,
+    "KafkaService",
+    "SetKafkaSharePartitionState",
+    true
+);
+
+impl_retriable_request!(
+    DeleteKafkaSharePartitionStateRequest,
+    KafkaServiceClient<Channel>,
+    DeleteKafkaSharePartitionStateReply,
+    delete_kafka_share_partition_state,
+    "KafkaService",
+    "DeleteKafkaSharePartitionState",
+    true
+);
+
+impl_retriable_request!(
+    ListKafkaSharePartitionStateRequest,
+    KafkaServiceClient<Channel>,
+    ListKafkaSharePartitionStateReply,
+    list_kafka_share_partition_state,
+    "KafkaService",
+    "ListKafkaSharePartitionState",
+    true
+);
@@ -14,8 +14,12 @@
 
 use common_base::error::common::CommonError;
 use protocol::meta::meta_service_nats::{
-    CreateNatsSubscribeReply, CreateNatsSubscribeRequest, DeleteNatsSubscribeReply,
-    DeleteNatsSubscribeRequest, ListNatsSubscribeReply, ListNatsSubscribeRequest,
+    CreateNatsSubscribeReply, CreateNatsSubscribeRequest, DeleteNatsConsumerReply,
+    DeleteNatsConsumerRequest, DeleteNatsStreamReply, DeleteNatsStreamRequest,
+    DeleteNatsSubscribeReply, DeleteNatsSubscribeRequest, ListNatsConsumerReply,
+    ListNatsConsumerRequest, ListNatsStreamReply, ListNatsStreamRequest, ListNatsSubscribeReply,
+    ListNatsSubscribeRequest, SetNatsConsumerReply, SetNatsConsumerRequest, SetNatsStreamReply,
+    SetNatsStreamRequest,
 };
 use tonic::Streaming;
 
@@ -53,3 +57,45 @@ pub async fn $fn_name(
     Streaming<ListNatsSubscribeReply>,
     ListNatsSubscribe
 );
+
+generate_nats_service_call!(
+    placement_set_nats_stream,
+    SetNatsStreamRequest,
+    SetNatsStreamReply,
+    SetNatsStream
+);
+
+generate_nats_service_call!(
+    placement_delete_nats_stream,
+    DeleteNatsStreamRequest,
+    DeleteNatsStreamReply,
+    DeleteNatsStream
+);
+
+generate_nats_service_call!(
+    placement_list_nats_stream,
+    ListNatsStreamRequest,
+    Streaming<ListNatsStreamReply>,
+    ListNatsStream
+);
+
+generate_nats_service_call!(
+    placement_set_nats_consumer,
+    SetNatsConsumerRequest,
+    SetNatsConsumerReply,
+    SetNatsConsumer
+);
+
+generate_nats_service_call!(
+    placement_delete_nats_consumer,
+    DeleteNatsConsumerRequest,
+    DeleteNatsConsumerReply,
+    DeleteNatsConsumer
+);
+
+generate_nats_service_call!(
+    placement_list_nats_consumer,
+    ListNatsConsumerRequest,
+    Streaming<ListNatsConsumerReply>,
+    ListNatsConsumer
+);
@@ -14,8 +14,12 @@
 
 use protocol::meta::meta_service_nats::nats_service_client::NatsServiceClient;
 use protocol::meta::meta_service_nats::{
-    CreateNatsSubscribeReply, CreateNatsSubscribeRequest, DeleteNatsSubscribeReply,
-    DeleteNatsSubscribeRequest, ListNatsSubscribeReply, ListNatsSubscribeRequest,
+    CreateNatsSubscribeReply, CreateNatsSubscribeRequest, DeleteNatsConsumerReply,
+    DeleteNatsConsumerRequest, DeleteNatsStreamReply, DeleteNatsStreamRequest,
+    DeleteNatsSubscribeReply, DeleteNatsSubscribeRequest, ListNatsConsumerReply,
+    ListNatsConsumerRequest, ListNatsStreamReply, ListNatsStreamRequest, ListNatsSubscribeReply,
+    ListNatsSubscribeRequest, SetNatsConsumerReply, SetNatsConsumerRequest, SetNatsStreamReply,
+    SetNatsStreamRequest,
 };
 use tonic::transport::Channel;
 use tonic::Streaming;
@@ -53,3 +57,63 @@
     "ListNatsSubscribe",
     true
 );
+
+impl_retriable_request!(
+    SetNatsStreamRequest,
+    NatsServiceClient<Channel>,
+    SetNatsStreamReply,
+    set_nats_stream,
+    "NatsService",
+    "SetNatsStream",
+    true
+);
+
+impl_retriable_request!(
+    DeleteNatsStreamRequest,
+    NatsServiceClient<Channel>,
+    DeleteNatsStreamReply,
+    delete_nats_stream,
+    "NatsService",
+    "DeleteNatsStream",
+    true
+);
+
+impl_retriable_request!(
+    ListNatsStreamRequest,
+    NatsServiceClient<Channel>,
+    Streaming<ListNatsStreamReply>,
+    list_nats_stream,
+    "NatsService",
+    "ListNatsStream",
+    true
+);
+
+impl_retriable_request!(
+    SetNatsConsumerRequest,
+    NatsServiceClient<Channel>,
+    SetNatsConsumerReply,
+    set_nats_consumer,
+    "NatsService",
+    "SetNatsConsumer",
+    true
+);
+
+impl_retriable_request!(
+    DeleteNatsConsumerRequest,
+    NatsServiceClient<Channel>,
+    DeleteNatsConsumerReply,
+    delete_nats_consumer,
+    "NatsService",
+    "DeleteNatsConsumer",
+    true
+);
+
+impl_retriable_request!(
+    ListNatsConsumerRequest,
+    NatsServiceClient<Channel>,
+    Streaming<ListNatsConsumerReply>,
+    list_nats_consumer,
+    "NatsService",
+    "ListNatsConsumer",
+    true
+);
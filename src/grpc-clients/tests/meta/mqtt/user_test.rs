@@ -44,6 +44,7 @@ async fn mqtt_user_test() {
             salt: None,
             is_superuser: false,
             create_time: now_second(),
+            scram_credentials: Vec::new(),
         };
 
         let request: CreateUserRequest = CreateUserRequest {
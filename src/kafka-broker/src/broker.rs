@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::core::cache::KafkaCacheManager;
+use crate::core::txn_coordinator::TransactionCoordinator;
 use crate::server::{KafkaServer, KafkaServerParams};
 use broker_core::cache::NodeCacheManager;
 use common_base::task::TaskSupervisor;
@@ -37,6 +38,9 @@ pub struct KafkaBrokerServerParams {
     pub request_channel: Arc<RequestChannel>,
     pub storage_driver_manager: Arc<StorageDriverManager>,
     pub kafka_cache: Arc<KafkaCacheManager>,
+    // Shared by the Kafka command handler and the broker gRPC service, which
+    // serves transactional produce forwarded from other nodes.
+    pub txn_coordinator: Arc<TransactionCoordinator>,
 }
 
 pub struct KafkaBrokerServer {
@@ -16,7 +16,7 @@
 
 use uuid::Uuid;
 
-use crate::core::consumer_group_meta::{ConsumerMemberMeta, TargetAssignment};
+use crate::core::consumer_group_meta::{ConsumerGroupMeta, TargetAssignment};
 
 pub struct TopicMeta {
     pub topic_id: Uuid,
@@ -28,15 +28,15 @@ pub struct TopicMeta {
 // `count % n` subscribers get one extra partition. Deterministic for a given
 // membership so recomputing at the same epoch yields the same target.
 pub(crate) fn compute_target(
-    members: &HashMap<String, ConsumerMemberMeta>,
+    group: &ConsumerGroupMeta,
     resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
 ) -> TargetAssignment {
     // topic_name -> sorted subscriber ids
     let mut subscribers: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
-    for member in members.values() {
-        for topic in &member.subscribed {
+    for member in group.members.values() {
+        for topic in group.member_subscription(member) {
             subscribers
-                .entry(topic.clone())
+                .entry(topic)
                 .or_default()
                 .insert(member.member_id.clone());
         }
@@ -72,9 +72,51 @@ pub(crate) fn compute_target(
     target
 }
 
+// Share-group assignment. Records of a share-partition are handed out one by
+// one, so a partition may safely go to several members: with at least as many
+// partitions as subscribers they are dealt round-robin, otherwise every
+// subscriber gets one partition and the partitions are shared.
+pub(crate) fn compute_share_target<'a>(
+    members: impl Iterator<Item = (&'a str, &'a [String])>,
+    resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
+) -> TargetAssignment {
+    let mut subscribers: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (member_id, subscribed) in members {
+        for topic in subscribed {
+            subscribers.entry(topic).or_default().insert(member_id);
+        }
+    }
+
+    let mut target: TargetAssignment = HashMap::new();
+    for (topic_name, subs) in subscribers {
+        let Some(meta) = resolve_topic(topic_name) else {
+            continue;
+        };
+        let count = meta.partitions as usize;
+        let n = subs.len();
+        if count == 0 || n == 0 {
+            continue;
+        }
+        for (idx, member_id) in subs.iter().enumerate() {
+            let partitions: Vec<i32> = if count >= n {
+                (idx..count).step_by(n).map(|p| p as i32).collect()
+            } else {
+                vec![(idx % count) as i32]
+            };
+            target
+                .entry(member_id.to_string())
+                .or_default()
+                .insert(meta.topic_id, partitions);
+        }
+    }
+    target
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::consumer_group_meta::{ConsumerMemberMeta, RegexSubscription};
+    use regex::Regex;
 
     fn member(id: &str, topics: Vec<&str>) -> (String, ConsumerMemberMeta) {
         (
@@ -86,6 +128,7 @@ fn member(id: &str, topics: Vec<&str>) -> (String, ConsumerMemberMeta) {
                 client_id: "c".to_string(),
                 rebalance_timeout_ms: 60_000,
                 subscribed: topics.into_iter().map(|t| t.to_string()).collect(),
+                subscribed_regex: None,
                 reported: HashMap::new(),
                 member_epoch: 0,
                 last_sent: None,
@@ -94,6 +137,12 @@ fn member(id: &str, topics: Vec<&str>) -> (String, ConsumerMemberMeta) {
         )
     }
 
+    fn group_of<const N: usize>(members: [(String, ConsumerMemberMeta); N]) -> ConsumerGroupMeta {
+        let mut group = ConsumerGroupMeta::new("g".to_string());
+        group.members = members.into();
+        group
+    }
+
     fn resolver(partitions: u32) -> impl Fn(&str) -> Option<TopicMeta> {
         move |name: &str| {
             Some(TopicMeta {
@@ -105,9 +154,9 @@ fn resolver(partitions: u32) -> impl Fn(&str) -> Option<TopicMeta> {
 
     #[test]
     fn range_splits_contiguously_with_remainder_to_first_members() {
-        let members: HashMap<_, _> = [member("m1", vec!["t"]), member("m2", vec!["t"])].into();
+        let group = group_of([member("m1", vec!["t"]), member("m2", vec!["t"])]);
         let resolve = resolver(5);
-        let target = compute_target(&members, &resolve);
+        let target = compute_target(&group, &resolve);
 
         let tid = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"t");
         assert_eq!(target["m1"][&tid], vec![0, 1, 2]);
@@ -116,9 +165,9 @@ fn range_splits_contiguously_with_remainder_to_first_members() {
 
     #[test]
     fn only_subscribers_get_a_topic() {
-        let members: HashMap<_, _> = [member("m1", vec!["a"]), member("m2", vec!["b"])].into();
+        let group = group_of([member("m1", vec!["a"]), member("m2", vec!["b"])]);
         let resolve = resolver(2);
-        let target = compute_target(&members, &resolve);
+        let target = compute_target(&group, &resolve);
 
         let ta = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"a");
         let tb = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"b");
@@ -129,9 +178,58 @@ fn only_subscribers_get_a_topic() {
 
     #[test]
     fn unknown_topics_are_skipped() {
-        let members: HashMap<_, _> = [member("m1", vec!["missing"])].into();
+        let group = group_of([member("m1", vec!["missing"])]);
         let resolve = |_: &str| None;
-        let target = compute_target(&members, &resolve);
+        let target = compute_target(&group, &resolve);
         assert!(!target.contains_key("m1"));
     }
+
+    #[test]
+    fn regex_subscribers_get_matched_topics() {
+        let (id, mut m1) = member("m1", vec!["a"]);
+        m1.subscribed_regex = Some("events\\..*".to_string());
+        let mut group = group_of([(id, m1), member("m2", vec!["events.x"])]);
+        group.regex_subscriptions.insert(
+            "events\\..*".to_string(),
+            RegexSubscription {
+                regex: Regex::new("^(?:events\\..*)$").unwrap(),
+                topics: vec!["events.x".to_string()],
+            },
+        );
+        let resolve = resolver(2);
+        let target = compute_target(&group, &resolve);
+
+        let ta = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"a");
+        let tx = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"events.x");
+        assert_eq!(target["m1"][&ta], vec![0, 1]);
+        assert_eq!(target["m1"][&tx], vec![0]);
+        assert_eq!(target["m2"][&tx], vec![1]);
+    }
+
+    #[test]
+    fn share_target_deals_partitions_round_robin() {
+        let subscribed = vec!["t".to_string()];
+        let members = [("m1", subscribed.as_slice()), ("m2", subscribed.as_slice())];
+        let target = compute_share_target(members.into_iter(), &resolver(5));
+
+        let tid = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"t");
+        assert_eq!(target["m1"][&tid], vec![0, 2, 4]);
+        assert_eq!(target["m2"][&tid], vec![1, 3]);
+    }
+
+    #[test]
+    fn share_target_shares_partitions_between_surplus_members() {
+        let subscribed = vec!["t".to_string()];
+        let members = [
+            ("m1", subscribed.as_slice()),
+            ("m2", subscribed.as_slice()),
+            ("m3", subscribed.as_slice()),
+        ];
+        let target = compute_share_target(members.into_iter(), &resolver(2));
+
+        let tid = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"t");
+        assert_eq!(target["m1"][&tid], vec![0]);
+        assert_eq!(target["m2"][&tid], vec![1]);
+        assert_eq!(target["m3"][&tid], vec![0]);
+    }
 }
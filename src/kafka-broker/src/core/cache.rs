@@ -18,13 +18,17 @@
 use metadata_struct::kafka::delegation_token::KafkaDelegationToken;
 use metadata_struct::kafka::quota::{KafkaClientQuota, QUOTA_DEFAULT_NAME};
 use metadata_struct::kafka::scram::KafkaScramCredential;
+use metadata_struct::kafka::transaction::{KafkaAbortedTxn, KafkaTransaction, KafkaTxnMarker};
 
 use crate::core::sasl::SaslSession;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
 
 use crate::core::assignor::TopicMeta;
+use crate::core::compression::TopicCompression;
+use crate::core::constants::TOPIC_COMPRESSION_CACHE_TTL_MS;
 use crate::core::consumer_group_meta::{self, ConsumerDescribedGroup, ConsumerGroupMeta};
 use crate::core::consumer_heartbeat::{
     self, heartbeat_error, ConsumerHeartbeatParams, ConsumerHeartbeatResult,
@@ -34,6 +38,8 @@
 use crate::core::heartbeat;
 use crate::core::join::{self, AddMemberOutcome, JoinCompletion};
 use crate::core::leave::{self, LeaveOutcome};
+use crate::core::share_group_meta::{self, ShareDescribedGroup, ShareGroupMeta};
+use crate::core::share_heartbeat::{self, ShareHeartbeatParams};
 use crate::core::sync::{self, sync_error, SyncOutcome, SyncResult};
 
 // In-memory data the Kafka broker caches on the coordinator node (consumer-group
@@ -83,6 +89,9 @@ pub struct KafkaCacheManager {
     groups: DashMap<String, GroupMeta>,
     // KIP-848 consumer groups; a group id belongs to exactly one protocol.
     consumer_groups: DashMap<String, ConsumerGroupMeta>,
+    // KIP-932 share groups; membership only, share-partition state lives in
+    // `ShareGroupCoordinator`.
+    share_groups: DashMap<String, ShareGroupMeta>,
     // Client quotas, keyed by entity_key ("{entity_type}/{name|__default__}").
     quotas: DashMap<String, KafkaClientQuota>,
     // Delegation tokens (KIP-48), keyed by token_id. Metadata only — nothing
@@ -92,12 +101,22 @@ pub struct KafkaCacheManager {
     scram_credentials: DashMap<String, KafkaScramCredential>,
     // Per-connection SASL state, keyed by connection id.
     sasl_sessions: DashMap<u64, SaslSession>,
-    // Idempotent-producer id allocator (InitProducerId). Broker-local monotonic
-    // counter — fine for a single node; transactions/cluster-wide blocks are out
-    // of scope.
+    // Producer id allocator (InitProducerId). Broker-local monotonic counter —
+    // fine for a single node; cluster-wide id blocks are out of scope. Moved
+    // past every recovered transactional producer id on startup.
     producer_id_counter: AtomicI64,
     // Per (producer_id, shard) sequence state for idempotent produce dedup.
     producer_sequences: DashMap<(i64, String), ProducerState>,
+    // Transaction coordinator state, keyed by transactional id. The durable
+    // copy lives in the transaction log inner topic; see `TransactionCoordinator`.
+    transactions: DashMap<String, KafkaTransaction>,
+    // Aborted transaction ranges per shard, consulted by read_committed Fetch.
+    aborted_transactions: DashMap<String, Vec<KafkaAbortedTxn>>,
+    // Offset of the latest commit/abort marker per shard and producer id; a
+    // transactional record past it is still undecided on this node.
+    txn_markers: DashMap<String, HashMap<i64, i64>>,
+    // Topic `compression.type`, keyed by topic name, with when it was read.
+    topic_compression: DashMap<String, (TopicCompression, Instant)>,
 }
 
 impl KafkaCacheManager {
@@ -105,12 +124,17 @@ pub fn new() -> Self {
         KafkaCacheManager {
             groups: DashMap::with_capacity(8),
             consumer_groups: DashMap::with_capacity(8),
+            share_groups: DashMap::with_capacity(8),
             quotas: DashMap::with_capacity(8),
             delegation_tokens: DashMap::with_capacity(8),
             scram_credentials: DashMap::with_capacity(8),
             sasl_sessions: DashMap::with_capacity(8),
             producer_id_counter: AtomicI64::new(1),
             producer_sequences: DashMap::with_capacity(8),
+            transactions: DashMap::with_capacity(8),
+            aborted_transactions: DashMap::with_capacity(8),
+            txn_markers: DashMap::with_capacity(8),
+            topic_compression: DashMap::with_capacity(8),
         }
     }
 
@@ -119,6 +143,13 @@ pub fn next_producer_id(&self) -> i64 {
         self.producer_id_counter.fetch_add(1, Ordering::SeqCst)
     }
 
+    /// Make sure ids handed out from now on are greater than `producer_id`
+    /// (used when transactional producers are recovered from the log).
+    pub fn reserve_producer_id(&self, producer_id: i64) {
+        self.producer_id_counter
+            .fetch_max(producer_id + 1, Ordering::SeqCst);
+    }
+
     /// Decide how to treat an incoming idempotent batch for this
     /// (producer_id, shard) at `epoch` with base sequence `base_seq`:
     /// - older epoch than seen  → `Fenced` (INVALID_PRODUCER_EPOCH)
@@ -188,6 +219,129 @@ pub fn record_producer_sequence(
         state.next_seq = last_seq.wrapping_add(1);
     }
 
+    pub fn get_transaction(&self, transactional_id: &str) -> Option<KafkaTransaction> {
+        self.transactions.get(transactional_id).map(|t| t.clone())
+    }
+
+    pub fn set_transaction(&self, txn: KafkaTransaction) {
+        self.transactions.insert(txn.transactional_id.clone(), txn);
+    }
+
+    /// Apply a transaction read back from the transaction log, unless the
+    /// cache already holds the same or a newer version of it.
+    pub fn merge_transaction(&self, txn: KafkaTransaction) {
+        match self.transactions.entry(txn.transactional_id.clone()) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().version < txn.version {
+                    entry.insert(txn);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(txn);
+            }
+        }
+    }
+
+    pub fn list_transactions(&self) -> Vec<KafkaTransaction> {
+        let mut txns: Vec<KafkaTransaction> = self
+            .transactions
+            .iter()
+            .map(|t| t.value().clone())
+            .collect();
+        txns.sort_by(|a, b| a.transactional_id.cmp(&b.transactional_id));
+        txns
+    }
+
+    pub fn add_aborted_transactions(&self, aborted: Vec<KafkaAbortedTxn>) {
+        for txn in aborted {
+            self.record_txn_marker(&txn.shard_name, txn.producer_id, txn.last_offset);
+            let mut entry = self
+                .aborted_transactions
+                .entry(txn.shard_name.clone())
+                .or_default();
+            if !entry.contains(&txn) {
+                entry.push(txn);
+            }
+        }
+    }
+
+    pub fn add_txn_markers(&self, markers: Vec<KafkaTxnMarker>) {
+        for marker in markers {
+            self.record_txn_marker(&marker.shard_name, marker.producer_id, marker.offset);
+        }
+    }
+
+    fn record_txn_marker(&self, shard: &str, producer_id: i64, offset: i64) {
+        let mut markers = self.txn_markers.entry(shard.to_string()).or_default();
+        let last = markers.entry(producer_id).or_insert(offset);
+        *last = (*last).max(offset);
+    }
+
+    /// Whether the transaction that wrote `offset` on `shard` is known to be
+    /// committed or aborted, i.e. a later marker of `producer_id` is indexed.
+    pub fn is_txn_decided(&self, shard: &str, producer_id: i64, offset: i64) -> bool {
+        self.txn_markers
+            .get(shard)
+            .and_then(|markers| markers.get(&producer_id).copied())
+            .is_some_and(|marker| marker > offset)
+    }
+
+    /// Aborted transactions on `shard` overlapping offsets [from, to].
+    pub fn aborted_transactions(&self, shard: &str, from: i64, to: i64) -> Vec<KafkaAbortedTxn> {
+        self.aborted_transactions
+            .get(shard)
+            .map(|list| {
+                list.iter()
+                    .filter(|t| t.first_offset <= to && t.last_offset >= from)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drop the aborted ranges and markers on `shard` that end below
+    /// `log_start_offset`; no fetch can reach their records any more.
+    pub fn prune_aborted_transactions(&self, shard: &str, log_start_offset: i64) {
+        if let Some(mut list) = self.aborted_transactions.get_mut(shard) {
+            list.retain(|t| t.last_offset >= log_start_offset);
+        }
+        if let Some(mut markers) = self.txn_markers.get_mut(shard) {
+            markers.retain(|_, offset| *offset >= log_start_offset);
+        }
+    }
+
+    /// Cached `compression.type` of `topic`, if read within
+    /// `TOPIC_COMPRESSION_CACHE_TTL_MS`.
+    pub fn topic_compression(&self, topic: &str) -> Option<TopicCompression> {
+        self.topic_compression
+            .get(topic)
+            .filter(|entry| {
+                entry.1.elapsed() < Duration::from_millis(TOPIC_COMPRESSION_CACHE_TTL_MS)
+            })
+            .map(|entry| entry.0)
+    }
+
+    pub fn set_topic_compression(&self, topic: &str, policy: TopicCompression) {
+        self.topic_compression
+            .insert(topic.to_string(), (policy, Instant::now()));
+    }
+
+    /// Offset below which every record on `shard` is decided: the first offset
+    /// of the oldest open transaction on it, or the high watermark when none is.
+    pub fn last_stable_offset(&self, shard: &str, high_watermark: i64) -> i64 {
+        self.transactions
+            .iter()
+            .filter(|t| t.state.is_open())
+            .flat_map(|t| {
+                t.partitions
+                    .iter()
+                    .filter(|p| p.shard_name == shard)
+                    .map(|p| p.first_offset)
+                    .collect::<Vec<_>>()
+            })
+            .fold(high_watermark, i64::min)
+    }
+
     pub fn set_sasl_session(&self, connection_id: u64, session: SaslSession) {
         self.sasl_sessions.insert(connection_id, session);
     }
@@ -266,6 +420,10 @@ pub fn has_consumer_group(&self, group_id: &str) -> bool {
         self.consumer_groups.contains_key(group_id)
     }
 
+    pub fn has_share_group(&self, group_id: &str) -> bool {
+        self.share_groups.contains_key(group_id)
+    }
+
     pub fn add_member(&self, group_id: &str, member: MemberMeta) -> AddMemberOutcome {
         let mut group = self
             .groups
@@ -361,6 +519,12 @@ pub fn list_groups(&self) -> Vec<ListedGroupInfo> {
             state: group.state_name().to_string(),
             group_type: "consumer".to_string(),
         }));
+        groups.extend(self.share_groups.iter().map(|group| ListedGroupInfo {
+            group_id: group.group_id.clone(),
+            protocol_type: "share".to_string(),
+            state: group.state_name().to_string(),
+            group_type: "share".to_string(),
+        }));
         groups.sort_by(|a, b| a.group_id.cmp(&b.group_id));
         groups
     }
@@ -369,6 +533,7 @@ pub fn consumer_heartbeat(
         &self,
         params: ConsumerHeartbeatParams,
         resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
+        list_topics: &dyn Fn() -> Vec<String>,
         now_ms: u128,
     ) -> ConsumerHeartbeatResult {
         if self.groups.contains_key(&params.group_id) {
@@ -378,12 +543,27 @@ pub fn consumer_heartbeat(
                 &params.member_id,
             );
         }
+        if self.share_groups.contains_key(&params.group_id) {
+            return heartbeat_error(
+                ResponseError::GroupIdNotFound.code(),
+                "group id is used by a share group",
+                &params.member_id,
+            );
+        }
         let group_id = params.group_id.clone();
         let mut group = self
             .consumer_groups
             .entry(group_id.clone())
             .or_insert_with(|| ConsumerGroupMeta::new(group_id));
-        consumer_heartbeat::heartbeat(&mut group, params, resolve_topic, now_ms)
+        consumer_heartbeat::heartbeat(&mut group, params, resolve_topic, list_topics, now_ms)
+    }
+
+    /// Force every consumer group's topic regexes to be resolved again on
+    /// its next heartbeat.
+    pub fn expire_regex_subscriptions(&self) {
+        for mut group in self.consumer_groups.iter_mut() {
+            group.regex_refreshed_ms = 0;
+        }
     }
 
     pub fn describe_consumer_group(
@@ -396,6 +576,65 @@ pub fn describe_consumer_group(
             .map(|group| consumer_group_meta::describe(&group, tenant))
     }
 
+    pub fn share_heartbeat(
+        &self,
+        params: ShareHeartbeatParams,
+        resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
+        now_ms: u128,
+    ) -> ConsumerHeartbeatResult {
+        if self.groups.contains_key(&params.group_id)
+            || self.consumer_groups.contains_key(&params.group_id)
+        {
+            return heartbeat_error(
+                ResponseError::GroupIdNotFound.code(),
+                "group id is used by a classic or consumer group",
+                &params.member_id,
+            );
+        }
+        let group_id = params.group_id.clone();
+        let mut group = self
+            .share_groups
+            .entry(group_id.clone())
+            .or_insert_with(|| ShareGroupMeta::new(group_id));
+        share_heartbeat::heartbeat(&mut group, params, resolve_topic, now_ms)
+    }
+
+    pub fn describe_share_group(
+        &self,
+        group_id: &str,
+        tenant: &str,
+    ) -> Option<ShareDescribedGroup> {
+        self.share_groups
+            .get(group_id)
+            .map(|group| share_group_meta::describe(&group, tenant))
+    }
+
+    pub fn share_group_member_count(&self, group_id: &str) -> usize {
+        self.share_groups
+            .get(group_id)
+            .map(|group| group.members.len())
+            .unwrap_or(0)
+    }
+
+    /// Expires share group members, returning (group_id, member_id) pairs whose
+    /// acquired records must be released.
+    pub fn reap_share_members(
+        &self,
+        now_ms: u128,
+        session_timeout_ms: u64,
+    ) -> Vec<(String, String)> {
+        let mut expired = Vec::new();
+        for mut group in self.share_groups.iter_mut() {
+            let group_id = group.group_id.clone();
+            for member_id in
+                share_heartbeat::remove_expired_members(&mut group, now_ms, session_timeout_ms)
+            {
+                expired.push((group_id.clone(), member_id));
+            }
+        }
+        expired
+    }
+
     pub fn delete_group(&self, group_id: &str) -> i16 {
         match self.groups.entry(group_id.to_string()) {
             Entry::Occupied(entry) => {
@@ -409,6 +648,17 @@ pub fn delete_group(&self, group_id: &str) -> i16 {
             Entry::Vacant(_) => {}
         }
         match self.consumer_groups.entry(group_id.to_string()) {
+            Entry::Occupied(entry) => {
+                return if entry.get().members.is_empty() {
+                    entry.remove();
+                    0
+                } else {
+                    ResponseError::NonEmptyGroup.code()
+                };
+            }
+            Entry::Vacant(_) => {}
+        }
+        match self.share_groups.entry(group_id.to_string()) {
             Entry::Occupied(entry) => {
                 if entry.get().members.is_empty() {
                     entry.remove();
@@ -545,3 +795,131 @@ fn sequence_state_is_isolated_per_producer_and_shard() {
         ));
     }
 }
+
+#[cfg(test)]
+mod transaction_tests {
+    use super::*;
+    use metadata_struct::kafka::transaction::{KafkaTransactionState, KafkaTxnPartition};
+
+    fn txn(id: &str, state: KafkaTransactionState, first_offset: i64) -> KafkaTransaction {
+        KafkaTransaction {
+            transactional_id: id.to_string(),
+            state,
+            partitions: vec![KafkaTxnPartition {
+                topic: "t".to_string(),
+                partition: 0,
+                shard_name: "shard-0".to_string(),
+                first_offset,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn last_stable_offset_is_held_back_by_open_transactions() {
+        let cache = KafkaCacheManager::new();
+        assert_eq!(cache.last_stable_offset("shard-0", 100), 100);
+
+        cache.set_transaction(txn("a", KafkaTransactionState::Ongoing, 40));
+        cache.set_transaction(txn("b", KafkaTransactionState::PrepareCommit, 60));
+        cache.set_transaction(txn("c", KafkaTransactionState::CompleteAbort, 10));
+        assert_eq!(cache.last_stable_offset("shard-0", 100), 40);
+        assert_eq!(cache.last_stable_offset("shard-1", 100), 100);
+
+        cache.set_transaction(txn("a", KafkaTransactionState::CompleteCommit, 40));
+        assert_eq!(cache.last_stable_offset("shard-0", 100), 60);
+    }
+
+    #[test]
+    fn aborted_transactions_are_deduplicated_and_filtered_by_range() {
+        let cache = KafkaCacheManager::new();
+        let aborted = KafkaAbortedTxn {
+            shard_name: "shard-0".to_string(),
+            producer_id: 1,
+            first_offset: 10,
+            last_offset: 20,
+        };
+        cache.add_aborted_transactions(vec![aborted.clone(), aborted.clone()]);
+
+        assert_eq!(
+            cache.aborted_transactions("shard-0", 0, 100),
+            vec![aborted.clone()]
+        );
+        assert_eq!(cache.aborted_transactions("shard-0", 20, 30), vec![aborted]);
+        assert!(cache.aborted_transactions("shard-0", 21, 30).is_empty());
+        assert!(cache.aborted_transactions("shard-1", 0, 100).is_empty());
+    }
+
+    #[test]
+    fn merge_transaction_keeps_the_newest_version() {
+        let cache = KafkaCacheManager::new();
+        let mut newer = txn("a", KafkaTransactionState::CompleteAbort, 40);
+        newer.version = 5;
+        cache.set_transaction(newer);
+
+        let mut older = txn("a", KafkaTransactionState::Ongoing, 40);
+        older.version = 4;
+        cache.merge_transaction(older.clone());
+        assert_eq!(cache.get_transaction("a").unwrap().version, 5);
+
+        older.version = 6;
+        cache.merge_transaction(older);
+        assert_eq!(
+            cache.get_transaction("a").unwrap().state,
+            KafkaTransactionState::Ongoing
+        );
+    }
+
+    #[test]
+    fn aborted_transactions_below_log_start_are_pruned() {
+        let cache = KafkaCacheManager::new();
+        let range = |first_offset, last_offset| KafkaAbortedTxn {
+            shard_name: "shard-0".to_string(),
+            producer_id: 1,
+            first_offset,
+            last_offset,
+        };
+        cache.add_aborted_transactions(vec![range(0, 9), range(10, 20), range(30, 40)]);
+
+        cache.prune_aborted_transactions("shard-0", 20);
+        assert_eq!(
+            cache.aborted_transactions("shard-0", 0, 100),
+            vec![range(10, 20), range(30, 40)]
+        );
+        cache.prune_aborted_transactions("shard-1", 100);
+        assert_eq!(cache.aborted_transactions("shard-0", 0, 100).len(), 2);
+    }
+
+    #[test]
+    fn records_are_decided_by_a_later_marker_of_their_producer() {
+        let cache = KafkaCacheManager::new();
+        assert!(!cache.is_txn_decided("shard-0", 1, 5));
+
+        cache.add_txn_markers(vec![KafkaTxnMarker {
+            shard_name: "shard-0".to_string(),
+            producer_id: 1,
+            offset: 10,
+        }]);
+        assert!(cache.is_txn_decided("shard-0", 1, 5));
+        assert!(!cache.is_txn_decided("shard-0", 1, 11));
+        assert!(!cache.is_txn_decided("shard-0", 2, 5));
+        assert!(!cache.is_txn_decided("shard-1", 1, 5));
+
+        // Abort markers count too, and an older marker never moves it back.
+        cache.add_aborted_transactions(vec![KafkaAbortedTxn {
+            shard_name: "shard-0".to_string(),
+            producer_id: 1,
+            first_offset: 11,
+            last_offset: 20,
+        }]);
+        cache.add_txn_markers(vec![KafkaTxnMarker {
+            shard_name: "shard-0".to_string(),
+            producer_id: 1,
+            offset: 10,
+        }]);
+        assert!(cache.is_txn_decided("shard-0", 1, 15));
+
+        cache.prune_aborted_transactions("shard-0", 21);
+        assert!(!cache.is_txn_decided("shard-0", 1, 15));
+    }
+}
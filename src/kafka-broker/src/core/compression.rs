@@ -0,0 +1,190 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Record-batch compression: the topic's `compression.type` and the codec
+//! helpers Produce/Fetch use to honor it.
+//!
+//! Storage keeps individual records, not Kafka batches, so the codec a record
+//! should be served in is remembered on the record itself
+//! (`StorageRecordProtocolDataKafka::compression`). The value itself is kept
+//! uncompressed so other protocols, connectors and rules read plain bytes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_base::utils::serialize::deserialize;
+use common_config::broker::broker_config;
+use grpc_clients::meta::common::call::get_resource_config;
+use kafka_protocol::records::Compression;
+use protocol::meta::meta_service_common::GetResourceConfigRequest;
+use storage_adapter::driver::StorageDriverManager;
+use tracing::warn;
+
+use crate::core::cache::KafkaCacheManager;
+
+pub const COMPRESSION_TYPE_CONFIG: &str = "compression.type";
+
+/// A topic's `compression.type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopicCompression {
+    /// Keep the codec the producer used (Kafka's default).
+    Producer,
+    Uncompressed,
+    Codec(Compression),
+}
+
+impl TopicCompression {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "producer" => Some(Self::Producer),
+            "uncompressed" => Some(Self::Uncompressed),
+            "gzip" => Some(Self::Codec(Compression::Gzip)),
+            "snappy" => Some(Self::Codec(Compression::Snappy)),
+            "lz4" => Some(Self::Codec(Compression::Lz4)),
+            "zstd" => Some(Self::Codec(Compression::Zstd)),
+            _ => None,
+        }
+    }
+
+    /// The codec a record produced with `producer_codec` is stored and
+    /// served in under this policy.
+    pub fn resolve(self, producer_codec: Compression) -> Compression {
+        match self {
+            Self::Producer => producer_codec,
+            Self::Uncompressed => Compression::None,
+            Self::Codec(codec) => codec,
+        }
+    }
+}
+
+/// Wire value of a codec (the record batch `attributes` bits).
+pub fn compression_to_wire(codec: Compression) -> i8 {
+    codec as i8
+}
+
+/// Inverse of `compression_to_wire`; unknown values read as uncompressed.
+pub fn compression_from_wire(value: i8) -> Compression {
+    match value {
+        1 => Compression::Gzip,
+        2 => Compression::Snappy,
+        3 => Compression::Lz4,
+        4 => Compression::Zstd,
+        _ => Compression::None,
+    }
+}
+
+/// The topic's `compression.type`, cached for `TOPIC_COMPRESSION_CACHE_TTL_MS`.
+/// A missing or unreadable config falls back to `Producer`.
+pub async fn topic_compression(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &KafkaCacheManager,
+    topic_name: &str,
+) -> TopicCompression {
+    if let Some(policy) = cache.topic_compression(topic_name) {
+        return policy;
+    }
+
+    let client_pool = &sdm.engine_storage_handler.client_pool;
+    let addrs = broker_config().get_meta_service_addr();
+    let request = GetResourceConfigRequest {
+        resources: vec![
+            "kafka".to_string(),
+            "topic".to_string(),
+            topic_name.to_string(),
+        ],
+    };
+    let configs: HashMap<String, String> =
+        match get_resource_config(client_pool, &addrs, request).await {
+            Ok(reply) if reply.config.is_empty() => HashMap::new(),
+            Ok(reply) => match deserialize(&reply.config) {
+                Ok(map) => map,
+                Err(e) => {
+                    warn!(
+                        "Kafka failed to decode stored config for topic '{}': {}",
+                        topic_name, e
+                    );
+                    return TopicCompression::Producer;
+                }
+            },
+            Err(e) => {
+                warn!(
+                    "Kafka failed to read config for topic '{}': {}",
+                    topic_name, e
+                );
+                return TopicCompression::Producer;
+            }
+        };
+
+    let policy = configs
+        .get(COMPRESSION_TYPE_CONFIG)
+        .and_then(|v| TopicCompression::parse(v))
+        .unwrap_or(TopicCompression::Producer);
+    cache.set_topic_compression(topic_name, policy);
+    policy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_CODECS: [Compression; 5] = [
+        Compression::None,
+        Compression::Gzip,
+        Compression::Snappy,
+        Compression::Lz4,
+        Compression::Zstd,
+    ];
+
+    #[test]
+    fn parse_accepts_kafka_values_only() {
+        assert_eq!(
+            TopicCompression::parse("producer"),
+            Some(TopicCompression::Producer)
+        );
+        assert_eq!(
+            TopicCompression::parse("uncompressed"),
+            Some(TopicCompression::Uncompressed)
+        );
+        assert_eq!(
+            TopicCompression::parse("zstd"),
+            Some(TopicCompression::Codec(Compression::Zstd))
+        );
+        assert_eq!(TopicCompression::parse("none"), None);
+        assert_eq!(TopicCompression::parse("LZ4"), None);
+    }
+
+    #[test]
+    fn resolve_overrides_producer_codec_unless_producer() {
+        assert_eq!(
+            TopicCompression::Producer.resolve(Compression::Lz4),
+            Compression::Lz4
+        );
+        assert_eq!(
+            TopicCompression::Uncompressed.resolve(Compression::Lz4),
+            Compression::None
+        );
+        assert_eq!(
+            TopicCompression::Codec(Compression::Gzip).resolve(Compression::None),
+            Compression::Gzip
+        );
+    }
+
+    #[test]
+    fn wire_values_round_trip() {
+        for codec in ALL_CODECS {
+            assert_eq!(compression_from_wire(compression_to_wire(codec)), codec);
+        }
+        assert_eq!(compression_from_wire(7), Compression::None);
+    }
+}
@@ -44,8 +44,78 @@
 pub const NO_PRODUCER_ID: i64 = -1;
 /// Fetch: no producer epoch (non-transactional / non-idempotent record).
 pub const NO_PRODUCER_EPOCH: i16 = -1;
-/// Fetch: last stable offset unknown (transactions not yet supported).
+/// Fetch: last stable offset unknown (partition could not be resolved).
 pub const NO_LAST_STABLE_OFFSET: i64 = -1;
 
 pub const ENDPOINT_TYPE_BROKERS: i8 = 1;
 pub const ALL_OPERATIONS_AUTHORIZED: i32 = -1;
+
+/// Fetch: isolation_level=1 — only return records below the last stable offset
+/// and drop records of aborted transactions.
+pub const ISOLATION_LEVEL_READ_COMMITTED: i8 = 1;
+
+/// Transactions: control record key is (version: i16, type: i16).
+pub const CONTROL_RECORD_VERSION: i16 = 0;
+/// Transactions: control record type of an abort marker.
+pub const CONTROL_TYPE_ABORT: i16 = 0;
+/// Transactions: control record type of a commit marker.
+pub const CONTROL_TYPE_COMMIT: i16 = 1;
+
+/// Transactions: upper bound on a producer's transaction.timeout.ms (Kafka's
+/// transaction.max.timeout.ms default).
+pub const MAX_TRANSACTION_TIMEOUT_MS: i32 = 900_000;
+/// Transactions: how often the coordinator node looks for timed-out
+/// transactions and aborts them.
+pub const TRANSACTION_TIMEOUT_CHECK_INTERVAL_MS: u64 = 10_000;
+/// Transactions: how often every node catches up with the transaction log.
+/// read_committed fetch holds back records until their marker is seen here.
+pub const TRANSACTION_LOG_SYNC_INTERVAL_MS: u64 = 500;
+/// Transactions: Produce version a transactional produce is forwarded to the
+/// coordinator node at (the highest advertised, so nothing is lost).
+pub const FORWARD_PRODUCE_VERSION: i16 = 7;
+
+/// Share groups: acknowledge type for an offset with no record behind it.
+pub const SHARE_ACK_GAP: i8 = 0;
+/// Share groups: the record was processed; it is never delivered again.
+pub const SHARE_ACK_ACCEPT: i8 = 1;
+/// Share groups: give the record back so it can be redelivered.
+pub const SHARE_ACK_RELEASE: i8 = 2;
+/// Share groups: the record could not be processed; it is never delivered again.
+pub const SHARE_ACK_REJECT: i8 = 3;
+
+/// Share groups: share_session_epoch that opens a new share session.
+pub const SHARE_SESSION_INITIAL_EPOCH: i32 = 0;
+/// Share groups: share_session_epoch that closes the share session.
+pub const SHARE_SESSION_FINAL_EPOCH: i32 = -1;
+
+/// Share groups: how long a consumer holds the records it acquired before they
+/// become available to other consumers (group.share.record.lock.duration.ms).
+pub const SHARE_RECORD_LOCK_DURATION_MS: u64 = 30_000;
+/// Share groups: deliveries after which a record is archived instead of being
+/// made available again (group.share.delivery.count.limit).
+pub const SHARE_DELIVERY_COUNT_LIMIT: i16 = 5;
+/// Share groups: cap on in-flight records per share-partition
+/// (group.share.partition.max.record.locks).
+pub const SHARE_PARTITION_MAX_RECORD_LOCKS: usize = 2000;
+/// Share groups: how often expired acquisition locks and members are reaped.
+pub const SHARE_LOCK_CHECK_INTERVAL_MS: u64 = 1000;
+/// Share groups: a member that has not heartbeated for this long is removed
+/// and its acquired records released (group.share.session.timeout.ms).
+pub const SHARE_GROUP_SESSION_TIMEOUT_MS: u64 = 45_000;
+/// Share groups: heartbeat interval handed to members
+/// (group.share.heartbeat.interval.ms).
+pub const SHARE_GROUP_HEARTBEAT_INTERVAL_MS: i32 = 5000;
+/// Share groups: records acquired per ShareFetch when the request sets no limit.
+pub const SHARE_DEFAULT_MAX_RECORDS: i32 = 500;
+
+/// How long a topic's `compression.type` is cached before it is read again
+/// from the resource-config store. Bounds how stale it can be on brokers that
+/// did not handle the AlterConfigs.
+pub const TOPIC_COMPRESSION_CACHE_TTL_MS: u64 = 10_000;
+
+/// ConsumerGroupHeartbeat: error code for an unparsable subscribed topic
+/// regex (INVALID_REGULAR_EXPRESSION, Kafka 4.0; not in `ResponseError`).
+pub const INVALID_REGULAR_EXPRESSION: i16 = 128;
+/// ConsumerGroupHeartbeat: how often subscribed topic regexes are resolved
+/// against the topic list again, so topics created elsewhere are picked up.
+pub const CONSUMER_REGEX_REFRESH_INTERVAL_MS: u64 = 10_000;
@@ -14,6 +14,7 @@
 
 use std::collections::HashMap;
 
+use regex::Regex;
 use uuid::Uuid;
 
 pub const DEFAULT_ASSIGNOR: &str = "range";
@@ -31,6 +32,13 @@ pub fn topic_uuid(tenant: &str, topic_name: &str) -> Uuid {
 // member_id -> (topic_id -> partitions)
 pub type TargetAssignment = HashMap<String, HashMap<Uuid, Vec<i32>>>;
 
+// A topic regex some member subscribes with, and the topics it matched when
+// last resolved (sorted).
+pub struct RegexSubscription {
+    pub regex: Regex,
+    pub topics: Vec<String>,
+}
+
 pub struct ConsumerGroupMeta {
     pub group_id: String,
     // Bumped on every membership/subscription change.
@@ -43,6 +51,11 @@ pub struct ConsumerGroupMeta {
     // Current owner of each partition, maintained from member reports; a partition
     // is only granted to its target member once the previous owner released it.
     pub owner: HashMap<(Uuid, i32), String>,
+    // Subscribed topic regexes, keyed by pattern.
+    pub regex_subscriptions: HashMap<String, RegexSubscription>,
+    // When the regexes were last resolved against the topic list; 0 forces a
+    // refresh on the next heartbeat.
+    pub regex_refreshed_ms: u128,
 }
 
 impl ConsumerGroupMeta {
@@ -55,6 +68,8 @@ pub fn new(group_id: String) -> Self {
             members: HashMap::new(),
             target: HashMap::new(),
             owner: HashMap::new(),
+            regex_subscriptions: HashMap::new(),
+            regex_refreshed_ms: 0,
         }
     }
 
@@ -75,6 +90,21 @@ pub fn state_name(&self) -> &'static str {
     pub fn member_target(&self, member_id: &str) -> HashMap<Uuid, Vec<i32>> {
         self.target.get(member_id).cloned().unwrap_or_default()
     }
+
+    // Explicitly named topics plus whatever the member's regex matched.
+    pub fn member_subscription(&self, member: &ConsumerMemberMeta) -> Vec<String> {
+        let mut topics = member.subscribed.clone();
+        if let Some(sub) = member
+            .subscribed_regex
+            .as_ref()
+            .and_then(|p| self.regex_subscriptions.get(p))
+        {
+            topics.extend(sub.topics.iter().cloned());
+            topics.sort();
+            topics.dedup();
+        }
+        topics
+    }
 }
 
 pub struct ConsumerMemberMeta {
@@ -84,6 +114,7 @@ pub struct ConsumerMemberMeta {
     pub client_id: String,
     pub rebalance_timeout_ms: i32,
     pub subscribed: Vec<String>,
+    pub subscribed_regex: Option<String>,
     // What the member last reported it actually owns.
     pub reported: HashMap<Uuid, Vec<i32>>,
     pub member_epoch: i32,
@@ -99,6 +130,7 @@ pub struct ConsumerDescribedMember {
     pub client_id: String,
     pub member_epoch: i32,
     pub subscribed: Vec<String>,
+    pub subscribed_regex: Option<String>,
     pub assignment: HashMap<Uuid, Vec<i32>>,
     pub target_assignment: HashMap<Uuid, Vec<i32>>,
 }
@@ -120,8 +152,8 @@ pub(crate) fn describe(group: &ConsumerGroupMeta, tenant: &str) -> ConsumerDescr
         .members
         .values()
         .map(|m| {
-            for name in &m.subscribed {
-                topic_names.insert(topic_uuid(tenant, name), name.clone());
+            for name in group.member_subscription(m) {
+                topic_names.insert(topic_uuid(tenant, &name), name);
             }
             ConsumerDescribedMember {
                 member_id: m.member_id.clone(),
@@ -130,6 +162,7 @@ pub(crate) fn describe(group: &ConsumerGroupMeta, tenant: &str) -> ConsumerDescr
                 client_id: m.client_id.clone(),
                 member_epoch: m.member_epoch,
                 subscribed: m.subscribed.clone(),
+                subscribed_regex: m.subscribed_regex.clone(),
                 assignment: m.reported.clone(),
                 target_assignment: group.member_target(&m.member_id),
             }
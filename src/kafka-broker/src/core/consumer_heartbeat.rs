@@ -12,13 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use kafka_protocol::error::ResponseError;
+use regex::Regex;
 use uuid::Uuid;
 
 use crate::core::assignor::{compute_target, TopicMeta};
-use crate::core::consumer_group_meta::{ConsumerGroupMeta, ConsumerMemberMeta};
+use crate::core::constants::{CONSUMER_REGEX_REFRESH_INTERVAL_MS, INVALID_REGULAR_EXPRESSION};
+use crate::core::consumer_group_meta::{ConsumerGroupMeta, ConsumerMemberMeta, RegexSubscription};
 
 pub const LEAVE_EPOCH: i32 = -1;
 pub const STATIC_LEAVE_EPOCH: i32 = -2;
@@ -32,6 +34,8 @@ pub struct ConsumerHeartbeatParams {
     pub client_id: String,
     pub rebalance_timeout_ms: i32,
     pub subscribed_topics: Option<Vec<String>>,
+    // None means unchanged; an empty string clears the member's regex.
+    pub subscribed_topic_regex: Option<String>,
     pub server_assignor: Option<String>,
     pub owned: Option<Vec<(Uuid, Vec<i32>)>>,
 }
@@ -59,10 +63,16 @@ pub(crate) fn heartbeat_error(
     }
 }
 
+// Kafka matches a subscribed regex against the whole topic name.
+pub(crate) fn compile_topic_regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| e.to_string())
+}
+
 pub(crate) fn heartbeat(
     group: &mut ConsumerGroupMeta,
     params: ConsumerHeartbeatParams,
     resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
+    list_topics: &dyn Fn() -> Vec<String>,
     now_ms: u128,
 ) -> ConsumerHeartbeatResult {
     let member_id = params.member_id.clone();
@@ -71,8 +81,24 @@ pub(crate) fn heartbeat(
         return leave(group, &member_id);
     }
 
+    // Some(None) clears the member's regex; None leaves it as it is.
+    let regex = match params.subscribed_topic_regex.as_deref() {
+        None => None,
+        Some("") => Some(None),
+        Some(pattern) => match compile_topic_regex(pattern) {
+            Ok(regex) => Some(Some((pattern.to_string(), regex))),
+            Err(e) => {
+                return heartbeat_error(
+                    INVALID_REGULAR_EXPRESSION,
+                    &format!("invalid subscribed topic regex: {}", e),
+                    &member_id,
+                );
+            }
+        },
+    };
+
     if params.member_epoch == 0 {
-        if let Err(result) = join(group, &params, now_ms) {
+        if let Err(result) = join(group, &params, regex.flatten(), list_topics, now_ms) {
             return result;
         }
     } else {
@@ -102,18 +128,30 @@ pub(crate) fn heartbeat(
                     subscription_changed = true;
                 }
             }
+            if let Some(regex) = &regex {
+                let pattern = regex.as_ref().map(|(p, _)| p.clone());
+                if pattern != member.subscribed_regex {
+                    member.subscribed_regex = pattern;
+                    subscription_changed = true;
+                }
+            }
+        }
+        if let Some(Some((pattern, regex))) = regex {
+            add_regex_subscription(group, pattern, regex, list_topics);
         }
         if subscription_changed {
             group.group_epoch += 1;
         }
     }
 
+    refresh_regex_subscriptions(group, list_topics, now_ms);
+
     if let Some(owned) = params.owned {
         apply_reported(group, &member_id, owned);
     }
 
     if group.assignment_epoch < group.group_epoch {
-        group.target = compute_target(&group.members, resolve_topic);
+        group.target = compute_target(group, resolve_topic);
         group.assignment_epoch = group.group_epoch;
     }
 
@@ -144,6 +182,8 @@ pub(crate) fn heartbeat(
 fn join(
     group: &mut ConsumerGroupMeta,
     params: &ConsumerHeartbeatParams,
+    regex: Option<(String, Regex)>,
+    list_topics: &dyn Fn() -> Vec<String>,
     now_ms: u128,
 ) -> Result<(), ConsumerHeartbeatResult> {
     if params.member_id.is_empty() {
@@ -153,13 +193,14 @@ fn join(
             &params.member_id,
         ));
     }
-    let Some(subscribed) = params.subscribed_topics.clone() else {
+    if params.subscribed_topics.is_none() && regex.is_none() {
         return Err(heartbeat_error(
             ResponseError::InvalidRequest.code(),
-            "subscribed topics are required when joining",
+            "subscribed topic names or regex are required when joining",
             &params.member_id,
         ));
-    };
+    }
+    let subscribed = params.subscribed_topics.clone().unwrap_or_default();
     if let Some(assignor) = &params.server_assignor {
         if group.members.is_empty() {
             group.assignor = assignor.clone();
@@ -172,6 +213,11 @@ fn join(
         }
     }
 
+    let subscribed_regex = regex.as_ref().map(|(p, _)| p.clone());
+    if let Some((pattern, regex)) = regex {
+        add_regex_subscription(group, pattern, regex, list_topics);
+    }
+
     // A rejoin starts from a clean slate: whatever the old incarnation owned is released.
     group.owner.retain(|_, o| o != &params.member_id);
     group.group_epoch += 1;
@@ -184,6 +230,7 @@ fn join(
             client_id: params.client_id.clone(),
             rebalance_timeout_ms: params.rebalance_timeout_ms,
             subscribed,
+            subscribed_regex,
             reported: HashMap::new(),
             member_epoch: group.group_epoch,
             last_sent: None,
@@ -193,6 +240,74 @@ fn join(
     Ok(())
 }
 
+// A pattern seen for the first time is resolved right away; the membership
+// change that brought it in already bumps the group epoch.
+fn add_regex_subscription(
+    group: &mut ConsumerGroupMeta,
+    pattern: String,
+    regex: Regex,
+    list_topics: &dyn Fn() -> Vec<String>,
+) {
+    if group.regex_subscriptions.contains_key(&pattern) {
+        return;
+    }
+    let topics = matching_topics(&regex, &list_topics());
+    group
+        .regex_subscriptions
+        .insert(pattern, RegexSubscription { regex, topics });
+}
+
+fn matching_topics(regex: &Regex, topics: &[String]) -> Vec<String> {
+    let mut matched: Vec<String> = topics
+        .iter()
+        .filter(|t| regex.is_match(t))
+        .cloned()
+        .collect();
+    matched.sort();
+    matched
+}
+
+// Re-resolve every regex against the current topic list, at most once per
+// CONSUMER_REGEX_REFRESH_INTERVAL_MS unless a topic was created or deleted in
+// the meantime. A different match set starts a new assignment epoch.
+pub(crate) fn refresh_regex_subscriptions(
+    group: &mut ConsumerGroupMeta,
+    list_topics: &dyn Fn() -> Vec<String>,
+    now_ms: u128,
+) {
+    let in_use: HashSet<&String> = group
+        .members
+        .values()
+        .filter_map(|m| m.subscribed_regex.as_ref())
+        .collect();
+    group
+        .regex_subscriptions
+        .retain(|pattern, _| in_use.contains(pattern));
+    if group.regex_subscriptions.is_empty() {
+        return;
+    }
+    if group.regex_refreshed_ms != 0
+        && now_ms.saturating_sub(group.regex_refreshed_ms)
+            < CONSUMER_REGEX_REFRESH_INTERVAL_MS as u128
+    {
+        return;
+    }
+
+    let topics = list_topics();
+    let mut changed = false;
+    for sub in group.regex_subscriptions.values_mut() {
+        let matched = matching_topics(&sub.regex, &topics);
+        if matched != sub.topics {
+            sub.topics = matched;
+            changed = true;
+        }
+    }
+    group.regex_refreshed_ms = now_ms;
+    if changed {
+        group.group_epoch += 1;
+    }
+}
+
 fn leave(group: &mut ConsumerGroupMeta, member_id: &str) -> ConsumerHeartbeatResult {
     if group.members.remove(member_id).is_none() {
         return heartbeat_error(
@@ -335,14 +450,52 @@ fn hb(
                 } else {
                     None
                 },
+                subscribed_topic_regex: None,
                 server_assignor: None,
                 owned,
             },
             &resolve,
+            &|| vec!["t".to_string()],
             1,
         )
     }
 
+    fn any_topic(name: &str) -> Option<TopicMeta> {
+        Some(TopicMeta {
+            topic_id: Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes()),
+            partitions: 2,
+        })
+    }
+
+    fn regex_hb(
+        group: &mut ConsumerGroupMeta,
+        epoch: i32,
+        regex: Option<&str>,
+        topics: &[&str],
+        now_ms: u128,
+    ) -> ConsumerHeartbeatResult {
+        let topics: Vec<String> = topics.iter().map(|t| t.to_string()).collect();
+        heartbeat(
+            group,
+            ConsumerHeartbeatParams {
+                group_id: "g".to_string(),
+                member_id: "m1".to_string(),
+                member_epoch: epoch,
+                instance_id: None,
+                rack_id: None,
+                client_id: "c".to_string(),
+                rebalance_timeout_ms: 60_000,
+                subscribed_topics: None,
+                subscribed_topic_regex: regex.map(|r| r.to_string()),
+                server_assignor: None,
+                owned: None,
+            },
+            &any_topic,
+            &move || topics.clone(),
+            now_ms,
+        )
+    }
+
     #[test]
     fn single_member_joins_and_converges() {
         let mut g = ConsumerGroupMeta::new("g".to_string());
@@ -419,6 +572,53 @@ fn leave_frees_partitions_for_survivors() {
         assert_eq!(r2.assignment.unwrap()[&topic_id()], vec![0, 1, 2, 3]);
     }
 
+    #[test]
+    fn regex_subscription_is_assigned_matching_topics() {
+        let mut g = ConsumerGroupMeta::new("g".to_string());
+        let r = regex_hb(&mut g, 0, Some("events\\..*"), &["events.a", "other"], 1);
+        assert_eq!(r.error_code, 0);
+
+        let assigned = r.assignment.unwrap();
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(
+            assigned[&any_topic("events.a").unwrap().topic_id],
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn regex_refresh_picks_up_new_topics_with_a_new_epoch() {
+        let mut g = ConsumerGroupMeta::new("g".to_string());
+        let r = regex_hb(&mut g, 0, Some("events\\..*"), &["events.a"], 1);
+        let epoch = r.member_epoch;
+
+        // Inside the refresh interval the new topic is not seen yet.
+        regex_hb(&mut g, epoch, None, &["events.a", "events.b"], 2);
+        assert_eq!(g.group_epoch, epoch);
+
+        // A topic change forces the next heartbeat to resolve again.
+        g.regex_refreshed_ms = 0;
+        let r = regex_hb(&mut g, epoch, None, &["events.a", "events.b"], 3);
+        assert_eq!(g.group_epoch, epoch + 1);
+        let assigned = r.assignment.unwrap();
+        assert!(assigned.contains_key(&any_topic("events.b").unwrap().topic_id));
+    }
+
+    #[test]
+    fn invalid_regex_is_rejected() {
+        let mut g = ConsumerGroupMeta::new("g".to_string());
+        let r = regex_hb(&mut g, 0, Some("events.(("), &[], 1);
+        assert_eq!(r.error_code, INVALID_REGULAR_EXPRESSION);
+        assert!(g.members.is_empty());
+    }
+
+    #[test]
+    fn join_without_names_or_regex_is_rejected() {
+        let mut g = ConsumerGroupMeta::new("g".to_string());
+        let r = regex_hb(&mut g, 0, None, &[], 1);
+        assert_eq!(r.error_code, ResponseError::InvalidRequest.code());
+    }
+
     #[test]
     fn session_expiry_removes_member_and_bumps_epoch() {
         let mut g = ConsumerGroupMeta::new("g".to_string());
@@ -90,7 +90,9 @@ fn ensure_reaper_started(&self) {
 
     pub async fn join_group(&self, params: JoinGroupParams) -> JoinResult {
         self.ensure_reaper_started();
-        if self.cache.has_consumer_group(&params.group_id) {
+        if self.cache.has_consumer_group(&params.group_id)
+            || self.cache.has_share_group(&params.group_id)
+        {
             return join_error(
                 ResponseError::InconsistentGroupProtocol.code(),
                 params.member_id.clone(),
@@ -197,10 +199,17 @@ pub fn consumer_heartbeat(
         &self,
         params: ConsumerHeartbeatParams,
         resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
+        list_topics: &dyn Fn() -> Vec<String>,
     ) -> ConsumerHeartbeatResult {
         self.ensure_reaper_started();
         self.cache
-            .consumer_heartbeat(params, resolve_topic, now_millis())
+            .consumer_heartbeat(params, resolve_topic, list_topics, now_millis())
+    }
+
+    // Topics were created or deleted: regex subscriptions are resolved again
+    // on each group's next heartbeat.
+    pub fn topics_changed(&self) {
+        self.cache.expire_regex_subscriptions();
     }
 
     pub fn describe_consumer_group(
@@ -745,10 +754,12 @@ async fn classic_join_rejected_when_consumer_group_owns_the_id() {
                 client_id: "c".to_string(),
                 rebalance_timeout_ms: 60_000,
                 subscribed_topics: Some(vec!["t".to_string()]),
+                subscribed_topic_regex: None,
                 server_assignor: None,
                 owned: None,
             },
             &resolve,
+            &Vec::new,
         );
 
         let r = coord.join_group(params("g", "m1", vec!["range"])).await;
@@ -771,13 +782,14 @@ async fn delete_group_covers_consumer_groups() {
             client_id: "c".to_string(),
             rebalance_timeout_ms: 60_000,
             subscribed_topics: Some(vec!["t".to_string()]),
+            subscribed_topic_regex: None,
             server_assignor: None,
             owned: None,
         };
-        coord.consumer_heartbeat(hb(0), &resolve);
+        coord.consumer_heartbeat(hb(0), &resolve, &Vec::new);
 
         assert_eq!(coord.delete_group("g"), ResponseError::NonEmptyGroup.code());
-        coord.consumer_heartbeat(hb(-1), &resolve);
+        coord.consumer_heartbeat(hb(-1), &resolve, &Vec::new);
         assert_eq!(coord.delete_group("g"), 0);
         assert_eq!(
             coord.delete_group("g"),
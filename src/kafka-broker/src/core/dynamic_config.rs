@@ -63,9 +63,10 @@ pub struct DynamicConfigKey {
 /// `kafka-configs.sh --entity-type topics --alter` operates on. Source of
 /// truth is Kafka's `org.apache.kafka.common.config.TopicConfig`.
 ///
-/// Only three currently map to a real RobustMQ field
-/// (`metadata_struct::storage::shard::EngineShardConfig`): `retention.ms`,
-/// `segment.bytes`, `min.insync.replicas`. Everything else is listed so a
+/// Only four currently map to something RobustMQ applies: `retention.ms`,
+/// `segment.bytes`, `min.insync.replicas`
+/// (`metadata_struct::storage::shard::EngineShardConfig`) and
+/// `compression.type` (read by Produce/Fetch). Everything else is listed so a
 /// future `process_alter_configs` can tell "unsupported but valid Kafka
 /// config" apart from "not a real Kafka config at all" (`InvalidConfig`
 /// vs `InvalidRequest`), and report the former as a no-op rather than an
@@ -81,7 +82,7 @@ pub struct DynamicConfigKey {
         name: "compression.type",
         default: "producer",
         description: "Compression codec applied to a topic's stored records.",
-        robustmq_field: None,
+        robustmq_field: Some("core::compression::TopicCompression"),
     },
     DynamicConfigKey {
         name: "delete.retention.ms",
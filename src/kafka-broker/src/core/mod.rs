@@ -15,6 +15,7 @@
 pub mod acl;
 pub mod assignor;
 pub mod cache;
+pub mod compression;
 pub mod constants;
 pub mod consumer_group_meta;
 pub mod consumer_heartbeat;
@@ -27,4 +28,9 @@
 pub mod join;
 pub mod leave;
 pub mod sasl;
+pub mod share_coordinator;
+pub mod share_group_meta;
+pub mod share_heartbeat;
+pub mod share_partition;
 pub mod sync;
+pub mod txn_coordinator;
@@ -0,0 +1,618 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_base::tools::now_millis;
+use common_config::broker::broker_config;
+use dashmap::DashMap;
+use grpc_clients::meta::kafka::call::{
+    delete_kafka_share_partition_state, list_kafka_share_partition_state,
+    set_kafka_share_partition_state,
+};
+use kafka_protocol::error::ResponseError;
+use metadata_struct::adapter::adapter_read_config::AdapterReadConfig;
+use metadata_struct::kafka::share_group::KafkaSharePartitionState;
+use metadata_struct::storage::record::StorageRecord;
+use protocol::meta::meta_service_kafka::{
+    DeleteKafkaSharePartitionStateRequest, ListKafkaSharePartitionStateRequest,
+    SetKafkaSharePartitionStateRequest,
+};
+use storage_adapter::driver::StorageDriverManager;
+use tokio::sync::{Mutex, OnceCell};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::core::assignor::TopicMeta;
+use crate::core::cache::KafkaCacheManager;
+use crate::core::constants::{
+    SHARE_GROUP_SESSION_TIMEOUT_MS, SHARE_LOCK_CHECK_INTERVAL_MS, SHARE_RECORD_LOCK_DURATION_MS,
+    SHARE_SESSION_FINAL_EPOCH, SHARE_SESSION_INITIAL_EPOCH,
+};
+use crate::core::consumer_heartbeat::ConsumerHeartbeatResult;
+use crate::core::coordinator_locator::is_coordinator_node;
+use crate::core::share_group_meta::ShareDescribedGroup;
+use crate::core::share_heartbeat::ShareHeartbeatParams;
+use crate::core::share_partition::SharePartition;
+use crate::handler::tenant::get_tenant;
+
+// (group_id, topic, partition)
+type SharePartitionKey = (String, String, i32);
+
+// A share session (KIP-932): the partitions a member fetches from, plus the
+// epoch its next ShareFetch/ShareAcknowledge must carry.
+struct ShareSession {
+    next_epoch: i32,
+    partitions: BTreeSet<(Uuid, i32)>,
+}
+
+/// Records acquired from one share-partition by a ShareFetch.
+pub struct ShareAcquired {
+    pub shard_name: String,
+    pub records: Vec<StorageRecord>,
+    // (offset, delivery_count), ascending by offset.
+    pub acquired: Vec<(i64, i16)>,
+    pub high_watermark: i64,
+}
+
+/// Share group coordinator (KIP-932). Membership is kept in `KafkaCacheManager`
+/// like consumer groups; the in-flight state of every share-partition is kept
+/// here and written through to meta-service as a full snapshot after each
+/// change, so a failed write is repaired by the next one. Only the group
+/// coordinator node serves share-partitions; any other node redirects the
+/// client and drops what it held, so the state is loaded from meta-service
+/// again if the role comes back.
+pub struct ShareGroupCoordinator {
+    sdm: Arc<StorageDriverManager>,
+    cache: Arc<KafkaCacheManager>,
+    partitions: DashMap<SharePartitionKey, Arc<Mutex<SharePartition>>>,
+    // Persisted state is loaded once per group, before its first use.
+    loaded_groups: DashMap<String, Arc<OnceCell<()>>>,
+    sessions: DashMap<(String, String), ShareSession>,
+    reaper_started: AtomicBool,
+}
+
+impl ShareGroupCoordinator {
+    pub fn new(sdm: Arc<StorageDriverManager>, cache: Arc<KafkaCacheManager>) -> Self {
+        ShareGroupCoordinator {
+            sdm,
+            cache,
+            partitions: DashMap::with_capacity(8),
+            loaded_groups: DashMap::with_capacity(8),
+            sessions: DashMap::with_capacity(8),
+            reaper_started: AtomicBool::new(false),
+        }
+    }
+
+    // The coordinator is constructed outside the tokio runtime during broker
+    // startup, so the reaper is spawned lazily from the first share request.
+    pub fn ensure_reaper_started(self: &Arc<Self>) {
+        if self.reaper_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let coordinator = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(SHARE_LOCK_CHECK_INTERVAL_MS)).await;
+                if is_coordinator_node(&coordinator.sdm).await {
+                    coordinator.reap(now_millis()).await;
+                } else {
+                    coordinator.drop_local_state();
+                }
+            }
+        });
+    }
+
+    /// Forget every share-partition and session held here, once this node is
+    /// no longer the coordinator; its snapshots must not overwrite the owner's.
+    pub fn drop_local_state(&self) {
+        self.partitions.clear();
+        self.loaded_groups.clear();
+        self.sessions.clear();
+    }
+
+    pub fn heartbeat(
+        self: &Arc<Self>,
+        params: ShareHeartbeatParams,
+        resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
+    ) -> ConsumerHeartbeatResult {
+        self.ensure_reaper_started();
+        self.cache
+            .share_heartbeat(params, resolve_topic, now_millis())
+    }
+
+    pub fn describe(&self, group_id: &str, tenant: &str) -> Option<ShareDescribedGroup> {
+        self.cache.describe_share_group(group_id, tenant)
+    }
+
+    /// Drop a member's share sessions and make every record it holds
+    /// available again.
+    pub async fn release_member(&self, group_id: &str, member_id: &str) {
+        self.sessions
+            .remove(&(group_id.to_string(), member_id.to_string()));
+        for (key, partition) in self.group_partitions(group_id, None) {
+            let mut sp = partition.lock().await;
+            if sp.release_member(member_id) {
+                self.persist(&key, &sp).await;
+            }
+        }
+    }
+
+    /// Validate `epoch` against the member's share session and apply the
+    /// partitions added/forgotten by this request. Returns the session's
+    /// partitions; when `epoch` closes the session they are the partitions
+    /// whose records the member gives back.
+    pub fn update_session(
+        &self,
+        group_id: &str,
+        member_id: &str,
+        epoch: i32,
+        added: &[(Uuid, i32)],
+        forgotten: &[(Uuid, i32)],
+    ) -> Result<Vec<(Uuid, i32)>, ResponseError> {
+        let key = (group_id.to_string(), member_id.to_string());
+        if epoch == SHARE_SESSION_FINAL_EPOCH {
+            let (_, session) = self
+                .sessions
+                .remove(&key)
+                .ok_or(ResponseError::ShareSessionNotFound)?;
+            return Ok(session.partitions.into_iter().collect());
+        }
+        if epoch == SHARE_SESSION_INITIAL_EPOCH {
+            self.sessions.insert(
+                key.clone(),
+                ShareSession {
+                    next_epoch: 0,
+                    partitions: BTreeSet::new(),
+                },
+            );
+        }
+
+        let mut session = self
+            .sessions
+            .get_mut(&key)
+            .ok_or(ResponseError::ShareSessionNotFound)?;
+        if session.next_epoch != epoch {
+            return Err(ResponseError::InvalidShareSessionEpoch);
+        }
+        session.next_epoch = next_session_epoch(epoch);
+        session.partitions.extend(added.iter().copied());
+        for p in forgotten {
+            session.partitions.remove(p);
+        }
+        Ok(session.partitions.iter().copied().collect())
+    }
+
+    /// ShareAcknowledge carries acknowledgements only, so it can never open a
+    /// session; it moves an existing one on exactly like ShareFetch does.
+    pub fn check_ack_session(
+        &self,
+        group_id: &str,
+        member_id: &str,
+        epoch: i32,
+    ) -> Result<Vec<(Uuid, i32)>, ResponseError> {
+        if epoch == SHARE_SESSION_INITIAL_EPOCH {
+            return Err(ResponseError::InvalidShareSessionEpoch);
+        }
+        self.update_session(group_id, member_id, epoch, &[], &[])
+    }
+
+    /// Acquire up to `max_records` records of `topic`/`partition` for
+    /// `member_id`, starting at the oldest record available to the group.
+    pub async fn acquire(
+        self: &Arc<Self>,
+        group_id: &str,
+        member_id: &str,
+        topic: &str,
+        partition: i32,
+        max_records: usize,
+        max_bytes: u64,
+    ) -> Result<ShareAcquired, ResponseError> {
+        self.ensure_reaper_started();
+        let (topic_meta, driver) = self
+            .sdm
+            .resolve_topic_driver(get_tenant(), topic)
+            .await
+            .map_err(|_| ResponseError::UnknownTopicOrPartition)?;
+        let shard_name = topic_meta
+            .storage_name_list
+            .get(&(partition as u32))
+            .cloned()
+            .ok_or(ResponseError::UnknownTopicOrPartition)?;
+        let detail = driver
+            .list_shard(Some(shard_name.clone()))
+            .await
+            .ok()
+            .and_then(|mut details| details.pop())
+            .ok_or(ResponseError::UnknownTopicOrPartition)?;
+        let high_watermark = detail.offset.high_watermark as i64;
+        let log_start_offset = detail.offset.start_offset as i64;
+
+        self.load_group(group_id).await?;
+        let key = (group_id.to_string(), topic.to_string(), partition);
+        // A share-partition without persisted state starts at the end of the
+        // log (group.share.auto.offset.reset=latest).
+        let share_partition = self
+            .partitions
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(SharePartition::new(high_watermark))))
+            .clone();
+
+        let mut sp = share_partition.lock().await;
+        sp.truncate_to(log_start_offset);
+        let fetch_offset = sp.next_fetch_offset();
+        let mut result = ShareAcquired {
+            shard_name: shard_name.clone(),
+            records: Vec::new(),
+            acquired: Vec::new(),
+            high_watermark,
+        };
+        if fetch_offset >= high_watermark || max_records == 0 {
+            return Ok(result);
+        }
+
+        let read_config = AdapterReadConfig {
+            max_record_num: u64::MAX,
+            max_size: max_bytes,
+        };
+        let records = match driver
+            .read_by_offset(&shard_name, fetch_offset as u64, &read_config)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                warn!(
+                    "Kafka ShareFetch read failed for shard {}: {}",
+                    shard_name, e
+                );
+                return Ok(result);
+            }
+        };
+
+        let now = now_millis() as u64;
+        let mut archived = false;
+        for record in records {
+            if result.acquired.len() >= max_records {
+                break;
+            }
+            let offset = record.metadata.offset as i64;
+            let control = record
+                .protocol_data
+                .as_ref()
+                .and_then(|d| d.kafka.as_ref())
+                .is_some_and(|k| k.control);
+            if control {
+                sp.archive(offset);
+                archived = true;
+                continue;
+            }
+            if let Some(delivery_count) =
+                sp.acquire(offset, member_id, now, SHARE_RECORD_LOCK_DURATION_MS)
+            {
+                result.acquired.push((offset, delivery_count));
+                result.records.push(record);
+            }
+        }
+        if archived {
+            self.persist(&key, &sp).await;
+        }
+        Ok(result)
+    }
+
+    /// Apply ShareFetch/ShareAcknowledge acknowledgement batches
+    /// (first_offset, last_offset, acknowledge_types) and return the
+    /// partition-level error code.
+    pub async fn acknowledge(
+        &self,
+        group_id: &str,
+        member_id: &str,
+        topic: &str,
+        partition: i32,
+        batches: &[(i64, i64, Vec<i8>)],
+    ) -> i16 {
+        if let Err(e) = self.load_group(group_id).await {
+            return e.code();
+        }
+        let key = (group_id.to_string(), topic.to_string(), partition);
+        let Some(share_partition) = self.partitions.get(&key).map(|p| p.clone()) else {
+            return ResponseError::InvalidRecordState.code();
+        };
+
+        let mut sp = share_partition.lock().await;
+        let mut error_code = 0;
+        let mut changed = false;
+        for (first_offset, last_offset, ack_types) in batches {
+            match sp.acknowledge(member_id, *first_offset, *last_offset, ack_types) {
+                Ok(()) => changed = true,
+                Err(e) => {
+                    error_code = e.code();
+                    break;
+                }
+            }
+        }
+        if changed {
+            self.persist(&key, &sp).await;
+        }
+        error_code
+    }
+
+    /// Start offset of a share-partition, or None when the group has never
+    /// consumed from it.
+    pub async fn start_offset(
+        &self,
+        group_id: &str,
+        topic: &str,
+        partition: i32,
+    ) -> Result<Option<i64>, ResponseError> {
+        self.load_group(group_id).await?;
+        let key = (group_id.to_string(), topic.to_string(), partition);
+        let Some(share_partition) = self.partitions.get(&key).map(|p| p.clone()) else {
+            return Ok(None);
+        };
+        let start_offset = share_partition.lock().await.start_offset;
+        Ok(Some(start_offset))
+    }
+
+    /// Partitions of `topic` the group has share-partition state for.
+    pub async fn partitions_of(
+        &self,
+        group_id: &str,
+        topic: &str,
+    ) -> Result<Vec<i32>, ResponseError> {
+        self.load_group(group_id).await?;
+        let mut partitions: Vec<i32> = self
+            .group_partitions(group_id, Some(topic))
+            .into_iter()
+            .map(|((_, _, partition), _)| partition)
+            .collect();
+        partitions.sort_unstable();
+        Ok(partitions)
+    }
+
+    /// Topics the group has share-partition state for.
+    pub async fn topics_of(&self, group_id: &str) -> Result<Vec<String>, ResponseError> {
+        self.load_group(group_id).await?;
+        let topics: BTreeSet<String> = self
+            .group_partitions(group_id, None)
+            .into_iter()
+            .map(|((_, topic, _), _)| topic)
+            .collect();
+        Ok(topics.into_iter().collect())
+    }
+
+    /// Move a share-partition to `start_offset`, dropping its in-flight state.
+    /// Only allowed while the group has no members.
+    pub async fn alter_start_offset(
+        &self,
+        group_id: &str,
+        topic: &str,
+        partition: i32,
+        start_offset: i64,
+    ) -> Result<(), ResponseError> {
+        if self.cache.share_group_member_count(group_id) > 0 {
+            return Err(ResponseError::NonEmptyGroup);
+        }
+        if start_offset < 0 {
+            return Err(ResponseError::InvalidRequest);
+        }
+        self.load_group(group_id).await?;
+        let key = (group_id.to_string(), topic.to_string(), partition);
+        let share_partition = self
+            .partitions
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(SharePartition::new(start_offset))))
+            .clone();
+        let mut sp = share_partition.lock().await;
+        sp.reset(start_offset);
+        self.write_state(sp.to_state(get_tenant(), group_id, topic, partition))
+            .await
+            .map_err(|e| {
+                warn!(
+                    "Failed to persist share-partition state for group {} {}-{}: {}",
+                    group_id, topic, partition, e
+                );
+                ResponseError::UnknownServerError
+            })
+    }
+
+    /// Forget the group's state for every partition of `topic`. Only allowed
+    /// while the group has no members.
+    pub async fn delete_topic_state(
+        &self,
+        group_id: &str,
+        topic: &str,
+    ) -> Result<(), ResponseError> {
+        if self.cache.share_group_member_count(group_id) > 0 {
+            return Err(ResponseError::NonEmptyGroup);
+        }
+        self.load_group(group_id).await?;
+        let client_pool = &self.sdm.engine_storage_handler.client_pool;
+        let addrs = broker_config().get_meta_service_addr();
+        for ((_, _, partition), _) in self.group_partitions(group_id, Some(topic)) {
+            let request = DeleteKafkaSharePartitionStateRequest {
+                tenant: get_tenant().to_string(),
+                group_id: group_id.to_string(),
+                topic: topic.to_string(),
+                partition,
+            };
+            if let Err(e) = delete_kafka_share_partition_state(client_pool, &addrs, request).await {
+                warn!(
+                    "Failed to delete share-partition state for group {} {}-{}: {}",
+                    group_id, topic, partition, e
+                );
+                return Err(ResponseError::UnknownServerError);
+            }
+            self.partitions
+                .remove(&(group_id.to_string(), topic.to_string(), partition));
+        }
+        Ok(())
+    }
+
+    async fn reap(&self, now_ms: u128) {
+        for (group_id, member_id) in self
+            .cache
+            .reap_share_members(now_ms, SHARE_GROUP_SESSION_TIMEOUT_MS)
+        {
+            self.release_member(&group_id, &member_id).await;
+        }
+
+        let all: Vec<(SharePartitionKey, Arc<Mutex<SharePartition>>)> = self
+            .partitions
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        for (key, partition) in all {
+            let mut sp = partition.lock().await;
+            if sp.release_expired(now_ms as u64) {
+                self.persist(&key, &sp).await;
+            }
+        }
+    }
+
+    fn group_partitions(
+        &self,
+        group_id: &str,
+        topic: Option<&str>,
+    ) -> Vec<(SharePartitionKey, Arc<Mutex<SharePartition>>)> {
+        self.partitions
+            .iter()
+            .filter(|entry| {
+                let (group, t, _) = entry.key();
+                group == group_id && topic.is_none_or(|topic| t == topic)
+            })
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
+    async fn load_group(&self, group_id: &str) -> Result<(), ResponseError> {
+        let cell = self
+            .loaded_groups
+            .entry(group_id.to_string())
+            .or_default()
+            .clone();
+        cell.get_or_try_init(|| async {
+            let states = self.read_states(group_id).await.map_err(|e| {
+                warn!(
+                    "Failed to load share-partition state for group {}: {}",
+                    group_id, e
+                );
+                ResponseError::CoordinatorLoadInProgress
+            })?;
+            for state in states {
+                self.partitions
+                    .entry((state.group_id.clone(), state.topic.clone(), state.partition))
+                    .or_insert_with(|| Arc::new(Mutex::new(SharePartition::from_state(&state))));
+            }
+            Ok(())
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn read_states(&self, group_id: &str) -> Result<Vec<KafkaSharePartitionState>, String> {
+        let client_pool = &self.sdm.engine_storage_handler.client_pool;
+        let addrs = broker_config().get_meta_service_addr();
+        let reply = list_kafka_share_partition_state(
+            client_pool,
+            &addrs,
+            ListKafkaSharePartitionStateRequest {
+                tenant: get_tenant().to_string(),
+                group_id: group_id.to_string(),
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let mut states = Vec::with_capacity(reply.states.len());
+        for raw in reply.states {
+            states.push(KafkaSharePartitionState::decode(&raw).map_err(|e| e.to_string())?);
+        }
+        Ok(states)
+    }
+
+    // The next successful snapshot supersedes a lost one, so a failed write
+    // is logged rather than failing the request that caused it.
+    async fn persist(&self, key: &SharePartitionKey, sp: &SharePartition) {
+        let (group_id, topic, partition) = key;
+        let state = sp.to_state(get_tenant(), group_id, topic, *partition);
+        if let Err(e) = self.write_state(state).await {
+            warn!(
+                "Failed to persist share-partition state for group {} {}-{}: {}",
+                group_id, topic, partition, e
+            );
+        }
+    }
+
+    async fn write_state(&self, state: KafkaSharePartitionState) -> Result<(), String> {
+        let client_pool = &self.sdm.engine_storage_handler.client_pool;
+        let addrs = broker_config().get_meta_service_addr();
+        let request = SetKafkaSharePartitionStateRequest {
+            state: state.encode().map_err(|e| e.to_string())?,
+        };
+        set_kafka_share_partition_state(client_pool, &addrs, request)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// Share session epochs wrap back to 1; 0 and -1 are reserved for opening and
+// closing a session.
+fn next_session_epoch(epoch: i32) -> i32 {
+    if epoch == i32::MAX {
+        1
+    } else {
+        epoch + 1
+    }
+}
+
+/// Collapse acquired (offset, delivery_count) pairs into
+/// (first_offset, last_offset, delivery_count) ranges of consecutive offsets.
+pub fn acquired_ranges(acquired: &[(i64, i16)]) -> Vec<(i64, i64, i16)> {
+    let mut ranges: Vec<(i64, i64, i16)> = Vec::new();
+    for &(offset, delivery_count) in acquired {
+        match ranges.last_mut() {
+            Some((_, last, count)) if *last + 1 == offset && *count == delivery_count => {
+                *last = offset;
+            }
+            _ => ranges.push((offset, offset, delivery_count)),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acquired_ranges_split_on_gaps_and_delivery_count() {
+        let acquired = [(3, 1), (4, 1), (5, 2), (6, 2), (8, 2), (9, 1)];
+        assert_eq!(
+            acquired_ranges(&acquired),
+            vec![(3, 4, 1), (5, 6, 2), (8, 8, 2), (9, 9, 1)]
+        );
+        assert!(acquired_ranges(&[]).is_empty());
+    }
+
+    #[test]
+    fn session_epochs_wrap_past_reserved_values() {
+        assert_eq!(next_session_epoch(0), 1);
+        assert_eq!(next_session_epoch(41), 42);
+        assert_eq!(next_session_epoch(i32::MAX), 1);
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::core::consumer_group_meta::{topic_uuid, TargetAssignment};
+
+pub const SHARE_ASSIGNOR: &str = "simple";
+
+// KIP-932 share group membership. Unlike consumer groups there is nothing to
+// revoke: records, not partitions, are what members own, so a member moves to
+// its new target as soon as the coordinator computes it.
+pub struct ShareGroupMeta {
+    pub group_id: String,
+    // Bumped on every membership/subscription change.
+    pub group_epoch: i32,
+    // The group_epoch the current target assignment was computed at.
+    pub assignment_epoch: i32,
+    pub members: HashMap<String, ShareMemberMeta>,
+    pub target: TargetAssignment,
+}
+
+impl ShareGroupMeta {
+    pub fn new(group_id: String) -> Self {
+        ShareGroupMeta {
+            group_id,
+            group_epoch: 0,
+            assignment_epoch: 0,
+            members: HashMap::new(),
+            target: HashMap::new(),
+        }
+    }
+
+    pub fn state_name(&self) -> &'static str {
+        if self.members.is_empty() {
+            "Empty"
+        } else {
+            "Stable"
+        }
+    }
+
+    pub fn member_target(&self, member_id: &str) -> HashMap<Uuid, Vec<i32>> {
+        self.target.get(member_id).cloned().unwrap_or_default()
+    }
+}
+
+pub struct ShareMemberMeta {
+    pub member_id: String,
+    pub rack_id: Option<String>,
+    pub client_id: String,
+    pub subscribed: Vec<String>,
+    pub member_epoch: i32,
+    // Last assignment sent, to omit `assignment` from responses when unchanged.
+    pub last_sent: Option<HashMap<Uuid, Vec<i32>>>,
+    pub last_heartbeat_ms: u128,
+}
+
+pub struct ShareDescribedMember {
+    pub member_id: String,
+    pub rack_id: Option<String>,
+    pub client_id: String,
+    pub member_epoch: i32,
+    pub subscribed: Vec<String>,
+    pub assignment: HashMap<Uuid, Vec<i32>>,
+}
+
+pub struct ShareDescribedGroup {
+    pub group_id: String,
+    pub state: String,
+    pub group_epoch: i32,
+    pub assignment_epoch: i32,
+    pub members: Vec<ShareDescribedMember>,
+    // topic_id -> topic_name for every subscribed topic, for responses that carry both.
+    pub topic_names: HashMap<Uuid, String>,
+}
+
+pub(crate) fn describe(group: &ShareGroupMeta, tenant: &str) -> ShareDescribedGroup {
+    let mut topic_names: HashMap<Uuid, String> = HashMap::new();
+    let mut members: Vec<ShareDescribedMember> = group
+        .members
+        .values()
+        .map(|m| {
+            for name in &m.subscribed {
+                topic_names.insert(topic_uuid(tenant, name), name.clone());
+            }
+            ShareDescribedMember {
+                member_id: m.member_id.clone(),
+                rack_id: m.rack_id.clone(),
+                client_id: m.client_id.clone(),
+                member_epoch: m.member_epoch,
+                subscribed: m.subscribed.clone(),
+                assignment: group.member_target(&m.member_id),
+            }
+        })
+        .collect();
+    members.sort_by(|a, b| a.member_id.cmp(&b.member_id));
+
+    ShareDescribedGroup {
+        group_id: group.group_id.clone(),
+        state: group.state_name().to_string(),
+        group_epoch: group.group_epoch,
+        assignment_epoch: group.assignment_epoch,
+        members,
+        topic_names,
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use kafka_protocol::error::ResponseError;
+
+use crate::core::assignor::{compute_share_target, TopicMeta};
+use crate::core::consumer_heartbeat::{heartbeat_error, ConsumerHeartbeatResult, LEAVE_EPOCH};
+use crate::core::share_group_meta::{ShareGroupMeta, ShareMemberMeta};
+
+pub struct ShareHeartbeatParams {
+    pub group_id: String,
+    pub member_id: String,
+    pub member_epoch: i32,
+    pub rack_id: Option<String>,
+    pub client_id: String,
+    pub subscribed_topics: Option<Vec<String>>,
+}
+
+pub(crate) fn heartbeat(
+    group: &mut ShareGroupMeta,
+    params: ShareHeartbeatParams,
+    resolve_topic: &dyn Fn(&str) -> Option<TopicMeta>,
+    now_ms: u128,
+) -> ConsumerHeartbeatResult {
+    let member_id = params.member_id.clone();
+
+    if params.member_epoch == LEAVE_EPOCH {
+        return leave(group, &member_id);
+    }
+
+    if params.member_epoch == 0 {
+        if let Err(result) = join(group, &params, now_ms) {
+            return result;
+        }
+    } else {
+        let Some(member) = group.members.get_mut(&member_id) else {
+            return heartbeat_error(
+                ResponseError::UnknownMemberId.code(),
+                "member is not in the group; rejoin with epoch 0",
+                &member_id,
+            );
+        };
+        if params.member_epoch != member.member_epoch {
+            return heartbeat_error(
+                ResponseError::FencedMemberEpoch.code(),
+                "member epoch does not match; rejoin",
+                &member_id,
+            );
+        }
+        member.last_heartbeat_ms = now_ms;
+        let mut subscription_changed = false;
+        if let Some(subs) = &params.subscribed_topics {
+            if *subs != member.subscribed {
+                member.subscribed = subs.clone();
+                subscription_changed = true;
+            }
+        }
+        if subscription_changed {
+            group.group_epoch += 1;
+        }
+    }
+
+    if group.assignment_epoch < group.group_epoch {
+        group.target = compute_share_target(
+            group
+                .members
+                .values()
+                .map(|m| (m.member_id.as_str(), m.subscribed.as_slice())),
+            resolve_topic,
+        );
+        group.assignment_epoch = group.group_epoch;
+    }
+
+    let target = group.member_target(&member_id);
+    let group_epoch = group.group_epoch;
+    let member = group.members.get_mut(&member_id).unwrap();
+    member.member_epoch = group_epoch;
+    let assignment = if member.last_sent.as_ref() != Some(&target) {
+        member.last_sent = Some(target.clone());
+        Some(target)
+    } else {
+        None
+    };
+
+    ConsumerHeartbeatResult {
+        error_code: 0,
+        error_message: None,
+        member_id,
+        member_epoch: group_epoch,
+        assignment,
+    }
+}
+
+fn join(
+    group: &mut ShareGroupMeta,
+    params: &ShareHeartbeatParams,
+    now_ms: u128,
+) -> Result<(), ConsumerHeartbeatResult> {
+    if params.member_id.is_empty() {
+        return Err(heartbeat_error(
+            ResponseError::InvalidRequest.code(),
+            "member id must be generated by the client",
+            &params.member_id,
+        ));
+    }
+    let Some(subscribed) = params.subscribed_topics.clone() else {
+        return Err(heartbeat_error(
+            ResponseError::InvalidRequest.code(),
+            "subscribed topics are required when joining",
+            &params.member_id,
+        ));
+    };
+
+    group.group_epoch += 1;
+    group.members.insert(
+        params.member_id.clone(),
+        ShareMemberMeta {
+            member_id: params.member_id.clone(),
+            rack_id: params.rack_id.clone(),
+            client_id: params.client_id.clone(),
+            subscribed,
+            member_epoch: group.group_epoch,
+            last_sent: None,
+            last_heartbeat_ms: now_ms,
+        },
+    );
+    Ok(())
+}
+
+fn leave(group: &mut ShareGroupMeta, member_id: &str) -> ConsumerHeartbeatResult {
+    if group.members.remove(member_id).is_none() {
+        return heartbeat_error(
+            ResponseError::UnknownMemberId.code(),
+            "member is not in the group",
+            member_id,
+        );
+    }
+    group.group_epoch += 1;
+
+    ConsumerHeartbeatResult {
+        error_code: 0,
+        error_message: None,
+        member_id: member_id.to_string(),
+        member_epoch: LEAVE_EPOCH,
+        assignment: None,
+    }
+}
+
+/// Drops members whose session expired and returns their ids, so the records
+/// they still hold can be released.
+pub(crate) fn remove_expired_members(
+    group: &mut ShareGroupMeta,
+    now_ms: u128,
+    session_timeout_ms: u64,
+) -> Vec<String> {
+    let expired: Vec<String> = group
+        .members
+        .values()
+        .filter(|m| now_ms.saturating_sub(m.last_heartbeat_ms) > session_timeout_ms as u128)
+        .map(|m| m.member_id.clone())
+        .collect();
+    if !expired.is_empty() {
+        for id in &expired {
+            group.members.remove(id);
+        }
+        group.group_epoch += 1;
+    }
+    expired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn topic_id() -> Uuid {
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, b"t")
+    }
+
+    fn resolve(name: &str) -> Option<TopicMeta> {
+        (name == "t").then(|| TopicMeta {
+            topic_id: topic_id(),
+            partitions: 2,
+        })
+    }
+
+    fn hb(group: &mut ShareGroupMeta, member: &str, epoch: i32) -> ConsumerHeartbeatResult {
+        heartbeat(
+            group,
+            ShareHeartbeatParams {
+                group_id: "g".to_string(),
+                member_id: member.to_string(),
+                member_epoch: epoch,
+                rack_id: None,
+                client_id: "c".to_string(),
+                subscribed_topics: (epoch == 0).then(|| vec!["t".to_string()]),
+            },
+            &resolve,
+            1,
+        )
+    }
+
+    #[test]
+    fn members_get_their_target_without_revocation() {
+        let mut g = ShareGroupMeta::new("g".to_string());
+        let r1 = hb(&mut g, "m1", 0);
+        assert_eq!(r1.member_epoch, 1);
+        assert_eq!(r1.assignment.unwrap()[&topic_id()], vec![0, 1]);
+
+        // A second member joins: both move to the new epoch immediately.
+        let r2 = hb(&mut g, "m2", 0);
+        assert_eq!(r2.member_epoch, 2);
+        assert_eq!(r2.assignment.unwrap()[&topic_id()], vec![1]);
+        let r1 = hb(&mut g, "m1", 1);
+        assert_eq!(r1.member_epoch, 2);
+        assert_eq!(r1.assignment.unwrap()[&topic_id()], vec![0]);
+
+        // Unchanged assignment is not resent.
+        assert!(hb(&mut g, "m1", 2).assignment.is_none());
+    }
+
+    #[test]
+    fn stale_epoch_and_unknown_member_are_rejected() {
+        let mut g = ShareGroupMeta::new("g".to_string());
+        hb(&mut g, "m1", 0);
+        assert_eq!(
+            hb(&mut g, "m1", 7).error_code,
+            ResponseError::FencedMemberEpoch.code()
+        );
+        assert_eq!(
+            hb(&mut g, "ghost", 1).error_code,
+            ResponseError::UnknownMemberId.code()
+        );
+    }
+
+    #[test]
+    fn leave_and_expiry_remove_members() {
+        let mut g = ShareGroupMeta::new("g".to_string());
+        hb(&mut g, "m1", 0);
+        hb(&mut g, "m2", 0);
+
+        let r = hb(&mut g, "m1", LEAVE_EPOCH);
+        assert_eq!(r.member_epoch, LEAVE_EPOCH);
+        assert_eq!(g.members.len(), 1);
+
+        assert_eq!(remove_expired_members(&mut g, 1_000_000, 100), vec!["m2"]);
+        assert_eq!(g.state_name(), "Empty");
+    }
+}
@@ -0,0 +1,485 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use kafka_protocol::error::ResponseError;
+use metadata_struct::kafka::share_group::{
+    KafkaSharePartitionState, KafkaShareStateBatch, SHARE_DELIVERY_STATE_ACKNOWLEDGED,
+    SHARE_DELIVERY_STATE_ARCHIVED, SHARE_DELIVERY_STATE_AVAILABLE,
+};
+
+use crate::core::constants::{
+    SHARE_ACK_ACCEPT, SHARE_ACK_GAP, SHARE_ACK_REJECT, SHARE_ACK_RELEASE,
+    SHARE_DELIVERY_COUNT_LIMIT, SHARE_PARTITION_MAX_RECORD_LOCKS,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordState {
+    Available,
+    Acquired,
+    Acknowledged,
+    Archived,
+}
+
+impl RecordState {
+    fn is_terminal(&self) -> bool {
+        matches!(self, RecordState::Acknowledged | RecordState::Archived)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InFlightRecord {
+    state: RecordState,
+    delivery_count: i16,
+    // Holder of the acquisition lock while `state` is Acquired.
+    member_id: Option<String>,
+    lock_expires_ms: u64,
+}
+
+/// The in-flight window of one share-partition: every record handed out to
+/// the group and not yet done with, with its acquisition lock and delivery
+/// count. Offsets below `start_offset` are finished; `end_offset` is one past
+/// the last offset ever handed out.
+pub struct SharePartition {
+    pub start_offset: i64,
+    pub end_offset: i64,
+    pub state_epoch: i32,
+    records: BTreeMap<i64, InFlightRecord>,
+}
+
+impl SharePartition {
+    pub fn new(start_offset: i64) -> Self {
+        SharePartition {
+            start_offset,
+            end_offset: start_offset,
+            state_epoch: 0,
+            records: BTreeMap::new(),
+        }
+    }
+
+    pub fn from_state(state: &KafkaSharePartitionState) -> Self {
+        let mut records = BTreeMap::new();
+        for batch in &state.state_batches {
+            let record_state = match batch.delivery_state {
+                SHARE_DELIVERY_STATE_ACKNOWLEDGED => RecordState::Acknowledged,
+                SHARE_DELIVERY_STATE_ARCHIVED => RecordState::Archived,
+                _ => RecordState::Available,
+            };
+            for offset in batch.first_offset..=batch.last_offset {
+                records.insert(
+                    offset,
+                    InFlightRecord {
+                        state: record_state,
+                        delivery_count: batch.delivery_count,
+                        member_id: None,
+                        lock_expires_ms: 0,
+                    },
+                );
+            }
+        }
+        let mut partition = SharePartition {
+            start_offset: state.start_offset,
+            end_offset: state.end_offset.max(state.start_offset),
+            state_epoch: state.state_epoch,
+            records,
+        };
+        partition.advance();
+        partition
+    }
+
+    /// Persistable form. Acquired records are written as available: their
+    /// locks do not survive a restart.
+    pub fn to_state(
+        &self,
+        tenant: &str,
+        group_id: &str,
+        topic: &str,
+        partition: i32,
+    ) -> KafkaSharePartitionState {
+        let mut state_batches: Vec<KafkaShareStateBatch> = Vec::new();
+        for (offset, record) in &self.records {
+            let delivery_state = match record.state {
+                RecordState::Available | RecordState::Acquired => SHARE_DELIVERY_STATE_AVAILABLE,
+                RecordState::Acknowledged => SHARE_DELIVERY_STATE_ACKNOWLEDGED,
+                RecordState::Archived => SHARE_DELIVERY_STATE_ARCHIVED,
+            };
+            match state_batches.last_mut() {
+                Some(last)
+                    if last.last_offset + 1 == *offset
+                        && last.delivery_state == delivery_state
+                        && last.delivery_count == record.delivery_count =>
+                {
+                    last.last_offset = *offset;
+                }
+                _ => state_batches.push(KafkaShareStateBatch {
+                    first_offset: *offset,
+                    last_offset: *offset,
+                    delivery_state,
+                    delivery_count: record.delivery_count,
+                }),
+            }
+        }
+        KafkaSharePartitionState {
+            tenant: tenant.to_string(),
+            group_id: group_id.to_string(),
+            topic: topic.to_string(),
+            partition,
+            start_offset: self.start_offset,
+            end_offset: self.end_offset,
+            state_epoch: self.state_epoch,
+            state_batches,
+        }
+    }
+
+    /// Where the next storage read has to start: the oldest record waiting
+    /// for redelivery, or the end of the window when there is none.
+    pub fn next_fetch_offset(&self) -> i64 {
+        self.records
+            .iter()
+            .find(|(_, r)| r.state == RecordState::Available)
+            .map(|(offset, _)| *offset)
+            .unwrap_or(self.end_offset)
+    }
+
+    /// Acquire the record at `offset` for `member_id`. Returns its delivery
+    /// count, or None when the record is not available to this group now.
+    pub fn acquire(
+        &mut self,
+        offset: i64,
+        member_id: &str,
+        now_ms: u64,
+        lock_duration_ms: u64,
+    ) -> Option<i16> {
+        if offset < self.start_offset {
+            return None;
+        }
+        if offset < self.end_offset {
+            let record = self.records.get_mut(&offset)?;
+            if record.state != RecordState::Available {
+                return None;
+            }
+            record.state = RecordState::Acquired;
+            record.delivery_count = record.delivery_count.saturating_add(1);
+            record.member_id = Some(member_id.to_string());
+            record.lock_expires_ms = now_ms + lock_duration_ms;
+            return Some(record.delivery_count);
+        }
+        if self.records.len() >= SHARE_PARTITION_MAX_RECORD_LOCKS {
+            return None;
+        }
+        self.records.insert(
+            offset,
+            InFlightRecord {
+                state: RecordState::Acquired,
+                delivery_count: 1,
+                member_id: Some(member_id.to_string()),
+                lock_expires_ms: now_ms + lock_duration_ms,
+            },
+        );
+        self.end_offset = offset + 1;
+        Some(1)
+    }
+
+    /// Skip a record that must never be delivered (e.g. a transaction marker).
+    pub fn archive(&mut self, offset: i64) {
+        if offset < self.start_offset {
+            return;
+        }
+        if offset >= self.end_offset {
+            self.end_offset = offset + 1;
+        }
+        self.records.insert(
+            offset,
+            InFlightRecord {
+                state: RecordState::Archived,
+                delivery_count: 0,
+                member_id: None,
+                lock_expires_ms: 0,
+            },
+        );
+        self.advance();
+    }
+
+    /// Apply one acknowledgement batch from `member_id`. `ack_types` holds a
+    /// single type for the whole batch or one type per offset. Nothing is
+    /// changed unless every offset in the batch can be acknowledged.
+    pub fn acknowledge(
+        &mut self,
+        member_id: &str,
+        first_offset: i64,
+        last_offset: i64,
+        ack_types: &[i8],
+    ) -> Result<(), ResponseError> {
+        let count = last_offset - first_offset + 1;
+        if count <= 0 || (ack_types.len() != 1 && ack_types.len() as i64 != count) {
+            return Err(ResponseError::InvalidRequest);
+        }
+        let ack_type = |offset: i64| {
+            if ack_types.len() == 1 {
+                ack_types[0]
+            } else {
+                ack_types[(offset - first_offset) as usize]
+            }
+        };
+
+        for offset in first_offset..=last_offset {
+            let ack = ack_type(offset);
+            if !matches!(
+                ack,
+                SHARE_ACK_GAP | SHARE_ACK_ACCEPT | SHARE_ACK_RELEASE | SHARE_ACK_REJECT
+            ) {
+                return Err(ResponseError::InvalidRequest);
+            }
+            let held = self.records.get(&offset).is_some_and(|r| {
+                r.state == RecordState::Acquired && r.member_id.as_deref() == Some(member_id)
+            });
+            let untracked_gap = ack == SHARE_ACK_GAP
+                && offset >= self.start_offset
+                && offset < self.end_offset
+                && !self.records.contains_key(&offset);
+            if !held && !untracked_gap {
+                return Err(ResponseError::InvalidRecordState);
+            }
+        }
+
+        for offset in first_offset..=last_offset {
+            let Some(record) = self.records.get_mut(&offset) else {
+                continue;
+            };
+            match ack_type(offset) {
+                SHARE_ACK_ACCEPT => {
+                    record.state = RecordState::Acknowledged;
+                    record.member_id = None;
+                }
+                SHARE_ACK_RELEASE => release(record),
+                _ => {
+                    record.state = RecordState::Archived;
+                    record.member_id = None;
+                }
+            }
+        }
+        self.advance();
+        Ok(())
+    }
+
+    /// Give back every record `member_id` holds. Returns whether anything changed.
+    pub fn release_member(&mut self, member_id: &str) -> bool {
+        let mut changed = false;
+        for record in self.records.values_mut() {
+            if record.state == RecordState::Acquired
+                && record.member_id.as_deref() == Some(member_id)
+            {
+                release(record);
+                changed = true;
+            }
+        }
+        if changed {
+            self.advance();
+        }
+        changed
+    }
+
+    /// Give back every record whose acquisition lock expired. Returns whether
+    /// anything changed.
+    pub fn release_expired(&mut self, now_ms: u64) -> bool {
+        let mut changed = false;
+        for record in self.records.values_mut() {
+            if record.state == RecordState::Acquired && record.lock_expires_ms <= now_ms {
+                release(record);
+                changed = true;
+            }
+        }
+        if changed {
+            self.advance();
+        }
+        changed
+    }
+
+    /// Forget records that no longer exist in the log.
+    pub fn truncate_to(&mut self, log_start_offset: i64) {
+        if log_start_offset <= self.start_offset {
+            return;
+        }
+        self.records = self.records.split_off(&log_start_offset);
+        self.start_offset = log_start_offset;
+        self.end_offset = self.end_offset.max(log_start_offset);
+        self.advance();
+    }
+
+    /// Restart the share-partition at `start_offset`, dropping all in-flight state.
+    pub fn reset(&mut self, start_offset: i64) {
+        self.records.clear();
+        self.start_offset = start_offset;
+        self.end_offset = start_offset;
+        self.state_epoch += 1;
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.records.len()
+    }
+
+    // Move start_offset past every finished record at the front of the window.
+    fn advance(&mut self) {
+        while let Some(entry) = self.records.first_entry() {
+            if !entry.get().state.is_terminal() {
+                break;
+            }
+            entry.remove();
+        }
+        self.start_offset = self
+            .records
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or(self.end_offset);
+    }
+}
+
+fn release(record: &mut InFlightRecord) {
+    record.member_id = None;
+    record.state = if record.delivery_count >= SHARE_DELIVERY_COUNT_LIMIT {
+        RecordState::Archived
+    } else {
+        RecordState::Available
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK_MS: u64 = 1000;
+
+    #[test]
+    fn acquire_extends_window_and_accept_advances_start() {
+        let mut sp = SharePartition::new(10);
+        assert_eq!(sp.acquire(10, "m1", 0, LOCK_MS), Some(1));
+        assert_eq!(sp.acquire(11, "m1", 0, LOCK_MS), Some(1));
+        assert_eq!(sp.end_offset, 12);
+        // Already acquired: nobody else gets it.
+        assert_eq!(sp.acquire(10, "m2", 0, LOCK_MS), None);
+
+        sp.acknowledge("m1", 10, 10, &[SHARE_ACK_ACCEPT]).unwrap();
+        assert_eq!(sp.start_offset, 11);
+        sp.acknowledge("m1", 11, 11, &[SHARE_ACK_REJECT]).unwrap();
+        assert_eq!(sp.start_offset, 12);
+        assert_eq!(sp.in_flight(), 0);
+    }
+
+    #[test]
+    fn released_records_are_redelivered_with_higher_count() {
+        let mut sp = SharePartition::new(0);
+        sp.acquire(0, "m1", 0, LOCK_MS);
+        sp.acknowledge("m1", 0, 0, &[SHARE_ACK_RELEASE]).unwrap();
+        assert_eq!(sp.next_fetch_offset(), 0);
+        assert_eq!(sp.acquire(0, "m2", 0, LOCK_MS), Some(2));
+    }
+
+    #[test]
+    fn delivery_count_limit_archives_record() {
+        let mut sp = SharePartition::new(0);
+        for attempt in 1..=SHARE_DELIVERY_COUNT_LIMIT {
+            assert_eq!(sp.acquire(0, "m1", 0, LOCK_MS), Some(attempt));
+            sp.acknowledge("m1", 0, 0, &[SHARE_ACK_RELEASE]).unwrap();
+        }
+        assert_eq!(sp.acquire(0, "m1", 0, LOCK_MS), None);
+        assert_eq!(sp.start_offset, 1);
+    }
+
+    #[test]
+    fn expired_locks_and_departed_members_release_records() {
+        let mut sp = SharePartition::new(0);
+        sp.acquire(0, "m1", 0, LOCK_MS);
+        sp.acquire(1, "m2", 0, LOCK_MS * 5);
+
+        assert!(sp.release_expired(LOCK_MS));
+        assert_eq!(sp.acquire(0, "m3", LOCK_MS, LOCK_MS), Some(2));
+        assert_eq!(sp.acquire(1, "m3", LOCK_MS, LOCK_MS), None);
+
+        assert!(sp.release_member("m2"));
+        assert_eq!(sp.acquire(1, "m3", LOCK_MS, LOCK_MS), Some(2));
+    }
+
+    #[test]
+    fn acknowledge_rejects_records_not_held_by_member() {
+        let mut sp = SharePartition::new(0);
+        sp.acquire(0, "m1", 0, LOCK_MS);
+        sp.acquire(1, "m1", 0, LOCK_MS);
+        assert_eq!(
+            sp.acknowledge("m2", 0, 0, &[SHARE_ACK_ACCEPT]),
+            Err(ResponseError::InvalidRecordState)
+        );
+        // All-or-nothing: offset 5 was never acquired, so offset 0 stays held.
+        assert_eq!(
+            sp.acknowledge("m1", 0, 5, &[SHARE_ACK_ACCEPT]),
+            Err(ResponseError::InvalidRecordState)
+        );
+        assert_eq!(sp.acknowledge("m1", 0, 1, &[SHARE_ACK_ACCEPT]), Ok(()));
+        assert_eq!(sp.start_offset, 2);
+    }
+
+    #[test]
+    fn per_offset_ack_types_and_gaps() {
+        let mut sp = SharePartition::new(0);
+        sp.acquire(0, "m1", 0, LOCK_MS);
+        sp.acquire(2, "m1", 0, LOCK_MS);
+        sp.acknowledge(
+            "m1",
+            0,
+            2,
+            &[SHARE_ACK_ACCEPT, SHARE_ACK_GAP, SHARE_ACK_RELEASE],
+        )
+        .unwrap();
+        assert_eq!(sp.start_offset, 2);
+        assert_eq!(sp.next_fetch_offset(), 2);
+    }
+
+    #[test]
+    fn state_round_trips_with_acquired_records_made_available() {
+        let mut sp = SharePartition::new(0);
+        for offset in 0..4 {
+            sp.acquire(offset, "m1", 0, LOCK_MS);
+        }
+        sp.acknowledge("m1", 1, 1, &[SHARE_ACK_ACCEPT]).unwrap();
+        sp.acknowledge("m1", 3, 3, &[SHARE_ACK_REJECT]).unwrap();
+
+        let state = sp.to_state("t", "g", "topic", 0);
+        assert_eq!(state.start_offset, 0);
+        assert_eq!(state.end_offset, 4);
+        assert_eq!(state.state_batches.len(), 4);
+
+        let mut restored = SharePartition::from_state(&state);
+        assert_eq!(restored.next_fetch_offset(), 0);
+        assert_eq!(restored.acquire(0, "m2", 0, LOCK_MS), Some(2));
+        assert_eq!(restored.acquire(1, "m2", 0, LOCK_MS), None);
+        assert_eq!(restored.acquire(2, "m2", 0, LOCK_MS), Some(2));
+    }
+
+    #[test]
+    fn truncate_and_reset_move_the_window() {
+        let mut sp = SharePartition::new(0);
+        sp.acquire(0, "m1", 0, LOCK_MS);
+        sp.acquire(5, "m1", 0, LOCK_MS);
+        sp.truncate_to(3);
+        assert_eq!(sp.start_offset, 5);
+
+        sp.reset(100);
+        assert_eq!(
+            (sp.start_offset, sp.end_offset, sp.state_epoch),
+            (100, 100, 1)
+        );
+        assert_eq!(sp.in_flight(), 0);
+    }
+}
@@ -0,0 +1,959 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use broker_core::inner_topic::KAFKA_TRANSACTION_LOG_TOPIC;
+use bytes::{BufMut, BytesMut};
+use common_base::error::common::CommonError;
+use common_base::tools::now_millis;
+use dashmap::DashMap;
+use kafka_protocol::error::ResponseError;
+use metadata_struct::adapter::adapter_offset::AdapterCommitOffset;
+use metadata_struct::adapter::adapter_read_config::AdapterReadConfig;
+use metadata_struct::adapter::adapter_record::AdapterWriteRecord;
+use metadata_struct::kafka::transaction::{
+    KafkaAbortedTxn, KafkaTransaction, KafkaTransactionLogRecord, KafkaTransactionState,
+    KafkaTxnMarker, KafkaTxnOffset, KafkaTxnPartition,
+};
+use metadata_struct::storage::record::{StorageRecordProtocolData, StorageRecordProtocolDataKafka};
+use storage_adapter::driver::StorageDriverManager;
+use tokio::sync::{Mutex, OnceCell, OwnedRwLockReadGuard, RwLock};
+use tracing::{info, warn};
+
+use crate::core::cache::KafkaCacheManager;
+use crate::core::constants::{
+    CONTROL_RECORD_VERSION, CONTROL_TYPE_ABORT, CONTROL_TYPE_COMMIT, MAX_TRANSACTION_TIMEOUT_MS,
+    NO_PRODUCER_ID, TRANSACTION_LOG_SYNC_INTERVAL_MS, TRANSACTION_TIMEOUT_CHECK_INTERVAL_MS,
+};
+use crate::core::coordinator_locator::is_coordinator_node;
+use crate::handler::tenant::get_tenant;
+
+// Per-partition results: (topic, [(partition, error_code)]).
+pub type TxnPartitionResults = Vec<(String, Vec<(i32, i16)>)>;
+
+/// Transaction coordinator (KIP-98). Transaction state is cached in
+/// `KafkaCacheManager` and every change is appended to the transaction log
+/// inner topic before it is acknowledged. Only the coordinator node writes the
+/// log and transactional records (other brokers forward such Produce to it);
+/// every node tails the log in the background, so read_committed fetch on any
+/// partition leader sees the same open transactions and markers, and a new or
+/// restarted coordinator finishes any commit/abort in flight.
+pub struct TransactionCoordinator {
+    sdm: Arc<StorageDriverManager>,
+    cache: Arc<KafkaCacheManager>,
+    // Control requests for a transactional id hold its lock exclusively; its
+    // transactional produce holds it shared, so no record can land after the
+    // commit/abort marker of the transaction it belongs to.
+    locks: DashMap<String, Arc<RwLock<()>>>,
+    // Next transaction log offset to read, per log shard.
+    log_offsets: Mutex<HashMap<String, u64>>,
+    loaded: OnceCell<()>,
+    tasks_started: AtomicBool,
+}
+
+impl TransactionCoordinator {
+    pub fn new(sdm: Arc<StorageDriverManager>, cache: Arc<KafkaCacheManager>) -> Self {
+        TransactionCoordinator {
+            sdm,
+            cache,
+            locks: DashMap::with_capacity(8),
+            log_offsets: Mutex::new(HashMap::new()),
+            loaded: OnceCell::new(),
+            tasks_started: AtomicBool::new(false),
+        }
+    }
+
+    // The coordinator is constructed outside the tokio runtime during broker
+    // startup, so the log is replayed (and the background loops spawned)
+    // lazily from the first request that needs transaction state.
+    pub async fn ensure_loaded(self: &Arc<Self>) {
+        self.loaded.get_or_init(|| self.recover()).await;
+        if self.tasks_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let coordinator = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(TRANSACTION_LOG_SYNC_INTERVAL_MS)).await;
+                coordinator.sync_log().await;
+            }
+        });
+        let coordinator = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(TRANSACTION_TIMEOUT_CHECK_INTERVAL_MS))
+                    .await;
+                if is_coordinator_node(&coordinator.sdm).await {
+                    coordinator.finish_prepared().await;
+                    coordinator.abort_timed_out(now_millis() as u64).await;
+                }
+            }
+        });
+    }
+
+    // A control request may reach a node that just became coordinator, so it
+    // catches up with the log before deciding anything.
+    async fn refresh(self: &Arc<Self>) {
+        self.ensure_loaded().await;
+        self.sync_log().await;
+    }
+
+    pub fn transaction(&self, transactional_id: &str) -> Option<KafkaTransaction> {
+        self.cache.get_transaction(transactional_id)
+    }
+
+    /// Shared lock held by a transactional produce for the whole write.
+    pub async fn produce_guard(&self, transactional_id: &str) -> OwnedRwLockReadGuard<()> {
+        self.lock(transactional_id).read_owned().await
+    }
+
+    pub async fn init_producer_id(
+        self: &Arc<Self>,
+        transactional_id: &str,
+        timeout_ms: i32,
+        producer_id: i64,
+        producer_epoch: i16,
+    ) -> Result<(i64, i16), ResponseError> {
+        if timeout_ms <= 0 || timeout_ms > MAX_TRANSACTION_TIMEOUT_MS {
+            return Err(ResponseError::InvalidTransactionTimeout);
+        }
+        self.refresh().await;
+        let lock = self.lock(transactional_id);
+        let _guard = lock.write().await;
+
+        let now = now_millis() as u64;
+        let Some(mut txn) = self.cache.get_transaction(transactional_id) else {
+            let txn = KafkaTransaction {
+                transactional_id: transactional_id.to_string(),
+                producer_id: self.cache.next_producer_id(),
+                producer_epoch: 0,
+                timeout_ms,
+                state: KafkaTransactionState::Empty,
+                start_time_ms: now,
+                ..Default::default()
+            };
+            let txn = self.persist(txn).await?;
+            return Ok((txn.producer_id, txn.producer_epoch));
+        };
+
+        // KIP-360: a producer re-initializing after an error must present the
+        // id/epoch it was last given.
+        if producer_id != NO_PRODUCER_ID
+            && (producer_id != txn.producer_id || producer_epoch != txn.producer_epoch)
+        {
+            return Err(ResponseError::InvalidProducerEpoch);
+        }
+
+        // A new incarnation fences the previous one: finish whatever it left
+        // open (an undecided transaction is aborted), then bump the epoch.
+        if txn.state.is_open() {
+            let commit = txn.state == KafkaTransactionState::PrepareCommit;
+            txn = self.complete(txn, commit).await?;
+        }
+        bump_epoch(&self.cache, &mut txn);
+        txn.state = KafkaTransactionState::Empty;
+        txn.timeout_ms = timeout_ms;
+        txn.start_time_ms = now;
+        let txn = self.persist(txn).await?;
+        Ok((txn.producer_id, txn.producer_epoch))
+    }
+
+    pub async fn add_partitions(
+        self: &Arc<Self>,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+        topics: &[(String, Vec<i32>)],
+    ) -> Result<TxnPartitionResults, ResponseError> {
+        self.refresh().await;
+        let lock = self.lock(transactional_id);
+        let _guard = lock.write().await;
+
+        let mut txn = self.active_transaction(transactional_id, producer_id, producer_epoch)?;
+
+        let mut added = Vec::new();
+        let mut results: TxnPartitionResults = Vec::with_capacity(topics.len());
+        let mut failed = false;
+        for (topic_name, partitions) in topics {
+            let resolved = self.resolve_partitions(topic_name, partitions).await;
+            let mut codes = Vec::with_capacity(partitions.len());
+            for (partition, shard) in partitions.iter().zip(resolved) {
+                match shard {
+                    Some((shard_name, high_watermark)) => {
+                        codes.push((*partition, 0));
+                        if !txn.has_partition(topic_name, *partition) {
+                            added.push(KafkaTxnPartition {
+                                topic: topic_name.clone(),
+                                partition: *partition,
+                                shard_name,
+                                first_offset: high_watermark,
+                            });
+                        }
+                    }
+                    None => {
+                        failed = true;
+                        codes.push((*partition, ResponseError::UnknownTopicOrPartition.code()));
+                    }
+                }
+            }
+            results.push((topic_name.clone(), codes));
+        }
+
+        // Like Kafka, the request is all or nothing: partitions that were fine
+        // are reported as not attempted when any other one failed.
+        if failed {
+            for (_, codes) in results.iter_mut() {
+                for (_, code) in codes.iter_mut().filter(|(_, code)| *code == 0) {
+                    *code = ResponseError::OperationNotAttempted.code();
+                }
+            }
+            return Ok(results);
+        }
+
+        begin(&mut txn);
+        txn.partitions.extend(added);
+        self.persist(txn).await?;
+        Ok(results)
+    }
+
+    pub async fn add_offsets(
+        self: &Arc<Self>,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+        group_id: &str,
+    ) -> Result<(), ResponseError> {
+        self.refresh().await;
+        let lock = self.lock(transactional_id);
+        let _guard = lock.write().await;
+
+        let mut txn = self.active_transaction(transactional_id, producer_id, producer_epoch)?;
+        begin(&mut txn);
+        if !txn.groups.iter().any(|g| g == group_id) {
+            txn.groups.push(group_id.to_string());
+        }
+        self.persist(txn).await?;
+        Ok(())
+    }
+
+    pub async fn txn_offset_commit(
+        self: &Arc<Self>,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+        group_id: &str,
+        topics: &[(String, Vec<(i32, i64)>)],
+    ) -> Result<TxnPartitionResults, ResponseError> {
+        self.refresh().await;
+        let lock = self.lock(transactional_id);
+        let _guard = lock.write().await;
+
+        let mut txn = self.active_transaction(transactional_id, producer_id, producer_epoch)?;
+        if txn.state != KafkaTransactionState::Ongoing || !txn.groups.iter().any(|g| g == group_id)
+        {
+            return Err(ResponseError::InvalidTxnState);
+        }
+
+        let mut results: TxnPartitionResults = Vec::with_capacity(topics.len());
+        for (topic_name, partitions) in topics {
+            let topic = self
+                .sdm
+                .broker_cache
+                .get_topic_by_name(get_tenant(), topic_name);
+            let mut codes = Vec::with_capacity(partitions.len());
+            for (partition, offset) in partitions {
+                let Some(shard_name) = topic
+                    .as_ref()
+                    .and_then(|t| t.storage_name_list.get(&(*partition as u32)))
+                else {
+                    codes.push((*partition, ResponseError::UnknownTopicOrPartition.code()));
+                    continue;
+                };
+                txn.pending_offsets
+                    .retain(|o| !(o.group_id == group_id && &o.offset.shard_name == shard_name));
+                txn.pending_offsets.push(KafkaTxnOffset {
+                    group_id: group_id.to_string(),
+                    offset: AdapterCommitOffset {
+                        shard_name: shard_name.clone(),
+                        topic_name: topic_name.clone(),
+                        partition: *partition as u32,
+                        offset: (*offset).max(0) as u64,
+                    },
+                });
+                codes.push((*partition, 0));
+            }
+            results.push((topic_name.clone(), codes));
+        }
+
+        self.persist(txn).await?;
+        Ok(results)
+    }
+
+    pub async fn end_txn(
+        self: &Arc<Self>,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+        committed: bool,
+    ) -> Result<(), ResponseError> {
+        self.refresh().await;
+        let lock = self.lock(transactional_id);
+        let _guard = lock.write().await;
+
+        let txn = self
+            .cache
+            .get_transaction(transactional_id)
+            .ok_or(ResponseError::InvalidProducerIdMapping)?;
+        check_producer(&txn, producer_id, producer_epoch)?;
+
+        match (txn.state, committed) {
+            (KafkaTransactionState::Ongoing, _)
+            | (KafkaTransactionState::PrepareCommit, true)
+            | (KafkaTransactionState::PrepareAbort, false) => {
+                self.complete(txn, committed).await?;
+                Ok(())
+            }
+            // A retried EndTxn whose first attempt already went through.
+            (KafkaTransactionState::CompleteCommit, true)
+            | (KafkaTransactionState::CompleteAbort, false) => Ok(()),
+            _ => Err(ResponseError::InvalidTxnState),
+        }
+    }
+
+    /// Finish the commits/aborts a previous coordinator left in Prepare*.
+    async fn finish_prepared(&self) {
+        for txn in self.cache.list_transactions() {
+            if !matches!(
+                txn.state,
+                KafkaTransactionState::PrepareCommit | KafkaTransactionState::PrepareAbort
+            ) {
+                continue;
+            }
+            let lock = self.lock(&txn.transactional_id);
+            let _guard = lock.write().await;
+            let Some(txn) = self.cache.get_transaction(&txn.transactional_id) else {
+                continue;
+            };
+            let commit = match txn.state {
+                KafkaTransactionState::PrepareCommit => true,
+                KafkaTransactionState::PrepareAbort => false,
+                _ => continue,
+            };
+            let id = txn.transactional_id.clone();
+            if let Err(e) = self.complete(txn, commit).await {
+                warn!("Kafka transaction {} could not be completed: {}", id, e);
+            }
+        }
+    }
+
+    /// Abort every ongoing transaction that has outlived its timeout, bumping
+    /// the epoch so the stalled producer is fenced if it comes back.
+    pub async fn abort_timed_out(&self, now_ms: u64) {
+        for txn in self.cache.list_transactions() {
+            if !is_timed_out(&txn, now_ms) {
+                continue;
+            }
+            let lock = self.lock(&txn.transactional_id);
+            let _guard = lock.write().await;
+            let Some(mut txn) = self.cache.get_transaction(&txn.transactional_id) else {
+                continue;
+            };
+            if !is_timed_out(&txn, now_ms) {
+                continue;
+            }
+            warn!(
+                "Kafka transaction {} timed out after {} ms, aborting",
+                txn.transactional_id, txn.timeout_ms
+            );
+            // Keep the producer id: aborted ranges are matched on it.
+            if txn.producer_epoch < i16::MAX - 1 {
+                txn.producer_epoch += 1;
+            }
+            let id = txn.transactional_id.clone();
+            if let Err(e) = self.complete(txn, false).await {
+                warn!("Kafka transaction {} abort failed: {}", id, e);
+            }
+        }
+    }
+
+    fn lock(&self, transactional_id: &str) -> Arc<RwLock<()>> {
+        self.locks
+            .entry(transactional_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    fn active_transaction(
+        &self,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+    ) -> Result<KafkaTransaction, ResponseError> {
+        let txn = self
+            .cache
+            .get_transaction(transactional_id)
+            .ok_or(ResponseError::InvalidProducerIdMapping)?;
+        check_producer(&txn, producer_id, producer_epoch)?;
+        if matches!(
+            txn.state,
+            KafkaTransactionState::PrepareCommit | KafkaTransactionState::PrepareAbort
+        ) {
+            return Err(ResponseError::ConcurrentTransactions);
+        }
+        Ok(txn)
+    }
+
+    // (shard name, high watermark) of each partition, None when it doesn't exist.
+    async fn resolve_partitions(
+        &self,
+        topic_name: &str,
+        partitions: &[i32],
+    ) -> Vec<Option<(String, i64)>> {
+        let topic = self
+            .sdm
+            .broker_cache
+            .get_topic_by_name(get_tenant(), topic_name);
+        let details = match topic {
+            Some(_) => self
+                .sdm
+                .list_storage_resource(get_tenant(), topic_name)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "Kafka AddPartitionsToTxn failed to list shards for {}: {}",
+                        topic_name, e
+                    );
+                    HashMap::new()
+                }),
+            None => HashMap::new(),
+        };
+        partitions
+            .iter()
+            .map(|p| {
+                let partition = *p as u32;
+                let shard_name = topic.as_ref()?.storage_name_list.get(&partition)?;
+                let detail = details.get(&partition)?;
+                Some((shard_name.clone(), detail.offset.high_watermark as i64))
+            })
+            .collect()
+    }
+
+    // Drive a transaction to CompleteCommit/CompleteAbort: log the prepare
+    // state, write a control marker to every partition, log the markers (so
+    // other nodes can release the records to read_committed) and apply the
+    // commit's offsets, then log the completion. A failure leaves it
+    // in Prepare*, which a retried EndTxn or the next restart picks up again.
+    async fn complete(
+        &self,
+        mut txn: KafkaTransaction,
+        commit: bool,
+    ) -> Result<KafkaTransaction, ResponseError> {
+        txn.state = if commit {
+            KafkaTransactionState::PrepareCommit
+        } else {
+            KafkaTransactionState::PrepareAbort
+        };
+        let mut txn = self.persist(txn).await?;
+
+        let control_type = if commit {
+            CONTROL_TYPE_COMMIT
+        } else {
+            CONTROL_TYPE_ABORT
+        };
+        let mut markers = Vec::with_capacity(txn.partitions.len());
+        let mut aborted = Vec::with_capacity(txn.partitions.len());
+        for partition in &txn.partitions {
+            let marker_offset = self
+                .write_marker(&txn, partition, control_type)
+                .await
+                .map_err(|e| {
+                    warn!(
+                        "Kafka transaction {} failed to write marker to {}[{}]: {}",
+                        txn.transactional_id, partition.topic, partition.partition, e
+                    );
+                    ResponseError::CoordinatorNotAvailable
+                })?;
+            markers.push(KafkaTxnMarker {
+                shard_name: partition.shard_name.clone(),
+                producer_id: txn.producer_id,
+                offset: marker_offset,
+            });
+            aborted.push(KafkaAbortedTxn {
+                shard_name: partition.shard_name.clone(),
+                producer_id: txn.producer_id,
+                first_offset: partition.first_offset,
+                last_offset: marker_offset,
+            });
+        }
+
+        if commit {
+            if !markers.is_empty() {
+                self.append_log(&KafkaTransactionLogRecord::Committed(markers.clone()))
+                    .await?;
+                self.cache.add_txn_markers(markers);
+            }
+            self.commit_offsets(&txn).await?;
+        } else if !aborted.is_empty() {
+            self.append_log(&KafkaTransactionLogRecord::Aborted(aborted.clone()))
+                .await?;
+            self.cache.add_aborted_transactions(aborted);
+        }
+
+        txn.state = if commit {
+            KafkaTransactionState::CompleteCommit
+        } else {
+            KafkaTransactionState::CompleteAbort
+        };
+        txn.partitions.clear();
+        txn.groups.clear();
+        txn.pending_offsets.clear();
+        self.persist(txn).await
+    }
+
+    async fn write_marker(
+        &self,
+        txn: &KafkaTransaction,
+        partition: &KafkaTxnPartition,
+        control_type: i16,
+    ) -> Result<i64, CommonError> {
+        let record = control_record(
+            &partition.topic,
+            txn.producer_id,
+            txn.producer_epoch,
+            control_type,
+        );
+        let rows = self
+            .sdm
+            .write_to_partition(
+                get_tenant(),
+                &partition.topic,
+                partition.partition as u32,
+                &[record],
+                1,
+            )
+            .await?;
+        let row = rows.first().ok_or_else(|| {
+            CommonError::CommonError("empty write response for control marker".to_string())
+        })?;
+        if row.is_error() {
+            return Err(CommonError::CommonError(row.error_info()));
+        }
+        Ok(row.offset as i64)
+    }
+
+    async fn commit_offsets(&self, txn: &KafkaTransaction) -> Result<(), ResponseError> {
+        let mut by_group: HashMap<&str, Vec<AdapterCommitOffset>> = HashMap::new();
+        for pending in &txn.pending_offsets {
+            by_group
+                .entry(pending.group_id.as_str())
+                .or_default()
+                .push(pending.offset.clone());
+        }
+        for (group_id, offsets) in by_group {
+            self.sdm
+                .commit_group_offset(get_tenant(), group_id, &offsets)
+                .await
+                .map_err(|e| {
+                    warn!(
+                        "Kafka transaction {} failed to commit offsets for group {}: {}",
+                        txn.transactional_id, group_id, e
+                    );
+                    ResponseError::CoordinatorNotAvailable
+                })?;
+        }
+        Ok(())
+    }
+
+    async fn persist(&self, mut txn: KafkaTransaction) -> Result<KafkaTransaction, ResponseError> {
+        txn.version += 1;
+        txn.update_time_ms = now_millis() as u64;
+        self.append_log(&KafkaTransactionLogRecord::Transaction(txn.clone()))
+            .await?;
+        self.cache.set_transaction(txn.clone());
+        Ok(txn)
+    }
+
+    async fn append_log(&self, record: &KafkaTransactionLogRecord) -> Result<(), ResponseError> {
+        self.write_log(record).await.map_err(|e| {
+            warn!("Kafka transaction log write failed: {}", e);
+            ResponseError::CoordinatorNotAvailable
+        })
+    }
+
+    async fn write_log(&self, record: &KafkaTransactionLogRecord) -> Result<(), CommonError> {
+        let record = AdapterWriteRecord::new(KAFKA_TRANSACTION_LOG_TOPIC, record.encode()?);
+        let rows = self
+            .sdm
+            .write(get_tenant(), KAFKA_TRANSACTION_LOG_TOPIC, &[record], 1)
+            .await?;
+        match rows.first() {
+            Some(row) if row.is_error() => Err(CommonError::CommonError(row.error_info())),
+            Some(_) => Ok(()),
+            None => Err(CommonError::CommonError(
+                "empty write response for transaction log".to_string(),
+            )),
+        }
+    }
+
+    async fn recover(&self) {
+        let applied = self.sync_log().await;
+        info!(
+            "Kafka transaction log replayed: {} records, {} transactions",
+            applied,
+            self.cache.list_transactions().len()
+        );
+        if is_coordinator_node(&self.sdm).await {
+            self.finish_prepared().await;
+        }
+    }
+
+    // Read the transaction log from where the last call stopped and apply it to
+    // the cache. Transactions only move forward by version, so the records this
+    // node appended itself (already in the cache) are applied again harmlessly.
+    async fn sync_log(&self) -> usize {
+        let read_config = AdapterReadConfig {
+            max_record_num: 100,
+            max_size: 10 * 1024 * 1024,
+        };
+        let mut offsets = self.log_offsets.lock().await;
+        let mut applied = 0;
+
+        loop {
+            let records = match self
+                .sdm
+                .read_by_offset(
+                    get_tenant(),
+                    KAFKA_TRANSACTION_LOG_TOPIC,
+                    &offsets,
+                    &read_config,
+                )
+                .await
+            {
+                Ok(records) => records,
+                Err(e) => {
+                    warn!("Kafka transaction log read failed: {}", e);
+                    break;
+                }
+            };
+            if records.is_empty() {
+                break;
+            }
+            for record in &records {
+                let next = offsets.entry(record.metadata.shard.clone()).or_insert(0);
+                *next = (*next).max(record.metadata.offset + 1);
+                match KafkaTransactionLogRecord::decode(&record.data) {
+                    Ok(KafkaTransactionLogRecord::Transaction(txn)) => {
+                        self.cache.reserve_producer_id(txn.producer_id);
+                        self.cache.merge_transaction(txn);
+                    }
+                    Ok(KafkaTransactionLogRecord::Aborted(list)) => {
+                        self.cache.add_aborted_transactions(list)
+                    }
+                    Ok(KafkaTransactionLogRecord::Committed(list)) => {
+                        self.cache.add_txn_markers(list)
+                    }
+                    Err(e) => warn!(
+                        "Kafka transaction log record at {}:{} is unreadable: {}",
+                        record.metadata.shard, record.metadata.offset, e
+                    ),
+                }
+                applied += 1;
+            }
+        }
+        applied
+    }
+}
+
+/// Validate a transactional produce against its transaction: the producer must
+/// be current and the partition added to the ongoing transaction.
+pub fn check_transactional_produce(
+    txn: &KafkaTransaction,
+    producer_id: i64,
+    producer_epoch: i16,
+    topic: &str,
+    partition: i32,
+) -> Result<(), ResponseError> {
+    check_producer(txn, producer_id, producer_epoch)?;
+    if txn.state != KafkaTransactionState::Ongoing || !txn.has_partition(topic, partition) {
+        return Err(ResponseError::InvalidTxnState);
+    }
+    Ok(())
+}
+
+/// A commit/abort marker in Kafka's control record format: key is
+/// (version, type), value is (version, coordinator epoch).
+pub fn control_record(
+    topic: &str,
+    producer_id: i64,
+    producer_epoch: i16,
+    control_type: i16,
+) -> AdapterWriteRecord {
+    let mut key = BytesMut::with_capacity(4);
+    key.put_i16(CONTROL_RECORD_VERSION);
+    key.put_i16(control_type);
+    let mut value = BytesMut::with_capacity(6);
+    value.put_i16(CONTROL_RECORD_VERSION);
+    value.put_i32(0);
+
+    AdapterWriteRecord::new(topic, value.freeze())
+        .with_key(key.freeze())
+        .with_protocol_data(Some(StorageRecordProtocolData {
+            kafka: Some(StorageRecordProtocolDataKafka {
+                producer_id,
+                producer_epoch,
+                transactional: true,
+                control: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+}
+
+fn check_producer(
+    txn: &KafkaTransaction,
+    producer_id: i64,
+    producer_epoch: i16,
+) -> Result<(), ResponseError> {
+    if producer_id != txn.producer_id {
+        return Err(ResponseError::InvalidProducerIdMapping);
+    }
+    if producer_epoch != txn.producer_epoch {
+        return Err(ResponseError::InvalidProducerEpoch);
+    }
+    Ok(())
+}
+
+// First AddPartitions/AddOffsets after Empty or Complete* starts a transaction.
+fn begin(txn: &mut KafkaTransaction) {
+    if txn.state != KafkaTransactionState::Ongoing {
+        txn.state = KafkaTransactionState::Ongoing;
+        txn.start_time_ms = now_millis() as u64;
+        txn.partitions.clear();
+        txn.groups.clear();
+        txn.pending_offsets.clear();
+    }
+}
+
+// Next epoch for a re-initialized producer; an exhausted epoch moves the
+// transactional id onto a fresh producer id.
+fn bump_epoch(cache: &KafkaCacheManager, txn: &mut KafkaTransaction) {
+    if txn.producer_epoch >= i16::MAX - 1 {
+        txn.producer_id = cache.next_producer_id();
+        txn.producer_epoch = 0;
+    } else {
+        txn.producer_epoch += 1;
+    }
+}
+
+fn is_timed_out(txn: &KafkaTransaction, now_ms: u64) -> bool {
+    txn.state == KafkaTransactionState::Ongoing
+        && now_ms > txn.start_time_ms + txn.timeout_ms.max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage_adapter::storage::{test_add_topic, test_build_storage_driver_manager};
+
+    fn ongoing(partitions: &[(&str, i32)]) -> KafkaTransaction {
+        KafkaTransaction {
+            transactional_id: "tx".to_string(),
+            producer_id: 7,
+            producer_epoch: 3,
+            timeout_ms: 1000,
+            state: KafkaTransactionState::Ongoing,
+            partitions: partitions
+                .iter()
+                .map(|(topic, partition)| KafkaTxnPartition {
+                    topic: topic.to_string(),
+                    partition: *partition,
+                    shard_name: format!("{}-{}", topic, partition),
+                    first_offset: 0,
+                })
+                .collect(),
+            start_time_ms: 10_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn transactional_produce_requires_current_producer_and_added_partition() {
+        let txn = ongoing(&[("orders", 0)]);
+        assert!(check_transactional_produce(&txn, 7, 3, "orders", 0).is_ok());
+        assert_eq!(
+            check_transactional_produce(&txn, 7, 2, "orders", 0),
+            Err(ResponseError::InvalidProducerEpoch)
+        );
+        assert_eq!(
+            check_transactional_produce(&txn, 8, 3, "orders", 0),
+            Err(ResponseError::InvalidProducerIdMapping)
+        );
+        assert_eq!(
+            check_transactional_produce(&txn, 7, 3, "orders", 1),
+            Err(ResponseError::InvalidTxnState)
+        );
+
+        let mut done = txn.clone();
+        done.state = KafkaTransactionState::CompleteCommit;
+        assert_eq!(
+            check_transactional_produce(&done, 7, 3, "orders", 0),
+            Err(ResponseError::InvalidTxnState)
+        );
+    }
+
+    #[test]
+    fn control_record_uses_kafka_marker_layout() {
+        let record = control_record("orders", 7, 3, CONTROL_TYPE_COMMIT);
+        assert_eq!(record.key(), Some([0u8, 0, 0, 1].as_ref()));
+        assert_eq!(record.data.as_ref(), &[0u8, 0, 0, 0, 0, 0]);
+        let kafka = record.protocol_data.unwrap().kafka.unwrap();
+        assert_eq!((kafka.producer_id, kafka.producer_epoch), (7, 3));
+        assert!(kafka.transactional && kafka.control);
+    }
+
+    #[test]
+    fn bump_epoch_rolls_over_to_a_new_producer_id() {
+        let cache = KafkaCacheManager::new();
+        let mut txn = ongoing(&[]);
+        bump_epoch(&cache, &mut txn);
+        assert_eq!((txn.producer_id, txn.producer_epoch), (7, 4));
+
+        txn.producer_epoch = i16::MAX - 1;
+        bump_epoch(&cache, &mut txn);
+        assert_ne!(txn.producer_id, 7);
+        assert_eq!(txn.producer_epoch, 0);
+    }
+
+    #[test]
+    fn begin_resets_a_completed_transaction() {
+        let mut txn = ongoing(&[("orders", 0)]);
+        txn.state = KafkaTransactionState::CompleteAbort;
+        begin(&mut txn);
+        assert_eq!(txn.state, KafkaTransactionState::Ongoing);
+        assert!(txn.partitions.is_empty());
+
+        txn.partitions.push(KafkaTxnPartition::default());
+        begin(&mut txn);
+        assert_eq!(txn.partitions.len(), 1, "an ongoing transaction is kept");
+    }
+
+    #[test]
+    fn only_ongoing_transactions_time_out() {
+        let txn = ongoing(&[]);
+        assert!(!is_timed_out(&txn, 10_500));
+        assert!(is_timed_out(&txn, 11_001));
+
+        let mut done = txn;
+        done.state = KafkaTransactionState::CompleteCommit;
+        assert!(!is_timed_out(&done, 20_000));
+    }
+
+    #[tokio::test]
+    async fn partition_leader_on_another_node_follows_the_transaction_log() {
+        let sdm = test_build_storage_driver_manager().await.unwrap();
+        test_add_topic(&sdm, KAFKA_TRANSACTION_LOG_TOPIC);
+        test_add_topic(&sdm, "orders");
+        let shard = sdm
+            .broker_cache
+            .get_topic_by_name(get_tenant(), "orders")
+            .unwrap()
+            .storage_name_list[&0]
+            .clone();
+
+        // Two brokers share nothing but the storage.
+        let coordinator =
+            TransactionCoordinator::new(sdm.clone(), Arc::new(KafkaCacheManager::new()));
+        let leader = TransactionCoordinator::new(sdm.clone(), Arc::new(KafkaCacheManager::new()));
+
+        let mut txn = ongoing(&[]);
+        txn.partitions.push(KafkaTxnPartition {
+            topic: "orders".to_string(),
+            partition: 0,
+            shard_name: shard.clone(),
+            first_offset: 0,
+        });
+        let txn = coordinator.persist(txn).await.unwrap();
+        assert!(leader.transaction("tx").is_none());
+
+        leader.sync_log().await;
+        let seen = leader.transaction("tx").unwrap();
+        assert!(check_transactional_produce(&seen, 7, 3, "orders", 0).is_ok());
+
+        let rows = sdm
+            .write_to_partition(
+                get_tenant(),
+                "orders",
+                0,
+                &[AdapterWriteRecord::new("orders", "pending")],
+                1,
+            )
+            .await
+            .unwrap();
+        let record_offset = rows[0].offset as i64;
+        assert_eq!(
+            leader.cache.last_stable_offset(&shard, record_offset + 1),
+            0
+        );
+        assert!(!leader.cache.is_txn_decided(&shard, 7, record_offset));
+
+        coordinator.complete(txn, false).await.unwrap();
+        leader.sync_log().await;
+        let seen = leader.transaction("tx").unwrap();
+        assert_eq!(seen.state, KafkaTransactionState::CompleteAbort);
+        assert_eq!(
+            check_transactional_produce(&seen, 7, 3, "orders", 0),
+            Err(ResponseError::InvalidTxnState)
+        );
+        assert_eq!(
+            leader.cache.last_stable_offset(&shard, record_offset + 2),
+            record_offset + 2
+        );
+        let aborted = leader.cache.aborted_transactions(&shard, 0, record_offset);
+        assert_eq!(aborted.len(), 1);
+        assert_eq!(aborted[0].producer_id, 7);
+        assert!(aborted[0].last_offset > record_offset);
+        assert!(leader.cache.is_txn_decided(&shard, 7, record_offset));
+
+        // Catching up again applies nothing twice.
+        assert_eq!(leader.sync_log().await, 0);
+        assert_eq!(
+            leader.cache.aborted_transactions(&shard, 0, i64::MAX).len(),
+            1
+        );
+
+        // A commit is learned through its markers alone.
+        let mut txn = leader.transaction("tx").unwrap();
+        txn.state = KafkaTransactionState::Ongoing;
+        txn.partitions.push(KafkaTxnPartition {
+            topic: "orders".to_string(),
+            partition: 0,
+            shard_name: shard.clone(),
+            first_offset: record_offset + 2,
+        });
+        let txn = coordinator.persist(txn).await.unwrap();
+        let committed_offset = record_offset + 2;
+        assert!(!leader.cache.is_txn_decided(&shard, 7, committed_offset));
+        coordinator.complete(txn, true).await.unwrap();
+        leader.sync_log().await;
+        assert!(leader.cache.is_txn_decided(&shard, 7, committed_offset));
+        assert_eq!(
+            leader.cache.aborted_transactions(&shard, 0, i64::MAX).len(),
+            1
+        );
+    }
+}
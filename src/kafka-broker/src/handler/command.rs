@@ -17,18 +17,24 @@
 use async_trait::async_trait;
 use broker_core::cache::NodeCacheManager;
 use common_config::broker::broker_config;
+use common_security::login::x509::peer_cert_username;
 use kafka_protocol::messages::ResponseHeader;
 use metadata_struct::connection::NetworkConnection;
 use network_server::command::Command;
 use network_server::common::packet::ResponsePackage;
 use protocol::kafka::packet::{KafkaHeader, KafkaPacket, KafkaPacketWrapper};
 use protocol::robust::RobustMQPacket;
+use rule_engine::manager::RuleEngineManager;
+use schema_register::schema::SchemaRegisterManager;
 use std::net::SocketAddr;
 use storage_adapter::driver::StorageDriverManager;
 use tracing::warn;
 
 use crate::core::cache::KafkaCacheManager;
 use crate::core::coordinator::GroupCoordinator;
+use crate::core::sasl::SaslSession;
+use crate::core::share_coordinator::ShareGroupCoordinator;
+use crate::core::txn_coordinator::TransactionCoordinator;
 use crate::kafka::{
     acl, admin, api_versions, auth, config, consumer_group, consumer_group_next,
     consumer_group_offset, delegation_token, fetch, metadata, offset, produce, quota, scram,
@@ -41,6 +47,10 @@ pub struct KafkaHandlerCommand {
     broker_cache: Arc<NodeCacheManager>,
     kafka_cache: Arc<KafkaCacheManager>,
     group_coordinator: Arc<GroupCoordinator>,
+    txn_coordinator: Arc<TransactionCoordinator>,
+    share_coordinator: Arc<ShareGroupCoordinator>,
+    schema_manager: Arc<SchemaRegisterManager>,
+    rule_manager: Arc<RuleEngineManager>,
 }
 
 impl KafkaHandlerCommand {
@@ -48,12 +58,22 @@ pub fn new(
         storage_driver_manager: Arc<StorageDriverManager>,
         broker_cache: Arc<NodeCacheManager>,
         kafka_cache: Arc<KafkaCacheManager>,
+        txn_coordinator: Arc<TransactionCoordinator>,
+        schema_manager: Arc<SchemaRegisterManager>,
+        rule_manager: Arc<RuleEngineManager>,
     ) -> Self {
         KafkaHandlerCommand {
+            txn_coordinator,
+            share_coordinator: Arc::new(ShareGroupCoordinator::new(
+                storage_driver_manager.clone(),
+                kafka_cache.clone(),
+            )),
             storage_driver_manager,
             broker_cache,
             kafka_cache: kafka_cache.clone(),
             group_coordinator: Arc::new(GroupCoordinator::new(kafka_cache)),
+            schema_manager,
+            rule_manager,
         }
     }
 }
@@ -74,6 +94,15 @@ async fn apply(
             KafkaHeader::Response(_) => return None,
         };
 
+        // A verified client certificate authenticates the connection as its
+        // mapped user, the way Kafka's SSL principal does.
+        if self.kafka_cache.get_sasl_session(connection_id).is_none() {
+            if let Some(principal) = peer_cert_username(tcp_connection.peer_cert.as_ref()) {
+                self.kafka_cache
+                    .set_sasl_session(connection_id, SaslSession::Authenticated { principal });
+            }
+        }
+
         // When SASL is enabled, an unauthenticated connection may only negotiate
         // versions or run the SASL handshake; any other request is dropped until
         // the connection authenticates.
@@ -92,13 +121,28 @@ async fn apply(
         let resp_packet = match &wrapper.packet {
             // Core Data Plane
             KafkaPacket::ProduceReq(req) => {
-                produce::process_produce(&self.storage_driver_manager, &self.kafka_cache, req).await
+                produce::process_produce(
+                    &self.storage_driver_manager,
+                    &self.kafka_cache,
+                    &self.txn_coordinator,
+                    &self.schema_manager,
+                    &self.rule_manager,
+                    req,
+                )
+                .await
             }
             KafkaPacket::FetchReq(req) => {
-                fetch::process_fetch(&self.storage_driver_manager, req).await
+                fetch::process_fetch(
+                    &self.storage_driver_manager,
+                    &self.kafka_cache,
+                    &self.txn_coordinator,
+                    req,
+                )
+                .await
             }
             KafkaPacket::ListOffsetsReq(req) => {
-                offset::process_list_offsets(&self.storage_driver_manager, req).await
+                offset::process_list_offsets(&self.storage_driver_manager, &self.kafka_cache, req)
+                    .await
             }
             KafkaPacket::MetadataReq(req) => {
                 metadata::process_metadata(&self.broker_cache, &self.storage_driver_manager, req)
@@ -196,10 +240,16 @@ async fn apply(
             }
             // Topic / Partition Management
             KafkaPacket::CreateTopicsReq(req) => {
-                topic::process_create_topics(&self.storage_driver_manager, req).await
+                let response =
+                    topic::process_create_topics(&self.storage_driver_manager, req).await;
+                self.group_coordinator.topics_changed();
+                response
             }
             KafkaPacket::DeleteTopicsReq(req) => {
-                topic::process_delete_topics(&self.storage_driver_manager, req).await
+                let response =
+                    topic::process_delete_topics(&self.storage_driver_manager, req).await;
+                self.group_coordinator.topics_changed();
+                response
             }
             KafkaPacket::DeleteRecordsReq(req) => {
                 topic::process_delete_records(&self.storage_driver_manager, req).await
@@ -219,18 +269,37 @@ async fn apply(
             }
             // Transaction Support
             KafkaPacket::InitProducerIdReq(req) => {
-                transaction::process_init_producer_id(&self.kafka_cache, req)
+                transaction::process_init_producer_id(&self.kafka_cache, &self.txn_coordinator, req)
+                    .await
             }
             KafkaPacket::AddPartitionsToTxnReq(req) => {
-                transaction::process_add_partitions_to_txn(req)
+                transaction::process_add_partitions_to_txn(&self.txn_coordinator, req).await
+            }
+            KafkaPacket::AddOffsetsToTxnReq(req) => {
+                transaction::process_add_offsets_to_txn(&self.txn_coordinator, req).await
+            }
+            KafkaPacket::EndTxnReq(req) => {
+                transaction::process_end_txn(&self.txn_coordinator, req).await
+            }
+            KafkaPacket::TxnOffsetCommitReq(req) => {
+                transaction::process_txn_offset_commit(&self.txn_coordinator, req).await
             }
-            KafkaPacket::AddOffsetsToTxnReq(req) => transaction::process_add_offsets_to_txn(req),
-            KafkaPacket::EndTxnReq(req) => transaction::process_end_txn(req),
-            KafkaPacket::TxnOffsetCommitReq(req) => transaction::process_txn_offset_commit(req),
             KafkaPacket::DescribeTransactionsReq(req) => {
-                transaction::process_describe_transactions(req)
+                transaction::process_describe_transactions(
+                    &self.kafka_cache,
+                    &self.txn_coordinator,
+                    req,
+                )
+                .await
+            }
+            KafkaPacket::ListTransactionsReq(req) => {
+                transaction::process_list_transactions(
+                    &self.kafka_cache,
+                    &self.txn_coordinator,
+                    req,
+                )
+                .await
             }
-            KafkaPacket::ListTransactionsReq(req) => transaction::process_list_transactions(req),
             // ACL Access Control
             KafkaPacket::DescribeAclsReq(req) => {
                 acl::process_describe_acls(&self.storage_driver_manager, req).await
@@ -338,21 +407,70 @@ async fn apply(
             }
             // Share Group (KIP-932)
             KafkaPacket::ShareGroupHeartbeatReq(req) => {
-                share_group::process_share_group_heartbeat(req)
+                let client_id = match &wrapper.header {
+                    KafkaHeader::Request(h) => h
+                        .client_id
+                        .as_ref()
+                        .map(|s| s.to_string())
+                        .unwrap_or_default(),
+                    KafkaHeader::Response(_) => String::new(),
+                };
+                share_group::process_share_group_heartbeat(
+                    &self.share_coordinator,
+                    &self.storage_driver_manager,
+                    client_id,
+                    req,
+                )
+                .await
             }
             KafkaPacket::ShareGroupDescribeReq(req) => {
-                share_group::process_share_group_describe(req)
+                share_group::process_share_group_describe(
+                    &self.share_coordinator,
+                    &self.storage_driver_manager,
+                    req,
+                )
+                .await
+            }
+            KafkaPacket::ShareFetchReq(req) => {
+                share_group::process_share_fetch(
+                    &self.share_coordinator,
+                    &self.storage_driver_manager,
+                    &self.kafka_cache,
+                    req,
+                )
+                .await
+            }
+            KafkaPacket::ShareAcknowledgeReq(req) => {
+                share_group::process_share_acknowledge(
+                    &self.share_coordinator,
+                    &self.storage_driver_manager,
+                    req,
+                )
+                .await
             }
-            KafkaPacket::ShareFetchReq(req) => share_group::process_share_fetch(req),
-            KafkaPacket::ShareAcknowledgeReq(req) => share_group::process_share_acknowledge(req),
             KafkaPacket::DescribeShareGroupOffsetsReq(req) => {
-                share_group::process_describe_share_group_offsets(req)
+                share_group::process_describe_share_group_offsets(
+                    &self.share_coordinator,
+                    &self.storage_driver_manager,
+                    req,
+                )
+                .await
             }
             KafkaPacket::AlterShareGroupOffsetsReq(req) => {
-                share_group::process_alter_share_group_offsets(req)
+                share_group::process_alter_share_group_offsets(
+                    &self.share_coordinator,
+                    &self.storage_driver_manager,
+                    req,
+                )
+                .await
             }
             KafkaPacket::DeleteShareGroupOffsetsReq(req) => {
-                share_group::process_delete_share_group_offsets(req)
+                share_group::process_delete_share_group_offsets(
+                    &self.share_coordinator,
+                    &self.storage_driver_manager,
+                    req,
+                )
+                .await
             }
             // Response variants — not handled by server
             other => {
@@ -393,10 +511,16 @@ pub fn create_command(
     storage_driver_manager: Arc<StorageDriverManager>,
     broker_cache: Arc<NodeCacheManager>,
     kafka_cache: Arc<KafkaCacheManager>,
+    txn_coordinator: Arc<TransactionCoordinator>,
+    schema_manager: Arc<SchemaRegisterManager>,
+    rule_manager: Arc<RuleEngineManager>,
 ) -> Arc<Box<dyn Command + Send + Sync>> {
     Arc::new(Box::new(KafkaHandlerCommand::new(
         storage_driver_manager,
         broker_cache,
         kafka_cache,
+        txn_coordinator,
+        schema_manager,
+        rule_manager,
     )))
 }
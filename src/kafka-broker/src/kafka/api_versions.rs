@@ -37,6 +37,14 @@ pub fn process_api_versions() -> Option<KafkaPacket> {
         // ── Consumer group (KIP-848) ─────────────────────────────────────
         v(ApiKey::ConsumerGroupHeartbeat, 0, 1),
         v(ApiKey::ConsumerGroupDescribe, 0, 1),
+        // ── Share groups (KIP-932) ───────────────────────────────────────
+        v(ApiKey::ShareGroupHeartbeat, 1, 1),
+        v(ApiKey::ShareGroupDescribe, 1, 1),
+        v(ApiKey::ShareFetch, 1, 1),
+        v(ApiKey::ShareAcknowledge, 1, 1),
+        v(ApiKey::DescribeShareGroupOffsets, 0, 0),
+        v(ApiKey::AlterShareGroupOffsets, 0, 0),
+        v(ApiKey::DeleteShareGroupOffsets, 0, 0),
         // ── Auth ─────────────────────────────────────────────────────────
         // Only v1 handshake is offered: v0 carried SASL tokens as raw bytes
         // outside the Kafka framing, which our network layer cannot parse.
@@ -57,16 +65,16 @@ pub fn process_api_versions() -> Option<KafkaPacket> {
         v(ApiKey::DescribeLogDirs, 0, 2),
         v(ApiKey::AlterPartitionReassignments, 0, 0),
         v(ApiKey::ListPartitionReassignments, 0, 0),
-        // ── Idempotent producer ───────────────────────────────────────────
+        // ── Idempotent / transactional producer ───────────────────────────
         v(ApiKey::InitProducerId, 0, 3),
-        // NOTE: transactions are NOT implemented, so the transaction APIs
-        // (AddPartitionsToTxn/AddOffsetsToTxn/EndTxn/TxnOffsetCommit/
-        // DescribeTransactions/ListTransactions) and DescribeProducers are
-        // deliberately NOT advertised. Their handlers return no response, and
-        // advertising them would make a client send the request and then hang.
-        // A transactional producer still fails fast: InitProducerId with a
-        // transactional_id returns TRANSACTIONAL_ID_AUTHORIZATION_FAILED, and an
-        // unadvertised API surfaces as UnsupportedVersionException client-side.
+        v(ApiKey::AddPartitionsToTxn, 0, 4),
+        v(ApiKey::AddOffsetsToTxn, 0, 3),
+        v(ApiKey::EndTxn, 0, 3),
+        v(ApiKey::TxnOffsetCommit, 0, 3),
+        v(ApiKey::DescribeTransactions, 0, 0),
+        v(ApiKey::ListTransactions, 0, 1),
+        // DescribeProducers is not implemented and deliberately not advertised:
+        // its handler returns no response, so a client would send it and hang.
         // ── ACL ──────────────────────────────────────────────────────────
         v(ApiKey::DescribeAcls, 0, 2),
         v(ApiKey::CreateAcls, 0, 2),
@@ -15,6 +15,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::core::compression::{TopicCompression, COMPRESSION_TYPE_CONFIG};
 use crate::core::dynamic_config::{
     find_broker_config, find_topic_config, ConfigResourceType, DynamicConfigKey, BROKER_CONFIGS,
     TOPIC_CONFIGS,
@@ -62,6 +63,15 @@
 const CONFIG_OP_SET: i8 = 0;
 const CONFIG_OP_DELETE: i8 = 1;
 
+/// Whether `value` is acceptable for topic config `name`. Only configs the
+/// broker actually applies are checked; everything else is stored as given.
+pub(crate) fn is_valid_topic_config_value(name: &str, value: &str) -> bool {
+    match name {
+        COMPRESSION_TYPE_CONFIG => TopicCompression::parse(value).is_some(),
+        _ => true,
+    }
+}
+
 /// Persist topic configs supplied at creation time (CreateTopics `configs`)
 /// into the same resource-config store DescribeConfigs/AlterConfigs use, so
 /// `kafka-topics --create --config k=v` is visible via `--describe` and
@@ -77,7 +87,7 @@ pub async fn store_topic_configs(
         resource_key_and_validator(ConfigResourceType::Topic, topic_name);
     let known: HashMap<String, String> = configs
         .iter()
-        .filter(|(name, _)| name_is_known(name))
+        .filter(|(name, value)| name_is_known(name) && is_valid_topic_config_value(name, value))
         .map(|(k, v)| (k.clone(), v.clone()))
         .collect();
     if known.is_empty() {
@@ -400,6 +410,24 @@ async fn alter_one_resource(
             )));
     }
 
+    if resource_type == ConfigResourceType::Topic {
+        if let Some(invalid) = resource.configs.iter().find(|c| {
+            c.value
+                .as_ref()
+                .is_some_and(|v| !is_valid_topic_config_value(&c.name, v))
+        }) {
+            warn!(
+                "Kafka AlterConfigs rejected invalid value for config '{}' on topic '{}'",
+                invalid.name, resource_name
+            );
+            return base
+                .with_error_code(ResponseError::InvalidConfig.code())
+                .with_error_message(Some(StrBytes::from_static_str(
+                    "Invalid configuration value",
+                )));
+        }
+    }
+
     if validate_only {
         return base.with_error_code(0);
     }
@@ -523,6 +551,25 @@ async fn incremental_alter_one_resource(
             )));
     }
 
+    if resource_type == ConfigResourceType::Topic {
+        if let Some(invalid) = resource.configs.iter().find(|c| {
+            c.config_operation == CONFIG_OP_SET
+                && c.value
+                    .as_ref()
+                    .is_some_and(|v| !is_valid_topic_config_value(&c.name, v))
+        }) {
+            warn!(
+                "Kafka IncrementalAlterConfigs rejected invalid value for config '{}' on topic '{}'",
+                invalid.name, resource_name
+            );
+            return base
+                .with_error_code(ResponseError::InvalidConfig.code())
+                .with_error_message(Some(StrBytes::from_static_str(
+                    "Invalid configuration value",
+                )));
+        }
+    }
+
     if validate_only {
         return base.with_error_code(0);
     }
@@ -695,6 +742,14 @@ fn alterable_config(name: &str, op: i8, value: Option<&str>) -> AlterableConfig
             .with_value(value.map(|v| StrBytes::from(v.to_string())))
     }
 
+    #[test]
+    fn is_valid_topic_config_value_checks_compression_type_only() {
+        assert!(is_valid_topic_config_value("compression.type", "zstd"));
+        assert!(is_valid_topic_config_value("compression.type", "producer"));
+        assert!(!is_valid_topic_config_value("compression.type", "brotli"));
+        assert!(is_valid_topic_config_value("cleanup.policy", "anything"));
+    }
+
     #[test]
     fn apply_incremental_ops_set_inserts_and_overwrites() {
         let mut map = HashMap::new();
@@ -55,11 +55,7 @@ pub async fn process_find_coordinator(
     req: &FindCoordinatorRequest,
 ) -> Option<KafkaPacket> {
     // Group and transaction coordinators both live on the meta-service raft
-    // leader. We resolve it for TRANSACTION too (rather than returning a
-    // retriable CoordinatorNotAvailable, which makes clients retry until
-    // timeout) so that clients proceed to the actual transaction API and fail
-    // fast there — InitProducerId rejects a transactional_id, and the other
-    // transaction APIs aren't advertised, so both surface a clear error at once.
+    // leader, so GROUP and TRANSACTION keys resolve to the same node.
     let resolved = if req.key_type == KEY_TYPE_GROUP || req.key_type == KEY_TYPE_TRANSACTION {
         resolve_group_coordinator(sdm).await
     } else {
@@ -48,13 +48,6 @@ pub async fn process_consumer_group_heartbeat(
             "this node is not the group coordinator",
         ));
     }
-    if req.subscribed_topic_regex.is_some() {
-        return Some(heartbeat_error_response(
-            ResponseError::InvalidRequest.code(),
-            "subscribed topic regex is not supported yet",
-        ));
-    }
-
     let params = ConsumerHeartbeatParams {
         group_id: req.group_id.to_string(),
         member_id: req.member_id.to_string(),
@@ -67,6 +60,7 @@ pub async fn process_consumer_group_heartbeat(
             .subscribed_topic_names
             .as_ref()
             .map(|names| names.iter().map(|n| n.to_string()).collect()),
+        subscribed_topic_regex: req.subscribed_topic_regex.as_ref().map(|r| r.to_string()),
         server_assignor: req.server_assignor.as_ref().map(|s| s.to_string()),
         owned: req.topic_partitions.as_ref().map(|tps| {
             tps.iter()
@@ -85,8 +79,18 @@ pub async fn process_consumer_group_heartbeat(
                 partitions: t.partition,
             })
     };
+    // Regexes never match the broker's internal ("$"-prefixed) topics.
+    let broker_cache = sdm.broker_cache.clone();
+    let list_topics = move || -> Vec<String> {
+        broker_cache
+            .list_topics_by_tenant(tenant)
+            .into_iter()
+            .map(|t| t.topic_name)
+            .filter(|name| !name.starts_with('$'))
+            .collect()
+    };
 
-    let result = coordinator.consumer_heartbeat(params, &resolve_topic);
+    let result = coordinator.consumer_heartbeat(params, &resolve_topic, &list_topics);
 
     Some(KafkaPacket::ConsumerGroupHeartbeatResponse(
         ConsumerGroupHeartbeatResponse::default()
@@ -150,6 +154,9 @@ pub async fn process_consumer_group_describe(
                                         .map(|n| TopicName(StrBytes::from(n.clone())))
                                         .collect(),
                                 )
+                                .with_subscribed_topic_regex(
+                                    m.subscribed_regex.clone().map(StrBytes::from),
+                                )
                                 .with_assignment(to_described_assignment(
                                     &m.assignment,
                                     &info.topic_names,
@@ -15,27 +15,36 @@
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use crate::core::cache::KafkaCacheManager;
+use crate::core::compression::{compression_from_wire, topic_compression, TopicCompression};
 use crate::core::consumer_group_meta::topic_uuid;
+use crate::core::txn_coordinator::TransactionCoordinator;
 use crate::handler::tenant::get_tenant;
 use common_config::broker::broker_config;
 use futures_util::future::join_all;
 use kafka_protocol::error::ResponseError;
 use kafka_protocol::indexmap::IndexMap;
 use kafka_protocol::messages::fetch_request::{FetchPartition, FetchTopic};
-use kafka_protocol::messages::fetch_response::{FetchableTopicResponse, PartitionData};
-use kafka_protocol::messages::{FetchRequest, FetchResponse, TopicName};
+use kafka_protocol::messages::fetch_response::{
+    AbortedTransaction, FetchableTopicResponse, PartitionData,
+};
+use kafka_protocol::messages::{FetchRequest, FetchResponse, ProducerId, TopicName};
 use kafka_protocol::protocol::StrBytes;
 use kafka_protocol::records::{
     Compression, Record, RecordBatchEncoder, RecordEncodeOptions, TimestampType,
 };
 use metadata_struct::adapter::adapter_read_config::AdapterReadConfig;
+use metadata_struct::kafka::transaction::KafkaAbortedTxn;
 use metadata_struct::storage::record::StorageRecord;
 use protocol::kafka::packet::KafkaPacket;
 use storage_adapter::driver::{ArcStorageAdapter, StorageDriverManager};
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::core::constants::{NO_LAST_STABLE_OFFSET, NO_OFFSET, NO_PRODUCER_EPOCH, NO_PRODUCER_ID};
+use crate::core::constants::{
+    ISOLATION_LEVEL_READ_COMMITTED, NO_LAST_STABLE_OFFSET, NO_OFFSET, NO_PRODUCER_EPOCH,
+    NO_PRODUCER_ID,
+};
 
 /// A single requested (topic, partition), either rejected up front or
 /// resolved to a concrete shard ready to be read.
@@ -48,6 +57,7 @@ enum FetchUnitPlan {
         max_bytes: u64,
         high_watermark: i64,
         log_start_offset: i64,
+        compression: TopicCompression,
     },
 }
 
@@ -56,14 +66,21 @@ struct FetchUnit {
     partition_index: i32,
     plan: FetchUnitPlan,
     records: Vec<StorageRecord>,
+    last_stable_offset: i64,
+    // Only set for read_committed fetches.
+    aborted_transactions: Option<Vec<KafkaAbortedTxn>>,
 }
 
 pub async fn process_fetch(
     sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<KafkaCacheManager>,
+    txn_coordinator: &Arc<TransactionCoordinator>,
     req: &FetchRequest,
 ) -> Option<KafkaPacket> {
     let start = Instant::now();
 
+    let read_committed = req.isolation_level == ISOLATION_LEVEL_READ_COMMITTED;
+
     // Fetch v12+ identifies topics by UUID (the `topic` name is left empty), so
     // resolve each requested topic to its name up front and echo the id back on
     // the response — clients match responses to requests by topic_id at those
@@ -78,7 +95,15 @@ pub async fn process_fetch(
             .unwrap_or_else(|| fetch_topic.topic.clone());
         topic_idents.push((response_name, fetch_topic.topic_id));
         units.extend(
-            resolve_fetch_topic(sdm, topic_idx, fetch_topic, resolved_name.as_deref(), req).await,
+            resolve_fetch_topic(
+                sdm,
+                cache,
+                topic_idx,
+                fetch_topic,
+                resolved_name.as_deref(),
+                req,
+            )
+            .await,
         );
     }
 
@@ -145,6 +170,14 @@ pub async fn process_fetch(
         }
     }
 
+    // Open transactions, aborted ranges and markers are replicated through
+    // the transaction log, which the coordinator tails in the background;
+    // records it has not seen decided yet are held back by the LSO.
+    txn_coordinator.ensure_loaded().await;
+    for unit in units.iter_mut() {
+        apply_isolation(cache, unit, read_committed);
+    }
+
     let mut topic_responses: Vec<FetchableTopicResponse> = topic_idents
         .into_iter()
         .map(|(name, id)| {
@@ -207,7 +240,11 @@ fn resolve_fetch_topic_name(
         return Some(name);
     }
 
-    let id = fetch_topic.topic_id;
+    topic_name_by_id(sdm, fetch_topic.topic_id)
+}
+
+/// Reverse the deterministic `topic_uuid` mapping for a topic id.
+pub(crate) fn topic_name_by_id(sdm: &Arc<StorageDriverManager>, id: Uuid) -> Option<String> {
     if id == Uuid::nil() {
         return None;
     }
@@ -220,6 +257,7 @@ fn resolve_fetch_topic_name(
 
 async fn resolve_fetch_topic(
     sdm: &Arc<StorageDriverManager>,
+    cache: &KafkaCacheManager,
     topic_idx: usize,
     fetch_topic: &FetchTopic,
     resolved_name: Option<&str>,
@@ -234,6 +272,8 @@ async fn resolve_fetch_topic(
                 partition_index: p.partition,
                 plan: FetchUnitPlan::Error(err),
                 records: Vec::new(),
+                last_stable_offset: NO_LAST_STABLE_OFFSET,
+                aborted_transactions: None,
             })
             .collect::<Vec<_>>()
     };
@@ -256,6 +296,7 @@ async fn resolve_fetch_topic(
             return to_error_units(ResponseError::UnknownServerError);
         }
     };
+    let compression = topic_compression(sdm, cache, topic_name).await;
 
     fetch_topic
         .partitions
@@ -273,6 +314,7 @@ async fn resolve_fetch_topic(
                     max_bytes: effective_max_bytes(req, p),
                     high_watermark: detail.offset.high_watermark as i64,
                     log_start_offset: detail.offset.start_offset as i64,
+                    compression,
                 },
                 _ => FetchUnitPlan::Error(ResponseError::UnknownTopicOrPartition),
             };
@@ -281,12 +323,14 @@ async fn resolve_fetch_topic(
                 partition_index: p.partition,
                 plan,
                 records: Vec::new(),
+                last_stable_offset: NO_LAST_STABLE_OFFSET,
+                aborted_transactions: None,
             }
         })
         .collect()
 }
 
-async fn read_fetch_unit(
+pub(crate) async fn read_fetch_unit(
     driver: &ArcStorageAdapter,
     shard_name: &str,
     fetch_offset: u64,
@@ -339,6 +383,97 @@ async fn read_all_units(units: &[FetchUnit]) -> Vec<Vec<StorageRecord>> {
     .await
 }
 
+// The last stable offset is taken after the read: a transaction that starts
+// later joins at a high watermark past every record already read, so nothing
+// it writes can slip under the offset computed here.
+fn apply_isolation(cache: &KafkaCacheManager, unit: &mut FetchUnit, read_committed: bool) {
+    let FetchUnitPlan::Data {
+        shard_name,
+        high_watermark,
+        log_start_offset,
+        ..
+    } = &unit.plan
+    else {
+        return;
+    };
+    cache.prune_aborted_transactions(shard_name, *log_start_offset);
+    let last_stable_offset = last_stable_offset(cache, shard_name, *high_watermark, &unit.records);
+    unit.last_stable_offset = last_stable_offset;
+    if !read_committed {
+        return;
+    }
+
+    unit.records
+        .retain(|r| (r.metadata.offset as i64) < last_stable_offset);
+    let aborted = match (unit.records.first(), unit.records.last()) {
+        (Some(first), Some(last)) => cache.aborted_transactions(
+            shard_name,
+            first.metadata.offset as i64,
+            last.metadata.offset as i64,
+        ),
+        _ => Vec::new(),
+    };
+    let committed: Vec<StorageRecord> = unit
+        .records
+        .iter()
+        .filter(|r| !is_aborted(r, &aborted))
+        .cloned()
+        .collect();
+    // A read that ends before an abort marker can be nothing but aborted
+    // records; those are returned as-is and `aborted_transactions` tells the
+    // client to drop them, so the consumer still moves past them.
+    if !committed.is_empty() {
+        unit.records = committed;
+    }
+    unit.aborted_transactions = Some(aborted);
+}
+
+// The cached LSO of the shard, further held back to the first record read
+// whose transaction this node does not know to be decided yet.
+fn last_stable_offset(
+    cache: &KafkaCacheManager,
+    shard_name: &str,
+    high_watermark: i64,
+    records: &[StorageRecord],
+) -> i64 {
+    let stable = cache.last_stable_offset(shard_name, high_watermark);
+    records
+        .iter()
+        .find(|r| is_undecided(cache, shard_name, r))
+        .map_or(stable, |r| stable.min(r.metadata.offset as i64))
+}
+
+// A transactional data record whose commit/abort marker this node has not
+// learned from the transaction log yet.
+fn is_undecided(cache: &KafkaCacheManager, shard_name: &str, record: &StorageRecord) -> bool {
+    record
+        .protocol_data
+        .as_ref()
+        .and_then(|d| d.kafka.as_ref())
+        .is_some_and(|kafka| {
+            kafka.transactional
+                && !kafka.control
+                && !cache.is_txn_decided(
+                    shard_name,
+                    kafka.producer_id,
+                    record.metadata.offset as i64,
+                )
+        })
+}
+
+fn is_aborted(record: &StorageRecord, aborted: &[KafkaAbortedTxn]) -> bool {
+    let Some(kafka) = record.protocol_data.as_ref().and_then(|d| d.kafka.as_ref()) else {
+        return false;
+    };
+    if !kafka.transactional || kafka.control {
+        return false;
+    }
+    let offset = record.metadata.offset as i64;
+    aborted.iter().any(|a| {
+        a.producer_id == kafka.producer_id && a.first_offset <= offset && offset <= a.last_offset
+    })
+}
+
 fn kafka_record_from_storage(sequence: i32, record: &StorageRecord) -> Record {
     let headers: IndexMap<StrBytes, Option<bytes::Bytes>> = record
         .metadata
@@ -356,12 +491,13 @@ fn kafka_record_from_storage(sequence: i32, record: &StorageRecord) -> Record {
         })
         .unwrap_or_default();
 
+    let kafka = record.protocol_data.as_ref().and_then(|d| d.kafka.as_ref());
     Record {
-        transactional: false,
-        control: false,
+        transactional: kafka.is_some_and(|k| k.transactional),
+        control: kafka.is_some_and(|k| k.control),
         partition_leader_epoch: 0,
-        producer_id: NO_PRODUCER_ID,
-        producer_epoch: NO_PRODUCER_EPOCH,
+        producer_id: kafka.map_or(NO_PRODUCER_ID, |k| k.producer_id),
+        producer_epoch: kafka.map_or(NO_PRODUCER_EPOCH, |k| k.producer_epoch),
         timestamp_type: TimestampType::Creation,
         offset: record.metadata.offset as i64,
         sequence,
@@ -372,31 +508,51 @@ fn kafka_record_from_storage(sequence: i32, record: &StorageRecord) -> Record {
     }
 }
 
-fn encode_fetch_records(shard_name: &str, records: &[StorageRecord]) -> Option<bytes::Bytes> {
+/// The record as served to Kafka clients plus the codec it is served in
+/// under the topic's `compression.type`. Values are stored uncompressed;
+/// the encoder compresses the whole batch.
+fn fetch_record(
+    sequence: i32,
+    record: &StorageRecord,
+    compression: TopicCompression,
+) -> (Record, Compression) {
+    let kafka_record = kafka_record_from_storage(sequence, record);
+    let kafka = record.protocol_data.as_ref().and_then(|d| d.kafka.as_ref());
+    let codec = kafka.map_or(Compression::None, |k| compression_from_wire(k.compression));
+    (kafka_record, compression.resolve(codec))
+}
+
+pub(crate) fn encode_fetch_records(
+    shard_name: &str,
+    records: &[StorageRecord],
+    compression: TopicCompression,
+) -> Option<bytes::Bytes> {
     if records.is_empty() {
         return None;
     }
-    let kafka_records: Vec<Record> = records
+    let kafka_records: Vec<(Record, Compression)> = records
         .iter()
         .enumerate()
-        .map(|(i, r)| kafka_record_from_storage(i as i32, r))
+        .map(|(i, r)| fetch_record(i as i32, r, compression))
         .collect();
 
+    // Records produced with different codecs can sit side by side under
+    // `compression.type=producer`; each run gets its own batch.
     let mut buf = bytes::BytesMut::new();
-    let opts = RecordEncodeOptions {
-        version: 2,
-        compression: Compression::None,
-    };
-    match RecordBatchEncoder::encode(&mut buf, kafka_records.iter(), &opts) {
-        Ok(()) => Some(buf.freeze()),
-        Err(e) => {
+    for run in kafka_records.chunk_by(|a, b| a.1 == b.1) {
+        let opts = RecordEncodeOptions {
+            version: 2,
+            compression: run[0].1,
+        };
+        if let Err(e) = RecordBatchEncoder::encode(&mut buf, run.iter().map(|(r, _)| r), &opts) {
             warn!(
                 "Kafka Fetch failed to encode record batch for shard {}: {}",
                 shard_name, e
             );
-            None
+            return None;
         }
     }
+    Some(buf.freeze())
 }
 
 /// A shard read only turns into an error when there *were* records to encode
@@ -416,17 +572,28 @@ fn build_partition_data(unit: &FetchUnit) -> PartitionData {
             shard_name,
             high_watermark,
             log_start_offset,
+            compression,
             ..
         } => {
-            let records_bytes = encode_fetch_records(shard_name, &unit.records);
+            let records_bytes = encode_fetch_records(shard_name, &unit.records, *compression);
             let error_code =
                 partition_error_code(!unit.records.is_empty(), records_bytes.is_some());
+            let aborted_transactions = unit.aborted_transactions.as_ref().map(|list| {
+                list.iter()
+                    .map(|a| {
+                        AbortedTransaction::default()
+                            .with_producer_id(ProducerId(a.producer_id))
+                            .with_first_offset(a.first_offset)
+                    })
+                    .collect()
+            });
             PartitionData::default()
                 .with_partition_index(unit.partition_index)
                 .with_error_code(error_code)
                 .with_high_watermark(*high_watermark)
-                .with_last_stable_offset(NO_LAST_STABLE_OFFSET)
+                .with_last_stable_offset(unit.last_stable_offset)
                 .with_log_start_offset(*log_start_offset)
+                .with_aborted_transactions(aborted_transactions)
                 .with_records(records_bytes)
         }
     }
@@ -436,6 +603,10 @@ fn build_partition_data(unit: &FetchUnit) -> PartitionData {
 mod tests {
     use super::*;
     use kafka_protocol::records::RecordBatchDecoder;
+    use metadata_struct::kafka::transaction::KafkaTxnMarker;
+    use metadata_struct::storage::record::{
+        StorageRecordProtocolData, StorageRecordProtocolDataKafka,
+    };
 
     #[test]
     fn fetch_partition_error_sets_sentinels() {
@@ -505,9 +676,76 @@ fn kafka_record_from_storage_converts_headers() {
         assert_eq!(value.as_deref(), Some(b"abc".as_ref()));
     }
 
+    fn transactional_record(offset: u64, producer_id: i64, control: bool) -> StorageRecord {
+        let mut record = make_storage_record(offset, None, b"payload");
+        record.protocol_data = Some(StorageRecordProtocolData {
+            kafka: Some(StorageRecordProtocolDataKafka {
+                producer_id,
+                producer_epoch: 3,
+                transactional: true,
+                control,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        record
+    }
+
+    #[test]
+    fn kafka_record_from_storage_carries_transaction_metadata() {
+        let kafka_record = kafka_record_from_storage(0, &transactional_record(5, 42, true));
+        assert!(kafka_record.transactional);
+        assert!(kafka_record.control);
+        assert_eq!(kafka_record.producer_id, 42);
+        assert_eq!(kafka_record.producer_epoch, 3);
+
+        let plain = kafka_record_from_storage(0, &make_storage_record(5, None, b"payload"));
+        assert!(!plain.transactional);
+        assert_eq!(plain.producer_id, NO_PRODUCER_ID);
+    }
+
+    #[test]
+    fn is_aborted_matches_producer_and_range_only() {
+        let aborted = vec![KafkaAbortedTxn {
+            shard_name: "shard".to_string(),
+            producer_id: 42,
+            first_offset: 10,
+            last_offset: 20,
+        }];
+        assert!(is_aborted(&transactional_record(15, 42, false), &aborted));
+        // The abort marker itself is skipped by the client, not filtered here.
+        assert!(!is_aborted(&transactional_record(20, 42, true), &aborted));
+        assert!(!is_aborted(&transactional_record(15, 7, false), &aborted));
+        assert!(!is_aborted(&transactional_record(21, 42, false), &aborted));
+        assert!(!is_aborted(
+            &make_storage_record(15, None, b"payload"),
+            &aborted
+        ));
+    }
+
+    #[test]
+    fn undecided_transactional_records_hold_back_the_last_stable_offset() {
+        let cache = KafkaCacheManager::new();
+        let records = vec![
+            make_storage_record(0, None, b"plain"),
+            transactional_record(1, 42, false),
+            transactional_record(2, 42, false),
+            transactional_record(3, 42, true),
+        ];
+        assert_eq!(last_stable_offset(&cache, "shard", 4, &records), 1);
+
+        cache.add_txn_markers(vec![KafkaTxnMarker {
+            shard_name: "shard".to_string(),
+            producer_id: 42,
+            offset: 3,
+        }]);
+        assert_eq!(last_stable_offset(&cache, "shard", 4, &records), 4);
+        assert_eq!(last_stable_offset(&cache, "other", 4, &records), 1);
+    }
+
     #[test]
     fn encode_fetch_records_returns_none_for_empty_input() {
-        assert!(encode_fetch_records("shard", &[]).is_none());
+        assert!(encode_fetch_records("shard", &[], TopicCompression::Producer).is_none());
     }
 
     #[test]
@@ -516,7 +754,8 @@ fn encode_fetch_records_round_trips_via_decode() {
             make_storage_record(0, None, b"one"),
             make_storage_record(1, Some(b"k"), b"two"),
         ];
-        let encoded = encode_fetch_records("shard", &records).expect("non-empty input encodes");
+        let encoded = encode_fetch_records("shard", &records, TopicCompression::Producer)
+            .expect("non-empty input encodes");
 
         let mut buf = encoded;
         let batches = RecordBatchDecoder::decode_all(&mut buf).unwrap();
@@ -527,6 +766,76 @@ fn encode_fetch_records_round_trips_via_decode() {
         assert_eq!(decoded[1].key.as_deref(), Some(b"k".as_ref()));
     }
 
+    fn compressed_record(offset: u64, codec: Compression) -> StorageRecord {
+        let mut record = make_storage_record(offset, None, b"payload");
+        record.protocol_data = Some(StorageRecordProtocolData {
+            kafka: Some(StorageRecordProtocolDataKafka {
+                producer_id: NO_PRODUCER_ID,
+                producer_epoch: NO_PRODUCER_EPOCH,
+                compression: codec as i8,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        record
+    }
+
+    fn decode_batches(encoded: bytes::Bytes) -> Vec<(Compression, Vec<Record>)> {
+        let mut buf = encoded;
+        RecordBatchDecoder::decode_all(&mut buf)
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.compression, b.records))
+            .collect()
+    }
+
+    #[test]
+    fn encode_fetch_records_keeps_producer_codec_per_run() {
+        let records = vec![
+            compressed_record(0, Compression::Lz4),
+            compressed_record(1, Compression::Lz4),
+            compressed_record(2, Compression::Zstd),
+        ];
+        let encoded = encode_fetch_records("shard", &records, TopicCompression::Producer).unwrap();
+
+        let batches = decode_batches(encoded);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].0, Compression::Lz4);
+        assert_eq!(batches[0].1.len(), 2);
+        assert_eq!(batches[1].0, Compression::Zstd);
+        for (_, records) in &batches {
+            for record in records {
+                assert_eq!(record.value.as_deref(), Some(b"payload".as_ref()));
+            }
+        }
+    }
+
+    #[test]
+    fn encode_fetch_records_applies_topic_codec() {
+        let records = vec![
+            compressed_record(0, Compression::Lz4),
+            make_storage_record(1, None, b"payload"),
+        ];
+
+        let encoded =
+            encode_fetch_records("shard", &records, TopicCompression::Uncompressed).unwrap();
+        let batches = decode_batches(encoded);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, Compression::None);
+        assert_eq!(batches[0].1[0].value.as_deref(), Some(b"payload".as_ref()));
+
+        let encoded = encode_fetch_records(
+            "shard",
+            &records,
+            TopicCompression::Codec(Compression::Gzip),
+        )
+        .unwrap();
+        let batches = decode_batches(encoded);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, Compression::Gzip);
+        assert_eq!(batches[0].1.len(), 2);
+    }
+
     fn ensure_test_broker_config() {
         use common_config::broker::{default_broker_config, init_broker_conf_by_config};
         use std::sync::Once;
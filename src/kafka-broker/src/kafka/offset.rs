@@ -15,6 +15,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::core::cache::KafkaCacheManager;
 use crate::handler::tenant::get_tenant;
 use kafka_protocol::error::ResponseError;
 use kafka_protocol::messages::list_offsets_response::{
@@ -31,13 +32,16 @@
 use tracing::warn;
 
 use crate::core::constants::{
-    LIST_OFFSETS_EARLIEST_TIMESTAMP, LIST_OFFSETS_LATEST_TIMESTAMP, NO_OFFSET,
+    ISOLATION_LEVEL_READ_COMMITTED, LIST_OFFSETS_EARLIEST_TIMESTAMP, LIST_OFFSETS_LATEST_TIMESTAMP,
+    NO_OFFSET,
 };
 
 pub async fn process_list_offsets(
     sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<KafkaCacheManager>,
     req: &ListOffsetsRequest,
 ) -> Option<KafkaPacket> {
+    let read_committed = req.isolation_level == ISOLATION_LEVEL_READ_COMMITTED;
     let mut topic_responses = Vec::with_capacity(req.topics.len());
     for topic_req in &req.topics {
         let topic_name = topic_req.name.to_string();
@@ -103,12 +107,17 @@ pub async fn process_list_offsets(
                     return unknown_partition_response(p.partition_index);
                 };
 
-                let offset = resolve_offset_for_partition(
+                let mut offset = resolve_offset_for_partition(
                     p.timestamp,
                     partition,
                     detail,
                     &resolved_by_timestamp,
                 );
+                // A read_committed consumer's end of log is the last stable
+                // offset, not the high watermark.
+                if read_committed && p.timestamp == LIST_OFFSETS_LATEST_TIMESTAMP {
+                    offset = cache.last_stable_offset(&detail.shard_name, offset);
+                }
 
                 ListOffsetsPartitionResponse::default()
                     .with_partition_index(p.partition_index)
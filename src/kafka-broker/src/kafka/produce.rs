@@ -15,30 +15,47 @@
 use std::sync::Arc;
 
 use crate::core::cache::{KafkaCacheManager, SequenceCheck};
+use crate::core::compression::{compression_to_wire, topic_compression, TopicCompression};
+use crate::core::coordinator_locator::{coordinator_node_id, is_coordinator_node};
+use crate::core::txn_coordinator::{check_transactional_produce, TransactionCoordinator};
 use crate::handler::tenant::get_tenant;
+use crate::kafka::topic::KafkaRuleTopicInit;
+use bytes::{Bytes, BytesMut};
 use common_base::error::common::CommonError;
+use common_base::tools::now_millis;
 use common_config::broker::broker_config;
 use futures_util::future::join_all;
+use grpc_clients::broker::common::call::broker_forward_kafka_produce;
 use kafka_protocol::error::ResponseError;
 use kafka_protocol::messages::produce_request::{PartitionProduceData, TopicProduceData};
 use kafka_protocol::messages::produce_response::{PartitionProduceResponse, TopicProduceResponse};
 use kafka_protocol::messages::{ProduceRequest, ProduceResponse};
-use kafka_protocol::records::{Record, RecordBatchDecoder};
+use kafka_protocol::protocol::{Decodable, Encodable};
+use kafka_protocol::records::{Compression, Record, RecordBatchDecoder};
 use metadata_struct::adapter::adapter_read_config::AdapterWriteRespRow;
 use metadata_struct::adapter::adapter_record::{AdapterWriteRecord, RecordHeader};
+use metadata_struct::kafka::transaction::KafkaTransaction;
+use metadata_struct::storage::record::{StorageRecordProtocolData, StorageRecordProtocolDataKafka};
 use metadata_struct::topic::Topic;
+use protocol::broker::broker::ForwardKafkaProduceRequest;
 use protocol::kafka::packet::KafkaPacket;
+use rule_engine::manager::RuleEngineManager;
+use rule_engine::sql::RuleMessage;
+use schema_register::schema::{dead_letter_record, SchemaRegisterManager};
 use storage_adapter::driver::{ArcStorageAdapter, StorageDriverManager};
 use tracing::warn;
 
 use crate::core::constants::{
-    NO_BASE_OFFSET, NO_LOG_APPEND_TIME, NO_PRODUCER_EPOCH, NO_PRODUCER_ID, PRODUCE_ACKS_NONE,
-    VALID_ACKS,
+    FORWARD_PRODUCE_VERSION, NO_BASE_OFFSET, NO_LOG_APPEND_TIME, NO_PRODUCER_EPOCH, NO_PRODUCER_ID,
+    PRODUCE_ACKS_NONE, VALID_ACKS,
 };
 
 pub async fn process_produce(
     sdm: &Arc<StorageDriverManager>,
     cache: &Arc<KafkaCacheManager>,
+    txn_coordinator: &Arc<TransactionCoordinator>,
+    schema_manager: &Arc<SchemaRegisterManager>,
+    rule_manager: &Arc<RuleEngineManager>,
     req: &ProduceRequest,
 ) -> Option<KafkaPacket> {
     if !VALID_ACKS.contains(&req.acks) {
@@ -48,30 +65,154 @@ pub async fn process_produce(
         )));
     }
 
-    // Transactions aren't implemented (only KIP-98 idempotence is): reject a
-    // transactional produce rather than accept it with no atomicity guarantee.
-    // Idempotent producers send a null transactional_id and are handled below.
-    if req.transactional_id.is_some() {
-        return Some(KafkaPacket::ProduceResponse(produce_error_response(
-            req,
-            ResponseError::TransactionalIdAuthorizationFailed,
-        )));
+    // Transactional records are ordered against the transaction's markers by
+    // the coordinator's lock, so they are written from the coordinator node.
+    if req.transactional_id.is_some() && !is_coordinator_node(sdm).await {
+        return match forward_produce(sdm, req).await {
+            Ok(resp) => resp.map(KafkaPacket::ProduceResponse),
+            Err(e) => {
+                warn!("Kafka transactional produce forward failed: {}", e);
+                Some(KafkaPacket::ProduceResponse(produce_error_response(
+                    req,
+                    ResponseError::NotLeaderOrFollower,
+                )))
+            }
+        };
     }
 
-    let topic_responses = join_all(
-        req.topic_data
-            .iter()
-            .map(|topic_data| produce_to_topic(sdm, cache, topic_data, req.acks)),
+    produce_local(
+        sdm,
+        cache,
+        txn_coordinator,
+        schema_manager,
+        rule_manager,
+        req,
     )
+    .await
+    .map(KafkaPacket::ProduceResponse)
+}
+
+/// Serve a transactional produce another broker forwarded to this node, the
+/// transaction coordinator. Returns the encoded response, None for acks=0.
+pub async fn process_forwarded_produce(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<KafkaCacheManager>,
+    txn_coordinator: &Arc<TransactionCoordinator>,
+    schema_manager: &Arc<SchemaRegisterManager>,
+    rule_manager: &Arc<RuleEngineManager>,
+    api_version: i16,
+    request: &[u8],
+) -> Result<Option<Vec<u8>>, CommonError> {
+    let mut buf = Bytes::copy_from_slice(request);
+    let req = ProduceRequest::decode(&mut buf, api_version)?;
+
+    // The coordinator moved while the request was in flight; the client
+    // retries on this error and the new coordinator takes it.
+    let resp = if is_coordinator_node(sdm).await {
+        produce_local(
+            sdm,
+            cache,
+            txn_coordinator,
+            schema_manager,
+            rule_manager,
+            &req,
+        )
+        .await
+    } else {
+        Some(produce_error_response(
+            &req,
+            ResponseError::NotLeaderOrFollower,
+        ))
+    };
+    let Some(resp) = resp else {
+        return Ok(None);
+    };
+    let mut buf = BytesMut::new();
+    resp.encode(&mut buf, api_version)?;
+    Ok(Some(buf.to_vec()))
+}
+
+async fn forward_produce(
+    sdm: &Arc<StorageDriverManager>,
+    req: &ProduceRequest,
+) -> Result<Option<ProduceResponse>, CommonError> {
+    let node_id = coordinator_node_id(sdm).await.ok_or_else(|| {
+        CommonError::CommonError("Kafka transaction coordinator is not known".to_string())
+    })?;
+    let addr = sdm
+        .broker_cache
+        .node_lists
+        .get(&node_id)
+        .map(|node| node.grpc_addr.clone())
+        .ok_or_else(|| {
+            CommonError::CommonError(format!(
+                "Kafka transaction coordinator {} is not known to this node",
+                node_id
+            ))
+        })?;
+
+    let mut buf = BytesMut::new();
+    req.encode(&mut buf, FORWARD_PRODUCE_VERSION)?;
+    let request = ForwardKafkaProduceRequest {
+        api_version: FORWARD_PRODUCE_VERSION as i32,
+        request: buf.to_vec(),
+    };
+    let reply =
+        broker_forward_kafka_produce(&sdm.engine_storage_handler.client_pool, &[addr], request)
+            .await?;
+    if !reply.has_response {
+        return Ok(None);
+    }
+    let mut buf = Bytes::from(reply.response);
+    Ok(Some(ProduceResponse::decode(
+        &mut buf,
+        FORWARD_PRODUCE_VERSION,
+    )?))
+}
+
+async fn produce_local(
+    sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<KafkaCacheManager>,
+    txn_coordinator: &Arc<TransactionCoordinator>,
+    schema_manager: &Arc<SchemaRegisterManager>,
+    rule_manager: &Arc<RuleEngineManager>,
+    req: &ProduceRequest,
+) -> Option<ProduceResponse> {
+    // A transactional produce holds the transaction's lock (shared) for the
+    // whole write, so EndTxn can't place its marker ahead of these records.
+    let (_txn_guard, txn) = match &req.transactional_id {
+        Some(transactional_id) => {
+            txn_coordinator.ensure_loaded().await;
+            let guard = txn_coordinator.produce_guard(transactional_id).await;
+            let Some(txn) = txn_coordinator.transaction(transactional_id) else {
+                return Some(produce_error_response(
+                    req,
+                    ResponseError::InvalidProducerIdMapping,
+                ));
+            };
+            (Some(guard), Some(txn))
+        }
+        None => (None, None),
+    };
+
+    let topic_responses = join_all(req.topic_data.iter().map(|topic_data| {
+        produce_to_topic(
+            sdm,
+            cache,
+            schema_manager,
+            rule_manager,
+            topic_data,
+            txn.as_ref(),
+            req.acks,
+        )
+    }))
     .await;
 
     if req.acks == PRODUCE_ACKS_NONE {
         return None;
     }
 
-    Some(KafkaPacket::ProduceResponse(
-        ProduceResponse::default().with_responses(topic_responses),
-    ))
+    Some(ProduceResponse::default().with_responses(topic_responses))
 }
 
 fn produce_partition_error(index: i32, err: ResponseError) -> PartitionProduceResponse {
@@ -91,12 +232,14 @@ fn produce_partition_ok(index: i32, base_offset: i64) -> PartitionProduceRespons
 }
 
 /// Decoded produce payload plus the idempotence identity carried on the batch
-/// (each decoded `Record` inherits the batch's producer id / base sequence).
+/// (each decoded `Record` inherits the batch's producer id / base sequence)
+/// and the codec the producer compressed it with.
 struct DecodedProduce {
     records: Vec<AdapterWriteRecord>,
     producer_id: i64,
     producer_epoch: i16,
     base_sequence: i32,
+    compression: Compression,
 }
 
 fn decode_produce_records(topic_name: &str, records: &bytes::Bytes) -> Option<DecodedProduce> {
@@ -112,6 +255,11 @@ fn decode_produce_records(topic_name: &str, records: &bytes::Bytes) -> Option<De
         }
     };
 
+    // A produce request carries one batch per partition in practice; should a
+    // client send several, the first one's codec stands for all of them.
+    let compression = batches
+        .first()
+        .map_or(Compression::None, |batch| batch.compression);
     let kafka_records: Vec<Record> = batches
         .into_iter()
         .flat_map(|batch| batch.records)
@@ -129,6 +277,7 @@ fn decode_produce_records(topic_name: &str, records: &bytes::Bytes) -> Option<De
         producer_id,
         producer_epoch,
         base_sequence,
+        compression,
     })
 }
 
@@ -156,12 +305,18 @@ fn adapter_record_from_kafka(topic_name: &str, record: Record) -> AdapterWriteRe
     adapter_record
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn produce_to_partition(
+    sdm: &Arc<StorageDriverManager>,
     driver: &ArcStorageAdapter,
     cache: &Arc<KafkaCacheManager>,
+    schema_manager: &Arc<SchemaRegisterManager>,
+    rule_manager: &Arc<RuleEngineManager>,
     topic: &Topic,
     topic_name: &str,
+    compression: TopicCompression,
     partition_data: &PartitionProduceData,
+    txn: Option<&KafkaTransaction>,
     acks: i16,
 ) -> PartitionProduceResponse {
     if partition_data.index < 0 || partition_data.index as u32 >= topic.partition {
@@ -182,7 +337,7 @@ async fn produce_to_partition(
         return produce_partition_error(partition_data.index, ResponseError::MessageTooLarge);
     }
 
-    let Some(decoded) = decode_produce_records(topic_name, records) else {
+    let Some(mut decoded) = decode_produce_records(topic_name, records) else {
         return produce_partition_error(partition_data.index, ResponseError::CorruptMessage);
     };
 
@@ -190,6 +345,12 @@ async fn produce_to_partition(
         return produce_partition_ok(partition_data.index, NO_BASE_OFFSET);
     }
 
+    // Checked before the sequence bookkeeping so a rejected batch can be
+    // fixed and resent with the same sequence.
+    if !check_record_schemas(sdm, schema_manager, topic_name, &decoded.records).await {
+        return produce_partition_error(partition_data.index, ResponseError::InvalidRecord);
+    }
+
     // Shard for this partition was already resolved once at the topic level
     // (see `produce_to_topic`), so no per-partition topic/driver lookup here.
     let Some(shard_name) = topic.storage_name_list.get(&(partition_data.index as u32)) else {
@@ -199,6 +360,18 @@ async fn produce_to_partition(
         );
     };
 
+    if let Some(txn) = txn {
+        if let Err(err) = check_transactional_produce(
+            txn,
+            decoded.producer_id,
+            decoded.producer_epoch,
+            topic_name,
+            partition_data.index,
+        ) {
+            return produce_partition_error(partition_data.index, err);
+        }
+    }
+
     // Idempotent producers (producer_id >= 0) tag each batch with a base
     // sequence; dedup exact retries and reject gaps so a resend can't create
     // duplicate records. Non-idempotent produce (producer_id < 0) skips this.
@@ -229,6 +402,35 @@ async fn produce_to_partition(
         }
     }
 
+    // Rules see the values as produced, before compression. Transactional
+    // records are left out since the transaction may still abort.
+    let rule_payloads: Vec<Bytes> =
+        if txn.is_none() && rule_manager.is_match_topic(get_tenant(), topic_name) {
+            decoded.records.iter().map(|r| r.data.clone()).collect()
+        } else {
+            Vec::new()
+        };
+
+    let codec = compression.resolve(decoded.compression);
+    if idempotent || codec != Compression::None {
+        // Keep the producer (and whether this is a transactional write) with
+        // each record: Fetch needs it to rebuild batches and filter aborts.
+        // The codec tells Fetch which compression to rebuild them with.
+        let kafka = StorageRecordProtocolDataKafka {
+            producer_id: decoded.producer_id,
+            producer_epoch: decoded.producer_epoch,
+            transactional: txn.is_some(),
+            control: false,
+            compression: compression_to_wire(codec),
+        };
+        for record in decoded.records.iter_mut() {
+            record.protocol_data = Some(StorageRecordProtocolData {
+                kafka: Some(kafka.clone()),
+                ..Default::default()
+            });
+        }
+    }
+
     let result = driver.write(shard_name, &decoded.records, acks as i8).await;
 
     if idempotent {
@@ -249,9 +451,39 @@ async fn produce_to_partition(
         }
     }
 
+    if !rule_payloads.is_empty() {
+        if let Ok(rows) = &result {
+            if !rows.iter().any(|r| r.is_error()) {
+                process_rules(sdm, rule_manager, topic_name, rule_payloads).await;
+            }
+        }
+    }
+
     build_produce_response(topic_name, partition_data.index, result)
 }
 
+async fn process_rules(
+    sdm: &Arc<StorageDriverManager>,
+    rule_manager: &Arc<RuleEngineManager>,
+    topic_name: &str,
+    payloads: Vec<Bytes>,
+) {
+    let timestamp = now_millis() as u64;
+    for payload in payloads {
+        let message = RuleMessage {
+            tenant: get_tenant().to_string(),
+            protocol: "kafka".to_string(),
+            topic: topic_name.to_string(),
+            payload,
+            timestamp,
+            ..Default::default()
+        };
+        rule_manager
+            .process(sdm, &KafkaRuleTopicInit { sdm }, &message)
+            .await;
+    }
+}
+
 fn build_produce_response(
     topic_name: &str,
     index: i32,
@@ -304,10 +536,55 @@ fn classify_write_error(e: &CommonError) -> ResponseError {
     }
 }
 
+/// Checks each record value against the schemas bound to the topic. Values
+/// must use the Confluent wire format once the topic's value subject has
+/// registered versions. Rejected values go to the dead-letter topic, if one is
+/// configured, and fail the whole batch.
+async fn check_record_schemas(
+    sdm: &Arc<StorageDriverManager>,
+    schema_manager: &Arc<SchemaRegisterManager>,
+    topic_name: &str,
+    records: &[AdapterWriteRecord],
+) -> bool {
+    let tenant = get_tenant();
+    let mut valid = true;
+    for record in records {
+        let Err(e) = schema_manager.validate_payload(tenant, topic_name, &record.data, true) else {
+            continue;
+        };
+        valid = false;
+        let dead_letter_topic = &broker_config().kafka_runtime.schema_dead_letter_topic;
+        if dead_letter_topic.is_empty() {
+            // The batch fails as a whole, no need to check the rest.
+            break;
+        }
+        let dead_letter = dead_letter_record(
+            dead_letter_topic,
+            "kafka",
+            topic_name,
+            &e.to_string(),
+            &record.data,
+        );
+        if let Err(err) = sdm
+            .write(tenant, dead_letter_topic, &[dead_letter], 1)
+            .await
+        {
+            warn!(
+                "Kafka Produce failed to write an invalid record for {} to dead-letter topic {}: {}",
+                topic_name, dead_letter_topic, err
+            );
+        }
+    }
+    valid
+}
+
 async fn produce_to_topic(
     sdm: &Arc<StorageDriverManager>,
     cache: &Arc<KafkaCacheManager>,
+    schema_manager: &Arc<SchemaRegisterManager>,
+    rule_manager: &Arc<RuleEngineManager>,
     topic_data: &TopicProduceData,
+    txn: Option<&KafkaTransaction>,
     acks: i16,
 ) -> TopicProduceResponse {
     let topic_name = topic_data.name.to_string();
@@ -327,14 +604,25 @@ async fn produce_to_topic(
             .with_partition_responses(partitions);
     };
 
+    let compression = topic_compression(sdm, cache, &topic_name).await;
+
     // Partitions are independent shards, so write them concurrently instead
     // of one at a time.
-    let partitions = join_all(
-        topic_data
-            .partition_data
-            .iter()
-            .map(|p| produce_to_partition(&driver, cache, &topic, &topic_name, p, acks)),
-    )
+    let partitions = join_all(topic_data.partition_data.iter().map(|p| {
+        produce_to_partition(
+            sdm,
+            &driver,
+            cache,
+            schema_manager,
+            rule_manager,
+            &topic,
+            &topic_name,
+            compression,
+            p,
+            txn,
+            acks,
+        )
+    }))
     .await;
 
     TopicProduceResponse::default()
@@ -528,6 +816,43 @@ fn decode_produce_records_round_trips_a_batch() {
         assert_eq!(decoded.records[0].key(), None);
         assert_eq!(decoded.records[1].data.as_ref(), b"two");
         assert_eq!(decoded.records[1].key(), Some(b"k".as_ref()));
+        assert_eq!(decoded.compression, Compression::None);
+    }
+
+    #[test]
+    fn decode_produce_records_accepts_every_codec() {
+        let record = Record {
+            transactional: false,
+            control: false,
+            partition_leader_epoch: 0,
+            producer_id: NO_PRODUCER_ID,
+            producer_epoch: NO_PRODUCER_EPOCH,
+            timestamp_type: TimestampType::Creation,
+            offset: 0,
+            sequence: 0,
+            timestamp: 0,
+            key: None,
+            value: Some(bytes::Bytes::from_static(b"compressed payload")),
+            headers: Default::default(),
+        };
+
+        for compression in [
+            Compression::Gzip,
+            Compression::Snappy,
+            Compression::Lz4,
+            Compression::Zstd,
+        ] {
+            let mut buf = bytes::BytesMut::new();
+            let opts = RecordEncodeOptions {
+                version: 2,
+                compression,
+            };
+            RecordBatchEncoder::encode(&mut buf, [&record], &opts).unwrap();
+
+            let decoded = decode_produce_records("my-topic", &buf.freeze()).unwrap();
+            assert_eq!(decoded.compression, compression);
+            assert_eq!(decoded.records[0].data.as_ref(), b"compressed payload");
+        }
     }
 
     #[test]
@@ -12,43 +12,923 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::core::assignor::TopicMeta;
+use crate::core::cache::KafkaCacheManager;
+use crate::core::compression::{topic_compression, TopicCompression};
+use crate::core::constants::{
+    SHARE_DEFAULT_MAX_RECORDS, SHARE_GROUP_HEARTBEAT_INTERVAL_MS, SHARE_RECORD_LOCK_DURATION_MS,
+    SHARE_SESSION_FINAL_EPOCH,
+};
+use crate::core::consumer_group_meta::topic_uuid;
+use crate::core::consumer_heartbeat::LEAVE_EPOCH;
+use crate::core::coordinator_locator::{is_coordinator_node, resolve_group_coordinator};
+use crate::core::share_coordinator::{acquired_ranges, ShareAcquired, ShareGroupCoordinator};
+use crate::core::share_group_meta::SHARE_ASSIGNOR;
+use crate::core::share_heartbeat::ShareHeartbeatParams;
+use crate::handler::tenant::get_tenant;
+use crate::kafka::fetch::{encode_fetch_records, topic_name_by_id};
+use common_config::broker::broker_config;
+use futures_util::future::select_all;
+use kafka_protocol::error::ResponseError;
+use kafka_protocol::messages::alter_share_group_offsets_response::{
+    AlterShareGroupOffsetsResponsePartition, AlterShareGroupOffsetsResponseTopic,
+};
+use kafka_protocol::messages::delete_share_group_offsets_response::DeleteShareGroupOffsetsResponseTopic;
+use kafka_protocol::messages::describe_share_group_offsets_response::{
+    DescribeShareGroupOffsetsResponseGroup, DescribeShareGroupOffsetsResponsePartition,
+    DescribeShareGroupOffsetsResponseTopic,
+};
+use kafka_protocol::messages::share_acknowledge_response::{
+    LeaderIdAndEpoch as AckLeaderIdAndEpoch, NodeEndpoint as AckNodeEndpoint,
+    PartitionData as AckPartitionData, ShareAcknowledgeTopicResponse,
+};
+use kafka_protocol::messages::share_fetch_request::AcknowledgementBatch;
+use kafka_protocol::messages::share_fetch_response::{
+    AcquiredRecords, LeaderIdAndEpoch, NodeEndpoint, PartitionData, ShareFetchableTopicResponse,
+};
+use kafka_protocol::messages::share_group_describe_response::{
+    Assignment as DescribedAssignment, DescribedGroup, Member,
+    TopicPartitions as DescribedTopicPartitions,
+};
+use kafka_protocol::messages::share_group_heartbeat_response::{Assignment, TopicPartitions};
 use kafka_protocol::messages::{
-    AlterShareGroupOffsetsRequest, DeleteShareGroupOffsetsRequest,
-    DescribeShareGroupOffsetsRequest, ShareAcknowledgeRequest, ShareFetchRequest,
-    ShareGroupDescribeRequest, ShareGroupHeartbeatRequest,
+    AlterShareGroupOffsetsRequest, AlterShareGroupOffsetsResponse, DeleteShareGroupOffsetsRequest,
+    DeleteShareGroupOffsetsResponse, DescribeShareGroupOffsetsRequest,
+    DescribeShareGroupOffsetsResponse, ShareAcknowledgeRequest, ShareAcknowledgeResponse,
+    ShareFetchRequest, ShareFetchResponse, ShareGroupDescribeRequest, ShareGroupDescribeResponse,
+    ShareGroupHeartbeatRequest, ShareGroupHeartbeatResponse, TopicName,
 };
+use kafka_protocol::protocol::StrBytes;
 use protocol::kafka::packet::KafkaPacket;
+use storage_adapter::driver::StorageDriverManager;
+use uuid::Uuid;
+
+pub async fn process_share_group_heartbeat(
+    coordinator: &Arc<ShareGroupCoordinator>,
+    sdm: &Arc<StorageDriverManager>,
+    client_id: String,
+    req: &ShareGroupHeartbeatRequest,
+) -> Option<KafkaPacket> {
+    if !is_coordinator_node(sdm).await {
+        return Some(heartbeat_error_response(
+            ResponseError::NotCoordinator.code(),
+            "this node is not the group coordinator",
+        ));
+    }
+
+    let group_id = req.group_id.to_string();
+    let params = ShareHeartbeatParams {
+        group_id: group_id.clone(),
+        member_id: req.member_id.to_string(),
+        member_epoch: req.member_epoch,
+        rack_id: req.rack_id.as_ref().map(|s| s.to_string()),
+        client_id,
+        subscribed_topics: req
+            .subscribed_topic_names
+            .as_ref()
+            .map(|names| names.iter().map(|n| n.to_string()).collect()),
+    };
 
-pub fn process_share_group_heartbeat(_req: &ShareGroupHeartbeatRequest) -> Option<KafkaPacket> {
-    None
+    let result = {
+        let broker_cache = sdm.broker_cache.clone();
+        let tenant = get_tenant();
+        let resolve_topic = move |name: &str| -> Option<TopicMeta> {
+            broker_cache
+                .get_topic_by_name(tenant, name)
+                .map(|t| TopicMeta {
+                    topic_id: topic_uuid(tenant, name),
+                    partitions: t.partition,
+                })
+        };
+        coordinator.heartbeat(params, &resolve_topic)
+    };
+
+    // A member that leaves gives back every record it still holds.
+    if result.error_code == 0 && result.member_epoch == LEAVE_EPOCH {
+        coordinator
+            .release_member(&group_id, &result.member_id)
+            .await;
+    }
+
+    Some(KafkaPacket::ShareGroupHeartbeatResponse(
+        ShareGroupHeartbeatResponse::default()
+            .with_error_code(result.error_code)
+            .with_error_message(result.error_message.map(StrBytes::from))
+            .with_member_id(Some(StrBytes::from(result.member_id)))
+            .with_member_epoch(result.member_epoch)
+            .with_heartbeat_interval_ms(SHARE_GROUP_HEARTBEAT_INTERVAL_MS)
+            .with_assignment(result.assignment.map(to_wire_assignment)),
+    ))
 }
 
-pub fn process_share_group_describe(_req: &ShareGroupDescribeRequest) -> Option<KafkaPacket> {
-    None
+pub async fn process_share_group_describe(
+    coordinator: &ShareGroupCoordinator,
+    sdm: &Arc<StorageDriverManager>,
+    req: &ShareGroupDescribeRequest,
+) -> Option<KafkaPacket> {
+    let authorized_operations = if req.include_authorized_operations {
+        -1
+    } else {
+        i32::MIN
+    };
+
+    if !is_coordinator_node(sdm).await {
+        let groups = req
+            .group_ids
+            .iter()
+            .map(|id| {
+                DescribedGroup::default()
+                    .with_group_id(id.clone())
+                    .with_error_code(ResponseError::NotCoordinator.code())
+                    .with_group_state(StrBytes::from_static_str("Dead"))
+                    .with_authorized_operations(authorized_operations)
+            })
+            .collect();
+        return Some(KafkaPacket::ShareGroupDescribeResponse(
+            ShareGroupDescribeResponse::default().with_groups(groups),
+        ));
+    }
+
+    let groups = req
+        .group_ids
+        .iter()
+        .map(|id| match coordinator.describe(id.as_str(), get_tenant()) {
+            Some(info) => {
+                let members = info
+                    .members
+                    .iter()
+                    .map(|m| {
+                        Member::default()
+                            .with_member_id(StrBytes::from(m.member_id.clone()))
+                            .with_rack_id(m.rack_id.clone().map(StrBytes::from))
+                            .with_member_epoch(m.member_epoch)
+                            .with_client_id(StrBytes::from(m.client_id.clone()))
+                            .with_client_host(StrBytes::from_static_str(""))
+                            .with_subscribed_topic_names(
+                                m.subscribed
+                                    .iter()
+                                    .map(|n| TopicName(StrBytes::from(n.clone())))
+                                    .collect(),
+                            )
+                            .with_assignment(to_described_assignment(
+                                &m.assignment,
+                                &info.topic_names,
+                            ))
+                    })
+                    .collect();
+                DescribedGroup::default()
+                    .with_group_id(id.clone())
+                    .with_error_code(0)
+                    .with_group_state(StrBytes::from(info.state))
+                    .with_group_epoch(info.group_epoch)
+                    .with_assignment_epoch(info.assignment_epoch)
+                    .with_assignor_name(StrBytes::from_static_str(SHARE_ASSIGNOR))
+                    .with_members(members)
+                    .with_authorized_operations(authorized_operations)
+            }
+            None => DescribedGroup::default()
+                .with_group_id(id.clone())
+                .with_error_code(ResponseError::GroupIdNotFound.code())
+                .with_group_state(StrBytes::from_static_str("Dead"))
+                .with_authorized_operations(authorized_operations),
+        })
+        .collect();
+
+    Some(KafkaPacket::ShareGroupDescribeResponse(
+        ShareGroupDescribeResponse::default().with_groups(groups),
+    ))
 }
 
-pub fn process_share_fetch(_req: &ShareFetchRequest) -> Option<KafkaPacket> {
-    None
+pub async fn process_share_fetch(
+    coordinator: &Arc<ShareGroupCoordinator>,
+    sdm: &Arc<StorageDriverManager>,
+    cache: &Arc<KafkaCacheManager>,
+    req: &ShareFetchRequest,
+) -> Option<KafkaPacket> {
+    let start = Instant::now();
+    let (Some(group_id), Some(member_id)) = (req.group_id.as_ref(), req.member_id.as_ref()) else {
+        return Some(share_fetch_error(
+            ResponseError::InvalidRequest,
+            "group id and member id are required",
+        ));
+    };
+    let group_id = group_id.to_string();
+    let member_id = member_id.to_string();
+
+    if let Some(owner) = share_owner_redirect(coordinator, sdm).await {
+        return Some(share_fetch_redirect(req, owner));
+    }
+
+    let added: Vec<(Uuid, i32)> = req
+        .topics
+        .iter()
+        .flat_map(|t| t.partitions.iter().map(|p| (t.topic_id, p.partition_index)))
+        .collect();
+    let forgotten: Vec<(Uuid, i32)> = req
+        .forgotten_topics_data
+        .iter()
+        .flat_map(|t| t.partitions.iter().map(|p| (t.topic_id, *p)))
+        .collect();
+    let session_partitions = match coordinator.update_session(
+        &group_id,
+        &member_id,
+        req.share_session_epoch,
+        &added,
+        &forgotten,
+    ) {
+        Ok(partitions) => partitions,
+        Err(e) => return Some(share_fetch_error(e, "share session rejected the request")),
+    };
+
+    let mut names: HashMap<Uuid, Option<String>> = HashMap::new();
+    let mut topic_name = |id: Uuid| {
+        names
+            .entry(id)
+            .or_insert_with(|| topic_name_by_id(sdm, id))
+            .clone()
+    };
+
+    // Partitions this request touched are always answered, even when there
+    // is nothing to report for them.
+    let mut responses: BTreeMap<(Uuid, i32), PartitionData> = added
+        .iter()
+        .map(|&(id, partition)| ((id, partition), share_partition_data(partition)))
+        .collect();
+
+    for topic in &req.topics {
+        let name = topic_name(topic.topic_id);
+        for p in &topic.partitions {
+            if p.acknowledgement_batches.is_empty() {
+                continue;
+            }
+            let code = match &name {
+                Some(name) => {
+                    coordinator
+                        .acknowledge(
+                            &group_id,
+                            &member_id,
+                            name,
+                            p.partition_index,
+                            &ack_batches(&p.acknowledgement_batches),
+                        )
+                        .await
+                }
+                None => ResponseError::UnknownTopicId.code(),
+            };
+            let entry = responses
+                .entry((topic.topic_id, p.partition_index))
+                .or_insert_with(|| share_partition_data(p.partition_index));
+            entry.acknowledge_error_code = code;
+        }
+    }
+
+    if req.share_session_epoch == SHARE_SESSION_FINAL_EPOCH {
+        coordinator.release_member(&group_id, &member_id).await;
+        return Some(share_fetch_response(responses));
+    }
+
+    let mut targets: Vec<(Uuid, i32, String)> = Vec::new();
+    for (id, partition) in session_partitions {
+        match topic_name(id) {
+            Some(name) => targets.push((id, partition, name)),
+            None => {
+                responses
+                    .entry((id, partition))
+                    .or_insert_with(|| share_partition_data(partition))
+                    .error_code = ResponseError::UnknownTopicId.code();
+            }
+        }
+    }
+
+    let max_records = if req.max_records > 0 {
+        req.max_records as usize
+    } else {
+        SHARE_DEFAULT_MAX_RECORDS as usize
+    };
+    let max_bytes = if req.max_bytes > 0 {
+        req.max_bytes as u64
+    } else {
+        i32::MAX as u64
+    };
+
+    let mut acquired = acquire_all(
+        coordinator,
+        &group_id,
+        &member_id,
+        &targets,
+        max_records,
+        max_bytes,
+    )
+    .await;
+
+    // Long-poll once, like Fetch: when nothing could be acquired, wait for new
+    // data on any of the session's partitions, then try exactly once more.
+    let nothing_acquired = !acquired
+        .iter()
+        .any(|(_, r)| r.as_ref().is_ok_and(|a| !a.acquired.is_empty()));
+    let remaining =
+        Duration::from_millis(req.max_wait_ms.max(0) as u64).saturating_sub(start.elapsed());
+    if nothing_acquired && !remaining.is_zero() {
+        let waits: Vec<_> = acquired
+            .iter()
+            .filter_map(|(_, r)| r.as_ref().ok())
+            .map(|a| {
+                let engine_storage_handler = sdm.engine_storage_handler.clone();
+                let shard_name = a.shard_name.clone();
+                let since_offset = a.high_watermark.max(0) as u64;
+                let wait_ms = remaining.as_millis() as u64;
+                Box::pin(async move {
+                    engine_storage_handler
+                        .wait_for_new_data(&shard_name, since_offset, wait_ms)
+                        .await
+                })
+            })
+            .collect();
+        if !waits.is_empty() {
+            select_all(waits).await;
+            acquired = acquire_all(
+                coordinator,
+                &group_id,
+                &member_id,
+                &targets,
+                max_records,
+                max_bytes,
+            )
+            .await;
+        }
+    }
+
+    let mut compressions: HashMap<Uuid, TopicCompression> = HashMap::new();
+    for (id, _, name) in &targets {
+        if let Entry::Vacant(entry) = compressions.entry(*id) {
+            entry.insert(topic_compression(sdm, cache, name).await);
+        }
+    }
+
+    for ((id, partition), result) in acquired {
+        let entry = responses
+            .entry((id, partition))
+            .or_insert_with(|| share_partition_data(partition));
+        match result {
+            Ok(a) => {
+                let compression = compressions
+                    .get(&id)
+                    .copied()
+                    .unwrap_or(TopicCompression::Producer);
+                entry.records = encode_fetch_records(&a.shard_name, &a.records, compression);
+                entry.acquired_records = acquired_ranges(&a.acquired)
+                    .into_iter()
+                    .map(|(first, last, count)| {
+                        AcquiredRecords::default()
+                            .with_first_offset(first)
+                            .with_last_offset(last)
+                            .with_delivery_count(count)
+                    })
+                    .collect();
+            }
+            Err(e) => entry.error_code = e.code(),
+        }
+    }
+
+    Some(share_fetch_response(responses))
 }
 
-pub fn process_share_acknowledge(_req: &ShareAcknowledgeRequest) -> Option<KafkaPacket> {
-    None
+pub async fn process_share_acknowledge(
+    coordinator: &Arc<ShareGroupCoordinator>,
+    sdm: &Arc<StorageDriverManager>,
+    req: &ShareAcknowledgeRequest,
+) -> Option<KafkaPacket> {
+    let (Some(group_id), Some(member_id)) = (req.group_id.as_ref(), req.member_id.as_ref()) else {
+        return Some(share_acknowledge_error(
+            ResponseError::InvalidRequest,
+            "group id and member id are required",
+        ));
+    };
+    let group_id = group_id.to_string();
+    let member_id = member_id.to_string();
+
+    if let Some(owner) = share_owner_redirect(coordinator, sdm).await {
+        return Some(share_acknowledge_redirect(req, owner));
+    }
+
+    if let Err(e) = coordinator.check_ack_session(&group_id, &member_id, req.share_session_epoch) {
+        return Some(share_acknowledge_error(
+            e,
+            "share session rejected the request",
+        ));
+    }
+
+    let mut responses = Vec::with_capacity(req.topics.len());
+    for topic in &req.topics {
+        let name = topic_name_by_id(sdm, topic.topic_id);
+        let mut partitions = Vec::with_capacity(topic.partitions.len());
+        for p in &topic.partitions {
+            let code = match &name {
+                Some(name) => {
+                    coordinator
+                        .acknowledge(
+                            &group_id,
+                            &member_id,
+                            name,
+                            p.partition_index,
+                            &ack_batches(&p.acknowledgement_batches),
+                        )
+                        .await
+                }
+                None => ResponseError::UnknownTopicId.code(),
+            };
+            partitions.push(
+                AckPartitionData::default()
+                    .with_partition_index(p.partition_index)
+                    .with_error_code(code)
+                    .with_current_leader(
+                        AckLeaderIdAndEpoch::default()
+                            .with_leader_id(broker_id())
+                            .with_leader_epoch(0),
+                    ),
+            );
+        }
+        responses.push(
+            ShareAcknowledgeTopicResponse::default()
+                .with_topic_id(topic.topic_id)
+                .with_partitions(partitions),
+        );
+    }
+
+    if req.share_session_epoch == SHARE_SESSION_FINAL_EPOCH {
+        coordinator.release_member(&group_id, &member_id).await;
+    }
+
+    Some(KafkaPacket::ShareAcknowledgeResponse(
+        ShareAcknowledgeResponse::default()
+            .with_error_code(0)
+            .with_responses(responses),
+    ))
 }
 
-pub fn process_describe_share_group_offsets(
-    _req: &DescribeShareGroupOffsetsRequest,
+pub async fn process_describe_share_group_offsets(
+    coordinator: &ShareGroupCoordinator,
+    sdm: &Arc<StorageDriverManager>,
+    req: &DescribeShareGroupOffsetsRequest,
 ) -> Option<KafkaPacket> {
-    None
+    if !is_coordinator_node(sdm).await {
+        let groups = req
+            .groups
+            .iter()
+            .map(|group| {
+                DescribeShareGroupOffsetsResponseGroup::default()
+                    .with_group_id(group.group_id.clone())
+                    .with_error_code(ResponseError::NotCoordinator.code())
+            })
+            .collect();
+        return Some(KafkaPacket::DescribeShareGroupOffsetsResponse(
+            DescribeShareGroupOffsetsResponse::default().with_groups(groups),
+        ));
+    }
+
+    let tenant = get_tenant();
+    let mut groups = Vec::with_capacity(req.groups.len());
+    for group in &req.groups {
+        let group_id = group.group_id.to_string();
+        // No topic list means every topic the group has state for.
+        let requested: Vec<(String, Option<Vec<i32>>)> = match &group.topics {
+            Some(topics) => topics
+                .iter()
+                .map(|t| (t.topic_name.to_string(), Some(t.partitions.clone())))
+                .collect(),
+            None => match coordinator.topics_of(&group_id).await {
+                Ok(topics) => topics.into_iter().map(|t| (t, None)).collect(),
+                Err(e) => {
+                    groups.push(
+                        DescribeShareGroupOffsetsResponseGroup::default()
+                            .with_group_id(group.group_id.clone())
+                            .with_error_code(e.code()),
+                    );
+                    continue;
+                }
+            },
+        };
+
+        let mut topics = Vec::with_capacity(requested.len());
+        for (topic, partitions) in requested {
+            let partitions = match partitions {
+                Some(partitions) => partitions,
+                None => coordinator
+                    .partitions_of(&group_id, &topic)
+                    .await
+                    .unwrap_or_default(),
+            };
+            let mut described = Vec::with_capacity(partitions.len());
+            for partition in partitions {
+                let described_partition = DescribeShareGroupOffsetsResponsePartition::default()
+                    .with_partition_index(partition)
+                    .with_leader_epoch(0);
+                described.push(
+                    match coordinator.start_offset(&group_id, &topic, partition).await {
+                        Ok(start_offset) => {
+                            described_partition.with_start_offset(start_offset.unwrap_or(-1))
+                        }
+                        Err(e) => described_partition
+                            .with_start_offset(-1)
+                            .with_error_code(e.code()),
+                    },
+                );
+            }
+            topics.push(
+                DescribeShareGroupOffsetsResponseTopic::default()
+                    .with_topic_id(topic_uuid(tenant, &topic))
+                    .with_topic_name(TopicName(StrBytes::from(topic)))
+                    .with_partitions(described),
+            );
+        }
+        groups.push(
+            DescribeShareGroupOffsetsResponseGroup::default()
+                .with_group_id(group.group_id.clone())
+                .with_topics(topics),
+        );
+    }
+
+    Some(KafkaPacket::DescribeShareGroupOffsetsResponse(
+        DescribeShareGroupOffsetsResponse::default().with_groups(groups),
+    ))
 }
 
-pub fn process_alter_share_group_offsets(
-    _req: &AlterShareGroupOffsetsRequest,
+pub async fn process_alter_share_group_offsets(
+    coordinator: &ShareGroupCoordinator,
+    sdm: &Arc<StorageDriverManager>,
+    req: &AlterShareGroupOffsetsRequest,
 ) -> Option<KafkaPacket> {
-    None
+    if !is_coordinator_node(sdm).await {
+        return Some(KafkaPacket::AlterShareGroupOffsetsResponse(
+            AlterShareGroupOffsetsResponse::default()
+                .with_error_code(ResponseError::NotCoordinator.code()),
+        ));
+    }
+
+    let tenant = get_tenant();
+    let group_id = req.group_id.to_string();
+    let mut responses = Vec::with_capacity(req.topics.len());
+    for topic in &req.topics {
+        let name = topic.topic_name.to_string();
+        let mut partitions = Vec::with_capacity(topic.partitions.len());
+        for p in &topic.partitions {
+            let code = match coordinator
+                .alter_start_offset(&group_id, &name, p.partition_index, p.start_offset)
+                .await
+            {
+                Ok(()) => 0,
+                Err(e) => e.code(),
+            };
+            partitions.push(
+                AlterShareGroupOffsetsResponsePartition::default()
+                    .with_partition_index(p.partition_index)
+                    .with_error_code(code),
+            );
+        }
+        responses.push(
+            AlterShareGroupOffsetsResponseTopic::default()
+                .with_topic_id(topic_uuid(tenant, &name))
+                .with_topic_name(topic.topic_name.clone())
+                .with_partitions(partitions),
+        );
+    }
+
+    Some(KafkaPacket::AlterShareGroupOffsetsResponse(
+        AlterShareGroupOffsetsResponse::default()
+            .with_error_code(0)
+            .with_responses(responses),
+    ))
 }
 
-pub fn process_delete_share_group_offsets(
-    _req: &DeleteShareGroupOffsetsRequest,
+pub async fn process_delete_share_group_offsets(
+    coordinator: &ShareGroupCoordinator,
+    sdm: &Arc<StorageDriverManager>,
+    req: &DeleteShareGroupOffsetsRequest,
 ) -> Option<KafkaPacket> {
-    None
+    if !is_coordinator_node(sdm).await {
+        return Some(KafkaPacket::DeleteShareGroupOffsetsResponse(
+            DeleteShareGroupOffsetsResponse::default()
+                .with_error_code(ResponseError::NotCoordinator.code()),
+        ));
+    }
+
+    let tenant = get_tenant();
+    let group_id = req.group_id.to_string();
+    let mut responses = Vec::with_capacity(req.topics.len());
+    for topic in &req.topics {
+        let name = topic.topic_name.to_string();
+        let code = match coordinator.delete_topic_state(&group_id, &name).await {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        };
+        responses.push(
+            DeleteShareGroupOffsetsResponseTopic::default()
+                .with_topic_id(topic_uuid(tenant, &name))
+                .with_topic_name(topic.topic_name.clone())
+                .with_error_code(code),
+        );
+    }
+
+    Some(KafkaPacket::DeleteShareGroupOffsetsResponse(
+        DeleteShareGroupOffsetsResponse::default()
+            .with_error_code(0)
+            .with_responses(responses),
+    ))
+}
+
+type AcquireResult = ((Uuid, i32), Result<ShareAcquired, ResponseError>);
+
+// Partitions are drained in order against one shared record/byte budget.
+async fn acquire_all(
+    coordinator: &Arc<ShareGroupCoordinator>,
+    group_id: &str,
+    member_id: &str,
+    targets: &[(Uuid, i32, String)],
+    max_records: usize,
+    max_bytes: u64,
+) -> Vec<AcquireResult> {
+    let mut records_left = max_records;
+    let mut bytes_left = max_bytes;
+    let mut results = Vec::with_capacity(targets.len());
+    for (id, partition, name) in targets {
+        let result = coordinator
+            .acquire(
+                group_id,
+                member_id,
+                name,
+                *partition,
+                records_left,
+                bytes_left,
+            )
+            .await;
+        if let Ok(a) = &result {
+            records_left = records_left.saturating_sub(a.acquired.len());
+            let bytes: u64 = a.records.iter().map(|r| r.data.len() as u64).sum();
+            bytes_left = bytes_left.saturating_sub(bytes);
+        }
+        results.push(((*id, *partition), result));
+    }
+    results
+}
+
+// Share-partition state lives on the group coordinator only, so two nodes
+// never hand out the same records. Any other node drops what it held and
+// returns the coordinator (when known) for the client to redirect to.
+async fn share_owner_redirect(
+    coordinator: &ShareGroupCoordinator,
+    sdm: &Arc<StorageDriverManager>,
+) -> Option<Option<(i32, String, i32)>> {
+    if is_coordinator_node(sdm).await {
+        return None;
+    }
+    coordinator.drop_local_state();
+    Some(resolve_group_coordinator(sdm).await.ok())
+}
+
+// Every requested partition answers NOT_LEADER_OR_FOLLOWER with the owner
+// as its current leader (KIP-951), which moves the client over to it.
+fn share_fetch_redirect(req: &ShareFetchRequest, owner: Option<(i32, String, i32)>) -> KafkaPacket {
+    let leader_id = owner.as_ref().map_or(-1, |(id, _, _)| *id);
+    let topics = req
+        .topics
+        .iter()
+        .map(|topic| {
+            let partitions = topic
+                .partitions
+                .iter()
+                .map(|p| {
+                    PartitionData::default()
+                        .with_partition_index(p.partition_index)
+                        .with_error_code(ResponseError::NotLeaderOrFollower.code())
+                        .with_current_leader(
+                            LeaderIdAndEpoch::default()
+                                .with_leader_id(leader_id)
+                                .with_leader_epoch(0),
+                        )
+                })
+                .collect();
+            ShareFetchableTopicResponse::default()
+                .with_topic_id(topic.topic_id)
+                .with_partitions(partitions)
+        })
+        .collect();
+    let endpoints = owner
+        .into_iter()
+        .map(|(id, host, port)| {
+            NodeEndpoint::default()
+                .with_node_id(id.into())
+                .with_host(StrBytes::from(host))
+                .with_port(port)
+        })
+        .collect();
+    KafkaPacket::ShareFetchResponse(
+        ShareFetchResponse::default()
+            .with_error_code(0)
+            .with_responses(topics)
+            .with_node_endpoints(endpoints),
+    )
+}
+
+fn share_acknowledge_redirect(
+    req: &ShareAcknowledgeRequest,
+    owner: Option<(i32, String, i32)>,
+) -> KafkaPacket {
+    let leader_id = owner.as_ref().map_or(-1, |(id, _, _)| *id);
+    let topics = req
+        .topics
+        .iter()
+        .map(|topic| {
+            let partitions = topic
+                .partitions
+                .iter()
+                .map(|p| {
+                    AckPartitionData::default()
+                        .with_partition_index(p.partition_index)
+                        .with_error_code(ResponseError::NotLeaderOrFollower.code())
+                        .with_current_leader(
+                            AckLeaderIdAndEpoch::default()
+                                .with_leader_id(leader_id)
+                                .with_leader_epoch(0),
+                        )
+                })
+                .collect();
+            ShareAcknowledgeTopicResponse::default()
+                .with_topic_id(topic.topic_id)
+                .with_partitions(partitions)
+        })
+        .collect();
+    let endpoints = owner
+        .into_iter()
+        .map(|(id, host, port)| {
+            AckNodeEndpoint::default()
+                .with_node_id(id.into())
+                .with_host(StrBytes::from(host))
+                .with_port(port)
+        })
+        .collect();
+    KafkaPacket::ShareAcknowledgeResponse(
+        ShareAcknowledgeResponse::default()
+            .with_error_code(0)
+            .with_responses(topics)
+            .with_node_endpoints(endpoints),
+    )
+}
+
+fn ack_batches(batches: &[AcknowledgementBatch]) -> Vec<(i64, i64, Vec<i8>)> {
+    batches
+        .iter()
+        .map(|b| (b.first_offset, b.last_offset, b.acknowledge_types.clone()))
+        .collect()
+}
+
+fn broker_id() -> i32 {
+    broker_config().broker_id as i32
+}
+
+fn share_partition_data(partition: i32) -> PartitionData {
+    PartitionData::default()
+        .with_partition_index(partition)
+        .with_current_leader(
+            LeaderIdAndEpoch::default()
+                .with_leader_id(broker_id())
+                .with_leader_epoch(0),
+        )
+}
+
+fn share_fetch_response(responses: BTreeMap<(Uuid, i32), PartitionData>) -> KafkaPacket {
+    let mut topics: Vec<ShareFetchableTopicResponse> = Vec::new();
+    for ((id, _), data) in responses {
+        match topics.last_mut() {
+            Some(topic) if topic.topic_id == id => topic.partitions.push(data),
+            _ => topics.push(
+                ShareFetchableTopicResponse::default()
+                    .with_topic_id(id)
+                    .with_partitions(vec![data]),
+            ),
+        }
+    }
+    KafkaPacket::ShareFetchResponse(
+        ShareFetchResponse::default()
+            .with_error_code(0)
+            .with_acquisition_lock_timeout_ms(SHARE_RECORD_LOCK_DURATION_MS as i32)
+            .with_responses(topics),
+    )
+}
+
+fn share_fetch_error(err: ResponseError, message: &str) -> KafkaPacket {
+    KafkaPacket::ShareFetchResponse(
+        ShareFetchResponse::default()
+            .with_error_code(err.code())
+            .with_error_message(Some(StrBytes::from(message.to_string()))),
+    )
+}
+
+fn share_acknowledge_error(err: ResponseError, message: &str) -> KafkaPacket {
+    KafkaPacket::ShareAcknowledgeResponse(
+        ShareAcknowledgeResponse::default()
+            .with_error_code(err.code())
+            .with_error_message(Some(StrBytes::from(message.to_string()))),
+    )
+}
+
+fn heartbeat_error_response(code: i16, message: &str) -> KafkaPacket {
+    KafkaPacket::ShareGroupHeartbeatResponse(
+        ShareGroupHeartbeatResponse::default()
+            .with_error_code(code)
+            .with_error_message(Some(StrBytes::from(message.to_string()))),
+    )
+}
+
+fn to_wire_assignment(assignment: HashMap<Uuid, Vec<i32>>) -> Assignment {
+    let mut topic_partitions: Vec<TopicPartitions> = assignment
+        .into_iter()
+        .map(|(topic_id, partitions)| {
+            TopicPartitions::default()
+                .with_topic_id(topic_id)
+                .with_partitions(partitions)
+        })
+        .collect();
+    topic_partitions.sort_by_key(|t| t.topic_id);
+    Assignment::default().with_topic_partitions(topic_partitions)
+}
+
+fn to_described_assignment(
+    assignment: &HashMap<Uuid, Vec<i32>>,
+    topic_names: &HashMap<Uuid, String>,
+) -> DescribedAssignment {
+    let mut topic_partitions: Vec<DescribedTopicPartitions> = assignment
+        .iter()
+        .map(|(topic_id, partitions)| {
+            DescribedTopicPartitions::default()
+                .with_topic_id(*topic_id)
+                .with_topic_name(TopicName(StrBytes::from(
+                    topic_names.get(topic_id).cloned().unwrap_or_default(),
+                )))
+                .with_partitions(partitions.clone())
+        })
+        .collect();
+    topic_partitions.sort_by_key(|t| t.topic_id);
+    DescribedAssignment::default().with_topic_partitions(topic_partitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kafka_protocol::messages::share_acknowledge_request::{
+        AcknowledgePartition, AcknowledgeTopic,
+    };
+    use kafka_protocol::messages::share_fetch_request::{FetchPartition, FetchTopic};
+
+    #[test]
+    fn share_fetch_on_another_node_is_redirected_to_the_owner() {
+        let topic_id = Uuid::from_u128(7);
+        let req = ShareFetchRequest::default().with_topics(vec![FetchTopic::default()
+            .with_topic_id(topic_id)
+            .with_partitions(vec![
+                FetchPartition::default().with_partition_index(0),
+                FetchPartition::default().with_partition_index(1),
+            ])]);
+        let KafkaPacket::ShareFetchResponse(resp) =
+            share_fetch_redirect(&req, Some((3, "broker-3".to_string(), 9092)))
+        else {
+            panic!("expected a ShareFetch response");
+        };
+        assert_eq!(resp.responses.len(), 1);
+        assert_eq!(resp.responses[0].topic_id, topic_id);
+        for p in &resp.responses[0].partitions {
+            assert_eq!(p.error_code, ResponseError::NotLeaderOrFollower.code());
+            assert_eq!(p.current_leader.leader_id, 3);
+            assert!(p.records.is_none() && p.acquired_records.is_empty());
+        }
+        assert_eq!(resp.node_endpoints.len(), 1);
+        assert_eq!(resp.node_endpoints[0].port, 9092);
+
+        let KafkaPacket::ShareFetchResponse(resp) = share_fetch_redirect(&req, None) else {
+            panic!("expected a ShareFetch response");
+        };
+        assert_eq!(resp.responses[0].partitions[0].current_leader.leader_id, -1);
+        assert!(resp.node_endpoints.is_empty());
+    }
+
+    #[test]
+    fn share_acknowledge_on_another_node_is_redirected_to_the_owner() {
+        let req = ShareAcknowledgeRequest::default().with_topics(vec![AcknowledgeTopic::default()
+            .with_topic_id(Uuid::from_u128(7))
+            .with_partitions(vec![AcknowledgePartition::default().with_partition_index(2)])]);
+        let KafkaPacket::ShareAcknowledgeResponse(resp) =
+            share_acknowledge_redirect(&req, Some((3, "broker-3".to_string(), 9092)))
+        else {
+            panic!("expected a ShareAcknowledge response");
+        };
+        let p = &resp.responses[0].partitions[0];
+        assert_eq!(p.partition_index, 2);
+        assert_eq!(p.error_code, ResponseError::NotLeaderOrFollower.code());
+        assert_eq!(p.current_leader.leader_id, 3);
+        assert_eq!(resp.node_endpoints[0].host.as_str(), "broker-3");
+    }
 }
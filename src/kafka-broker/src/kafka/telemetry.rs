@@ -32,9 +32,9 @@
 use protocol::kafka::packet::KafkaPacket;
 use uuid::Uuid;
 
-// Kafka's `CompressionType` wire value for "no compression" — the only one
-// worth accepting while we never decode a push's payload anyway.
-const COMPRESSION_NONE: i8 = 0;
+// Kafka's `CompressionType` wire values (none, gzip, snappy, lz4, zstd). The
+// payload is never decoded, so every codec is as cheap to accept as none.
+const ACCEPTED_COMPRESSION_TYPES: [i8; 5] = [0, 1, 2, 3, 4];
 
 // Real Kafka clients don't push anything when they see an empty subscribed-metrics
 // set, so this is mostly cosmetic; picked to match Kafka's own default.
@@ -59,7 +59,7 @@ pub fn process_get_telemetry_subscriptions(
             .with_error_code(0)
             .with_client_instance_id(client_instance_id)
             .with_subscription_id(0)
-            .with_accepted_compression_types(vec![COMPRESSION_NONE])
+            .with_accepted_compression_types(ACCEPTED_COMPRESSION_TYPES.to_vec())
             .with_push_interval_ms(DEFAULT_PUSH_INTERVAL_MS)
             .with_telemetry_max_bytes(DEFAULT_TELEMETRY_MAX_BYTES)
             .with_delta_temporality(false)
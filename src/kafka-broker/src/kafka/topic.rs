@@ -15,7 +15,10 @@
 use std::sync::Arc;
 
 use crate::handler::tenant::get_tenant;
+use crate::kafka::config::is_valid_topic_config_value;
+use async_trait::async_trait;
 use broker_core::topic::TopicStorage;
+use common_base::error::common::CommonError;
 use common_config::{broker::broker_config, storage::StorageType};
 use kafka_protocol::error::ResponseError;
 use kafka_protocol::messages::create_partitions_request::CreatePartitionsTopic;
@@ -33,6 +36,7 @@
 };
 use kafka_protocol::protocol::StrBytes;
 use metadata_struct::topic::{Topic, TopicConfig, TopicSource};
+use rule_engine::manager::RuleTopicInit;
 use uuid::Uuid;
 
 use crate::core::constants::{
@@ -132,6 +136,35 @@ pub(crate) async fn auto_create_topic(
     }
 }
 
+/// Creates the topics rules republish produced records to, the way Metadata
+/// auto-creates them: only when `auto_create_topics_enable` is on.
+pub struct KafkaRuleTopicInit<'a> {
+    pub sdm: &'a Arc<StorageDriverManager>,
+}
+
+#[async_trait]
+impl RuleTopicInit for KafkaRuleTopicInit<'_> {
+    async fn init_topic(&self, _tenant: &str, topic: &str) -> Result<(), CommonError> {
+        let cache = &self.sdm.broker_cache;
+        if cache.get_topic_by_name(get_tenant(), topic).is_some() {
+            return Ok(());
+        }
+        if !cache
+            .get_cluster_config()
+            .kafka_runtime
+            .auto_create_topics_enable
+        {
+            return Err(CommonError::CommonError(format!(
+                "topic {topic} does not exist and auto_create_topics_enable is off"
+            )));
+        }
+        auto_create_topic(self.sdm, topic)
+            .await
+            .map(|_| ())
+            .ok_or_else(|| CommonError::CommonError(format!("failed to create topic {topic}")))
+    }
+}
+
 fn topic_error(
     name: kafka_protocol::messages::TopicName,
     err: ResponseError,
@@ -200,6 +233,14 @@ async fn create_one_topic(
         }
     };
 
+    if creatable.configs.iter().any(|c| {
+        c.value
+            .as_ref()
+            .is_some_and(|v| !is_valid_topic_config_value(&c.name, v))
+    }) {
+        return topic_error(creatable.name.clone(), ResponseError::InvalidConfig);
+    }
+
     let mut config = TopicConfig::default();
     apply_supported_configs(&mut config, &creatable.configs);
 
@@ -12,64 +12,476 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+use common_base::tools::now_millis;
 use kafka_protocol::error::ResponseError;
+use kafka_protocol::messages::add_partitions_to_txn_response::{
+    AddPartitionsToTxnPartitionResult, AddPartitionsToTxnResult, AddPartitionsToTxnTopicResult,
+};
+use kafka_protocol::messages::describe_transactions_response::{TopicData, TransactionState};
+use kafka_protocol::messages::list_transactions_response::TransactionState as ListedTransaction;
+use kafka_protocol::messages::txn_offset_commit_response::{
+    TxnOffsetCommitResponsePartition, TxnOffsetCommitResponseTopic,
+};
 use kafka_protocol::messages::{
-    AddOffsetsToTxnRequest, AddPartitionsToTxnRequest, DescribeTransactionsRequest, EndTxnRequest,
-    InitProducerIdRequest, InitProducerIdResponse, ListTransactionsRequest, ProducerId,
-    TxnOffsetCommitRequest,
+    AddOffsetsToTxnRequest, AddOffsetsToTxnResponse, AddPartitionsToTxnRequest,
+    AddPartitionsToTxnResponse, DescribeTransactionsRequest, DescribeTransactionsResponse,
+    EndTxnRequest, EndTxnResponse, InitProducerIdRequest, InitProducerIdResponse,
+    ListTransactionsRequest, ListTransactionsResponse, ProducerId, TopicName,
+    TxnOffsetCommitRequest, TxnOffsetCommitResponse,
 };
+use kafka_protocol::protocol::StrBytes;
+use metadata_struct::kafka::transaction::{KafkaTransaction, KafkaTransactionState};
 use protocol::kafka::packet::KafkaPacket;
 
 use crate::core::cache::KafkaCacheManager;
+use crate::core::constants::{NO_PRODUCER_EPOCH, NO_PRODUCER_ID};
+use crate::core::txn_coordinator::{TransactionCoordinator, TxnPartitionResults};
 
-/// Allocate a producer id for an **idempotent** producer (KIP-98 idempotence
-/// only — transactions are not implemented). A request carrying a
-/// `transactional_id` is rejected, since honoring it would promise
-/// transactional semantics the broker can't provide. A pure idempotent producer
-/// (null transactional id) gets a fresh producer id at epoch 0.
-pub fn process_init_producer_id(
+pub async fn process_init_producer_id(
     cache: &Arc<KafkaCacheManager>,
+    coordinator: &Arc<TransactionCoordinator>,
     req: &InitProducerIdRequest,
 ) -> Option<KafkaPacket> {
-    if req.transactional_id.is_some() {
+    let Some(transactional_id) = &req.transactional_id else {
         return Some(KafkaPacket::InitProducerIdResponse(
             InitProducerIdResponse::default()
-                .with_error_code(ResponseError::TransactionalIdAuthorizationFailed.code())
-                .with_producer_id(ProducerId(-1))
-                .with_producer_epoch(-1),
+                .with_error_code(0)
+                .with_producer_id(ProducerId(cache.next_producer_id()))
+                .with_producer_epoch(0),
+        ));
+    };
+
+    let resp = match coordinator
+        .init_producer_id(
+            transactional_id,
+            req.transaction_timeout_ms,
+            req.producer_id.0,
+            req.producer_epoch,
+        )
+        .await
+    {
+        Ok((producer_id, producer_epoch)) => InitProducerIdResponse::default()
+            .with_error_code(0)
+            .with_producer_id(ProducerId(producer_id))
+            .with_producer_epoch(producer_epoch),
+        Err(err) => InitProducerIdResponse::default()
+            .with_error_code(err.code())
+            .with_producer_id(ProducerId(NO_PRODUCER_ID))
+            .with_producer_epoch(NO_PRODUCER_EPOCH),
+    };
+    Some(KafkaPacket::InitProducerIdResponse(resp))
+}
+
+pub async fn process_add_partitions_to_txn(
+    coordinator: &Arc<TransactionCoordinator>,
+    req: &AddPartitionsToTxnRequest,
+) -> Option<KafkaPacket> {
+    // v4+ batches several transactions per request; v0-3 carries one.
+    if !req.transactions.is_empty() {
+        let mut results = Vec::with_capacity(req.transactions.len());
+        for txn in &req.transactions {
+            let topics = requested_partitions(
+                txn.topics
+                    .iter()
+                    .map(|t| (t.name.to_string(), t.partitions.clone())),
+            );
+            let outcome = if txn.verify_only {
+                verify_partitions(coordinator, &txn.transactional_id, &topics)
+            } else {
+                coordinator
+                    .add_partitions(
+                        &txn.transactional_id,
+                        txn.producer_id.0,
+                        txn.producer_epoch,
+                        &topics,
+                    )
+                    .await
+            };
+            results.push(
+                AddPartitionsToTxnResult::default()
+                    .with_transactional_id(txn.transactional_id.clone())
+                    .with_topic_results(add_partitions_results(&topics, outcome)),
+            );
+        }
+        return Some(KafkaPacket::AddPartitionsToTxnResponse(
+            AddPartitionsToTxnResponse::default()
+                .with_error_code(0)
+                .with_results_by_transaction(results),
         ));
     }
 
-    Some(KafkaPacket::InitProducerIdResponse(
-        InitProducerIdResponse::default()
+    let topics = requested_partitions(
+        req.v3_and_below_topics
+            .iter()
+            .map(|t| (t.name.to_string(), t.partitions.clone())),
+    );
+    let outcome = coordinator
+        .add_partitions(
+            &req.v3_and_below_transactional_id,
+            req.v3_and_below_producer_id.0,
+            req.v3_and_below_producer_epoch,
+            &topics,
+        )
+        .await;
+    Some(KafkaPacket::AddPartitionsToTxnResponse(
+        AddPartitionsToTxnResponse::default()
+            .with_results_by_topic_v3_and_below(add_partitions_results(&topics, outcome)),
+    ))
+}
+
+pub async fn process_add_offsets_to_txn(
+    coordinator: &Arc<TransactionCoordinator>,
+    req: &AddOffsetsToTxnRequest,
+) -> Option<KafkaPacket> {
+    let error_code = match coordinator
+        .add_offsets(
+            &req.transactional_id,
+            req.producer_id.0,
+            req.producer_epoch,
+            &req.group_id,
+        )
+        .await
+    {
+        Ok(()) => 0,
+        Err(err) => err.code(),
+    };
+    Some(KafkaPacket::AddOffsetsToTxnResponse(
+        AddOffsetsToTxnResponse::default().with_error_code(error_code),
+    ))
+}
+
+pub async fn process_end_txn(
+    coordinator: &Arc<TransactionCoordinator>,
+    req: &EndTxnRequest,
+) -> Option<KafkaPacket> {
+    let error_code = match coordinator
+        .end_txn(
+            &req.transactional_id,
+            req.producer_id.0,
+            req.producer_epoch,
+            req.committed,
+        )
+        .await
+    {
+        Ok(()) => 0,
+        Err(err) => err.code(),
+    };
+    Some(KafkaPacket::EndTxnResponse(
+        EndTxnResponse::default()
+            .with_error_code(error_code)
+            .with_producer_id(req.producer_id)
+            .with_producer_epoch(req.producer_epoch),
+    ))
+}
+
+pub async fn process_txn_offset_commit(
+    coordinator: &Arc<TransactionCoordinator>,
+    req: &TxnOffsetCommitRequest,
+) -> Option<KafkaPacket> {
+    let topics: Vec<(String, Vec<(i32, i64)>)> = req
+        .topics
+        .iter()
+        .map(|t| {
+            (
+                t.name.to_string(),
+                t.partitions
+                    .iter()
+                    .map(|p| (p.partition_index, p.committed_offset))
+                    .collect(),
+            )
+        })
+        .collect();
+    let outcome = coordinator
+        .txn_offset_commit(
+            &req.transactional_id,
+            req.producer_id.0,
+            req.producer_epoch,
+            &req.group_id,
+            &topics,
+        )
+        .await;
+    let results = partition_results(
+        &topics
+            .iter()
+            .map(|(name, partitions)| (name.clone(), partitions.iter().map(|p| p.0).collect()))
+            .collect::<Vec<_>>(),
+        outcome,
+    );
+
+    let topics = results
+        .into_iter()
+        .map(|(name, partitions)| {
+            TxnOffsetCommitResponseTopic::default()
+                .with_name(TopicName(StrBytes::from_string(name)))
+                .with_partitions(
+                    partitions
+                        .into_iter()
+                        .map(|(index, code)| {
+                            TxnOffsetCommitResponsePartition::default()
+                                .with_partition_index(index)
+                                .with_error_code(code)
+                        })
+                        .collect(),
+                )
+        })
+        .collect();
+    Some(KafkaPacket::TxnOffsetCommitResponse(
+        TxnOffsetCommitResponse::default().with_topics(topics),
+    ))
+}
+
+pub async fn process_describe_transactions(
+    cache: &Arc<KafkaCacheManager>,
+    coordinator: &Arc<TransactionCoordinator>,
+    req: &DescribeTransactionsRequest,
+) -> Option<KafkaPacket> {
+    coordinator.ensure_loaded().await;
+    let states = req
+        .transactional_ids
+        .iter()
+        .map(|id| match cache.get_transaction(id) {
+            Some(txn) => describe_transaction(&txn),
+            None => TransactionState::default()
+                .with_error_code(ResponseError::TransactionalIdNotFound.code())
+                .with_transactional_id(id.clone())
+                .with_producer_id(ProducerId(NO_PRODUCER_ID))
+                .with_producer_epoch(NO_PRODUCER_EPOCH),
+        })
+        .collect();
+    Some(KafkaPacket::DescribeTransactionsResponse(
+        DescribeTransactionsResponse::default().with_transaction_states(states),
+    ))
+}
+
+pub async fn process_list_transactions(
+    cache: &Arc<KafkaCacheManager>,
+    coordinator: &Arc<TransactionCoordinator>,
+    req: &ListTransactionsRequest,
+) -> Option<KafkaPacket> {
+    coordinator.ensure_loaded().await;
+    let mut states = Vec::with_capacity(req.state_filters.len());
+    let mut unknown_state_filters = Vec::new();
+    for filter in &req.state_filters {
+        match KafkaTransactionState::from_name(filter) {
+            Some(state) => states.push(state),
+            None => unknown_state_filters.push(filter.clone()),
+        }
+    }
+
+    let now = now_millis() as u64;
+    let listed = cache
+        .list_transactions()
+        .into_iter()
+        .filter(|txn| states.is_empty() || states.contains(&txn.state))
+        .filter(|txn| {
+            req.producer_id_filters.is_empty()
+                || req
+                    .producer_id_filters
+                    .contains(&ProducerId(txn.producer_id))
+        })
+        .filter(|txn| {
+            req.duration_filter < 0
+                || now.saturating_sub(txn.start_time_ms) >= req.duration_filter as u64
+        })
+        .map(|txn| {
+            ListedTransaction::default()
+                .with_transactional_id(StrBytes::from_string(txn.transactional_id).into())
+                .with_producer_id(ProducerId(txn.producer_id))
+                .with_transaction_state(StrBytes::from_static_str(txn.state.name()))
+        })
+        .collect();
+
+    Some(KafkaPacket::ListTransactionsResponse(
+        ListTransactionsResponse::default()
             .with_error_code(0)
-            .with_producer_id(ProducerId(cache.next_producer_id()))
-            .with_producer_epoch(0),
+            .with_unknown_state_filters(unknown_state_filters)
+            .with_transaction_states(listed),
     ))
 }
 
-pub fn process_add_partitions_to_txn(_req: &AddPartitionsToTxnRequest) -> Option<KafkaPacket> {
-    None
+// Merge repeated topics so each partition is added once.
+fn requested_partitions(
+    topics: impl Iterator<Item = (String, Vec<i32>)>,
+) -> Vec<(String, Vec<i32>)> {
+    let mut merged: Vec<(String, Vec<i32>)> = Vec::new();
+    for (name, partitions) in topics {
+        let entry = match merged.iter().position(|(n, _)| *n == name) {
+            Some(i) => &mut merged[i].1,
+            None => {
+                merged.push((name, Vec::new()));
+                &mut merged.last_mut().expect("just pushed").1
+            }
+        };
+        for p in partitions {
+            if !entry.contains(&p) {
+                entry.push(p);
+            }
+        }
+    }
+    merged
 }
 
-pub fn process_add_offsets_to_txn(_req: &AddOffsetsToTxnRequest) -> Option<KafkaPacket> {
-    None
+// Broker-side verification (v4+ `verify_only`): report whether each partition
+// is already part of the ongoing transaction, without changing it.
+fn verify_partitions(
+    coordinator: &Arc<TransactionCoordinator>,
+    transactional_id: &str,
+    topics: &[(String, Vec<i32>)],
+) -> Result<TxnPartitionResults, ResponseError> {
+    let txn = coordinator
+        .transaction(transactional_id)
+        .ok_or(ResponseError::InvalidProducerIdMapping)?;
+    Ok(topics
+        .iter()
+        .map(|(name, partitions)| {
+            let codes = partitions
+                .iter()
+                .map(|p| {
+                    let code = if txn.state == KafkaTransactionState::Ongoing
+                        && txn.has_partition(name, *p)
+                    {
+                        0
+                    } else {
+                        ResponseError::InvalidTxnState.code()
+                    };
+                    (*p, code)
+                })
+                .collect();
+            (name.clone(), codes)
+        })
+        .collect())
 }
 
-pub fn process_end_txn(_req: &EndTxnRequest) -> Option<KafkaPacket> {
-    None
+// A request-level error applies to every requested partition.
+fn partition_results(
+    topics: &[(String, Vec<i32>)],
+    outcome: Result<TxnPartitionResults, ResponseError>,
+) -> TxnPartitionResults {
+    match outcome {
+        Ok(results) => results,
+        Err(err) => topics
+            .iter()
+            .map(|(name, partitions)| {
+                (
+                    name.clone(),
+                    partitions.iter().map(|p| (*p, err.code())).collect(),
+                )
+            })
+            .collect(),
+    }
 }
 
-pub fn process_txn_offset_commit(_req: &TxnOffsetCommitRequest) -> Option<KafkaPacket> {
-    None
+fn add_partitions_results(
+    topics: &[(String, Vec<i32>)],
+    outcome: Result<TxnPartitionResults, ResponseError>,
+) -> Vec<AddPartitionsToTxnTopicResult> {
+    partition_results(topics, outcome)
+        .into_iter()
+        .map(|(name, partitions)| {
+            AddPartitionsToTxnTopicResult::default()
+                .with_name(TopicName(StrBytes::from_string(name)))
+                .with_results_by_partition(
+                    partitions
+                        .into_iter()
+                        .map(|(index, code)| {
+                            AddPartitionsToTxnPartitionResult::default()
+                                .with_partition_index(index)
+                                .with_partition_error_code(code)
+                        })
+                        .collect(),
+                )
+        })
+        .collect()
 }
 
-pub fn process_describe_transactions(_req: &DescribeTransactionsRequest) -> Option<KafkaPacket> {
-    None
+fn describe_transaction(txn: &KafkaTransaction) -> TransactionState {
+    let mut topics: BTreeMap<&str, Vec<i32>> = BTreeMap::new();
+    for p in &txn.partitions {
+        topics.entry(&p.topic).or_default().push(p.partition);
+    }
+    TransactionState::default()
+        .with_error_code(0)
+        .with_transactional_id(StrBytes::from_string(txn.transactional_id.clone()).into())
+        .with_transaction_state(StrBytes::from_static_str(txn.state.name()))
+        .with_transaction_timeout_ms(txn.timeout_ms)
+        .with_transaction_start_time_ms(txn.start_time_ms as i64)
+        .with_producer_id(ProducerId(txn.producer_id))
+        .with_producer_epoch(txn.producer_epoch)
+        .with_topics(
+            topics
+                .into_iter()
+                .map(|(topic, partitions)| {
+                    TopicData::default()
+                        .with_topic(TopicName(StrBytes::from_string(topic.to_string())))
+                        .with_partitions(partitions)
+                })
+                .collect(),
+        )
 }
 
-pub fn process_list_transactions(_req: &ListTransactionsRequest) -> Option<KafkaPacket> {
-    None
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::kafka::transaction::KafkaTxnPartition;
+
+    #[test]
+    fn requested_partitions_merges_repeated_topics() {
+        let merged = requested_partitions(
+            vec![
+                ("a".to_string(), vec![0, 1]),
+                ("b".to_string(), vec![0]),
+                ("a".to_string(), vec![1, 2]),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(
+            merged,
+            vec![("a".to_string(), vec![0, 1, 2]), ("b".to_string(), vec![0])]
+        );
+    }
+
+    #[test]
+    fn request_error_is_reported_on_every_partition() {
+        let topics = vec![("a".to_string(), vec![0, 1])];
+        let results = partition_results(&topics, Err(ResponseError::InvalidProducerEpoch));
+        let code = ResponseError::InvalidProducerEpoch.code();
+        assert_eq!(results, vec![("a".to_string(), vec![(0, code), (1, code)])]);
+    }
+
+    #[test]
+    fn describe_groups_partitions_by_topic() {
+        let txn = KafkaTransaction {
+            transactional_id: "tx".to_string(),
+            producer_id: 7,
+            producer_epoch: 1,
+            state: KafkaTransactionState::Ongoing,
+            partitions: vec![
+                KafkaTxnPartition {
+                    topic: "b".to_string(),
+                    partition: 0,
+                    ..Default::default()
+                },
+                KafkaTxnPartition {
+                    topic: "a".to_string(),
+                    partition: 1,
+                    ..Default::default()
+                },
+                KafkaTxnPartition {
+                    topic: "a".to_string(),
+                    partition: 0,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let state = describe_transaction(&txn);
+        assert_eq!(state.transaction_state.as_str(), "Ongoing");
+        assert_eq!(state.producer_id, ProducerId(7));
+        assert_eq!(state.topics.len(), 2);
+        assert_eq!(state.topics[0].topic.as_str(), "a");
+        assert_eq!(state.topics[0].partitions, vec![1, 0]);
+    }
 }
@@ -162,6 +162,18 @@ pub enum MetaServiceError {
     #[error("Schema [{0}] already exist")]
     SchemaAlreadyExist(String),
 
+    #[error("Schema subject [{0}] version {1} does not exist")]
+    SchemaVersionDoesNotExist(String, u32),
+
+    #[error("Schema is incompatible with subject [{0}]: {1}")]
+    SchemaIncompatible(String, String),
+
+    #[error("Rule [{0}] does not exist")]
+    RuleDoesNotExist(String),
+
+    #[error("Rule [{0}] already exist")]
+    RuleAlreadyExist(String),
+
     #[error("{0} has raft stopped")]
     RaftNodeHasStopped(String),
 
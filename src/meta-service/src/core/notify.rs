@@ -35,9 +35,12 @@
 use metadata_struct::mqtt::subscribe::MqttSubscribe;
 use metadata_struct::mqtt::topic::Topic;
 use metadata_struct::mqtt::topic_rewrite_rule::MqttTopicRewriteRule;
+use metadata_struct::nats::consumer::NatsConsumer;
+use metadata_struct::nats::stream::NatsStream;
 use metadata_struct::nats::subscribe::NatsSubscribe;
 use metadata_struct::resource_config::ResourceConfig;
-use metadata_struct::schema::{SchemaData, SchemaResourceBind};
+use metadata_struct::rule::Rule;
+use metadata_struct::schema::{SchemaData, SchemaResourceBind, SchemaVersion};
 use metadata_struct::storage::{
     segment::EngineSegment, segment_meta::EngineSegmentMetadata, shard::EngineShard,
 };
@@ -166,6 +169,59 @@ pub async fn send_notify_by_delete_schema_bind(
     .await
 }
 
+pub async fn send_notify_by_add_schema_version(
+    call_manager: &Arc<NodeCallManager>,
+    schema_version: SchemaVersion,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Create,
+        BrokerUpdateCacheResourceType::SchemaVersion,
+        serialize::serialize(&schema_version)?,
+    )
+    .await
+}
+
+pub async fn send_notify_by_delete_schema_version(
+    call_manager: &Arc<NodeCallManager>,
+    schema_version: SchemaVersion,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Delete,
+        BrokerUpdateCacheResourceType::SchemaVersion,
+        serialize::serialize(&schema_version)?,
+    )
+    .await
+}
+
+// Rule
+pub async fn send_notify_by_add_rule(
+    call_manager: &Arc<NodeCallManager>,
+    rule: Rule,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Create,
+        BrokerUpdateCacheResourceType::Rule,
+        serialize::serialize(&rule)?,
+    )
+    .await
+}
+
+pub async fn send_notify_by_delete_rule(
+    call_manager: &Arc<NodeCallManager>,
+    rule: Rule,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Delete,
+        BrokerUpdateCacheResourceType::Rule,
+        serialize::serialize(&rule)?,
+    )
+    .await
+}
+
 // MQTT Connector
 pub async fn send_notify_by_add_connector(
     call_manager: &Arc<NodeCallManager>,
@@ -709,6 +765,60 @@ pub async fn send_notify_by_delete_nats_subscribe(
     .await
 }
 
+// NATS JetStream stream
+pub async fn send_notify_by_set_nats_stream(
+    call_manager: &Arc<NodeCallManager>,
+    stream: NatsStream,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Create,
+        BrokerUpdateCacheResourceType::NatsStream,
+        serialize::serialize(&stream)?,
+    )
+    .await
+}
+
+pub async fn send_notify_by_delete_nats_stream(
+    call_manager: &Arc<NodeCallManager>,
+    stream: NatsStream,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Delete,
+        BrokerUpdateCacheResourceType::NatsStream,
+        serialize::serialize(&stream)?,
+    )
+    .await
+}
+
+// NATS JetStream consumer
+pub async fn send_notify_by_set_nats_consumer(
+    call_manager: &Arc<NodeCallManager>,
+    consumer: NatsConsumer,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Create,
+        BrokerUpdateCacheResourceType::NatsConsumer,
+        serialize::serialize(&consumer)?,
+    )
+    .await
+}
+
+pub async fn send_notify_by_delete_nats_consumer(
+    call_manager: &Arc<NodeCallManager>,
+    consumer: NatsConsumer,
+) -> Result<(), MetaServiceError> {
+    send_update_cache(
+        call_manager,
+        BrokerUpdateCacheActionType::Delete,
+        BrokerUpdateCacheResourceType::NatsConsumer,
+        serialize::serialize(&consumer)?,
+    )
+    .await
+}
+
 // MQ9 Mail
 pub async fn send_notify_by_create_mq9_mail(
     call_manager: &Arc<NodeCallManager>,
@@ -13,18 +13,28 @@
 // limitations under the License.
 
 use bytes::Bytes;
+use common_base::error::common::CommonError;
 use common_base::tools::now_second;
+use common_base::utils::serialize;
 use metadata_struct::meta::node::BrokerNode;
-use metadata_struct::schema::{SchemaData, SchemaResourceBind};
+use metadata_struct::rule::Rule;
+use metadata_struct::schema::{
+    schema_compatibility_resource, SchemaCompatibility, SchemaData, SchemaResourceBind, SchemaType,
+    SchemaVersion,
+};
 use metadata_struct::tenant::{Tenant, TenantConfig};
 use prost::Message as _;
 use protocol::meta::meta_service_common::{
-    BindSchemaRequest, CreateSchemaRequest, CreateTenantRequest, DeleteOffsetDataRequest,
-    DeleteResourceConfigRequest, DeleteSchemaRequest, DeleteShareGroupRequest, DeleteTenantRequest,
-    RegisterNodeRequest, SaveOffsetDataRequest, SetResourceConfigRequest, UnBindSchemaRequest,
-    UnRegisterNodeRequest, UpdateTenantRequest,
+    BindSchemaRequest, CreateRuleRequest, CreateSchemaRequest, CreateTenantRequest,
+    DeleteOffsetDataRequest, DeleteResourceConfigRequest, DeleteRuleRequest, DeleteSchemaRequest,
+    DeleteSchemaVersionRequest, DeleteShareGroupRequest, DeleteTenantRequest, RegisterNodeRequest,
+    RegisterSchemaVersionRequest, SaveOffsetDataRequest, SetResourceConfigRequest,
+    UnBindSchemaRequest, UnRegisterNodeRequest, UpdateTenantRequest,
 };
 use rocksdb_engine::rocksdb::RocksDBEngine;
+use schema_register::compatibility::{check_compatibility, is_same_schema};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::core::cache::MetaCacheManager;
@@ -32,9 +42,29 @@
 use crate::storage::common::config::ResourceConfigStorage;
 use crate::storage::common::node::NodeStorage;
 use crate::storage::common::offset::{OffsetData, OffsetStorage};
+use crate::storage::common::rule::RuleStorage;
 use crate::storage::common::schema::SchemaStorage;
 use crate::storage::common::tenant::TenantStorage;
 
+/// The reply to a `SchemaVersionSet` entry. An incompatible schema is a
+/// normal outcome of the apply rather than an error, which would stop the
+/// state machine.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum RegisterSchemaVersionResult {
+    Registered(SchemaVersion),
+    Incompatible(String),
+}
+
+impl RegisterSchemaVersionResult {
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        serialize::serialize(self)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        serialize::deserialize(data)
+    }
+}
+
 #[derive(Clone)]
 pub struct DataRouteCluster {
     rocksdb_engine_handler: Arc<RocksDBEngine>,
@@ -154,6 +184,9 @@ pub fn set_schema_bind(&self, value: Bytes) -> Result<(), MetaServiceError> {
             tenant: req.tenant.clone(),
             resource_name: req.resource_name.clone(),
             schema_name: req.schema_name.clone(),
+            message_name: req.message_name.clone(),
+            strict_unknown_fields: req.strict_unknown_fields,
+            strict_required_fields: req.strict_required_fields,
         };
         schema_storage.save_bind(&bind_data)?;
         Ok(())
@@ -166,6 +199,141 @@ pub fn delete_schema_bind(&self, value: Bytes) -> Result<(), MetaServiceError> {
         Ok(())
     }
 
+    // Schema Version
+    //
+    // Version numbers and schema ids are handed out here, in the raft apply
+    // step, so every replica hands out the same ones. A schema the tenant
+    // already registered under any subject keeps its id. The compatibility
+    // check runs here too, against the versions as of this entry, so two
+    // registrations racing on one subject can't both pass it.
+    pub fn set_schema_version(
+        &self,
+        value: Bytes,
+    ) -> Result<RegisterSchemaVersionResult, MetaServiceError> {
+        let req = RegisterSchemaVersionRequest::decode(value.as_ref())?;
+        let schema_type = SchemaType::from_str(&req.schema_type)?;
+        let schema_storage = SchemaStorage::new(self.rocksdb_engine_handler.clone());
+
+        let registered = schema_storage.list_versions_by_tenant(&req.tenant)?;
+        let same = |v: &&SchemaVersion| {
+            v.schema_type == schema_type && is_same_schema(&schema_type, &v.schema, &req.schema)
+        };
+        if let Some(existing) = registered
+            .iter()
+            .filter(|v| v.subject == req.subject)
+            .find(same)
+        {
+            return Ok(RegisterSchemaVersionResult::Registered(existing.clone()));
+        }
+
+        let previous = schema_storage.list_versions_by_subject(&req.tenant, &req.subject)?;
+        let errors = self
+            .schema_compatibility(&req.tenant, &req.subject)
+            .and_then(|level| {
+                check_compatibility(&schema_type, &req.schema, &previous, level)
+                    .map_err(MetaServiceError::from)
+            })
+            .unwrap_or_else(|e| vec![e.to_string()]);
+        if !errors.is_empty() {
+            return Ok(RegisterSchemaVersionResult::Incompatible(errors.join("; ")));
+        }
+
+        let id = match registered.iter().find(same) {
+            Some(existing) => existing.id,
+            None => schema_storage.next_id()?,
+        };
+
+        let version = SchemaVersion {
+            tenant: req.tenant.clone(),
+            subject: req.subject.clone(),
+            version: schema_storage.next_version(&req.tenant, &req.subject)?,
+            id,
+            schema_type: schema_type.clone(),
+            schema: req.schema.clone(),
+            create_time: now_second(),
+        };
+        schema_storage.save_version(&version)?;
+
+        let desc = schema_storage
+            .get(&req.tenant, &req.subject)?
+            .map(|s| s.desc)
+            .unwrap_or_default();
+        let latest = SchemaData {
+            tenant: req.tenant.clone(),
+            name: req.subject.clone(),
+            schema_type,
+            desc,
+            schema: req.schema,
+        };
+        schema_storage.save(&req.tenant, &req.subject, &latest)?;
+        Ok(RegisterSchemaVersionResult::Registered(version))
+    }
+
+    /// The subject's compatibility level, else the tenant's, else BACKWARD.
+    fn schema_compatibility(
+        &self,
+        tenant: &str,
+        subject: &str,
+    ) -> Result<SchemaCompatibility, MetaServiceError> {
+        let config_storage = ResourceConfigStorage::new(self.rocksdb_engine_handler.clone());
+        for resource in [
+            schema_compatibility_resource(tenant, Some(subject)),
+            schema_compatibility_resource(tenant, None),
+        ] {
+            if let Some(level) = config_storage.get(resource)? {
+                return Ok(SchemaCompatibility::from_str(&String::from_utf8(level)?)?);
+            }
+        }
+        Ok(SchemaCompatibility::default())
+    }
+
+    pub fn delete_schema_version(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = DeleteSchemaVersionRequest::decode(value.as_ref())?;
+        let schema_storage = SchemaStorage::new(self.rocksdb_engine_handler.clone());
+
+        for version in schema_storage.list_versions_by_subject(&req.tenant, &req.subject)? {
+            if req.version == 0 || req.version == version.version {
+                schema_storage.delete_version(&req.tenant, &req.subject, version.version)?;
+            }
+        }
+
+        // The subject's schema follows its latest remaining version.
+        match schema_storage
+            .list_versions_by_subject(&req.tenant, &req.subject)?
+            .pop()
+        {
+            Some(latest) => {
+                if let Some(mut schema) = schema_storage.get(&req.tenant, &req.subject)? {
+                    schema.schema_type = latest.schema_type;
+                    schema.schema = latest.schema;
+                    schema_storage.save(&req.tenant, &req.subject, &schema)?;
+                }
+            }
+            None => {
+                schema_storage.delete(&req.tenant, &req.subject)?;
+                if req.version == 0 {
+                    schema_storage.delete_version_seq(&req.tenant, &req.subject)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Rule
+    pub fn set_rule(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = CreateRuleRequest::decode(value.as_ref())?;
+        let rule_storage = RuleStorage::new(self.rocksdb_engine_handler.clone());
+        rule_storage.save(&Rule::decode(&req.rule)?)?;
+        Ok(())
+    }
+
+    pub fn delete_rule(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = DeleteRuleRequest::decode(value.as_ref())?;
+        let rule_storage = RuleStorage::new(self.rocksdb_engine_handler.clone());
+        rule_storage.delete(&req.tenant, &req.rule_name)?;
+        Ok(())
+    }
+
     pub fn delete_offset_data(&self, value: Bytes) -> Result<(), MetaServiceError> {
         let req = DeleteShareGroupRequest::decode(value.as_ref())?;
         let offset_storage = OffsetStorage::new(self.rocksdb_engine_handler.clone());
@@ -240,7 +408,7 @@ mod tests {
     use std::sync::Arc;
 
     use crate::core::cache::MetaCacheManager;
-    use crate::raft::route::common::DataRouteCluster;
+    use crate::raft::route::common::{DataRouteCluster, RegisterSchemaVersionResult};
     use crate::storage::common::node::NodeStorage;
     use prost::Message;
     use protocol::meta::meta_service_common::RegisterNodeRequest;
@@ -364,4 +532,53 @@ async fn save_offset_data_cas_rejects_stale_expected_offset() {
         let stored = offset_storage.get("t1", "amqp:q1", "shard1").unwrap();
         assert_eq!(stored.unwrap().offset, 1);
     }
+
+    fn register_schema_version_request(schema: &str) -> Bytes {
+        use protocol::meta::meta_service_common::RegisterSchemaVersionRequest;
+        let req = RegisterSchemaVersionRequest {
+            tenant: "t1".to_string(),
+            subject: "orders".to_string(),
+            schema_type: "avro".to_string(),
+            schema: schema.to_string(),
+        };
+        Bytes::copy_from_slice(&RegisterSchemaVersionRequest::encode_to_vec(&req))
+    }
+
+    #[tokio::test]
+    async fn set_schema_version_checks_compatibility_in_apply() {
+        let (route, rocksdb_engine) = new_route();
+        let v1 = r#"{"type":"record","name":"Order","fields":[{"name":"id","type":"long"}]}"#;
+        // Both were checked against v1 alone before the apply; in the log
+        // order only the first of them is compatible.
+        let v2 = r#"{"type":"record","name":"Order","fields":[
+            {"name":"id","type":"long"},
+            {"name":"note","type":"string","default":""}]}"#;
+        let v3 = r#"{"type":"record","name":"Order","fields":[
+            {"name":"id","type":"long"},
+            {"name":"note","type":"int","default":0}]}"#;
+
+        for (schema, expected) in [(v1, 1), (v2, 2)] {
+            match route
+                .set_schema_version(register_schema_version_request(schema))
+                .unwrap()
+            {
+                RegisterSchemaVersionResult::Registered(version) => {
+                    assert_eq!(version.version, expected)
+                }
+                other => panic!("unexpected result {other:?}"),
+            }
+        }
+        assert!(matches!(
+            route
+                .set_schema_version(register_schema_version_request(v3))
+                .unwrap(),
+            RegisterSchemaVersionResult::Incompatible(_)
+        ));
+
+        let schema_storage = crate::storage::common::schema::SchemaStorage::new(rocksdb_engine);
+        let versions = schema_storage
+            .list_versions_by_subject("t1", "orders")
+            .unwrap();
+        assert_eq!(versions.len(), 2);
+    }
 }
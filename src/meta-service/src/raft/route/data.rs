@@ -55,6 +55,10 @@ pub enum StorageDataType {
     SchemaDelete,
     SchemaBindSet,
     SchemaBindDelete,
+    SchemaVersionSet,
+    SchemaVersionDelete,
+    RuleSet,
+    RuleDelete,
     ResourceConfigSet,
     ResourceConfigDelete,
     OffsetSet,
@@ -68,6 +72,8 @@ pub enum StorageDataType {
     KafkaDeleteDelegationToken,
     KafkaSetScram,
     KafkaDeleteScram,
+    KafkaSetSharePartitionState,
+    KafkaDeleteSharePartitionState,
 
     // AMQP
     AmqpSetExchange,
@@ -119,6 +125,12 @@ pub enum StorageDataType {
     Mq9DeleteMail,
     Mq9CreateAgent,
     Mq9DeleteAgent,
+
+    // nats jetstream
+    NatsSetStream,
+    NatsDeleteStream,
+    NatsSetConsumer,
+    NatsDeleteConsumer,
 }
 
 impl fmt::Display for StorageDataType {
@@ -138,6 +150,10 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             StorageDataType::SchemaDelete => write!(f, "SchemaDelete"),
             StorageDataType::SchemaBindSet => write!(f, "SchemaBindSet"),
             StorageDataType::SchemaBindDelete => write!(f, "SchemaBindDelete"),
+            StorageDataType::SchemaVersionSet => write!(f, "SchemaVersionSet"),
+            StorageDataType::SchemaVersionDelete => write!(f, "SchemaVersionDelete"),
+            StorageDataType::RuleSet => write!(f, "RuleSet"),
+            StorageDataType::RuleDelete => write!(f, "RuleDelete"),
             StorageDataType::ResourceConfigSet => write!(f, "ResourceConfigSet"),
             StorageDataType::ResourceConfigDelete => write!(f, "ResourceConfigDelete"),
             StorageDataType::OffsetSet => write!(f, "OffsetSet"),
@@ -148,6 +164,12 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             StorageDataType::KafkaSetDelegationToken => write!(f, "KafkaSetDelegationToken"),
             StorageDataType::KafkaSetScram => write!(f, "KafkaSetScram"),
             StorageDataType::KafkaDeleteScram => write!(f, "KafkaDeleteScram"),
+            StorageDataType::KafkaSetSharePartitionState => {
+                write!(f, "KafkaSetSharePartitionState")
+            }
+            StorageDataType::KafkaDeleteSharePartitionState => {
+                write!(f, "KafkaDeleteSharePartitionState")
+            }
             StorageDataType::KafkaDeleteDelegationToken => {
                 write!(f, "KafkaDeleteDelegationToken")
             }
@@ -215,6 +237,11 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             StorageDataType::Mq9DeleteMail => write!(f, "Mq9DeleteMail"),
             StorageDataType::Mq9CreateAgent => write!(f, "Mq9CreateAgent"),
             StorageDataType::Mq9DeleteAgent => write!(f, "Mq9DeleteAgent"),
+
+            StorageDataType::NatsSetStream => write!(f, "NatsSetStream"),
+            StorageDataType::NatsDeleteStream => write!(f, "NatsDeleteStream"),
+            StorageDataType::NatsSetConsumer => write!(f, "NatsSetConsumer"),
+            StorageDataType::NatsDeleteConsumer => write!(f, "NatsDeleteConsumer"),
         }
     }
 }
@@ -18,10 +18,13 @@
 use metadata_struct::kafka::delegation_token::KafkaDelegationToken;
 use metadata_struct::kafka::quota::KafkaClientQuota;
 use metadata_struct::kafka::scram::KafkaScramCredential;
+use metadata_struct::kafka::share_group::KafkaSharePartitionState;
 use prost::Message;
 use protocol::meta::meta_service_kafka::{
-    DeleteKafkaDelegationTokenRequest, DeleteKafkaQuotaRequest, DeleteScramCredentialRequest,
-    SetKafkaDelegationTokenRequest, SetKafkaQuotaRequest, SetScramCredentialRequest,
+    DeleteKafkaDelegationTokenRequest, DeleteKafkaQuotaRequest,
+    DeleteKafkaSharePartitionStateRequest, DeleteScramCredentialRequest,
+    SetKafkaDelegationTokenRequest, SetKafkaQuotaRequest, SetKafkaSharePartitionStateRequest,
+    SetScramCredentialRequest,
 };
 use rocksdb_engine::rocksdb::RocksDBEngine;
 
@@ -29,6 +32,7 @@
 use crate::storage::kafka::delegation_token::KafkaDelegationTokenStorage;
 use crate::storage::kafka::quota::KafkaQuotaStorage;
 use crate::storage::kafka::scram::KafkaScramStorage;
+use crate::storage::kafka::share_group::KafkaShareGroupStorage;
 
 #[derive(Clone)]
 pub struct DataRouteKafka {
@@ -86,4 +90,19 @@ pub fn delete_scram_credential(&self, value: Bytes) -> Result<(), MetaServiceErr
         storage.delete(&req.tenant, &req.user, req.mechanism as i8)?;
         Ok(())
     }
+
+    pub fn set_share_partition_state(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = SetKafkaSharePartitionStateRequest::decode(value.as_ref())?;
+        let state = KafkaSharePartitionState::decode(&req.state)?;
+        let storage = KafkaShareGroupStorage::new(self.rocksdb_engine_handler.clone());
+        storage.save(state)?;
+        Ok(())
+    }
+
+    pub fn delete_share_partition_state(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = DeleteKafkaSharePartitionStateRequest::decode(value.as_ref())?;
+        let storage = KafkaShareGroupStorage::new(self.rocksdb_engine_handler.clone());
+        storage.delete(&req.tenant, &req.group_id, &req.topic, req.partition)?;
+        Ok(())
+    }
 }
@@ -164,6 +164,16 @@ pub async fn route(
                     .delete_scram_credential(storage_data.value.clone())?;
                 Ok(None)
             }
+            StorageDataType::KafkaSetSharePartitionState => {
+                self.route_kafka
+                    .set_share_partition_state(storage_data.value.clone())?;
+                Ok(None)
+            }
+            StorageDataType::KafkaDeleteSharePartitionState => {
+                self.route_kafka
+                    .delete_share_partition_state(storage_data.value.clone())?;
+                Ok(None)
+            }
             StorageDataType::KafkaSetDelegationToken => {
                 self.route_kafka
                     .set_delegation_token(storage_data.value.clone())?;
@@ -233,6 +243,24 @@ pub async fn route(
                     .delete_schema_bind(storage_data.value.clone())?;
                 Ok(None)
             }
+            StorageDataType::SchemaVersionSet => Ok(Some(Bytes::from(
+                self.route_cluster
+                    .set_schema_version(storage_data.value.clone())?
+                    .encode()?,
+            ))),
+            StorageDataType::SchemaVersionDelete => {
+                self.route_cluster
+                    .delete_schema_version(storage_data.value.clone())?;
+                Ok(None)
+            }
+            StorageDataType::RuleSet => {
+                self.route_cluster.set_rule(storage_data.value.clone())?;
+                Ok(None)
+            }
+            StorageDataType::RuleDelete => {
+                self.route_cluster.delete_rule(storage_data.value.clone())?;
+                Ok(None)
+            }
 
             // Storage Engine
             StorageDataType::StorageEngineSetShard => Ok(Some(
@@ -411,6 +439,25 @@ pub async fn route(
                 self.route_mq9.delete_agent(storage_data.value.clone())?;
                 Ok(None)
             }
+
+            // nats jetstream
+            StorageDataType::NatsSetStream => {
+                self.route_nats.set_stream(storage_data.value.clone())?;
+                Ok(None)
+            }
+            StorageDataType::NatsDeleteStream => {
+                self.route_nats.delete_stream(storage_data.value.clone())?;
+                Ok(None)
+            }
+            StorageDataType::NatsSetConsumer => {
+                self.route_nats.set_consumer(storage_data.value.clone())?;
+                Ok(None)
+            }
+            StorageDataType::NatsDeleteConsumer => {
+                self.route_nats
+                    .delete_consumer(storage_data.value.clone())?;
+                Ok(None)
+            }
         }
     }
 }
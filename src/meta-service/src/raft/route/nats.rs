@@ -13,11 +13,16 @@
 // limitations under the License.
 
 use crate::core::error::MetaServiceError;
-use crate::storage::nats::NatsSubscribeStorage;
+use crate::storage::nats::{NatsConsumerStorage, NatsStreamStorage, NatsSubscribeStorage};
 use bytes::Bytes;
+use metadata_struct::nats::consumer::NatsConsumer;
+use metadata_struct::nats::stream::NatsStream;
 use metadata_struct::nats::subscribe::NatsSubscribe;
 use prost::Message as _;
-use protocol::meta::meta_service_nats::{CreateNatsSubscribeRequest, DeleteNatsSubscribeRequest};
+use protocol::meta::meta_service_nats::{
+    CreateNatsSubscribeRequest, DeleteNatsConsumerRequest, DeleteNatsStreamRequest,
+    DeleteNatsSubscribeRequest, SetNatsConsumerRequest, SetNatsStreamRequest,
+};
 use rocksdb_engine::rocksdb::RocksDBEngine;
 use std::sync::Arc;
 
@@ -51,4 +56,34 @@ pub fn delete_subscribe(&self, value: Bytes) -> Result<(), MetaServiceError> {
         }
         Ok(())
     }
+
+    pub fn set_stream(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = SetNatsStreamRequest::decode(value.as_ref())?;
+        let stream = NatsStream::decode(&req.content)?;
+        let storage = NatsStreamStorage::new(self.rocksdb_engine_handler.clone());
+        storage.save(&stream)?;
+        Ok(())
+    }
+
+    pub fn delete_stream(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = DeleteNatsStreamRequest::decode(value.as_ref())?;
+        let storage = NatsStreamStorage::new(self.rocksdb_engine_handler.clone());
+        storage.delete(&req.tenant, &req.stream_name)?;
+        Ok(())
+    }
+
+    pub fn set_consumer(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = SetNatsConsumerRequest::decode(value.as_ref())?;
+        let consumer = NatsConsumer::decode(&req.content)?;
+        let storage = NatsConsumerStorage::new(self.rocksdb_engine_handler.clone());
+        storage.save(&consumer)?;
+        Ok(())
+    }
+
+    pub fn delete_consumer(&self, value: Bytes) -> Result<(), MetaServiceError> {
+        let req = DeleteNatsConsumerRequest::decode(value.as_ref())?;
+        let storage = NatsConsumerStorage::new(self.rocksdb_engine_handler.clone());
+        storage.delete(&req.tenant, &req.stream_name, &req.consumer_name)?;
+        Ok(())
+    }
 }
@@ -27,8 +27,12 @@
 use crate::server::services::common::kv::{
     delete_by_req, exists_by_req, get_by_req, get_prefix_by_req, set_by_req,
 };
+use crate::server::services::common::rule::{
+    create_rule_req, delete_rule_req, list_rule_req, update_rule_req,
+};
 use crate::server::services::common::schema::{
-    bind_schema_req, create_schema_req, delete_schema_req, list_bind_schema_req, list_schema_req,
+    bind_schema_req, create_schema_req, delete_schema_req, delete_schema_version_req,
+    list_bind_schema_req, list_schema_req, list_schema_version_req, register_schema_version_req,
     un_bind_schema_req, update_schema_req,
 };
 use crate::server::services::common::tenant::{
@@ -45,24 +49,27 @@
 use protocol::meta::meta_service_common::meta_service_service_server::MetaServiceService;
 use protocol::meta::meta_service_common::{
     AddShareGroupMemberReply, AddShareGroupMemberRequest, AppendReply, AppendRequest,
-    BindSchemaReply, BindSchemaRequest, ClusterStatusReply, ClusterStatusRequest,
-    CreateSchemaReply, CreateSchemaRequest, CreateShareGroupReply, CreateShareGroupRequest,
-    CreateTenantReply, CreateTenantRequest, DeleteOffsetDataReply, DeleteOffsetDataRequest,
-    DeleteReply, DeleteRequest, DeleteResourceConfigReply, DeleteResourceConfigRequest,
-    DeleteSchemaReply, DeleteSchemaRequest, DeleteShareGroupMemberReply,
-    DeleteShareGroupMemberRequest, DeleteShareGroupReply, DeleteShareGroupRequest,
-    DeleteTenantReply, DeleteTenantRequest, ExistsReply, ExistsRequest, GetOffsetDataReply,
-    GetOffsetDataRequest, GetPrefixReply, GetPrefixRequest, GetReply, GetRequest,
-    GetResourceConfigReply, GetResourceConfigRequest, HeartbeatReply, HeartbeatRequest,
+    BindSchemaReply, BindSchemaRequest, ClusterStatusReply, ClusterStatusRequest, CreateRuleReply,
+    CreateRuleRequest, CreateSchemaReply, CreateSchemaRequest, CreateShareGroupReply,
+    CreateShareGroupRequest, CreateTenantReply, CreateTenantRequest, DeleteOffsetDataReply,
+    DeleteOffsetDataRequest, DeleteReply, DeleteRequest, DeleteResourceConfigReply,
+    DeleteResourceConfigRequest, DeleteRuleReply, DeleteRuleRequest, DeleteSchemaReply,
+    DeleteSchemaRequest, DeleteSchemaVersionReply, DeleteSchemaVersionRequest,
+    DeleteShareGroupMemberReply, DeleteShareGroupMemberRequest, DeleteShareGroupReply,
+    DeleteShareGroupRequest, DeleteTenantReply, DeleteTenantRequest, ExistsReply, ExistsRequest,
+    GetOffsetDataReply, GetOffsetDataRequest, GetPrefixReply, GetPrefixRequest, GetReply,
+    GetRequest, GetResourceConfigReply, GetResourceConfigRequest, HeartbeatReply, HeartbeatRequest,
     JoinClusterReply, JoinClusterRequest, LeaveClusterReply, LeaveClusterRequest,
-    ListBindSchemaReply, ListBindSchemaRequest, ListSchemaReply, ListSchemaRequest,
-    ListShareGroupMemberReply, ListShareGroupMemberRequest, ListShareGroupReply,
-    ListShareGroupRequest, ListTenantReply, ListTenantRequest, NodeListReply, NodeListRequest,
-    RegisterNodeReply, RegisterNodeRequest, ReportMonitorReply, ReportMonitorRequest,
-    SaveOffsetDataReply, SaveOffsetDataRequest, SetReply, SetRequest, SetResourceConfigReply,
-    SetResourceConfigRequest, SnapshotReply, SnapshotRequest, UnBindSchemaReply,
-    UnBindSchemaRequest, UnRegisterNodeReply, UnRegisterNodeRequest, UpdateSchemaReply,
-    UpdateSchemaRequest, UpdateTenantReply, UpdateTenantRequest, VoteReply, VoteRequest,
+    ListBindSchemaReply, ListBindSchemaRequest, ListRuleReply, ListRuleRequest, ListSchemaReply,
+    ListSchemaRequest, ListSchemaVersionReply, ListSchemaVersionRequest, ListShareGroupMemberReply,
+    ListShareGroupMemberRequest, ListShareGroupReply, ListShareGroupRequest, ListTenantReply,
+    ListTenantRequest, NodeListReply, NodeListRequest, RegisterNodeReply, RegisterNodeRequest,
+    RegisterSchemaVersionReply, RegisterSchemaVersionRequest, ReportMonitorReply,
+    ReportMonitorRequest, SaveOffsetDataReply, SaveOffsetDataRequest, SetReply, SetRequest,
+    SetResourceConfigReply, SetResourceConfigRequest, SnapshotReply, SnapshotRequest,
+    UnBindSchemaReply, UnBindSchemaRequest, UnRegisterNodeReply, UnRegisterNodeRequest,
+    UpdateRuleReply, UpdateRuleRequest, UpdateSchemaReply, UpdateSchemaRequest, UpdateTenantReply,
+    UpdateTenantRequest, VoteReply, VoteRequest,
 };
 use rocksdb_engine::rocksdb::RocksDBEngine;
 use std::pin::Pin;
@@ -112,6 +119,9 @@ impl MetaServiceService for GrpcPlacementService {
     type ListSchemaStream = Pin<Box<dyn Stream<Item = Result<ListSchemaReply, Status>> + Send>>;
     type ListBindSchemaStream =
         Pin<Box<dyn Stream<Item = Result<ListBindSchemaReply, Status>> + Send>>;
+    type ListSchemaVersionStream =
+        Pin<Box<dyn Stream<Item = Result<ListSchemaVersionReply, Status>> + Send>>;
+    type ListRuleStream = Pin<Box<dyn Stream<Item = Result<ListRuleReply, Status>> + Send>>;
     type ListTenantStream = Pin<Box<dyn Stream<Item = Result<ListTenantReply, Status>> + Send>>;
 
     // Cluster
@@ -416,6 +426,128 @@ async fn un_bind_schema(
         Ok(Response::new(UnBindSchemaReply {}))
     }
 
+    async fn list_schema_version(
+        &self,
+        request: Request<ListSchemaVersionRequest>,
+    ) -> Result<Response<Self::ListSchemaVersionStream>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+
+        list_schema_version_req(&self.rocksdb_engine_handler, &req)
+            .map_err(Self::to_status)
+            .map(Response::new)
+    }
+
+    async fn register_schema_version(
+        &self,
+        request: Request<RegisterSchemaVersionRequest>,
+    ) -> Result<Response<RegisterSchemaVersionReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+
+        let schema_version = register_schema_version_req(
+            &self.rocksdb_engine_handler,
+            &self.raft_manager,
+            &self.mqtt_call_manager,
+            &req,
+        )
+        .await
+        .map_err(Self::to_status)?;
+
+        Ok(Response::new(RegisterSchemaVersionReply {
+            schema_version: schema_version.encode().map_err(Self::to_status)?,
+        }))
+    }
+
+    async fn delete_schema_version(
+        &self,
+        request: Request<DeleteSchemaVersionRequest>,
+    ) -> Result<Response<DeleteSchemaVersionReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+
+        let versions = delete_schema_version_req(
+            &self.rocksdb_engine_handler,
+            &self.raft_manager,
+            &self.mqtt_call_manager,
+            &req,
+        )
+        .await
+        .map_err(Self::to_status)?;
+
+        Ok(Response::new(DeleteSchemaVersionReply { versions }))
+    }
+
+    // Rule
+    async fn list_rule(
+        &self,
+        request: Request<ListRuleRequest>,
+    ) -> Result<Response<Self::ListRuleStream>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+
+        list_rule_req(&self.rocksdb_engine_handler, &req)
+            .map_err(Self::to_status)
+            .map(Response::new)
+    }
+
+    async fn create_rule(
+        &self,
+        request: Request<CreateRuleRequest>,
+    ) -> Result<Response<CreateRuleReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+
+        create_rule_req(
+            &self.rocksdb_engine_handler,
+            &self.raft_manager,
+            &self.mqtt_call_manager,
+            &req,
+        )
+        .await
+        .map_err(Self::to_status)?;
+
+        Ok(Response::new(CreateRuleReply {}))
+    }
+
+    async fn update_rule(
+        &self,
+        request: Request<UpdateRuleRequest>,
+    ) -> Result<Response<UpdateRuleReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+
+        update_rule_req(
+            &self.rocksdb_engine_handler,
+            &self.raft_manager,
+            &self.mqtt_call_manager,
+            &req,
+        )
+        .await
+        .map_err(Self::to_status)?;
+
+        Ok(Response::new(UpdateRuleReply {}))
+    }
+
+    async fn delete_rule(
+        &self,
+        request: Request<DeleteRuleRequest>,
+    ) -> Result<Response<DeleteRuleReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+
+        delete_rule_req(
+            &self.rocksdb_engine_handler,
+            &self.raft_manager,
+            &self.mqtt_call_manager,
+            &req,
+        )
+        .await
+        .map_err(Self::to_status)?;
+
+        Ok(Response::new(DeleteRuleReply {}))
+    }
+
     // Tenant Operations
     async fn create_tenant(
         &self,
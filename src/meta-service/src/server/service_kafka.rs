@@ -22,23 +22,28 @@
 use crate::storage::kafka::delegation_token::KafkaDelegationTokenStorage;
 use crate::storage::kafka::quota::KafkaQuotaStorage;
 use crate::storage::kafka::scram::KafkaScramStorage;
+use crate::storage::kafka::share_group::KafkaShareGroupStorage;
 use bytes::Bytes;
 use grpc_clients::utils::NOT_RAFT_LEADER_MARKER;
 use metadata_struct::kafka::delegation_token::KafkaDelegationToken;
 use metadata_struct::kafka::quota::KafkaClientQuota;
 use metadata_struct::kafka::scram::KafkaScramCredential;
+use metadata_struct::kafka::share_group::KafkaSharePartitionState;
 use node_call::NodeCallManager;
 use prost::Message;
 use prost_validate::Validator;
 use protocol::meta::meta_service_kafka::kafka_service_server::KafkaService;
 use protocol::meta::meta_service_kafka::{
     DeleteKafkaDelegationTokenReply, DeleteKafkaDelegationTokenRequest, DeleteKafkaQuotaReply,
-    DeleteKafkaQuotaRequest, DeleteScramCredentialReply, DeleteScramCredentialRequest,
-    GetCoordinatorLeaderReply, GetCoordinatorLeaderRequest, ListKafkaDelegationTokenReply,
-    ListKafkaDelegationTokenRequest, ListKafkaQuotaReply, ListKafkaQuotaRequest,
+    DeleteKafkaQuotaRequest, DeleteKafkaSharePartitionStateReply,
+    DeleteKafkaSharePartitionStateRequest, DeleteScramCredentialReply,
+    DeleteScramCredentialRequest, GetCoordinatorLeaderReply, GetCoordinatorLeaderRequest,
+    ListKafkaDelegationTokenReply, ListKafkaDelegationTokenRequest, ListKafkaQuotaReply,
+    ListKafkaQuotaRequest, ListKafkaSharePartitionStateReply, ListKafkaSharePartitionStateRequest,
     ListScramCredentialReply, ListScramCredentialRequest, SetKafkaDelegationTokenReply,
     SetKafkaDelegationTokenRequest, SetKafkaQuotaReply, SetKafkaQuotaRequest,
-    SetScramCredentialReply, SetScramCredentialRequest,
+    SetKafkaSharePartitionStateReply, SetKafkaSharePartitionStateRequest, SetScramCredentialReply,
+    SetScramCredentialRequest,
 };
 use rocksdb_engine::rocksdb::RocksDBEngine;
 use std::sync::Arc;
@@ -320,4 +325,75 @@ async fn list_scram_credential(
             credentials: encoded,
         }))
     }
+
+    async fn set_kafka_share_partition_state(
+        &self,
+        request: Request<SetKafkaSharePartitionStateRequest>,
+    ) -> Result<Response<SetKafkaSharePartitionStateReply>, Status> {
+        let req = request.into_inner();
+        Self::validate_request(&req)?;
+        // Reject undecodable payloads here rather than failing the raft apply.
+        KafkaSharePartitionState::decode(&req.state).map_err(Self::to_status)?;
+
+        // No cache notify: share-partition state is only read back by the
+        // broker serving the partition, which keeps its own live copy.
+        let data = StorageData::new(
+            StorageDataType::KafkaSetSharePartitionState,
+            Bytes::from(req.encode_to_vec()),
+        );
+        self.raft_manager
+            .write_metadata(data)
+            .await
+            .map_err(Self::to_status)?;
+
+        Ok(Response::new(SetKafkaSharePartitionStateReply {}))
+    }
+
+    async fn delete_kafka_share_partition_state(
+        &self,
+        request: Request<DeleteKafkaSharePartitionStateRequest>,
+    ) -> Result<Response<DeleteKafkaSharePartitionStateReply>, Status> {
+        let req = request.into_inner();
+        Self::validate_request(&req)?;
+
+        let data = StorageData::new(
+            StorageDataType::KafkaDeleteSharePartitionState,
+            Bytes::from(req.encode_to_vec()),
+        );
+        self.raft_manager
+            .write_metadata(data)
+            .await
+            .map_err(Self::to_status)?;
+
+        Ok(Response::new(DeleteKafkaSharePartitionStateReply {}))
+    }
+
+    async fn list_kafka_share_partition_state(
+        &self,
+        request: Request<ListKafkaSharePartitionStateRequest>,
+    ) -> Result<Response<ListKafkaSharePartitionStateReply>, Status> {
+        let req = request.into_inner();
+        Self::validate_request(&req)?;
+
+        // Same leader-only rule as list_scram_credential: a broker reloading
+        // share-partition state right after writing it must see that write.
+        if !self.raft_manager.is_metadata_leader() {
+            return Err(Self::to_status(format!(
+                "{NOT_RAFT_LEADER_MARKER} for ListKafkaSharePartitionState"
+            )));
+        }
+
+        let storage = KafkaShareGroupStorage::new(self.rocksdb_engine_handler.clone());
+        let states = storage
+            .list(&req.tenant, &req.group_id)
+            .map_err(Self::to_status)?;
+        let mut encoded = Vec::with_capacity(states.len());
+        for state in states {
+            encoded.push(state.encode().map_err(Self::to_status)?);
+        }
+
+        Ok(Response::new(ListKafkaSharePartitionStateReply {
+            states: encoded,
+        }))
+    }
 }
@@ -13,6 +13,12 @@
 // limitations under the License.
 
 use crate::raft::manager::MultiRaftManager;
+use crate::server::services::nats::consumer::{
+    delete_nats_consumer_by_req, list_nats_consumer_by_req, set_nats_consumer_by_req,
+};
+use crate::server::services::nats::stream::{
+    delete_nats_stream_by_req, list_nats_stream_by_req, set_nats_stream_by_req,
+};
 use crate::server::services::nats::subscribe::{
     create_nats_subscribe_by_req, delete_nats_subscribe_by_req, list_nats_subscribe_by_req,
 };
@@ -20,8 +26,12 @@
 use prost_validate::Validator;
 use protocol::meta::meta_service_nats::nats_service_server::NatsService;
 use protocol::meta::meta_service_nats::{
-    CreateNatsSubscribeReply, CreateNatsSubscribeRequest, DeleteNatsSubscribeReply,
-    DeleteNatsSubscribeRequest, ListNatsSubscribeReply, ListNatsSubscribeRequest,
+    CreateNatsSubscribeReply, CreateNatsSubscribeRequest, DeleteNatsConsumerReply,
+    DeleteNatsConsumerRequest, DeleteNatsStreamReply, DeleteNatsStreamRequest,
+    DeleteNatsSubscribeReply, DeleteNatsSubscribeRequest, ListNatsConsumerReply,
+    ListNatsConsumerRequest, ListNatsStreamReply, ListNatsStreamRequest, ListNatsSubscribeReply,
+    ListNatsSubscribeRequest, SetNatsConsumerReply, SetNatsConsumerRequest, SetNatsStreamReply,
+    SetNatsStreamRequest,
 };
 use rocksdb_engine::rocksdb::RocksDBEngine;
 use std::pin::Pin;
@@ -62,6 +72,10 @@ fn to_status<E: ToString>(e: E) -> Status {
 impl NatsService for GrpcNatsService {
     type ListNatsSubscribeStream =
         Pin<Box<dyn Stream<Item = Result<ListNatsSubscribeReply, Status>> + Send>>;
+    type ListNatsStreamStream =
+        Pin<Box<dyn Stream<Item = Result<ListNatsStreamReply, Status>> + Send>>;
+    type ListNatsConsumerStream =
+        Pin<Box<dyn Stream<Item = Result<ListNatsConsumerReply, Status>> + Send>>;
 
     async fn create_nats_subscribe(
         &self,
@@ -106,4 +120,82 @@ async fn list_nats_subscribe(
             .map_err(Self::to_status)
             .map(Response::new)
     }
+
+    async fn set_nats_stream(
+        &self,
+        request: Request<SetNatsStreamRequest>,
+    ) -> Result<Response<SetNatsStreamReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+        set_nats_stream_by_req(&self.raft_manager, &self.call_manager, &req)
+            .await
+            .map_err(Self::to_status)
+            .map(Response::new)
+    }
+
+    async fn delete_nats_stream(
+        &self,
+        request: Request<DeleteNatsStreamRequest>,
+    ) -> Result<Response<DeleteNatsStreamReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+        delete_nats_stream_by_req(
+            &self.raft_manager,
+            &self.call_manager,
+            &self.rocksdb_engine_handler,
+            &req,
+        )
+        .await
+        .map_err(Self::to_status)
+        .map(Response::new)
+    }
+
+    async fn list_nats_stream(
+        &self,
+        request: Request<ListNatsStreamRequest>,
+    ) -> Result<Response<Self::ListNatsStreamStream>, Status> {
+        let req = request.into_inner();
+        list_nats_stream_by_req(&self.rocksdb_engine_handler, &req)
+            .map_err(Self::to_status)
+            .map(Response::new)
+    }
+
+    async fn set_nats_consumer(
+        &self,
+        request: Request<SetNatsConsumerRequest>,
+    ) -> Result<Response<SetNatsConsumerReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+        set_nats_consumer_by_req(&self.raft_manager, &self.call_manager, &req)
+            .await
+            .map_err(Self::to_status)
+            .map(Response::new)
+    }
+
+    async fn delete_nats_consumer(
+        &self,
+        request: Request<DeleteNatsConsumerRequest>,
+    ) -> Result<Response<DeleteNatsConsumerReply>, Status> {
+        let req = request.into_inner();
+        self.validate_request(&req)?;
+        delete_nats_consumer_by_req(
+            &self.raft_manager,
+            &self.call_manager,
+            &self.rocksdb_engine_handler,
+            &req,
+        )
+        .await
+        .map_err(Self::to_status)
+        .map(Response::new)
+    }
+
+    async fn list_nats_consumer(
+        &self,
+        request: Request<ListNatsConsumerRequest>,
+    ) -> Result<Response<Self::ListNatsConsumerStream>, Status> {
+        let req = request.into_inner();
+        list_nats_consumer_by_req(&self.rocksdb_engine_handler, &req)
+            .map_err(Self::to_status)
+            .map(Response::new)
+    }
 }
@@ -14,5 +14,6 @@
 
 pub mod inner;
 pub mod kv;
+pub mod rule;
 pub mod schema;
 pub mod tenant;
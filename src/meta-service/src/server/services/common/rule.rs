@@ -0,0 +1,153 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    core::error::MetaServiceError,
+    core::notify::{send_notify_by_add_rule, send_notify_by_delete_rule},
+    raft::{
+        manager::MultiRaftManager,
+        route::data::{StorageData, StorageDataType},
+    },
+    storage::common::rule::RuleStorage,
+};
+use common_base::utils::serialize::encode_to_bytes;
+use metadata_struct::rule::{Rule, RuleAction};
+use node_call::NodeCallManager;
+use protocol::meta::meta_service_common::{
+    CreateRuleRequest, DeleteRuleRequest, ListRuleReply, ListRuleRequest, UpdateRuleRequest,
+};
+use rocksdb_engine::rocksdb::RocksDBEngine;
+use rule_engine::sql::SqlRule;
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::codegen::tokio_stream::Stream;
+use tonic::Status;
+
+type ListRuleStream = std::result::Result<
+    Pin<Box<dyn Stream<Item = std::result::Result<ListRuleReply, Status>> + Send>>,
+    MetaServiceError,
+>;
+
+// Helper: Decode the rule and check it matches the request and compiles
+fn decode_rule(tenant: &str, rule_name: &str, data: &[u8]) -> Result<Rule, MetaServiceError> {
+    let rule = Rule::decode(data)?;
+    if rule.tenant != tenant || rule.name != rule_name {
+        return Err(MetaServiceError::CommonError(format!(
+            "rule {}/{} does not match request {}/{}",
+            rule.tenant, rule.name, tenant, rule_name
+        )));
+    }
+    SqlRule::compile(&rule.sql)?;
+    for action in rule.actions.iter() {
+        let empty = match action {
+            RuleAction::Republish { topic, .. } => topic.is_empty(),
+            RuleAction::Connector { connector_name } => connector_name.is_empty(),
+        };
+        if empty {
+            return Err(MetaServiceError::RequestParamsNotEmpty(
+                "rule action target".to_string(),
+            ));
+        }
+    }
+    Ok(rule)
+}
+
+pub fn list_rule_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    req: &ListRuleRequest,
+) -> ListRuleStream {
+    let rule_storage = RuleStorage::new(rocksdb_engine_handler.clone());
+    let list = if !req.rule_name.is_empty() {
+        rule_storage
+            .get(&req.tenant, &req.rule_name)?
+            .into_iter()
+            .collect()
+    } else if !req.tenant.is_empty() {
+        rule_storage.list_by_tenant(&req.tenant)?
+    } else {
+        rule_storage.list()?
+    };
+
+    let rules = list
+        .into_iter()
+        .map(|rule| rule.encode())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let output = async_stream::try_stream! {
+        for rule in rules {
+            yield ListRuleReply { rule };
+        }
+    };
+
+    Ok(Box::pin(output))
+}
+
+pub async fn create_rule_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    req: &CreateRuleRequest,
+) -> Result<(), MetaServiceError> {
+    let rule = decode_rule(&req.tenant, &req.rule_name, &req.rule)?;
+
+    let rule_storage = RuleStorage::new(rocksdb_engine_handler.clone());
+    if rule_storage.get(&req.tenant, &req.rule_name)?.is_some() {
+        return Err(MetaServiceError::RuleAlreadyExist(req.rule_name.clone()));
+    }
+
+    let data = StorageData::new(StorageDataType::RuleSet, encode_to_bytes(req));
+    raft_manager.write_metadata(data).await?;
+
+    send_notify_by_add_rule(call_manager, rule).await?;
+    Ok(())
+}
+
+pub async fn update_rule_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    req: &UpdateRuleRequest,
+) -> Result<(), MetaServiceError> {
+    let rule = decode_rule(&req.tenant, &req.rule_name, &req.rule)?;
+
+    let rule_storage = RuleStorage::new(rocksdb_engine_handler.clone());
+    if rule_storage.get(&req.tenant, &req.rule_name)?.is_none() {
+        return Err(MetaServiceError::RuleDoesNotExist(req.rule_name.clone()));
+    }
+
+    // Same wire layout as CreateRuleRequest, which RuleSet decodes.
+    let data = StorageData::new(StorageDataType::RuleSet, encode_to_bytes(req));
+    raft_manager.write_metadata(data).await?;
+
+    send_notify_by_add_rule(call_manager, rule).await?;
+    Ok(())
+}
+
+pub async fn delete_rule_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    req: &DeleteRuleRequest,
+) -> Result<(), MetaServiceError> {
+    let rule_storage = RuleStorage::new(rocksdb_engine_handler.clone());
+    let rule = rule_storage
+        .get(&req.tenant, &req.rule_name)?
+        .ok_or_else(|| MetaServiceError::RuleDoesNotExist(req.rule_name.clone()))?;
+
+    let data = StorageData::new(StorageDataType::RuleDelete, encode_to_bytes(req));
+    raft_manager.write_metadata(data).await?;
+
+    send_notify_by_delete_rule(call_manager, rule).await?;
+    Ok(())
+}
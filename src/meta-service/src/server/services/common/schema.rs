@@ -15,26 +15,33 @@
 use crate::{
     core::error::MetaServiceError,
     core::notify::{
-        send_notify_by_add_schema, send_notify_by_add_schema_bind, send_notify_by_delete_schema,
-        send_notify_by_delete_schema_bind,
+        send_notify_by_add_schema, send_notify_by_add_schema_bind,
+        send_notify_by_add_schema_version, send_notify_by_delete_schema,
+        send_notify_by_delete_schema_bind, send_notify_by_delete_schema_version,
     },
     raft::{
         manager::MultiRaftManager,
-        route::data::{StorageData, StorageDataType},
+        route::{
+            common::RegisterSchemaVersionResult,
+            data::{StorageData, StorageDataType},
+        },
     },
     storage::common::schema::SchemaStorage,
 };
 use common_base::utils::serialize::encode_to_bytes;
-use metadata_struct::schema::{SchemaData, SchemaResourceBind};
+use metadata_struct::schema::{SchemaData, SchemaResourceBind, SchemaType, SchemaVersion};
 use node_call::NodeCallManager;
 use prost_validate::Result;
 use protocol::meta::meta_service_common::{
-    BindSchemaRequest, CreateSchemaRequest, DeleteSchemaRequest, ListBindSchemaReply,
-    ListBindSchemaRequest, ListSchemaReply, ListSchemaRequest, UnBindSchemaRequest,
-    UpdateSchemaRequest,
+    BindSchemaRequest, CreateSchemaRequest, DeleteSchemaRequest, DeleteSchemaVersionRequest,
+    ListBindSchemaReply, ListBindSchemaRequest, ListSchemaReply, ListSchemaRequest,
+    ListSchemaVersionReply, ListSchemaVersionRequest, RegisterSchemaVersionRequest,
+    UnBindSchemaRequest, UpdateSchemaRequest,
 };
 use rocksdb_engine::rocksdb::RocksDBEngine;
+use schema_register::compatibility::parse_schema;
 use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::Arc;
 use tonic::codegen::tokio_stream::Stream;
 use tonic::Status;
@@ -44,6 +51,11 @@
     MetaServiceError,
 >;
 
+type ListSchemaVersionStream = std::result::Result<
+    Pin<Box<dyn Stream<Item = std::result::Result<ListSchemaVersionReply, Status>> + Send>>,
+    MetaServiceError,
+>;
+
 type ListBindSchemaStream = std::result::Result<
     Pin<Box<dyn Stream<Item = std::result::Result<ListBindSchemaReply, Status>> + Send>>,
     MetaServiceError,
@@ -249,6 +261,9 @@ pub async fn bind_schema_req(
         tenant: req.tenant.clone(),
         schema_name: req.schema_name.clone(),
         resource_name: req.resource_name.clone(),
+        message_name: req.message_name.clone(),
+        strict_unknown_fields: req.strict_unknown_fields,
+        strict_required_fields: req.strict_required_fields,
     };
     send_notify_by_add_schema_bind(call_manager, schema_data).await?;
 
@@ -269,8 +284,124 @@ pub async fn un_bind_schema_req(
         tenant: req.tenant.clone(),
         schema_name: req.schema_name.clone(),
         resource_name: req.resource_name.clone(),
+        message_name: String::new(),
+        strict_unknown_fields: false,
+        strict_required_fields: false,
     };
     send_notify_by_delete_schema_bind(call_manager, schema_data).await?;
 
     Ok(())
 }
+
+// Schema Version Operations
+pub fn list_schema_version_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    req: &ListSchemaVersionRequest,
+) -> ListSchemaVersionStream {
+    let schema_storage = SchemaStorage::new(rocksdb_engine_handler.clone());
+    let list = if !req.subject.is_empty() && req.version != 0 {
+        schema_storage
+            .get_version(&req.tenant, &req.subject, req.version)?
+            .into_iter()
+            .collect()
+    } else if !req.subject.is_empty() {
+        schema_storage.list_versions_by_subject(&req.tenant, &req.subject)?
+    } else if !req.tenant.is_empty() {
+        schema_storage.list_versions_by_tenant(&req.tenant)?
+    } else {
+        schema_storage.list_versions()?
+    };
+
+    let versions = list
+        .into_iter()
+        .filter(|v| req.id == 0 || v.id == req.id)
+        .map(|v| v.encode())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let output = async_stream::try_stream! {
+        for schema_version in versions {
+            yield ListSchemaVersionReply { schema_version };
+        }
+    };
+
+    Ok(Box::pin(output))
+}
+
+pub async fn register_schema_version_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    req: &RegisterSchemaVersionRequest,
+) -> Result<SchemaVersion, MetaServiceError> {
+    validate_non_empty(&req.subject, "subject")?;
+    let schema_type = SchemaType::from_str(&req.schema_type)?;
+    parse_schema(&schema_type, &req.schema)?;
+
+    // The compatibility check runs in the raft apply, next to the version
+    // numbering, so it sees every version registered before this one.
+    let data = StorageData::new(StorageDataType::SchemaVersionSet, encode_to_bytes(req));
+    let response = raft_manager
+        .write_metadata(data)
+        .await?
+        .ok_or(MetaServiceError::ExecutionResultIsEmpty)?;
+    let value = response
+        .data
+        .value
+        .ok_or(MetaServiceError::ExecutionResultIsEmpty)?;
+    let version = match RegisterSchemaVersionResult::decode(&value)? {
+        RegisterSchemaVersionResult::Registered(version) => version,
+        RegisterSchemaVersionResult::Incompatible(errors) => {
+            return Err(MetaServiceError::SchemaIncompatible(
+                req.subject.clone(),
+                errors,
+            ));
+        }
+    };
+
+    let schema_storage = SchemaStorage::new(rocksdb_engine_handler.clone());
+    if let Some(schema) = schema_storage.get(&req.tenant, &req.subject)? {
+        send_notify_by_add_schema(call_manager, schema).await?;
+    }
+    send_notify_by_add_schema_version(call_manager, version.clone()).await?;
+    Ok(version)
+}
+
+pub async fn delete_schema_version_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    req: &DeleteSchemaVersionRequest,
+) -> Result<Vec<u32>, MetaServiceError> {
+    validate_non_empty(&req.subject, "subject")?;
+
+    let schema_storage = SchemaStorage::new(rocksdb_engine_handler.clone());
+    let deleted: Vec<SchemaVersion> = schema_storage
+        .list_versions_by_subject(&req.tenant, &req.subject)?
+        .into_iter()
+        .filter(|v| req.version == 0 || v.version == req.version)
+        .collect();
+    if deleted.is_empty() {
+        return Err(MetaServiceError::SchemaVersionDoesNotExist(
+            req.subject.clone(),
+            req.version,
+        ));
+    }
+    let schema = schema_storage.get(&req.tenant, &req.subject)?;
+
+    let data = StorageData::new(StorageDataType::SchemaVersionDelete, encode_to_bytes(req));
+    raft_manager.write_metadata(data).await?;
+
+    match schema_storage.get(&req.tenant, &req.subject)? {
+        Some(latest) => send_notify_by_add_schema(call_manager, latest).await?,
+        None => {
+            if let Some(schema) = schema {
+                send_notify_by_delete_schema(call_manager, schema).await?;
+            }
+        }
+    }
+    let versions = deleted.iter().map(|v| v.version).collect();
+    for version in deleted {
+        send_notify_by_delete_schema_version(call_manager, version).await?;
+    }
+    Ok(versions)
+}
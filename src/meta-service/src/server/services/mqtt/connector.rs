@@ -18,10 +18,14 @@
 use crate::core::notify::send_notify_by_delete_connector;
 use crate::raft::manager::MultiRaftManager;
 use crate::raft::route::data::{StorageData, StorageDataType};
+use crate::server::services::common::kv::delete_by_req;
+use crate::storage::common::kv::KvStorage;
 use crate::storage::mqtt::connector::MqttConnectorStorage;
 use common_base::utils::serialize::encode_to_bytes;
+use metadata_struct::connector::source::source_offset_key;
 use metadata_struct::connector::MQTTConnector;
 use node_call::NodeCallManager;
+use protocol::meta::meta_service_common::DeleteRequest;
 use protocol::meta::meta_service_mqtt::{
     ConnectorHeartbeatReply, ConnectorHeartbeatRequest, CreateConnectorReply,
     CreateConnectorRequest, DeleteConnectorReply, DeleteConnectorRequest, ListConnectorReply,
@@ -168,6 +172,14 @@ pub async fn delete_connector_by_req(
     let data = StorageData::new(StorageDataType::MqttDeleteConnector, encode_to_bytes(req));
     raft_manager.write_metadata(data).await?;
 
+    // Drop the stored source offset so a recreated connector starts afresh
+    if connector.connector_type.is_source() {
+        let key = source_offset_key(&connector.tenant, &connector.connector_name);
+        if KvStorage::new(rocksdb_engine_handler.clone()).exists(key.clone())? {
+            delete_by_req(raft_manager, &DeleteRequest { key }).await?;
+        }
+    }
+
     send_notify_by_delete_connector(mqtt_call_manager, connector).await?;
 
     Ok(DeleteConnectorReply {})
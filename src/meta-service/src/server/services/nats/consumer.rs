@@ -0,0 +1,86 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::error::MetaServiceError;
+use crate::core::notify::{send_notify_by_delete_nats_consumer, send_notify_by_set_nats_consumer};
+use crate::raft::manager::MultiRaftManager;
+use crate::raft::route::data::{StorageData, StorageDataType};
+use crate::storage::nats::NatsConsumerStorage;
+use common_base::utils::serialize::encode_to_bytes;
+use metadata_struct::nats::consumer::NatsConsumer;
+use node_call::NodeCallManager;
+use protocol::meta::meta_service_nats::{
+    DeleteNatsConsumerReply, DeleteNatsConsumerRequest, ListNatsConsumerReply,
+    ListNatsConsumerRequest, SetNatsConsumerReply, SetNatsConsumerRequest,
+};
+use rocksdb_engine::rocksdb::RocksDBEngine;
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::codegen::tokio_stream::Stream;
+use tonic::Status;
+
+pub type ListNatsConsumerStream = Result<
+    Pin<Box<dyn Stream<Item = Result<ListNatsConsumerReply, Status>> + Send>>,
+    MetaServiceError,
+>;
+
+pub fn list_nats_consumer_by_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    req: &ListNatsConsumerRequest,
+) -> ListNatsConsumerStream {
+    let storage = NatsConsumerStorage::new(rocksdb_engine_handler.clone());
+    let consumers = if req.tenant.is_empty() {
+        storage.list()?
+    } else if req.stream_name.is_empty() {
+        storage.list_by_tenant(&req.tenant)?
+    } else {
+        storage.list_by_stream(&req.tenant, &req.stream_name)?
+    };
+
+    let output = async_stream::try_stream! {
+        for consumer in consumers {
+            yield ListNatsConsumerReply { consumer: consumer.encode()? };
+        }
+    };
+
+    Ok(Box::pin(output))
+}
+
+pub async fn set_nats_consumer_by_req(
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    req: &SetNatsConsumerRequest,
+) -> Result<SetNatsConsumerReply, MetaServiceError> {
+    let consumer = NatsConsumer::decode(&req.content)?;
+    let data = StorageData::new(StorageDataType::NatsSetConsumer, encode_to_bytes(req));
+    raft_manager.write_data("nats/consumer", data).await?;
+    send_notify_by_set_nats_consumer(call_manager, consumer).await?;
+    Ok(SetNatsConsumerReply {})
+}
+
+pub async fn delete_nats_consumer_by_req(
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    req: &DeleteNatsConsumerRequest,
+) -> Result<DeleteNatsConsumerReply, MetaServiceError> {
+    let storage = NatsConsumerStorage::new(rocksdb_engine_handler.clone());
+    let Some(consumer) = storage.get(&req.tenant, &req.stream_name, &req.consumer_name)? else {
+        return Ok(DeleteNatsConsumerReply {});
+    };
+    let data = StorageData::new(StorageDataType::NatsDeleteConsumer, encode_to_bytes(req));
+    raft_manager.write_data("nats/consumer", data).await?;
+    send_notify_by_delete_nats_consumer(call_manager, consumer).await?;
+    Ok(DeleteNatsConsumerReply {})
+}
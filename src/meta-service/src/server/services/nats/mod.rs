@@ -12,4 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod consumer;
+pub mod stream;
 pub mod subscribe;
@@ -0,0 +1,84 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::error::MetaServiceError;
+use crate::core::notify::{send_notify_by_delete_nats_stream, send_notify_by_set_nats_stream};
+use crate::raft::manager::MultiRaftManager;
+use crate::raft::route::data::{StorageData, StorageDataType};
+use crate::storage::nats::NatsStreamStorage;
+use common_base::utils::serialize::encode_to_bytes;
+use metadata_struct::nats::stream::NatsStream;
+use node_call::NodeCallManager;
+use protocol::meta::meta_service_nats::{
+    DeleteNatsStreamReply, DeleteNatsStreamRequest, ListNatsStreamReply, ListNatsStreamRequest,
+    SetNatsStreamReply, SetNatsStreamRequest,
+};
+use rocksdb_engine::rocksdb::RocksDBEngine;
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::codegen::tokio_stream::Stream;
+use tonic::Status;
+
+pub type ListNatsStreamStream = Result<
+    Pin<Box<dyn Stream<Item = Result<ListNatsStreamReply, Status>> + Send>>,
+    MetaServiceError,
+>;
+
+pub fn list_nats_stream_by_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    req: &ListNatsStreamRequest,
+) -> ListNatsStreamStream {
+    let storage = NatsStreamStorage::new(rocksdb_engine_handler.clone());
+    let streams = if req.tenant.is_empty() {
+        storage.list()?
+    } else {
+        storage.list_by_tenant(&req.tenant)?
+    };
+
+    let output = async_stream::try_stream! {
+        for stream in streams {
+            yield ListNatsStreamReply { stream: stream.encode()? };
+        }
+    };
+
+    Ok(Box::pin(output))
+}
+
+pub async fn set_nats_stream_by_req(
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    req: &SetNatsStreamRequest,
+) -> Result<SetNatsStreamReply, MetaServiceError> {
+    let stream = NatsStream::decode(&req.content)?;
+    let data = StorageData::new(StorageDataType::NatsSetStream, encode_to_bytes(req));
+    raft_manager.write_data("nats/stream", data).await?;
+    send_notify_by_set_nats_stream(call_manager, stream).await?;
+    Ok(SetNatsStreamReply {})
+}
+
+pub async fn delete_nats_stream_by_req(
+    raft_manager: &Arc<MultiRaftManager>,
+    call_manager: &Arc<NodeCallManager>,
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    req: &DeleteNatsStreamRequest,
+) -> Result<DeleteNatsStreamReply, MetaServiceError> {
+    let storage = NatsStreamStorage::new(rocksdb_engine_handler.clone());
+    let Some(stream) = storage.get(&req.tenant, &req.stream_name)? else {
+        return Ok(DeleteNatsStreamReply {});
+    };
+    let data = StorageData::new(StorageDataType::NatsDeleteStream, encode_to_bytes(req));
+    raft_manager.write_data("nats/stream", data).await?;
+    send_notify_by_delete_nats_stream(call_manager, stream).await?;
+    Ok(DeleteNatsStreamReply {})
+}
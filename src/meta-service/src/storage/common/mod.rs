@@ -17,6 +17,7 @@
 pub mod lock;
 pub mod node;
 pub mod offset;
+pub mod rule;
 pub mod schema;
 pub mod share_group;
 pub mod tenant;
@@ -0,0 +1,120 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::error::MetaServiceError;
+use metadata_struct::rule::Rule;
+use rocksdb_engine::keys::meta::{
+    storage_key_rule, storage_key_rule_prefix, storage_key_rule_tenant_prefix,
+};
+use rocksdb_engine::rocksdb::RocksDBEngine;
+use rocksdb_engine::storage::meta_metadata::{
+    engine_delete_by_meta_metadata, engine_get_by_meta_metadata,
+    engine_prefix_list_by_meta_metadata, engine_save_by_meta_metadata,
+};
+use std::sync::Arc;
+
+pub struct RuleStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl RuleStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        RuleStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(&self, rule: &Rule) -> Result<(), MetaServiceError> {
+        let key = storage_key_rule(&rule.tenant, &rule.name);
+        engine_save_by_meta_metadata(&self.rocksdb_engine_handler, &key, rule)?;
+        Ok(())
+    }
+
+    pub fn list(&self) -> Result<Vec<Rule>, MetaServiceError> {
+        let prefix_key = storage_key_rule_prefix();
+        let data =
+            engine_prefix_list_by_meta_metadata::<Rule>(&self.rocksdb_engine_handler, &prefix_key)?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+
+    pub fn list_by_tenant(&self, tenant: &str) -> Result<Vec<Rule>, MetaServiceError> {
+        let prefix_key = storage_key_rule_tenant_prefix(tenant);
+        let data =
+            engine_prefix_list_by_meta_metadata::<Rule>(&self.rocksdb_engine_handler, &prefix_key)?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+
+    pub fn get(&self, tenant: &str, rule_name: &str) -> Result<Option<Rule>, MetaServiceError> {
+        let key = storage_key_rule(tenant, rule_name);
+        if let Some(data) = engine_get_by_meta_metadata::<Rule>(&self.rocksdb_engine_handler, &key)?
+        {
+            return Ok(Some(data.data));
+        }
+        Ok(None)
+    }
+
+    pub fn delete(&self, tenant: &str, rule_name: &str) -> Result<(), MetaServiceError> {
+        let key = storage_key_rule(tenant, rule_name);
+        engine_delete_by_meta_metadata(&self.rocksdb_engine_handler, &key)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RuleStorage;
+    use metadata_struct::rule::{Rule, RuleAction};
+    use rocksdb_engine::rocksdb::RocksDBEngine;
+    use rocksdb_engine::storage::family::column_family_list;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn rule_storage_test() {
+        let rocksdb_engine = Arc::new(RocksDBEngine::new(
+            tempdir().unwrap().path().to_str().unwrap(),
+            100,
+            column_family_list(),
+        ));
+        let rule_storage = RuleStorage::new(rocksdb_engine);
+        let rule = |tenant: &str, name: &str| Rule {
+            tenant: tenant.to_string(),
+            name: name.to_string(),
+            sql: "SELECT * FROM 'sensors/#'".to_string(),
+            actions: vec![RuleAction::Republish {
+                topic: "copy".to_string(),
+                payload: None,
+            }],
+            desc: String::new(),
+            enable: true,
+            create_time: 1,
+            update_time: 1,
+        };
+
+        rule_storage.save(&rule("t1", "r1")).unwrap();
+        rule_storage.save(&rule("t1", "r2")).unwrap();
+        rule_storage.save(&rule("t2", "r1")).unwrap();
+
+        assert_eq!(
+            rule_storage.get("t1", "r1").unwrap(),
+            Some(rule("t1", "r1"))
+        );
+        assert_eq!(rule_storage.list_by_tenant("t1").unwrap().len(), 2);
+        assert_eq!(rule_storage.list().unwrap().len(), 3);
+
+        rule_storage.delete("t1", "r1").unwrap();
+        assert!(rule_storage.get("t1", "r1").unwrap().is_none());
+        assert_eq!(rule_storage.list_by_tenant("t1").unwrap().len(), 1);
+    }
+}
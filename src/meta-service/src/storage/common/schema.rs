@@ -13,11 +13,14 @@
 // limitations under the License.
 
 use crate::core::error::MetaServiceError;
-use metadata_struct::schema::{SchemaData, SchemaResourceBind};
+use metadata_struct::schema::{SchemaData, SchemaResourceBind, SchemaVersion};
 use rocksdb_engine::keys::meta::{
     storage_key_mqtt_schema, storage_key_mqtt_schema_bind, storage_key_mqtt_schema_bind_prefix,
     storage_key_mqtt_schema_bind_prefix_by_resource, storage_key_mqtt_schema_bind_tenant_prefix,
-    storage_key_mqtt_schema_prefix, storage_key_mqtt_schema_tenant_prefix,
+    storage_key_mqtt_schema_id_seq, storage_key_mqtt_schema_prefix,
+    storage_key_mqtt_schema_tenant_prefix, storage_key_mqtt_schema_version,
+    storage_key_mqtt_schema_version_prefix, storage_key_mqtt_schema_version_seq,
+    storage_key_mqtt_schema_version_subject_prefix, storage_key_mqtt_schema_version_tenant_prefix,
 };
 use rocksdb_engine::rocksdb::RocksDBEngine;
 use rocksdb_engine::storage::meta_metadata::{
@@ -87,6 +90,102 @@ pub fn delete(&self, tenant: &str, schema_name: &str) -> Result<(), MetaServiceE
         Ok(())
     }
 
+    // Schema Version
+    pub fn save_version(&self, version: &SchemaVersion) -> Result<(), MetaServiceError> {
+        let key =
+            storage_key_mqtt_schema_version(&version.tenant, &version.subject, version.version);
+        engine_save_by_meta_metadata(&self.rocksdb_engine_handler, &key, version)?;
+        Ok(())
+    }
+
+    pub fn delete_version(
+        &self,
+        tenant: &str,
+        subject: &str,
+        version: u32,
+    ) -> Result<(), MetaServiceError> {
+        let key = storage_key_mqtt_schema_version(tenant, subject, version);
+        engine_delete_by_meta_metadata(&self.rocksdb_engine_handler, &key)?;
+        Ok(())
+    }
+
+    pub fn get_version(
+        &self,
+        tenant: &str,
+        subject: &str,
+        version: u32,
+    ) -> Result<Option<SchemaVersion>, MetaServiceError> {
+        let key = storage_key_mqtt_schema_version(tenant, subject, version);
+        Ok(
+            engine_get_by_meta_metadata::<SchemaVersion>(&self.rocksdb_engine_handler, &key)?
+                .map(|data| data.data),
+        )
+    }
+
+    /// Versions of a subject, oldest first.
+    pub fn list_versions_by_subject(
+        &self,
+        tenant: &str,
+        subject: &str,
+    ) -> Result<Vec<SchemaVersion>, MetaServiceError> {
+        let prefix_key = storage_key_mqtt_schema_version_subject_prefix(tenant, subject);
+        let data = engine_prefix_list_by_meta_metadata::<SchemaVersion>(
+            &self.rocksdb_engine_handler,
+            &prefix_key,
+        )?;
+        // The prefix of subject `a` also covers subject `a/b`.
+        Ok(data
+            .into_iter()
+            .map(|raw| raw.data)
+            .filter(|v| v.subject == subject)
+            .collect())
+    }
+
+    pub fn list_versions_by_tenant(
+        &self,
+        tenant: &str,
+    ) -> Result<Vec<SchemaVersion>, MetaServiceError> {
+        let prefix_key = storage_key_mqtt_schema_version_tenant_prefix(tenant);
+        let data = engine_prefix_list_by_meta_metadata::<SchemaVersion>(
+            &self.rocksdb_engine_handler,
+            &prefix_key,
+        )?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+
+    pub fn list_versions(&self) -> Result<Vec<SchemaVersion>, MetaServiceError> {
+        let prefix_key = storage_key_mqtt_schema_version_prefix();
+        let data = engine_prefix_list_by_meta_metadata::<SchemaVersion>(
+            &self.rocksdb_engine_handler,
+            &prefix_key,
+        )?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+
+    pub fn next_version(&self, tenant: &str, subject: &str) -> Result<u32, MetaServiceError> {
+        let key = storage_key_mqtt_schema_version_seq(tenant, subject);
+        self.next_seq(&key)
+    }
+
+    pub fn delete_version_seq(&self, tenant: &str, subject: &str) -> Result<(), MetaServiceError> {
+        let key = storage_key_mqtt_schema_version_seq(tenant, subject);
+        engine_delete_by_meta_metadata(&self.rocksdb_engine_handler, &key)?;
+        Ok(())
+    }
+
+    pub fn next_id(&self) -> Result<u32, MetaServiceError> {
+        self.next_seq(&storage_key_mqtt_schema_id_seq())
+    }
+
+    fn next_seq(&self, key: &str) -> Result<u32, MetaServiceError> {
+        let current = engine_get_by_meta_metadata::<u32>(&self.rocksdb_engine_handler, key)?
+            .map(|w| w.data)
+            .unwrap_or(0);
+        let next = current + 1;
+        engine_save_by_meta_metadata(&self.rocksdb_engine_handler, key, next)?;
+        Ok(next)
+    }
+
     // Schema Bind
     pub fn list_bind(&self) -> Result<Vec<SchemaResourceBind>, MetaServiceError> {
         let prefix_key = storage_key_mqtt_schema_bind_prefix();
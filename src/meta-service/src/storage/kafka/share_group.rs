@@ -0,0 +1,77 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use metadata_struct::kafka::share_group::KafkaSharePartitionState;
+use rocksdb_engine::keys::meta::{
+    storage_key_kafka_share_partition, storage_key_kafka_share_partition_group_prefix,
+    storage_key_kafka_share_partition_tenant_prefix,
+};
+use rocksdb_engine::rocksdb::RocksDBEngine;
+use rocksdb_engine::storage::meta_metadata::{
+    engine_delete_by_meta_metadata, engine_prefix_list_by_meta_metadata,
+    engine_save_by_meta_metadata,
+};
+
+pub struct KafkaShareGroupStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl KafkaShareGroupStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        KafkaShareGroupStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(&self, state: KafkaSharePartitionState) -> Result<(), CommonError> {
+        let key = storage_key_kafka_share_partition(
+            &state.tenant,
+            &state.group_id,
+            &state.topic,
+            state.partition,
+        );
+        engine_save_by_meta_metadata(&self.rocksdb_engine_handler, &key, state)
+    }
+
+    pub fn list(
+        &self,
+        tenant: &str,
+        group_id: &str,
+    ) -> Result<Vec<KafkaSharePartitionState>, CommonError> {
+        let prefix_key = if group_id.is_empty() {
+            storage_key_kafka_share_partition_tenant_prefix(tenant)
+        } else {
+            storage_key_kafka_share_partition_group_prefix(tenant, group_id)
+        };
+        let data = engine_prefix_list_by_meta_metadata::<KafkaSharePartitionState>(
+            &self.rocksdb_engine_handler,
+            &prefix_key,
+        )?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+
+    pub fn delete(
+        &self,
+        tenant: &str,
+        group_id: &str,
+        topic: &str,
+        partition: i32,
+    ) -> Result<(), CommonError> {
+        let key = storage_key_kafka_share_partition(tenant, group_id, topic, partition);
+        engine_delete_by_meta_metadata(&self.rocksdb_engine_handler, &key)
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use metadata_struct::nats::consumer::NatsConsumer;
+use rocksdb_engine::keys::meta::{
+    storage_key_nats_consumer, storage_key_nats_consumer_prefix,
+    storage_key_nats_consumer_stream_prefix, storage_key_nats_consumer_tenant_prefix,
+};
+use rocksdb_engine::rocksdb::RocksDBEngine;
+use rocksdb_engine::storage::meta_data::{
+    engine_delete_by_meta_data, engine_get_by_meta_data, engine_prefix_list_by_meta_data,
+    engine_save_by_meta_data,
+};
+use std::sync::Arc;
+
+pub struct NatsConsumerStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl NatsConsumerStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        NatsConsumerStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(&self, consumer: &NatsConsumer) -> Result<(), CommonError> {
+        let key =
+            storage_key_nats_consumer(&consumer.tenant, &consumer.stream_name, &consumer.name);
+        engine_save_by_meta_data(&self.rocksdb_engine_handler, &key, consumer)
+    }
+
+    pub fn get(
+        &self,
+        tenant: &str,
+        stream_name: &str,
+        consumer_name: &str,
+    ) -> Result<Option<NatsConsumer>, CommonError> {
+        let key = storage_key_nats_consumer(tenant, stream_name, consumer_name);
+        Ok(
+            engine_get_by_meta_data::<NatsConsumer>(&self.rocksdb_engine_handler, &key)?
+                .map(|data| data.data),
+        )
+    }
+
+    pub fn list(&self) -> Result<Vec<NatsConsumer>, CommonError> {
+        self.list_by_prefix(&storage_key_nats_consumer_prefix())
+    }
+
+    pub fn list_by_tenant(&self, tenant: &str) -> Result<Vec<NatsConsumer>, CommonError> {
+        self.list_by_prefix(&storage_key_nats_consumer_tenant_prefix(tenant))
+    }
+
+    pub fn list_by_stream(
+        &self,
+        tenant: &str,
+        stream_name: &str,
+    ) -> Result<Vec<NatsConsumer>, CommonError> {
+        self.list_by_prefix(&storage_key_nats_consumer_stream_prefix(
+            tenant,
+            stream_name,
+        ))
+    }
+
+    pub fn delete(
+        &self,
+        tenant: &str,
+        stream_name: &str,
+        consumer_name: &str,
+    ) -> Result<(), CommonError> {
+        let key = storage_key_nats_consumer(tenant, stream_name, consumer_name);
+        engine_delete_by_meta_data(&self.rocksdb_engine_handler, &key)
+    }
+
+    fn list_by_prefix(&self, prefix: &str) -> Result<Vec<NatsConsumer>, CommonError> {
+        let data =
+            engine_prefix_list_by_meta_data::<NatsConsumer>(&self.rocksdb_engine_handler, prefix)?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+}
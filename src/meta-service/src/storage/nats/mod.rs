@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod consumer;
+pub mod stream;
 pub mod subscribe;
 
+pub use consumer::NatsConsumerStorage;
+pub use stream::NatsStreamStorage;
 pub use subscribe::NatsSubscribeStorage;
@@ -0,0 +1,69 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use metadata_struct::nats::stream::NatsStream;
+use rocksdb_engine::keys::meta::{
+    storage_key_nats_stream, storage_key_nats_stream_prefix, storage_key_nats_stream_tenant_prefix,
+};
+use rocksdb_engine::rocksdb::RocksDBEngine;
+use rocksdb_engine::storage::meta_data::{
+    engine_delete_by_meta_data, engine_get_by_meta_data, engine_prefix_list_by_meta_data,
+    engine_save_by_meta_data,
+};
+use std::sync::Arc;
+
+pub struct NatsStreamStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl NatsStreamStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        NatsStreamStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(&self, stream: &NatsStream) -> Result<(), CommonError> {
+        let key = storage_key_nats_stream(&stream.tenant, &stream.name);
+        engine_save_by_meta_data(&self.rocksdb_engine_handler, &key, stream)
+    }
+
+    pub fn get(&self, tenant: &str, stream_name: &str) -> Result<Option<NatsStream>, CommonError> {
+        let key = storage_key_nats_stream(tenant, stream_name);
+        Ok(
+            engine_get_by_meta_data::<NatsStream>(&self.rocksdb_engine_handler, &key)?
+                .map(|data| data.data),
+        )
+    }
+
+    pub fn list(&self) -> Result<Vec<NatsStream>, CommonError> {
+        let prefix = storage_key_nats_stream_prefix();
+        let data =
+            engine_prefix_list_by_meta_data::<NatsStream>(&self.rocksdb_engine_handler, &prefix)?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+
+    pub fn list_by_tenant(&self, tenant: &str) -> Result<Vec<NatsStream>, CommonError> {
+        let prefix = storage_key_nats_stream_tenant_prefix(tenant);
+        let data =
+            engine_prefix_list_by_meta_data::<NatsStream>(&self.rocksdb_engine_handler, &prefix)?;
+        Ok(data.into_iter().map(|raw| raw.data).collect())
+    }
+
+    pub fn delete(&self, tenant: &str, stream_name: &str) -> Result<(), CommonError> {
+        let key = storage_key_nats_stream(tenant, stream_name);
+        engine_delete_by_meta_data(&self.rocksdb_engine_handler, &key)
+    }
+}
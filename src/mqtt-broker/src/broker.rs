@@ -44,6 +44,7 @@
 use rate_limit::mqtt::MQTTRateLimiterManager;
 use rocksdb_engine::metrics::mqtt::MQTTMetricsCache;
 use rocksdb_engine::rocksdb::RocksDBEngine;
+use rule_engine::manager::RuleEngineManager;
 use schema_register::schema::SchemaRegisterManager;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
@@ -63,6 +64,7 @@ pub struct MqttBrokerServerParams {
     pub security_manager: Arc<SecurityManager>,
     pub delay_message_manager: Arc<DelayMessageManager>,
     pub schema_manager: Arc<SchemaRegisterManager>,
+    pub rule_manager: Arc<RuleEngineManager>,
     pub metrics_cache_manager: Arc<MQTTMetricsCache>,
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
     pub node_cache: Arc<NodeCacheManager>,
@@ -119,6 +121,7 @@ pub async fn new(params: MqttBrokerServerParams, stop: broadcast::Sender<bool>)
                 storage_driver_manager: params.storage_driver_manager.clone(),
                 delay_message_manager: params.delay_message_manager.clone(),
                 schema_manager: params.schema_manager.clone(),
+                rule_manager: params.rule_manager.clone(),
                 client_pool: params.client_pool.clone(),
                 session_batcher: params.session_batcher.clone(),
                 stop_sx: stop.clone(),
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::enhanced_auth::{EnhancedAuthState, ENHANCED_AUTH_TIMEOUT_SECS};
 use crate::core::flapping_detect::FlappingDetectCondition;
 use crate::core::pkid_manager::PkidManager;
 use broker_core::cache::NodeCacheManager;
@@ -125,6 +126,9 @@ pub struct MQTTCacheManager {
 
     // Topic is Validator
     pub topic_is_validator: DashMap<String, bool>,
+
+    // (connect_id, EnhancedAuthState) — MQTT 5 enhanced authentication
+    pub enhanced_auth: Arc<DashMap<u64, EnhancedAuthState>>,
 }
 
 impl MQTTCacheManager {
@@ -144,6 +148,7 @@ pub fn new(client_pool: Arc<ClientPool>, broker_cache: Arc<NodeCacheManager>) ->
             re_calc_topic_rewrite: Arc::new(RwLock::new(false)),
             topic_rewrite_new_name: DashMap::with_capacity(8),
             flapping_detect_map: DashMap::new(),
+            enhanced_auth: Arc::new(DashMap::with_capacity(8)),
         }
     }
 
@@ -209,6 +214,30 @@ pub fn remove_connection(&self, connect_id: u64) {
                 set.remove(&connect_id);
             }
         }
+        self.enhanced_auth.remove(&connect_id);
+    }
+
+    // enhanced auth
+    pub fn has_pending_connect_auth(&self, connect_id: u64) -> bool {
+        self.enhanced_auth
+            .get(&connect_id)
+            .is_some_and(|state| state.pending_connect.is_some())
+    }
+
+    pub fn get_enhanced_auth_user(&self, connect_id: u64) -> Option<String> {
+        self.enhanced_auth
+            .get(&connect_id)
+            .and_then(|state| state.username.clone())
+    }
+
+    /// Drops exchanges that never led to a connection, e.g. a client that
+    /// went away halfway through authenticating.
+    pub fn expire_enhanced_auth(&self) {
+        let now = now_second();
+        self.enhanced_auth.retain(|connect_id, state| {
+            self.connection_info.contains_key(connect_id)
+                || now.saturating_sub(state.start_time) < ENHANCED_AUTH_TIMEOUT_SECS
+        });
     }
 
     pub fn get_connect_id(&self, client_id: &str) -> Option<u64> {
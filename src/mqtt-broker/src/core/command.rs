@@ -40,16 +40,18 @@
 use network_server::common::packet::ResponsePackage;
 use node_call::NodeCallManager;
 use protocol::mqtt::common::{
-    is_mqtt3, is_mqtt4, is_mqtt5, mqtt_packet_to_string, Connect, ConnectProperties,
-    ConnectReturnCode, Disconnect, DisconnectProperties, DisconnectReasonCode, LastWill,
-    LastWillProperties, Login, MqttPacket, MqttProtocol, PingReq, PubAck, PubAckProperties,
-    PubComp, PubCompProperties, PubRec, PubRecProperties, PubRel, PubRelProperties, Publish,
-    PublishProperties, Subscribe, SubscribeProperties, Unsubscribe, UnsubscribeProperties,
+    is_mqtt3, is_mqtt4, is_mqtt5, mqtt_packet_to_string, Auth, AuthProperties, Connect,
+    ConnectProperties, ConnectReturnCode, Disconnect, DisconnectProperties, DisconnectReasonCode,
+    LastWill, LastWillProperties, Login, MqttPacket, MqttProtocol, PingReq, PubAck,
+    PubAckProperties, PubComp, PubCompProperties, PubRec, PubRecProperties, PubRel,
+    PubRelProperties, Publish, PublishProperties, Subscribe, SubscribeProperties, Unsubscribe,
+    UnsubscribeProperties,
 };
 use protocol::robust::RobustMQPacket;
 use rate_limit::global::GlobalRateLimiterManager;
 use rate_limit::mqtt::MQTTRateLimiterManager;
 use rocksdb_engine::rocksdb::RocksDBEngine;
+use rule_engine::manager::RuleEngineManager;
 use schema_register::schema::SchemaRegisterManager;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -80,6 +82,7 @@ pub struct CommandContext {
     pub session_batcher: Arc<SessionBatcher>,
     pub connection_manager: Arc<ConnectionManager>,
     pub schema_manager: Arc<SchemaRegisterManager>,
+    pub rule_manager: Arc<RuleEngineManager>,
     pub security_manager: Arc<SecurityManager>,
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
     pub broker_cache: Arc<NodeCacheManager>,
@@ -104,6 +107,15 @@ async fn apply(
         if let MqttPacket::Connect(_, _, _, _, _, _) = packet {
             is_connect_pkg = true;
         }
+        // AUTH packets finishing a held CONNECT arrive before login, like the CONNECT itself.
+        if let MqttPacket::Auth(_, _) = packet {
+            if self
+                .cache_manager
+                .has_pending_connect_auth(tcp_connection.connection_id)
+            {
+                is_connect_pkg = true;
+            }
+        }
 
         let connection = if let Some(se) = self
             .cache_manager
@@ -221,6 +233,11 @@ async fn apply(
                 .await
             }
 
+            MqttPacket::Auth(auth, auth_properties) if tcp_connection.is_mqtt5() => {
+                self.process_auth(tcp_connection, &auth, &auth_properties)
+                    .await
+            }
+
             _ => {
                 return Some(ResponsePackage::new(
                     tcp_connection.connection_id,
@@ -305,6 +322,7 @@ pub async fn process_connect(
                 last_will_properties: last_will_properties.clone(),
                 login: login.clone(),
                 addr: *addr,
+                enhanced_auth: None,
             };
             Some(self.mqtt3_service.connect(connect_context).await)
         } else if is_mqtt4(protocol_version.to_owned()) {
@@ -316,6 +334,7 @@ pub async fn process_connect(
                 last_will_properties: last_will_properties.clone(),
                 login: login.clone(),
                 addr: *addr,
+                enhanced_auth: None,
             };
             Some(self.mqtt4_service.connect(connect_context).await)
         } else if is_mqtt5(protocol_version.to_owned()) {
@@ -327,6 +346,7 @@ pub async fn process_connect(
                 last_will_properties: last_will_properties.clone(),
                 login: login.clone(),
                 addr: *addr,
+                enhanced_auth: None,
             };
             Some(self.mqtt5_service.connect(connect_context).await)
         } else {
@@ -341,26 +361,48 @@ pub async fn process_connect(
             ));
         };
 
-        let ack_pkg = resp_pkg.unwrap();
+        Some(self.connect_ack_response(tcp_connection.connection_id, resp_pkg.unwrap(), login))
+    }
+
+    pub async fn process_auth(
+        &self,
+        tcp_connection: &NetworkConnection,
+        auth: &Auth,
+        auth_properties: &Option<AuthProperties>,
+    ) -> Option<ResponsePackage> {
+        let pkg = self
+            .mqtt5_service
+            .auth(tcp_connection.connection_id, auth, auth_properties)
+            .await;
+        Some(self.connect_ack_response(tcp_connection.connection_id, pkg, None))
+    }
+
+    // A CONNACK, whether answering CONNECT or the AUTH that finished its
+    // enhanced authentication, logs the connection in; other packets pass through.
+    fn connect_ack_response(
+        &self,
+        connection_id: u64,
+        ack_pkg: MqttPacket,
+        login: Option<Login>,
+    ) -> ResponsePackage {
         if let MqttPacket::ConnAck(conn_ack, _) = ack_pkg.clone() {
             if conn_ack.code == ConnectReturnCode::Success {
-                let username = if let Some(user) = login {
-                    user.username
-                } else {
-                    "anonymous".to_string()
-                };
-                self.cache_manager
-                    .login_success(tcp_connection.connection_id, username);
-                debug!("connect [{}] login success", tcp_connection.connection_id);
+                let username =
+                    if let Some(user) = self.cache_manager.get_enhanced_auth_user(connection_id) {
+                        user
+                    } else if let Some(user) = login {
+                        user.username
+                    } else {
+                        "anonymous".to_string()
+                    };
+                self.cache_manager.login_success(connection_id, username);
+                debug!("connect [{}] login success", connection_id);
                 record_mqtt_connection_success();
             } else {
                 record_mqtt_connection_failed();
             }
         }
-        Some(ResponsePackage::new(
-            tcp_connection.connection_id,
-            RobustMQPacket::MQTT(ack_pkg),
-        ))
+        ResponsePackage::new(connection_id, RobustMQPacket::MQTT(ack_pkg))
     }
 
     pub async fn process_publish(
@@ -677,6 +719,7 @@ pub fn new(context: CommandContext) -> Self {
             delay_message_manager: context.delay_message_manager.clone(),
             subscribe_manager: context.subscribe_manager.clone(),
             schema_manager: context.schema_manager.clone(),
+            rule_manager: context.rule_manager.clone(),
             client_pool: context.client_pool.clone(),
             session_batcher: context.session_batcher.clone(),
             security_manager: context.security_manager.clone(),
@@ -695,6 +738,7 @@ pub fn new(context: CommandContext) -> Self {
             delay_message_manager: context.delay_message_manager.clone(),
             subscribe_manager: context.subscribe_manager.clone(),
             schema_manager: context.schema_manager.clone(),
+            rule_manager: context.rule_manager.clone(),
             client_pool: context.client_pool.clone(),
             session_batcher: context.session_batcher.clone(),
             security_manager: context.security_manager.clone(),
@@ -713,6 +757,7 @@ pub fn new(context: CommandContext) -> Self {
             delay_message_manager: context.delay_message_manager.clone(),
             subscribe_manager: context.subscribe_manager.clone(),
             schema_manager: context.schema_manager.clone(),
+            rule_manager: context.rule_manager.clone(),
             client_pool: context.client_pool.clone(),
             session_batcher: context.session_batcher.clone(),
             security_manager: context.security_manager.clone(),
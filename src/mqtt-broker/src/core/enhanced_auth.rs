@@ -0,0 +1,55 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::mqtt::MqttServiceConnectContext;
+use bytes::Bytes;
+use common_security::login::enhanced::EnhancedAuthSession;
+use protocol::mqtt::common::{Auth, AuthProperties, AuthReason, MqttPacket};
+
+// A CONNECT held for an exchange the client never finishes is dropped after this long.
+pub const ENHANCED_AUTH_TIMEOUT_SECS: u64 = 60;
+
+/// A connection's MQTT 5 enhanced authentication: the method it authenticated
+/// with, and the exchange in progress while it connects or re-authenticates.
+pub struct EnhancedAuthState {
+    pub method: String,
+    pub tenant: String,
+    // Set once an exchange has succeeded.
+    pub username: Option<String>,
+    pub session: Option<Box<dyn EnhancedAuthSession>>,
+    // The CONNECT waiting on the exchange; None once connected.
+    pub pending_connect: Option<Box<MqttServiceConnectContext>>,
+    pub start_time: u64,
+}
+
+/// A finished exchange, carried into the rest of CONNECT processing.
+#[derive(Clone)]
+pub struct EnhancedAuthResult {
+    pub method: String,
+    pub username: String,
+    pub data: Option<Bytes>,
+}
+
+pub fn build_auth_packet(reason: AuthReason, method: &str, data: Option<Bytes>) -> MqttPacket {
+    MqttPacket::Auth(
+        Auth {
+            reason: Some(reason),
+        },
+        Some(AuthProperties {
+            authentication_method: Some(method.to_string()),
+            authentication_data: data,
+            ..Default::default()
+        }),
+    )
+}
@@ -201,6 +201,7 @@ async fn keep_alive(&self) -> ResultCommonError {
                 self.cache_manager.heartbeat_data.remove(&client_id);
             }
         }
+        self.cache_manager.expire_enhanced_auth();
         Ok(())
     }
 
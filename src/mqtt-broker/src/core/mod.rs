@@ -19,6 +19,7 @@
 pub mod content_type;
 pub mod delay_message;
 pub mod dynamic_cache;
+pub mod enhanced_auth;
 pub mod error;
 pub mod event;
 pub mod flapping_detect;
@@ -17,6 +17,7 @@
 use crate::core::tool::ResultMqttBrokerError;
 use crate::subscribe::manager::SubscribeManager;
 use crate::{core::cache::MQTTCacheManager, subscribe::parse::ParseSubscribeData};
+use async_trait::async_trait;
 use common_base::error::common::CommonError;
 use common_config::broker::broker_config;
 use common_config::storage::StorageType;
@@ -28,6 +29,7 @@
 };
 use protocol::broker::broker::{BrokerUpdateCacheActionType, BrokerUpdateCacheResourceType};
 use protocol::mqtt::common::{Publish, PublishProperties};
+use rule_engine::manager::RuleTopicInit;
 use std::sync::Arc;
 use std::time::Duration;
 use storage_adapter::topic::topic_replication_num;
@@ -152,6 +154,31 @@ pub async fn get_topic_alias(
     Err(MqttBrokerError::TopicAliasInvalid(topic_alias))
 }
 
+/// Creates the topics rules republish MQTT messages to, the way an MQTT
+/// publish creates them.
+pub struct MqttRuleTopicInit<'a> {
+    pub cache_manager: &'a Arc<MQTTCacheManager>,
+    pub storage_driver_manager: &'a Arc<StorageDriverManager>,
+    pub client_pool: &'a Arc<ClientPool>,
+}
+
+#[async_trait]
+impl RuleTopicInit for MqttRuleTopicInit<'_> {
+    async fn init_topic(&self, tenant: &str, topic: &str) -> Result<(), CommonError> {
+        try_init_topic(
+            tenant,
+            topic,
+            false,
+            self.cache_manager,
+            self.storage_driver_manager,
+            self.client_pool,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| CommonError::CommonError(e.to_string()))
+    }
+}
+
 pub async fn try_init_topic(
     tenant: &str,
     topic_name: &str,
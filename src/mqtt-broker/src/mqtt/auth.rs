@@ -0,0 +1,294 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::connect::build_connect_ack_fail_packet;
+use super::disconnect::build_distinct_packet;
+use super::{MqttService, MqttServiceConnectContext};
+use crate::core::enhanced_auth::{build_auth_packet, EnhancedAuthResult, EnhancedAuthState};
+use bytes::Bytes;
+use common_base::tools::now_second;
+use common_metrics::mqtt::auth::{record_mqtt_auth_failed, record_mqtt_auth_success};
+use common_security::login::enhanced::EnhancedAuthStep;
+use protocol::mqtt::common::{
+    Auth, AuthProperties, AuthReason, ConnectProperties, ConnectReturnCode, DisconnectReasonCode,
+    MqttPacket, MqttProtocol,
+};
+
+pub(crate) enum EnhancedConnect {
+    /// Send this (an AUTH challenge or a failed CONNACK) and stop processing the CONNECT.
+    Reply(MqttPacket),
+    Authenticated(EnhancedAuthResult),
+}
+
+/// The Authentication-Method a CONNECT asks for. Only MQTT 5 has one.
+pub(crate) fn enhanced_auth_method(
+    protocol: &MqttProtocol,
+    connect_properties: &Option<ConnectProperties>,
+) -> Option<String> {
+    if !protocol.is_mqtt5() {
+        return None;
+    }
+    connect_properties
+        .as_ref()
+        .and_then(|p| p.authentication_method.clone())
+}
+
+impl MqttService {
+    /// Runs the first step of the exchange with the CONNECT's Authentication-Data.
+    /// If the method wants more, the CONNECT is held until the client's AUTH
+    /// packets finish the exchange.
+    pub(crate) async fn begin_connect_auth(
+        &self,
+        context: &MqttServiceConnectContext,
+        method: &str,
+        tenant: &str,
+    ) -> EnhancedConnect {
+        let Some(mut session) = self.security_manager.enhanced_auth.start(method) else {
+            record_mqtt_auth_failed();
+            return EnhancedConnect::Reply(build_connect_ack_fail_packet(
+                &self.protocol,
+                ConnectReturnCode::BadAuthenticationMethod,
+                &context.connect_properties,
+                Some(format!("unsupported authentication method {}", method)),
+            ));
+        };
+
+        let data = context
+            .connect_properties
+            .as_ref()
+            .and_then(|p| p.authentication_data.clone())
+            .unwrap_or_default();
+        match session.step(&self.security_manager, tenant, &data).await {
+            EnhancedAuthStep::Continue(challenge) => {
+                self.cache_manager.enhanced_auth.insert(
+                    context.connect_id,
+                    EnhancedAuthState {
+                        method: method.to_string(),
+                        tenant: tenant.to_string(),
+                        username: None,
+                        session: Some(session),
+                        pending_connect: Some(Box::new(context.clone())),
+                        start_time: now_second(),
+                    },
+                );
+                EnhancedConnect::Reply(build_auth_packet(
+                    AuthReason::ContinueAuthentication,
+                    method,
+                    Some(Bytes::from(challenge)),
+                ))
+            }
+            EnhancedAuthStep::Success { username, data } => {
+                EnhancedConnect::Authenticated(EnhancedAuthResult {
+                    method: method.to_string(),
+                    username,
+                    data: data.map(Bytes::from),
+                })
+            }
+            EnhancedAuthStep::Failed(reason) => {
+                record_mqtt_auth_failed();
+                EnhancedConnect::Reply(build_connect_ack_fail_packet(
+                    &self.protocol,
+                    ConnectReturnCode::NotAuthorized,
+                    &context.connect_properties,
+                    Some(reason),
+                ))
+            }
+        }
+    }
+
+    /// An AUTH packet from the client. It either continues the exchange a
+    /// held CONNECT is waiting on, resuming the CONNECT once it succeeds, or
+    /// re-authenticates an established connection with the method it
+    /// connected with.
+    pub async fn auth(
+        &self,
+        connect_id: u64,
+        auth: &Auth,
+        auth_properties: &Option<AuthProperties>,
+    ) -> MqttPacket {
+        let method = auth_properties
+            .as_ref()
+            .and_then(|p| p.authentication_method.clone());
+        let data = auth_properties
+            .as_ref()
+            .and_then(|p| p.authentication_data.clone())
+            .unwrap_or_default();
+        let reason = auth.reason.unwrap_or(AuthReason::Success);
+
+        // The held CONNECT's properties, if there is one: failures are then
+        // answered with a CONNACK rather than a DISCONNECT. The session is
+        // taken out of the state while it steps, so no map entry stays
+        // locked across the await.
+        let (mut session, tenant, pending) = {
+            let Some(mut state) = self.cache_manager.enhanced_auth.get_mut(&connect_id) else {
+                return self.auth_failed(
+                    connect_id,
+                    None,
+                    DisconnectReasonCode::ProtocolError,
+                    "connection did not use enhanced authentication".to_string(),
+                );
+            };
+            let pending = state
+                .pending_connect
+                .as_ref()
+                .map(|connect| connect.connect_properties.clone());
+
+            if method.as_deref() != Some(state.method.as_str()) {
+                let reason = format!("authentication method must remain {}", state.method);
+                drop(state);
+                return self.auth_failed(
+                    connect_id,
+                    pending,
+                    DisconnectReasonCode::BadAuthenticationMethod,
+                    reason,
+                );
+            }
+            match reason {
+                AuthReason::ReAuthenticate if pending.is_none() => {
+                    state.session = self.security_manager.enhanced_auth.start(&state.method);
+                }
+                AuthReason::ContinueAuthentication if state.session.is_some() => {}
+                _ => {
+                    drop(state);
+                    return self.auth_failed(
+                        connect_id,
+                        pending,
+                        DisconnectReasonCode::ProtocolError,
+                        format!("unexpected AUTH reason {:?}", reason),
+                    );
+                }
+            }
+
+            let tenant = state.tenant.clone();
+            let Some(session) = state.session.take() else {
+                let reason = format!("authentication method {} is not supported", state.method);
+                drop(state);
+                return self.auth_failed(
+                    connect_id,
+                    pending,
+                    DisconnectReasonCode::BadAuthenticationMethod,
+                    reason,
+                );
+            };
+            (session, tenant, pending)
+        };
+        let step = session.step(&self.security_manager, &tenant, &data).await;
+
+        let method = method.unwrap_or_default();
+        match step {
+            EnhancedAuthStep::Continue(challenge) => {
+                if let Some(mut state) = self.cache_manager.enhanced_auth.get_mut(&connect_id) {
+                    state.session = Some(session);
+                }
+                build_auth_packet(
+                    AuthReason::ContinueAuthentication,
+                    &method,
+                    Some(Bytes::from(challenge)),
+                )
+            }
+            EnhancedAuthStep::Success { username, data } => {
+                self.auth_succeeded(connect_id, method, username, data.map(Bytes::from))
+                    .await
+            }
+            EnhancedAuthStep::Failed(reason) => {
+                record_mqtt_auth_failed();
+                self.auth_failed(
+                    connect_id,
+                    pending,
+                    DisconnectReasonCode::NotAuthorized,
+                    reason,
+                )
+            }
+        }
+    }
+
+    async fn auth_succeeded(
+        &self,
+        connect_id: u64,
+        method: String,
+        username: String,
+        data: Option<Bytes>,
+    ) -> MqttPacket {
+        let Some(mut state) = self.cache_manager.enhanced_auth.get_mut(&connect_id) else {
+            return self.auth_failed(
+                connect_id,
+                None,
+                DisconnectReasonCode::ProtocolError,
+                "enhanced authentication state is gone".to_string(),
+            );
+        };
+        state.session = None;
+
+        if let Some(mut connect) = state.pending_connect.take() {
+            drop(state);
+            connect.enhanced_auth = Some(EnhancedAuthResult {
+                method,
+                username,
+                data,
+            });
+            return self.connect(*connect).await;
+        }
+
+        // ACLs were applied for the logged-in user, so re-authentication may
+        // not switch to another one.
+        if state.username.as_deref() != Some(username.as_str()) {
+            drop(state);
+            record_mqtt_auth_failed();
+            return self.auth_failed(
+                connect_id,
+                None,
+                DisconnectReasonCode::NotAuthorized,
+                "re-authentication must not change the user".to_string(),
+            );
+        }
+        drop(state);
+        record_mqtt_auth_success();
+        build_auth_packet(AuthReason::Success, &method, data)
+    }
+
+    fn auth_failed(
+        &self,
+        connect_id: u64,
+        pending_connect: Option<Option<ConnectProperties>>,
+        code: DisconnectReasonCode,
+        reason: String,
+    ) -> MqttPacket {
+        match pending_connect {
+            Some(connect_properties) => {
+                self.cache_manager.enhanced_auth.remove(&connect_id);
+                let code = match code {
+                    DisconnectReasonCode::BadAuthenticationMethod => {
+                        ConnectReturnCode::BadAuthenticationMethod
+                    }
+                    DisconnectReasonCode::ProtocolError => ConnectReturnCode::ProtocolError,
+                    _ => ConnectReturnCode::NotAuthorized,
+                };
+                build_connect_ack_fail_packet(
+                    &self.protocol,
+                    code,
+                    &connect_properties,
+                    Some(reason),
+                )
+            }
+            None => build_distinct_packet(
+                &self.cache_manager,
+                connect_id,
+                &self.protocol,
+                Some(code),
+                None,
+                Some(reason),
+            ),
+        }
+    }
+}
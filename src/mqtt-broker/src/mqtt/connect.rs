@@ -12,17 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::auth::{enhanced_auth_method, EnhancedConnect};
 use super::{MqttService, MqttServiceConnectContext};
 use crate::core::cache::{ConnectionLiveTime, MQTTCacheManager};
 use crate::core::connection::response_information;
 use crate::core::connection::{build_connection, get_client_id};
 use crate::core::content_type::payload_format_indicator_check_by_lastwill;
+use crate::core::enhanced_auth::{EnhancedAuthResult, EnhancedAuthState};
 use crate::core::error::MqttBrokerError;
 use crate::core::event::st_report_connected_event;
 use crate::core::flapping_detect::check_flapping_detect;
 use crate::core::last_will::save_last_will_message;
 use crate::core::limit::connection_total_num_limit;
-use crate::core::security::{security_check_connect, ConnectAuthResult};
+use crate::core::security::{security_check_connect, security_is_allow_connect, ConnectAuthResult};
 use crate::core::session::{session_process, BuildSessionContext};
 use crate::core::string_validator::{validate_client_id, validate_password, validate_username};
 use crate::core::sub_auto::try_auto_subscribe;
@@ -31,6 +33,7 @@
 use common_base::tools::now_second;
 use common_config::config::BrokerConfig;
 use common_metrics::mqtt::auth::{record_mqtt_auth_failed, record_mqtt_auth_success};
+use common_security::login::x509::{peer_cert_client_id, peer_cert_username};
 use protocol::mqtt::common::{
     ConnAck, ConnAckProperties, Connect, ConnectProperties, ConnectReturnCode, LastWill,
     LastWillProperties, Login, MqttPacket, MqttProtocol,
@@ -41,9 +44,18 @@
 use tracing::warn;
 
 impl MqttService {
-    pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
+    pub async fn connect(&self, mut context: MqttServiceConnectContext) -> MqttPacket {
         let cluster = self.cache_manager.node_cache.get_cluster_config();
 
+        // a verified client certificate may stand in for the client id and the login
+        let peer_cert = self
+            .connection_manager
+            .get_connect(context.connect_id)
+            .and_then(|connection| connection.peer_cert);
+        if let Some(client_id) = peer_cert_client_id(peer_cert.as_ref()) {
+            context.connect.client_id = client_id;
+        }
+
         if let Some(res) = connect_validator(
             &self.protocol,
             &cluster,
@@ -56,6 +68,14 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
             return res;
         }
 
+        let cert_username = peer_cert_username(peer_cert.as_ref());
+        if let Some(username) = &cert_username {
+            context.login = Some(Login {
+                username: username.clone(),
+                password: String::new(),
+            });
+        }
+
         // client id
         let (data, resp) = get_client_id(
             &self.protocol,
@@ -121,8 +141,8 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
         )
         .await;
 
-        // flapping detect check
-        if cluster.mqtt_runtime.flapping_detect.enable {
+        // flapping detect check; a CONNECT resumed after enhanced auth was already counted
+        if cluster.mqtt_runtime.flapping_detect.enable && context.enhanced_auth.is_none() {
             if let Err(e) = check_flapping_detect(
                 &tenant.tenant_name,
                 context.connect.client_id.clone(),
@@ -140,18 +160,54 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
                 );
             }
         }
+        // enhanced auth: hold the CONNECT until the exchange finishes
+        let auth_method = enhanced_auth_method(&self.protocol, &context.connect_properties);
+        if let (Some(method), None) = (&auth_method, &context.enhanced_auth) {
+            match self
+                .begin_connect_auth(&context, method, &tenant.tenant_name)
+                .await
+            {
+                EnhancedConnect::Reply(pkt) => return pkt,
+                EnhancedConnect::Authenticated(result) => context.enhanced_auth = Some(result),
+            }
+        }
+        if let Some(result) = &context.enhanced_auth {
+            context.login = Some(Login {
+                username: result.username.clone(),
+                password: String::new(),
+            });
+        }
+
         // auth check (blacklist + login)
-        match security_check_connect(
-            &self.security_manager,
-            &self.cache_manager.node_cache,
-            &tenant.tenant_name,
-            &connection.client_id,
-            &connection.source_ip,
-            &context.login,
-            &context.connect_properties,
-        )
-        .await
-        {
+        let auth_result = if context.enhanced_auth.is_some() || cert_username.is_some() {
+            security_is_allow_connect(
+                &self.security_manager,
+                &tenant.tenant_name,
+                &connection.client_id,
+                &connection.source_ip,
+                &context.login,
+            )
+            .await
+            .map(|allowed| {
+                if allowed {
+                    ConnectAuthResult::Allowed
+                } else {
+                    ConnectAuthResult::Banned
+                }
+            })
+        } else {
+            security_check_connect(
+                &self.security_manager,
+                &self.cache_manager.node_cache,
+                &tenant.tenant_name,
+                &connection.client_id,
+                &connection.source_ip,
+                &context.login,
+                &context.connect_properties,
+            )
+            .await
+        };
+        match auth_result {
             Ok(ConnectAuthResult::Allowed) => {
                 record_mqtt_auth_success();
             }
@@ -256,6 +312,20 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
         self.cache_manager.add_session(&client_id, &session);
         self.cache_manager
             .add_connection(context.connect_id, connection.clone());
+        if let Some(result) = &context.enhanced_auth {
+            // Kept so the client can re-authenticate with the same method.
+            self.cache_manager.enhanced_auth.insert(
+                context.connect_id,
+                EnhancedAuthState {
+                    method: result.method.clone(),
+                    tenant: tenant.tenant_name.clone(),
+                    username: Some(result.username.clone()),
+                    session: None,
+                    pending_connect: None,
+                    start_time: now_second(),
+                },
+            );
+        }
         st_report_connected_event(
             &self.event_manager,
             &self.connection_manager,
@@ -274,6 +344,7 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
             session_present: !new_session,
             keep_alive: connection.keep_alive,
             connect_properties: context.connect_properties.clone(),
+            enhanced_auth: context.enhanced_auth.clone(),
         })
     }
 }
@@ -319,6 +390,7 @@ struct ResponsePacketMqttConnectSuccessContext {
     pub session_present: bool,
     pub keep_alive: u16,
     pub connect_properties: Option<ConnectProperties>,
+    pub enhanced_auth: Option<EnhancedAuthResult>,
 }
 
 fn build_connect_ack_success_packet(
@@ -356,8 +428,14 @@ fn build_connect_ack_success_packet(
         server_keep_alive: Some(context.keep_alive),
         response_information: response_information(&context.connect_properties),
         server_reference: None,
-        authentication_method: None,
-        authentication_data: None,
+        authentication_method: context
+            .enhanced_auth
+            .as_ref()
+            .map(|result| result.method.clone()),
+        authentication_data: context
+            .enhanced_auth
+            .as_ref()
+            .and_then(|result| result.data.clone()),
     };
     MqttPacket::ConnAck(
         ConnAck {
@@ -514,6 +592,15 @@ fn connect_validator(
             }
         }
 
+        if properties.authentication_data.is_some() && properties.authentication_method.is_none() {
+            return Some(build_connect_ack_fail_packet(
+                protocol,
+                ConnectReturnCode::ProtocolError,
+                connect_properties,
+                Some("authentication_data requires authentication_method".to_string()),
+            ));
+        }
+
         if let Some(request_problem_info) = properties.request_problem_info {
             if request_problem_info > 1 {
                 return Some(build_connect_ack_fail_packet(
@@ -779,6 +866,28 @@ fn test_request_problem_info_invalid() {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn test_authentication_data_without_method() {
+        let protocol = MqttProtocol::Mqtt5;
+        let cluster = common_config::broker::default_broker_config();
+        let connect = build_test_connect("test_client");
+        let properties = Some(ConnectProperties {
+            authentication_data: Some(Bytes::from_static(b"n,,n=user,r=nonce")),
+            ..Default::default()
+        });
+
+        let result = connect_validator(
+            &protocol,
+            &cluster,
+            &connect,
+            &properties,
+            &None,
+            &None,
+            &None,
+        );
+        assert!(result.is_some());
+    }
+
     #[test]
     fn test_will_topic_empty() {
         let protocol = MqttProtocol::Mqtt5;
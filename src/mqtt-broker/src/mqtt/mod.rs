@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod auth;
 pub mod connect;
 pub mod disconnect;
 mod ping;
@@ -28,6 +29,7 @@
 };
 use rate_limit::mqtt::MQTTRateLimiterManager;
 use rocksdb_engine::rocksdb::RocksDBEngine;
+use rule_engine::manager::RuleEngineManager;
 use schema_register::schema::SchemaRegisterManager;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -35,6 +37,7 @@
 use tokio::sync::broadcast;
 
 use crate::core::cache::MQTTCacheManager;
+use crate::core::enhanced_auth::EnhancedAuthResult;
 use crate::core::event::EventReportManager;
 
 use crate::storage::session::SessionBatcher;
@@ -51,6 +54,7 @@ pub struct MqttService {
     delay_message_manager: Arc<DelayMessageManager>,
     subscribe_manager: Arc<SubscribeManager>,
     schema_manager: Arc<SchemaRegisterManager>,
+    rule_manager: Arc<RuleEngineManager>,
     client_pool: Arc<ClientPool>,
     session_batcher: Arc<SessionBatcher>,
     security_manager: Arc<SecurityManager>,
@@ -69,6 +73,7 @@ pub struct MqttServiceContext {
     pub delay_message_manager: Arc<DelayMessageManager>,
     pub subscribe_manager: Arc<SubscribeManager>,
     pub schema_manager: Arc<SchemaRegisterManager>,
+    pub rule_manager: Arc<RuleEngineManager>,
     pub client_pool: Arc<ClientPool>,
     pub session_batcher: Arc<SessionBatcher>,
     pub security_manager: Arc<SecurityManager>,
@@ -88,6 +93,8 @@ pub struct MqttServiceConnectContext {
     pub last_will_properties: Option<LastWillProperties>,
     pub login: Option<Login>,
     pub addr: SocketAddr,
+    // Set when CONNECT is resumed after a finished enhanced authentication exchange.
+    pub enhanced_auth: Option<EnhancedAuthResult>,
 }
 
 impl MqttService {
@@ -103,6 +110,7 @@ pub fn new(context: MqttServiceContext) -> Self {
             session_batcher: context.session_batcher,
             security_manager: context.security_manager,
             schema_manager: context.schema_manager,
+            rule_manager: context.rule_manager,
             rocksdb_engine_handler: context.rocksdb_engine_handler,
             limit_manager: context.limit_manager,
             event_manager: context.event_manager,
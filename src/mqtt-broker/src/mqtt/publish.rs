@@ -24,8 +24,8 @@
 use crate::core::pkid_manager::{PkidAckEnum, ReceiveQosPkidData};
 use crate::core::qos::{get_temporary_qos2_message, persistent_save_qos2_message};
 use crate::core::security::security_is_allow_publish;
-use crate::core::topic::{get_topic_name, try_init_topic};
-use common_base::tools::now_second;
+use crate::core::topic::{get_topic_name, try_init_topic, MqttRuleTopicInit};
+use common_base::tools::{now_millis, now_second};
 use common_metrics::mqtt::publish::record_mqtt_messages_delayed_inc;
 use metadata_struct::mqtt::connection::MQTTConnection;
 use protocol::mqtt::common::{
@@ -33,6 +33,7 @@
     PubCompReason, PubRec, PubRecProperties, PubRecReason, PubRel, PubRelProperties, Publish,
     PublishProperties, QoS,
 };
+use rule_engine::sql::RuleMessage;
 use std::cmp::min;
 use std::sync::Arc;
 use tracing::debug;
@@ -220,6 +221,30 @@ async fn process_publish0(
         })
         .await?;
 
+        if self
+            .rule_manager
+            .is_match_topic(&connection.tenant, &topic_name)
+        {
+            let message = RuleMessage {
+                tenant: connection.tenant.clone(),
+                protocol: "mqtt".to_string(),
+                topic: topic_name.clone(),
+                client_id,
+                username: connection.login_user.clone(),
+                qos: publish.qos as u8,
+                payload: publish.payload.clone(),
+                timestamp: now_millis() as u64,
+            };
+            let topic_init = MqttRuleTopicInit {
+                cache_manager: &self.cache_manager,
+                storage_driver_manager: &self.storage_driver_manager,
+                client_pool: &self.client_pool,
+            };
+            self.rule_manager
+                .process(&self.storage_driver_manager, &topic_init, &message)
+                .await;
+        }
+
         Ok((format!("{:?}", offset), topic_name))
     }
 
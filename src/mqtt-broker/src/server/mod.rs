@@ -39,6 +39,7 @@
 use rate_limit::global::GlobalRateLimiterManager;
 use rate_limit::mqtt::MQTTRateLimiterManager;
 use rocksdb_engine::rocksdb::RocksDBEngine;
+use rule_engine::manager::RuleEngineManager;
 use schema_register::schema::SchemaRegisterManager;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
@@ -60,6 +61,7 @@ pub struct TcpServerContext {
     pub storage_driver_manager: Arc<StorageDriverManager>,
     pub delay_message_manager: Arc<DelayMessageManager>,
     pub schema_manager: Arc<SchemaRegisterManager>,
+    pub rule_manager: Arc<RuleEngineManager>,
     pub client_pool: Arc<ClientPool>,
     pub session_batcher: Arc<SessionBatcher>,
     pub stop_sx: broadcast::Sender<bool>,
@@ -88,6 +90,7 @@ pub fn new(
             session_batcher: context.session_batcher.clone(),
             connection_manager: context.connection_manager.clone(),
             schema_manager: context.schema_manager.clone(),
+            rule_manager: context.rule_manager.clone(),
             security_manager: context.security_manager.clone(),
             rocksdb_engine_handler: context.rocksdb_engine_handler.clone(),
             broker_cache: context.broker_cache.clone(),
@@ -18,6 +18,7 @@
 pub mod local;
 pub mod message;
 pub mod retain;
+pub mod rule;
 pub mod schema;
 pub mod session;
 pub mod topic_rewrite;
@@ -0,0 +1,90 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::{common::CommonError, ResultCommonError};
+use common_config::broker::broker_config;
+use grpc_clients::{
+    meta::common::call::{create_rule, delete_rule, list_rule, update_rule},
+    pool::ClientPool,
+};
+use metadata_struct::rule::Rule;
+use protocol::meta::meta_service_common::{
+    CreateRuleRequest, DeleteRuleRequest, ListRuleRequest, UpdateRuleRequest,
+};
+use std::sync::Arc;
+
+pub struct RuleStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl RuleStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        RuleStorage { client_pool }
+    }
+
+    pub async fn list(
+        &self,
+        tenant: Option<String>,
+        rule_name: Option<String>,
+    ) -> Result<Vec<Rule>, CommonError> {
+        let config = broker_config();
+        let request = ListRuleRequest {
+            tenant: tenant.unwrap_or_default(),
+            rule_name: rule_name.unwrap_or_default(),
+        };
+
+        let mut stream =
+            list_rule(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        let mut results = Vec::new();
+        while let Some(reply) = stream.message().await? {
+            results.push(Rule::decode(&reply.rule)?);
+        }
+        Ok(results)
+    }
+
+    pub async fn create(&self, rule: &Rule) -> ResultCommonError {
+        let config = broker_config();
+        let request = CreateRuleRequest {
+            tenant: rule.tenant.clone(),
+            rule_name: rule.name.clone(),
+            rule: rule.encode()?,
+        };
+
+        create_rule(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+
+    pub async fn update(&self, rule: &Rule) -> ResultCommonError {
+        let config = broker_config();
+        let request = UpdateRuleRequest {
+            tenant: rule.tenant.clone(),
+            rule_name: rule.name.clone(),
+            rule: rule.encode()?,
+        };
+
+        update_rule(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, tenant: &str, rule_name: &str) -> ResultCommonError {
+        let config = broker_config();
+        let request = DeleteRuleRequest {
+            tenant: tenant.to_string(),
+            rule_name: rule_name.to_string(),
+        };
+
+        delete_rule(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+}
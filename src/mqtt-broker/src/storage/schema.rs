@@ -16,15 +16,23 @@
 use common_config::broker::broker_config;
 use grpc_clients::{
     meta::common::call::{
-        bind_schema, create_schema, delete_schema, list_bind_schema, list_schema, un_bind_schema,
+        bind_schema, create_schema, delete_resource_config, delete_schema, delete_schema_version,
+        get_resource_config, list_bind_schema, list_schema, list_schema_version,
+        register_schema_version, set_resource_config, un_bind_schema,
     },
     pool::ClientPool,
 };
-use metadata_struct::schema::{SchemaData, SchemaResourceBind};
+use metadata_struct::schema::{
+    schema_compatibility_resource, SchemaCompatibility, SchemaData, SchemaResourceBind,
+    SchemaVersion,
+};
 use protocol::meta::meta_service_common::{
-    BindSchemaRequest, CreateSchemaRequest, DeleteSchemaRequest, ListBindSchemaRequest,
-    ListSchemaRequest, UnBindSchemaRequest,
+    BindSchemaRequest, CreateSchemaRequest, DeleteResourceConfigRequest, DeleteSchemaRequest,
+    DeleteSchemaVersionRequest, GetResourceConfigRequest, ListBindSchemaRequest, ListSchemaRequest,
+    ListSchemaVersionRequest, RegisterSchemaVersionRequest, SetResourceConfigRequest,
+    UnBindSchemaRequest,
 };
+use std::str::FromStr;
 use std::sync::Arc;
 
 pub struct SchemaStorage {
@@ -81,17 +89,15 @@ pub async fn delete(&self, tenant: String, schema_name: String) -> ResultCommonE
         Ok(())
     }
 
-    pub async fn create_bind(
-        &self,
-        tenant: &str,
-        schema_name: &str,
-        resource_name: &str,
-    ) -> ResultCommonError {
+    pub async fn create_bind(&self, bind: &SchemaResourceBind) -> ResultCommonError {
         let config = broker_config();
         let request = BindSchemaRequest {
-            tenant: tenant.to_string(),
-            schema_name: schema_name.to_string(),
-            resource_name: resource_name.to_string(),
+            tenant: bind.tenant.clone(),
+            schema_name: bind.schema_name.clone(),
+            resource_name: bind.resource_name.clone(),
+            message_name: bind.message_name.clone(),
+            strict_unknown_fields: bind.strict_unknown_fields,
+            strict_required_fields: bind.strict_required_fields,
         };
 
         bind_schema(&self.client_pool, &config.get_meta_service_addr(), request).await?;
@@ -134,4 +140,113 @@ pub async fn list_bind(
         }
         Ok(results)
     }
+
+    pub async fn list_versions(
+        &self,
+        request: ListSchemaVersionRequest,
+    ) -> Result<Vec<SchemaVersion>, CommonError> {
+        let config = broker_config();
+        let mut stream =
+            list_schema_version(&self.client_pool, &config.get_meta_service_addr(), request)
+                .await?;
+        let mut results = Vec::new();
+        while let Some(reply) = stream.message().await? {
+            results.push(SchemaVersion::decode(&reply.schema_version)?);
+        }
+        Ok(results)
+    }
+
+    pub async fn register_version(
+        &self,
+        tenant: &str,
+        subject: &str,
+        schema_type: &str,
+        schema: &str,
+    ) -> Result<SchemaVersion, CommonError> {
+        let config = broker_config();
+        let request = RegisterSchemaVersionRequest {
+            tenant: tenant.to_string(),
+            subject: subject.to_string(),
+            schema_type: schema_type.to_string(),
+            schema: schema.to_string(),
+        };
+
+        let reply =
+            register_schema_version(&self.client_pool, &config.get_meta_service_addr(), request)
+                .await?;
+        SchemaVersion::decode(&reply.schema_version)
+    }
+
+    /// Deletes one version of a subject, or every version when `version` is 0.
+    pub async fn delete_version(
+        &self,
+        tenant: &str,
+        subject: &str,
+        version: u32,
+    ) -> Result<Vec<u32>, CommonError> {
+        let config = broker_config();
+        let request = DeleteSchemaVersionRequest {
+            tenant: tenant.to_string(),
+            subject: subject.to_string(),
+            version,
+        };
+
+        let reply =
+            delete_schema_version(&self.client_pool, &config.get_meta_service_addr(), request)
+                .await?;
+        Ok(reply.versions)
+    }
+
+    /// The compatibility level set on the subject, or on the tenant when
+    /// `subject` is None. Returns None if no level was set.
+    pub async fn get_compatibility(
+        &self,
+        tenant: &str,
+        subject: Option<&str>,
+    ) -> Result<Option<SchemaCompatibility>, CommonError> {
+        let config = broker_config();
+        let request = GetResourceConfigRequest {
+            resources: schema_compatibility_resource(tenant, subject),
+        };
+
+        let reply =
+            get_resource_config(&self.client_pool, &config.get_meta_service_addr(), request)
+                .await?;
+        if reply.config.is_empty() {
+            return Ok(None);
+        }
+        let level =
+            String::from_utf8(reply.config).map_err(|e| CommonError::CommonError(e.to_string()))?;
+        Ok(Some(SchemaCompatibility::from_str(&level)?))
+    }
+
+    pub async fn set_compatibility(
+        &self,
+        tenant: &str,
+        subject: Option<&str>,
+        level: SchemaCompatibility,
+    ) -> ResultCommonError {
+        let config = broker_config();
+        let request = SetResourceConfigRequest {
+            resources: schema_compatibility_resource(tenant, subject),
+            config: level.to_string().into_bytes(),
+        };
+
+        set_resource_config(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_compatibility(
+        &self,
+        tenant: &str,
+        subject: Option<&str>,
+    ) -> ResultCommonError {
+        let config = broker_config();
+        let request = DeleteResourceConfigRequest {
+            resources: schema_compatibility_resource(tenant, subject),
+        };
+
+        delete_resource_config(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
 }
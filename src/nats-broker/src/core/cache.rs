@@ -13,6 +13,10 @@
 // limitations under the License.
 
 use crate::core::connection::NatsConnection;
+use crate::jstream::delivery::ConsumerRuntime;
+use crate::jstream::object::ObjectUpload;
+use crate::jstream::store::StreamCounters;
+use crate::push::parse::nats_subject_match;
 use broker_core::cache::NodeCacheManager;
 use dashmap::DashMap;
 use grpc_clients::pool::ClientPool;
@@ -20,7 +24,11 @@
 use metadata_struct::mq9::forward_rule::Mq9ForwardRule;
 use metadata_struct::mq9::mail::MQ9Mail;
 use metadata_struct::mq9::Priority;
+use metadata_struct::nats::consumer::NatsConsumer;
+use metadata_struct::nats::stream::NatsStream;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 pub struct NatsCacheManager {
     pub node_cache: Arc<NodeCacheManager>,
@@ -37,6 +45,20 @@ pub struct NatsCacheManager {
     /// read-mostly: lookups happen inline on the send hot path, mutations
     /// only via admin API / metadata-change notifications.
     pub forward_rules: DashMap<String, Vec<Mq9ForwardRule>>,
+    /// Key: "{tenant}/{stream_name}"
+    pub stream_info: DashMap<String, NatsStream>,
+    /// Message counters of a stream. Holding the lock also serializes writes
+    /// to the stream on this broker, so that publish expectations are checked
+    /// against the sequence the write lands on. Same key as `stream_info`.
+    pub stream_state: DashMap<String, Arc<Mutex<StreamCounters>>>,
+    /// Key: "{tenant}/{stream_name}/{consumer_name}"
+    pub consumer_info: DashMap<String, NatsConsumer>,
+    /// Delivery state of consumers served by this broker, same key as
+    /// `consumer_info`.
+    pub consumer_runtime: DashMap<String, Arc<ConsumerRuntime>>,
+    /// Object store uploads waiting for chunks.
+    /// Key: "{tenant}/{bucket}/{nonce}"
+    pub object_uploads: DashMap<String, ObjectUpload>,
 }
 
 impl NatsCacheManager {
@@ -49,6 +71,11 @@ pub fn new(client_pool: Arc<ClientPool>, node_cache: Arc<NodeCacheManager>) -> S
             inbox_data: DashMap::new(),
             agent_info: DashMap::new(),
             forward_rules: DashMap::new(),
+            stream_info: DashMap::new(),
+            stream_state: DashMap::new(),
+            consumer_info: DashMap::new(),
+            consumer_runtime: DashMap::new(),
+            object_uploads: DashMap::new(),
         }
     }
 
@@ -65,7 +92,15 @@ pub fn remove_inbox_by_sid(&self, sid: &str) {
     }
 
     pub fn get_inbox_sid(&self, inbox: &str) -> Option<String> {
-        self.inbox_data.get(inbox).map(|e| e.value().clone())
+        if let Some(sid) = self.inbox_data.get(inbox) {
+            return Some(sid.value().clone());
+        }
+        // Clients usually subscribe once to a wildcard inbox (`_INBOX.<id>.*`)
+        // and send every request with a concrete reply subject under it.
+        self.inbox_data
+            .iter()
+            .find(|e| nats_subject_match(e.key(), inbox))
+            .map(|e| e.value().clone())
     }
 
     pub fn add_mail(&self, mail: MQ9Mail) {
@@ -143,6 +178,166 @@ pub fn match_forward_rules(
         }
     }
 
+    // ---------- JetStream streams ----------
+
+    pub fn add_stream(&self, stream: NatsStream) {
+        let key = format!("{}/{}", stream.tenant, stream.name);
+        self.stream_info.insert(key, stream);
+    }
+
+    pub fn get_stream(&self, tenant: &str, name: &str) -> Option<NatsStream> {
+        let key = format!("{}/{}", tenant, name);
+        self.stream_info.get(&key).map(|e| e.value().clone())
+    }
+
+    pub fn remove_stream(&self, tenant: &str, name: &str) {
+        let key = format!("{}/{}", tenant, name);
+        self.stream_info.remove(&key);
+        self.stream_state.remove(&key);
+    }
+
+    pub fn get_stream_state(&self, tenant: &str, name: &str) -> Arc<Mutex<StreamCounters>> {
+        let key = format!("{}/{}", tenant, name);
+        self.stream_state.entry(key).or_default().clone()
+    }
+
+    /// All streams of `tenant`, sorted by name so paged listings are stable.
+    pub fn list_streams(&self, tenant: &str) -> Vec<NatsStream> {
+        let mut streams: Vec<NatsStream> = self
+            .stream_info
+            .iter()
+            .filter(|e| e.tenant == tenant)
+            .map(|e| e.value().clone())
+            .collect();
+        streams.sort_by(|a, b| a.name.cmp(&b.name));
+        streams
+    }
+
+    /// Streams of `tenant` that capture messages published on `subject`.
+    pub fn match_streams(&self, tenant: &str, subject: &str) -> Vec<NatsStream> {
+        self.stream_info
+            .iter()
+            .filter(|e| {
+                e.tenant == tenant && e.subjects.iter().any(|p| nats_subject_match(p, subject))
+            })
+            .map(|e| e.value().clone())
+            .collect()
+    }
+
+    // ---------- JetStream consumers ----------
+
+    pub fn add_consumer(&self, consumer: NatsConsumer) {
+        let key = format!(
+            "{}/{}/{}",
+            consumer.tenant, consumer.stream_name, consumer.name
+        );
+        self.consumer_info.insert(key, consumer);
+    }
+
+    pub fn get_consumer(
+        &self,
+        tenant: &str,
+        stream_name: &str,
+        name: &str,
+    ) -> Option<NatsConsumer> {
+        let key = format!("{}/{}/{}", tenant, stream_name, name);
+        self.consumer_info.get(&key).map(|e| e.value().clone())
+    }
+
+    /// Removes the consumer and stops its delivery.
+    pub fn remove_consumer(&self, tenant: &str, stream_name: &str, name: &str) {
+        let key = format!("{}/{}/{}", tenant, stream_name, name);
+        self.consumer_info.remove(&key);
+        if let Some((_, runtime)) = self.consumer_runtime.remove(&key) {
+            runtime.stopped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Consumers of a stream, sorted by name so paged listings are stable.
+    pub fn list_consumers(&self, tenant: &str, stream_name: &str) -> Vec<NatsConsumer> {
+        let mut consumers: Vec<NatsConsumer> = self
+            .consumer_info
+            .iter()
+            .filter(|e| e.tenant == tenant && e.stream_name == stream_name)
+            .map(|e| e.value().clone())
+            .collect();
+        consumers.sort_by(|a, b| a.name.cmp(&b.name));
+        consumers
+    }
+
+    pub fn count_consumers(&self, tenant: &str) -> usize {
+        self.consumer_info
+            .iter()
+            .filter(|e| e.tenant == tenant)
+            .count()
+    }
+
+    pub fn get_consumer_runtime(
+        &self,
+        tenant: &str,
+        stream_name: &str,
+        name: &str,
+    ) -> Option<Arc<ConsumerRuntime>> {
+        let key = format!("{}/{}/{}", tenant, stream_name, name);
+        self.consumer_runtime.get(&key).map(|e| e.value().clone())
+    }
+
+    /// Registers `runtime` unless another one won the race, and returns the
+    /// runtime in use.
+    pub fn add_consumer_runtime(
+        &self,
+        consumer: &NatsConsumer,
+        runtime: Arc<ConsumerRuntime>,
+    ) -> Arc<ConsumerRuntime> {
+        let key = format!(
+            "{}/{}/{}",
+            consumer.tenant, consumer.stream_name, consumer.name
+        );
+        self.consumer_runtime.entry(key).or_insert(runtime).clone()
+    }
+
+    /// Ephemeral consumers live as long as the connection that created them.
+    pub fn remove_ephemeral_consumers_by_connection(&self, connect_id: u64) -> Vec<NatsConsumer> {
+        let owned: Vec<NatsConsumer> = self
+            .consumer_info
+            .iter()
+            .filter(|e| !e.durable)
+            .filter(|e| {
+                self.get_consumer_runtime(&e.tenant, &e.stream_name, &e.name)
+                    .is_some_and(|r| r.owner_connect_id == connect_id)
+            })
+            .map(|e| e.value().clone())
+            .collect();
+        for consumer in owned.iter() {
+            self.remove_consumer(&consumer.tenant, &consumer.stream_name, &consumer.name);
+        }
+        owned
+    }
+
+    // ---------- Object store uploads ----------
+
+    pub fn add_object_upload(&self, tenant: &str, bucket: &str, upload: ObjectUpload) {
+        let key = format!("{}/{}/{}", tenant, bucket, upload.meta.nonce);
+        self.object_uploads.insert(key, upload);
+    }
+
+    /// Removes and returns the upload, so one chunk at a time works on it.
+    pub fn take_object_upload(
+        &self,
+        tenant: &str,
+        bucket: &str,
+        nonce: &str,
+    ) -> Option<ObjectUpload> {
+        let key = format!("{}/{}/{}", tenant, bucket, nonce);
+        self.object_uploads.remove(&key).map(|(_, upload)| upload)
+    }
+
+    /// Unfinished uploads are abandoned when their connection goes away.
+    pub fn remove_object_uploads_by_connection(&self, connect_id: u64) {
+        self.object_uploads
+            .retain(|_, upload| upload.connect_id != connect_id);
+    }
+
     pub fn add_connection(&self, connection: NatsConnection) {
         self.connection_info
             .insert(connection.connect_id, connection);
@@ -278,6 +473,69 @@ fn match_is_tenant_scoped() {
             .is_none());
     }
 
+    #[test]
+    fn match_streams_by_subject() {
+        let c = cache();
+        c.add_stream(NatsStream {
+            tenant: "t1".to_string(),
+            name: "ORDERS".to_string(),
+            subjects: vec!["orders.>".to_string()],
+            ..Default::default()
+        });
+        c.add_stream(NatsStream {
+            tenant: "t1".to_string(),
+            name: "EVENTS".to_string(),
+            subjects: vec!["events.*".to_string()],
+            ..Default::default()
+        });
+
+        let hits = c.match_streams("t1", "orders.eu.new");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].name, "ORDERS");
+        assert!(c.match_streams("t1", "events.a.b").is_empty());
+        assert!(c.match_streams("t2", "orders.eu").is_empty());
+
+        let names: Vec<String> = c.list_streams("t1").into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["EVENTS".to_string(), "ORDERS".to_string()]);
+
+        c.remove_stream("t1", "ORDERS");
+        assert!(c.get_stream("t1", "ORDERS").is_none());
+    }
+
+    #[test]
+    fn ephemeral_consumers_removed_with_connection() {
+        let c = cache();
+        for (name, durable) in [("durable", true), ("ephemeral", false)] {
+            let consumer = NatsConsumer {
+                tenant: "t1".to_string(),
+                stream_name: "ORDERS".to_string(),
+                name: name.to_string(),
+                durable,
+                ..Default::default()
+            };
+            c.add_consumer(consumer.clone());
+            c.add_consumer_runtime(
+                &consumer,
+                Arc::new(ConsumerRuntime::new(Default::default(), 7)),
+            );
+        }
+        assert_eq!(c.list_consumers("t1", "ORDERS").len(), 2);
+
+        let runtime = c.get_consumer_runtime("t1", "ORDERS", "ephemeral").unwrap();
+        let removed = c.remove_ephemeral_consumers_by_connection(7);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].name, "ephemeral");
+        assert!(runtime.stopped.load(Ordering::SeqCst));
+
+        let names: Vec<String> = c
+            .list_consumers("t1", "ORDERS")
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["durable".to_string()]);
+        assert!(c.get_consumer_runtime("t1", "ORDERS", "durable").is_some());
+    }
+
     #[test]
     fn multiple_matching_rules_all_returned() {
         let c = cache();
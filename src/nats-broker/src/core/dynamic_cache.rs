@@ -19,6 +19,8 @@
 use common_base::utils::serialize;
 use metadata_struct::mq9::agent::MQ9Agent;
 use metadata_struct::mq9::mail::MQ9Mail;
+use metadata_struct::nats::consumer::NatsConsumer;
+use metadata_struct::nats::stream::NatsStream;
 use metadata_struct::nats::subscribe::NatsSubscribe;
 use protocol::broker::broker::{
     BrokerUpdateCacheActionType, BrokerUpdateCacheResourceType, UpdateCacheRecord,
@@ -82,6 +84,34 @@ pub async fn update_nats_cache_metadata(
             }
         }
 
+        BrokerUpdateCacheResourceType::NatsStream => {
+            let stream: NatsStream = serialize::deserialize(&record.data)?;
+            match record.action_type() {
+                BrokerUpdateCacheActionType::Create | BrokerUpdateCacheActionType::Update => {
+                    cache_manager.add_stream(stream);
+                }
+                BrokerUpdateCacheActionType::Delete => {
+                    cache_manager.remove_stream(&stream.tenant, &stream.name);
+                }
+            }
+        }
+
+        BrokerUpdateCacheResourceType::NatsConsumer => {
+            let consumer: NatsConsumer = serialize::deserialize(&record.data)?;
+            match record.action_type() {
+                BrokerUpdateCacheActionType::Create | BrokerUpdateCacheActionType::Update => {
+                    cache_manager.add_consumer(consumer);
+                }
+                BrokerUpdateCacheActionType::Delete => {
+                    cache_manager.remove_consumer(
+                        &consumer.tenant,
+                        &consumer.stream_name,
+                        &consumer.name,
+                    );
+                }
+            }
+        }
+
         _ => {}
     }
     Ok(())
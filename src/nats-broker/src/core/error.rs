@@ -32,6 +32,15 @@ pub enum NatsBrokerError {
 
     #[error("{0}")]
     FromSerdeJsonError(#[from] serde_json::Error),
+
+    /// JetStream API error, returned to the client in the `error` field of
+    /// the `$JS.API` JSON reply.
+    #[error("{description}")]
+    JetStreamApi {
+        code: u16,
+        err_code: u32,
+        description: String,
+    },
 }
 
 impl From<CommonError> for NatsBrokerError {
@@ -70,6 +79,7 @@ pub enum NatsProtocolError {
     InvalidSubject,
     PermissionsViolationForSubscription(String),
     PermissionsViolationForPublish(String),
+    SchemaValidationFailed(String),
 }
 
 impl NatsProtocolError {
@@ -123,6 +133,9 @@ pub fn message(&self) -> String {
             NatsProtocolError::PermissionsViolationForPublish(subject) => {
                 format!("Permissions Violation for Publish to {}", subject)
             }
+            NatsProtocolError::SchemaValidationFailed(subject) => {
+                format!("Schema Validation Failed for Publish to {}", subject)
+            }
         }
     }
 }
@@ -107,6 +107,12 @@ async fn close_and_cleanup(&self, connect_id: u64) {
         // remove connection cache
         self.cache_manager.remove_connection(connect_id);
 
+        // ephemeral JetStream consumers go away with their connection
+        self.cache_manager
+            .remove_ephemeral_consumers_by_connection(connect_id);
+        self.cache_manager
+            .remove_object_uploads_by_connection(connect_id);
+
         // remove fanout subscribe
         self.subscribe_manager
             .remove_fanout_by_connection(connect_id);
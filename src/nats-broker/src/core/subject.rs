@@ -20,7 +20,10 @@
     manager::NatsSubscribeManager,
     parse::{ParseAction, ParseSubscribeData},
 };
+use async_trait::async_trait;
+use common_base::error::common::CommonError;
 use grpc_clients::pool::ClientPool;
+use rule_engine::manager::RuleTopicInit;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
 pub const INBOX_PREFIX: &str = "_INBOX.";
@@ -29,6 +32,33 @@ pub fn is_inbox_subject(subject: &str) -> bool {
     subject.starts_with(INBOX_PREFIX)
 }
 
+/// Creates the subjects rules republish NATS messages to, the way a NATS
+/// publish creates them.
+pub struct NatsRuleSubjectInit<'a> {
+    pub cache_manager: &'a Arc<NatsCacheManager>,
+    pub storage_driver_manager: &'a Arc<StorageDriverManager>,
+    pub client_pool: &'a Arc<ClientPool>,
+    pub subscribe_manager: &'a Arc<NatsSubscribeManager>,
+}
+
+#[async_trait]
+impl RuleTopicInit for NatsRuleSubjectInit<'_> {
+    async fn init_topic(&self, tenant: &str, topic: &str) -> Result<(), CommonError> {
+        try_get_or_init_subject(
+            self.cache_manager,
+            self.storage_driver_manager,
+            self.client_pool,
+            self.subscribe_manager,
+            tenant,
+            topic,
+            false,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| CommonError::CommonError(e.to_string()))
+    }
+}
+
 pub async fn try_get_or_init_subject(
     cache_manager: &Arc<NatsCacheManager>,
     storage_driver_manager: &Arc<StorageDriverManager>,
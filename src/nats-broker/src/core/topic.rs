@@ -23,6 +23,10 @@
 //     e.g. subject "orders.created"  →  topic name "orders.created"
 //   - MQ9:  topic name = mail ID
 //     e.g. mail address "user-inbox-42"   →  topic name "user-inbox-42"
+//
+// JetStream streams are the exception: each stream owns a dedicated topic
+// ("$sys.inner.nats.stream.<name>") so that its offsets can serve directly as
+// stream sequence numbers (seq = offset + 1).
 
 use crate::{
     core::{cache::NatsCacheManager, error::NatsBrokerError},
@@ -35,8 +39,9 @@
 use common_config::storage::StorageType;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::{
+    nats::stream::{NatsStream, NatsStreamStorage},
     tenant::DEFAULT_TENANT,
-    topic::{Topic, TopicSource},
+    topic::{Topic, TopicConfig, TopicSource},
 };
 use std::{
     collections::HashMap,
@@ -52,6 +57,8 @@
 pub type NatSubject = Topic;
 const INNER_NATS_CORE_SHARD_NAME: &str = "$sys.inner.nats.core.shard.";
 const INNER_MQ9_SHARD_NAME: &str = "$sys.inner.mq9.shard.";
+/// Backing topic retention for streams without `max_age`.
+const NATS_STREAM_UNLIMITED_RETENTION_SEC: u64 = 100 * 365 * 86400;
 
 pub async fn create_topic_by_nats_and_mq9(
     subscribe_manager: Arc<NatsSubscribeManager>,
@@ -135,6 +142,45 @@ pub async fn try_get_or_init_nats_core_subject(
     Ok(topic)
 }
 
+pub async fn try_get_or_init_stream_topic(
+    cache_manager: &Arc<NatsCacheManager>,
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    client_pool: &Arc<ClientPool>,
+    stream: &NatsStream,
+) -> Result<Topic, NatsBrokerError> {
+    let topic_name = stream.topic_name();
+    if let Some(topic) = cache_manager
+        .node_cache
+        .get_topic_by_name(&stream.tenant, &topic_name)
+    {
+        return Ok(topic);
+    }
+
+    let storage_type = match stream.storage {
+        NatsStreamStorage::File => StorageType::EngineRocksDB,
+        NatsStreamStorage::Memory => StorageType::EngineMemory,
+    };
+    let topic = Topic::new(&stream.tenant, &topic_name, storage_type)
+        .with_source(TopicSource::NATS)
+        .with_replication(topic_replication_num(stream.num_replicas))
+        .with_config(TopicConfig {
+            retention_sec: stream
+                .max_age_sec()
+                .unwrap_or(NATS_STREAM_UNLIMITED_RETENTION_SEC),
+            ..Default::default()
+        });
+
+    create_topic_full(
+        &cache_manager.node_cache,
+        storage_driver_manager,
+        client_pool,
+        &topic,
+    )
+    .await?;
+
+    Ok(topic)
+}
+
 // Returns the shared inner shard name list for a given subject/mail topic.
 //
 // NATS subjects and MQ9 mails do NOT get their own dedicated storage shard.
@@ -25,6 +25,8 @@
 use network_server::common::packet::ResponsePackage;
 use protocol::nats::packet::NatsPacket;
 use protocol::robust::RobustMQPacket;
+use rule_engine::manager::RuleEngineManager;
+use schema_register::schema::SchemaRegisterManager;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use storage_adapter::driver::StorageDriverManager;
@@ -39,6 +41,8 @@ pub struct NatsProcessContext {
     pub client_pool: Arc<ClientPool>,
     pub security_manager: Arc<SecurityManager>,
     pub delay_message_manager: Arc<DelayMessageManager>,
+    pub schema_manager: Arc<SchemaRegisterManager>,
+    pub rule_manager: Arc<RuleEngineManager>,
 }
 
 #[derive(Clone)]
@@ -50,9 +54,12 @@ pub struct NatsHandlerCommand {
     pub client_pool: Arc<ClientPool>,
     pub security_manager: Arc<SecurityManager>,
     pub delay_message_manager: Arc<DelayMessageManager>,
+    pub schema_manager: Arc<SchemaRegisterManager>,
+    pub rule_manager: Arc<RuleEngineManager>,
 }
 
 impl NatsHandlerCommand {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         connection_manager: Arc<ConnectionManager>,
         cache_manager: Arc<NatsCacheManager>,
@@ -61,6 +68,8 @@ pub fn new(
         client_pool: Arc<ClientPool>,
         security_manager: Arc<SecurityManager>,
         delay_message_manager: Arc<DelayMessageManager>,
+        schema_manager: Arc<SchemaRegisterManager>,
+        rule_manager: Arc<RuleEngineManager>,
     ) -> Self {
         NatsHandlerCommand {
             connection_manager,
@@ -70,6 +79,8 @@ pub fn new(
             client_pool,
             security_manager,
             delay_message_manager,
+            schema_manager,
+            rule_manager,
         }
     }
 }
@@ -98,6 +109,8 @@ async fn apply(
             client_pool: self.client_pool.clone(),
             security_manager: self.security_manager.clone(),
             delay_message_manager: self.delay_message_manager.clone(),
+            schema_manager: self.schema_manager.clone(),
+            rule_manager: self.rule_manager.clone(),
         };
 
         // Helper: convert Result<(), NatsPacket> into Option<NatsPacket> with verbose.
@@ -186,6 +199,7 @@ async fn apply(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_command(
     connection_manager: Arc<ConnectionManager>,
     cache_manager: Arc<NatsCacheManager>,
@@ -194,6 +208,8 @@ pub fn create_command(
     client_pool: Arc<ClientPool>,
     security_manager: Arc<SecurityManager>,
     delay_message_manager: Arc<DelayMessageManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
+    rule_manager: Arc<RuleEngineManager>,
 ) -> Arc<Box<dyn Command + Send + Sync>> {
     Arc::new(Box::new(NatsHandlerCommand::new(
         connection_manager,
@@ -203,5 +219,39 @@ pub fn create_command(
         client_pool,
         security_manager,
         delay_message_manager,
+        schema_manager,
+        rule_manager,
     )))
 }
+
+/// Context of a connection on a broker backed by in-memory test storage.
+#[cfg(test)]
+pub async fn test_process_context() -> NatsProcessContext {
+    let storage_driver_manager = storage_adapter::storage::test_build_storage_driver_manager()
+        .await
+        .unwrap();
+    let client_pool = Arc::new(ClientPool::new(8));
+    let delay_message_manager = DelayMessageManager::new(
+        client_pool.clone(),
+        storage_driver_manager.clone(),
+        1,
+        common_config::config::DelayMessageConfig::default(),
+    )
+    .await
+    .unwrap();
+    NatsProcessContext {
+        connect_id: 1,
+        connection_manager: Arc::new(ConnectionManager::new()),
+        cache_manager: Arc::new(NatsCacheManager::new(
+            client_pool.clone(),
+            storage_driver_manager.broker_cache.clone(),
+        )),
+        subscribe_manager: Arc::new(NatsSubscribeManager::new()),
+        storage_driver_manager,
+        client_pool,
+        security_manager: Arc::new(SecurityManager::new()),
+        delay_message_manager: Arc::new(delay_message_manager),
+        schema_manager: Arc::new(SchemaRegisterManager::new()),
+        rule_manager: Arc::new(RuleEngineManager::new()),
+    }
+}
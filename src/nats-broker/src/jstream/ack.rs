@@ -14,51 +14,194 @@
 
 use crate::core::error::NatsBrokerError;
 use crate::handler::command::NatsProcessContext;
-use crate::jstream::protocol::{AckNextRequest, NakRequest};
+use crate::jstream::consumer::load_consumer;
+use crate::jstream::delivery::{ack_message, ensure_runtime, start_pull_request, AckAction};
+use crate::jstream::error::js_bad_request;
+use crate::jstream::protocol::{AckNextRequest, ConsumerMsgNextRequest, NakRequest};
+
+const JS_ACK_SUBJECT_PREFIX: &str = "$JS.ACK.";
+
+/// Metadata encoded in the reply subject of a delivered message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AckReply {
+    pub stream: String,
+    pub consumer: String,
+    pub delivered: u64,
+    pub stream_seq: u64,
+    pub consumer_seq: u64,
+}
+
+impl AckReply {
+    /// Parses both the v1 (`$JS.ACK.<stream>.<consumer>.<delivered>...`) and
+    /// the v2 (`$JS.ACK.<domain>.<account_hash>.<stream>...`) forms.
+    pub fn parse(subject: &str) -> Option<Self> {
+        let tokens: Vec<&str> = subject
+            .strip_prefix(JS_ACK_SUBJECT_PREFIX)?
+            .split('.')
+            .collect();
+        let tokens = match tokens.len() {
+            7 => &tokens[..],
+            n if n >= 9 => &tokens[2..],
+            _ => return None,
+        };
+        Some(AckReply {
+            stream: tokens[0].to_string(),
+            consumer: tokens[1].to_string(),
+            delivered: tokens[2].parse().ok()?,
+            stream_seq: tokens[3].parse().ok()?,
+            consumer_seq: tokens[4].parse().ok()?,
+        })
+    }
+}
+
+/// Reply subject attached to a delivered message (v1 format).
+pub fn ack_reply_subject(
+    stream: &str,
+    consumer: &str,
+    delivered: u64,
+    stream_seq: u64,
+    consumer_seq: u64,
+    timestamp_ns: u64,
+    pending: u64,
+) -> String {
+    format!(
+        "{}{}.{}.{}.{}.{}.{}.{}",
+        JS_ACK_SUBJECT_PREFIX,
+        stream,
+        consumer,
+        delivered,
+        stream_seq,
+        consumer_seq,
+        timestamp_ns,
+        pending
+    )
+}
+
+async fn apply(
+    ctx: &NatsProcessContext,
+    subject: &str,
+    stream: &str,
+    consumer: &str,
+    action: AckAction,
+) -> Result<(), NatsBrokerError> {
+    let reply = AckReply::parse(subject)
+        .ok_or_else(|| js_bad_request(format!("invalid ack subject: {}", subject)))?;
+    let (stream_info, consumer_info) = load_consumer(ctx, stream, consumer)?;
+    let runtime = ensure_runtime(ctx, &stream_info, &consumer_info).await?;
+    ack_message(
+        ctx,
+        &stream_info,
+        &consumer_info,
+        &runtime,
+        reply.stream_seq,
+        action,
+    )
+    .await
+}
 
 /// `$JS.ACK.*` with empty payload or `+ACK` — positive acknowledgement.
 pub async fn process_ack(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
+    ctx: &NatsProcessContext,
+    subject: &str,
+    stream: &str,
+    consumer: &str,
 ) -> Result<(), NatsBrokerError> {
-    todo!("ACK")
+    apply(ctx, subject, stream, consumer, AckAction::Ack).await
 }
 
 /// `$JS.ACK.*` with `-NAK` — negative acknowledgement, optional delay.
 pub async fn process_nak(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _req: NakRequest,
+    ctx: &NatsProcessContext,
+    subject: &str,
+    stream: &str,
+    consumer: &str,
+    req: NakRequest,
 ) -> Result<(), NatsBrokerError> {
-    todo!("NAK")
+    let delay_ms = req.delay.unwrap_or(0) / 1_000_000;
+    apply(ctx, subject, stream, consumer, AckAction::Nak(delay_ms)).await
 }
 
-/// `$JS.ACK.*` with `-WPI` — in-progress signal, resets ack_wait timer.
+/// `$JS.ACK.*` with `+WPI` — in-progress signal, resets ack_wait timer.
 pub async fn process_ack_progress(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
+    ctx: &NatsProcessContext,
+    subject: &str,
+    stream: &str,
+    consumer: &str,
 ) -> Result<(), NatsBrokerError> {
-    todo!("ACK progress (+WPI)")
+    apply(ctx, subject, stream, consumer, AckAction::Progress).await
 }
 
-/// `$JS.ACK.*` with `-TERM` — terminate, no redelivery.
+/// `$JS.ACK.*` with `+TERM` — terminate, no redelivery.
 pub async fn process_ack_term(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
+    ctx: &NatsProcessContext,
+    subject: &str,
+    stream: &str,
+    consumer: &str,
 ) -> Result<(), NatsBrokerError> {
-    todo!("ACK term (+TERM)")
+    apply(ctx, subject, stream, consumer, AckAction::Term).await
 }
 
-/// `$JS.ACK.*` with `-NXT` — ack and request next message (Pull Consumer).
+/// `$JS.ACK.*` with `+NXT` — ack and request the next messages on
+/// `reply_to` (pull consumers only).
 pub async fn process_ack_next(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _req: AckNextRequest,
+    ctx: &NatsProcessContext,
+    subject: &str,
+    reply_to: Option<&str>,
+    stream: &str,
+    consumer: &str,
+    req: AckNextRequest,
 ) -> Result<(), NatsBrokerError> {
-    todo!("ACK next (+NXT)")
+    apply(ctx, subject, stream, consumer, AckAction::Ack).await?;
+    let Some(reply_to) = reply_to else {
+        return Ok(());
+    };
+    let (stream_info, consumer_info) = load_consumer(ctx, stream, consumer)?;
+    if !consumer_info.is_pull() {
+        return Err(js_bad_request("consumer is push based"));
+    }
+    let runtime = ensure_runtime(ctx, &stream_info, &consumer_info).await?;
+    let next = ConsumerMsgNextRequest {
+        batch: req.batch,
+        ..Default::default()
+    };
+    start_pull_request(ctx, stream_info, consumer_info, runtime, reply_to, next);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_reply_roundtrip() {
+        let subject = ack_reply_subject("ORDERS", "worker", 2, 15, 31, 1_700_000_000, 4);
+        assert_eq!(subject, "$JS.ACK.ORDERS.worker.2.15.31.1700000000.4");
+        assert_eq!(
+            AckReply::parse(&subject),
+            Some(AckReply {
+                stream: "ORDERS".to_string(),
+                consumer: "worker".to_string(),
+                delivered: 2,
+                stream_seq: 15,
+                consumer_seq: 31,
+            })
+        );
+    }
+
+    #[test]
+    fn test_ack_reply_v2() {
+        let reply =
+            AckReply::parse("$JS.ACK.hub.ACC.ORDERS.worker.1.7.9.1700000000.0.token").unwrap();
+        assert_eq!(reply.stream, "ORDERS");
+        assert_eq!(reply.consumer, "worker");
+        assert_eq!(reply.stream_seq, 7);
+        assert_eq!(reply.consumer_seq, 9);
+    }
+
+    #[test]
+    fn test_ack_reply_invalid() {
+        assert!(AckReply::parse("$JS.ACK.ORDERS.worker").is_none());
+        assert!(AckReply::parse("$JS.ACK.ORDERS.worker.x.7.9.1.0").is_none());
+        assert!(AckReply::parse("$JS.API.INFO").is_none());
+    }
 }
@@ -143,13 +143,15 @@ pub enum JsCommand {
     KvWatch { bucket: String, pattern: String },
 
     // ── $OBJ.* ───────────────────────────────────────────────────
-    /// `$OBJ.{bucket}` — put (chunked upload)
+    /// `$OBJ.{bucket}.info.{object}` — put (starts a chunked upload)
     ObjPut { bucket: String },
-    /// `$OBJ.{bucket}.{object}` — get object
+    /// `$OBJ.{bucket}.chunks.{nonce}` — one chunk of an upload
+    ObjChunk { bucket: String, nonce: String },
+    /// `$OBJ.{bucket}.get.{object}` — get object
     ObjGet { bucket: String, object: String },
-    /// `$OBJ.{bucket}.{object}` — delete object
+    /// `$OBJ.{bucket}.delete.{object}` — delete object
     ObjDelete { bucket: String, object: String },
-    /// `$OBJ.{bucket}.{object}` — get object info/metadata
+    /// `$OBJ.{bucket}.stat.{object}` — get object info/metadata
     ObjInfo { bucket: String, object: String },
     /// `$OBJ.{bucket}` — list all objects
     ObjList { bucket: String },
@@ -166,6 +168,47 @@ pub fn is_js_subject(subject: &str) -> bool {
             || subject.starts_with(OBJ_PREFIX)
     }
 
+    /// `$JS.API.>` request subjects.
+    pub fn is_js_api_subject(subject: &str) -> bool {
+        subject
+            .strip_prefix(JS_API_PREFIX)
+            .is_some_and(|rest| rest.starts_with('.'))
+    }
+
+    /// `$OBJ.>` object store requests.
+    pub fn is_obj_subject(subject: &str) -> bool {
+        subject
+            .strip_prefix(OBJ_PREFIX)
+            .is_some_and(|rest| rest.starts_with('.'))
+    }
+
+    /// `$JS.ACK.>` reply subjects of delivered messages.
+    pub fn is_js_ack_subject(subject: &str) -> bool {
+        subject
+            .strip_prefix(JS_ACK_PREFIX)
+            .is_some_and(|rest| rest.starts_with('.'))
+    }
+
+    /// Refines a parsed `Ack` by the ack kind carried in the payload
+    /// (`+ACK`, `-NAK`, `+WPI`, `+TERM`, `+NXT`). Other commands are
+    /// returned unchanged.
+    pub fn with_ack_payload(self, payload: &[u8]) -> Self {
+        let JsCommand::Ack { stream, consumer } = self else {
+            return self;
+        };
+        let kind = payload
+            .split(|b| b.is_ascii_whitespace())
+            .next()
+            .unwrap_or_default();
+        match kind {
+            b"-NAK" => JsCommand::Nak { stream, consumer },
+            b"+WPI" => JsCommand::AckProgress { stream, consumer },
+            b"+TERM" => JsCommand::AckTerm { stream, consumer },
+            b"+NXT" => JsCommand::AckNext { stream, consumer },
+            _ => JsCommand::Ack { stream, consumer },
+        }
+    }
+
     pub fn parse(subject: &str) -> Option<Self> {
         if let Some(rest) = subject.strip_prefix(JS_API_PREFIX) {
             return parse_js_api(rest.strip_prefix('.')?);
@@ -376,21 +419,26 @@ fn parse_direct(tail: &str) -> Option<JsCommand> {
     }
 }
 
-/// Parse `$JS.ACK.{stream}.{consumer}.{ack_type}.*`
+/// Parse `$JS.ACK.{stream}.{consumer}.*`. The ack kind is carried in the
+/// payload, see [`JsCommand::with_ack_payload`].
 fn parse_js_ack(rest: &str) -> Option<JsCommand> {
-    // Format: {stream}.{consumer}.{delivery_count}.{stream_seq}.{consumer_seq}.{timestamp}.{num_pending}[.{ack_type}]
-    // For routing purposes we only need stream + consumer + ack kind from the reply subject.
-    // The ack subject encodes the ack type as the last meaningful token by convention from client libraries.
-    // We extract stream and consumer from the first two dot-separated tokens.
-    let mut parts = rest.splitn(3, '.');
-    let stream = parts.next()?.to_string();
-    let consumer = parts.next()?.to_string();
-    let tail = parts.next().unwrap_or("");
-
-    // Ack type is signaled by the client via the payload, not the subject in most cases.
-    // We default to Ack here; the handler will inspect the payload for nak/term/next/progress.
-    let _ = tail;
-    Some(JsCommand::Ack { stream, consumer })
+    // v1: {stream}.{consumer}.{delivered}.{stream_seq}.{consumer_seq}.{timestamp}.{pending}
+    // v2: {domain}.{account_hash}.{stream}.{consumer}.{delivered}.{stream_seq}.{consumer_seq}.{timestamp}.{pending}[.{token}]
+    let tokens: Vec<&str> = rest.split('.').collect();
+    let (stream, consumer) = if tokens.len() >= 9 {
+        (tokens[2], tokens[3])
+    } else if tokens.len() >= 2 {
+        (tokens[0], tokens[1])
+    } else {
+        return None;
+    };
+    if stream.is_empty() || consumer.is_empty() {
+        return None;
+    }
+    Some(JsCommand::Ack {
+        stream: stream.to_string(),
+        consumer: consumer.to_string(),
+    })
 }
 
 fn parse_js_event(rest: &str) -> Option<JsCommand> {
@@ -510,10 +558,31 @@ fn parse_obj(rest: &str) -> Option<JsCommand> {
             bucket: bucket.to_string(),
         });
     }
-    Some(JsCommand::ObjGet {
-        bucket: bucket.to_string(),
-        object: tail.to_string(),
-    })
+    let (op, name) = split_first(tail);
+    if name.is_empty() {
+        return None;
+    }
+    let (bucket, name) = (bucket.to_string(), name.to_string());
+    match op {
+        "info" => Some(JsCommand::ObjPut { bucket }),
+        "chunks" => Some(JsCommand::ObjChunk {
+            bucket,
+            nonce: name,
+        }),
+        "get" => Some(JsCommand::ObjGet {
+            bucket,
+            object: name,
+        }),
+        "delete" => Some(JsCommand::ObjDelete {
+            bucket,
+            object: name,
+        }),
+        "stat" => Some(JsCommand::ObjInfo {
+            bucket,
+            object: name,
+        }),
+        _ => None,
+    }
 }
 
 /// Split `s` at the first `.`, returning `(before, after)`.
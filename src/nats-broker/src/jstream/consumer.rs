@@ -14,104 +14,851 @@
 
 use crate::core::error::NatsBrokerError;
 use crate::handler::command::NatsProcessContext;
+use crate::jstream::delivery::{
+    ensure_runtime, is_paused, pending_count, start_pull_request, write_status, DEFAULT_ACK_WAIT,
+    DEFAULT_MAX_ACK_PENDING, DEFAULT_MAX_WAITING,
+};
+use crate::jstream::error::{
+    js_bad_request, js_consumer_already_exists, js_consumer_create_error,
+    js_consumer_does_not_exist, js_consumer_filter_not_subset, js_consumer_invalid_name,
+    js_consumer_invalid_policy, js_consumer_name_mismatch, js_consumer_not_found,
+    js_consumer_wq_multiple_unfiltered, js_consumer_wq_not_unique,
+    js_consumer_wq_requires_explicit_ack, js_not_supported, js_stream_mismatch,
+};
+use crate::jstream::event::{notify_consumer_action, AdvisoryAction};
+use crate::jstream::info::check_consumer_limit;
 use crate::jstream::protocol::{
-    ConsumerCreateRequest, ConsumerDeleteResponse, ConsumerInfoResponse, ConsumerLeaderResponse,
-    ConsumerListRequest, ConsumerListResponse, ConsumerMsgNextRequest, ConsumerNamesResponse,
-    ConsumerPauseRequest, ConsumerPauseResponse,
+    ConsumerConfig, ConsumerCreateRequest, ConsumerDeleteResponse, ConsumerInfo,
+    ConsumerInfoResponse, ConsumerLeaderResponse, ConsumerListRequest, ConsumerListResponse,
+    ConsumerMsgNextRequest, ConsumerNamesResponse, ConsumerPauseRequest, ConsumerPauseResponse,
+    PageInfo, SequenceInfo,
+};
+use crate::jstream::store::{
+    first_sequence_since, last_message_by_subject, last_sequence, stream_shard_name,
+};
+use crate::jstream::stream::{
+    format_time, is_valid_subject, load_stream, normalize_limit, subjects_overlap,
 };
+use crate::storage::consumer::NatsConsumerStorage;
+use chrono::DateTime;
+use common_base::tools::now_second;
+use metadata_struct::nats::consumer::{NatsAckPolicy, NatsConsumer, NatsDeliverPolicy};
+use metadata_struct::nats::stream::{NatsStream, NatsStreamRetention};
+use std::sync::atomic::Ordering;
+use tracing::warn;
 
-/// `$JS.API.CONSUMER.CREATE.<stream>` — ephemeral consumer
+const CONSUMER_LIST_LIMIT: u64 = 256;
+const CONSUMER_NAMES_LIMIT: u64 = 1024;
+
+/// `$JS.API.CONSUMER.CREATE.<stream>` — ephemeral unless `durable_name` is set
 pub async fn process_consumer_create(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: ConsumerCreateRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    req: ConsumerCreateRequest,
 ) -> Result<ConsumerInfoResponse, NatsBrokerError> {
-    todo!("CONSUMER.CREATE")
+    let (name, durable) = match req.config.durable_name.as_deref() {
+        Some(durable) if !durable.is_empty() => (durable.to_string(), true),
+        _ => match req.config.name.as_deref() {
+            Some(name) if !name.is_empty() => (name.to_string(), false),
+            _ => (uuid::Uuid::new_v4().simple().to_string(), false),
+        },
+    };
+    create_consumer(ctx, stream, &name, durable, req).await
 }
 
-/// `$JS.API.CONSUMER.CREATE.<stream>.<consumer>` — named ephemeral consumer
+/// `$JS.API.CONSUMER.CREATE.<stream>.<consumer>[.<filter>]`
 pub async fn process_consumer_create_named(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _req: ConsumerCreateRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    req: ConsumerCreateRequest,
 ) -> Result<ConsumerInfoResponse, NatsBrokerError> {
-    todo!("CONSUMER.CREATE (named)")
+    // Newer clients append the filter subject to the request subject.
+    let name = consumer.split_once('.').map_or(consumer, |(name, _)| name);
+    let durable = match req.config.durable_name.as_deref() {
+        Some(durable) if !durable.is_empty() => {
+            if durable != name {
+                return Err(js_consumer_name_mismatch());
+            }
+            true
+        }
+        _ => false,
+    };
+    if req
+        .config
+        .name
+        .as_deref()
+        .is_some_and(|n| !n.is_empty() && n != name)
+    {
+        return Err(js_consumer_name_mismatch());
+    }
+    create_consumer(ctx, stream, name, durable, req).await
 }
 
 /// `$JS.API.CONSUMER.DURABLE.CREATE.<stream>.<consumer>`
 pub async fn process_consumer_durable_create(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _req: ConsumerCreateRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    req: ConsumerCreateRequest,
 ) -> Result<ConsumerInfoResponse, NatsBrokerError> {
-    todo!("CONSUMER.DURABLE.CREATE")
+    if req
+        .config
+        .durable_name
+        .as_deref()
+        .is_some_and(|d| !d.is_empty() && d != consumer)
+    {
+        return Err(js_consumer_name_mismatch());
+    }
+    create_consumer(ctx, stream, consumer, true, req).await
 }
 
 /// `$JS.API.CONSUMER.DELETE.<stream>.<consumer>`
 pub async fn process_consumer_delete(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
 ) -> Result<ConsumerDeleteResponse, NatsBrokerError> {
-    todo!("CONSUMER.DELETE")
+    let (stream, consumer) = load_consumer(ctx, stream, consumer)?;
+    delete_consumer(ctx, &stream, &consumer).await?;
+    Ok(ConsumerDeleteResponse {
+        kind: "io.nats.jetstream.api.v1.consumer_delete_response".to_string(),
+        success: true,
+        error: None,
+    })
 }
 
 /// `$JS.API.CONSUMER.INFO.<stream>.<consumer>`
 pub async fn process_consumer_info(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
 ) -> Result<ConsumerInfoResponse, NatsBrokerError> {
-    todo!("CONSUMER.INFO")
+    let (stream, consumer) = load_consumer(ctx, stream, consumer)?;
+    Ok(ConsumerInfoResponse {
+        kind: "io.nats.jetstream.api.v1.consumer_info_response".to_string(),
+        info: consumer_info(ctx, &stream, &consumer).await?,
+        error: None,
+    })
 }
 
 /// `$JS.API.CONSUMER.LIST.<stream>`
 pub async fn process_consumer_list(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: ConsumerListRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    req: ConsumerListRequest,
 ) -> Result<ConsumerListResponse, NatsBrokerError> {
-    todo!("CONSUMER.LIST")
+    let stream = load_stream(ctx, stream)?;
+    let consumers = ctx
+        .cache_manager
+        .list_consumers(&stream.tenant, &stream.name);
+    let total = consumers.len() as u64;
+    let offset = req.offset.unwrap_or(0);
+
+    let mut infos = Vec::new();
+    for consumer in consumers
+        .iter()
+        .skip(offset as usize)
+        .take(CONSUMER_LIST_LIMIT as usize)
+    {
+        infos.push(consumer_info(ctx, &stream, consumer).await?);
+    }
+
+    Ok(ConsumerListResponse {
+        kind: "io.nats.jetstream.api.v1.consumer_list_response".to_string(),
+        page: PageInfo {
+            total,
+            offset,
+            limit: CONSUMER_LIST_LIMIT,
+        },
+        consumers: infos,
+        error: None,
+    })
 }
 
 /// `$JS.API.CONSUMER.NAMES.<stream>`
 pub async fn process_consumer_names(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: ConsumerListRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    req: ConsumerListRequest,
 ) -> Result<ConsumerNamesResponse, NatsBrokerError> {
-    todo!("CONSUMER.NAMES")
+    let stream = load_stream(ctx, stream)?;
+    let consumers = ctx
+        .cache_manager
+        .list_consumers(&stream.tenant, &stream.name);
+    let total = consumers.len() as u64;
+    let offset = req.offset.unwrap_or(0);
+
+    Ok(ConsumerNamesResponse {
+        kind: "io.nats.jetstream.api.v1.consumer_names_response".to_string(),
+        page: PageInfo {
+            total,
+            offset,
+            limit: CONSUMER_NAMES_LIMIT,
+        },
+        consumers: consumers
+            .into_iter()
+            .skip(offset as usize)
+            .take(CONSUMER_NAMES_LIMIT as usize)
+            .map(|c| c.name)
+            .collect(),
+        error: None,
+    })
 }
 
 /// `$JS.API.CONSUMER.MSG.NEXT.<stream>.<consumer>`
 pub async fn process_consumer_msg_next(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _req: ConsumerMsgNextRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    reply_to: Option<&str>,
+    req: ConsumerMsgNextRequest,
 ) -> Result<(), NatsBrokerError> {
     // Messages are pushed directly to the client's reply-to subject,
     // not returned as a single response body.
-    todo!("CONSUMER.MSG.NEXT")
+    let reply_to =
+        reply_to.ok_or_else(|| js_bad_request("pull request requires a reply subject"))?;
+    let (stream, consumer) = load_consumer(ctx, stream, consumer)?;
+    if !consumer.is_pull() {
+        let sid = ctx
+            .cache_manager
+            .get_inbox_sid(reply_to)
+            .unwrap_or_else(|| "0".to_string());
+        return write_status(
+            ctx,
+            ctx.connect_id,
+            reply_to,
+            &sid,
+            409,
+            "Consumer is push based",
+            &[],
+        )
+        .await;
+    }
+    let runtime = ensure_runtime(ctx, &stream, &consumer).await?;
+    start_pull_request(ctx, stream, consumer, runtime, reply_to, req);
+    Ok(())
 }
 
 /// `$JS.API.CONSUMER.LEADER.STEPDOWN.<stream>.<consumer>`
+///
+/// A consumer is served by the broker that holds its delivery state, so
+/// there is no consumer leader to step down.
 pub async fn process_consumer_leader_stepdown(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
 ) -> Result<ConsumerLeaderResponse, NatsBrokerError> {
-    todo!("CONSUMER.LEADER.STEPDOWN")
+    load_consumer(ctx, stream, consumer)?;
+    Err(js_not_supported("consumer leader stepdown"))
 }
 
-/// `$JS.API.CONSUMER.PAUSE.<stream>.<consumer>`
+/// `$JS.API.CONSUMER.PAUSE.<stream>.<consumer>` — an empty or past
+/// `pause_until` resumes the consumer.
 pub async fn process_consumer_pause(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _req: ConsumerPauseRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    req: ConsumerPauseRequest,
 ) -> Result<ConsumerPauseResponse, NatsBrokerError> {
-    todo!("CONSUMER.PAUSE")
+    let (_, mut consumer) = load_consumer(ctx, stream, consumer)?;
+    consumer.pause_until = parse_time(req.pause_until.as_deref(), "pause_until")?;
+    save_consumer(ctx, &consumer).await?;
+
+    let paused = is_paused(&consumer);
+    Ok(ConsumerPauseResponse {
+        kind: "io.nats.jetstream.api.v1.consumer_pause_response".to_string(),
+        paused,
+        pause_until: paused.then(|| format_time(consumer.pause_until)),
+        error: None,
+    })
+}
+
+/// Looks up a consumer of a stream of the current tenant.
+pub fn load_consumer(
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    consumer_name: &str,
+) -> Result<(NatsStream, NatsConsumer), NatsBrokerError> {
+    let stream = load_stream(ctx, stream_name)?;
+    let consumer = ctx
+        .cache_manager
+        .get_consumer(&stream.tenant, &stream.name, consumer_name)
+        .ok_or_else(js_consumer_not_found)?;
+    Ok((stream, consumer))
+}
+
+/// Creates an ephemeral push consumer that first delivers the newest message
+/// of every subject matching `filter` to `deliver_subject` and then follows
+/// new messages. KV and object store watchers are built on it.
+pub async fn create_watch_consumer(
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    filter: String,
+    deliver_subject: &str,
+) -> Result<ConsumerInfoResponse, NatsBrokerError> {
+    let config = ConsumerConfig {
+        durable_name: None,
+        name: None,
+        description: None,
+        deliver_subject: Some(deliver_subject.to_string()),
+        deliver_policy: "last_per_subject".to_string(),
+        ack_policy: "none".to_string(),
+        ack_wait: None,
+        max_deliver: Some(1),
+        replay_policy: "instant".to_string(),
+        filter_subject: Some(filter),
+        filter_subjects: Vec::new(),
+        opt_start_seq: None,
+        opt_start_time: None,
+        max_waiting: None,
+        max_ack_pending: None,
+        flow_control: false,
+        idle_heartbeat: None,
+        backoff: None,
+        headers_only: false,
+        pause_until: None,
+    };
+    let req = ConsumerCreateRequest {
+        stream_name: stream_name.to_string(),
+        config,
+        action: None,
+    };
+    process_consumer_create(ctx, stream_name, req).await
+}
+
+/// Removes a consumer together with its committed ack floor.
+pub async fn delete_consumer(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+) -> Result<(), NatsBrokerError> {
+    if consumer.durable {
+        NatsConsumerStorage::new(ctx.client_pool.clone())
+            .delete(&consumer.tenant, &consumer.stream_name, &consumer.name)
+            .await?;
+        let offsets_removed = match stream_shard_name(&ctx.storage_driver_manager, stream) {
+            Ok(shard_name) => ctx
+                .storage_driver_manager
+                .delete_group_offset(&consumer.tenant, &consumer.group_name(), &[shard_name])
+                .await
+                .map_err(NatsBrokerError::from),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = offsets_removed {
+            warn!(
+                "JetStream consumer delete: failed to remove offsets of {}/{}: {}",
+                consumer.stream_name, consumer.name, e
+            );
+        }
+    }
+    ctx.cache_manager
+        .remove_consumer(&consumer.tenant, &consumer.stream_name, &consumer.name);
+    notify_consumer_action(
+        ctx,
+        &consumer.stream_name,
+        &consumer.name,
+        AdvisoryAction::Delete,
+    )
+    .await;
+    Ok(())
+}
+
+async fn create_consumer(
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    name: &str,
+    durable: bool,
+    req: ConsumerCreateRequest,
+) -> Result<ConsumerInfoResponse, NatsBrokerError> {
+    if !req.stream_name.is_empty() && req.stream_name != stream_name {
+        return Err(js_stream_mismatch());
+    }
+    let stream = load_stream(ctx, stream_name)?;
+    validate_consumer_name(name)?;
+    let mut consumer = build_consumer(&stream, name, durable, &req.config, now_second())?;
+    let existing = ctx
+        .cache_manager
+        .get_consumer(&stream.tenant, &stream.name, name);
+
+    let mut action = AdvisoryAction::Create;
+    match (req.action.as_deref().unwrap_or(""), existing) {
+        ("create", Some(existing)) => {
+            // Creating a consumer again with an identical config is a no-op.
+            let same = NatsConsumer {
+                start_seq: existing.start_seq,
+                create_time: existing.create_time,
+                ..consumer.clone()
+            } == existing;
+            if !same {
+                return Err(js_consumer_already_exists());
+            }
+            return consumer_info_response(ctx, &stream, &existing).await;
+        }
+        ("update", None) => return Err(js_consumer_does_not_exist()),
+        ("" | "create" | "update", Some(existing)) => {
+            check_update(&existing, &consumer)?;
+            consumer.start_seq = existing.start_seq;
+            consumer.create_time = existing.create_time;
+            action = AdvisoryAction::Modify;
+        }
+        ("" | "create", None) => {
+            check_consumer_limit(ctx, &stream.tenant)?;
+            check_work_queue(ctx, &stream, &consumer)?;
+            consumer.start_seq = resolve_start_seq(ctx, &stream, &consumer).await?;
+        }
+        (action, _) => {
+            return Err(js_bad_request(format!(
+                "invalid consumer action {}",
+                action
+            )))
+        }
+    }
+
+    save_consumer(ctx, &consumer).await?;
+    notify_consumer_action(ctx, &stream.name, &consumer.name, action).await;
+    consumer_info_response(ctx, &stream, &consumer).await
+}
+
+/// Durable consumers go to the meta service, ephemeral ones only live in
+/// this broker.
+async fn save_consumer(
+    ctx: &NatsProcessContext,
+    consumer: &NatsConsumer,
+) -> Result<(), NatsBrokerError> {
+    if consumer.durable {
+        NatsConsumerStorage::new(ctx.client_pool.clone())
+            .set(consumer)
+            .await?;
+    }
+    ctx.cache_manager.add_consumer(consumer.clone());
+    Ok(())
+}
+
+async fn consumer_info_response(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+) -> Result<ConsumerInfoResponse, NatsBrokerError> {
+    Ok(ConsumerInfoResponse {
+        kind: "io.nats.jetstream.api.v1.consumer_create_response".to_string(),
+        info: consumer_info(ctx, stream, consumer).await?,
+        error: None,
+    })
+}
+
+pub async fn consumer_info(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+) -> Result<ConsumerInfo, NatsBrokerError> {
+    let runtime = ensure_runtime(ctx, stream, consumer).await?;
+    let (delivered, ack_floor, num_ack_pending, num_redelivered, num_pending) = {
+        let mut state = runtime.state.lock().await;
+        let (floor_stream_seq, floor_consumer_seq) = state.ack_floor();
+        (
+            SequenceInfo {
+                consumer_seq: state.consumer_seq,
+                stream_seq: state.delivered_stream_seq,
+            },
+            SequenceInfo {
+                consumer_seq: floor_consumer_seq,
+                stream_seq: floor_stream_seq,
+            },
+            state.pending.len() as u64,
+            state.num_redelivered,
+            pending_count(&ctx.storage_driver_manager, stream, consumer, &mut state).await?,
+        )
+    };
+
+    let paused = is_paused(consumer);
+    Ok(ConsumerInfo {
+        stream_name: consumer.stream_name.clone(),
+        name: consumer.name.clone(),
+        config: consumer_config(consumer),
+        created: format_time(consumer.create_time),
+        delivered,
+        ack_floor,
+        num_ack_pending,
+        num_redelivered,
+        num_waiting: runtime.num_waiting.load(Ordering::SeqCst),
+        num_pending,
+        cluster: None,
+        paused: paused.then_some(true),
+        pause_remaining: paused
+            .then(|| consumer.pause_until.saturating_sub(now_second()) * 1_000_000_000),
+    })
+}
+
+fn validate_consumer_name(name: &str) -> Result<(), NatsBrokerError> {
+    if name.is_empty() || name.contains(['.', '*', '>', '/', '\\', ' ', '\t']) {
+        return Err(js_consumer_invalid_name());
+    }
+    Ok(())
+}
+
+/// RFC 3339 time to seconds, `0` when absent.
+fn parse_time(value: Option<&str>, field: &str) -> Result<u64, NatsBrokerError> {
+    match value {
+        None | Some("") => Ok(0),
+        Some(value) => DateTime::parse_from_rfc3339(value)
+            .map(|t| t.timestamp().max(0) as u64)
+            .map_err(|e| js_consumer_invalid_policy(format!("invalid {}: {}", field, e))),
+    }
+}
+
+fn build_consumer(
+    stream: &NatsStream,
+    name: &str,
+    durable: bool,
+    config: &ConsumerConfig,
+    create_time: u64,
+) -> Result<NatsConsumer, NatsBrokerError> {
+    let deliver_policy = NatsDeliverPolicy::parse(&config.deliver_policy).ok_or_else(|| {
+        js_consumer_invalid_policy(format!("invalid deliver policy {}", config.deliver_policy))
+    })?;
+    let ack_policy = NatsAckPolicy::parse(&config.ack_policy).ok_or_else(|| {
+        js_consumer_invalid_policy(format!("invalid ack policy {}", config.ack_policy))
+    })?;
+    if !config.replay_policy.is_empty() && config.replay_policy != "instant" {
+        return Err(js_consumer_invalid_policy(format!(
+            "replay policy {} is not supported",
+            config.replay_policy
+        )));
+    }
+
+    let opt_start_seq = config.opt_start_seq.unwrap_or(0);
+    let opt_start_time = parse_time(config.opt_start_time.as_deref(), "opt_start_time")?;
+    match deliver_policy {
+        NatsDeliverPolicy::ByStartSequence if opt_start_seq == 0 => {
+            return Err(js_consumer_invalid_policy(
+                "consumer deliver policy by_start_sequence requires opt_start_seq",
+            ))
+        }
+        NatsDeliverPolicy::ByStartTime if config.opt_start_time.is_none() => {
+            return Err(js_consumer_invalid_policy(
+                "consumer deliver policy by_start_time requires opt_start_time",
+            ))
+        }
+        NatsDeliverPolicy::ByStartSequence | NatsDeliverPolicy::ByStartTime => {}
+        _ if opt_start_seq > 0 || config.opt_start_time.is_some() => {
+            return Err(js_consumer_invalid_policy(
+                "consumer opt_start_seq and opt_start_time require a by_start deliver policy",
+            ))
+        }
+        _ => {}
+    }
+    if opt_start_seq > 0 && config.opt_start_time.is_some() {
+        return Err(js_consumer_invalid_policy(
+            "consumer opt_start_seq and opt_start_time are exclusive",
+        ));
+    }
+
+    let filter_subjects = match config.filter_subject.as_deref() {
+        Some(filter) if !filter.is_empty() => {
+            if !config.filter_subjects.is_empty() {
+                return Err(js_consumer_invalid_policy(
+                    "consumer filter_subject and filter_subjects are exclusive",
+                ));
+            }
+            vec![filter.to_string()]
+        }
+        _ => config.filter_subjects.clone(),
+    };
+    for filter in filter_subjects.iter() {
+        if !is_valid_subject(filter) {
+            return Err(js_consumer_invalid_policy(format!(
+                "invalid filter subject {}",
+                filter
+            )));
+        }
+        if !stream.subjects.iter().any(|s| subjects_overlap(s, filter)) {
+            return Err(js_consumer_filter_not_subset());
+        }
+    }
+
+    let deliver_subject = config.deliver_subject.clone().unwrap_or_default();
+    let is_pull = deliver_subject.is_empty();
+    if is_pull && config.flow_control {
+        return Err(js_consumer_invalid_policy(
+            "consumer flow control requires a push consumer",
+        ));
+    }
+
+    let max_deliver = normalize_limit(config.max_deliver.unwrap_or(-1));
+    let backoff = config.backoff.clone().unwrap_or_default();
+    if max_deliver > 0 && backoff.len() as i64 > max_deliver {
+        return Err(js_consumer_invalid_policy(
+            "max deliver is required to be >= length of backoff values",
+        ));
+    }
+    // nats-server uses the first backoff step as the ack wait.
+    let ack_wait = match backoff.first() {
+        Some(first) => *first,
+        None => config
+            .ack_wait
+            .filter(|w| *w > 0)
+            .unwrap_or(DEFAULT_ACK_WAIT),
+    };
+    let max_ack_pending = if ack_policy == NatsAckPolicy::None {
+        -1
+    } else {
+        config
+            .max_ack_pending
+            .filter(|m| *m != 0)
+            .unwrap_or(DEFAULT_MAX_ACK_PENDING)
+    };
+    let max_waiting = if is_pull {
+        config
+            .max_waiting
+            .filter(|m| *m > 0)
+            .map_or(DEFAULT_MAX_WAITING, |m| m as i64)
+    } else {
+        0
+    };
+
+    Ok(NatsConsumer {
+        tenant: stream.tenant.clone(),
+        stream_name: stream.name.clone(),
+        name: name.to_string(),
+        durable,
+        description: config.description.clone().unwrap_or_default(),
+        deliver_subject,
+        deliver_policy,
+        opt_start_seq,
+        opt_start_time,
+        ack_policy,
+        ack_wait,
+        max_deliver,
+        filter_subjects,
+        max_waiting,
+        max_ack_pending,
+        idle_heartbeat: config.idle_heartbeat.unwrap_or(0),
+        flow_control: config.flow_control,
+        backoff,
+        headers_only: config.headers_only,
+        pause_until: parse_time(config.pause_until.as_deref(), "pause_until")?,
+        start_seq: 0,
+        create_time,
+    })
+}
+
+fn check_update(existing: &NatsConsumer, updated: &NatsConsumer) -> Result<(), NatsBrokerError> {
+    let unchanged = |what: &str, same: bool| {
+        if same {
+            Ok(())
+        } else {
+            Err(js_consumer_create_error(format!(
+                "consumer update can not change {}",
+                what
+            )))
+        }
+    };
+    unchanged("durable", existing.durable == updated.durable)?;
+    unchanged(
+        "deliver policy",
+        existing.deliver_policy == updated.deliver_policy,
+    )?;
+    unchanged(
+        "start position",
+        existing.opt_start_seq == updated.opt_start_seq
+            && existing.opt_start_time == updated.opt_start_time,
+    )?;
+    unchanged("ack policy", existing.ack_policy == updated.ack_policy)?;
+    unchanged("push/pull mode", existing.is_pull() == updated.is_pull())
+}
+
+/// Work-queue streams hand every message to exactly one consumer.
+fn check_work_queue(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+) -> Result<(), NatsBrokerError> {
+    if stream.retention != NatsStreamRetention::WorkQueue {
+        return Ok(());
+    }
+    if consumer.ack_policy != NatsAckPolicy::Explicit {
+        return Err(js_consumer_wq_requires_explicit_ack());
+    }
+    for other in ctx
+        .cache_manager
+        .list_consumers(&stream.tenant, &stream.name)
+    {
+        if other.filter_subjects.is_empty() || consumer.filter_subjects.is_empty() {
+            return Err(js_consumer_wq_multiple_unfiltered());
+        }
+        let overlap = other.filter_subjects.iter().any(|a| {
+            consumer
+                .filter_subjects
+                .iter()
+                .any(|b| subjects_overlap(a, b))
+        });
+        if overlap {
+            return Err(js_consumer_wq_not_unique());
+        }
+    }
+    Ok(())
+}
+
+/// First stream sequence a new consumer delivers, from its deliver policy.
+async fn resolve_start_seq(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+) -> Result<u64, NatsBrokerError> {
+    let last_seq = last_sequence(&ctx.storage_driver_manager, stream).await?;
+    let start_seq = match consumer.deliver_policy {
+        NatsDeliverPolicy::All | NatsDeliverPolicy::LastPerSubject => 1,
+        NatsDeliverPolicy::New => last_seq + 1,
+        NatsDeliverPolicy::ByStartSequence => consumer.opt_start_seq,
+        NatsDeliverPolicy::Last if consumer.filter_subjects.is_empty() => last_seq.max(1),
+        NatsDeliverPolicy::Last => {
+            let mut last = None;
+            for filter in consumer.filter_subjects.iter() {
+                if let Some(message) =
+                    last_message_by_subject(&ctx.storage_driver_manager, stream, filter).await?
+                {
+                    last = last.max(Some(message.seq));
+                }
+            }
+            last.unwrap_or(last_seq + 1)
+        }
+        NatsDeliverPolicy::ByStartTime => {
+            // The first message stored at or after the start time, or the
+            // end of the stream when there is none.
+            first_sequence_since(&ctx.storage_driver_manager, stream, consumer.opt_start_time)
+                .await?
+                .unwrap_or(last_seq + 1)
+        }
+    };
+    Ok(start_seq)
+}
+
+fn consumer_config(consumer: &NatsConsumer) -> ConsumerConfig {
+    let (filter_subject, filter_subjects) = match consumer.filter_subjects.as_slice() {
+        [single] => (Some(single.clone()), Vec::new()),
+        filters => (None, filters.to_vec()),
+    };
+    ConsumerConfig {
+        durable_name: consumer.durable.then(|| consumer.name.clone()),
+        name: Some(consumer.name.clone()),
+        description: (!consumer.description.is_empty()).then(|| consumer.description.clone()),
+        deliver_subject: (!consumer.is_pull()).then(|| consumer.deliver_subject.clone()),
+        deliver_policy: consumer.deliver_policy.as_str().to_string(),
+        ack_policy: consumer.ack_policy.as_str().to_string(),
+        ack_wait: Some(consumer.ack_wait),
+        max_deliver: Some(consumer.max_deliver),
+        replay_policy: "instant".to_string(),
+        filter_subject,
+        filter_subjects,
+        opt_start_seq: (consumer.opt_start_seq > 0).then_some(consumer.opt_start_seq),
+        opt_start_time: (consumer.deliver_policy == NatsDeliverPolicy::ByStartTime)
+            .then(|| format_time(consumer.opt_start_time)),
+        max_waiting: consumer
+            .is_pull()
+            .then_some(consumer.max_waiting.max(0) as u64),
+        max_ack_pending: Some(consumer.max_ack_pending),
+        flow_control: consumer.flow_control,
+        idle_heartbeat: (consumer.idle_heartbeat > 0).then_some(consumer.idle_heartbeat),
+        backoff: (!consumer.backoff.is_empty()).then(|| consumer.backoff.clone()),
+        headers_only: consumer.headers_only,
+        pause_until: (consumer.pause_until > 0).then(|| format_time(consumer.pause_until)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream() -> NatsStream {
+        NatsStream {
+            tenant: "default".to_string(),
+            name: "ORDERS".to_string(),
+            subjects: vec!["orders.>".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn config() -> ConsumerConfig {
+        serde_json::from_str("{}").unwrap()
+    }
+
+    #[test]
+    fn test_build_consumer_defaults() {
+        let consumer = build_consumer(&stream(), "worker", true, &config(), 10).unwrap();
+        assert!(consumer.is_pull());
+        assert_eq!(consumer.deliver_policy, NatsDeliverPolicy::All);
+        assert_eq!(consumer.ack_policy, NatsAckPolicy::Explicit);
+        assert_eq!(consumer.ack_wait, DEFAULT_ACK_WAIT);
+        assert_eq!(consumer.max_deliver, -1);
+        assert_eq!(consumer.max_ack_pending, DEFAULT_MAX_ACK_PENDING);
+        assert_eq!(consumer.max_waiting, DEFAULT_MAX_WAITING);
+
+        let roundtrip =
+            build_consumer(&stream(), "worker", true, &consumer_config(&consumer), 10).unwrap();
+        assert_eq!(roundtrip, consumer);
+    }
+
+    #[test]
+    fn test_build_consumer_validation() {
+        let mut cfg = config();
+        cfg.deliver_policy = "by_start_sequence".to_string();
+        assert!(build_consumer(&stream(), "c", false, &cfg, 0).is_err());
+        cfg.opt_start_seq = Some(5);
+        assert_eq!(
+            build_consumer(&stream(), "c", false, &cfg, 0)
+                .unwrap()
+                .opt_start_seq,
+            5
+        );
+
+        let mut cfg = config();
+        cfg.deliver_policy = "by_start_time".to_string();
+        cfg.opt_start_time = Some("2024-01-01T00:00:00Z".to_string());
+        assert_eq!(
+            build_consumer(&stream(), "c", false, &cfg, 0)
+                .unwrap()
+                .opt_start_time,
+            1_704_067_200
+        );
+
+        let mut cfg = config();
+        cfg.filter_subject = Some("events.>".to_string());
+        assert!(build_consumer(&stream(), "c", false, &cfg, 0).is_err());
+        cfg.filter_subject = Some("orders.eu.*".to_string());
+        assert!(build_consumer(&stream(), "c", false, &cfg, 0).is_ok());
+        cfg.filter_subjects = vec!["orders.us.*".to_string()];
+        assert!(build_consumer(&stream(), "c", false, &cfg, 0).is_err());
+
+        let mut cfg = config();
+        cfg.max_deliver = Some(2);
+        cfg.backoff = Some(vec![1, 2, 3]);
+        assert!(build_consumer(&stream(), "c", false, &cfg, 0).is_err());
+
+        let mut cfg = config();
+        cfg.ack_policy = "none".to_string();
+        assert_eq!(
+            build_consumer(&stream(), "c", false, &cfg, 0)
+                .unwrap()
+                .max_ack_pending,
+            -1
+        );
+
+        assert!(validate_consumer_name("worker-1").is_ok());
+        assert!(validate_consumer_name("a.b").is_err());
+        assert!(validate_consumer_name("").is_err());
+    }
+
+    #[test]
+    fn test_check_update() {
+        let existing = build_consumer(&stream(), "worker", true, &config(), 0).unwrap();
+        let mut updated = existing.clone();
+        updated.max_ack_pending = 10;
+        assert!(check_update(&existing, &updated).is_ok());
+        updated.ack_policy = NatsAckPolicy::All;
+        assert!(check_update(&existing, &updated).is_err());
+        let mut updated = existing.clone();
+        updated.deliver_subject = "deliver.worker".to_string();
+        assert!(check_update(&existing, &updated).is_err());
+    }
 }
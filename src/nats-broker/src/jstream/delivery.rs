@@ -0,0 +1,943 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delivery state of JetStream consumers.
+//!
+//! The consumer definition lives in the meta service, while what a consumer
+//! has delivered and is still waiting acks for is kept in memory on the
+//! broker. Only the ack floor is durable: it is committed as the offset of the
+//! consumer group `NatsConsumer::group_name()` on the stream topic, so after a
+//! restart a durable consumer resumes right after its ack floor and redelivers
+//! whatever was still pending.
+
+use crate::core::error::NatsBrokerError;
+use crate::core::subject::is_inbox_subject;
+use crate::core::write_client::write_nats_packet;
+use crate::handler::command::NatsProcessContext;
+use crate::jstream::ack::ack_reply_subject;
+use crate::jstream::headers::{append_headers, NATS_MSG_SIZE};
+use crate::jstream::protocol::ConsumerMsgNextRequest;
+use crate::jstream::store::{
+    delete_messages, get_message, last_message_by_subject, last_sequence, read_messages,
+    stream_shard_name, StreamMessage,
+};
+use crate::push::parse::nats_subject_match;
+use bytes::Bytes;
+use common_base::tools::{now_millis, now_second};
+use common_config::broker::broker_config;
+use metadata_struct::adapter::adapter_offset::AdapterCommitOffset;
+use metadata_struct::nats::consumer::{NatsAckPolicy, NatsConsumer, NatsDeliverPolicy};
+use metadata_struct::nats::stream::{NatsStream, NatsStreamRetention};
+use protocol::nats::packet::NatsPacket;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use storage_adapter::driver::StorageDriverManager;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+use tracing::warn;
+
+/// nats-server defaults.
+pub const DEFAULT_ACK_WAIT: u64 = 30_000_000_000;
+pub const DEFAULT_MAX_ACK_PENDING: i64 = 1000;
+pub const DEFAULT_MAX_WAITING: i64 = 512;
+
+const FETCH_BATCH_SIZE: u64 = 256;
+const PULL_POLL_INTERVAL_MS: u64 = 50;
+const PUSH_POLL_INTERVAL_MS: u64 = 100;
+
+/// A delivered message that still waits for its ack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingMessage {
+    pub consumer_seq: u64,
+    pub deliveries: u64,
+    /// Redelivered once this passes, in milliseconds.
+    pub deadline_ms: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConsumerState {
+    /// Next stream sequence to deliver for the first time.
+    pub next_seq: u64,
+    /// Last consumer sequence handed out, redeliveries included.
+    pub consumer_seq: u64,
+    /// Stream sequence of the last first-time delivery.
+    pub delivered_stream_seq: u64,
+    /// Keyed by stream sequence.
+    pub pending: BTreeMap<u64, PendingMessage>,
+    pub num_redelivered: u64,
+    /// Last ack floor committed to the group offset.
+    pub committed_floor: u64,
+    /// For `LastPerSubject`, messages up to this sequence are delivered only
+    /// when they are the newest on their subject.
+    pub last_per_subject_until: u64,
+    /// Matching messages from `next_seq` up to `counted_seq` that are yet to
+    /// be delivered, not counting the `LastPerSubject` window.
+    pub num_pending: u64,
+    /// Last stream sequence read into `num_pending`.
+    pub counted_seq: u64,
+    /// Newest message of each subject in the `LastPerSubject` window that is
+    /// yet to be delivered.
+    pub last_per_subject_pending: BTreeSet<u64>,
+}
+
+impl ConsumerState {
+    pub fn new(next_seq: u64) -> Self {
+        ConsumerState {
+            next_seq,
+            delivered_stream_seq: next_seq.saturating_sub(1),
+            committed_floor: next_seq.saturating_sub(1),
+            ..Default::default()
+        }
+    }
+
+    /// `(stream_seq, consumer_seq)` below which every message is acknowledged.
+    pub fn ack_floor(&self) -> (u64, u64) {
+        match self.pending.iter().next() {
+            Some((seq, pending)) => (seq - 1, pending.consumer_seq.saturating_sub(1)),
+            None => (self.next_seq.saturating_sub(1), self.consumer_seq),
+        }
+    }
+
+    pub fn is_acked(&self, seq: u64) -> bool {
+        seq < self.next_seq && !self.pending.contains_key(&seq)
+    }
+
+    /// Applies an ack for `seq` and returns the sequences that are now done
+    /// with (acknowledged or terminated).
+    pub fn apply_ack(
+        &mut self,
+        ack_policy: &NatsAckPolicy,
+        seq: u64,
+        action: &AckAction,
+        ack_wait_ms: u64,
+        now_ms: u64,
+    ) -> Vec<u64> {
+        match action {
+            AckAction::Ack if *ack_policy == NatsAckPolicy::All => {
+                let rest = self.pending.split_off(&(seq + 1));
+                let done = std::mem::replace(&mut self.pending, rest);
+                done.into_keys().collect()
+            }
+            AckAction::Ack | AckAction::Term => self
+                .pending
+                .remove(&seq)
+                .map(|_| vec![seq])
+                .unwrap_or_default(),
+            AckAction::Nak(delay_ms) => {
+                if let Some(pending) = self.pending.get_mut(&seq) {
+                    pending.deadline_ms = now_ms + delay_ms;
+                }
+                Vec::new()
+            }
+            AckAction::Progress => {
+                if let Some(pending) = self.pending.get_mut(&seq) {
+                    pending.deadline_ms = now_ms + ack_wait_ms;
+                }
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AckAction {
+    Ack,
+    /// Redeliver after the given delay in milliseconds.
+    Nak(u64),
+    Progress,
+    Term,
+}
+
+pub struct ConsumerRuntime {
+    pub state: Mutex<ConsumerState>,
+    /// Connection the runtime was created from. Inbox subscriptions are not
+    /// tracked per connection, so push deliveries to an inbox go here.
+    pub owner_connect_id: u64,
+    pub num_waiting: AtomicU64,
+    pub stopped: AtomicBool,
+    push_started: AtomicBool,
+}
+
+impl ConsumerRuntime {
+    pub fn new(state: ConsumerState, owner_connect_id: u64) -> Self {
+        ConsumerRuntime {
+            state: Mutex::new(state),
+            owner_connect_id,
+            num_waiting: AtomicU64::new(0),
+            stopped: AtomicBool::new(false),
+            push_started: AtomicBool::new(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub message: StreamMessage,
+    pub consumer_seq: u64,
+    pub deliveries: u64,
+    pub num_pending: u64,
+}
+
+/// Returns the runtime of `consumer`, loading its ack floor on first use and
+/// starting push delivery for push consumers.
+pub async fn ensure_runtime(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+) -> Result<Arc<ConsumerRuntime>, NatsBrokerError> {
+    let runtime = match ctx.cache_manager.get_consumer_runtime(
+        &consumer.tenant,
+        &consumer.stream_name,
+        &consumer.name,
+    ) {
+        Some(runtime) => runtime,
+        None => {
+            let state = load_state(&ctx.storage_driver_manager, stream, consumer).await?;
+            ctx.cache_manager.add_consumer_runtime(
+                consumer,
+                Arc::new(ConsumerRuntime::new(state, ctx.connect_id)),
+            )
+        }
+    };
+    if !consumer.is_pull() {
+        start_push_delivery(ctx, stream, consumer, &runtime);
+    }
+    Ok(runtime)
+}
+
+async fn load_state(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+) -> Result<ConsumerState, NatsBrokerError> {
+    let mut next_seq = consumer.start_seq.max(1);
+    let last_per_subject_until = if consumer.deliver_policy == NatsDeliverPolicy::LastPerSubject {
+        last_sequence(storage_driver_manager, stream).await?
+    } else {
+        0
+    };
+    if consumer.durable {
+        let offsets = storage_driver_manager
+            .get_offset_by_group(&consumer.tenant, &consumer.group_name())
+            .await?;
+        if let Some(committed) = offsets
+            .iter()
+            .find(|o| o.topic_name == stream.topic_name() && o.partition == 0)
+        {
+            next_seq = next_seq.max(committed.offset + 1);
+        }
+    }
+    Ok(ConsumerState {
+        last_per_subject_until,
+        ..ConsumerState::new(next_seq)
+    })
+}
+
+/// Commits the ack floor of a durable consumer when it moved.
+async fn sync_ack_floor(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+    state: &mut ConsumerState,
+) -> Result<(), NatsBrokerError> {
+    let (floor, _) = state.ack_floor();
+    if !consumer.durable || floor == state.committed_floor {
+        return Ok(());
+    }
+    let offset = AdapterCommitOffset {
+        shard_name: stream_shard_name(storage_driver_manager, stream)?,
+        topic_name: stream.topic_name(),
+        partition: 0,
+        offset: floor,
+    };
+    storage_driver_manager
+        .commit_group_offset(&consumer.tenant, &consumer.group_name(), &[offset])
+        .await?;
+    state.committed_floor = floor;
+    Ok(())
+}
+
+/// Ack deadline of the `deliveries`-th delivery, honoring `backoff`.
+pub fn ack_wait_ms(consumer: &NatsConsumer, deliveries: u64) -> u64 {
+    let wait = if consumer.backoff.is_empty() {
+        consumer.ack_wait
+    } else {
+        let idx = (deliveries.saturating_sub(1) as usize).min(consumer.backoff.len() - 1);
+        consumer.backoff[idx]
+    };
+    wait / 1_000_000
+}
+
+pub fn matches_filter(consumer: &NatsConsumer, subject: &str) -> bool {
+    consumer.filter_subjects.is_empty()
+        || consumer
+            .filter_subjects
+            .iter()
+            .any(|f| nats_subject_match(f, subject))
+}
+
+pub fn is_paused(consumer: &NatsConsumer) -> bool {
+    consumer.pause_until > now_second()
+}
+
+fn has_ack_capacity(consumer: &NatsConsumer, state: &ConsumerState) -> bool {
+    consumer.ack_policy == NatsAckPolicy::None
+        || consumer.max_ack_pending <= 0
+        || (state.pending.len() as i64) < consumer.max_ack_pending
+}
+
+/// Picks up to `max_msgs` messages to hand out, expired pending messages
+/// first, and records them as delivered.
+pub async fn next_deliveries(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+    runtime: &ConsumerRuntime,
+    max_msgs: u64,
+    max_bytes: Option<u64>,
+) -> Result<Vec<Delivery>, NatsBrokerError> {
+    let mut state = runtime.state.lock().await;
+    let now = now_millis() as u64;
+    let mut deliveries: Vec<Delivery> = Vec::new();
+    let mut bytes = 0;
+    let fits = |bytes: u64, message: &StreamMessage| {
+        max_bytes.is_none_or(|max| bytes + message.size() <= max)
+    };
+
+    let expired: Vec<u64> = state
+        .pending
+        .iter()
+        .filter(|(_, p)| p.deadline_ms <= now)
+        .map(|(seq, _)| *seq)
+        .collect();
+    for seq in expired {
+        if deliveries.len() as u64 >= max_msgs {
+            break;
+        }
+        let delivered = state.pending.get(&seq).map_or(0, |p| p.deliveries);
+        if consumer.max_deliver > 0 && delivered >= consumer.max_deliver as u64 {
+            state.pending.remove(&seq);
+            continue;
+        }
+        let Some(message) = get_message(storage_driver_manager, stream, seq).await? else {
+            state.pending.remove(&seq);
+            continue;
+        };
+        if !fits(bytes, &message) {
+            break;
+        }
+        bytes += message.size();
+        state.consumer_seq += 1;
+        state.num_redelivered += 1;
+        let consumer_seq = state.consumer_seq;
+        if let Some(pending) = state.pending.get_mut(&seq) {
+            pending.consumer_seq = consumer_seq;
+            pending.deliveries += 1;
+            pending.deadline_ms = now + ack_wait_ms(consumer, pending.deliveries);
+            deliveries.push(Delivery {
+                message,
+                consumer_seq,
+                deliveries: pending.deliveries,
+                num_pending: 0,
+            });
+        }
+    }
+
+    'fetch: while (deliveries.len() as u64) < max_msgs && has_ack_capacity(consumer, &state) {
+        let batch = read_messages(
+            storage_driver_manager,
+            stream,
+            state.next_seq,
+            FETCH_BATCH_SIZE,
+        )
+        .await?;
+        if batch.is_empty() {
+            break;
+        }
+        for message in batch {
+            if deliveries.len() as u64 >= max_msgs || !has_ack_capacity(consumer, &state) {
+                break 'fetch;
+            }
+            if !matches_filter(consumer, &message.subject)
+                || (message.seq <= state.last_per_subject_until
+                    && !is_last_for_subject(storage_driver_manager, stream, &message).await?)
+            {
+                state.next_seq = message.seq + 1;
+                continue;
+            }
+            if !fits(bytes, &message) {
+                break 'fetch;
+            }
+            bytes += message.size();
+            if message.seq > state.last_per_subject_until && message.seq <= state.counted_seq {
+                state.num_pending = state.num_pending.saturating_sub(1);
+            }
+            state.next_seq = message.seq + 1;
+            state.delivered_stream_seq = message.seq;
+            state.consumer_seq += 1;
+            if consumer.ack_policy != NatsAckPolicy::None {
+                state.pending.insert(
+                    message.seq,
+                    PendingMessage {
+                        consumer_seq: state.consumer_seq,
+                        deliveries: 1,
+                        deadline_ms: now + ack_wait_ms(consumer, 1),
+                    },
+                );
+            }
+            deliveries.push(Delivery {
+                message,
+                consumer_seq: state.consumer_seq,
+                deliveries: 1,
+                num_pending: 0,
+            });
+        }
+    }
+
+    if !deliveries.is_empty() {
+        let num_pending =
+            pending_count(storage_driver_manager, stream, consumer, &mut state).await?;
+        for delivery in deliveries.iter_mut() {
+            delivery.num_pending = num_pending;
+        }
+    }
+    sync_ack_floor(storage_driver_manager, stream, consumer, &mut state).await?;
+    Ok(deliveries)
+}
+
+/// Messages from `next_seq` on that the consumer has yet to deliver.
+///
+/// Filtered consumers keep a running count in `state`: only messages stored
+/// since the last call are read, and first-time deliveries take their
+/// message off the count.
+pub async fn pending_count(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+    state: &mut ConsumerState,
+) -> Result<u64, NatsBrokerError> {
+    let last_seq = last_sequence(storage_driver_manager, stream).await?;
+    let delivered = state.next_seq.saturating_sub(1);
+    let remaining = last_seq.saturating_sub(delivered);
+    if consumer.filter_subjects.is_empty() && state.last_per_subject_until <= delivered {
+        return Ok(remaining);
+    }
+
+    if state.counted_seq < delivered {
+        state.counted_seq = delivered;
+        state.num_pending = 0;
+    }
+    state.last_per_subject_pending = state.last_per_subject_pending.split_off(&state.next_seq);
+    let mut newest_per_subject = HashMap::new();
+    loop {
+        let batch = read_messages(
+            storage_driver_manager,
+            stream,
+            state.counted_seq + 1,
+            FETCH_BATCH_SIZE,
+        )
+        .await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        state.counted_seq = last.seq;
+        for message in batch {
+            if !matches_filter(consumer, &message.subject) {
+                continue;
+            }
+            if message.seq <= state.last_per_subject_until {
+                newest_per_subject.insert(message.subject, message.seq);
+            } else {
+                state.num_pending += 1;
+            }
+        }
+    }
+    state
+        .last_per_subject_pending
+        .extend(newest_per_subject.into_values());
+
+    // Messages removed after they were counted are not tracked, so never
+    // report more than what is left in the stream.
+    let count = state.num_pending + state.last_per_subject_pending.len() as u64;
+    Ok(count.min(remaining))
+}
+
+async fn is_last_for_subject(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    message: &StreamMessage,
+) -> Result<bool, NatsBrokerError> {
+    let last = last_message_by_subject(storage_driver_manager, stream, &message.subject).await?;
+    Ok(last.is_none_or(|last| last.seq <= message.seq))
+}
+
+/// Applies an ack received on the ack subject of message `seq`.
+pub async fn ack_message(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+    runtime: &ConsumerRuntime,
+    seq: u64,
+    action: AckAction,
+) -> Result<(), NatsBrokerError> {
+    let done = {
+        let mut state = runtime.state.lock().await;
+        let deliveries = state.pending.get(&seq).map_or(1, |p| p.deliveries);
+        let done = state.apply_ack(
+            &consumer.ack_policy,
+            seq,
+            &action,
+            ack_wait_ms(consumer, deliveries),
+            now_millis() as u64,
+        );
+        sync_ack_floor(&ctx.storage_driver_manager, stream, consumer, &mut state).await?;
+        done
+    };
+    if !done.is_empty() {
+        apply_retention(ctx, stream, done).await?;
+    }
+    Ok(())
+}
+
+/// Drops acknowledged messages from work-queue and interest streams.
+async fn apply_retention(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    done: Vec<u64>,
+) -> Result<(), NatsBrokerError> {
+    let removable = match stream.retention {
+        NatsStreamRetention::Limits => return Ok(()),
+        NatsStreamRetention::WorkQueue => done,
+        NatsStreamRetention::Interest => {
+            // A message stays while any consumer of the stream has not
+            // acknowledged it yet.
+            let mut removable = done;
+            for other in ctx
+                .cache_manager
+                .list_consumers(&stream.tenant, &stream.name)
+            {
+                let Some(runtime) = ctx.cache_manager.get_consumer_runtime(
+                    &other.tenant,
+                    &other.stream_name,
+                    &other.name,
+                ) else {
+                    return Ok(());
+                };
+                let state = runtime.state.lock().await;
+                removable.retain(|seq| state.is_acked(*seq));
+            }
+            removable
+        }
+    };
+    if removable.is_empty() {
+        return Ok(());
+    }
+    let state = ctx
+        .cache_manager
+        .get_stream_state(&stream.tenant, &stream.name);
+    delete_messages(
+        &ctx.storage_driver_manager,
+        stream,
+        &mut *state.lock().await,
+        &removable,
+    )
+    .await?;
+    Ok(())
+}
+
+fn delivery_packet(consumer: &NatsConsumer, sid: &str, delivery: Delivery) -> NatsPacket {
+    let message = delivery.message;
+    let reply_to = Some(ack_reply_subject(
+        &consumer.stream_name,
+        &consumer.name,
+        delivery.deliveries,
+        message.seq,
+        delivery.consumer_seq,
+        message.timestamp * 1_000_000_000,
+        delivery.num_pending,
+    ));
+    let (headers, payload) = if consumer.headers_only {
+        let size = [(NATS_MSG_SIZE, message.data.len().to_string())];
+        (
+            Some(append_headers(message.headers.as_ref(), &size)),
+            Bytes::new(),
+        )
+    } else {
+        (message.headers, message.data)
+    };
+    match headers {
+        Some(headers) => NatsPacket::HMsg {
+            subject: message.subject,
+            sid: sid.to_string(),
+            reply_to,
+            headers,
+            payload,
+        },
+        None => NatsPacket::Msg {
+            subject: message.subject,
+            sid: sid.to_string(),
+            reply_to,
+            payload,
+        },
+    }
+}
+
+/// Sends a JetStream status message (`NATS/1.0 <code> <description>`).
+pub async fn write_status(
+    ctx: &NatsProcessContext,
+    connect_id: u64,
+    subject: &str,
+    sid: &str,
+    code: u16,
+    description: &str,
+    extra_headers: &[(&str, String)],
+) -> Result<(), NatsBrokerError> {
+    let mut headers = format!("NATS/1.0 {} {}\r\n", code, description);
+    for (key, value) in extra_headers {
+        headers.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    headers.push_str("\r\n");
+    let packet = NatsPacket::HMsg {
+        subject: subject.to_string(),
+        sid: sid.to_string(),
+        reply_to: None,
+        headers: Bytes::from(headers),
+        payload: Bytes::new(),
+    };
+    write_nats_packet(&ctx.connection_manager, connect_id, packet).await
+}
+
+/// Serves a pull request (`MSG.NEXT` or `+NXT`) in the background: delivers
+/// up to `batch` messages to `reply_to`, waiting for new ones until the
+/// request expires, and closes it with a 404/408 status like nats-server.
+pub fn start_pull_request(
+    ctx: &NatsProcessContext,
+    stream: NatsStream,
+    consumer: NatsConsumer,
+    runtime: Arc<ConsumerRuntime>,
+    reply_to: &str,
+    req: ConsumerMsgNextRequest,
+) {
+    let ctx = ctx.clone();
+    let reply_to = reply_to.to_string();
+    tokio::spawn(async move {
+        let Some(sid) = ctx.cache_manager.get_inbox_sid(&reply_to) else {
+            return;
+        };
+        let connect_id = ctx.connect_id;
+        if consumer.max_waiting > 0
+            && runtime.num_waiting.load(Ordering::SeqCst) >= consumer.max_waiting as u64
+        {
+            let _ = write_status(
+                &ctx,
+                connect_id,
+                &reply_to,
+                &sid,
+                409,
+                "Exceeded MaxWaiting",
+                &[],
+            )
+            .await;
+            return;
+        }
+
+        runtime.num_waiting.fetch_add(1, Ordering::SeqCst);
+        if let Err(e) =
+            serve_pull_request(&ctx, &stream, &consumer, &runtime, &reply_to, &sid, &req).await
+        {
+            warn!(
+                "JetStream pull request on {}/{} failed: {}",
+                consumer.stream_name, consumer.name, e
+            );
+        }
+        runtime.num_waiting.fetch_sub(1, Ordering::SeqCst);
+    });
+}
+
+async fn serve_pull_request(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+    runtime: &ConsumerRuntime,
+    reply_to: &str,
+    sid: &str,
+    req: &ConsumerMsgNextRequest,
+) -> Result<(), NatsBrokerError> {
+    let connect_id = ctx.connect_id;
+    let started = now_millis() as u64;
+    let deadline = req
+        .expires
+        .filter(|ns| *ns > 0)
+        .map(|ns| started + ns / 1_000_000);
+    let heartbeat_ms = req.idle_heartbeat.unwrap_or(0) / 1_000_000;
+    let mut remaining = req.batch.max(1);
+    let mut remaining_bytes = req.max_bytes.filter(|b| *b > 0);
+    let mut delivered = 0;
+    let mut last_sent = started;
+
+    loop {
+        if runtime.stopped.load(Ordering::SeqCst) {
+            return write_status(ctx, connect_id, reply_to, sid, 409, "Consumer Deleted", &[])
+                .await;
+        }
+        // The paused flag may change while the request waits.
+        let paused = ctx
+            .cache_manager
+            .get_consumer(&consumer.tenant, &consumer.stream_name, &consumer.name)
+            .is_some_and(|c| is_paused(&c));
+
+        if !paused {
+            let deliveries = next_deliveries(
+                &ctx.storage_driver_manager,
+                stream,
+                consumer,
+                runtime,
+                remaining,
+                remaining_bytes,
+            )
+            .await?;
+            for delivery in deliveries {
+                let size = delivery.message.size();
+                let packet = delivery_packet(consumer, sid, delivery);
+                write_nats_packet(&ctx.connection_manager, connect_id, packet).await?;
+                remaining -= 1;
+                delivered += 1;
+                remaining_bytes = remaining_bytes.map(|b| b.saturating_sub(size));
+                last_sent = now_millis() as u64;
+            }
+            if remaining == 0 || remaining_bytes == Some(0) {
+                return Ok(());
+            }
+        }
+
+        let now = now_millis() as u64;
+        if req.no_wait && delivered == 0 {
+            return write_status(ctx, connect_id, reply_to, sid, 404, "No Messages", &[]).await;
+        }
+        if req.no_wait || deadline.is_some_and(|d| now >= d) {
+            let pending = [
+                ("Nats-Pending-Messages", remaining.to_string()),
+                (
+                    "Nats-Pending-Bytes",
+                    remaining_bytes.unwrap_or(0).to_string(),
+                ),
+            ];
+            return write_status(
+                ctx,
+                connect_id,
+                reply_to,
+                sid,
+                408,
+                "Request Timeout",
+                &pending,
+            )
+            .await;
+        }
+        if heartbeat_ms > 0 && now.saturating_sub(last_sent) >= heartbeat_ms {
+            write_status(ctx, connect_id, reply_to, sid, 100, "Idle Heartbeat", &[]).await?;
+            last_sent = now;
+        }
+        if ctx.cache_manager.get_connection(connect_id).is_none() {
+            return Ok(());
+        }
+        sleep(Duration::from_millis(PULL_POLL_INTERVAL_MS)).await;
+    }
+}
+
+/// Where push deliveries of `consumer` go: `(connect_id, sid)` of a local
+/// subscription on the deliver subject.
+fn resolve_push_target(
+    ctx: &NatsProcessContext,
+    consumer: &NatsConsumer,
+    runtime: &ConsumerRuntime,
+) -> Option<(u64, String)> {
+    if is_inbox_subject(&consumer.deliver_subject) {
+        let sid = ctx.cache_manager.get_inbox_sid(&consumer.deliver_subject)?;
+        ctx.cache_manager.get_connection(runtime.owner_connect_id)?;
+        return Some((runtime.owner_connect_id, sid));
+    }
+    let broker_id = broker_config().broker_id;
+    ctx.subscribe_manager
+        .subscribe_list
+        .iter()
+        .find(|s| {
+            s.broker_id == broker_id && nats_subject_match(&s.subject, &consumer.deliver_subject)
+        })
+        .map(|s| (s.connect_id, s.sid.clone()))
+}
+
+/// Starts the background loop pushing messages of a push consumer to its
+/// deliver subject. The loop ends when the consumer or its stream is removed.
+fn start_push_delivery(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+    runtime: &Arc<ConsumerRuntime>,
+) {
+    if runtime.push_started.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let ctx = ctx.clone();
+    let runtime = runtime.clone();
+    let (tenant, stream_name, consumer_name) = (
+        stream.tenant.clone(),
+        stream.name.clone(),
+        consumer.name.clone(),
+    );
+    tokio::spawn(async move {
+        let mut last_sent = now_millis() as u64;
+        while !runtime.stopped.load(Ordering::SeqCst) {
+            let (Some(stream), Some(consumer)) = (
+                ctx.cache_manager.get_stream(&tenant, &stream_name),
+                ctx.cache_manager
+                    .get_consumer(&tenant, &stream_name, &consumer_name),
+            ) else {
+                break;
+            };
+            match push_once(&ctx, &stream, &consumer, &runtime, last_sent).await {
+                Ok(Some(sent_at)) => last_sent = sent_at,
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "JetStream push delivery of {}/{} failed: {}",
+                    stream_name, consumer_name, e
+                ),
+            }
+            sleep(Duration::from_millis(PUSH_POLL_INTERVAL_MS)).await;
+        }
+    });
+}
+
+/// One round of push delivery. Returns the time something was last sent to
+/// the client, messages or an idle heartbeat.
+async fn push_once(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    consumer: &NatsConsumer,
+    runtime: &ConsumerRuntime,
+    last_sent: u64,
+) -> Result<Option<u64>, NatsBrokerError> {
+    let Some((connect_id, sid)) = resolve_push_target(ctx, consumer, runtime) else {
+        return Ok(None);
+    };
+    if is_paused(consumer) {
+        return Ok(None);
+    }
+    let deliveries = next_deliveries(
+        &ctx.storage_driver_manager,
+        stream,
+        consumer,
+        runtime,
+        FETCH_BATCH_SIZE,
+        None,
+    )
+    .await?;
+    let now = now_millis() as u64;
+    if deliveries.is_empty() {
+        let heartbeat_ms = consumer.idle_heartbeat / 1_000_000;
+        if heartbeat_ms == 0 || now.saturating_sub(last_sent) < heartbeat_ms {
+            return Ok(None);
+        }
+        let state = runtime.state.lock().await;
+        let headers = [
+            ("Nats-Last-Consumer", state.consumer_seq.to_string()),
+            ("Nats-Last-Stream", state.delivered_stream_seq.to_string()),
+        ];
+        drop(state);
+        write_status(
+            ctx,
+            connect_id,
+            &consumer.deliver_subject,
+            &sid,
+            100,
+            "Idle Heartbeat",
+            &headers,
+        )
+        .await?;
+        return Ok(Some(now));
+    }
+    for delivery in deliveries {
+        let packet = delivery_packet(consumer, &sid, delivery);
+        write_nats_packet(&ctx.connection_manager, connect_id, packet).await?;
+    }
+    Ok(Some(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(consumer_seq: u64) -> PendingMessage {
+        PendingMessage {
+            consumer_seq,
+            deliveries: 1,
+            deadline_ms: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_ack_floor() {
+        let mut state = ConsumerState::new(5);
+        assert_eq!(state.ack_floor(), (4, 0));
+
+        state.next_seq = 9;
+        state.consumer_seq = 3;
+        state.pending.insert(6, pending(1));
+        state.pending.insert(8, pending(3));
+        assert_eq!(state.ack_floor(), (5, 0));
+        assert!(state.is_acked(7));
+        assert!(!state.is_acked(8));
+        assert!(!state.is_acked(9));
+
+        let done = state.apply_ack(&NatsAckPolicy::Explicit, 6, &AckAction::Ack, 0, 0);
+        assert_eq!(done, vec![6]);
+        assert_eq!(state.ack_floor(), (7, 2));
+
+        state.apply_ack(&NatsAckPolicy::Explicit, 8, &AckAction::Term, 0, 0);
+        assert_eq!(state.ack_floor(), (8, 3));
+    }
+
+    #[test]
+    fn test_ack_all_and_nak() {
+        let mut state = ConsumerState::new(1);
+        state.next_seq = 4;
+        for seq in 1..4 {
+            state.pending.insert(seq, pending(seq));
+        }
+
+        state.apply_ack(&NatsAckPolicy::All, 2, &AckAction::Nak(500), 0, 100);
+        assert_eq!(state.pending[&2].deadline_ms, 600);
+        state.apply_ack(&NatsAckPolicy::All, 2, &AckAction::Progress, 30_000, 100);
+        assert_eq!(state.pending[&2].deadline_ms, 30_100);
+
+        let done = state.apply_ack(&NatsAckPolicy::All, 2, &AckAction::Ack, 0, 0);
+        assert_eq!(done, vec![1, 2]);
+        assert_eq!(state.pending.keys().copied().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_ack_wait_with_backoff() {
+        let mut consumer = NatsConsumer {
+            ack_wait: DEFAULT_ACK_WAIT,
+            ..Default::default()
+        };
+        assert_eq!(ack_wait_ms(&consumer, 3), 30_000);
+
+        consumer.backoff = vec![1_000_000_000, 5_000_000_000];
+        assert_eq!(ack_wait_ms(&consumer, 1), 1_000);
+        assert_eq!(ack_wait_ms(&consumer, 2), 5_000);
+        assert_eq!(ack_wait_ms(&consumer, 7), 5_000);
+    }
+}
@@ -12,35 +12,245 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! `$JS.API.DIRECT.GET` — message reads answered with the raw message, its
+//! metadata in `Nats-*` headers, instead of a JSON envelope. KV clients use
+//! it for gets when the stream has `allow_direct` set; other streams refuse
+//! direct gets.
+
 use crate::core::error::NatsBrokerError;
+use crate::core::write_client::write_nats_packet;
 use crate::handler::command::NatsProcessContext;
+use crate::jstream::delivery::write_status;
+use crate::jstream::error::{js_bad_request, js_no_message_found};
+use crate::jstream::headers::append_headers;
 use crate::jstream::protocol::{DirectGetHeaders, DirectGetRequest};
+use crate::jstream::store::{
+    first_sequence_since, get_message, last_message_by_subject, next_message_by_subject,
+    read_messages, StreamMessage,
+};
+use crate::jstream::stream::{format_time, load_stream};
 use bytes::Bytes;
+use chrono::DateTime;
+use metadata_struct::nats::stream::NatsStream;
+use protocol::nats::packet::NatsPacket;
+use tracing::warn;
+
+fn direct_response(stream: &str, message: StreamMessage) -> (DirectGetHeaders, Bytes) {
+    let headers = DirectGetHeaders {
+        stream: stream.to_string(),
+        sequence: message.seq,
+        subject: message.subject,
+        timestamp: format_time(message.timestamp),
+        num_pending: None,
+        message_headers: message.headers,
+    };
+    (headers, message.data)
+}
 
-/// `$JS.API.DIRECT.GET.<stream>`
-/// Returns headers + raw payload; caller builds the HMSG response.
+fn load_direct_stream(
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+) -> Result<NatsStream, NatsBrokerError> {
+    let stream = load_stream(ctx, stream_name)?;
+    if !stream.allow_direct {
+        return Err(js_bad_request("direct get is not enabled on this stream"));
+    }
+    Ok(stream)
+}
+
+/// `$JS.API.DIRECT.GET.<stream>` with a request body.
 pub async fn process_direct_get(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: DirectGetRequest,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    req: DirectGetRequest,
 ) -> Result<(DirectGetHeaders, Bytes), NatsBrokerError> {
-    todo!("DIRECT.GET")
+    let stream = load_direct_stream(ctx, stream)?;
+    let sdm = &ctx.storage_driver_manager;
+    let message = if let Some(subject) = &req.last_by_subj {
+        last_message_by_subject(sdm, &stream, subject).await?
+    } else if let Some(start_time) = &req.start_time {
+        let start_time = DateTime::parse_from_rfc3339(start_time)
+            .map_err(|e| js_bad_request(format!("invalid start_time: {}", e)))?;
+        let start_seq =
+            first_sequence_since(sdm, &stream, start_time.timestamp().max(0) as u64).await?;
+        match (start_seq, &req.next_by_subj) {
+            (None, _) => None,
+            (Some(seq), Some(subject)) => {
+                next_message_by_subject(sdm, &stream, subject, seq).await?
+            }
+            (Some(seq), None) => read_messages(sdm, &stream, seq, 1)
+                .await?
+                .into_iter()
+                .next(),
+        }
+    } else if let Some(subject) = &req.next_by_subj {
+        next_message_by_subject(sdm, &stream, subject, req.seq.unwrap_or(1)).await?
+    } else if let Some(seq) = req.seq {
+        get_message(sdm, &stream, seq).await?
+    } else {
+        return Err(js_bad_request("request is missing seq or subject"));
+    };
+    let message = message.ok_or_else(js_no_message_found)?;
+    Ok(direct_response(&stream.name, message))
 }
 
-/// `$JS.API.DIRECT.GET.<stream>.<subject>` — get latest message for subject
+/// `$JS.API.DIRECT.GET.<stream>.<subject>` — newest message on `subject`.
 pub async fn process_direct_get_by_subject(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _subject: &str,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    subject: &str,
 ) -> Result<(DirectGetHeaders, Bytes), NatsBrokerError> {
-    todo!("DIRECT.GET (by subject)")
+    process_direct_get_last(ctx, stream, subject).await
 }
 
-/// `$JS.API.DIRECT.GET.LAST.<stream>.<subject>` — alias for last-by-subject
+/// `$JS.API.DIRECT.GET.LAST.<stream>.<subject>` — newest message on `subject`.
 pub async fn process_direct_get_last(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _subject: &str,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    subject: &str,
 ) -> Result<(DirectGetHeaders, Bytes), NatsBrokerError> {
-    todo!("DIRECT.GET.LAST")
+    let stream = load_direct_stream(ctx, stream)?;
+    let message = last_message_by_subject(&ctx.storage_driver_manager, &stream, subject)
+        .await?
+        .ok_or_else(js_no_message_found)?;
+    Ok(direct_response(&stream.name, message))
+}
+
+/// Sends a direct get result to `reply_to`: the message with its metadata
+/// headers, or a status message such as `404 Message Not Found`.
+pub async fn reply_direct_get(
+    ctx: &NatsProcessContext,
+    reply_to: Option<&str>,
+    result: Result<(DirectGetHeaders, Bytes), NatsBrokerError>,
+) -> Option<NatsPacket> {
+    let reply_to = reply_to?;
+    let sid = ctx
+        .cache_manager
+        .get_inbox_sid(reply_to)
+        .unwrap_or_else(|| "0".to_string());
+    let written = match result {
+        Ok((headers, payload)) => {
+            let mut pairs = vec![
+                ("Nats-Stream", headers.stream),
+                ("Nats-Subject", headers.subject),
+                ("Nats-Sequence", headers.sequence.to_string()),
+                ("Nats-Time-Stamp", headers.timestamp),
+            ];
+            if let Some(num_pending) = headers.num_pending {
+                pairs.push(("Nats-Num-Pending", num_pending.to_string()));
+            }
+            let packet = NatsPacket::HMsg {
+                subject: reply_to.to_string(),
+                sid,
+                reply_to: None,
+                headers: append_headers(headers.message_headers.as_ref(), &pairs),
+                payload,
+            };
+            write_nats_packet(&ctx.connection_manager, ctx.connect_id, packet).await
+        }
+        Err(NatsBrokerError::JetStreamApi {
+            code, description, ..
+        }) => {
+            let description = if code == 404 {
+                "Message Not Found".to_string()
+            } else {
+                description
+            };
+            write_status(ctx, ctx.connect_id, reply_to, &sid, code, &description, &[]).await
+        }
+        Err(e) => {
+            write_status(
+                ctx,
+                ctx.connect_id,
+                reply_to,
+                &sid,
+                500,
+                &e.to_string(),
+                &[],
+            )
+            .await
+        }
+    };
+    if let Err(e) = written {
+        warn!(
+            "JetStream direct get: failed to reply on {}: {}",
+            reply_to, e
+        );
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tenant::get_tenant;
+    use crate::handler::command::test_process_context;
+    use storage_adapter::storage::test_add_topic;
+
+    async fn ctx_with_stream(allow_direct: bool) -> NatsProcessContext {
+        let ctx = test_process_context().await;
+        let stream = NatsStream {
+            tenant: get_tenant(),
+            name: "ORDERS".to_string(),
+            subjects: vec!["orders.>".to_string()],
+            allow_direct,
+            ..Default::default()
+        };
+        test_add_topic(&ctx.storage_driver_manager, &stream.topic_name());
+        ctx.cache_manager.add_stream(stream);
+        ctx
+    }
+
+    fn error_code(result: Result<(DirectGetHeaders, Bytes), NatsBrokerError>) -> u16 {
+        match result {
+            Err(NatsBrokerError::JetStreamApi { code, .. }) => code,
+            other => panic!(
+                "expected a JetStream API error, got {:?}",
+                other.map(|r| r.0)
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn direct_get_is_rejected_without_allow_direct() {
+        let ctx = ctx_with_stream(false).await;
+        let req = DirectGetRequest {
+            seq: Some(1),
+            last_by_subj: None,
+            next_by_subj: None,
+            start_time: None,
+        };
+        assert_eq!(
+            error_code(process_direct_get(&ctx, "ORDERS", req).await),
+            400
+        );
+        assert_eq!(
+            error_code(process_direct_get_last(&ctx, "ORDERS", "orders.new").await),
+            400
+        );
+        assert_eq!(
+            error_code(process_direct_get_by_subject(&ctx, "ORDERS", "orders.new").await),
+            400
+        );
+    }
+
+    #[tokio::test]
+    async fn direct_get_reads_the_stream_with_allow_direct() {
+        let ctx = ctx_with_stream(true).await;
+        let req = DirectGetRequest {
+            seq: Some(1),
+            last_by_subj: None,
+            next_by_subj: None,
+            start_time: None,
+        };
+        // The empty stream is read: no such message rather than a refusal.
+        assert_eq!(
+            error_code(process_direct_get(&ctx, "ORDERS", req).await),
+            404
+        );
+        assert_eq!(
+            error_code(process_direct_get_last(&ctx, "ORDERS", "orders.new").await),
+            404
+        );
+    }
 }
@@ -0,0 +1,197 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JetStream API errors. `code` / `err_code` follow the values used by
+//! nats-server so that clients can match on them.
+
+use crate::core::error::NatsBrokerError;
+use crate::jstream::protocol::JsError;
+
+fn js_error(code: u16, err_code: u32, description: impl Into<String>) -> NatsBrokerError {
+    NatsBrokerError::JetStreamApi {
+        code,
+        err_code,
+        description: description.into(),
+    }
+}
+
+pub fn js_bad_request(description: impl Into<String>) -> NatsBrokerError {
+    js_error(400, 10003, description)
+}
+
+pub fn js_not_supported(feature: &str) -> NatsBrokerError {
+    js_error(501, 10004, format!("{} is not supported", feature))
+}
+
+pub fn js_maximum_streams_limit() -> NatsBrokerError {
+    js_error(400, 10027, "maximum number of streams reached")
+}
+
+pub fn js_stream_general_error(description: impl Into<String>) -> NatsBrokerError {
+    js_error(500, 10051, description)
+}
+
+pub fn js_stream_invalid_config(description: impl Into<String>) -> NatsBrokerError {
+    js_error(500, 10052, description)
+}
+
+pub fn js_stream_message_exceeds_maximum() -> NatsBrokerError {
+    js_error(400, 10054, "message size exceeds maximum allowed")
+}
+
+pub fn js_stream_mismatch() -> NatsBrokerError {
+    js_error(400, 10056, "stream name in subject does not match request")
+}
+
+pub fn js_stream_msg_delete_failed(description: impl Into<String>) -> NatsBrokerError {
+    js_error(500, 10057, description)
+}
+
+pub fn js_stream_name_exist() -> NatsBrokerError {
+    js_error(
+        400,
+        10058,
+        "stream name already in use with a different configuration",
+    )
+}
+
+pub fn js_stream_not_found() -> NatsBrokerError {
+    js_error(404, 10059, "stream not found")
+}
+
+pub fn js_stream_not_match() -> NatsBrokerError {
+    js_error(400, 10060, "expected stream does not match")
+}
+
+pub fn js_stream_subject_overlap() -> NatsBrokerError {
+    js_error(400, 10065, "subjects overlap with an existing stream")
+}
+
+pub fn js_stream_update_failed(description: impl Into<String>) -> NatsBrokerError {
+    js_error(500, 10069, description)
+}
+
+pub fn js_stream_wrong_last_sequence(last_seq: u64) -> NatsBrokerError {
+    js_error(400, 10071, format!("wrong last sequence: {}", last_seq))
+}
+
+pub fn js_stream_store_failed(description: impl Into<String>) -> NatsBrokerError {
+    js_error(503, 10077, description)
+}
+
+pub fn js_stream_rollup_failed(description: impl Into<String>) -> NatsBrokerError {
+    js_error(500, 10111, description)
+}
+
+pub fn js_message_ttl_invalid() -> NatsBrokerError {
+    js_error(400, 10165, "invalid per-message TTL")
+}
+
+pub fn js_message_ttl_disabled() -> NatsBrokerError {
+    js_error(400, 10166, "per-message TTL is disabled")
+}
+
+pub fn js_consumer_create_error(description: impl Into<String>) -> NatsBrokerError {
+    js_error(500, 10012, description)
+}
+
+pub fn js_consumer_not_found() -> NatsBrokerError {
+    js_error(404, 10014, "consumer not found")
+}
+
+pub fn js_consumer_name_mismatch() -> NatsBrokerError {
+    js_error(
+        400,
+        10017,
+        "consumer name in subject does not match durable name in request",
+    )
+}
+
+pub fn js_maximum_consumers_limit() -> NatsBrokerError {
+    js_error(400, 10026, "maximum consumers limit reached")
+}
+
+pub fn js_consumer_filter_not_subset() -> NatsBrokerError {
+    js_error(
+        400,
+        10093,
+        "consumer filter subject is not a valid subset of the interest subjects",
+    )
+}
+
+pub fn js_consumer_invalid_policy(description: impl Into<String>) -> NatsBrokerError {
+    js_error(400, 10094, description)
+}
+
+pub fn js_consumer_wq_requires_explicit_ack() -> NatsBrokerError {
+    js_error(400, 10098, "workqueue stream requires explicit ack")
+}
+
+pub fn js_consumer_wq_multiple_unfiltered() -> NatsBrokerError {
+    js_error(
+        400,
+        10099,
+        "multiple non-filtered consumers not allowed on workqueue stream",
+    )
+}
+
+pub fn js_consumer_wq_not_unique() -> NatsBrokerError {
+    js_error(
+        400,
+        10100,
+        "filtered consumer not unique on workqueue stream",
+    )
+}
+
+pub fn js_consumer_invalid_name() -> NatsBrokerError {
+    js_error(
+        400,
+        10103,
+        "consumer name can not contain whitespace, '.', '*', '>', path separators",
+    )
+}
+
+pub fn js_consumer_already_exists() -> NatsBrokerError {
+    js_error(400, 10105, "consumer already exists")
+}
+
+pub fn js_consumer_does_not_exist() -> NatsBrokerError {
+    js_error(400, 10149, "consumer does not exist")
+}
+
+pub fn js_no_message_found() -> NatsBrokerError {
+    js_error(404, 10037, "no message found")
+}
+
+/// Converts any broker error into the `error` object of a JetStream reply.
+/// Errors that did not originate from the JetStream layer are reported as a
+/// general stream error.
+pub fn to_js_error(e: &NatsBrokerError) -> JsError {
+    match e {
+        NatsBrokerError::JetStreamApi {
+            code,
+            err_code,
+            description,
+        } => JsError {
+            code: *code,
+            err_code: *err_code,
+            description: description.clone(),
+        },
+        other => JsError {
+            code: 500,
+            err_code: 10051,
+            description: other.to_string(),
+        },
+    }
+}
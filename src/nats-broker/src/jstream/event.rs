@@ -12,117 +12,368 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! JetStream advisories. Stream and consumer changes and API calls are
+//! announced on `$JS.EVENT.ADVISORY.>` with the payloads nats-server uses, so
+//! monitoring tools can subscribe to them. An advisory is only written when a
+//! subscription or a stream is interested in its subject.
+
 use crate::core::error::NatsBrokerError;
+use crate::core::tenant::get_tenant;
 use crate::handler::command::NatsProcessContext;
 use crate::jstream::protocol::AdvisoryEvent;
+use crate::jstream::stream::format_time;
+use crate::nats::publish::process_pub0;
+use crate::push::parse::nats_subject_match;
+use bytes::Bytes;
+use common_base::tools::now_second;
+use common_config::broker::broker_config;
+use serde_json::json;
+use tracing::warn;
+
+const ADVISORY_PREFIX: &str = "$JS.EVENT.ADVISORY";
+const ADVISORY_TYPE_PREFIX: &str = "io.nats.jetstream.advisory.v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvisoryAction {
+    Create,
+    Delete,
+    Modify,
+}
+
+impl AdvisoryAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdvisoryAction::Create => "create",
+            AdvisoryAction::Delete => "delete",
+            AdvisoryAction::Modify => "modify",
+        }
+    }
+}
+
+fn advisory(kind: &str, data: serde_json::Value) -> AdvisoryEvent {
+    AdvisoryEvent {
+        kind: format!("{}.{}", ADVISORY_TYPE_PREFIX, kind),
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        timestamp: format_time(now_second()),
+        data,
+    }
+}
+
+// `subscribe_list` holds the subscriptions of every broker (the UpdateCache
+// broadcast adds the remote ones), so a monitor connected elsewhere in the
+// cluster counts. Only subscriptions and streams of the publishing tenant do.
+fn has_interest(ctx: &NatsProcessContext, subject: &str) -> bool {
+    let tenant = get_tenant();
+    ctx.subscribe_manager
+        .subscribe_list
+        .iter()
+        .any(|s| s.tenant == tenant && nats_subject_match(&s.subject, subject))
+        || !ctx.cache_manager.match_streams(&tenant, subject).is_empty()
+}
+
+async fn publish_advisory(
+    ctx: &NatsProcessContext,
+    subject: &str,
+    event: &AdvisoryEvent,
+) -> Result<(), NatsBrokerError> {
+    if !has_interest(ctx, subject) {
+        return Ok(());
+    }
+    let payload = serde_json::to_vec(event)
+        .map(Bytes::from)
+        .map_err(|e| NatsBrokerError::CommonError(e.to_string()))?;
+    process_pub0(ctx, subject, None, &payload, &None).await
+}
+
+/// Announces a stream create / update / delete. Failures are only logged,
+/// the API call that caused the advisory has already succeeded.
+pub async fn notify_stream_action(ctx: &NatsProcessContext, stream: &str, action: AdvisoryAction) {
+    let event = advisory(
+        "stream_action",
+        json!({ "stream": stream, "action": action.as_str() }),
+    );
+    let result = match action {
+        AdvisoryAction::Create => process_event_stream_created(ctx, stream, event).await,
+        AdvisoryAction::Delete => process_event_stream_deleted(ctx, stream, event).await,
+        AdvisoryAction::Modify => process_event_stream_updated(ctx, stream, event).await,
+    };
+    if let Err(e) = result {
+        warn!("JetStream advisory for stream {} failed: {}", stream, e);
+    }
+}
+
+/// Announces a consumer create / update / delete.
+pub async fn notify_consumer_action(
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    action: AdvisoryAction,
+) {
+    let event = advisory(
+        "consumer_action",
+        json!({ "stream": stream, "consumer": consumer, "action": action.as_str() }),
+    );
+    let result = match action {
+        AdvisoryAction::Delete => {
+            process_event_consumer_deleted(ctx, stream, consumer, event).await
+        }
+        // nats-server reports updates on the created subject as well
+        AdvisoryAction::Create | AdvisoryAction::Modify => {
+            process_event_consumer_created(ctx, stream, consumer, event).await
+        }
+    };
+    if let Err(e) = result {
+        warn!(
+            "JetStream advisory for consumer {}/{} failed: {}",
+            stream, consumer, e
+        );
+    }
+}
+
+/// Audit record of a `$JS.API` request and the response sent for it.
+pub async fn notify_api_audit(
+    ctx: &NatsProcessContext,
+    subject: &str,
+    request: &Bytes,
+    response: &Bytes,
+) {
+    let event = advisory(
+        "api_audit",
+        json!({
+            "server": broker_config().broker_id.to_string(),
+            "client": { "acc": get_tenant(), "cid": ctx.connect_id },
+            "subject": subject,
+            "request": String::from_utf8_lossy(request),
+            "response": String::from_utf8_lossy(response),
+        }),
+    );
+    if let Err(e) = process_event_api_audit(ctx, "", event).await {
+        warn!("JetStream API audit advisory for {} failed: {}", subject, e);
+    }
+}
 
 /// `$JS.EVENT.ADVISORY.STREAM.CREATED.<stream>`
 pub async fn process_event_stream_created(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.CREATED")
+    let subject = format!("{}.STREAM.CREATED.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.STREAM.DELETED.<stream>`
 pub async fn process_event_stream_deleted(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.DELETED")
+    let subject = format!("{}.STREAM.DELETED.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.STREAM.UPDATED.<stream>`
 pub async fn process_event_stream_updated(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.UPDATED")
+    let subject = format!("{}.STREAM.UPDATED.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.CONSUMER.CREATED.<stream>.<consumer>`
 pub async fn process_event_consumer_created(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.CONSUMER.CREATED")
+    let subject = format!(
+        "{}.CONSUMER.CREATED.{}.{}",
+        ADVISORY_PREFIX, stream, consumer
+    );
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.CONSUMER.DELETED.<stream>.<consumer>`
 pub async fn process_event_consumer_deleted(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.CONSUMER.DELETED")
+    let subject = format!(
+        "{}.CONSUMER.DELETED.{}.{}",
+        ADVISORY_PREFIX, stream, consumer
+    );
+    publish_advisory(ctx, &subject, &event).await
 }
 
-/// `$JS.EVENT.ADVISORY.API`
+/// `$JS.EVENT.ADVISORY.API[.<subject>]`
 pub async fn process_event_api_audit(
-    _ctx: &NatsProcessContext,
-    _subject: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    subject: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.API")
+    let subject = if subject.is_empty() {
+        format!("{}.API", ADVISORY_PREFIX)
+    } else {
+        format!("{}.API.{}", ADVISORY_PREFIX, subject)
+    };
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.STREAM.SNAPSHOT.CREATE.<stream>`
 pub async fn process_event_snapshot_create(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.SNAPSHOT.CREATE")
+    let subject = format!("{}.STREAM.SNAPSHOT.CREATE.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.STREAM.SNAPSHOT.COMPLETE.<stream>`
 pub async fn process_event_snapshot_complete(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.SNAPSHOT.COMPLETE")
+    let subject = format!("{}.STREAM.SNAPSHOT.COMPLETE.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.STREAM.RESTORE.CREATE.<stream>`
 pub async fn process_event_restore_create(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.RESTORE.CREATE")
+    let subject = format!("{}.STREAM.RESTORE.CREATE.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.STREAM.RESTORE.COMPLETE.<stream>`
 pub async fn process_event_restore_complete(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.RESTORE.COMPLETE")
+    let subject = format!("{}.STREAM.RESTORE.COMPLETE.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.CONSUMER.LEADER_ELECTED.<stream>.<consumer>`
 pub async fn process_event_consumer_leader_elected(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _consumer: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    consumer: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.CONSUMER.LEADER_ELECTED")
+    let subject = format!(
+        "{}.CONSUMER.LEADER_ELECTED.{}.{}",
+        ADVISORY_PREFIX, stream, consumer
+    );
+    publish_advisory(ctx, &subject, &event).await
 }
 
 /// `$JS.EVENT.ADVISORY.STREAM.LEADER_ELECTED.<stream>`
 pub async fn process_event_stream_leader_elected(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _event: AdvisoryEvent,
+    ctx: &NatsProcessContext,
+    stream: &str,
+    event: AdvisoryEvent,
 ) -> Result<(), NatsBrokerError> {
-    todo!("EVENT.ADVISORY.STREAM.LEADER_ELECTED")
+    let subject = format!("{}.STREAM.LEADER_ELECTED.{}", ADVISORY_PREFIX, stream);
+    publish_advisory(ctx, &subject, &event).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::command::test_process_context;
+    use metadata_struct::nats::stream::NatsStream;
+    use metadata_struct::nats::subscribe::NatsSubscribe;
+
+    fn subscribe(broker_id: u64, tenant: &str, subject: &str) -> NatsSubscribe {
+        NatsSubscribe {
+            broker_id,
+            tenant: tenant.to_string(),
+            connect_id: broker_id * 100,
+            sid: "1".to_string(),
+            subject: subject.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn advisories_follow_interest_of_the_tenant() {
+        let ctx = test_process_context().await;
+        let subject = format!("{}.STREAM.CREATED.ORDERS", ADVISORY_PREFIX);
+        assert!(!has_interest(&ctx, &subject));
+
+        ctx.subscribe_manager
+            .add_subscribe(subscribe(1, "other-tenant", "$JS.EVENT.>"));
+        assert!(!has_interest(&ctx, &subject));
+
+        // A monitor connected to another broker.
+        ctx.subscribe_manager.add_subscribe(subscribe(
+            2,
+            &get_tenant(),
+            "$JS.EVENT.ADVISORY.STREAM.*.*",
+        ));
+        assert!(has_interest(&ctx, &subject));
+        assert!(!has_interest(
+            &ctx,
+            &format!("{}.CONSUMER.CREATED.ORDERS.C1", ADVISORY_PREFIX)
+        ));
+    }
+
+    #[tokio::test]
+    async fn advisories_captured_by_a_stream_count_as_interest() {
+        let ctx = test_process_context().await;
+        ctx.cache_manager.add_stream(NatsStream {
+            tenant: get_tenant(),
+            name: "AUDIT".to_string(),
+            subjects: vec!["$JS.EVENT.ADVISORY.API".to_string()],
+            ..Default::default()
+        });
+        assert!(has_interest(&ctx, &format!("{}.API", ADVISORY_PREFIX)));
+        assert!(!has_interest(
+            &ctx,
+            &format!("{}.STREAM.DELETED.ORDERS", ADVISORY_PREFIX)
+        ));
+    }
+
+    #[tokio::test]
+    async fn advisory_without_interest_is_not_published() {
+        let ctx = test_process_context().await;
+        let event = advisory("stream_action", json!({ "stream": "ORDERS" }));
+        // Publishing would need the subject's topic; with no interest nothing
+        // is written and the call succeeds.
+        process_event_stream_created(&ctx, "ORDERS", event)
+            .await
+            .unwrap();
+        assert!(ctx
+            .storage_driver_manager
+            .broker_cache
+            .get_topic_by_name(
+                &get_tenant(),
+                &format!("{}.STREAM.CREATED.ORDERS", ADVISORY_PREFIX)
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_advisory_payload() {
+        let event = advisory(
+            "stream_action",
+            json!({ "stream": "ORDERS", "action": AdvisoryAction::Create.as_str() }),
+        );
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], "io.nats.jetstream.advisory.v1.stream_action");
+        assert_eq!(value["stream"], "ORDERS");
+        assert_eq!(value["action"], "create");
+        assert!(!value["id"].as_str().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,132 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Message headers JetStream acts on. Headers travel as the raw NATS block
+//! `NATS/1.0\r\nKey: Value\r\n...\r\n`.
+
+use bytes::Bytes;
+
+pub const NATS_EXPECTED_STREAM: &str = "Nats-Expected-Stream";
+pub const NATS_EXPECTED_LAST_SEQUENCE: &str = "Nats-Expected-Last-Sequence";
+pub const NATS_EXPECTED_LAST_SUBJECT_SEQUENCE: &str = "Nats-Expected-Last-Subject-Sequence";
+pub const NATS_ROLLUP: &str = "Nats-Rollup";
+pub const NATS_TTL: &str = "Nats-TTL";
+pub const NATS_MSG_SIZE: &str = "Nats-Msg-Size";
+pub const KV_OPERATION: &str = "KV-Operation";
+
+pub const ROLLUP_SUBJECT: &str = "sub";
+pub const ROLLUP_ALL: &str = "all";
+
+const HEADER_VERSION_LINE: &str = "NATS/1.0";
+
+/// Value of the first header named `name`, compared case-insensitively.
+pub fn header_value(headers: &Option<Bytes>, name: &str) -> Option<String> {
+    let text = std::str::from_utf8(headers.as_ref()?).ok()?;
+    text.split("\r\n")
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// Builds a header block from `(name, value)` pairs.
+pub fn build_headers(pairs: &[(&str, String)]) -> Bytes {
+    append_headers(None, pairs)
+}
+
+/// Adds `pairs` to an existing header block, or starts a new one.
+pub fn append_headers(headers: Option<&Bytes>, pairs: &[(&str, String)]) -> Bytes {
+    let existing = headers
+        .and_then(|h| std::str::from_utf8(h).ok())
+        .map(|h| h.trim_end_matches("\r\n"))
+        .filter(|h| !h.is_empty());
+    let mut block = existing.unwrap_or(HEADER_VERSION_LINE).to_string();
+    block.push_str("\r\n");
+    for (name, value) in pairs {
+        block.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    block.push_str("\r\n");
+    Bytes::from(block)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageTtl {
+    Never,
+    Seconds(u64),
+}
+
+/// Parses a `Nats-TTL` value: plain seconds, a duration such as `90s`, `5m`
+/// or `1h`, or `never`.
+pub fn parse_ttl(value: &str) -> Option<MessageTtl> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("never") {
+        return Some(MessageTtl::Never);
+    }
+    if let Ok(secs) = value.parse::<u64>() {
+        return (secs > 0).then_some(MessageTtl::Seconds(secs));
+    }
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let secs = match unit {
+        "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 3600,
+        _ => return None,
+    };
+    (secs > 0).then_some(MessageTtl::Seconds(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_value() {
+        let headers = Some(Bytes::from(
+            "NATS/1.0\r\nKV-Operation: DEL\r\nnats-rollup:sub\r\n\r\n",
+        ));
+        assert_eq!(header_value(&headers, KV_OPERATION).as_deref(), Some("DEL"));
+        assert_eq!(header_value(&headers, NATS_ROLLUP).as_deref(), Some("sub"));
+        assert!(header_value(&headers, NATS_TTL).is_none());
+        assert!(header_value(&None, KV_OPERATION).is_none());
+    }
+
+    #[test]
+    fn test_append_headers() {
+        let built = build_headers(&[(KV_OPERATION, "PURGE".to_string())]);
+        assert_eq!(
+            built,
+            Bytes::from("NATS/1.0\r\nKV-Operation: PURGE\r\n\r\n")
+        );
+
+        let appended = append_headers(Some(&built), &[(NATS_MSG_SIZE, "5".to_string())]);
+        assert_eq!(
+            appended,
+            Bytes::from("NATS/1.0\r\nKV-Operation: PURGE\r\nNats-Msg-Size: 5\r\n\r\n")
+        );
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("30"), Some(MessageTtl::Seconds(30)));
+        assert_eq!(parse_ttl("5m"), Some(MessageTtl::Seconds(300)));
+        assert_eq!(parse_ttl("2h"), Some(MessageTtl::Seconds(7200)));
+        assert_eq!(parse_ttl("never"), Some(MessageTtl::Never));
+        assert!(parse_ttl("0").is_none());
+        assert!(parse_ttl("5d").is_none());
+        assert!(parse_ttl("soon").is_none());
+    }
+}
@@ -12,18 +12,103 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Account information of the current tenant. Limits are derived from the
+//! tenant config: streams count against its topic limit and consumers
+//! against its session limit. Usage is summed over the tenant's streams.
+
 use crate::core::error::NatsBrokerError;
+use crate::core::tenant::get_tenant;
 use crate::handler::command::NatsProcessContext;
-use crate::jstream::protocol::JsInfoResponse;
+use crate::jstream::error::{js_maximum_consumers_limit, js_maximum_streams_limit};
+use crate::jstream::protocol::{JsAccountStats, JsApiLimits, JsInfoResponse};
+use crate::jstream::store::stream_stats;
+use broker_core::tenant::get_tenant_config;
+use metadata_struct::nats::stream::NatsStreamStorage;
+use tracing::warn;
 
-/// `$JS.API.INFO` — server-level JetStream info.
-pub async fn process_info(_ctx: &NatsProcessContext) -> Result<JsInfoResponse, NatsBrokerError> {
-    todo!("JS.API.INFO")
+/// `$JS.API.INFO` — same answer as `$JS.API.ACCOUNT.INFO`.
+pub async fn process_info(ctx: &NatsProcessContext) -> Result<JsInfoResponse, NatsBrokerError> {
+    process_account_info(ctx).await
 }
 
-/// `$JS.API.ACCOUNT.INFO` — account-level JetStream usage and limits.
+/// `$JS.API.ACCOUNT.INFO`
 pub async fn process_account_info(
-    _ctx: &NatsProcessContext,
+    ctx: &NatsProcessContext,
 ) -> Result<JsInfoResponse, NatsBrokerError> {
-    todo!("JS.API.ACCOUNT.INFO")
+    let tenant = get_tenant();
+    let streams = ctx.cache_manager.list_streams(&tenant);
+    let (mut memory, mut storage) = (0, 0);
+    for stream in streams.iter() {
+        let state = ctx
+            .cache_manager
+            .get_stream_state(&stream.tenant, &stream.name);
+        let mut counters = state.lock().await;
+        let stats = match stream_stats(&ctx.storage_driver_manager, stream, &mut counters).await {
+            Ok(stats) => stats,
+            Err(e) => {
+                warn!(
+                    "JetStream account info: failed to read usage of stream {}: {}",
+                    stream.name, e
+                );
+                continue;
+            }
+        };
+        match stream.storage {
+            NatsStreamStorage::Memory => memory += stats.bytes,
+            NatsStreamStorage::File => storage += stats.bytes,
+        }
+    }
+
+    Ok(JsInfoResponse {
+        kind: "io.nats.jetstream.api.v1.account_info_response".to_string(),
+        stats: JsAccountStats {
+            memory,
+            storage,
+            reserved_memory: 0,
+            reserved_storage: 0,
+            streams: streams.len() as u64,
+            consumers: ctx.cache_manager.count_consumers(&tenant) as u64,
+            limits: account_limits(ctx, &tenant),
+            tiers: None,
+        },
+        error: None,
+    })
+}
+
+fn to_limit(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// JetStream limits of `tenant`; `-1` means unlimited.
+pub fn account_limits(ctx: &NatsProcessContext, tenant: &str) -> JsApiLimits {
+    let config = get_tenant_config(&ctx.cache_manager.node_cache, tenant);
+    JsApiLimits {
+        max_memory: -1,
+        max_storage: -1,
+        max_streams: to_limit(config.max_topics),
+        max_consumers: to_limit(config.max_sessions),
+        max_ack_pending: -1,
+        memory_max_stream_bytes: -1,
+        storage_max_stream_bytes: -1,
+        duplicate_window_max: 0,
+        max_bytes_required: false,
+    }
+}
+
+/// Fails when `tenant` already has as many streams as it may create.
+pub fn check_stream_limit(ctx: &NatsProcessContext, tenant: &str) -> Result<(), NatsBrokerError> {
+    let limit = get_tenant_config(&ctx.cache_manager.node_cache, tenant).max_topics;
+    if ctx.cache_manager.list_streams(tenant).len() as u64 >= limit {
+        return Err(js_maximum_streams_limit());
+    }
+    Ok(())
+}
+
+/// Fails when `tenant` already has as many consumers as it may create.
+pub fn check_consumer_limit(ctx: &NatsProcessContext, tenant: &str) -> Result<(), NatsBrokerError> {
+    let limit = get_tenant_config(&ctx.cache_manager.node_cache, tenant).max_sessions;
+    if ctx.cache_manager.count_consumers(tenant) as u64 >= limit {
+        return Err(js_maximum_consumers_limit());
+    }
+    Ok(())
 }
@@ -12,65 +12,256 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! KV buckets on top of JetStream streams.
+//!
+//! A bucket `<bucket>` is the stream `KV_<bucket>` bound to `$KV.<bucket>.>`,
+//! and every key is the subject `$KV.<bucket>.<key>`. The stream sequence of a
+//! message is the revision of that key, `max_msgs_per_subject` is the history
+//! depth and `max_age` the TTL. Deletes and purges are marker messages carrying
+//! a `KV-Operation` header, so client libraries that publish to the subjects
+//! directly and those going through these helpers see the same data.
+
 use crate::core::error::NatsBrokerError;
 use crate::handler::command::NatsProcessContext;
+use crate::jstream::consumer::create_watch_consumer;
+use crate::jstream::error::{js_bad_request, js_no_message_found};
+use crate::jstream::headers::{
+    build_headers, header_value, KV_OPERATION, NATS_ROLLUP, ROLLUP_SUBJECT,
+};
 use crate::jstream::protocol::{KvGetHeaders, KvPutResponse};
+use crate::jstream::store::{last_message_by_subject, scan_messages, StreamMessage};
+use crate::jstream::stream::{format_time, load_stream, publish_to_stream};
 use bytes::Bytes;
+use metadata_struct::nats::stream::NatsStream;
+use std::collections::BTreeMap;
+
+pub const KV_STREAM_PREFIX: &str = "KV_";
+pub const KV_SUBJECT_PREFIX: &str = "$KV.";
+
+const KV_OPERATION_DEL: &str = "DEL";
+const KV_OPERATION_PURGE: &str = "PURGE";
+
+/// Name of the stream backing `bucket`.
+pub fn kv_stream_name(bucket: &str) -> String {
+    format!("{}{}", KV_STREAM_PREFIX, bucket)
+}
+
+/// Subject of `key` in `bucket`.
+pub fn kv_subject(bucket: &str, key: &str) -> String {
+    format!("{}{}.{}", KV_SUBJECT_PREFIX, bucket, key)
+}
+
+/// Key of a `$KV.<bucket>.<key>` subject.
+pub fn kv_key<'a>(bucket: &str, subject: &'a str) -> Option<&'a str> {
+    subject
+        .strip_prefix(KV_SUBJECT_PREFIX)?
+        .strip_prefix(bucket)?
+        .strip_prefix('.')
+}
+
+/// Bucket names: letters, digits, `-` and `_`.
+pub fn is_valid_bucket(bucket: &str) -> bool {
+    !bucket.is_empty()
+        && bucket
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Keys: dot separated tokens of letters, digits, `-`, `_`, `/`, `\` and `=`.
+/// A leading or trailing dot, an empty token or a `_kv` prefix is rejected.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with("_kv")
+        && key.split('.').all(|token| {
+            !token.is_empty()
+                && token
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_/\\=".contains(c))
+        })
+}
+
+/// Watch patterns are keys that may also use the `*` and `>` wildcards.
+fn is_valid_watch_pattern(pattern: &str) -> bool {
+    let tokens: Vec<&str> = pattern.split('.').collect();
+    tokens.iter().enumerate().all(|(i, token)| match *token {
+        "*" => true,
+        ">" => i == tokens.len() - 1,
+        _ => is_valid_key(token),
+    })
+}
+
+/// `true` when the message is a DEL or PURGE marker rather than a value.
+fn is_marker(message: &StreamMessage) -> bool {
+    matches!(
+        header_value(&message.headers, KV_OPERATION).as_deref(),
+        Some(KV_OPERATION_DEL) | Some(KV_OPERATION_PURGE)
+    )
+}
+
+fn load_bucket(ctx: &NatsProcessContext, bucket: &str) -> Result<NatsStream, NatsBrokerError> {
+    if !is_valid_bucket(bucket) {
+        return Err(js_bad_request(format!("invalid bucket name: {}", bucket)));
+    }
+    load_stream(ctx, &kv_stream_name(bucket))
+}
+
+fn check_key(key: &str) -> Result<(), NatsBrokerError> {
+    if !is_valid_key(key) {
+        return Err(js_bad_request(format!("invalid key: {}", key)));
+    }
+    Ok(())
+}
+
+async fn write_entry(
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    key: &str,
+    headers: Option<Bytes>,
+    payload: Bytes,
+) -> Result<KvPutResponse, NatsBrokerError> {
+    check_key(key)?;
+    let stream = load_bucket(ctx, bucket)?;
+    let seq = publish_to_stream(ctx, &stream, &kv_subject(bucket, key), &headers, &payload).await?;
+    Ok(KvPutResponse {
+        stream: stream.name,
+        seq,
+        duplicate: false,
+    })
+}
 
 /// `$KV.<bucket>.<key>` — put value.
 /// Returns a publish ACK confirming the message was written to the stream.
 pub async fn process_kv_put(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _key: &str,
-    _payload: Bytes,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    key: &str,
+    payload: Bytes,
 ) -> Result<KvPutResponse, NatsBrokerError> {
-    todo!("KV PUT")
+    write_entry(ctx, bucket, key, None, payload).await
 }
 
 /// `$KV.<bucket>.<key>` — get latest value.
 /// Returns headers describing the entry plus the raw value bytes.
 pub async fn process_kv_get(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _key: &str,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    key: &str,
 ) -> Result<(KvGetHeaders, Bytes), NatsBrokerError> {
-    todo!("KV GET")
+    check_key(key)?;
+    let stream = load_bucket(ctx, bucket)?;
+    let subject = kv_subject(bucket, key);
+    let message = last_message_by_subject(&ctx.storage_driver_manager, &stream, &subject)
+        .await?
+        .filter(|message| !is_marker(message))
+        .ok_or_else(js_no_message_found)?;
+    let headers = KvGetHeaders {
+        stream: stream.name,
+        subject: message.subject,
+        sequence: message.seq,
+        timestamp: format_time(message.timestamp),
+        operation: String::new(),
+        num_pending: None,
+    };
+    Ok((headers, message.data))
 }
 
 /// `$KV.<bucket>.<key>` — delete key (writes a DEL marker).
 pub async fn process_kv_delete(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _key: &str,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    key: &str,
 ) -> Result<KvPutResponse, NatsBrokerError> {
-    todo!("KV DELETE")
+    let headers = build_headers(&[(KV_OPERATION, KV_OPERATION_DEL.to_string())]);
+    write_entry(ctx, bucket, key, Some(headers), Bytes::new()).await
 }
 
 /// `$KV.<bucket>.<key>` — purge all revisions of key (writes a PURGE marker).
 pub async fn process_kv_purge(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _key: &str,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    key: &str,
 ) -> Result<KvPutResponse, NatsBrokerError> {
-    todo!("KV PURGE")
+    let headers = build_headers(&[
+        (KV_OPERATION, KV_OPERATION_PURGE.to_string()),
+        (NATS_ROLLUP, ROLLUP_SUBJECT.to_string()),
+    ]);
+    write_entry(ctx, bucket, key, Some(headers), Bytes::new()).await
 }
 
 /// `$KV.<bucket>.>` — list all keys in bucket.
 /// Returns the key names as strings.
 pub async fn process_kv_keys(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
+    ctx: &NatsProcessContext,
+    bucket: &str,
 ) -> Result<Vec<String>, NatsBrokerError> {
-    todo!("KV KEYS")
+    let stream = load_bucket(ctx, bucket)?;
+    let mut latest: BTreeMap<String, bool> = BTreeMap::new();
+    for message in scan_messages(&ctx.storage_driver_manager, &stream, 1).await? {
+        if let Some(key) = kv_key(bucket, &message.subject) {
+            latest.insert(key.to_string(), is_marker(&message));
+        }
+    }
+    Ok(latest
+        .into_iter()
+        .filter(|(_, deleted)| !deleted)
+        .map(|(key, _)| key)
+        .collect())
 }
 
 /// `$KV.<bucket>.<key|>` — watch for changes.
-/// Sets up a push consumer; individual change events are delivered as KV get responses.
+/// Sets up an ephemeral push consumer delivering to `deliver_subject`: the
+/// latest revision of every matching key first, then each later update.
 pub async fn process_kv_watch(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _pattern: &str,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    pattern: &str,
+    deliver_subject: Option<&str>,
 ) -> Result<(), NatsBrokerError> {
-    todo!("KV WATCH")
+    let Some(deliver_subject) = deliver_subject else {
+        return Err(js_bad_request("watch requires a reply subject"));
+    };
+    if !is_valid_watch_pattern(pattern) {
+        return Err(js_bad_request(format!("invalid key pattern: {}", pattern)));
+    }
+    let stream = load_bucket(ctx, bucket)?;
+    create_watch_consumer(
+        ctx,
+        &stream.name,
+        kv_subject(bucket, pattern),
+        deliver_subject,
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kv_subjects() {
+        assert_eq!(kv_stream_name("config"), "KV_config");
+        assert_eq!(kv_subject("config", "app.port"), "$KV.config.app.port");
+        assert_eq!(kv_key("config", "$KV.config.app.port"), Some("app.port"));
+        assert_eq!(kv_key("config", "$KV.configs.app"), None);
+        assert_eq!(kv_key("config", "$JS.API.INFO"), None);
+    }
+
+    #[test]
+    fn test_kv_names() {
+        assert!(is_valid_bucket("my_bucket-1"));
+        assert!(!is_valid_bucket("my.bucket"));
+        assert!(!is_valid_bucket(""));
+
+        assert!(is_valid_key("app.port"));
+        assert!(is_valid_key("path/to=value"));
+        assert!(!is_valid_key(".app"));
+        assert!(!is_valid_key("app..port"));
+        assert!(!is_valid_key("app.*"));
+        assert!(!is_valid_key("_kv_internal"));
+
+        assert!(is_valid_watch_pattern(">"));
+        assert!(is_valid_watch_pattern("app.*.port"));
+        assert!(!is_valid_watch_pattern("app.>.port"));
+    }
 }
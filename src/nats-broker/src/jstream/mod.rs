@@ -15,11 +15,15 @@
 pub mod ack;
 pub mod command;
 pub mod consumer;
+pub mod delivery;
 pub mod direct;
+pub mod error;
 pub mod event;
+pub mod headers;
 pub mod info;
 pub mod kv;
 pub mod object;
 pub mod process;
 pub mod protocol;
+pub mod store;
 pub mod stream;
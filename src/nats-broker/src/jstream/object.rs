@@ -12,76 +12,429 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Object store buckets on top of JetStream streams.
+//!
+//! A bucket `<bucket>` is the stream `OBJ_<bucket>` with the same layout the
+//! NATS client libraries use: the data of an upload goes to the chunk subject
+//! `$O.<bucket>.C.<nonce>` and the object description (`ObjectInfo` as JSON)
+//! to the metadata subject `$O.<bucket>.M.<base64url(name)>`. Metadata is
+//! published with `Nats-Rollup: sub`, so only the newest revision of every
+//! object is kept, and the chunks of a replaced or deleted revision are purged.
+
 use crate::core::error::NatsBrokerError;
 use crate::handler::command::NatsProcessContext;
+use crate::jstream::consumer::create_watch_consumer;
+use crate::jstream::error::{js_bad_request, js_no_message_found};
+use crate::jstream::headers::{build_headers, NATS_ROLLUP, ROLLUP_SUBJECT};
+use crate::jstream::process::reply_nats_packet;
 use crate::jstream::protocol::{
-    ObjectDeleteResponse, ObjectInfo, ObjectListResponse, ObjectMeta, ObjectRequest,
+    ObjectDeleteResponse, ObjectInfo, ObjectLink, ObjectListResponse, ObjectMeta, ObjectRequest,
+};
+use crate::jstream::store::{
+    last_message_by_subject, purge_messages, read_subject_messages, scan_messages,
 };
+use crate::jstream::stream::{format_time, load_stream, publish_to_stream};
+use base64::engine::general_purpose::URL_SAFE;
+use base64::Engine;
 use bytes::Bytes;
+use common_base::tools::now_second;
+use metadata_struct::nats::stream::NatsStream;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+pub const OBJ_STREAM_PREFIX: &str = "OBJ_";
+pub const OBJ_SUBJECT_PREFIX: &str = "$O.";
+
+/// An upload whose metadata arrived and whose chunks are still coming in.
+pub struct ObjectUpload {
+    pub meta: ObjectMeta,
+    /// Connection that started the upload; the upload is dropped with it.
+    pub connect_id: u64,
+    pub received: u64,
+    pub size: u64,
+    pub hasher: Sha256,
+}
+
+/// Name of the stream backing `bucket`.
+pub fn obj_stream_name(bucket: &str) -> String {
+    format!("{}{}", OBJ_STREAM_PREFIX, bucket)
+}
+
+/// Subject holding the chunks of the upload `nonce`.
+pub fn obj_chunk_subject(bucket: &str, nonce: &str) -> String {
+    format!("{}{}.C.{}", OBJ_SUBJECT_PREFIX, bucket, nonce)
+}
+
+/// Subject holding the metadata of object `name`.
+pub fn obj_meta_subject(bucket: &str, name: &str) -> String {
+    format!(
+        "{}{}.M.{}",
+        OBJ_SUBJECT_PREFIX,
+        bucket,
+        URL_SAFE.encode(name)
+    )
+}
+
+/// `SHA-256=<base64url>` digest as reported in `ObjectInfo::digest`.
+pub fn obj_digest(hasher: Sha256) -> String {
+    format!("SHA-256={}", URL_SAFE.encode(hasher.finalize()))
+}
+
+/// Bucket names: letters, digits, `-` and `_`.
+pub fn is_valid_bucket(bucket: &str) -> bool {
+    !bucket.is_empty()
+        && bucket
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Nonces become a subject token, so they must be a single plain token.
+fn is_valid_nonce(nonce: &str) -> bool {
+    !nonce.is_empty() && !nonce.contains(['.', '*', '>', ' '])
+}
+
+fn load_bucket(ctx: &NatsProcessContext, bucket: &str) -> Result<NatsStream, NatsBrokerError> {
+    if !is_valid_bucket(bucket) {
+        return Err(js_bad_request(format!("invalid bucket name: {}", bucket)));
+    }
+    load_stream(ctx, &obj_stream_name(bucket))
+}
+
+/// Current revision of object `name`, including delete markers.
+async fn load_info(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    bucket: &str,
+    name: &str,
+) -> Result<Option<ObjectInfo>, NatsBrokerError> {
+    let subject = obj_meta_subject(bucket, name);
+    let Some(message) =
+        last_message_by_subject(&ctx.storage_driver_manager, stream, &subject).await?
+    else {
+        return Ok(None);
+    };
+    let info = serde_json::from_slice(&message.data)
+        .map_err(|e| NatsBrokerError::CommonError(e.to_string()))?;
+    Ok(Some(info))
+}
+
+/// Like `load_info`, but deleted objects are reported as missing.
+async fn load_live_info(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    bucket: &str,
+    name: &str,
+) -> Result<ObjectInfo, NatsBrokerError> {
+    load_info(ctx, stream, bucket, name)
+        .await?
+        .filter(|info| !info.deleted)
+        .ok_or_else(js_no_message_found)
+}
+
+fn link_of(info: &ObjectInfo) -> Option<&ObjectLink> {
+    info.options.as_ref().and_then(|o| o.link.as_ref())
+}
+
+/// Publishes `info` as the new revision of its object and purges the chunks
+/// of the revision it replaces.
+async fn write_info(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    info: &ObjectInfo,
+) -> Result<(), NatsBrokerError> {
+    let previous = load_info(ctx, stream, &info.bucket, &info.name).await?;
+    let payload = serde_json::to_vec(info)
+        .map(Bytes::from)
+        .map_err(|e| NatsBrokerError::CommonError(e.to_string()))?;
+    let headers = Some(build_headers(&[(NATS_ROLLUP, ROLLUP_SUBJECT.to_string())]));
+    publish_to_stream(
+        ctx,
+        stream,
+        &obj_meta_subject(&info.bucket, &info.name),
+        &headers,
+        &payload,
+    )
+    .await?;
+    if let Some(previous) = previous.filter(|p| p.nonce != info.nonce) {
+        purge_chunks(ctx, stream, &previous.bucket, &previous.nonce).await?;
+    }
+    Ok(())
+}
+
+async fn purge_chunks(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    bucket: &str,
+    nonce: &str,
+) -> Result<(), NatsBrokerError> {
+    if nonce.is_empty() {
+        return Ok(());
+    }
+    let subject = obj_chunk_subject(bucket, nonce);
+    let state = ctx
+        .cache_manager
+        .get_stream_state(&stream.tenant, &stream.name);
+    purge_messages(
+        &ctx.storage_driver_manager,
+        stream,
+        &mut *state.lock().await,
+        Some(&subject),
+        None,
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+fn object_info(meta: &ObjectMeta, bucket: &str, size: u64, digest: String) -> ObjectInfo {
+    ObjectInfo {
+        name: meta.name.clone(),
+        description: meta.description.clone(),
+        nonce: meta.nonce.clone(),
+        bucket: bucket.to_string(),
+        chunks: meta.chunks,
+        size,
+        digest,
+        mtime: format_time(now_second()),
+        deleted: false,
+        headers: meta.headers.clone(),
+        options: meta.options.clone(),
+    }
+}
 
 /// `$OBJ.<bucket>.info.<object>` + `$OBJ.<bucket>.chunks.<nonce>` — chunked upload.
 /// Caller publishes metadata first, then data chunks to the chunks subject.
 /// This handler processes the metadata message that initiates the upload.
+/// Links and empty objects complete right away; otherwise the returned info
+/// has an empty digest until the last chunk arrives.
 pub async fn process_obj_put(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _meta: ObjectMeta,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    meta: ObjectMeta,
 ) -> Result<ObjectInfo, NatsBrokerError> {
-    todo!("OBJ PUT")
+    let stream = load_bucket(ctx, bucket)?;
+    if meta.name.is_empty() {
+        return Err(js_bad_request("object name is required"));
+    }
+    if !meta.bucket.is_empty() && meta.bucket != bucket {
+        return Err(js_bad_request("object bucket does not match subject"));
+    }
+
+    if let Some(link) = meta.options.as_ref().and_then(|o| o.link.as_ref()) {
+        if meta.chunks > 0 || meta.size > 0 {
+            return Err(js_bad_request("a link can not carry data"));
+        }
+        let target_stream = load_bucket(ctx, &link.bucket)?;
+        let target = load_live_info(ctx, &target_stream, &link.bucket, &link.name).await?;
+        if link_of(&target).is_some() {
+            return Err(js_bad_request("a link can not point to another link"));
+        }
+        let info = object_info(&meta, bucket, 0, String::new());
+        write_info(ctx, &stream, &info).await?;
+        return Ok(info);
+    }
+
+    if !is_valid_nonce(&meta.nonce) {
+        return Err(js_bad_request(format!("invalid nonce: {}", meta.nonce)));
+    }
+    if meta.chunks == 0 {
+        if meta.size > 0 {
+            return Err(js_bad_request("object size mismatch"));
+        }
+        let info = object_info(&meta, bucket, 0, obj_digest(Sha256::new()));
+        write_info(ctx, &stream, &info).await?;
+        return Ok(info);
+    }
+
+    let info = object_info(&meta, bucket, meta.size, String::new());
+    ctx.cache_manager.add_object_upload(
+        &stream.tenant,
+        bucket,
+        ObjectUpload {
+            meta,
+            connect_id: ctx.connect_id,
+            received: 0,
+            size: 0,
+            hasher: Sha256::new(),
+        },
+    );
+    Ok(info)
 }
 
-/// `$OBJ.<bucket>` with `{"name": "...", "deliver_subject": "..."}` — stream object chunks.
-/// Server pushes chunks to the deliver_subject; caller must SUB first.
+/// `$OBJ.<bucket>.get.<object>` with `{"name": "...", "deliver_subject": "..."}` — stream object chunks.
+/// Server pushes chunks to the deliver_subject in order; caller must SUB first.
+/// Links are followed to the object they point to.
 pub async fn process_obj_get(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _req: ObjectRequest,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    req: ObjectRequest,
 ) -> Result<(), NatsBrokerError> {
-    todo!("OBJ GET")
+    let Some(deliver_subject) = req.deliver_subject.as_deref() else {
+        return Err(js_bad_request("deliver_subject is required"));
+    };
+    let stream = load_bucket(ctx, bucket)?;
+    let mut info = load_live_info(ctx, &stream, bucket, &req.name).await?;
+    let mut data_stream = stream;
+    if let Some(link) = link_of(&info).cloned() {
+        data_stream = load_bucket(ctx, &link.bucket)?;
+        info = load_live_info(ctx, &data_stream, &link.bucket, &link.name).await?;
+    }
+
+    let subject = obj_chunk_subject(&info.bucket, &info.nonce);
+    let chunks = read_subject_messages(&ctx.storage_driver_manager, &data_stream, &subject).await?;
+    for chunk in chunks {
+        reply_nats_packet(ctx, deliver_subject, chunk.data).await?;
+    }
+    Ok(())
 }
 
-/// `$OBJ.<bucket>` with `{"name": "..."}` — delete object.
+/// `$OBJ.<bucket>.delete.<object>` with `{"name": "..."}` — delete object.
+/// Leaves a delete marker as the newest revision and purges the chunks.
 pub async fn process_obj_delete(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _req: ObjectRequest,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    req: ObjectRequest,
 ) -> Result<ObjectDeleteResponse, NatsBrokerError> {
-    todo!("OBJ DELETE")
+    let stream = load_bucket(ctx, bucket)?;
+    let mut info = load_live_info(ctx, &stream, bucket, &req.name).await?;
+    info.deleted = true;
+    info.chunks = 0;
+    info.size = 0;
+    info.digest = String::new();
+    info.mtime = format_time(now_second());
+    write_info(ctx, &stream, &info).await?;
+    if link_of(&info).is_none() {
+        purge_chunks(ctx, &stream, bucket, &info.nonce).await?;
+    }
+    Ok(ObjectDeleteResponse { success: true })
 }
 
-/// `$OBJ.<bucket>` with `{"name": "..."}` — get object metadata.
+/// `$OBJ.<bucket>.stat.<object>` with `{"name": "..."}` — get object metadata.
 pub async fn process_obj_info(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _req: ObjectRequest,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    req: ObjectRequest,
 ) -> Result<ObjectInfo, NatsBrokerError> {
-    todo!("OBJ INFO")
+    let stream = load_bucket(ctx, bucket)?;
+    load_live_info(ctx, &stream, bucket, &req.name).await
 }
 
 /// `$OBJ.<bucket>` with empty body — list all objects in bucket.
 pub async fn process_obj_list(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
+    ctx: &NatsProcessContext,
+    bucket: &str,
 ) -> Result<ObjectListResponse, NatsBrokerError> {
-    todo!("OBJ LIST")
+    let stream = load_bucket(ctx, bucket)?;
+    let prefix = format!("{}{}.M.", OBJ_SUBJECT_PREFIX, bucket);
+    let mut latest: BTreeMap<String, ObjectInfo> = BTreeMap::new();
+    for message in scan_messages(&ctx.storage_driver_manager, &stream, 1).await? {
+        if !message.subject.starts_with(&prefix) {
+            continue;
+        }
+        if let Ok(info) = serde_json::from_slice::<ObjectInfo>(&message.data) {
+            latest.insert(info.name.clone(), info);
+        }
+    }
+    Ok(ObjectListResponse {
+        bucket: bucket.to_string(),
+        objects: latest.into_values().filter(|info| !info.deleted).collect(),
+    })
 }
 
 /// `$OBJ.<bucket>.>` — watch for object changes (upload / delete).
-/// Sets up a push consumer on the info subject prefix.
+/// Sets up a push consumer on the metadata subjects delivering to
+/// `deliver_subject`: the current revision of every object, then each change.
 pub async fn process_obj_watch(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    deliver_subject: Option<&str>,
 ) -> Result<(), NatsBrokerError> {
-    todo!("OBJ WATCH")
+    let Some(deliver_subject) = deliver_subject else {
+        return Err(js_bad_request("watch requires a reply subject"));
+    };
+    let stream = load_bucket(ctx, bucket)?;
+    let filter = format!("{}{}.M.>", OBJ_SUBJECT_PREFIX, bucket);
+    create_watch_consumer(ctx, &stream.name, filter, deliver_subject).await?;
+    Ok(())
 }
 
 /// Handle a single chunk published to `$OBJ.<bucket>.chunks.<nonce>`.
-/// Called for each chunk during an active upload session.
+/// Called for each chunk during an active upload session. The last chunk
+/// checks the size and publishes the metadata with the SHA-256 digest.
 pub async fn process_obj_chunk(
-    _ctx: &NatsProcessContext,
-    _bucket: &str,
-    _nonce: &str,
-    _chunk: Bytes,
-) -> Result<(), NatsBrokerError> {
-    todo!("OBJ CHUNK")
+    ctx: &NatsProcessContext,
+    bucket: &str,
+    nonce: &str,
+    chunk: Bytes,
+) -> Result<Option<ObjectInfo>, NatsBrokerError> {
+    let stream = load_bucket(ctx, bucket)?;
+    let Some(mut upload) = ctx
+        .cache_manager
+        .take_object_upload(&stream.tenant, bucket, nonce)
+    else {
+        return Err(js_bad_request(format!(
+            "no upload in progress for {}",
+            nonce
+        )));
+    };
+
+    let subject = obj_chunk_subject(bucket, nonce);
+    if let Err(e) = publish_to_stream(ctx, &stream, &subject, &None, &chunk).await {
+        purge_chunks(ctx, &stream, bucket, nonce).await?;
+        return Err(e);
+    }
+    upload.received += 1;
+    upload.size += chunk.len() as u64;
+    upload.hasher.update(&chunk);
+
+    if upload.received < upload.meta.chunks {
+        ctx.cache_manager
+            .add_object_upload(&stream.tenant, bucket, upload);
+        return Ok(None);
+    }
+
+    if upload.size != upload.meta.size {
+        purge_chunks(ctx, &stream, bucket, nonce).await?;
+        return Err(js_bad_request(format!(
+            "object size mismatch: expected {}, received {}",
+            upload.meta.size, upload.size
+        )));
+    }
+    let info = object_info(&upload.meta, bucket, upload.size, obj_digest(upload.hasher));
+    write_info(ctx, &stream, &info).await?;
+    Ok(Some(info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_obj_subjects() {
+        assert_eq!(obj_stream_name("models"), "OBJ_models");
+        assert_eq!(obj_chunk_subject("models", "n1"), "$O.models.C.n1");
+        assert_eq!(
+            obj_meta_subject("models", "resnet.onnx"),
+            "$O.models.M.cmVzbmV0Lm9ubng="
+        );
+    }
+
+    #[test]
+    fn test_obj_digest() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"hello");
+        assert_eq!(
+            obj_digest(hasher),
+            "SHA-256=LPJNul-wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ="
+        );
+    }
+
+    #[test]
+    fn test_obj_names() {
+        assert!(is_valid_bucket("firmware_v2"));
+        assert!(!is_valid_bucket("firmware.v2"));
+        assert!(is_valid_nonce("Z1Tq7oBz2pVZ8p"));
+        assert!(!is_valid_nonce("a.b"));
+        assert!(!is_valid_nonce(""));
+    }
 }
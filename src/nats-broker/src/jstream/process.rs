@@ -26,13 +26,14 @@
     process_consumer_pause,
 };
 use crate::jstream::direct::{
-    process_direct_get, process_direct_get_by_subject, process_direct_get_last,
+    process_direct_get, process_direct_get_by_subject, process_direct_get_last, reply_direct_get,
 };
+use crate::jstream::error::{js_bad_request, to_js_error};
 use crate::jstream::event::{
-    process_event_api_audit, process_event_consumer_created, process_event_consumer_deleted,
-    process_event_consumer_leader_elected, process_event_restore_complete,
-    process_event_restore_create, process_event_snapshot_complete, process_event_snapshot_create,
-    process_event_stream_created, process_event_stream_deleted,
+    notify_api_audit, process_event_api_audit, process_event_consumer_created,
+    process_event_consumer_deleted, process_event_consumer_leader_elected,
+    process_event_restore_complete, process_event_restore_create, process_event_snapshot_complete,
+    process_event_snapshot_create, process_event_stream_created, process_event_stream_deleted,
     process_event_stream_leader_elected, process_event_stream_updated,
 };
 use crate::jstream::info::{process_account_info, process_info};
@@ -41,13 +42,13 @@
     process_kv_watch,
 };
 use crate::jstream::object::{
-    process_obj_delete, process_obj_get, process_obj_info, process_obj_list, process_obj_put,
-    process_obj_watch,
+    process_obj_chunk, process_obj_delete, process_obj_get, process_obj_info, process_obj_list,
+    process_obj_put, process_obj_watch,
 };
 use crate::jstream::protocol::{
     AckNextRequest, AdvisoryEvent, ConsumerCreateRequest, ConsumerListRequest,
-    ConsumerMsgNextRequest, ConsumerPauseRequest, DirectGetRequest, NakRequest, ObjectMeta,
-    ObjectRequest, StreamCreateRequest, StreamListRequest, StreamMsgDeleteRequest,
+    ConsumerMsgNextRequest, ConsumerPauseRequest, DirectGetRequest, JsErrorResponse, NakRequest,
+    ObjectMeta, ObjectRequest, StreamCreateRequest, StreamListRequest, StreamMsgDeleteRequest,
     StreamMsgGetRequest, StreamPeerRemoveRequest, StreamPurgeRequest, StreamSnapshotRequest,
 };
 use crate::jstream::stream::{
@@ -58,6 +59,7 @@
 };
 use bytes::Bytes;
 use protocol::nats::packet::NatsPacket;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 pub async fn js_command(
@@ -67,7 +69,7 @@ pub async fn js_command(
     _headers: &Option<Bytes>,
     payload: &Bytes,
 ) -> Option<NatsPacket> {
-    let parsed = match JsCommand::parse(subject) {
+    let parsed = match JsCommand::parse(subject).map(|c| c.with_ack_payload(payload)) {
         Some(c) => c,
         None => {
             return Some(NatsPacket::Err(format!(
@@ -77,12 +79,15 @@ pub async fn js_command(
         }
     };
 
-    // Helper: deserialize payload into T; return ERR packet on failure.
+    // Helper: deserialize payload into T; reply with a JetStream error on failure.
     macro_rules! parse_req {
         ($t:ty) => {
             match serde_json::from_slice::<$t>(payload) {
                 Ok(v) => v,
-                Err(e) => return Some(NatsPacket::Err(format!("invalid request body: {}", e))),
+                Err(e) => {
+                    let err = js_bad_request(format!("invalid request body: {}", e));
+                    return reply_js_error(ctx, reply_to, err).await;
+                }
             }
         };
         // Zero-byte / optional payload: use Default when empty.
@@ -92,7 +97,10 @@ macro_rules! parse_req {
             } else {
                 match serde_json::from_slice::<$t>(payload) {
                     Ok(v) => v,
-                    Err(e) => return Some(NatsPacket::Err(format!("invalid request body: {}", e))),
+                    Err(e) => {
+                        let err = js_bad_request(format!("invalid request body: {}", e));
+                        return reply_js_error(ctx, reply_to, err).await;
+                    }
                 }
             }
         };
@@ -212,7 +220,7 @@ macro_rules! parse_req {
         }
         JsCommand::ConsumerMsgNext { stream, consumer } => {
             let req = parse_req!(ConsumerMsgNextRequest, default);
-            process_consumer_msg_next(ctx, &stream, &consumer, req)
+            process_consumer_msg_next(ctx, &stream, &consumer, reply_to, req)
                 .await
                 .map(|_| None)
         }
@@ -232,40 +240,43 @@ macro_rules! parse_req {
         JsCommand::DirectGet { stream } => {
             let req = parse_req!(DirectGetRequest);
             // Direct Get returns raw bytes + headers, not JSON — handled separately.
-            process_direct_get(ctx, &stream, req).await.map(|_| None)
+            let result = process_direct_get(ctx, &stream, req).await;
+            return reply_direct_get(ctx, reply_to, result).await;
         }
         JsCommand::DirectGetBySubject { stream, subject } => {
-            process_direct_get_by_subject(ctx, &stream, &subject)
-                .await
-                .map(|_| None)
+            let result = process_direct_get_by_subject(ctx, &stream, &subject).await;
+            return reply_direct_get(ctx, reply_to, result).await;
         }
         JsCommand::DirectGetLast { stream, subject } => {
-            process_direct_get_last(ctx, &stream, &subject)
-                .await
-                .map(|_| None)
+            let result = process_direct_get_last(ctx, &stream, &subject).await;
+            return reply_direct_get(ctx, reply_to, result).await;
         }
 
-        // ── ACK (fire-and-forget) ─────────────────────────────────
-        JsCommand::Ack { stream, consumer } => {
-            process_ack(ctx, &stream, &consumer).await.map(|_| None)
-        }
+        // ── ACK ───────────────────────────────────────────────────
+        // Acks are fire-and-forget unless the client asked for a
+        // confirmation (double ack), which gets an empty reply.
+        JsCommand::Ack { stream, consumer } => process_ack(ctx, subject, &stream, &consumer)
+            .await
+            .map(|_| Some(String::new())),
         JsCommand::Nak { stream, consumer } => {
-            let req = parse_req!(NakRequest, default);
-            process_nak(ctx, &stream, &consumer, req)
+            let req: NakRequest = ack_payload_body(payload);
+            process_nak(ctx, subject, &stream, &consumer, req)
                 .await
-                .map(|_| None)
+                .map(|_| Some(String::new()))
         }
         JsCommand::AckProgress { stream, consumer } => {
-            process_ack_progress(ctx, &stream, &consumer)
+            process_ack_progress(ctx, subject, &stream, &consumer)
                 .await
-                .map(|_| None)
+                .map(|_| Some(String::new()))
+        }
+        JsCommand::AckTerm { stream, consumer } => {
+            process_ack_term(ctx, subject, &stream, &consumer)
+                .await
+                .map(|_| Some(String::new()))
         }
-        JsCommand::AckTerm { stream, consumer } => process_ack_term(ctx, &stream, &consumer)
-            .await
-            .map(|_| None),
         JsCommand::AckNext { stream, consumer } => {
-            let req = parse_req!(AckNextRequest, default);
-            process_ack_next(ctx, &stream, &consumer, req)
+            let req: AckNextRequest = ack_payload_body(payload);
+            process_ack_next(ctx, subject, reply_to, &stream, &consumer, req)
                 .await
                 .map(|_| None)
         }
@@ -357,7 +368,9 @@ macro_rules! parse_req {
         }
         JsCommand::KvKeys { bucket } => process_kv_keys(ctx, &bucket).await.and_then(to_json),
         JsCommand::KvWatch { bucket, pattern } => {
-            process_kv_watch(ctx, &bucket, &pattern).await.map(|_| None)
+            process_kv_watch(ctx, &bucket, &pattern, reply_to)
+                .await
+                .map(|_| None)
         }
 
         // ── Object Store ──────────────────────────────────────────
@@ -365,43 +378,85 @@ macro_rules! parse_req {
             let meta = parse_req!(ObjectMeta);
             process_obj_put(ctx, &bucket, meta).await.and_then(to_json)
         }
-        JsCommand::ObjGet { bucket, object: _ } => {
-            let req = parse_req!(ObjectRequest);
+        JsCommand::ObjChunk { bucket, nonce } => {
+            process_obj_chunk(ctx, &bucket, &nonce, payload.clone())
+                .await
+                .and_then(|info| info.map_or(Ok(None), to_json))
+        }
+        JsCommand::ObjGet { bucket, object } => {
+            let req = parse_req!(ObjectRequest, default).with_name(object);
             process_obj_get(ctx, &bucket, req).await.map(|_| None)
         }
-        JsCommand::ObjDelete { bucket, object: _ } => {
-            let req = parse_req!(ObjectRequest);
+        JsCommand::ObjDelete { bucket, object } => {
+            let req = parse_req!(ObjectRequest, default).with_name(object);
             process_obj_delete(ctx, &bucket, req)
                 .await
                 .and_then(to_json)
         }
-        JsCommand::ObjInfo { bucket, object: _ } => {
-            let req = parse_req!(ObjectRequest);
+        JsCommand::ObjInfo { bucket, object } => {
+            let req = parse_req!(ObjectRequest, default).with_name(object);
             process_obj_info(ctx, &bucket, req).await.and_then(to_json)
         }
         JsCommand::ObjList { bucket } => process_obj_list(ctx, &bucket).await.and_then(to_json),
-        JsCommand::ObjWatch { bucket } => process_obj_watch(ctx, &bucket).await.map(|_| None),
+        JsCommand::ObjWatch { bucket } => process_obj_watch(ctx, &bucket, reply_to)
+            .await
+            .map(|_| None),
     };
 
     if let Some(reply_subject) = reply_to {
         let body = match result {
             Ok(Some(json)) => Bytes::from(json),
             Ok(None) => return None,
-            Err(e) => Bytes::from(e.to_string()),
+            Err(e) => js_error_body(&e),
         };
+        if JsCommand::is_js_api_subject(subject) {
+            notify_api_audit(ctx, subject, payload, &body).await;
+        }
         let _ = reply_nats_packet(ctx, reply_subject, body).await;
     }
 
     None
 }
 
+async fn reply_js_error(
+    ctx: &NatsProcessContext,
+    reply_to: Option<&str>,
+    e: NatsBrokerError,
+) -> Option<NatsPacket> {
+    let Some(reply_subject) = reply_to else {
+        return Some(NatsPacket::Err(e.to_string()));
+    };
+    let _ = reply_nats_packet(ctx, reply_subject, js_error_body(&e)).await;
+    None
+}
+
+pub(crate) fn js_error_body(e: &NatsBrokerError) -> Bytes {
+    let resp = JsErrorResponse {
+        kind: "io.nats.jetstream.api.v1.error_response".to_string(),
+        error: to_js_error(e),
+    };
+    serde_json::to_vec(&resp)
+        .map(Bytes::from)
+        .unwrap_or_else(|_| Bytes::from(e.to_string()))
+}
+
+/// Optional JSON body following the ack kind, e.g. `-NAK {"delay":1000}`.
+/// Malformed bodies fall back to the defaults like nats-server does.
+fn ack_payload_body<T: DeserializeOwned + Default>(payload: &[u8]) -> T {
+    let body = match payload.iter().position(|b| b.is_ascii_whitespace()) {
+        Some(pos) => &payload[pos..],
+        None => return T::default(),
+    };
+    serde_json::from_slice(body).unwrap_or_default()
+}
+
 fn to_json<T: Serialize>(v: T) -> Result<Option<String>, NatsBrokerError> {
     serde_json::to_string(&v)
         .map(Some)
         .map_err(|e| NatsBrokerError::CommonError(e.to_string()))
 }
 
-async fn reply_nats_packet(
+pub(crate) async fn reply_nats_packet(
     ctx: &NatsProcessContext,
     subject: &str,
     payload: Bytes,
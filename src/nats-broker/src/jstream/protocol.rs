@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -26,6 +27,15 @@ pub struct JsError {
     pub description: String,
 }
 
+/// Reply sent when a `$JS.API` request fails before a typed response could be
+/// built. Clients only inspect `error`, so one shape serves every endpoint.
+#[derive(Debug, Serialize)]
+pub struct JsErrorResponse {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub error: JsError,
+}
+
 /// Shared pagination cursor used by LIST / NAMES requests.
 #[derive(Debug, Default, Deserialize)]
 pub struct PageRequest {
@@ -109,6 +119,10 @@ pub struct StreamConfig {
     pub deny_purge: bool,
     #[serde(default)]
     pub allow_rollup_hdrs: bool,
+    #[serde(default)]
+    pub allow_msg_ttl: bool,
+    #[serde(default)]
+    pub allow_direct: bool,
     #[serde(default = "neg_one_i64")]
     pub max_msgs_per_subject: i64,
     #[serde(default = "neg_one_i64")]
@@ -283,10 +297,12 @@ pub struct StreamPurgeResponse {
 pub struct RawMessage {
     pub subject: String,
     pub seq: u64,
+    /// Base64 encoded payload.
     pub data: String,
     pub time: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub headers: Option<HashMap<String, String>>,
+    /// Base64 encoded `NATS/1.0` header block.
+    #[serde(rename = "hdrs", skip_serializing_if = "Option::is_none")]
+    pub headers: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -308,6 +324,14 @@ pub struct StreamMsgDeleteResponse {
     pub error: Option<JsError>,
 }
 
+/// Reply to a publish that was stored by a stream.
+#[derive(Debug, Serialize)]
+pub struct PubAckResponse {
+    pub stream: String,
+    pub seq: u64,
+    pub duplicate: bool,
+}
+
 /// `$JS.API.STREAM.LEADER.STEPDOWN` / `PEER.REMOVE`
 #[derive(Debug, Serialize)]
 pub struct StreamLeaderResponse {
@@ -360,6 +384,8 @@ pub struct ConsumerConfig {
     pub idle_heartbeat: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub backoff: Option<Vec<u64>>,
+    #[serde(default)]
+    pub headers_only: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pause_until: Option<String>,
 }
@@ -541,6 +567,9 @@ pub struct DirectGetHeaders {
     /// `Nats-Num-Pending` (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub num_pending: Option<u64>,
+    /// Headers the message was published with, sent ahead of the above.
+    #[serde(skip)]
+    pub message_headers: Option<Bytes>,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -623,11 +652,27 @@ pub struct ObjectMeta {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<HashMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub options: Option<serde_json::Value>,
+    pub options: Option<ObjectMetaOptions>,
 }
 
-/// Response returned by get-info and after successful upload.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectMetaOptions {
+    /// Makes the object a link to another object instead of carrying data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<ObjectLink>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_chunk_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectLink {
+    pub bucket: String,
+    pub name: String,
+}
+
+/// Response returned by get-info and after successful upload. This is also
+/// the body of the metadata message stored for the object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectInfo {
     pub name: String,
     pub description: String,
@@ -635,21 +680,37 @@ pub struct ObjectInfo {
     pub bucket: String,
     pub chunks: u64,
     pub size: u64,
+    /// `SHA-256=<url-safe base64>`, empty while the upload is in progress.
     pub digest: String,
+    #[serde(default)]
+    pub mtime: String,
     pub deleted: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<ObjectMetaOptions>,
 }
 
-/// `$OBJ.<bucket>` — get / delete / info / list request body.
-#[derive(Debug, Deserialize)]
+/// `$OBJ.<bucket>.<op>.<object>` — get / delete / info request body.
+#[derive(Debug, Default, Deserialize)]
 pub struct ObjectRequest {
+    /// Defaults to the object named in the subject.
+    #[serde(default)]
     pub name: String,
     /// Only used for get: subject to stream chunks to.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deliver_subject: Option<String>,
 }
 
+impl ObjectRequest {
+    pub fn with_name(mut self, object: String) -> Self {
+        if self.name.is_empty() {
+            self.name = object;
+        }
+        self
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct ObjectDeleteResponse {
     pub success: bool,
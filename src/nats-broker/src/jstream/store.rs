@@ -0,0 +1,954 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Message storage of JetStream streams.
+//!
+//! Every stream owns a single-partition topic (see `core::topic`), so the
+//! shard offset of a record is its stream sequence minus one. Records are
+//! keyed by their subject and tagged with `subject_message_tag`, which lets
+//! per-subject lookups use `read_by_tag` instead of a full scan. Message and
+//! byte counts are kept in `StreamCounters`, so publishing never has to
+//! recount the stream.
+
+use crate::core::error::NatsBrokerError;
+use crate::jstream::error::{
+    js_message_ttl_disabled, js_message_ttl_invalid, js_stream_general_error,
+    js_stream_message_exceeds_maximum, js_stream_not_match, js_stream_rollup_failed,
+    js_stream_store_failed, js_stream_wrong_last_sequence,
+};
+use crate::jstream::headers::{
+    header_value, parse_ttl, MessageTtl, NATS_EXPECTED_LAST_SEQUENCE,
+    NATS_EXPECTED_LAST_SUBJECT_SEQUENCE, NATS_EXPECTED_STREAM, NATS_ROLLUP, NATS_TTL, ROLLUP_ALL,
+    ROLLUP_SUBJECT,
+};
+use crate::nats::subscribe::subject_message_tag;
+use crate::push::parse::nats_subject_match;
+use crate::storage::message::MessageStorage;
+use bytes::Bytes;
+use common_base::tools::now_second;
+use metadata_struct::adapter::adapter_offset::AdapterOffsetStrategy;
+use metadata_struct::nats::stream::{NatsStream, NatsStreamDiscard};
+use metadata_struct::storage::adapter_read_config::AdapterReadConfig;
+use metadata_struct::storage::adapter_record::AdapterWriteRecord;
+use metadata_struct::storage::record::{
+    StorageRecord, StorageRecordProtocolData, StorageRecordProtocolDataNats,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use storage_adapter::driver::StorageDriverManager;
+
+const SCAN_BATCH_SIZE: u64 = 500;
+const SCAN_BATCH_BYTES: u64 = 1024 * 1024 * 30;
+
+#[derive(Debug, Clone)]
+pub struct StreamMessage {
+    pub seq: u64,
+    pub subject: String,
+    pub headers: Option<Bytes>,
+    pub data: Bytes,
+    /// Store time in seconds.
+    pub timestamp: u64,
+    /// Expiry in seconds, `0` when the message does not expire.
+    pub expire_at: u64,
+}
+
+impl StreamMessage {
+    fn from_record(record: StorageRecord) -> Self {
+        let subject = record
+            .metadata
+            .key
+            .as_ref()
+            .map(|k| String::from_utf8_lossy(k).to_string())
+            .unwrap_or_default();
+        let headers = record
+            .protocol_data
+            .and_then(|p| p.nats)
+            .and_then(|n| n.header);
+        StreamMessage {
+            seq: record.metadata.offset + 1,
+            subject,
+            headers,
+            data: record.data,
+            timestamp: record.metadata.create_t,
+            expire_at: record.metadata.expire_at,
+        }
+    }
+
+    /// Stored size as accounted against `max_bytes`.
+    pub fn size(&self) -> u64 {
+        (self.subject.len() + self.headers.as_ref().map_or(0, |h| h.len()) + self.data.len()) as u64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamStats {
+    pub messages: u64,
+    pub bytes: u64,
+    pub first_seq: u64,
+    pub last_seq: u64,
+}
+
+/// Message and byte totals of a stream, kept in step with every store and
+/// removal on this broker so that limits and stream info need no scan. They
+/// are loaded with one scan on first use, and again when the stream turns out
+/// to have been written through another broker.
+#[derive(Debug, Default)]
+pub struct StreamCounters {
+    loaded: bool,
+    messages: u64,
+    bytes: u64,
+    first_seq: u64,
+    last_seq: u64,
+    /// Stored messages per subject.
+    subjects: HashMap<String, u64>,
+    /// Sequence of the newest stored message per subject. Missing while the
+    /// subject has messages means it is not known since that message was
+    /// removed; `subject_last_seq` reads it back from the storage.
+    subject_last_seqs: HashMap<String, u64>,
+    /// Subject and size of messages with a TTL, keyed by `(expire_at, seq)`.
+    /// The storage stops returning them once expired, so they are counted out
+    /// here at the same time.
+    expiring: BTreeMap<(u64, u64), (String, u64)>,
+}
+
+impl StreamCounters {
+    pub fn stats(&self) -> StreamStats {
+        if self.messages == 0 {
+            return StreamStats {
+                first_seq: if self.last_seq > 0 {
+                    self.last_seq + 1
+                } else {
+                    0
+                },
+                last_seq: self.last_seq,
+                ..Default::default()
+            };
+        }
+        StreamStats {
+            messages: self.messages,
+            bytes: self.bytes,
+            first_seq: self.first_seq,
+            last_seq: self.last_seq,
+        }
+    }
+
+    pub fn subject_messages(&self, subject: &str) -> u64 {
+        self.subjects.get(subject).copied().unwrap_or(0)
+    }
+
+    fn add(&mut self, message: &StreamMessage) {
+        if self.messages == 0 || message.seq < self.first_seq {
+            self.first_seq = message.seq;
+        }
+        self.messages += 1;
+        self.bytes += message.size();
+        self.last_seq = self.last_seq.max(message.seq);
+        *self.subjects.entry(message.subject.clone()).or_default() += 1;
+        let subject_seq = self
+            .subject_last_seqs
+            .entry(message.subject.clone())
+            .or_default();
+        *subject_seq = (*subject_seq).max(message.seq);
+        if message.expire_at > 0 {
+            self.expiring.insert(
+                (message.expire_at, message.seq),
+                (message.subject.clone(), message.size()),
+            );
+        }
+    }
+
+    /// Returns `true` when the first message was removed.
+    fn remove(&mut self, message: &StreamMessage) -> bool {
+        // A message with a TTL that is no longer tracked was already counted
+        // out when it expired.
+        if message.expire_at > 0
+            && self
+                .expiring
+                .remove(&(message.expire_at, message.seq))
+                .is_none()
+        {
+            return false;
+        }
+        self.count_out(message.seq, &message.subject, message.size())
+    }
+
+    /// Counts out the messages whose TTL has passed. Returns `true` when the
+    /// first message was among them.
+    fn expire(&mut self, now: u64) -> bool {
+        let mut first_removed = false;
+        while let Some(entry) = self.expiring.first_entry() {
+            // Same rule as the storage: expired once `expire_at` is in the past.
+            if entry.key().0 >= now {
+                break;
+            }
+            let ((_, seq), (subject, size)) = entry.remove_entry();
+            first_removed |= self.count_out(seq, &subject, size);
+        }
+        first_removed
+    }
+
+    fn count_out(&mut self, seq: u64, subject: &str, size: u64) -> bool {
+        self.messages = self.messages.saturating_sub(1);
+        self.bytes = self.bytes.saturating_sub(size);
+        if let Some(count) = self.subjects.get_mut(subject) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.subjects.remove(subject);
+            }
+        }
+        if !self.subjects.contains_key(subject) || self.subject_last_seqs.get(subject) == Some(&seq)
+        {
+            self.subject_last_seqs.remove(subject);
+        }
+        seq == self.first_seq
+    }
+}
+
+pub fn stream_shard_name(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+) -> Result<String, NatsBrokerError> {
+    storage_driver_manager
+        .broker_cache
+        .get_topic_by_name(&stream.tenant, &stream.topic_name())
+        .and_then(|topic| topic.storage_name_list.get(&0).cloned())
+        .ok_or_else(|| {
+            js_stream_general_error(format!("storage of stream {} is not ready", stream.name))
+        })
+}
+
+/// Reads up to `limit` messages starting at `start_seq`, in sequence order.
+pub async fn read_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    start_seq: u64,
+    limit: u64,
+) -> Result<Vec<StreamMessage>, NatsBrokerError> {
+    let shard_name = stream_shard_name(storage_driver_manager, stream)?;
+    let offsets = HashMap::from([(shard_name, start_seq.saturating_sub(1))]);
+    let read_config = AdapterReadConfig {
+        max_record_num: limit,
+        max_size: SCAN_BATCH_BYTES,
+    };
+    let records = storage_driver_manager
+        .read_by_offset(&stream.tenant, &stream.topic_name(), &offsets, &read_config)
+        .await?;
+    let mut messages: Vec<StreamMessage> = records
+        .into_iter()
+        .map(StreamMessage::from_record)
+        .filter(|m| m.seq >= start_seq)
+        .collect();
+    messages.sort_by_key(|m| m.seq);
+    Ok(messages)
+}
+
+/// Reads every message from `start_seq` to the end of the stream.
+pub async fn scan_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    start_seq: u64,
+) -> Result<Vec<StreamMessage>, NatsBrokerError> {
+    let mut results = Vec::new();
+    let mut next_seq = start_seq.max(1);
+    loop {
+        let batch =
+            read_messages(storage_driver_manager, stream, next_seq, SCAN_BATCH_SIZE).await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        next_seq = last.seq + 1;
+        results.extend(batch);
+    }
+    Ok(results)
+}
+
+/// Reads every message stored under exactly `subject`.
+pub async fn read_subject_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    subject: &str,
+) -> Result<Vec<StreamMessage>, NatsBrokerError> {
+    read_oldest_subject_messages(storage_driver_manager, stream, subject, u64::MAX).await
+}
+
+/// Reads the oldest `limit` messages stored under exactly `subject`.
+async fn read_oldest_subject_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    subject: &str,
+    limit: u64,
+) -> Result<Vec<StreamMessage>, NatsBrokerError> {
+    let shard_name = stream_shard_name(storage_driver_manager, stream)?;
+    let tag = subject_message_tag(&stream.tenant, subject);
+
+    let mut results: Vec<StreamMessage> = Vec::new();
+    let mut next_offset = 0;
+    while (results.len() as u64) < limit {
+        let read_config = AdapterReadConfig {
+            max_record_num: SCAN_BATCH_SIZE.min(limit - results.len() as u64),
+            max_size: SCAN_BATCH_BYTES,
+        };
+        let offsets = HashMap::from([(shard_name.clone(), next_offset)]);
+        let records = storage_driver_manager
+            .read_by_tag(
+                &stream.tenant,
+                &stream.topic_name(),
+                &tag,
+                &offsets,
+                &read_config,
+            )
+            .await?;
+        let mut batch: Vec<StreamMessage> = records
+            .into_iter()
+            .map(StreamMessage::from_record)
+            .filter(|m| m.seq > next_offset)
+            .collect();
+        batch.sort_by_key(|m| m.seq);
+        let Some(last) = batch.last() else {
+            break;
+        };
+        // seq - 1 is the offset of `last`, so the next read starts right after it.
+        next_offset = last.seq;
+        results.extend(batch);
+    }
+    Ok(results)
+}
+
+/// Reads the messages from `start_seq` up to, not including, `end_seq`.
+async fn read_message_range(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    start_seq: u64,
+    end_seq: u64,
+) -> Result<Vec<StreamMessage>, NatsBrokerError> {
+    let mut results = Vec::new();
+    let mut next_seq = start_seq.max(1);
+    while next_seq < end_seq {
+        let limit = SCAN_BATCH_SIZE.min(end_seq - next_seq);
+        let batch = read_messages(storage_driver_manager, stream, next_seq, limit).await?;
+        let Some(last_seq) = batch.last().map(|m| m.seq) else {
+            break;
+        };
+        results.extend(batch.into_iter().filter(|m| m.seq < end_seq));
+        next_seq = last_seq + 1;
+    }
+    Ok(results)
+}
+
+/// `counters` is the stream state from the cache, see `get_stream_state`.
+pub async fn stream_stats(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+) -> Result<StreamStats, NatsBrokerError> {
+    sync_counters(storage_driver_manager, stream, counters).await?;
+    if last_sequence(storage_driver_manager, stream).await? > counters.last_seq {
+        // Messages were stored through another broker.
+        load_counters(storage_driver_manager, stream, counters).await?;
+    }
+    Ok(counters.stats())
+}
+
+/// Loads the counters on first use and counts out expired messages.
+async fn sync_counters(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+) -> Result<(), NatsBrokerError> {
+    if !counters.loaded {
+        return load_counters(storage_driver_manager, stream, counters).await;
+    }
+    if counters.expire(now_second()) {
+        refresh_first_seq(storage_driver_manager, stream, counters).await?;
+    }
+    Ok(())
+}
+
+async fn load_counters(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+) -> Result<(), NatsBrokerError> {
+    let mut loaded = StreamCounters {
+        loaded: true,
+        last_seq: last_sequence(storage_driver_manager, stream).await?,
+        ..Default::default()
+    };
+    for message in scan_messages(storage_driver_manager, stream, 1).await? {
+        loaded.add(&message);
+    }
+    *counters = loaded;
+    Ok(())
+}
+
+/// Looks up the first message again after it was removed.
+async fn refresh_first_seq(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+) -> Result<(), NatsBrokerError> {
+    if counters.messages == 0 {
+        return Ok(());
+    }
+    let next = read_messages(storage_driver_manager, stream, counters.first_seq, 1).await?;
+    counters.first_seq = next.first().map_or(counters.last_seq + 1, |m| m.seq);
+    Ok(())
+}
+
+/// Deletes `messages` from the stream and counts them out.
+async fn remove_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    messages: &[StreamMessage],
+) -> Result<(), NatsBrokerError> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let offsets: Vec<u64> = messages.iter().map(|m| m.seq - 1).collect();
+    storage_driver_manager
+        .delete_by_offsets(&stream.tenant, &stream.topic_name(), &offsets)
+        .await?;
+    forget_messages(storage_driver_manager, stream, counters, messages).await
+}
+
+async fn forget_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    messages: &[StreamMessage],
+) -> Result<(), NatsBrokerError> {
+    let mut first_removed = false;
+    for message in messages {
+        first_removed |= counters.remove(message);
+    }
+    if first_removed {
+        refresh_first_seq(storage_driver_manager, stream, counters).await?;
+    }
+    Ok(())
+}
+
+/// Sequence of the last message ever stored, including removed ones.
+pub async fn last_sequence(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+) -> Result<u64, NatsBrokerError> {
+    Ok(storage_driver_manager
+        .list_storage_resource(&stream.tenant, &stream.topic_name())
+        .await?
+        .get(&0)
+        .map(|detail| detail.offset.high_watermark)
+        .unwrap_or(0))
+}
+
+/// Stores a message published on `subject` and applies the stream limits.
+/// Returns the sequence assigned to the message. `counters` is the stream
+/// state from the cache, locked for the whole write.
+pub async fn store_message(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    subject: &str,
+    headers: &Option<Bytes>,
+    payload: &Bytes,
+) -> Result<u64, NatsBrokerError> {
+    if stream.max_msg_size >= 0 && payload.len() as i64 > stream.max_msg_size {
+        return Err(js_stream_message_exceeds_maximum());
+    }
+    sync_counters(storage_driver_manager, stream, counters).await?;
+
+    if stream.discard == NatsStreamDiscard::New {
+        if stream.max_msgs > 0 && counters.messages >= stream.max_msgs as u64 {
+            return Err(js_stream_store_failed("maximum messages exceeded"));
+        }
+        let size = (subject.len() + headers.as_ref().map_or(0, |h| h.len()) + payload.len()) as u64;
+        if stream.max_bytes > 0 && counters.bytes + size > stream.max_bytes as u64 {
+            return Err(js_stream_store_failed("maximum bytes exceeded"));
+        }
+    }
+
+    check_expectations(storage_driver_manager, stream, counters, subject, headers).await?;
+    let rollup = rollup_mode(stream, headers)?;
+    let expire_at = message_expire_at(stream, headers)?;
+
+    let mut record = AdapterWriteRecord::new(stream.topic_name(), payload.clone())
+        .with_key(subject.to_string())
+        .with_tags(vec![subject_message_tag(&stream.tenant, subject)])
+        .with_protocol_data(Some(StorageRecordProtocolData {
+            nats: Some(StorageRecordProtocolDataNats {
+                reply_to: None,
+                header: headers.clone(),
+            }),
+            ..Default::default()
+        }));
+    if let Some(expire_at) = expire_at {
+        record = record.with_expire_at(expire_at);
+    }
+
+    let offsets = MessageStorage::new(storage_driver_manager.clone())
+        .write(&stream.tenant, &stream.topic_name(), vec![record])
+        .await?;
+    let seq = offsets
+        .first()
+        .map(|offset| offset + 1)
+        .ok_or_else(|| js_stream_store_failed("no sequence assigned to message"))?;
+
+    if seq > counters.last_seq + 1 {
+        // Messages were stored through another broker in between.
+        load_counters(storage_driver_manager, stream, counters).await?;
+    } else {
+        counters.add(&StreamMessage {
+            seq,
+            subject: subject.to_string(),
+            headers: headers.clone(),
+            data: payload.clone(),
+            timestamp: now_second(),
+            expire_at: expire_at.unwrap_or(0),
+        });
+    }
+
+    if let Some(rollup) = rollup {
+        apply_rollup(
+            storage_driver_manager,
+            stream,
+            counters,
+            subject,
+            seq,
+            rollup,
+        )
+        .await?;
+    }
+    enforce_limits(storage_driver_manager, stream, counters, subject).await?;
+    Ok(seq)
+}
+
+/// Publish preconditions carried in `Nats-Expected-*` headers. The
+/// per-subject form is what KV compare-and-set builds on.
+async fn check_expectations(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    subject: &str,
+    headers: &Option<Bytes>,
+) -> Result<(), NatsBrokerError> {
+    if headers.is_none() {
+        return Ok(());
+    }
+    if let Some(expected) = header_value(headers, NATS_EXPECTED_STREAM) {
+        if expected != stream.name {
+            return Err(js_stream_not_match());
+        }
+    }
+    if let Some(expected) = header_value(headers, NATS_EXPECTED_LAST_SEQUENCE) {
+        let last_seq = last_sequence(storage_driver_manager, stream).await?;
+        if expected.parse::<u64>().ok() != Some(last_seq) {
+            return Err(js_stream_wrong_last_sequence(last_seq));
+        }
+    }
+    if let Some(expected) = header_value(headers, NATS_EXPECTED_LAST_SUBJECT_SEQUENCE) {
+        let last_seq = subject_last_seq(storage_driver_manager, stream, counters, subject).await?;
+        if expected.parse::<u64>().ok() != Some(last_seq) {
+            return Err(js_stream_wrong_last_sequence(last_seq));
+        }
+    }
+    Ok(())
+}
+
+/// Sequence of the newest message stored under exactly `subject`, `0` when
+/// there is none. Answered from `counters` unless that message was removed.
+async fn subject_last_seq(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    subject: &str,
+) -> Result<u64, NatsBrokerError> {
+    if last_sequence(storage_driver_manager, stream).await? > counters.last_seq {
+        // Messages were stored through another broker.
+        load_counters(storage_driver_manager, stream, counters).await?;
+    }
+    if counters.subject_messages(subject) == 0 {
+        return Ok(0);
+    }
+    if let Some(seq) = counters.subject_last_seqs.get(subject) {
+        return Ok(*seq);
+    }
+    let last_seq = read_subject_messages(storage_driver_manager, stream, subject)
+        .await?
+        .last()
+        .map_or(0, |m| m.seq);
+    if last_seq > 0 {
+        counters
+            .subject_last_seqs
+            .insert(subject.to_string(), last_seq);
+    }
+    Ok(last_seq)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rollup {
+    Subject,
+    All,
+}
+
+fn rollup_mode(
+    stream: &NatsStream,
+    headers: &Option<Bytes>,
+) -> Result<Option<Rollup>, NatsBrokerError> {
+    let Some(value) = header_value(headers, NATS_ROLLUP) else {
+        return Ok(None);
+    };
+    if !stream.allow_rollup_hdrs {
+        return Err(js_stream_rollup_failed("rollup not permitted"));
+    }
+    match value.to_ascii_lowercase().as_str() {
+        ROLLUP_SUBJECT => Ok(Some(Rollup::Subject)),
+        ROLLUP_ALL => Ok(Some(Rollup::All)),
+        _ => Err(js_stream_rollup_failed(format!(
+            "rollup value invalid: {}",
+            value
+        ))),
+    }
+}
+
+/// A `Nats-TTL` header overrides the stream `max_age` for one message.
+fn message_expire_at(
+    stream: &NatsStream,
+    headers: &Option<Bytes>,
+) -> Result<Option<u64>, NatsBrokerError> {
+    let Some(value) = header_value(headers, NATS_TTL) else {
+        return Ok(stream.max_age_sec().map(|max_age| now_second() + max_age));
+    };
+    if !stream.allow_msg_ttl {
+        return Err(js_message_ttl_disabled());
+    }
+    match parse_ttl(&value) {
+        Some(MessageTtl::Never) => Ok(None),
+        Some(MessageTtl::Seconds(ttl)) => Ok(Some(now_second() + ttl)),
+        None => Err(js_message_ttl_invalid()),
+    }
+}
+
+/// Removes what the rollup message `seq` replaces: earlier messages on its
+/// subject, or the whole stream before it.
+async fn apply_rollup(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    subject: &str,
+    seq: u64,
+    rollup: Rollup,
+) -> Result<(), NatsBrokerError> {
+    let messages: Vec<StreamMessage> = match rollup {
+        Rollup::Subject => read_subject_messages(storage_driver_manager, stream, subject).await?,
+        Rollup::All => read_message_range(storage_driver_manager, stream, 1, seq).await?,
+    };
+    let replaced: Vec<StreamMessage> = messages.into_iter().filter(|m| m.seq < seq).collect();
+    remove_messages(storage_driver_manager, stream, counters, &replaced).await
+}
+
+/// Drops the oldest messages that fall outside the stream limits after a
+/// message on `subject` was stored. Like `Discard::New`, `max_msgs` counts
+/// the stored messages. Only the dropped messages are read; what is stored is
+/// known from `counters`.
+async fn enforce_limits(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    subject: &str,
+) -> Result<(), NatsBrokerError> {
+    if stream.max_msgs_per_subject > 0 {
+        let excess = counters
+            .subject_messages(subject)
+            .saturating_sub(stream.max_msgs_per_subject as u64);
+        if excess > 0 {
+            let messages =
+                read_oldest_subject_messages(storage_driver_manager, stream, subject, excess)
+                    .await?;
+            remove_messages(storage_driver_manager, stream, counters, &messages).await?;
+        }
+    }
+
+    if stream.discard != NatsStreamDiscard::Old {
+        return Ok(());
+    }
+
+    if stream.max_msgs > 0 {
+        let max_msgs = stream.max_msgs as u64;
+        while counters.messages > max_msgs {
+            let excess = counters.messages - max_msgs;
+            let dropped = read_messages(
+                storage_driver_manager,
+                stream,
+                counters.first_seq,
+                excess.min(SCAN_BATCH_SIZE),
+            )
+            .await?;
+            let Some(last_dropped) = dropped.last() else {
+                break;
+            };
+            // Everything below the first message is gone already, so the
+            // dropped messages are exactly the records before the next one.
+            storage_driver_manager
+                .delete_records_before(
+                    &stream.tenant,
+                    &stream.topic_name(),
+                    &HashMap::from([(0, last_dropped.seq)]),
+                )
+                .await?;
+            forget_messages(storage_driver_manager, stream, counters, &dropped).await?;
+        }
+    }
+
+    if stream.max_bytes > 0 {
+        let max_bytes = stream.max_bytes as u64;
+        while counters.bytes > max_bytes && counters.messages > 0 {
+            let oldest = read_messages(
+                storage_driver_manager,
+                stream,
+                counters.first_seq,
+                SCAN_BATCH_SIZE,
+            )
+            .await?;
+            let mut total = counters.bytes;
+            let dropped: Vec<StreamMessage> = oldest
+                .into_iter()
+                .take_while(|m| {
+                    let over = total > max_bytes;
+                    total = total.saturating_sub(m.size());
+                    over
+                })
+                .collect();
+            if dropped.is_empty() {
+                break;
+            }
+            remove_messages(storage_driver_manager, stream, counters, &dropped).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes messages matching `filter` (all when `None`). `seq` limits the
+/// purge to messages below that sequence and `keep` preserves the newest
+/// `keep` matching messages. Returns the number of purged messages.
+pub async fn purge_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    filter: Option<&str>,
+    seq: Option<u64>,
+    keep: Option<u64>,
+) -> Result<u64, NatsBrokerError> {
+    sync_counters(storage_driver_manager, stream, counters).await?;
+    let mut purged: Vec<StreamMessage> = scan_messages(storage_driver_manager, stream, 1)
+        .await?
+        .into_iter()
+        .filter(|m| filter.is_none_or(|f| nats_subject_match(f, &m.subject)))
+        .filter(|m| seq.is_none_or(|s| m.seq < s))
+        .collect();
+    if let Some(keep) = keep {
+        let purge_count = purged.len().saturating_sub(keep as usize);
+        purged.truncate(purge_count);
+    }
+    remove_messages(storage_driver_manager, stream, counters, &purged).await?;
+    Ok(purged.len() as u64)
+}
+
+/// Sequence of the first message stored at or after `timestamp` (seconds),
+/// `None` when there is no such message.
+pub async fn first_sequence_since(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    timestamp: u64,
+) -> Result<Option<u64>, NatsBrokerError> {
+    let offsets = storage_driver_manager
+        .get_offset_by_timestamp(
+            &stream.tenant,
+            &stream.topic_name(),
+            timestamp,
+            AdapterOffsetStrategy::Latest,
+        )
+        .await?;
+    Ok(offsets.get(&0).map(|offset| offset + 1))
+}
+
+pub async fn get_message(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    seq: u64,
+) -> Result<Option<StreamMessage>, NatsBrokerError> {
+    if seq == 0 {
+        return Ok(None);
+    }
+    let messages = read_messages(storage_driver_manager, stream, seq, 1).await?;
+    Ok(messages.into_iter().find(|m| m.seq == seq))
+}
+
+/// Newest message whose subject matches `subject` (wildcards allowed).
+pub async fn last_message_by_subject(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    subject: &str,
+) -> Result<Option<StreamMessage>, NatsBrokerError> {
+    if !subject.contains(['*', '>']) {
+        let messages = read_subject_messages(storage_driver_manager, stream, subject).await?;
+        return Ok(messages.into_iter().last());
+    }
+    let messages = scan_messages(storage_driver_manager, stream, 1).await?;
+    Ok(messages
+        .into_iter()
+        .rev()
+        .find(|m| nats_subject_match(subject, &m.subject)))
+}
+
+/// First message at or after `start_seq` whose subject matches `subject`.
+pub async fn next_message_by_subject(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    subject: &str,
+    start_seq: u64,
+) -> Result<Option<StreamMessage>, NatsBrokerError> {
+    let mut next_seq = start_seq.max(1);
+    loop {
+        let batch =
+            read_messages(storage_driver_manager, stream, next_seq, SCAN_BATCH_SIZE).await?;
+        let Some(last_seq) = batch.last().map(|m| m.seq) else {
+            return Ok(None);
+        };
+        if let Some(message) = batch
+            .into_iter()
+            .find(|m| nats_subject_match(subject, &m.subject))
+        {
+            return Ok(Some(message));
+        }
+        next_seq = last_seq + 1;
+    }
+}
+
+/// Deletes a single message. Returns `false` when it does not exist.
+pub async fn delete_message(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    seq: u64,
+) -> Result<bool, NatsBrokerError> {
+    Ok(delete_messages(storage_driver_manager, stream, counters, &[seq]).await? > 0)
+}
+
+/// Deletes the messages with the given sequences, skipping those that no
+/// longer exist. Returns the number of deleted messages.
+pub async fn delete_messages(
+    storage_driver_manager: &Arc<StorageDriverManager>,
+    stream: &NatsStream,
+    counters: &mut StreamCounters,
+    seqs: &[u64],
+) -> Result<u64, NatsBrokerError> {
+    sync_counters(storage_driver_manager, stream, counters).await?;
+    let mut messages = Vec::with_capacity(seqs.len());
+    for seq in seqs {
+        if let Some(message) = get_message(storage_driver_manager, stream, *seq).await? {
+            messages.push(message);
+        }
+    }
+    remove_messages(storage_driver_manager, stream, counters, &messages).await?;
+    Ok(messages.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(seq: u64, subject: &str, expire_at: u64) -> StreamMessage {
+        StreamMessage {
+            seq,
+            subject: subject.to_string(),
+            headers: None,
+            data: Bytes::from_static(b"0123456789"),
+            timestamp: 0,
+            expire_at,
+        }
+    }
+
+    #[test]
+    fn counters_follow_adds_and_removes() {
+        let mut counters = StreamCounters::default();
+        counters.add(&message(1, "a", 0));
+        counters.add(&message(2, "b", 0));
+        counters.add(&message(3, "a", 0));
+        assert_eq!(
+            counters.stats(),
+            StreamStats {
+                messages: 3,
+                bytes: 33,
+                first_seq: 1,
+                last_seq: 3,
+            }
+        );
+        assert_eq!(counters.subject_messages("a"), 2);
+
+        assert!(!counters.remove(&message(2, "b", 0)));
+        assert_eq!(counters.subject_messages("b"), 0);
+        assert!(counters.remove(&message(1, "a", 0)));
+        assert_eq!(counters.stats().messages, 1);
+
+        counters.remove(&message(3, "a", 0));
+        let stats = counters.stats();
+        assert_eq!((stats.messages, stats.bytes), (0, 0));
+        assert_eq!((stats.first_seq, stats.last_seq), (4, 3));
+    }
+
+    #[test]
+    fn subject_last_seq_follows_adds_and_removes() {
+        let mut counters = StreamCounters::default();
+        counters.add(&message(1, "a", 0));
+        counters.add(&message(2, "a", 0));
+        counters.add(&message(3, "b", 0));
+        assert_eq!(counters.subject_last_seqs.get("a"), Some(&2));
+
+        // Removing an older message keeps the newest one known.
+        counters.remove(&message(1, "a", 0));
+        assert_eq!(counters.subject_last_seqs.get("a"), Some(&2));
+
+        // Removing the newest one of a subject that still has messages leaves
+        // it to be read back from the storage.
+        counters.add(&message(4, "b", 0));
+        counters.remove(&message(4, "b", 0));
+        assert_eq!(counters.subject_messages("b"), 1);
+        assert_eq!(counters.subject_last_seqs.get("b"), None);
+
+        counters.remove(&message(2, "a", 0));
+        assert_eq!(counters.subject_last_seqs.get("a"), None);
+    }
+
+    #[test]
+    fn expired_messages_are_counted_out_once() {
+        let mut counters = StreamCounters::default();
+        counters.add(&message(1, "a", 100));
+        counters.add(&message(2, "a", 200));
+        counters.add(&message(3, "b", 0));
+
+        assert!(!counters.expire(100));
+        assert!(counters.expire(101));
+        assert_eq!(counters.stats().messages, 2);
+        assert_eq!(counters.subject_messages("a"), 1);
+
+        // Removing a message that already expired changes nothing.
+        assert!(!counters.remove(&message(1, "a", 100)));
+        assert_eq!(counters.stats().messages, 2);
+
+        assert!(!counters.remove(&message(2, "a", 200)));
+        assert!(!counters.expire(1_000));
+        assert_eq!(counters.stats().messages, 1);
+        assert_eq!(counters.stats().bytes, 11);
+    }
+}
@@ -13,121 +13,735 @@
 // limitations under the License.
 
 use crate::core::error::NatsBrokerError;
+use crate::core::tenant::get_tenant;
+use crate::core::topic::try_get_or_init_stream_topic;
 use crate::handler::command::NatsProcessContext;
+use crate::jstream::consumer::delete_consumer;
+use crate::jstream::error::{
+    js_bad_request, js_no_message_found, js_not_supported, js_stream_general_error,
+    js_stream_invalid_config, js_stream_mismatch, js_stream_msg_delete_failed,
+    js_stream_name_exist, js_stream_not_found, js_stream_subject_overlap, js_stream_update_failed,
+};
+use crate::jstream::event::{notify_stream_action, AdvisoryAction};
+use crate::jstream::info::check_stream_limit;
+use crate::jstream::process::{js_error_body, reply_nats_packet};
 use crate::jstream::protocol::{
-    StreamCreateRequest, StreamDeleteResponse, StreamInfoResponse, StreamLeaderResponse,
-    StreamListRequest, StreamListResponse, StreamMsgDeleteRequest, StreamMsgDeleteResponse,
-    StreamMsgGetRequest, StreamMsgGetResponse, StreamNamesResponse, StreamPeerRemoveRequest,
-    StreamPurgeRequest, StreamPurgeResponse, StreamSnapshotRequest,
+    PageInfo, PubAckResponse, RawMessage, StreamConfig, StreamCreateRequest, StreamDeleteResponse,
+    StreamInfo, StreamInfoResponse, StreamLeaderResponse, StreamListRequest, StreamListResponse,
+    StreamMsgDeleteRequest, StreamMsgDeleteResponse, StreamMsgGetRequest, StreamMsgGetResponse,
+    StreamNamesResponse, StreamPeerRemoveRequest, StreamPurgeRequest, StreamPurgeResponse,
+    StreamSnapshotRequest, StreamState,
+};
+use crate::jstream::store::{
+    delete_message, get_message, last_message_by_subject, next_message_by_subject, purge_messages,
+    store_message, stream_stats, StreamMessage,
+};
+use crate::storage::stream::NatsStreamStorage;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use broker_core::topic::TopicStorage;
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat};
+use common_base::tools::now_second;
+use metadata_struct::nats::stream::{
+    NatsStream, NatsStreamDiscard, NatsStreamRetention, NatsStreamStorage as StreamStorageType,
 };
+use tracing::warn;
+
+const STREAM_LIST_LIMIT: u64 = 256;
+const STREAM_NAMES_LIMIT: u64 = 1024;
+const MAX_STREAM_REPLICAS: u32 = 5;
+/// nats-server default for `duplicate_window`: two minutes, in nanoseconds.
+const DEFAULT_DUPLICATE_WINDOW: u64 = 120_000_000_000;
 
 /// `$JS.API.STREAM.CREATE.<stream>`
 pub async fn process_stream_create(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: StreamCreateRequest,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    req: StreamCreateRequest,
 ) -> Result<StreamInfoResponse, NatsBrokerError> {
-    todo!("STREAM.CREATE")
+    let kind = "io.nats.jetstream.api.v1.stream_create_response";
+    let tenant = get_tenant();
+    if !req.name.is_empty() && req.name != stream_name {
+        return Err(js_stream_mismatch());
+    }
+    let stream = build_stream(&tenant, stream_name, &req, now_second())?;
+
+    // Creating a stream again with an identical config is a no-op.
+    if let Some(existing) = ctx.cache_manager.get_stream(&tenant, stream_name) {
+        let same = NatsStream {
+            create_time: existing.create_time,
+            ..stream.clone()
+        } == existing;
+        if !same {
+            return Err(js_stream_name_exist());
+        }
+        return stream_info_response(ctx, &existing, kind).await;
+    }
+
+    check_stream_limit(ctx, &tenant)?;
+    check_subject_overlap(ctx, &stream)?;
+    try_get_or_init_stream_topic(
+        &ctx.cache_manager,
+        &ctx.storage_driver_manager,
+        &ctx.client_pool,
+        &stream,
+    )
+    .await?;
+    NatsStreamStorage::new(ctx.client_pool.clone())
+        .set(&stream)
+        .await?;
+    ctx.cache_manager.add_stream(stream.clone());
+    notify_stream_action(ctx, &stream.name, AdvisoryAction::Create).await;
+
+    stream_info_response(ctx, &stream, kind).await
 }
 
 /// `$JS.API.STREAM.UPDATE.<stream>`
 pub async fn process_stream_update(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: StreamCreateRequest,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    req: StreamCreateRequest,
 ) -> Result<StreamInfoResponse, NatsBrokerError> {
-    todo!("STREAM.UPDATE")
+    let existing = load_stream(ctx, stream_name)?;
+    if !req.name.is_empty() && req.name != stream_name {
+        return Err(js_stream_mismatch());
+    }
+    let stream = build_stream(&existing.tenant, stream_name, &req, existing.create_time)?;
+    if stream.storage != existing.storage {
+        return Err(js_stream_update_failed(
+            "stream configuration update can not change storage type",
+        ));
+    }
+    if stream.retention != existing.retention {
+        return Err(js_stream_update_failed(
+            "stream configuration update can not change retention policy",
+        ));
+    }
+
+    check_subject_overlap(ctx, &stream)?;
+    NatsStreamStorage::new(ctx.client_pool.clone())
+        .set(&stream)
+        .await?;
+    ctx.cache_manager.add_stream(stream.clone());
+    notify_stream_action(ctx, &stream.name, AdvisoryAction::Modify).await;
+
+    stream_info_response(
+        ctx,
+        &stream,
+        "io.nats.jetstream.api.v1.stream_update_response",
+    )
+    .await
 }
 
 /// `$JS.API.STREAM.DELETE.<stream>`
 pub async fn process_stream_delete(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
 ) -> Result<StreamDeleteResponse, NatsBrokerError> {
-    todo!("STREAM.DELETE")
+    let stream = load_stream(ctx, stream_name)?;
+    let topic_name = stream.topic_name();
+
+    for consumer in ctx
+        .cache_manager
+        .list_consumers(&stream.tenant, &stream.name)
+    {
+        if let Err(e) = delete_consumer(ctx, &stream, &consumer).await {
+            warn!(
+                "JetStream stream delete: failed to remove consumer {} of {}: {}",
+                consumer.name, stream.name, e
+            );
+        }
+    }
+
+    // The storage has to go first: resolving its shards needs the topic
+    // metadata that is removed right after.
+    if let Err(e) = ctx
+        .storage_driver_manager
+        .delete_storage_resource(&stream.tenant, &topic_name)
+        .await
+    {
+        warn!(
+            "JetStream stream delete: failed to remove storage of {}: {}",
+            stream.name, e
+        );
+    }
+    if let Err(e) = TopicStorage::new(ctx.client_pool.clone())
+        .delete_topic(&stream.tenant, &topic_name)
+        .await
+    {
+        warn!(
+            "JetStream stream delete: failed to remove topic of {}: {}",
+            stream.name, e
+        );
+    }
+
+    NatsStreamStorage::new(ctx.client_pool.clone())
+        .delete(&stream.tenant, &stream.name)
+        .await?;
+    ctx.cache_manager
+        .remove_stream(&stream.tenant, &stream.name);
+    notify_stream_action(ctx, &stream.name, AdvisoryAction::Delete).await;
+
+    Ok(StreamDeleteResponse {
+        kind: "io.nats.jetstream.api.v1.stream_delete_response".to_string(),
+        success: true,
+        error: None,
+    })
 }
 
 /// `$JS.API.STREAM.INFO.<stream>`
 pub async fn process_stream_info(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
 ) -> Result<StreamInfoResponse, NatsBrokerError> {
-    todo!("STREAM.INFO")
+    let stream = load_stream(ctx, stream_name)?;
+    stream_info_response(
+        ctx,
+        &stream,
+        "io.nats.jetstream.api.v1.stream_info_response",
+    )
+    .await
 }
 
 /// `$JS.API.STREAM.LIST`
 pub async fn process_stream_list(
-    _ctx: &NatsProcessContext,
-    _req: StreamListRequest,
+    ctx: &NatsProcessContext,
+    req: StreamListRequest,
 ) -> Result<StreamListResponse, NatsBrokerError> {
-    todo!("STREAM.LIST")
+    let streams = ctx.cache_manager.list_streams(&get_tenant());
+    let total = streams.len() as u64;
+    let offset = req.offset.unwrap_or(0);
+
+    let mut infos = Vec::new();
+    for stream in streams
+        .iter()
+        .skip(offset as usize)
+        .take(STREAM_LIST_LIMIT as usize)
+    {
+        infos.push(stream_info(ctx, stream).await?);
+    }
+
+    Ok(StreamListResponse {
+        kind: "io.nats.jetstream.api.v1.stream_list_response".to_string(),
+        page: PageInfo {
+            total,
+            offset,
+            limit: STREAM_LIST_LIMIT,
+        },
+        streams: infos,
+        error: None,
+    })
 }
 
 /// `$JS.API.STREAM.NAMES`
 pub async fn process_stream_names(
-    _ctx: &NatsProcessContext,
-    _req: StreamListRequest,
+    ctx: &NatsProcessContext,
+    req: StreamListRequest,
 ) -> Result<StreamNamesResponse, NatsBrokerError> {
-    todo!("STREAM.NAMES")
+    let streams = ctx.cache_manager.list_streams(&get_tenant());
+    let total = streams.len() as u64;
+    let offset = req.offset.unwrap_or(0);
+
+    Ok(StreamNamesResponse {
+        kind: "io.nats.jetstream.api.v1.stream_names_response".to_string(),
+        page: PageInfo {
+            total,
+            offset,
+            limit: STREAM_NAMES_LIMIT,
+        },
+        streams: streams
+            .into_iter()
+            .skip(offset as usize)
+            .take(STREAM_NAMES_LIMIT as usize)
+            .map(|s| s.name)
+            .collect(),
+        error: None,
+    })
 }
 
 /// `$JS.API.STREAM.PURGE.<stream>`
 pub async fn process_stream_purge(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: StreamPurgeRequest,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    req: StreamPurgeRequest,
 ) -> Result<StreamPurgeResponse, NatsBrokerError> {
-    todo!("STREAM.PURGE")
+    let stream = load_stream(ctx, stream_name)?;
+    if stream.deny_purge {
+        return Err(js_stream_general_error("stream purge not permitted"));
+    }
+    if req.seq.is_some() && req.keep.is_some() {
+        return Err(js_bad_request(
+            "sequence and keep can not both be set in a purge request",
+        ));
+    }
+
+    let state = ctx
+        .cache_manager
+        .get_stream_state(&stream.tenant, &stream.name);
+    let purged = purge_messages(
+        &ctx.storage_driver_manager,
+        &stream,
+        &mut *state.lock().await,
+        req.filter.as_deref(),
+        req.seq,
+        req.keep,
+    )
+    .await?;
+
+    Ok(StreamPurgeResponse {
+        kind: "io.nats.jetstream.api.v1.stream_purge_response".to_string(),
+        success: true,
+        purged,
+        error: None,
+    })
 }
 
 /// `$JS.API.STREAM.MSG.GET.<stream>`
 pub async fn process_stream_msg_get(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: StreamMsgGetRequest,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    req: StreamMsgGetRequest,
 ) -> Result<StreamMsgGetResponse, NatsBrokerError> {
-    todo!("STREAM.MSG.GET")
+    let stream = load_stream(ctx, stream_name)?;
+    let sdm = &ctx.storage_driver_manager;
+
+    let message = if let Some(subject) = &req.last_by_subj {
+        last_message_by_subject(sdm, &stream, subject).await?
+    } else if let Some(subject) = &req.next_by_subj {
+        next_message_by_subject(sdm, &stream, subject, req.seq.unwrap_or(1)).await?
+    } else if let Some(seq) = req.seq {
+        get_message(sdm, &stream, seq).await?
+    } else {
+        return Err(js_bad_request("request is missing seq or subject"));
+    };
+    let message = message.ok_or_else(js_no_message_found)?;
+
+    Ok(StreamMsgGetResponse {
+        kind: "io.nats.jetstream.api.v1.stream_msg_get_response".to_string(),
+        message: raw_message(message),
+        error: None,
+    })
 }
 
 /// `$JS.API.STREAM.MSG.DELETE.<stream>`
 pub async fn process_stream_msg_delete(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
-    _req: StreamMsgDeleteRequest,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+    req: StreamMsgDeleteRequest,
 ) -> Result<StreamMsgDeleteResponse, NatsBrokerError> {
-    todo!("STREAM.MSG.DELETE")
+    let stream = load_stream(ctx, stream_name)?;
+    if stream.deny_delete {
+        return Err(js_stream_msg_delete_failed("message delete not permitted"));
+    }
+    let state = ctx
+        .cache_manager
+        .get_stream_state(&stream.tenant, &stream.name);
+    let deleted = delete_message(
+        &ctx.storage_driver_manager,
+        &stream,
+        &mut *state.lock().await,
+        req.seq,
+    )
+    .await?;
+    if !deleted {
+        return Err(js_stream_msg_delete_failed("no message found"));
+    }
+
+    Ok(StreamMsgDeleteResponse {
+        kind: "io.nats.jetstream.api.v1.stream_msg_delete_response".to_string(),
+        success: true,
+        error: None,
+    })
 }
 
 /// `$JS.API.STREAM.SNAPSHOT.<stream>`
 pub async fn process_stream_snapshot(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
     _req: StreamSnapshotRequest,
 ) -> Result<StreamLeaderResponse, NatsBrokerError> {
-    todo!("STREAM.SNAPSHOT")
+    load_stream(ctx, stream_name)?;
+    Err(js_not_supported("stream snapshot"))
 }
 
 /// `$JS.API.STREAM.RESTORE.<stream>`
 pub async fn process_stream_restore(
     _ctx: &NatsProcessContext,
-    _stream: &str,
+    _stream_name: &str,
 ) -> Result<StreamLeaderResponse, NatsBrokerError> {
-    todo!("STREAM.RESTORE")
+    Err(js_not_supported("stream restore"))
 }
 
 /// `$JS.API.STREAM.LEADER.STEPDOWN.<stream>`
+///
+/// Stream storage is replicated by the storage engine, so there is no
+/// per-stream leader to step down.
 pub async fn process_stream_leader_stepdown(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
 ) -> Result<StreamLeaderResponse, NatsBrokerError> {
-    todo!("STREAM.LEADER.STEPDOWN")
+    load_stream(ctx, stream_name)?;
+    Err(js_not_supported("stream leader stepdown"))
 }
 
 /// `$JS.API.STREAM.PEER.REMOVE.<stream>`
 pub async fn process_stream_peer_remove(
-    _ctx: &NatsProcessContext,
-    _stream: &str,
+    ctx: &NatsProcessContext,
+    stream_name: &str,
     _req: StreamPeerRemoveRequest,
 ) -> Result<StreamLeaderResponse, NatsBrokerError> {
-    todo!("STREAM.PEER.REMOVE")
+    load_stream(ctx, stream_name)?;
+    Err(js_not_supported("stream peer remove"))
+}
+
+/// Stores a message published on `subject` into every stream capturing it and,
+/// when the publisher asked for a reply, answers with the JetStream PubAck.
+pub async fn capture_stream_message(
+    ctx: &NatsProcessContext,
+    tenant: &str,
+    subject: &str,
+    reply_to: Option<&str>,
+    headers: &Option<Bytes>,
+    payload: &Bytes,
+) -> Result<(), NatsBrokerError> {
+    for stream in ctx.cache_manager.match_streams(tenant, subject) {
+        let result = publish_to_stream(ctx, &stream, subject, headers, payload).await;
+
+        let Some(reply_subject) = reply_to else {
+            result?;
+            continue;
+        };
+        let body = match result {
+            Ok(seq) => Bytes::from(serde_json::to_vec(&PubAckResponse {
+                stream: stream.name.clone(),
+                seq,
+                duplicate: false,
+            })?),
+            Err(e) => js_error_body(&e),
+        };
+        reply_nats_packet(ctx, reply_subject, body).await?;
+    }
+    Ok(())
+}
+
+/// Stores a message in `stream`, one write at a time per stream.
+pub async fn publish_to_stream(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    subject: &str,
+    headers: &Option<Bytes>,
+    payload: &Bytes,
+) -> Result<u64, NatsBrokerError> {
+    let state = ctx
+        .cache_manager
+        .get_stream_state(&stream.tenant, &stream.name);
+    let mut counters = state.lock().await;
+    store_message(
+        &ctx.storage_driver_manager,
+        stream,
+        &mut counters,
+        subject,
+        headers,
+        payload,
+    )
+    .await
+}
+
+/// Looks up a stream of the current tenant, failing with "stream not found".
+pub fn load_stream(
+    ctx: &NatsProcessContext,
+    stream_name: &str,
+) -> Result<NatsStream, NatsBrokerError> {
+    ctx.cache_manager
+        .get_stream(&get_tenant(), stream_name)
+        .ok_or_else(js_stream_not_found)
+}
+
+pub async fn stream_info(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+) -> Result<StreamInfo, NatsBrokerError> {
+    let state = ctx
+        .cache_manager
+        .get_stream_state(&stream.tenant, &stream.name);
+    let stats = stream_stats(
+        &ctx.storage_driver_manager,
+        stream,
+        &mut *state.lock().await,
+    )
+    .await?;
+    Ok(StreamInfo {
+        config: stream_config(stream),
+        state: StreamState {
+            messages: stats.messages,
+            bytes: stats.bytes,
+            first_seq: stats.first_seq,
+            last_seq: stats.last_seq,
+            consumer_count: ctx
+                .cache_manager
+                .list_consumers(&stream.tenant, &stream.name)
+                .len() as u64,
+        },
+        created: format_time(stream.create_time),
+        cluster: None,
+    })
+}
+
+async fn stream_info_response(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+    kind: &str,
+) -> Result<StreamInfoResponse, NatsBrokerError> {
+    Ok(StreamInfoResponse {
+        kind: kind.to_string(),
+        info: stream_info(ctx, stream).await?,
+        error: None,
+    })
+}
+
+/// Formats a timestamp in seconds the way JetStream replies carry times.
+pub fn format_time(seconds: u64) -> String {
+    DateTime::from_timestamp(seconds as i64, 0)
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn raw_message(message: StreamMessage) -> RawMessage {
+    RawMessage {
+        subject: message.subject,
+        seq: message.seq,
+        data: BASE64.encode(&message.data),
+        time: format_time(message.timestamp),
+        headers: message.headers.map(|h| BASE64.encode(&h)),
+    }
+}
+
+fn check_subject_overlap(
+    ctx: &NatsProcessContext,
+    stream: &NatsStream,
+) -> Result<(), NatsBrokerError> {
+    let overlap = ctx
+        .cache_manager
+        .list_streams(&stream.tenant)
+        .iter()
+        .filter(|other| other.name != stream.name)
+        .any(|other| {
+            other
+                .subjects
+                .iter()
+                .any(|a| stream.subjects.iter().any(|b| subjects_overlap(a, b)))
+        });
+    if overlap {
+        return Err(js_stream_subject_overlap());
+    }
+    Ok(())
+}
+
+/// Whether some subject could match both `a` and `b`.
+pub(crate) fn subjects_overlap(a: &str, b: &str) -> bool {
+    let a: Vec<&str> = a.split('.').collect();
+    let b: Vec<&str> = b.split('.').collect();
+    for i in 0..a.len().max(b.len()) {
+        match (a.get(i), b.get(i)) {
+            (Some(&">"), Some(_)) | (Some(_), Some(&">")) => return true,
+            (Some(&"*"), Some(_)) | (Some(_), Some(&"*")) => continue,
+            (Some(x), Some(y)) if x == y => continue,
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn validate_stream_name(name: &str) -> Result<(), NatsBrokerError> {
+    if name.is_empty() {
+        return Err(js_stream_invalid_config("stream name is required"));
+    }
+    if name.contains(['.', '*', '>', '/', '\\', ' ', '\t']) {
+        return Err(js_stream_invalid_config(
+            "stream name can not contain whitespace, '.', '*', '>', path separators",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_subject(subject: &str) -> Result<(), NatsBrokerError> {
+    if !is_valid_subject(subject) {
+        return Err(js_stream_invalid_config(format!(
+            "invalid subject {}",
+            subject
+        )));
+    }
+    Ok(())
+}
+
+/// Non-empty tokens, no whitespace, `>` only as the last token.
+pub(crate) fn is_valid_subject(subject: &str) -> bool {
+    let tokens: Vec<&str> = subject.split('.').collect();
+    !subject.is_empty()
+        && !subject.contains([' ', '\t', '\r', '\n'])
+        && tokens.iter().all(|t| !t.is_empty())
+        && tokens
+            .iter()
+            .position(|t| *t == ">")
+            .is_none_or(|pos| pos == tokens.len() - 1)
+}
+
+/// `0` is accepted for the unlimited `-1` on limits, like nats-server does.
+pub(crate) fn normalize_limit(value: i64) -> i64 {
+    if value == 0 {
+        -1
+    } else {
+        value
+    }
+}
+
+fn build_stream(
+    tenant: &str,
+    name: &str,
+    config: &StreamConfig,
+    create_time: u64,
+) -> Result<NatsStream, NatsBrokerError> {
+    validate_stream_name(name)?;
+
+    let retention = NatsStreamRetention::parse(&config.retention).ok_or_else(|| {
+        js_stream_invalid_config(format!("invalid retention policy {}", config.retention))
+    })?;
+    let storage = StreamStorageType::parse(&config.storage).ok_or_else(|| {
+        js_stream_invalid_config(format!("invalid storage type {}", config.storage))
+    })?;
+    let discard = NatsStreamDiscard::parse(&config.discard).ok_or_else(|| {
+        js_stream_invalid_config(format!("invalid discard policy {}", config.discard))
+    })?;
+
+    let subjects = if config.subjects.is_empty() {
+        vec![name.to_string()]
+    } else {
+        config.subjects.clone()
+    };
+    for (i, subject) in subjects.iter().enumerate() {
+        validate_subject(subject)?;
+        if subjects[..i].contains(subject) {
+            return Err(js_stream_invalid_config("duplicate subjects detected"));
+        }
+    }
+
+    let num_replicas = config.num_replicas.max(1);
+    if num_replicas > MAX_STREAM_REPLICAS {
+        return Err(js_stream_invalid_config(format!(
+            "maximum replicas is {}",
+            MAX_STREAM_REPLICAS
+        )));
+    }
+
+    let duplicate_window = match config.duplicate_window {
+        Some(window) if window > 0 => window,
+        _ if config.max_age > 0 => DEFAULT_DUPLICATE_WINDOW.min(config.max_age),
+        _ => DEFAULT_DUPLICATE_WINDOW,
+    };
+    if config.max_age > 0 && duplicate_window > config.max_age {
+        return Err(js_stream_invalid_config(
+            "duplicates window can not be larger then max age",
+        ));
+    }
+
+    Ok(NatsStream {
+        tenant: tenant.to_string(),
+        name: name.to_string(),
+        description: config.description.clone(),
+        subjects,
+        retention,
+        storage,
+        discard,
+        max_msgs: normalize_limit(config.max_msgs),
+        max_bytes: normalize_limit(config.max_bytes),
+        max_age: config.max_age,
+        max_msgs_per_subject: normalize_limit(config.max_msgs_per_subject),
+        max_msg_size: normalize_limit(config.max_msg_size),
+        num_replicas,
+        duplicate_window,
+        deny_delete: config.deny_delete,
+        deny_purge: config.deny_purge,
+        allow_rollup_hdrs: config.allow_rollup_hdrs,
+        allow_msg_ttl: config.allow_msg_ttl,
+        allow_direct: config.allow_direct,
+        create_time,
+    })
+}
+
+pub fn stream_config(stream: &NatsStream) -> StreamConfig {
+    StreamConfig {
+        name: stream.name.clone(),
+        subjects: stream.subjects.clone(),
+        storage: stream.storage.as_str().to_string(),
+        retention: stream.retention.as_str().to_string(),
+        max_msgs: stream.max_msgs,
+        max_bytes: stream.max_bytes,
+        max_age: stream.max_age,
+        num_replicas: stream.num_replicas,
+        description: stream.description.clone(),
+        deny_delete: stream.deny_delete,
+        deny_purge: stream.deny_purge,
+        allow_rollup_hdrs: stream.allow_rollup_hdrs,
+        allow_msg_ttl: stream.allow_msg_ttl,
+        allow_direct: stream.allow_direct,
+        max_msgs_per_subject: stream.max_msgs_per_subject,
+        max_msg_size: stream.max_msg_size,
+        discard: stream.discard.as_str().to_string(),
+        duplicate_window: Some(stream.duplicate_window),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str, subjects: &[&str]) -> StreamConfig {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "subjects": subjects,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_subjects_overlap() {
+        assert!(subjects_overlap("orders.*", "orders.new"));
+        assert!(subjects_overlap("orders.>", "orders.eu.new"));
+        assert!(subjects_overlap("*.new", "orders.*"));
+        assert!(subjects_overlap(">", "anything"));
+        assert!(!subjects_overlap("orders.*", "orders.eu.new"));
+        assert!(!subjects_overlap("orders.new", "orders.old"));
+        assert!(!subjects_overlap("orders", "orders.>"));
+    }
+
+    #[test]
+    fn test_build_stream_defaults() {
+        let stream = build_stream("default", "ORDERS", &config("ORDERS", &[]), 1).unwrap();
+        assert_eq!(stream.subjects, vec!["ORDERS".to_string()]);
+        assert_eq!(stream.retention, NatsStreamRetention::Limits);
+        assert_eq!(stream.storage, StreamStorageType::File);
+        assert_eq!(stream.discard, NatsStreamDiscard::Old);
+        assert_eq!(stream.max_msgs, -1);
+        assert_eq!(stream.num_replicas, 1);
+        assert_eq!(stream.duplicate_window, DEFAULT_DUPLICATE_WINDOW);
+
+        let round_trip = build_stream("default", "ORDERS", &stream_config(&stream), 1).unwrap();
+        assert_eq!(stream, round_trip);
+    }
+
+    #[test]
+    fn test_build_stream_rejects_invalid_config() {
+        assert!(build_stream("default", "a.b", &config("a.b", &[]), 1).is_err());
+        assert!(build_stream("default", "S", &config("S", &["a.>.b"]), 1).is_err());
+        assert!(build_stream("default", "S", &config("S", &["a", "a"]), 1).is_err());
+
+        let mut bad_retention = config("S", &["a"]);
+        bad_retention.retention = "forever".to_string();
+        assert!(build_stream("default", "S", &bad_retention, 1).is_err());
+
+        let mut too_many_replicas = config("S", &["a"]);
+        too_many_replicas.num_replicas = 7;
+        assert!(build_stream("default", "S", &too_many_replicas, 1).is_err());
+    }
 }
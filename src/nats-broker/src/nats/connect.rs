@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use common_config::broker::broker_config;
+use common_security::login::x509::peer_cert_username;
 use protocol::nats::packet::{ClientConnect, NatsPacket};
 
 use crate::core::connection::NatsConnection;
@@ -24,13 +25,20 @@
 pub fn process_connect(ctx: &NatsProcessContext, req: &ClientConnect) -> Result<(), NatsPacket> {
     let auth_required = broker_config().nats_runtime.auth_required;
 
-    let authed = login_check(
-        &ctx.security_manager,
-        &get_tenant(),
-        auth_required,
-        req.user.as_deref(),
-        req.pass.as_deref(),
-    );
+    // A verified client certificate mapped to a user replaces user/pass.
+    let cert_user = ctx
+        .connection_manager
+        .get_connect(ctx.connect_id)
+        .and_then(|connection| peer_cert_username(connection.peer_cert.as_ref()));
+
+    let authed = cert_user.is_some()
+        || login_check(
+            &ctx.security_manager,
+            &get_tenant(),
+            auth_required,
+            req.user.as_deref(),
+            req.pass.as_deref(),
+        );
 
     if !authed {
         return Err(NatsPacket::Err(
@@ -49,7 +57,9 @@ pub fn process_connect(ctx: &NatsProcessContext, req: &ClientConnect) -> Result<
     connection.lang = req.lang.clone();
     connection.version = req.version.clone();
 
-    if auth_required {
+    if let Some(user) = cert_user {
+        connection.login_success(user);
+    } else if auth_required {
         connection.login_success(req.user.clone().unwrap_or_default());
     }
 
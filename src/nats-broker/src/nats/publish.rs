@@ -13,18 +13,25 @@
 // limitations under the License.
 
 use crate::core::error::{NatsBrokerError, NatsProtocolError};
-use crate::core::subject::{is_inbox_subject, try_get_or_init_subject};
+use crate::core::subject::{is_inbox_subject, try_get_or_init_subject, NatsRuleSubjectInit};
 use crate::core::tenant::get_tenant;
 use crate::handler::command::NatsProcessContext;
+use crate::jstream::command::JsCommand;
+use crate::jstream::process::js_command;
+use crate::jstream::stream::capture_stream_message;
 use crate::mq9::process::mq9_command;
 use crate::nats::subscribe::subject_message_tag;
 use crate::storage::message::MessageStorage;
 use bytes::Bytes;
+use common_base::tools::now_millis;
 use common_config::broker::broker_config;
 use metadata_struct::storage::adapter_record::AdapterWriteRecord;
 use metadata_struct::storage::record::{StorageRecordProtocolData, StorageRecordProtocolDataNats};
 use mq9_core::command::Mq9Command;
 use protocol::nats::packet::NatsPacket;
+use rule_engine::sql::RuleMessage;
+use schema_register::schema::dead_letter_record;
+use tracing::{debug, warn};
 
 /// Returns `Ok(None)` for normal commands (verbose handled by caller),
 /// `Ok(Some(packet))` for server-initiated replies (e.g. mq9 reply-to MSG),
@@ -58,6 +65,18 @@ pub async fn process_pub(
         return Ok(pkt);
     }
 
+    if JsCommand::is_js_api_subject(subject)
+        || JsCommand::is_js_ack_subject(subject)
+        || JsCommand::is_obj_subject(subject)
+    {
+        // JetStream API requests, acks and object store requests are answered
+        // on reply_to by js_command itself
+        let pkt = js_command(ctx, subject, reply_to, headers, payload).await;
+        return Ok(pkt);
+    }
+
+    check_payload_schema(ctx, subject, payload).await?;
+
     process_pub0(ctx, subject, reply_to, payload, headers)
         .await
         .map_err(|e| NatsPacket::Err(e.to_string()))?;
@@ -65,7 +84,43 @@ pub async fn process_pub(
     Ok(None)
 }
 
-async fn process_pub0(
+/// Rejects a payload that doesn't match the schemas bound to the subject.
+/// The payload goes to the dead-letter topic first, if one is configured.
+async fn check_payload_schema(
+    ctx: &NatsProcessContext,
+    subject: &str,
+    payload: &Bytes,
+) -> Result<(), NatsPacket> {
+    let tenant = get_tenant();
+    let Err(e) = ctx
+        .schema_manager
+        .validate_payload(&tenant, subject, payload, false)
+    else {
+        return Ok(());
+    };
+    debug!("NATS publish to {} rejected by schema: {}", subject, e);
+
+    let dead_letter_topic = &broker_config().nats_runtime.schema_dead_letter_topic;
+    if !dead_letter_topic.is_empty() {
+        let record =
+            dead_letter_record(dead_letter_topic, "nats", subject, &e.to_string(), payload);
+        if let Err(err) = ctx
+            .storage_driver_manager
+            .write(&tenant, dead_letter_topic, &[record], 1)
+            .await
+        {
+            warn!(
+                "NATS publish failed to write an invalid payload for {} to dead-letter topic {}: {}",
+                subject, dead_letter_topic, err
+            );
+        }
+    }
+    Err(NatsPacket::Err(
+        NatsProtocolError::SchemaValidationFailed(subject.to_string()).message(),
+    ))
+}
+
+pub(crate) async fn process_pub0(
     ctx: &NatsProcessContext,
     subject: &str,
     reply_to: Option<&str>,
@@ -98,5 +153,33 @@ async fn process_pub0(
             ..Default::default()
         }));
     let _offset = message.write(&tenant, subject, vec![record]).await?;
+
+    capture_stream_message(ctx, &tenant, subject, reply_to, header, payload).await?;
+
+    if ctx.rule_manager.is_match_topic(&tenant, subject) {
+        let connection = ctx.cache_manager.get_connection(ctx.connect_id);
+        let message = RuleMessage {
+            tenant: tenant.clone(),
+            protocol: "nats".to_string(),
+            topic: subject.to_string(),
+            client_id: connection
+                .as_ref()
+                .map(|c| c.client_name.clone())
+                .unwrap_or_default(),
+            username: connection.and_then(|c| c.login_user),
+            qos: 0,
+            payload: payload.clone(),
+            timestamp: now_millis() as u64,
+        };
+        let subject_init = NatsRuleSubjectInit {
+            cache_manager: &ctx.cache_manager,
+            storage_driver_manager: &ctx.storage_driver_manager,
+            client_pool: &ctx.client_pool,
+            subscribe_manager: &ctx.subscribe_manager,
+        };
+        ctx.rule_manager
+            .process(&ctx.storage_driver_manager, &subject_init, &message)
+            .await;
+    }
     Ok(())
 }
@@ -0,0 +1,88 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use common_config::broker::broker_config;
+use grpc_clients::meta::nats::call::{
+    placement_delete_nats_consumer, placement_list_nats_consumer, placement_set_nats_consumer,
+};
+use grpc_clients::pool::ClientPool;
+use metadata_struct::nats::consumer::NatsConsumer;
+use protocol::meta::meta_service_nats::{
+    DeleteNatsConsumerRequest, ListNatsConsumerRequest, SetNatsConsumerRequest,
+};
+use std::sync::Arc;
+use tonic::Streaming;
+
+pub struct NatsConsumerStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl NatsConsumerStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        NatsConsumerStorage { client_pool }
+    }
+
+    pub async fn set(&self, consumer: &NatsConsumer) -> Result<(), CommonError> {
+        let config = broker_config();
+        let request = SetNatsConsumerRequest {
+            tenant: consumer.tenant.clone(),
+            content: consumer.encode()?,
+        };
+        placement_set_nats_consumer(&self.client_pool, &config.get_meta_service_addr(), request)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(
+        &self,
+        tenant: &str,
+        stream_name: &str,
+        consumer_name: &str,
+    ) -> Result<(), CommonError> {
+        let config = broker_config();
+        let request = DeleteNatsConsumerRequest {
+            tenant: tenant.to_string(),
+            stream_name: stream_name.to_string(),
+            consumer_name: consumer_name.to_string(),
+        };
+        placement_delete_nats_consumer(&self.client_pool, &config.get_meta_service_addr(), request)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list(
+        &self,
+        tenant: &str,
+        stream_name: &str,
+    ) -> Result<Vec<NatsConsumer>, CommonError> {
+        let config = broker_config();
+        let request = ListNatsConsumerRequest {
+            tenant: tenant.to_string(),
+            stream_name: stream_name.to_string(),
+        };
+        let mut stream: Streaming<_> = placement_list_nats_consumer(
+            &self.client_pool,
+            &config.get_meta_service_addr(),
+            request,
+        )
+        .await?;
+
+        let mut results = Vec::new();
+        while let Some(reply) = stream.message().await? {
+            results.push(NatsConsumer::decode(&reply.consumer)?);
+        }
+        Ok(results)
+    }
+}
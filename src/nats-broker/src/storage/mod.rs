@@ -13,6 +13,8 @@
 // limitations under the License.
 
 pub mod agent;
+pub mod consumer;
 pub mod mail;
 pub mod message;
+pub mod stream;
 pub mod subscribe;
@@ -0,0 +1,74 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use common_config::broker::broker_config;
+use grpc_clients::meta::nats::call::{
+    placement_delete_nats_stream, placement_list_nats_stream, placement_set_nats_stream,
+};
+use grpc_clients::pool::ClientPool;
+use metadata_struct::nats::stream::NatsStream;
+use protocol::meta::meta_service_nats::{
+    DeleteNatsStreamRequest, ListNatsStreamRequest, SetNatsStreamRequest,
+};
+use std::sync::Arc;
+use tonic::Streaming;
+
+pub struct NatsStreamStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl NatsStreamStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        NatsStreamStorage { client_pool }
+    }
+
+    pub async fn set(&self, stream: &NatsStream) -> Result<(), CommonError> {
+        let config = broker_config();
+        let request = SetNatsStreamRequest {
+            tenant: stream.tenant.clone(),
+            content: stream.encode()?,
+        };
+        placement_set_nats_stream(&self.client_pool, &config.get_meta_service_addr(), request)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, tenant: &str, stream_name: &str) -> Result<(), CommonError> {
+        let config = broker_config();
+        let request = DeleteNatsStreamRequest {
+            tenant: tenant.to_string(),
+            stream_name: stream_name.to_string(),
+        };
+        placement_delete_nats_stream(&self.client_pool, &config.get_meta_service_addr(), request)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list(&self, tenant: &str) -> Result<Vec<NatsStream>, CommonError> {
+        let config = broker_config();
+        let request = ListNatsStreamRequest {
+            tenant: tenant.to_string(),
+        };
+        let mut stream: Streaming<_> =
+            placement_list_nats_stream(&self.client_pool, &config.get_meta_service_addr(), request)
+                .await?;
+
+        let mut results = Vec::new();
+        while let Some(reply) = stream.message().await? {
+            results.push(NatsStream::decode(&reply.stream)?);
+        }
+        Ok(results)
+    }
+}
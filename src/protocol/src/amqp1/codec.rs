@@ -0,0 +1,221 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::frame::{Amqp1Frame, Amqp1ProtocolHeader, Performative, SaslPerformative};
+use super::types::{decode_value, encode_value};
+use super::Amqp1CodecError;
+
+/// Largest frame the broker accepts, advertised as its max-frame-size.
+pub const MAX_FRAME_SIZE: u32 = 1024 * 1024;
+
+/// Every frame's size, data offset, type and channel: the 8-byte header.
+pub const FRAME_HEADER_SIZE: usize = 8;
+const FRAME_TYPE_AMQP: u8 = 0;
+const FRAME_TYPE_SASL: u8 = 1;
+
+/// Whether `src` opens with an AMQP 1.0 protocol header (plain or SASL),
+/// as opposed to AMQP 0-9-1's `AMQP\0\0\x09\x01`. Needs 6 bytes.
+pub fn is_amqp1_header(src: &[u8]) -> bool {
+    src.len() >= 6 && &src[..4] == b"AMQP" && matches!(src[4], 0 | 3) && src[5] == 1
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Amqp1Codec {}
+
+impl Amqp1Codec {
+    pub fn new() -> Self {
+        Amqp1Codec {}
+    }
+
+    pub fn decode_data(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<Amqp1Frame>, Amqp1CodecError> {
+        if src.len() < FRAME_HEADER_SIZE {
+            return Ok(None);
+        }
+
+        if &src[..4] == b"AMQP" {
+            let mut raw = [0u8; 8];
+            raw.copy_from_slice(&src[..8]);
+            let header = Amqp1ProtocolHeader::from_protocol_id(raw[4])
+                .filter(|_| raw[5..] == [1, 0, 0])
+                .ok_or(Amqp1CodecError::UnsupportedHeader(raw))?;
+            src.advance(8);
+            return Ok(Some(Amqp1Frame::Header(header)));
+        }
+
+        let size = u32::from_be_bytes([src[0], src[1], src[2], src[3]]);
+        if (size as usize) < FRAME_HEADER_SIZE || size > MAX_FRAME_SIZE {
+            return Err(Amqp1CodecError::FrameSize(size));
+        }
+        let size = size as usize;
+        if src.len() < size {
+            src.reserve(size - src.len());
+            return Ok(None);
+        }
+
+        let frame = src.split_to(size).freeze();
+        let data_offset = frame[4] as usize * 4;
+        let frame_type = frame[5];
+        let channel = u16::from_be_bytes([frame[6], frame[7]]);
+        if data_offset < FRAME_HEADER_SIZE || data_offset > size {
+            return Err(Amqp1CodecError::Malformed(format!(
+                "data offset {data_offset} outside frame of {size} bytes"
+            )));
+        }
+        let body = frame.slice(data_offset..);
+        if body.is_empty() {
+            return Ok(Some(Amqp1Frame::Heartbeat));
+        }
+
+        let mut cursor = &body[..];
+        let value = decode_value(&mut cursor)?;
+        match frame_type {
+            FRAME_TYPE_AMQP => {
+                let performative = Performative::decode(value)?;
+                let payload = body.slice(body.len() - cursor.len()..);
+                Ok(Some(Amqp1Frame::Amqp {
+                    channel,
+                    performative,
+                    payload,
+                }))
+            }
+            FRAME_TYPE_SASL => Ok(Some(Amqp1Frame::Sasl(SaslPerformative::decode(value)?))),
+            other => Err(Amqp1CodecError::Malformed(format!(
+                "unknown frame type {other}"
+            ))),
+        }
+    }
+
+    pub fn encode_data(
+        &mut self,
+        frame: Amqp1Frame,
+        dst: &mut BytesMut,
+    ) -> Result<(), Amqp1CodecError> {
+        match frame {
+            Amqp1Frame::Header(header) => {
+                dst.put_slice(b"AMQP");
+                dst.put_slice(&[header.protocol_id(), 1, 0, 0]);
+            }
+            Amqp1Frame::Heartbeat => put_frame(dst, FRAME_TYPE_AMQP, 0, &[], &Bytes::new()),
+            Amqp1Frame::Amqp {
+                channel,
+                performative,
+                payload,
+            } => {
+                let mut body = BytesMut::new();
+                encode_value(&performative.encode(), &mut body);
+                put_frame(dst, FRAME_TYPE_AMQP, channel, &body, &payload);
+            }
+            Amqp1Frame::Sasl(sasl) => {
+                let mut body = BytesMut::new();
+                encode_value(&sasl.encode(), &mut body);
+                put_frame(dst, FRAME_TYPE_SASL, 0, &body, &Bytes::new());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn put_frame(dst: &mut BytesMut, frame_type: u8, channel: u16, body: &[u8], payload: &Bytes) {
+    let size = FRAME_HEADER_SIZE + body.len() + payload.len();
+    dst.reserve(size);
+    dst.put_u32(size as u32);
+    dst.put_u8((FRAME_HEADER_SIZE / 4) as u8);
+    dst.put_u8(frame_type);
+    dst.put_u16(channel);
+    dst.put_slice(body);
+    dst.put_slice(payload);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amqp1::frame::{Begin, SaslMechanisms, Transfer};
+
+    fn round_trip(frame: Amqp1Frame) {
+        let mut codec = Amqp1Codec::new();
+        let mut buf = BytesMut::new();
+        codec.encode_data(frame.clone(), &mut buf).unwrap();
+        assert_eq!(codec.decode_data(&mut buf).unwrap(), Some(frame));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        round_trip(Amqp1Frame::Header(Amqp1ProtocolHeader::Sasl));
+        round_trip(Amqp1Frame::Header(Amqp1ProtocolHeader::Amqp));
+        round_trip(Amqp1Frame::Heartbeat);
+        round_trip(Amqp1Frame::amqp(
+            3,
+            Performative::Begin(Begin {
+                remote_channel: Some(0),
+                next_outgoing_id: 0,
+                incoming_window: 2048,
+                outgoing_window: 2048,
+                handle_max: None,
+            }),
+        ));
+        round_trip(Amqp1Frame::Amqp {
+            channel: 1,
+            performative: Performative::Transfer(Transfer {
+                handle: 0,
+                delivery_id: Some(0),
+                delivery_tag: Some(vec![0]),
+                ..Default::default()
+            }),
+            payload: Bytes::from_static(b"message bytes"),
+        });
+        round_trip(Amqp1Frame::Sasl(SaslPerformative::Mechanisms(
+            SaslMechanisms {
+                mechanisms: vec!["PLAIN".to_string()],
+            },
+        )));
+    }
+
+    #[test]
+    fn waits_for_a_whole_frame() {
+        let mut codec = Amqp1Codec::new();
+        let mut buf = BytesMut::new();
+        codec.encode_data(Amqp1Frame::Heartbeat, &mut buf).unwrap();
+        let mut partial = BytesMut::from(&buf[..5]);
+        assert_eq!(codec.decode_data(&mut partial).unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_oversized_frames_and_foreign_headers() {
+        let mut codec = Amqp1Codec::new();
+        let mut buf = BytesMut::new();
+        buf.put_u32(MAX_FRAME_SIZE + 1);
+        buf.put_slice(&[2, 0, 0, 0]);
+        assert!(matches!(
+            codec.decode_data(&mut buf),
+            Err(Amqp1CodecError::FrameSize(_))
+        ));
+
+        let mut buf = BytesMut::from(&b"AMQP\x00\x00\x09\x01"[..]);
+        assert!(codec.decode_data(&mut buf).is_err());
+    }
+
+    #[test]
+    fn detects_amqp1_headers_only() {
+        assert!(is_amqp1_header(b"AMQP\x00\x01\x00\x00"));
+        assert!(is_amqp1_header(b"AMQP\x03\x01\x00\x00"));
+        assert!(!is_amqp1_header(b"AMQP\x00\x00\x09\x01"));
+        assert!(!is_amqp1_header(b"AMQ"));
+    }
+}
@@ -0,0 +1,980 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! AMQP 1.0 frames (spec part 2) and the performatives they carry. Only the
+//! fields the broker acts on are modelled; the rest decode as defaults and
+//! encode as absent.
+
+use std::fmt;
+
+use bytes::Bytes;
+
+use super::types::Amqp1Value;
+use super::Amqp1CodecError;
+
+const OPEN: u64 = 0x10;
+const BEGIN: u64 = 0x11;
+const ATTACH: u64 = 0x12;
+const FLOW: u64 = 0x13;
+const TRANSFER: u64 = 0x14;
+const DISPOSITION: u64 = 0x15;
+const DETACH: u64 = 0x16;
+const END: u64 = 0x17;
+const CLOSE: u64 = 0x18;
+const ERROR: u64 = 0x1d;
+const RECEIVED: u64 = 0x23;
+const ACCEPTED: u64 = 0x24;
+const REJECTED: u64 = 0x25;
+const RELEASED: u64 = 0x26;
+const MODIFIED: u64 = 0x27;
+const SOURCE: u64 = 0x28;
+const TARGET: u64 = 0x29;
+const SASL_MECHANISMS: u64 = 0x40;
+const SASL_INIT: u64 = 0x41;
+const SASL_OUTCOME: u64 = 0x44;
+
+/// The 8-byte header that opens the connection and, after SASL, the AMQP
+/// layer: `AMQP` followed by a protocol id and version 1.0.0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Amqp1ProtocolHeader {
+    Amqp,
+    Sasl,
+}
+
+impl Amqp1ProtocolHeader {
+    pub fn protocol_id(&self) -> u8 {
+        match self {
+            Amqp1ProtocolHeader::Amqp => 0,
+            Amqp1ProtocolHeader::Sasl => 3,
+        }
+    }
+
+    pub fn from_protocol_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Amqp1ProtocolHeader::Amqp),
+            3 => Some(Amqp1ProtocolHeader::Sasl),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Amqp1Frame {
+    Header(Amqp1ProtocolHeader),
+    /// An AMQP frame with no body, sent to keep an idle connection alive.
+    Heartbeat,
+    Amqp {
+        channel: u16,
+        performative: Performative,
+        /// The message bytes following a transfer; empty otherwise.
+        payload: Bytes,
+    },
+    Sasl(SaslPerformative),
+}
+
+impl Amqp1Frame {
+    pub fn amqp(channel: u16, performative: Performative) -> Self {
+        Amqp1Frame::Amqp {
+            channel,
+            performative,
+            payload: Bytes::new(),
+        }
+    }
+}
+
+impl fmt::Display for Amqp1Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Amqp1Frame::Header(header) => write!(f, "Header({header:?})"),
+            Amqp1Frame::Heartbeat => write!(f, "Heartbeat"),
+            Amqp1Frame::Amqp {
+                channel,
+                performative,
+                payload,
+            } => write!(
+                f,
+                "{}(channel={}, payload={}B)",
+                performative.name(),
+                channel,
+                payload.len()
+            ),
+            Amqp1Frame::Sasl(sasl) => write!(f, "{sasl:?}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Performative {
+    Open(Open),
+    Begin(Begin),
+    Attach(Attach),
+    Flow(Flow),
+    Transfer(Transfer),
+    Disposition(Disposition),
+    Detach(Detach),
+    End(End),
+    Close(Close),
+}
+
+impl Performative {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Performative::Open(_) => "Open",
+            Performative::Begin(_) => "Begin",
+            Performative::Attach(_) => "Attach",
+            Performative::Flow(_) => "Flow",
+            Performative::Transfer(_) => "Transfer",
+            Performative::Disposition(_) => "Disposition",
+            Performative::Detach(_) => "Detach",
+            Performative::End(_) => "End",
+            Performative::Close(_) => "Close",
+        }
+    }
+
+    pub fn decode(value: Amqp1Value) -> Result<Self, Amqp1CodecError> {
+        let code = value.descriptor_code();
+        let fields = Fields::of(value)?;
+        let performative = match code {
+            Some(OPEN) => Performative::Open(Open::from_fields(&fields)?),
+            Some(BEGIN) => Performative::Begin(Begin::from_fields(&fields)?),
+            Some(ATTACH) => Performative::Attach(Attach::from_fields(&fields)?),
+            Some(FLOW) => Performative::Flow(Flow::from_fields(&fields)?),
+            Some(TRANSFER) => Performative::Transfer(Transfer::from_fields(&fields)?),
+            Some(DISPOSITION) => Performative::Disposition(Disposition::from_fields(&fields)?),
+            Some(DETACH) => Performative::Detach(Detach::from_fields(&fields)?),
+            Some(END) => Performative::End(End {
+                error: fields.error(0)?,
+            }),
+            Some(CLOSE) => Performative::Close(Close {
+                error: fields.error(0)?,
+            }),
+            other => return Err(Amqp1CodecError::UnknownPerformative(other)),
+        };
+        Ok(performative)
+    }
+
+    pub fn encode(&self) -> Amqp1Value {
+        match self {
+            Performative::Open(open) => described_list(OPEN, open.to_fields()),
+            Performative::Begin(begin) => described_list(BEGIN, begin.to_fields()),
+            Performative::Attach(attach) => described_list(ATTACH, attach.to_fields()),
+            Performative::Flow(flow) => described_list(FLOW, flow.to_fields()),
+            Performative::Transfer(transfer) => described_list(TRANSFER, transfer.to_fields()),
+            Performative::Disposition(disposition) => {
+                described_list(DISPOSITION, disposition.to_fields())
+            }
+            Performative::Detach(detach) => described_list(DETACH, detach.to_fields()),
+            Performative::End(end) => described_list(END, vec![error_value(&end.error)]),
+            Performative::Close(close) => described_list(CLOSE, vec![error_value(&close.error)]),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SaslPerformative {
+    Mechanisms(SaslMechanisms),
+    Init(SaslInit),
+    Outcome(SaslOutcome),
+}
+
+impl SaslPerformative {
+    pub fn decode(value: Amqp1Value) -> Result<Self, Amqp1CodecError> {
+        let code = value.descriptor_code();
+        let fields = Fields::of(value)?;
+        let performative = match code {
+            Some(SASL_MECHANISMS) => SaslPerformative::Mechanisms(SaslMechanisms {
+                mechanisms: fields.symbols(0),
+            }),
+            Some(SASL_INIT) => SaslPerformative::Init(SaslInit {
+                mechanism: fields.required_string(0, "sasl-init.mechanism")?,
+                initial_response: fields.binary(1),
+                hostname: fields.string(2),
+            }),
+            Some(SASL_OUTCOME) => SaslPerformative::Outcome(SaslOutcome {
+                code: fields.get(0).as_u8().unwrap_or(0),
+                additional_data: fields.binary(1),
+            }),
+            other => return Err(Amqp1CodecError::UnknownPerformative(other)),
+        };
+        Ok(performative)
+    }
+
+    pub fn encode(&self) -> Amqp1Value {
+        match self {
+            SaslPerformative::Mechanisms(m) => {
+                described_list(SASL_MECHANISMS, vec![symbol_array(&m.mechanisms)])
+            }
+            SaslPerformative::Init(init) => described_list(
+                SASL_INIT,
+                vec![
+                    Amqp1Value::symbol(init.mechanism.clone()),
+                    opt(init.initial_response.clone().map(Amqp1Value::Binary)),
+                    opt(init.hostname.clone().map(Amqp1Value::String)),
+                ],
+            ),
+            SaslPerformative::Outcome(outcome) => described_list(
+                SASL_OUTCOME,
+                vec![
+                    Amqp1Value::Ubyte(outcome.code),
+                    opt(outcome.additional_data.clone().map(Amqp1Value::Binary)),
+                ],
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SaslMechanisms {
+    pub mechanisms: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SaslInit {
+    pub mechanism: String,
+    pub initial_response: Option<Vec<u8>>,
+    pub hostname: Option<String>,
+}
+
+/// `code` 0 is ok, 1 auth failure, 2 system error.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SaslOutcome {
+    pub code: u8,
+    pub additional_data: Option<Vec<u8>>,
+}
+
+/// The `error` record carried by detach/end/close and rejected outcomes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ErrorCondition {
+    pub condition: String,
+    pub description: Option<String>,
+}
+
+impl ErrorCondition {
+    pub fn new(condition: &str, description: impl Into<String>) -> Self {
+        ErrorCondition {
+            condition: condition.to_string(),
+            description: Some(description.into()),
+        }
+    }
+
+    fn decode(value: &Amqp1Value) -> Result<Option<Self>, Amqp1CodecError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        let fields = Fields::of(value.clone())?;
+        Ok(Some(ErrorCondition {
+            condition: fields.required_string(0, "error.condition")?,
+            description: fields.string(1),
+        }))
+    }
+
+    fn encode(&self) -> Amqp1Value {
+        described_list(
+            ERROR,
+            vec![
+                Amqp1Value::symbol(self.condition.clone()),
+                opt(self.description.clone().map(Amqp1Value::String)),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Open {
+    pub container_id: String,
+    pub hostname: Option<String>,
+    pub max_frame_size: Option<u32>,
+    pub channel_max: Option<u16>,
+    /// Milliseconds; the peer closes the connection after twice this long
+    /// without a frame.
+    pub idle_time_out: Option<u32>,
+    pub offered_capabilities: Vec<String>,
+    pub desired_capabilities: Vec<String>,
+}
+
+impl Open {
+    fn from_fields(fields: &Fields) -> Result<Self, Amqp1CodecError> {
+        Ok(Open {
+            container_id: fields.required_string(0, "open.container-id")?,
+            hostname: fields.string(1),
+            max_frame_size: fields.get(2).as_u32(),
+            channel_max: fields.get(3).as_u16(),
+            idle_time_out: fields.get(4).as_u32(),
+            offered_capabilities: fields.symbols(7),
+            desired_capabilities: fields.symbols(8),
+        })
+    }
+
+    fn to_fields(&self) -> Vec<Amqp1Value> {
+        vec![
+            Amqp1Value::String(self.container_id.clone()),
+            opt(self.hostname.clone().map(Amqp1Value::String)),
+            opt(self.max_frame_size.map(Amqp1Value::Uint)),
+            opt(self.channel_max.map(Amqp1Value::Ushort)),
+            opt(self.idle_time_out.map(Amqp1Value::Uint)),
+            Amqp1Value::Null,
+            Amqp1Value::Null,
+            symbols_or_null(&self.offered_capabilities),
+            symbols_or_null(&self.desired_capabilities),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Begin {
+    pub remote_channel: Option<u16>,
+    pub next_outgoing_id: u32,
+    pub incoming_window: u32,
+    pub outgoing_window: u32,
+    pub handle_max: Option<u32>,
+}
+
+impl Begin {
+    fn from_fields(fields: &Fields) -> Result<Self, Amqp1CodecError> {
+        Ok(Begin {
+            remote_channel: fields.get(0).as_u16(),
+            next_outgoing_id: fields.required_u32(1, "begin.next-outgoing-id")?,
+            incoming_window: fields.required_u32(2, "begin.incoming-window")?,
+            outgoing_window: fields.required_u32(3, "begin.outgoing-window")?,
+            handle_max: fields.get(4).as_u32(),
+        })
+    }
+
+    fn to_fields(&self) -> Vec<Amqp1Value> {
+        vec![
+            opt(self.remote_channel.map(Amqp1Value::Ushort)),
+            Amqp1Value::Uint(self.next_outgoing_id),
+            Amqp1Value::Uint(self.incoming_window),
+            Amqp1Value::Uint(self.outgoing_window),
+            opt(self.handle_max.map(Amqp1Value::Uint)),
+        ]
+    }
+}
+
+/// A link endpoint's role: the sender sends transfers, the receiver settles
+/// them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Role {
+    #[default]
+    Sender,
+    Receiver,
+}
+
+impl Role {
+    fn from_value(value: &Amqp1Value) -> Self {
+        if value.as_bool().unwrap_or(false) {
+            Role::Receiver
+        } else {
+            Role::Sender
+        }
+    }
+
+    fn to_value(self) -> Amqp1Value {
+        Amqp1Value::Bool(self == Role::Receiver)
+    }
+}
+
+pub const SND_SETTLE_MODE_UNSETTLED: u8 = 0;
+pub const SND_SETTLE_MODE_SETTLED: u8 = 1;
+pub const SND_SETTLE_MODE_MIXED: u8 = 2;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Source {
+    pub address: Option<String>,
+    pub durable: u32,
+    pub expiry_policy: Option<String>,
+    pub timeout: u32,
+    pub dynamic: bool,
+    pub distribution_mode: Option<String>,
+    pub filter: Option<Amqp1Value>,
+    pub outcomes: Vec<String>,
+    pub capabilities: Vec<String>,
+}
+
+impl Source {
+    fn decode(value: &Amqp1Value) -> Result<Option<Self>, Amqp1CodecError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        let fields = Fields::of(value.clone())?;
+        Ok(Some(Source {
+            address: fields.string(0),
+            durable: fields.get(1).as_u32().unwrap_or(0),
+            expiry_policy: fields.string(2),
+            timeout: fields.get(3).as_u32().unwrap_or(0),
+            dynamic: fields.bool_or(4, false),
+            distribution_mode: fields.string(6),
+            filter: Some(fields.get(7).clone()).filter(|v| !v.is_null()),
+            outcomes: fields.symbols(9),
+            capabilities: fields.symbols(10),
+        }))
+    }
+
+    fn encode(&self) -> Amqp1Value {
+        described_list(
+            SOURCE,
+            vec![
+                opt(self.address.clone().map(Amqp1Value::String)),
+                Amqp1Value::Uint(self.durable),
+                opt(self.expiry_policy.clone().map(Amqp1Value::symbol)),
+                Amqp1Value::Uint(self.timeout),
+                Amqp1Value::Bool(self.dynamic),
+                Amqp1Value::Null,
+                opt(self.distribution_mode.clone().map(Amqp1Value::symbol)),
+                opt(self.filter.clone()),
+                Amqp1Value::Null,
+                symbols_or_null(&self.outcomes),
+                symbols_or_null(&self.capabilities),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Target {
+    pub address: Option<String>,
+    pub durable: u32,
+    pub expiry_policy: Option<String>,
+    pub timeout: u32,
+    pub dynamic: bool,
+    pub capabilities: Vec<String>,
+}
+
+impl Target {
+    fn decode(value: &Amqp1Value) -> Result<Option<Self>, Amqp1CodecError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        let fields = Fields::of(value.clone())?;
+        Ok(Some(Target {
+            address: fields.string(0),
+            durable: fields.get(1).as_u32().unwrap_or(0),
+            expiry_policy: fields.string(2),
+            timeout: fields.get(3).as_u32().unwrap_or(0),
+            dynamic: fields.bool_or(4, false),
+            capabilities: fields.symbols(6),
+        }))
+    }
+
+    fn encode(&self) -> Amqp1Value {
+        described_list(
+            TARGET,
+            vec![
+                opt(self.address.clone().map(Amqp1Value::String)),
+                Amqp1Value::Uint(self.durable),
+                opt(self.expiry_policy.clone().map(Amqp1Value::symbol)),
+                Amqp1Value::Uint(self.timeout),
+                Amqp1Value::Bool(self.dynamic),
+                Amqp1Value::Null,
+                symbols_or_null(&self.capabilities),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Attach {
+    pub name: String,
+    pub handle: u32,
+    pub role: Role,
+    pub snd_settle_mode: u8,
+    pub rcv_settle_mode: u8,
+    pub source: Option<Source>,
+    pub target: Option<Target>,
+    pub initial_delivery_count: Option<u32>,
+    pub max_message_size: Option<u64>,
+}
+
+impl Attach {
+    fn from_fields(fields: &Fields) -> Result<Self, Amqp1CodecError> {
+        Ok(Attach {
+            name: fields.required_string(0, "attach.name")?,
+            handle: fields.required_u32(1, "attach.handle")?,
+            role: Role::from_value(fields.get(2)),
+            snd_settle_mode: fields.get(3).as_u8().unwrap_or(SND_SETTLE_MODE_MIXED),
+            rcv_settle_mode: fields.get(4).as_u8().unwrap_or(0),
+            source: Source::decode(fields.get(5))?,
+            target: Target::decode(fields.get(6))?,
+            initial_delivery_count: fields.get(9).as_u32(),
+            max_message_size: fields.get(10).as_u64(),
+        })
+    }
+
+    fn to_fields(&self) -> Vec<Amqp1Value> {
+        vec![
+            Amqp1Value::String(self.name.clone()),
+            Amqp1Value::Uint(self.handle),
+            self.role.to_value(),
+            Amqp1Value::Ubyte(self.snd_settle_mode),
+            Amqp1Value::Ubyte(self.rcv_settle_mode),
+            opt(self.source.as_ref().map(Source::encode)),
+            opt(self.target.as_ref().map(Target::encode)),
+            Amqp1Value::Null,
+            Amqp1Value::Bool(false),
+            opt(self.initial_delivery_count.map(Amqp1Value::Uint)),
+            opt(self.max_message_size.map(Amqp1Value::Ulong)),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Flow {
+    pub next_incoming_id: Option<u32>,
+    pub incoming_window: u32,
+    pub next_outgoing_id: u32,
+    pub outgoing_window: u32,
+    pub handle: Option<u32>,
+    pub delivery_count: Option<u32>,
+    pub link_credit: Option<u32>,
+    pub available: Option<u32>,
+    pub drain: bool,
+    pub echo: bool,
+}
+
+impl Flow {
+    fn from_fields(fields: &Fields) -> Result<Self, Amqp1CodecError> {
+        Ok(Flow {
+            next_incoming_id: fields.get(0).as_u32(),
+            incoming_window: fields.required_u32(1, "flow.incoming-window")?,
+            next_outgoing_id: fields.required_u32(2, "flow.next-outgoing-id")?,
+            outgoing_window: fields.required_u32(3, "flow.outgoing-window")?,
+            handle: fields.get(4).as_u32(),
+            delivery_count: fields.get(5).as_u32(),
+            link_credit: fields.get(6).as_u32(),
+            available: fields.get(7).as_u32(),
+            drain: fields.bool_or(8, false),
+            echo: fields.bool_or(9, false),
+        })
+    }
+
+    fn to_fields(&self) -> Vec<Amqp1Value> {
+        vec![
+            opt(self.next_incoming_id.map(Amqp1Value::Uint)),
+            Amqp1Value::Uint(self.incoming_window),
+            Amqp1Value::Uint(self.next_outgoing_id),
+            Amqp1Value::Uint(self.outgoing_window),
+            opt(self.handle.map(Amqp1Value::Uint)),
+            opt(self.delivery_count.map(Amqp1Value::Uint)),
+            opt(self.link_credit.map(Amqp1Value::Uint)),
+            opt(self.available.map(Amqp1Value::Uint)),
+            Amqp1Value::Bool(self.drain),
+            Amqp1Value::Bool(self.echo),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transfer {
+    pub handle: u32,
+    /// Only required on the first transfer of a multi-frame delivery.
+    pub delivery_id: Option<u32>,
+    pub delivery_tag: Option<Vec<u8>>,
+    pub message_format: Option<u32>,
+    pub settled: Option<bool>,
+    pub more: bool,
+    pub state: Option<DeliveryState>,
+    pub aborted: bool,
+}
+
+impl Transfer {
+    fn from_fields(fields: &Fields) -> Result<Self, Amqp1CodecError> {
+        Ok(Transfer {
+            handle: fields.required_u32(0, "transfer.handle")?,
+            delivery_id: fields.get(1).as_u32(),
+            delivery_tag: fields.binary(2),
+            message_format: fields.get(3).as_u32(),
+            settled: fields.get(4).as_bool(),
+            more: fields.bool_or(5, false),
+            state: DeliveryState::decode(fields.get(7))?,
+            aborted: fields.bool_or(9, false),
+        })
+    }
+
+    fn to_fields(&self) -> Vec<Amqp1Value> {
+        vec![
+            Amqp1Value::Uint(self.handle),
+            opt(self.delivery_id.map(Amqp1Value::Uint)),
+            opt(self.delivery_tag.clone().map(Amqp1Value::Binary)),
+            opt(self.message_format.map(Amqp1Value::Uint)),
+            opt(self.settled.map(Amqp1Value::Bool)),
+            Amqp1Value::Bool(self.more),
+            Amqp1Value::Null,
+            opt(self.state.as_ref().map(DeliveryState::encode)),
+            Amqp1Value::Null,
+            Amqp1Value::Bool(self.aborted),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Disposition {
+    pub role: Role,
+    pub first: u32,
+    pub last: Option<u32>,
+    pub settled: bool,
+    pub state: Option<DeliveryState>,
+}
+
+impl Disposition {
+    fn from_fields(fields: &Fields) -> Result<Self, Amqp1CodecError> {
+        Ok(Disposition {
+            role: Role::from_value(fields.get(0)),
+            first: fields.required_u32(1, "disposition.first")?,
+            last: fields.get(2).as_u32(),
+            settled: fields.bool_or(3, false),
+            state: DeliveryState::decode(fields.get(4))?,
+        })
+    }
+
+    fn to_fields(&self) -> Vec<Amqp1Value> {
+        vec![
+            self.role.to_value(),
+            Amqp1Value::Uint(self.first),
+            opt(self.last.map(Amqp1Value::Uint)),
+            Amqp1Value::Bool(self.settled),
+            opt(self.state.as_ref().map(DeliveryState::encode)),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Detach {
+    pub handle: u32,
+    pub closed: bool,
+    pub error: Option<ErrorCondition>,
+}
+
+impl Detach {
+    fn from_fields(fields: &Fields) -> Result<Self, Amqp1CodecError> {
+        Ok(Detach {
+            handle: fields.required_u32(0, "detach.handle")?,
+            closed: fields.bool_or(1, false),
+            error: fields.error(2)?,
+        })
+    }
+
+    fn to_fields(&self) -> Vec<Amqp1Value> {
+        vec![
+            Amqp1Value::Uint(self.handle),
+            Amqp1Value::Bool(self.closed),
+            error_value(&self.error),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct End {
+    pub error: Option<ErrorCondition>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Close {
+    pub error: Option<ErrorCondition>,
+}
+
+/// Delivery states and outcomes (spec 3.4).
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeliveryState {
+    Received {
+        section_number: u32,
+        section_offset: u64,
+    },
+    Accepted,
+    Rejected(Option<ErrorCondition>),
+    Released,
+    Modified {
+        delivery_failed: bool,
+        undeliverable_here: bool,
+    },
+}
+
+impl DeliveryState {
+    fn decode(value: &Amqp1Value) -> Result<Option<Self>, Amqp1CodecError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        let code = value.descriptor_code();
+        let fields = Fields::of(value.clone())?;
+        let state = match code {
+            Some(RECEIVED) => DeliveryState::Received {
+                section_number: fields.get(0).as_u32().unwrap_or(0),
+                section_offset: fields.get(1).as_u64().unwrap_or(0),
+            },
+            Some(ACCEPTED) => DeliveryState::Accepted,
+            Some(REJECTED) => DeliveryState::Rejected(fields.error(0)?),
+            Some(RELEASED) => DeliveryState::Released,
+            Some(MODIFIED) => DeliveryState::Modified {
+                delivery_failed: fields.bool_or(0, false),
+                undeliverable_here: fields.bool_or(1, false),
+            },
+            // Transactional states and the like aren't supported; treat
+            // them as absent rather than failing the whole frame.
+            _ => return Ok(None),
+        };
+        Ok(Some(state))
+    }
+
+    fn encode(&self) -> Amqp1Value {
+        match self {
+            DeliveryState::Received {
+                section_number,
+                section_offset,
+            } => described_list(
+                RECEIVED,
+                vec![
+                    Amqp1Value::Uint(*section_number),
+                    Amqp1Value::Ulong(*section_offset),
+                ],
+            ),
+            DeliveryState::Accepted => described_list(ACCEPTED, Vec::new()),
+            DeliveryState::Rejected(error) => described_list(REJECTED, vec![error_value(error)]),
+            DeliveryState::Released => described_list(RELEASED, Vec::new()),
+            DeliveryState::Modified {
+                delivery_failed,
+                undeliverable_here,
+            } => described_list(
+                MODIFIED,
+                vec![
+                    Amqp1Value::Bool(*delivery_failed),
+                    Amqp1Value::Bool(*undeliverable_here),
+                ],
+            ),
+        }
+    }
+}
+
+/// Positional access to a described list's fields; a missing trailing field
+/// reads as null, as the spec allows senders to omit them.
+struct Fields(Vec<Amqp1Value>);
+
+const NULL_VALUE: Amqp1Value = Amqp1Value::Null;
+
+impl Fields {
+    fn of(value: Amqp1Value) -> Result<Self, Amqp1CodecError> {
+        match value {
+            Amqp1Value::Described(_, body) => match *body {
+                Amqp1Value::List(items) => Ok(Fields(items)),
+                other => Err(Amqp1CodecError::Malformed(format!(
+                    "expected a described list, got {other:?}"
+                ))),
+            },
+            other => Err(Amqp1CodecError::Malformed(format!(
+                "expected a described value, got {other:?}"
+            ))),
+        }
+    }
+
+    fn get(&self, index: usize) -> &Amqp1Value {
+        self.0.get(index).unwrap_or(&NULL_VALUE)
+    }
+
+    fn string(&self, index: usize) -> Option<String> {
+        self.get(index).as_str().map(str::to_string)
+    }
+
+    fn required_string(&self, index: usize, name: &str) -> Result<String, Amqp1CodecError> {
+        self.string(index)
+            .ok_or_else(|| Amqp1CodecError::Malformed(format!("missing {name}")))
+    }
+
+    fn required_u32(&self, index: usize, name: &str) -> Result<u32, Amqp1CodecError> {
+        self.get(index)
+            .as_u32()
+            .ok_or_else(|| Amqp1CodecError::Malformed(format!("missing {name}")))
+    }
+
+    fn bool_or(&self, index: usize, default: bool) -> bool {
+        self.get(index).as_bool().unwrap_or(default)
+    }
+
+    fn binary(&self, index: usize) -> Option<Vec<u8>> {
+        match self.get(index) {
+            Amqp1Value::Binary(b) => Some(b.clone()),
+            _ => None,
+        }
+    }
+
+    /// A `multiple` symbol field: a single symbol or an array of them.
+    fn symbols(&self, index: usize) -> Vec<String> {
+        match self.get(index) {
+            Amqp1Value::Symbol(s) => vec![s.clone()],
+            Amqp1Value::Array(items) | Amqp1Value::List(items) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn error(&self, index: usize) -> Result<Option<ErrorCondition>, Amqp1CodecError> {
+        ErrorCondition::decode(self.get(index))
+    }
+}
+
+fn opt(value: Option<Amqp1Value>) -> Amqp1Value {
+    value.unwrap_or(Amqp1Value::Null)
+}
+
+fn error_value(error: &Option<ErrorCondition>) -> Amqp1Value {
+    opt(error.as_ref().map(ErrorCondition::encode))
+}
+
+fn symbol_array(symbols: &[String]) -> Amqp1Value {
+    Amqp1Value::Array(symbols.iter().cloned().map(Amqp1Value::Symbol).collect())
+}
+
+fn symbols_or_null(symbols: &[String]) -> Amqp1Value {
+    if symbols.is_empty() {
+        Amqp1Value::Null
+    } else {
+        symbol_array(symbols)
+    }
+}
+
+/// A described list with trailing nulls trimmed, as senders should.
+fn described_list(code: u64, mut fields: Vec<Amqp1Value>) -> Amqp1Value {
+    while fields.last().is_some_and(Amqp1Value::is_null) {
+        fields.pop();
+    }
+    Amqp1Value::described(code, Amqp1Value::List(fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(performative: Performative) {
+        let decoded = Performative::decode(performative.encode()).unwrap();
+        assert_eq!(decoded, performative);
+    }
+
+    #[test]
+    fn performatives_round_trip() {
+        round_trip(Performative::Open(Open {
+            container_id: "robustmq".to_string(),
+            hostname: Some("vhost:default".to_string()),
+            max_frame_size: Some(65536),
+            channel_max: Some(255),
+            idle_time_out: Some(30_000),
+            offered_capabilities: vec!["ANONYMOUS-RELAY".to_string()],
+            desired_capabilities: Vec::new(),
+        }));
+        round_trip(Performative::Begin(Begin {
+            remote_channel: Some(0),
+            next_outgoing_id: 1,
+            incoming_window: 2048,
+            outgoing_window: 2048,
+            handle_max: Some(255),
+        }));
+        round_trip(Performative::Attach(Attach {
+            name: "sender-1".to_string(),
+            handle: 0,
+            role: Role::Receiver,
+            snd_settle_mode: SND_SETTLE_MODE_MIXED,
+            rcv_settle_mode: 0,
+            source: Some(Source {
+                address: Some("/queues/orders".to_string()),
+                ..Default::default()
+            }),
+            target: Some(Target {
+                address: Some("/exchanges/amq.topic/a.b".to_string()),
+                ..Default::default()
+            }),
+            initial_delivery_count: Some(0),
+            max_message_size: None,
+        }));
+        round_trip(Performative::Flow(Flow {
+            next_incoming_id: Some(3),
+            incoming_window: 100,
+            next_outgoing_id: 4,
+            outgoing_window: 100,
+            handle: Some(1),
+            delivery_count: Some(10),
+            link_credit: Some(50),
+            available: None,
+            drain: true,
+            echo: false,
+        }));
+        round_trip(Performative::Transfer(Transfer {
+            handle: 1,
+            delivery_id: Some(7),
+            delivery_tag: Some(vec![0, 1]),
+            message_format: Some(0),
+            settled: Some(false),
+            more: true,
+            state: None,
+            aborted: false,
+        }));
+        round_trip(Performative::Disposition(Disposition {
+            role: Role::Receiver,
+            first: 1,
+            last: Some(5),
+            settled: true,
+            state: Some(DeliveryState::Rejected(Some(ErrorCondition::new(
+                "amqp:not-found",
+                "no such queue",
+            )))),
+        }));
+        round_trip(Performative::Detach(Detach {
+            handle: 2,
+            closed: true,
+            error: None,
+        }));
+        round_trip(Performative::Close(Close {
+            error: Some(ErrorCondition::new("amqp:unauthorized-access", "denied")),
+        }));
+    }
+
+    #[test]
+    fn sasl_round_trip() {
+        for sasl in [
+            SaslPerformative::Mechanisms(SaslMechanisms {
+                mechanisms: vec!["PLAIN".to_string()],
+            }),
+            SaslPerformative::Init(SaslInit {
+                mechanism: "PLAIN".to_string(),
+                initial_response: Some(b"\0user\0pass".to_vec()),
+                hostname: None,
+            }),
+            SaslPerformative::Outcome(SaslOutcome {
+                code: 0,
+                additional_data: None,
+            }),
+        ] {
+            assert_eq!(SaslPerformative::decode(sasl.encode()).unwrap(), sasl);
+        }
+    }
+
+    #[test]
+    fn omitted_trailing_fields_take_defaults() {
+        let value = Amqp1Value::described(DETACH, Amqp1Value::List(vec![Amqp1Value::Uint(4)]));
+        assert_eq!(
+            Performative::decode(value).unwrap(),
+            Performative::Detach(Detach {
+                handle: 4,
+                closed: false,
+                error: None,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_descriptor_is_rejected() {
+        let value = Amqp1Value::described(0x99, Amqp1Value::List(vec![]));
+        assert!(matches!(
+            Performative::decode(value),
+            Err(Amqp1CodecError::UnknownPerformative(Some(0x99)))
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! AMQP 1.0 wire protocol: the type system, frames and performatives, the
+//! message format and a frame codec. Spoken alongside AMQP 0-9-1 on the
+//! same listener; the protocol header a client opens with selects which.
+
+pub mod codec;
+pub mod frame;
+pub mod message;
+pub mod types;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Amqp1CodecError {
+    #[error("AMQP 1.0 frame is truncated")]
+    Truncated,
+
+    #[error("AMQP 1.0 unknown format code 0x{0:02x}")]
+    UnknownFormatCode(u8),
+
+    #[error("AMQP 1.0 string is not valid UTF-8")]
+    InvalidUtf8,
+
+    #[error("AMQP 1.0 unknown performative {0:?}")]
+    UnknownPerformative(Option<u64>),
+
+    #[error("AMQP 1.0 unsupported protocol header {0:?}")]
+    UnsupportedHeader([u8; 8]),
+
+    #[error("AMQP 1.0 frame size {0} is outside the allowed range")]
+    FrameSize(u32),
+
+    #[error("AMQP 1.0 malformed frame: {0}")]
+    Malformed(String),
+}
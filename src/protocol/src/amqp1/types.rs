@@ -0,0 +1,663 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The AMQP 1.0 type system (spec part 1): a self-describing value model
+//! and its binary encoding. Performatives and message sections are built
+//! from these values in `frame` and `message`.
+
+use bytes::{BufMut, BytesMut};
+
+use super::Amqp1CodecError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Amqp1Value {
+    Null,
+    Bool(bool),
+    Ubyte(u8),
+    Ushort(u16),
+    Uint(u32),
+    Ulong(u64),
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    // Decimals are carried as their raw IEEE 754 bits; nothing here does
+    // arithmetic on them.
+    Decimal32(u32),
+    Decimal64(u64),
+    Decimal128([u8; 16]),
+    Char(char),
+    /// Milliseconds since the Unix epoch.
+    Timestamp(i64),
+    Uuid([u8; 16]),
+    Binary(Vec<u8>),
+    String(String),
+    Symbol(String),
+    List(Vec<Amqp1Value>),
+    Map(Vec<(Amqp1Value, Amqp1Value)>),
+    Array(Vec<Amqp1Value>),
+    Described(Box<Amqp1Value>, Box<Amqp1Value>),
+}
+
+impl Amqp1Value {
+    pub fn symbol(s: impl Into<String>) -> Self {
+        Amqp1Value::Symbol(s.into())
+    }
+
+    pub fn described(code: u64, value: Amqp1Value) -> Self {
+        Amqp1Value::Described(Box::new(Amqp1Value::Ulong(code)), Box::new(value))
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Amqp1Value::Null)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Amqp1Value::String(s) | Amqp1Value::Symbol(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Amqp1Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Any unsigned integer that fits, as the spec lets senders pick the
+    /// narrowest encoding.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Amqp1Value::Ubyte(v) => Some(*v as u64),
+            Amqp1Value::Ushort(v) => Some(*v as u64),
+            Amqp1Value::Uint(v) => Some(*v as u64),
+            Amqp1Value::Ulong(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_u64().and_then(|v| u32::try_from(v).ok())
+    }
+
+    pub fn as_u16(&self) -> Option<u16> {
+        self.as_u64().and_then(|v| u16::try_from(v).ok())
+    }
+
+    pub fn as_u8(&self) -> Option<u8> {
+        self.as_u64().and_then(|v| u8::try_from(v).ok())
+    }
+
+    /// The descriptor code of a described value. Symbolic descriptors
+    /// (`amqp:open:list`) are mapped to their numeric codes.
+    pub fn descriptor_code(&self) -> Option<u64> {
+        let Amqp1Value::Described(descriptor, _) = self else {
+            return None;
+        };
+        match descriptor.as_ref() {
+            Amqp1Value::Ulong(code) => Some(*code),
+            Amqp1Value::Symbol(name) => symbolic_descriptor_code(name),
+            _ => None,
+        }
+    }
+
+    /// Renders scalar values as text, for carrying AMQP 1.0
+    /// application-properties as 0-9-1 headers.
+    pub fn to_display_string(&self) -> String {
+        match self {
+            Amqp1Value::Null => String::new(),
+            Amqp1Value::Bool(v) => v.to_string(),
+            Amqp1Value::Ubyte(v) => v.to_string(),
+            Amqp1Value::Ushort(v) => v.to_string(),
+            Amqp1Value::Uint(v) => v.to_string(),
+            Amqp1Value::Ulong(v) => v.to_string(),
+            Amqp1Value::Byte(v) => v.to_string(),
+            Amqp1Value::Short(v) => v.to_string(),
+            Amqp1Value::Int(v) => v.to_string(),
+            Amqp1Value::Long(v) => v.to_string(),
+            Amqp1Value::Float(v) => v.to_string(),
+            Amqp1Value::Double(v) => v.to_string(),
+            Amqp1Value::Timestamp(v) => v.to_string(),
+            Amqp1Value::Char(c) => c.to_string(),
+            Amqp1Value::String(s) | Amqp1Value::Symbol(s) => s.clone(),
+            other => format!("{other:?}"),
+        }
+    }
+}
+
+fn symbolic_descriptor_code(name: &str) -> Option<u64> {
+    let code = match name {
+        "amqp:open:list" => 0x10,
+        "amqp:begin:list" => 0x11,
+        "amqp:attach:list" => 0x12,
+        "amqp:flow:list" => 0x13,
+        "amqp:transfer:list" => 0x14,
+        "amqp:disposition:list" => 0x15,
+        "amqp:detach:list" => 0x16,
+        "amqp:end:list" => 0x17,
+        "amqp:close:list" => 0x18,
+        "amqp:error:list" => 0x1d,
+        "amqp:received:list" => 0x23,
+        "amqp:accepted:list" => 0x24,
+        "amqp:rejected:list" => 0x25,
+        "amqp:released:list" => 0x26,
+        "amqp:modified:list" => 0x27,
+        "amqp:source:list" => 0x28,
+        "amqp:target:list" => 0x29,
+        "amqp:sasl-mechanisms:list" => 0x40,
+        "amqp:sasl-init:list" => 0x41,
+        "amqp:sasl-challenge:list" => 0x42,
+        "amqp:sasl-response:list" => 0x43,
+        "amqp:sasl-outcome:list" => 0x44,
+        "amqp:header:list" => 0x70,
+        "amqp:delivery-annotations:map" => 0x71,
+        "amqp:message-annotations:map" => 0x72,
+        "amqp:properties:list" => 0x73,
+        "amqp:application-properties:map" => 0x74,
+        "amqp:data:binary" => 0x75,
+        "amqp:amqp-sequence:list" => 0x76,
+        "amqp:amqp-value:*" => 0x77,
+        "amqp:footer:map" => 0x78,
+        _ => return None,
+    };
+    Some(code)
+}
+
+// Format codes (spec 1.6).
+const DESCRIBED: u8 = 0x00;
+const NULL: u8 = 0x40;
+const TRUE: u8 = 0x41;
+const FALSE: u8 = 0x42;
+const UINT0: u8 = 0x43;
+const ULONG0: u8 = 0x44;
+const LIST0: u8 = 0x45;
+const UBYTE: u8 = 0x50;
+const BYTE: u8 = 0x51;
+const SMALLUINT: u8 = 0x52;
+const SMALLULONG: u8 = 0x53;
+const SMALLINT: u8 = 0x54;
+const SMALLLONG: u8 = 0x55;
+const BOOLEAN: u8 = 0x56;
+const USHORT: u8 = 0x60;
+const SHORT: u8 = 0x61;
+const UINT: u8 = 0x70;
+const INT: u8 = 0x71;
+const FLOAT: u8 = 0x72;
+const CHAR: u8 = 0x73;
+const DECIMAL32: u8 = 0x74;
+const ULONG: u8 = 0x80;
+const LONG: u8 = 0x81;
+const DOUBLE: u8 = 0x82;
+const TIMESTAMP: u8 = 0x83;
+const DECIMAL64: u8 = 0x84;
+const DECIMAL128: u8 = 0x94;
+const UUID: u8 = 0x98;
+const VBIN8: u8 = 0xa0;
+const STR8: u8 = 0xa1;
+const SYM8: u8 = 0xa3;
+const VBIN32: u8 = 0xb0;
+const STR32: u8 = 0xb1;
+const SYM32: u8 = 0xb3;
+const LIST8: u8 = 0xc0;
+const MAP8: u8 = 0xc1;
+const LIST32: u8 = 0xd0;
+const MAP32: u8 = 0xd1;
+const ARRAY8: u8 = 0xe0;
+const ARRAY32: u8 = 0xf0;
+
+fn take<'a>(buf: &mut &'a [u8], n: usize) -> Result<&'a [u8], Amqp1CodecError> {
+    if buf.len() < n {
+        return Err(Amqp1CodecError::Truncated);
+    }
+    let (head, tail) = buf.split_at(n);
+    *buf = tail;
+    Ok(head)
+}
+
+fn take_u8(buf: &mut &[u8]) -> Result<u8, Amqp1CodecError> {
+    Ok(take(buf, 1)?[0])
+}
+
+fn take_array<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], Amqp1CodecError> {
+    let mut out = [0u8; N];
+    out.copy_from_slice(take(buf, N)?);
+    Ok(out)
+}
+
+fn take_u32(buf: &mut &[u8]) -> Result<u32, Amqp1CodecError> {
+    Ok(u32::from_be_bytes(take_array(buf)?))
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, Amqp1CodecError> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| Amqp1CodecError::InvalidUtf8)
+}
+
+/// Decodes one value from the front of `buf`, advancing it.
+pub fn decode_value(buf: &mut &[u8]) -> Result<Amqp1Value, Amqp1CodecError> {
+    let code = take_u8(buf)?;
+    if code == DESCRIBED {
+        let descriptor = decode_value(buf)?;
+        let value = decode_value(buf)?;
+        return Ok(Amqp1Value::Described(Box::new(descriptor), Box::new(value)));
+    }
+    decode_with_code(code, buf)
+}
+
+fn decode_with_code(code: u8, buf: &mut &[u8]) -> Result<Amqp1Value, Amqp1CodecError> {
+    let value = match code {
+        NULL => Amqp1Value::Null,
+        TRUE => Amqp1Value::Bool(true),
+        FALSE => Amqp1Value::Bool(false),
+        BOOLEAN => Amqp1Value::Bool(take_u8(buf)? != 0),
+        UBYTE => Amqp1Value::Ubyte(take_u8(buf)?),
+        BYTE => Amqp1Value::Byte(take_u8(buf)? as i8),
+        USHORT => Amqp1Value::Ushort(u16::from_be_bytes(take_array(buf)?)),
+        SHORT => Amqp1Value::Short(i16::from_be_bytes(take_array(buf)?)),
+        UINT0 => Amqp1Value::Uint(0),
+        SMALLUINT => Amqp1Value::Uint(take_u8(buf)? as u32),
+        UINT => Amqp1Value::Uint(take_u32(buf)?),
+        ULONG0 => Amqp1Value::Ulong(0),
+        SMALLULONG => Amqp1Value::Ulong(take_u8(buf)? as u64),
+        ULONG => Amqp1Value::Ulong(u64::from_be_bytes(take_array(buf)?)),
+        SMALLINT => Amqp1Value::Int(take_u8(buf)? as i8 as i32),
+        INT => Amqp1Value::Int(i32::from_be_bytes(take_array(buf)?)),
+        SMALLLONG => Amqp1Value::Long(take_u8(buf)? as i8 as i64),
+        LONG => Amqp1Value::Long(i64::from_be_bytes(take_array(buf)?)),
+        FLOAT => Amqp1Value::Float(f32::from_be_bytes(take_array(buf)?)),
+        DOUBLE => Amqp1Value::Double(f64::from_be_bytes(take_array(buf)?)),
+        DECIMAL32 => Amqp1Value::Decimal32(take_u32(buf)?),
+        DECIMAL64 => Amqp1Value::Decimal64(u64::from_be_bytes(take_array(buf)?)),
+        DECIMAL128 => Amqp1Value::Decimal128(take_array(buf)?),
+        CHAR => {
+            Amqp1Value::Char(char::from_u32(take_u32(buf)?).ok_or(Amqp1CodecError::InvalidUtf8)?)
+        }
+        TIMESTAMP => Amqp1Value::Timestamp(i64::from_be_bytes(take_array(buf)?)),
+        UUID => Amqp1Value::Uuid(take_array(buf)?),
+        VBIN8 | VBIN32 | STR8 | STR32 | SYM8 | SYM32 => {
+            let len = if code & 0xf0 == 0xa0 {
+                take_u8(buf)? as usize
+            } else {
+                take_u32(buf)? as usize
+            };
+            let bytes = take(buf, len)?;
+            match code {
+                VBIN8 | VBIN32 => Amqp1Value::Binary(bytes.to_vec()),
+                STR8 | STR32 => Amqp1Value::String(utf8(bytes)?),
+                _ => Amqp1Value::Symbol(utf8(bytes)?),
+            }
+        }
+        LIST0 => Amqp1Value::List(Vec::new()),
+        LIST8 | LIST32 | MAP8 | MAP32 => {
+            let (size, count) = if code == LIST8 || code == MAP8 {
+                let size = take_u8(buf)? as usize;
+                (size, take_u8(buf)? as usize)
+            } else {
+                let size = take_u32(buf)? as usize;
+                (size, take_u32(buf)? as usize)
+            };
+            let width = if code == LIST8 || code == MAP8 { 1 } else { 4 };
+            // `size` counts the count field plus the items.
+            let mut items = take(buf, size.saturating_sub(width))?;
+            let mut values = Vec::with_capacity(count.min(256));
+            for _ in 0..count {
+                values.push(decode_value(&mut items)?);
+            }
+            if code == LIST8 || code == LIST32 {
+                Amqp1Value::List(values)
+            } else {
+                if values.len() % 2 != 0 {
+                    return Err(Amqp1CodecError::Malformed(
+                        "map with an odd number of items".to_string(),
+                    ));
+                }
+                let mut pairs = Vec::with_capacity(values.len() / 2);
+                let mut iter = values.into_iter();
+                while let (Some(k), Some(v)) = (iter.next(), iter.next()) {
+                    pairs.push((k, v));
+                }
+                Amqp1Value::Map(pairs)
+            }
+        }
+        ARRAY8 | ARRAY32 => {
+            let (size, count) = if code == ARRAY8 {
+                let size = take_u8(buf)? as usize;
+                (size, take_u8(buf)? as usize)
+            } else {
+                let size = take_u32(buf)? as usize;
+                (size, take_u32(buf)? as usize)
+            };
+            let width = if code == ARRAY8 { 1 } else { 4 };
+            let mut items = take(buf, size.saturating_sub(width))?;
+            // Every element shares one constructor, which may itself be
+            // described.
+            let mut element_code = take_u8(&mut items)?;
+            let descriptor = if element_code == DESCRIBED {
+                let descriptor = decode_value(&mut items)?;
+                element_code = take_u8(&mut items)?;
+                Some(descriptor)
+            } else {
+                None
+            };
+            let mut values = Vec::with_capacity(count.min(256));
+            for _ in 0..count {
+                let value = decode_with_code(element_code, &mut items)?;
+                values.push(match &descriptor {
+                    Some(d) => Amqp1Value::Described(Box::new(d.clone()), Box::new(value)),
+                    None => value,
+                });
+            }
+            Amqp1Value::Array(values)
+        }
+        other => return Err(Amqp1CodecError::UnknownFormatCode(other)),
+    };
+    Ok(value)
+}
+
+/// Appends the encoding of `value`, using the most compact format code.
+pub fn encode_value(value: &Amqp1Value, dst: &mut BytesMut) {
+    match value {
+        Amqp1Value::Null => dst.put_u8(NULL),
+        Amqp1Value::Bool(true) => dst.put_u8(TRUE),
+        Amqp1Value::Bool(false) => dst.put_u8(FALSE),
+        Amqp1Value::Ubyte(v) => {
+            dst.put_u8(UBYTE);
+            dst.put_u8(*v);
+        }
+        Amqp1Value::Byte(v) => {
+            dst.put_u8(BYTE);
+            dst.put_i8(*v);
+        }
+        Amqp1Value::Ushort(v) => {
+            dst.put_u8(USHORT);
+            dst.put_u16(*v);
+        }
+        Amqp1Value::Short(v) => {
+            dst.put_u8(SHORT);
+            dst.put_i16(*v);
+        }
+        Amqp1Value::Uint(0) => dst.put_u8(UINT0),
+        Amqp1Value::Uint(v) if *v < 256 => {
+            dst.put_u8(SMALLUINT);
+            dst.put_u8(*v as u8);
+        }
+        Amqp1Value::Uint(v) => {
+            dst.put_u8(UINT);
+            dst.put_u32(*v);
+        }
+        Amqp1Value::Ulong(0) => dst.put_u8(ULONG0),
+        Amqp1Value::Ulong(v) if *v < 256 => {
+            dst.put_u8(SMALLULONG);
+            dst.put_u8(*v as u8);
+        }
+        Amqp1Value::Ulong(v) => {
+            dst.put_u8(ULONG);
+            dst.put_u64(*v);
+        }
+        Amqp1Value::Int(v) if i8::try_from(*v).is_ok() => {
+            dst.put_u8(SMALLINT);
+            dst.put_i8(*v as i8);
+        }
+        Amqp1Value::Int(v) => {
+            dst.put_u8(INT);
+            dst.put_i32(*v);
+        }
+        Amqp1Value::Long(v) if i8::try_from(*v).is_ok() => {
+            dst.put_u8(SMALLLONG);
+            dst.put_i8(*v as i8);
+        }
+        Amqp1Value::Long(v) => {
+            dst.put_u8(LONG);
+            dst.put_i64(*v);
+        }
+        Amqp1Value::Float(v) => {
+            dst.put_u8(FLOAT);
+            dst.put_f32(*v);
+        }
+        Amqp1Value::Double(v) => {
+            dst.put_u8(DOUBLE);
+            dst.put_f64(*v);
+        }
+        Amqp1Value::Decimal32(v) => {
+            dst.put_u8(DECIMAL32);
+            dst.put_u32(*v);
+        }
+        Amqp1Value::Decimal64(v) => {
+            dst.put_u8(DECIMAL64);
+            dst.put_u64(*v);
+        }
+        Amqp1Value::Decimal128(v) => {
+            dst.put_u8(DECIMAL128);
+            dst.put_slice(v);
+        }
+        Amqp1Value::Char(c) => {
+            dst.put_u8(CHAR);
+            dst.put_u32(*c as u32);
+        }
+        Amqp1Value::Timestamp(v) => {
+            dst.put_u8(TIMESTAMP);
+            dst.put_i64(*v);
+        }
+        Amqp1Value::Uuid(v) => {
+            dst.put_u8(UUID);
+            dst.put_slice(v);
+        }
+        Amqp1Value::Binary(bytes) => encode_variable(VBIN8, VBIN32, bytes, dst),
+        Amqp1Value::String(s) => encode_variable(STR8, STR32, s.as_bytes(), dst),
+        Amqp1Value::Symbol(s) => encode_variable(SYM8, SYM32, s.as_bytes(), dst),
+        Amqp1Value::List(items) if items.is_empty() => dst.put_u8(LIST0),
+        Amqp1Value::List(items) => {
+            let mut body = BytesMut::new();
+            for item in items {
+                encode_value(item, &mut body);
+            }
+            encode_compound(LIST8, LIST32, items.len(), &body, dst);
+        }
+        Amqp1Value::Map(pairs) => {
+            let mut body = BytesMut::new();
+            for (k, v) in pairs {
+                encode_value(k, &mut body);
+                encode_value(v, &mut body);
+            }
+            encode_compound(MAP8, MAP32, pairs.len() * 2, &body, dst);
+        }
+        Amqp1Value::Array(items) => encode_array(items, dst),
+        Amqp1Value::Described(descriptor, value) => {
+            dst.put_u8(DESCRIBED);
+            encode_value(descriptor, dst);
+            encode_value(value, dst);
+        }
+    }
+}
+
+fn encode_variable(short: u8, long: u8, bytes: &[u8], dst: &mut BytesMut) {
+    if bytes.len() < 256 {
+        dst.put_u8(short);
+        dst.put_u8(bytes.len() as u8);
+    } else {
+        dst.put_u8(long);
+        dst.put_u32(bytes.len() as u32);
+    }
+    dst.put_slice(bytes);
+}
+
+fn encode_compound(short: u8, long: u8, count: usize, body: &[u8], dst: &mut BytesMut) {
+    if body.len() + 1 < 256 && count < 256 {
+        dst.put_u8(short);
+        dst.put_u8((body.len() + 1) as u8);
+        dst.put_u8(count as u8);
+    } else {
+        dst.put_u8(long);
+        dst.put_u32((body.len() + 4) as u32);
+        dst.put_u32(count as u32);
+    }
+    dst.put_slice(body);
+}
+
+/// Arrays share one constructor, so elements are written in the fixed-width
+/// form of their type. Only the homogeneous scalar arrays the broker sends
+/// (symbols for capabilities and SASL mechanisms) need to be compact.
+fn encode_array(items: &[Amqp1Value], dst: &mut BytesMut) {
+    let element_code = match items.first() {
+        None | Some(Amqp1Value::Symbol(_)) => SYM32,
+        Some(Amqp1Value::String(_)) => STR32,
+        Some(Amqp1Value::Binary(_)) => VBIN32,
+        Some(Amqp1Value::Uint(_)) => UINT,
+        Some(Amqp1Value::Ulong(_)) => ULONG,
+        Some(Amqp1Value::Int(_)) => INT,
+        Some(Amqp1Value::Long(_)) => LONG,
+        Some(Amqp1Value::Bool(_)) => BOOLEAN,
+        Some(Amqp1Value::Ubyte(_)) => UBYTE,
+        Some(Amqp1Value::Ushort(_)) => USHORT,
+        Some(Amqp1Value::Timestamp(_)) => TIMESTAMP,
+        Some(Amqp1Value::Uuid(_)) => UUID,
+        Some(_) => {
+            // Anything else goes out as a list, which every peer can read
+            // wherever a multiple is expected.
+            encode_value(&Amqp1Value::List(items.to_vec()), dst);
+            return;
+        }
+    };
+    let mut body = BytesMut::new();
+    body.put_u8(element_code);
+    for item in items {
+        match item {
+            Amqp1Value::Symbol(s) | Amqp1Value::String(s) => {
+                body.put_u32(s.len() as u32);
+                body.put_slice(s.as_bytes());
+            }
+            Amqp1Value::Binary(b) => {
+                body.put_u32(b.len() as u32);
+                body.put_slice(b);
+            }
+            Amqp1Value::Uint(v) => body.put_u32(*v),
+            Amqp1Value::Ulong(v) => body.put_u64(*v),
+            Amqp1Value::Int(v) => body.put_i32(*v),
+            Amqp1Value::Long(v) => body.put_i64(*v),
+            Amqp1Value::Bool(v) => body.put_u8(*v as u8),
+            Amqp1Value::Ubyte(v) => body.put_u8(*v),
+            Amqp1Value::Ushort(v) => body.put_u16(*v),
+            Amqp1Value::Timestamp(v) => body.put_i64(*v),
+            Amqp1Value::Uuid(v) => body.put_slice(v),
+            _ => {}
+        }
+    }
+    encode_compound(ARRAY8, ARRAY32, items.len(), &body, dst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: Amqp1Value) {
+        let mut buf = BytesMut::new();
+        encode_value(&value, &mut buf);
+        let mut slice = &buf[..];
+        assert_eq!(decode_value(&mut slice).unwrap(), value);
+        assert!(slice.is_empty());
+    }
+
+    #[test]
+    fn scalars_round_trip() {
+        for value in [
+            Amqp1Value::Null,
+            Amqp1Value::Bool(true),
+            Amqp1Value::Bool(false),
+            Amqp1Value::Ubyte(7),
+            Amqp1Value::Ushort(65535),
+            Amqp1Value::Uint(0),
+            Amqp1Value::Uint(200),
+            Amqp1Value::Uint(70_000),
+            Amqp1Value::Ulong(0),
+            Amqp1Value::Ulong(3),
+            Amqp1Value::Ulong(u64::MAX),
+            Amqp1Value::Int(-5),
+            Amqp1Value::Int(1 << 20),
+            Amqp1Value::Long(-1),
+            Amqp1Value::Long(i64::MIN),
+            Amqp1Value::Double(1.5),
+            Amqp1Value::Char('é'),
+            Amqp1Value::Timestamp(1_700_000_000_000),
+            Amqp1Value::Uuid([9; 16]),
+            Amqp1Value::Binary(vec![1, 2, 3]),
+            Amqp1Value::Binary(vec![0; 300]),
+            Amqp1Value::String("queue".to_string()),
+            Amqp1Value::String("x".repeat(300)),
+            Amqp1Value::symbol("amqp:accepted:list"),
+        ] {
+            round_trip(value);
+        }
+    }
+
+    #[test]
+    fn compounds_round_trip() {
+        round_trip(Amqp1Value::List(vec![]));
+        round_trip(Amqp1Value::List(vec![
+            Amqp1Value::String("a".to_string()),
+            Amqp1Value::Null,
+            Amqp1Value::Uint(1),
+        ]));
+        round_trip(Amqp1Value::List(vec![Amqp1Value::Binary(vec![0; 300])]));
+        round_trip(Amqp1Value::Map(vec![(
+            Amqp1Value::symbol("key"),
+            Amqp1Value::Long(42),
+        )]));
+        round_trip(Amqp1Value::Array(vec![
+            Amqp1Value::symbol("PLAIN"),
+            Amqp1Value::symbol("ANONYMOUS"),
+        ]));
+        round_trip(Amqp1Value::described(
+            0x10,
+            Amqp1Value::List(vec![Amqp1Value::String("container".to_string())]),
+        ));
+    }
+
+    #[test]
+    fn decodes_described_array_elements() {
+        // array8 of two described(ulong 0x24) list0 values.
+        let bytes = [0xe0, 0x06, 0x02, 0x00, 0x53, 0x24, 0x45, 0x45];
+        let mut slice = &bytes[..];
+        let Amqp1Value::Array(items) = decode_value(&mut slice).unwrap() else {
+            panic!("expected an array");
+        };
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].descriptor_code(), Some(0x24));
+    }
+
+    #[test]
+    fn symbolic_descriptors_map_to_codes() {
+        let value = Amqp1Value::Described(
+            Box::new(Amqp1Value::symbol("amqp:transfer:list")),
+            Box::new(Amqp1Value::List(vec![])),
+        );
+        assert_eq!(value.descriptor_code(), Some(0x14));
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        let mut slice = &[0xa1u8, 0x05, b'a'][..];
+        assert!(matches!(
+            decode_value(&mut slice),
+            Err(Amqp1CodecError::Truncated)
+        ));
+        let mut slice = &[0xffu8][..];
+        assert!(matches!(
+            decode_value(&mut slice),
+            Err(Amqp1CodecError::UnknownFormatCode(0xff))
+        ));
+    }
+}
@@ -16,6 +16,10 @@
 
 use crate::{
     amqp::codec::AmqpCodec,
+    amqp1::{
+        codec::{is_amqp1_header, Amqp1Codec},
+        frame::Amqp1Frame,
+    },
     kafka::{codec::KafkaCodec, packet::KafkaPacketWrapper},
     mqtt::{
         codec::{MqttCodec, MqttPacketWrapper},
@@ -37,6 +41,7 @@ pub enum RobustMQCodecWrapper {
     MQTT(MqttPacketWrapper),
     AMQP(AMQPFrame),
     NATS(NatsPacket),
+    AMQP1(Amqp1Frame),
 }
 
 impl fmt::Display for RobustMQCodecWrapper {
@@ -62,6 +67,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             RobustMQCodecWrapper::NATS(pkt) => {
                 write!(f, "NATS({pkt:?})")
             }
+            RobustMQCodecWrapper::AMQP1(frame) => {
+                write!(f, "AMQP1({frame})")
+            }
         }
     }
 }
@@ -72,6 +80,7 @@ pub struct RobustMQCodec {
     pub mqtt_codec: MqttCodec,
     pub kafka_codec: KafkaCodec,
     pub amqp_codec: AmqpCodec,
+    pub amqp1_codec: Amqp1Codec,
     pub storage_engine_codec: StorageEngineCodec,
     pub nats_codec: NatsCodec,
 }
@@ -83,6 +92,7 @@ pub fn new() -> Self {
             mqtt_codec: MqttCodec::new(None),
             kafka_codec: KafkaCodec::new(),
             amqp_codec: AmqpCodec::new(),
+            amqp1_codec: Amqp1Codec::new(),
             storage_engine_codec: StorageEngineCodec::new(),
             nats_codec: NatsCodec::new(),
         }
@@ -129,10 +139,24 @@ pub fn decode_data(
                 }
             }
             Some(RobustMQProtocol::AMQP) => {
+                // AMQP 1.0 clients share the 0-9-1 listener; their protocol
+                // header switches the connection over for good.
+                if is_amqp1_header(stream) {
+                    self.protocol = Some(RobustMQProtocol::AMQP1);
+                    return self.decode_data(stream);
+                }
                 if let Ok(Some(pkg)) = self.amqp_codec.decode_data(stream) {
                     return Ok(Some(RobustMQCodecWrapper::AMQP(pkg)));
                 }
             }
+            Some(RobustMQProtocol::AMQP1) => {
+                // Unlike the other codecs, errors are surfaced: a frame that
+                // fails to parse can't be skipped, so the connection is lost.
+                return match self.amqp1_codec.decode_data(stream) {
+                    Ok(frame) => Ok(frame.map(RobustMQCodecWrapper::AMQP1)),
+                    Err(e) => Err(CommonError::CommonError(e.to_string())),
+                };
+            }
             Some(RobustMQProtocol::StorageEngine) => {
                 if let Ok(Some(pkg)) = self.storage_engine_codec.decode_data(stream) {
                     return Ok(Some(RobustMQCodecWrapper::StorageEngine(pkg)));
@@ -184,6 +208,11 @@ pub fn encode_data(
                     return Err(CommonError::CommonError(e.to_string()));
                 }
             }
+            RobustMQCodecWrapper::AMQP1(frame) => {
+                if let Err(e) = self.amqp1_codec.encode_data(frame, buffer) {
+                    return Err(CommonError::CommonError(e.to_string()));
+                }
+            }
             RobustMQCodecWrapper::NATS(pkt) => {
                 use tokio_util::codec::Encoder;
                 self.nats_codec
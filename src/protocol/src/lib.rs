@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod amqp;
+pub mod amqp1;
 pub mod broker;
 pub mod codec;
 pub mod kafka;
@@ -1135,6 +1135,8 @@ pub enum DisconnectReasonCode {
     ServerBusy,
     /// 0x8B: Server is shutting down.
     ServerShuttingDown,
+    /// 0x8C: Re-authentication used a different authentication method.
+    BadAuthenticationMethod,
     /// 0x8D: No packet received within keep-alive timeout window.
     KeepAliveTimeout,
     /// 0x8E: Another connection with same ClientID took over session.
@@ -24,7 +24,8 @@ pub fn len(auth: &Auth, properties: &Option<AuthProperties>) -> usize {
         return 2; // Packet type + 0x00
     }
 
-    let mut len = 0;
+    // 1 for the reason code
+    let mut len = 1;
     if let Some(p) = properties {
         let properties_len = properties::len(p);
         let properties_len_len = len_len(properties_len);
@@ -45,8 +46,8 @@ pub fn write(
     let len = len(auth, properties);
     buffer.put_u8(0b1111_0000);
 
-    if len == 2 {
-        buffer.put_u8(0x00); // reason code Success (0x00)
+    if auth.reason.unwrap() == AuthReason::Success && properties.is_none() {
+        buffer.put_u8(0x00); // remaining length 0: Success, no properties
         return Ok(len);
     }
     let count = write_remaining_length(buffer, len)?;
@@ -272,7 +273,7 @@ fn test_auth_v5() {
         let fixed_header: FixedHeader = parse_fixed_header(buffer.iter()).unwrap();
         assert_eq!(fixed_header.byte1, 0b1111_0000);
         assert_eq!(fixed_header.fixed_header_len, 2);
-        assert_eq!(fixed_header.remaining_len, 88);
+        assert_eq!(fixed_header.remaining_len, 89);
 
         // test the read function of pubrec packet and check the result of write function in MQTT v5
         let (auth_read, y) = read(fixed_header, buffer.copy_to_bytes(buffer.len())).unwrap();
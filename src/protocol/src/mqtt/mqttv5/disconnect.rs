@@ -223,6 +223,7 @@ fn code(reason: DisconnectReasonCode) -> u8 {
         DisconnectReasonCode::NotAuthorized => 0x87,
         DisconnectReasonCode::ServerBusy => 0x89,
         DisconnectReasonCode::ServerShuttingDown => 0x8B,
+        DisconnectReasonCode::BadAuthenticationMethod => 0x8C,
         DisconnectReasonCode::KeepAliveTimeout => 0x8D,
         DisconnectReasonCode::SessionTakenOver => 0x8E,
         DisconnectReasonCode::TopicFilterInvalid => 0x8F,
@@ -257,6 +258,7 @@ fn reason(code: u8) -> Result<DisconnectReasonCode, MQTTProtocolError> {
         0x87 => DisconnectReasonCode::NotAuthorized,
         0x89 => DisconnectReasonCode::ServerBusy,
         0x8B => DisconnectReasonCode::ServerShuttingDown,
+        0x8C => DisconnectReasonCode::BadAuthenticationMethod,
         0x8D => DisconnectReasonCode::KeepAliveTimeout,
         0x8E => DisconnectReasonCode::SessionTakenOver,
         0x8F => DisconnectReasonCode::TopicFilterInvalid,
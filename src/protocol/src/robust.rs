@@ -17,6 +17,7 @@
 use amq_protocol::frame::AMQPFrame;
 
 use crate::{
+    amqp1::frame::Amqp1Frame,
     kafka::packet::KafkaPacketWrapper,
     mqtt::{
         codec::MqttPacketWrapper,
@@ -36,6 +37,8 @@ pub enum RobustMQProtocol {
     AMQP,
     StorageEngine,
     NATS,
+    /// AMQP 1.0, served on the AMQP listener next to 0-9-1 (`AMQP`).
+    AMQP1,
 }
 
 impl RobustMQProtocol {
@@ -57,6 +60,10 @@ pub fn is_amqp(&self) -> bool {
         *self == RobustMQProtocol::AMQP
     }
 
+    pub fn is_amqp1(&self) -> bool {
+        *self == RobustMQProtocol::AMQP1
+    }
+
     pub fn is_engine(&self) -> bool {
         *self == RobustMQProtocol::StorageEngine
     }
@@ -74,6 +81,7 @@ pub fn to_u8(&self) -> u8 {
             RobustMQProtocol::AMQP => 11,
             RobustMQProtocol::StorageEngine => 10,
             RobustMQProtocol::NATS => 12,
+            RobustMQProtocol::AMQP1 => 13,
         }
     }
 
@@ -86,6 +94,7 @@ pub fn to_str(&self) -> String {
             RobustMQProtocol::AMQP => "AMQP".to_string(),
             RobustMQProtocol::StorageEngine => "StorageEngine".to_string(),
             RobustMQProtocol::NATS => "NATS".to_string(),
+            RobustMQProtocol::AMQP1 => "AMQP1".to_string(),
         }
     }
 
@@ -98,6 +107,7 @@ pub fn to_mqtt(&self) -> MqttProtocol {
             RobustMQProtocol::AMQP => MqttProtocol::Mqtt3,
             RobustMQProtocol::StorageEngine => MqttProtocol::Mqtt3,
             RobustMQProtocol::NATS => MqttProtocol::Mqtt3,
+            RobustMQProtocol::AMQP1 => MqttProtocol::Mqtt3,
         }
     }
 
@@ -122,6 +132,9 @@ pub struct KafkaWrapperExtend {}
 #[derive(Clone, Debug, Default)]
 pub struct AmqpWrapperExtend {}
 
+#[derive(Clone, Debug, Default)]
+pub struct Amqp1WrapperExtend {}
+
 #[derive(Clone, Debug, Default)]
 pub struct StorageEngineWrapperExtend {}
 
@@ -135,6 +148,7 @@ pub enum RobustMQWrapperExtend {
     AMQP(AmqpWrapperExtend),
     StorageEngine(StorageEngineWrapperExtend),
     NATS(NatsWrapperExtend),
+    AMQP1(Amqp1WrapperExtend),
 }
 
 impl RobustMQWrapperExtend {
@@ -145,6 +159,7 @@ pub fn to_mqtt_protocol(&self) -> u8 {
             RobustMQWrapperExtend::AMQP(_) => 3,
             RobustMQWrapperExtend::StorageEngine(_) => 3,
             RobustMQWrapperExtend::NATS(_) => 3,
+            RobustMQWrapperExtend::AMQP1(_) => 3,
         }
     }
 }
@@ -185,6 +200,9 @@ pub enum RobustMQPacket {
     AMQP(Vec<AMQPFrame>),
     StorageEngine(StorageEnginePacket),
     NATS(NatsPacket),
+    // Like AMQP, one reply can be several frames (e.g. a transfer split to
+    // fit the negotiated max-frame-size), written back-to-back.
+    AMQP1(Vec<Amqp1Frame>),
 }
 
 impl RobustMQPacket {
@@ -209,6 +227,13 @@ pub fn get_amqp_packet(&self) -> Option<Vec<AMQPFrame>> {
         }
     }
 
+    pub fn get_amqp1_packet(&self) -> Option<Vec<Amqp1Frame>> {
+        match self.clone() {
+            RobustMQPacket::AMQP1(frames) => Some(frames),
+            _ => None,
+        }
+    }
+
     pub fn get_nats_packet(&self) -> Option<NatsPacket> {
         match self.clone() {
             RobustMQPacket::NATS(pkt) => Some(pkt),
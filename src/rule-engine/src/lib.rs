@@ -26,8 +26,10 @@
 
 pub mod decode;
 pub mod encode;
+pub mod manager;
 pub mod operator;
 pub mod rule_trait;
+pub mod sql;
 #[cfg(test)]
 pub mod test_data;
 
@@ -0,0 +1,427 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::sql::functions::value_to_string;
+use crate::sql::{RuleMessage, SqlRule};
+use async_trait::async_trait;
+use bytes::Bytes;
+use common_base::error::common::CommonError;
+use common_metrics::rule::record_rule_failure;
+use dashmap::DashMap;
+use metadata_struct::rule::{Rule, RuleAction};
+use metadata_struct::storage::adapter_record::{AdapterWriteRecord, RecordHeader};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use storage_adapter::driver::StorageDriverManager;
+use tracing::warn;
+
+#[derive(Clone)]
+struct CompiledRule {
+    rule: Rule,
+    sql: SqlRule,
+}
+
+/// What one action of a rule writes for a message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleOutput {
+    pub rule_name: String,
+    pub topic: String,
+    pub payload: Bytes,
+}
+
+/// Creates a topic a rule writes to the way the protocol the message came in
+/// on creates topics on publish, so the topic gets that protocol's defaults,
+/// limits and caches.
+#[async_trait]
+pub trait RuleTopicInit: Send + Sync {
+    async fn init_topic(&self, tenant: &str, topic: &str) -> Result<(), CommonError>;
+}
+
+#[derive(Default)]
+pub struct RuleEngineManager {
+    // (tenant, rule name)
+    rules: DashMap<String, DashMap<String, CompiledRule>>,
+    // (tenant, connector name) -> the topic the connector reads from
+    connector_topics: DashMap<String, DashMap<String, String>>,
+}
+
+impl RuleEngineManager {
+    pub fn new() -> Self {
+        RuleEngineManager::default()
+    }
+
+    pub fn add_rule(&self, rule: Rule) -> Result<(), CommonError> {
+        let sql = SqlRule::compile(&rule.sql)?;
+        self.rules
+            .entry(rule.tenant.clone())
+            .or_default()
+            .insert(rule.name.clone(), CompiledRule { rule, sql });
+        Ok(())
+    }
+
+    pub fn remove_rule(&self, tenant: &str, name: &str) {
+        if let Some(rules) = self.rules.get(tenant) {
+            rules.remove(name);
+        }
+    }
+
+    pub fn get_rule(&self, tenant: &str, name: &str) -> Option<Rule> {
+        self.rules
+            .get(tenant)?
+            .get(name)
+            .map(|compiled| compiled.rule.clone())
+    }
+
+    pub fn add_connector_topic(&self, tenant: &str, connector_name: &str, topic: &str) {
+        self.connector_topics
+            .entry(tenant.to_string())
+            .or_default()
+            .insert(connector_name.to_string(), topic.to_string());
+    }
+
+    pub fn remove_connector_topic(&self, tenant: &str, connector_name: &str) {
+        if let Some(topics) = self.connector_topics.get(tenant) {
+            topics.remove(connector_name);
+        }
+    }
+
+    /// Whether any enabled rule of the tenant listens on the topic. Publish
+    /// paths check this before building a `RuleMessage`.
+    pub fn is_match_topic(&self, tenant: &str, topic: &str) -> bool {
+        self.rules.get(tenant).is_some_and(|rules| {
+            rules
+                .iter()
+                .any(|compiled| compiled.rule.enable && compiled.sql.is_match_topic(topic))
+        })
+    }
+
+    /// Runs the tenant's enabled rules on a message. A rule that fails on the
+    /// message is logged, counted in `rule_failures_total` and skipped.
+    pub fn evaluate(&self, message: &RuleMessage) -> Vec<RuleOutput> {
+        let Some(rules) = self.rules.get(&message.tenant) else {
+            return Vec::new();
+        };
+        let matched: Vec<CompiledRule> = rules
+            .iter()
+            .filter(|compiled| compiled.rule.enable && compiled.sql.is_match_topic(&message.topic))
+            .map(|compiled| compiled.clone())
+            .collect();
+        drop(rules);
+
+        let mut outputs = Vec::new();
+        for compiled in matched {
+            let selected = match compiled.sql.apply(message) {
+                Ok(Some(selected)) => selected,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!(
+                        "Rule {} failed on a message from {}: {}",
+                        compiled.rule.name, message.topic, e
+                    );
+                    record_rule_failure(&message.tenant, &compiled.rule.name, "evaluate");
+                    continue;
+                }
+            };
+            for action in compiled.rule.actions.iter() {
+                match self.action_output(&message.tenant, action, &selected) {
+                    Ok(output) => outputs.push(RuleOutput {
+                        rule_name: compiled.rule.name.clone(),
+                        topic: output.0,
+                        payload: output.1,
+                    }),
+                    Err(e) => {
+                        warn!(
+                            "Rule {} action failed on a message from {}: {}",
+                            compiled.rule.name, message.topic, e
+                        );
+                        record_rule_failure(&message.tenant, &compiled.rule.name, "action");
+                    }
+                }
+            }
+        }
+        outputs
+    }
+
+    /// Evaluates a message and writes what the rules' actions produce.
+    /// Target topics that don't exist yet are created through `topic_init`.
+    /// Written messages are not evaluated again, so a rule republishing to a
+    /// topic it listens on doesn't loop.
+    pub async fn process(
+        &self,
+        storage_driver_manager: &Arc<StorageDriverManager>,
+        topic_init: &dyn RuleTopicInit,
+        message: &RuleMessage,
+    ) {
+        for output in self.evaluate(message) {
+            if let Err(e) = topic_init.init_topic(&message.tenant, &output.topic).await {
+                warn!(
+                    "Rule {} failed to create topic {}: {}",
+                    output.rule_name, output.topic, e
+                );
+                record_rule_failure(&message.tenant, &output.rule_name, "init_topic");
+                continue;
+            }
+            let record = AdapterWriteRecord::new(output.topic.clone(), output.payload)
+                // NATS subscribers read a subject by this tag.
+                .with_tags(vec![format!("{}_{}", message.tenant, output.topic)])
+                .with_header(vec![
+                    RecordHeader {
+                        name: "rule-name".to_string(),
+                        value: output.rule_name.clone(),
+                    },
+                    RecordHeader {
+                        name: "rule-source-topic".to_string(),
+                        value: message.topic.clone(),
+                    },
+                ]);
+            if let Err(e) = storage_driver_manager
+                .write(&message.tenant, &output.topic, &[record], 1)
+                .await
+            {
+                warn!(
+                    "Rule {} failed to write to topic {}: {}",
+                    output.rule_name, output.topic, e
+                );
+                record_rule_failure(&message.tenant, &output.rule_name, "write");
+            }
+        }
+    }
+
+    fn action_output(
+        &self,
+        tenant: &str,
+        action: &RuleAction,
+        selected: &Map<String, Value>,
+    ) -> Result<(String, Bytes), CommonError> {
+        match action {
+            RuleAction::Republish { topic, payload } => {
+                let topic = render_template(topic, selected);
+                if topic.is_empty() {
+                    return Err(CommonError::CommonError(
+                        "republish topic is empty".to_string(),
+                    ));
+                }
+                let payload = match payload {
+                    Some(template) => Bytes::from(render_template(template, selected)),
+                    None => Bytes::from(serde_json::to_vec(selected)?),
+                };
+                Ok((topic, payload))
+            }
+            RuleAction::Connector { connector_name } => {
+                let topic = self
+                    .connector_topics
+                    .get(tenant)
+                    .and_then(|topics| topics.get(connector_name).map(|t| t.clone()))
+                    .ok_or_else(|| {
+                        CommonError::CommonError(format!(
+                            "connector {} does not exist",
+                            connector_name
+                        ))
+                    })?;
+                Ok((topic, Bytes::from(serde_json::to_vec(selected)?)))
+            }
+        }
+    }
+}
+
+/// Replaces `${name}` and `${name.field}` with the selected values; missing
+/// ones become empty.
+pub fn render_template(template: &str, selected: &Map<String, Value>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let path = &rest[start + 2..start + 2 + len];
+        let mut keys = path.split('.');
+        let mut value = keys.next().and_then(|key| selected.get(key.trim()));
+        for key in keys {
+            value = value.and_then(|v| v.get(key.trim()));
+        }
+        out.push_str(&value.map(value_to_string).unwrap_or_default());
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_template, RuleEngineManager, RuleTopicInit};
+    use crate::sql::RuleMessage;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use common_base::error::common::CommonError;
+    use common_metrics::rule::get_rule_failure;
+    use metadata_struct::rule::{Rule, RuleAction};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use storage_adapter::driver::StorageDriverManager;
+    use storage_adapter::storage::{test_add_topic, test_build_storage_driver_manager};
+
+    // Creates topics like a protocol would, except "alerts/denied".
+    struct TestTopics {
+        storage_driver_manager: Arc<StorageDriverManager>,
+        created: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl RuleTopicInit for TestTopics {
+        async fn init_topic(&self, _tenant: &str, topic: &str) -> Result<(), CommonError> {
+            if topic == "alerts/denied" {
+                return Err(CommonError::CommonError("topic limit reached".to_string()));
+            }
+            test_add_topic(&self.storage_driver_manager, topic);
+            self.created.lock().unwrap().push(topic.to_string());
+            Ok(())
+        }
+    }
+
+    fn rule(name: &str, sql: &str, actions: Vec<RuleAction>) -> Rule {
+        Rule {
+            tenant: "default".to_string(),
+            name: name.to_string(),
+            sql: sql.to_string(),
+            actions,
+            desc: String::new(),
+            enable: true,
+            create_time: 0,
+            update_time: 0,
+        }
+    }
+
+    fn message(topic: &str, payload: &str) -> RuleMessage {
+        RuleMessage {
+            tenant: "default".to_string(),
+            protocol: "mqtt".to_string(),
+            topic: topic.to_string(),
+            client_id: "c1".to_string(),
+            payload: Bytes::from(payload.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn template() {
+        let selected = json!({"t": 41.5, "device": {"id": "d1"}});
+        let selected = selected.as_object().unwrap();
+        assert_eq!(
+            render_template("alerts/${device.id}/${t}", selected),
+            "alerts/d1/41.5"
+        );
+        assert_eq!(render_template("a/${missing}/b", selected), "a//b");
+        assert_eq!(render_template("a/${open", selected), "a/${open");
+    }
+
+    #[test]
+    fn evaluate_rules() {
+        let manager = RuleEngineManager::new();
+        manager
+            .add_rule(rule(
+                "hot",
+                r#"SELECT payload.temp AS t, clientid FROM "sensors/+/data" WHERE payload.temp > 40"#,
+                vec![
+                    RuleAction::Republish {
+                        topic: "alerts/${clientid}".to_string(),
+                        payload: None,
+                    },
+                    RuleAction::Republish {
+                        topic: "alerts/raw".to_string(),
+                        payload: Some("temp=${t}".to_string()),
+                    },
+                    RuleAction::Connector {
+                        connector_name: "sink".to_string(),
+                    },
+                ],
+            ))
+            .unwrap();
+        assert!(manager.add_rule(rule("bad", "SELECT", vec![])).is_err());
+
+        assert!(manager.is_match_topic("default", "sensors/s1/data"));
+        assert!(!manager.is_match_topic("default", "other"));
+        assert!(!manager.is_match_topic("other", "sensors/s1/data"));
+
+        // The connector isn't known yet, so only the republish actions run.
+        let outputs = manager.evaluate(&message("sensors/s1/data", r#"{"temp": 42}"#));
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].topic, "alerts/c1");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&outputs[0].payload).unwrap(),
+            json!({"t": 42, "clientid": "c1"})
+        );
+        assert_eq!(outputs[1].topic, "alerts/raw");
+        assert_eq!(outputs[1].payload, Bytes::from("temp=42"));
+
+        manager.add_connector_topic("default", "sink", "sink-topic");
+        let outputs = manager.evaluate(&message("sensors/s1/data", r#"{"temp": 42}"#));
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[2].topic, "sink-topic");
+
+        assert!(manager
+            .evaluate(&message("sensors/s1/data", r#"{"temp": 40}"#))
+            .is_empty());
+
+        let mut disabled = manager.get_rule("default", "hot").unwrap();
+        disabled.enable = false;
+        manager.add_rule(disabled).unwrap();
+        assert!(!manager.is_match_topic("default", "sensors/s1/data"));
+
+        manager.remove_rule("default", "hot");
+        assert!(manager.get_rule("default", "hot").is_none());
+    }
+
+    #[tokio::test]
+    async fn process_creates_target_topics_and_counts_failures() {
+        let storage_driver_manager = test_build_storage_driver_manager().await.unwrap();
+        let topics = TestTopics {
+            storage_driver_manager: storage_driver_manager.clone(),
+            created: Mutex::new(Vec::new()),
+        };
+        let manager = RuleEngineManager::new();
+        manager
+            .add_rule(rule(
+                "fanout",
+                r#"SELECT payload.temp AS t FROM "sensors/+/data""#,
+                vec![
+                    RuleAction::Republish {
+                        topic: "alerts/new".to_string(),
+                        payload: None,
+                    },
+                    RuleAction::Republish {
+                        topic: "alerts/denied".to_string(),
+                        payload: None,
+                    },
+                ],
+            ))
+            .unwrap();
+
+        manager
+            .process(
+                &storage_driver_manager,
+                &topics,
+                &message("sensors/s1/data", r#"{"temp": 42}"#),
+            )
+            .await;
+
+        assert_eq!(*topics.created.lock().unwrap(), vec!["alerts/new"]);
+        assert!(storage_driver_manager
+            .broker_cache
+            .get_topic_by_name("default", "alerts/new")
+            .is_some());
+        assert_eq!(get_rule_failure("default", "fanout", "init_topic"), 1);
+        assert_eq!(get_rule_failure("default", "fanout", "write"), 0);
+    }
+}
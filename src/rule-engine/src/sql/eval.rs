@@ -0,0 +1,239 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Expression evaluation. Missing fields are null; comparisons with null are
+//! null, and only `true` lets a WHERE clause pass.
+
+use super::functions::{call_function, float_value};
+use super::parser::{Accessor, BinaryOp, Expr, UnaryOp};
+use common_base::error::common::CommonError;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+pub fn eval_expr(expr: &Expr, context: &Map<String, Value>) -> Result<Value, CommonError> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Field(name) => Ok(context.get(name).cloned().unwrap_or(Value::Null)),
+        Expr::Get(parent, accessor) => {
+            let parent = eval_expr(parent, context)?;
+            Ok(match (parent, accessor) {
+                (Value::Object(mut map), Accessor::Key(key)) => {
+                    map.remove(key).unwrap_or(Value::Null)
+                }
+                (Value::Array(mut list), Accessor::Index(index)) if *index < list.len() => {
+                    list.swap_remove(*index)
+                }
+                _ => Value::Null,
+            })
+        }
+        Expr::Unary(UnaryOp::Not, expr) => Ok(match eval_expr(expr, context)? {
+            Value::Bool(b) => Value::Bool(!b),
+            _ => Value::Null,
+        }),
+        Expr::Unary(UnaryOp::Neg, expr) => {
+            let value = eval_expr(expr, context)?;
+            arithmetic(BinaryOp::Sub, &Value::from(0), &value)
+        }
+        Expr::Binary(BinaryOp::And, left, right) => {
+            let left = eval_expr(left, context)?;
+            if left == Value::Bool(false) {
+                return Ok(left);
+            }
+            let right = eval_expr(right, context)?;
+            Ok(match (left, right) {
+                (_, Value::Bool(false)) => Value::Bool(false),
+                (Value::Bool(true), Value::Bool(true)) => Value::Bool(true),
+                _ => Value::Null,
+            })
+        }
+        Expr::Binary(BinaryOp::Or, left, right) => {
+            let left = eval_expr(left, context)?;
+            if left == Value::Bool(true) {
+                return Ok(left);
+            }
+            let right = eval_expr(right, context)?;
+            Ok(match (left, right) {
+                (_, Value::Bool(true)) => Value::Bool(true),
+                (Value::Bool(false), Value::Bool(false)) => Value::Bool(false),
+                _ => Value::Null,
+            })
+        }
+        Expr::Binary(op, left, right) => {
+            let left = eval_expr(left, context)?;
+            let right = eval_expr(right, context)?;
+            match op {
+                BinaryOp::Eq | BinaryOp::NotEq => {
+                    if left.is_null() || right.is_null() {
+                        return Ok(Value::Null);
+                    }
+                    Ok(Value::Bool(
+                        values_equal(&left, &right) == (*op == BinaryOp::Eq),
+                    ))
+                }
+                BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq => {
+                    let Some(ordering) = compare(&left, &right) else {
+                        return Ok(Value::Null);
+                    };
+                    Ok(Value::Bool(match op {
+                        BinaryOp::Lt => ordering == Ordering::Less,
+                        BinaryOp::LtEq => ordering != Ordering::Greater,
+                        BinaryOp::Gt => ordering == Ordering::Greater,
+                        _ => ordering != Ordering::Less,
+                    }))
+                }
+                _ => arithmetic(*op, &left, &right),
+            }
+        }
+        Expr::IsNull { expr, negated } => {
+            Ok(Value::Bool(eval_expr(expr, context)?.is_null() != *negated))
+        }
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            let value = eval_expr(expr, context)?;
+            if value.is_null() {
+                return Ok(Value::Null);
+            }
+            for item in list {
+                if values_equal(&value, &eval_expr(item, context)?) {
+                    return Ok(Value::Bool(!negated));
+                }
+            }
+            Ok(Value::Bool(*negated))
+        }
+        Expr::Like {
+            expr,
+            pattern,
+            negated,
+        } => {
+            let value = eval_expr(expr, context)?;
+            let pattern = eval_expr(pattern, context)?;
+            match (value.as_str(), pattern.as_str()) {
+                (Some(value), Some(pattern)) => {
+                    let value: Vec<char> = value.chars().collect();
+                    let pattern: Vec<char> = pattern.chars().collect();
+                    Ok(Value::Bool(like_match(&value, &pattern) != *negated))
+                }
+                _ => Ok(Value::Null),
+            }
+        }
+        Expr::Call { name, args } => {
+            let args = args
+                .iter()
+                .map(|arg| eval_expr(arg, context))
+                .collect::<Result<Vec<_>, _>>()?;
+            call_function(name, &args)
+        }
+    }
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(_), Value::Number(_)) => compare(left, right) == Some(Ordering::Equal),
+        _ => left == right,
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => match (l.as_i64(), r.as_i64()) {
+            (Some(l), Some(r)) => Some(l.cmp(&r)),
+            _ => l.as_f64()?.partial_cmp(&r.as_f64()?),
+        },
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, CommonError> {
+    if left.is_null() || right.is_null() {
+        return Ok(Value::Null);
+    }
+    let (Value::Number(l), Value::Number(r)) = (left, right) else {
+        return Err(CommonError::CommonError(format!(
+            "cannot apply {:?} to {} and {}",
+            op, left, right
+        )));
+    };
+
+    if let (Some(l), Some(r)) = (l.as_i64(), r.as_i64()) {
+        let result = match op {
+            BinaryOp::Add => l.checked_add(r),
+            BinaryOp::Sub => l.checked_sub(r),
+            BinaryOp::Mul => l.checked_mul(r),
+            BinaryOp::Mod if r == 0 => {
+                return Err(CommonError::CommonError("modulo by zero".to_string()))
+            }
+            BinaryOp::Mod => l.checked_rem(r),
+            _ => None,
+        };
+        if let Some(result) = result {
+            return Ok(Value::from(result));
+        }
+    }
+
+    let (l, r) = (
+        l.as_f64().unwrap_or(f64::NAN),
+        r.as_f64().unwrap_or(f64::NAN),
+    );
+    let result = match op {
+        BinaryOp::Add => l + r,
+        BinaryOp::Sub => l - r,
+        BinaryOp::Mul => l * r,
+        BinaryOp::Div if r == 0.0 => {
+            return Err(CommonError::CommonError("division by zero".to_string()))
+        }
+        BinaryOp::Div => l / r,
+        BinaryOp::Mod if r == 0.0 => {
+            return Err(CommonError::CommonError("modulo by zero".to_string()))
+        }
+        BinaryOp::Mod => l % r,
+        _ => f64::NAN,
+    };
+    Ok(float_value(result))
+}
+
+/// SQL LIKE: `%` matches any run of characters and `_` exactly one.
+fn like_match(value: &[char], pattern: &[char]) -> bool {
+    match pattern.split_first() {
+        None => value.is_empty(),
+        Some(('%', rest)) => (0..=value.len()).any(|i| like_match(&value[i..], rest)),
+        Some(('_', rest)) => !value.is_empty() && like_match(&value[1..], rest),
+        Some((c, rest)) => value.first() == Some(c) && like_match(&value[1..], rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::like_match;
+
+    fn like(value: &str, pattern: &str) -> bool {
+        let value: Vec<char> = value.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+        like_match(&value, &pattern)
+    }
+
+    #[test]
+    fn like_patterns() {
+        assert!(like("sensors/s1/data", "sensors/%"));
+        assert!(like("sensors/s1/data", "%/s_/%"));
+        assert!(like("abc", "abc"));
+        assert!(!like("abc", "ab"));
+        assert!(!like("abc", "a_"));
+        assert!(like("", "%"));
+    }
+}
@@ -0,0 +1,434 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Built-in functions of rule SQL. Timestamps are in seconds unless a unit
+//! argument (`second`, `millisecond`) says otherwise.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+use common_base::error::common::CommonError;
+use serde_json::{Number, Value};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+// name, min args, max args
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    // string
+    ("lower", 1, 1),
+    ("upper", 1, 1),
+    ("trim", 1, 1),
+    ("ltrim", 1, 1),
+    ("rtrim", 1, 1),
+    ("concat", 1, usize::MAX),
+    ("substr", 2, 3),
+    ("strlen", 1, 1),
+    ("replace", 3, 3),
+    ("split", 2, 2),
+    ("str", 1, 1),
+    // math
+    ("abs", 1, 1),
+    ("ceil", 1, 1),
+    ("floor", 1, 1),
+    ("round", 1, 2),
+    ("sqrt", 1, 1),
+    ("power", 2, 2),
+    ("ln", 1, 1),
+    ("log10", 1, 1),
+    ("int", 1, 1),
+    ("float", 1, 1),
+    // time
+    ("now_timestamp", 0, 1),
+    ("now_rfc3339", 0, 0),
+    ("unix_ts_to_rfc3339", 1, 2),
+    ("rfc3339_to_unix_ts", 1, 2),
+    ("format_date", 2, 3),
+    // hashing
+    ("md5", 1, 1),
+    ("sha1", 1, 1),
+    ("sha256", 1, 1),
+    ("base64_encode", 1, 1),
+    ("base64_decode", 1, 1),
+    // json
+    ("json_encode", 1, 1),
+    ("json_decode", 1, 1),
+    ("map_get", 2, 3),
+    ("coalesce", 1, usize::MAX),
+];
+
+pub fn check_function(name: &str, args: usize) -> Result<(), CommonError> {
+    let Some((_, min, max)) = FUNCTIONS.iter().find(|(n, _, _)| *n == name) else {
+        return Err(CommonError::CommonError(format!(
+            "invalid rule SQL: unknown function {}",
+            name
+        )));
+    };
+    if args < *min || args > *max {
+        return Err(CommonError::CommonError(format!(
+            "invalid rule SQL: function {} takes {} arguments, got {}",
+            name,
+            if min == max {
+                min.to_string()
+            } else if *max == usize::MAX {
+                format!("at least {}", min)
+            } else {
+                format!("{} to {}", min, max)
+            },
+            args
+        )));
+    }
+    Ok(())
+}
+
+/// Calls a built-in function. Most of them return null for a null argument.
+pub fn call_function(name: &str, args: &[Value]) -> Result<Value, CommonError> {
+    let arg = |i: usize| args.get(i).unwrap_or(&Value::Null);
+    if args.first().is_some_and(Value::is_null)
+        && !matches!(
+            name,
+            "concat" | "coalesce" | "json_encode" | "str" | "map_get"
+        )
+    {
+        return Ok(Value::Null);
+    }
+
+    let value = match name {
+        "lower" => Value::String(as_str(name, arg(0))?.to_lowercase()),
+        "upper" => Value::String(as_str(name, arg(0))?.to_uppercase()),
+        "trim" => Value::String(as_str(name, arg(0))?.trim().to_string()),
+        "ltrim" => Value::String(as_str(name, arg(0))?.trim_start().to_string()),
+        "rtrim" => Value::String(as_str(name, arg(0))?.trim_end().to_string()),
+        "concat" => Value::String(args.iter().map(value_to_string).collect()),
+        "substr" => {
+            let s = as_str(name, arg(0))?;
+            let start = as_usize(name, arg(1))?;
+            let chars = s.chars().skip(start);
+            Value::String(match args.get(2) {
+                Some(len) => chars.take(as_usize(name, len)?).collect(),
+                None => chars.collect(),
+            })
+        }
+        "strlen" => Value::from(as_str(name, arg(0))?.chars().count()),
+        "replace" => Value::String(
+            as_str(name, arg(0))?.replace(as_str(name, arg(1))?, as_str(name, arg(2))?),
+        ),
+        "split" => {
+            let sep = as_str(name, arg(1))?;
+            if sep.is_empty() {
+                return Err(arg_error(name, "an empty separator"));
+            }
+            Value::Array(
+                as_str(name, arg(0))?
+                    .split(sep)
+                    .map(|s| Value::String(s.to_string()))
+                    .collect(),
+            )
+        }
+        "str" => Value::String(value_to_string(arg(0))),
+
+        "abs" => match arg(0) {
+            Value::Number(n) if n.is_i64() => Value::from(n.as_i64().unwrap_or(0).unsigned_abs()),
+            v => float_value(as_f64(name, v)?.abs()),
+        },
+        "ceil" => int_value(as_f64(name, arg(0))?.ceil()),
+        "floor" => int_value(as_f64(name, arg(0))?.floor()),
+        "round" => {
+            let x = as_f64(name, arg(0))?;
+            match args.get(1) {
+                Some(digits) => {
+                    let factor = 10f64.powi(as_usize(name, digits)? as i32);
+                    float_value((x * factor).round() / factor)
+                }
+                None => int_value(x.round()),
+            }
+        }
+        "sqrt" => float_value(as_f64(name, arg(0))?.sqrt()),
+        "power" => float_value(as_f64(name, arg(0))?.powf(as_f64(name, arg(1))?)),
+        "ln" => float_value(as_f64(name, arg(0))?.ln()),
+        "log10" => float_value(as_f64(name, arg(0))?.log10()),
+        "int" => match arg(0) {
+            Value::Bool(b) => Value::from(*b as i64),
+            Value::String(s) => match s.trim().parse::<i64>() {
+                Ok(i) => Value::from(i),
+                Err(_) => int_value(as_f64(name, arg(0))?.trunc()),
+            },
+            v => int_value(as_f64(name, v)?.trunc()),
+        },
+        "float" => float_value(as_f64(name, arg(0))?),
+
+        "now_timestamp" => {
+            let now = Utc::now().timestamp_millis();
+            Value::from(match args.first() {
+                Some(unit) => from_millis(name, now, unit)?,
+                None => now / 1000,
+            })
+        }
+        "now_rfc3339" => Value::String(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        "unix_ts_to_rfc3339" => {
+            let time = date_time(name, arg(0), args.get(1))?;
+            Value::String(time.to_rfc3339_opts(SecondsFormat::Millis, true))
+        }
+        "rfc3339_to_unix_ts" => {
+            let time = DateTime::parse_from_rfc3339(as_str(name, arg(0))?)
+                .map_err(|e| arg_error(name, &e.to_string()))?;
+            let millis = time.timestamp_millis();
+            Value::from(match args.get(1) {
+                Some(unit) => from_millis(name, millis, unit)?,
+                None => millis / 1000,
+            })
+        }
+        "format_date" => {
+            let time = date_time(name, arg(0), args.get(2))?;
+            let format = as_str(name, arg(1))?;
+            let mut out = String::new();
+            std::fmt::write(&mut out, format_args!("{}", time.format(format)))
+                .map_err(|_| arg_error(name, &format!("format {}", format)))?;
+            Value::String(out)
+        }
+
+        "md5" => Value::String(format!("{:x}", md5::compute(hash_input(arg(0))))),
+        "sha1" => Value::String(hex::encode(Sha1::digest(hash_input(arg(0))))),
+        "sha256" => Value::String(hex::encode(Sha256::digest(hash_input(arg(0))))),
+        "base64_encode" => Value::String(STANDARD.encode(hash_input(arg(0)))),
+        "base64_decode" => {
+            let data = STANDARD
+                .decode(as_str(name, arg(0))?)
+                .map_err(|e| arg_error(name, &e.to_string()))?;
+            Value::String(String::from_utf8_lossy(&data).into_owned())
+        }
+
+        "json_encode" => Value::String(arg(0).to_string()),
+        "json_decode" => serde_json::from_str(as_str(name, arg(0))?)
+            .map_err(|e| arg_error(name, &e.to_string()))?,
+        "map_get" => {
+            let key = as_str(name, arg(0))?;
+            let default = args.get(2).cloned().unwrap_or(Value::Null);
+            match arg(1) {
+                Value::Object(map) => map.get(key).cloned().unwrap_or(default),
+                _ => default,
+            }
+        }
+        "coalesce" => args
+            .iter()
+            .find(|v| !v.is_null())
+            .cloned()
+            .unwrap_or(Value::Null),
+
+        _ => {
+            return Err(CommonError::CommonError(format!(
+                "unknown function {}",
+                name
+            )))
+        }
+    };
+    Ok(value)
+}
+
+/// Text form of a value: strings as they are, null as empty, anything else
+/// as JSON.
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        v => v.to_string(),
+    }
+}
+
+pub fn float_value(x: f64) -> Value {
+    Number::from_f64(x)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+fn int_value(x: f64) -> Value {
+    if x.is_finite() && x.abs() < i64::MAX as f64 {
+        return Value::from(x as i64);
+    }
+    float_value(x)
+}
+
+fn arg_error(name: &str, message: &str) -> CommonError {
+    CommonError::CommonError(format!("function {}: invalid argument, {}", name, message))
+}
+
+fn as_str<'a>(name: &str, value: &'a Value) -> Result<&'a str, CommonError> {
+    value
+        .as_str()
+        .ok_or_else(|| arg_error(name, &format!("expected a string, got {}", value)))
+}
+
+fn as_f64(name: &str, value: &Value) -> Result<f64, CommonError> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| arg_error(name, &format!("expected a number, got {}", value)))
+}
+
+fn as_usize(name: &str, value: &Value) -> Result<usize, CommonError> {
+    value.as_u64().map(|n| n as usize).ok_or_else(|| {
+        arg_error(
+            name,
+            &format!("expected a non-negative integer, got {}", value),
+        )
+    })
+}
+
+fn hash_input(value: &Value) -> Vec<u8> {
+    value_to_string(value).into_bytes()
+}
+
+fn unit_millis(name: &str, unit: &Value) -> Result<i64, CommonError> {
+    match as_str(name, unit)? {
+        "second" => Ok(1000),
+        "millisecond" => Ok(1),
+        other => Err(arg_error(
+            name,
+            &format!("unit {}, expected second or millisecond", other),
+        )),
+    }
+}
+
+fn from_millis(name: &str, millis: i64, unit: &Value) -> Result<i64, CommonError> {
+    Ok(millis / unit_millis(name, unit)?)
+}
+
+fn date_time(name: &str, ts: &Value, unit: Option<&Value>) -> Result<DateTime<Utc>, CommonError> {
+    let scale = match unit {
+        Some(unit) => unit_millis(name, unit)?,
+        None => 1000,
+    };
+    let millis = (as_f64(name, ts)? * scale as f64) as i64;
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| arg_error(name, &format!("timestamp {} is out of range", ts)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call_function, check_function};
+    use serde_json::{json, Value};
+
+    fn call(name: &str, args: &[Value]) -> Value {
+        check_function(name, args.len()).unwrap();
+        call_function(name, args).unwrap()
+    }
+
+    #[test]
+    fn string_functions() {
+        assert_eq!(call("upper", &[json!("abc")]), json!("ABC"));
+        assert_eq!(call("trim", &[json!("  a ")]), json!("a"));
+        assert_eq!(
+            call("concat", &[json!("a"), json!(1), Value::Null]),
+            json!("a1")
+        );
+        assert_eq!(
+            call("substr", &[json!("héllo"), json!(1), json!(3)]),
+            json!("éll")
+        );
+        assert_eq!(call("strlen", &[json!("héllo")]), json!(5));
+        assert_eq!(
+            call("replace", &[json!("a-b-c"), json!("-"), json!("/")]),
+            json!("a/b/c")
+        );
+        assert_eq!(
+            call("split", &[json!("a/b"), json!("/")]),
+            json!(["a", "b"])
+        );
+        assert_eq!(call("str", &[json!({"a": 1})]), json!("{\"a\":1}"));
+        assert_eq!(call("lower", &[Value::Null]), Value::Null);
+        assert!(call_function("lower", &[json!(1)]).is_err());
+    }
+
+    #[test]
+    fn math_functions() {
+        assert_eq!(call("abs", &[json!(-3)]), json!(3));
+        assert_eq!(call("abs", &[json!(-1.5)]), json!(1.5));
+        assert_eq!(call("ceil", &[json!(1.2)]), json!(2));
+        assert_eq!(call("floor", &[json!(-1.2)]), json!(-2));
+        assert_eq!(call("round", &[json!(2.345), json!(2)]), json!(2.35));
+        assert_eq!(call("power", &[json!(2), json!(10)]), json!(1024.0));
+        assert_eq!(call("int", &[json!("42")]), json!(42));
+        assert_eq!(call("float", &[json!("1.5")]), json!(1.5));
+        assert_eq!(call("sqrt", &[json!(-1)]), Value::Null);
+    }
+
+    #[test]
+    fn time_functions() {
+        assert_eq!(
+            call("unix_ts_to_rfc3339", &[json!(1700000000)]),
+            json!("2023-11-14T22:13:20.000Z")
+        );
+        assert_eq!(
+            call(
+                "unix_ts_to_rfc3339",
+                &[json!(1700000000123i64), json!("millisecond")]
+            ),
+            json!("2023-11-14T22:13:20.123Z")
+        );
+        assert_eq!(
+            call("rfc3339_to_unix_ts", &[json!("2023-11-14T22:13:20Z")]),
+            json!(1700000000)
+        );
+        assert_eq!(
+            call("format_date", &[json!(1700000000), json!("%Y-%m-%d %H:%M")]),
+            json!("2023-11-14 22:13")
+        );
+        assert!(
+            call("now_timestamp", &[json!("millisecond")])
+                .as_i64()
+                .unwrap()
+                > 1700000000000
+        );
+        assert!(call_function("now_timestamp", &[json!("hour")]).is_err());
+    }
+
+    #[test]
+    fn hash_and_json_functions() {
+        assert_eq!(
+            call("md5", &[json!("abc")]),
+            json!("900150983cd24fb0d6963f7d28e17f72")
+        );
+        assert_eq!(
+            call("sha1", &[json!("abc")]),
+            json!("a9993e364706816aba3e25717850c26c9cd0d89d")
+        );
+        assert_eq!(
+            call("sha256", &[json!("abc")]),
+            json!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(call("base64_encode", &[json!("abc")]), json!("YWJj"));
+        assert_eq!(call("base64_decode", &[json!("YWJj")]), json!("abc"));
+
+        assert_eq!(
+            call("json_decode", &[json!("{\"a\":[1]}")]),
+            json!({"a": [1]})
+        );
+        assert_eq!(call("json_encode", &[json!([1, "a"])]), json!("[1,\"a\"]"));
+        assert_eq!(call("map_get", &[json!("a"), json!({"a": 1})]), json!(1));
+        assert_eq!(
+            call("map_get", &[json!("b"), json!({"a": 1}), json!(0)]),
+            json!(0)
+        );
+        assert_eq!(call("coalesce", &[Value::Null, json!(2)]), json!(2));
+        assert!(call_function("json_decode", &[json!("{")]).is_err());
+    }
+
+    #[test]
+    fn function_arity() {
+        assert!(check_function("concat", 5).is_ok());
+        assert!(check_function("substr", 1).is_err());
+        assert!(check_function("now_rfc3339", 1).is_err());
+        assert!(check_function("nope", 1).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    // Keywords are identifiers too; the parser matches them case-insensitively.
+    Ident(String),
+    // Single- or double-quoted.
+    Str(String),
+    Int(i64),
+    Float(f64),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Dot,
+    Star,
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+pub fn tokenize(sql: &str) -> Result<Vec<Token>, CommonError> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        if c == '\'' || c == '"' {
+            let (value, next) = read_string(&chars, i)?;
+            tokens.push(Token::Str(value));
+            i = next;
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let mut is_float = false;
+            if i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+                is_float = true;
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    is_float = true;
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let token = if is_float {
+                text.parse().map(Token::Float).ok()
+            } else {
+                text.parse().map(Token::Int).ok()
+            };
+            tokens.push(token.ok_or_else(|| {
+                CommonError::CommonError(format!("invalid number {} in rule SQL", text))
+            })?);
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            continue;
+        }
+
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            ('!', Some('=')) => (Token::NotEq, 2),
+            ('<', Some('>')) => (Token::NotEq, 2),
+            ('<', Some('=')) => (Token::LtEq, 2),
+            ('>', Some('=')) => (Token::GtEq, 2),
+            ('=', Some('=')) => (Token::Eq, 2),
+            ('=', _) => (Token::Eq, 1),
+            ('<', _) => (Token::Lt, 1),
+            ('>', _) => (Token::Gt, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('[', _) => (Token::LBracket, 1),
+            (']', _) => (Token::RBracket, 1),
+            (',', _) => (Token::Comma, 1),
+            ('.', _) => (Token::Dot, 1),
+            ('*', _) => (Token::Star, 1),
+            ('+', _) => (Token::Plus, 1),
+            ('-', _) => (Token::Minus, 1),
+            ('/', _) => (Token::Slash, 1),
+            ('%', _) => (Token::Percent, 1),
+            _ => {
+                return Err(CommonError::CommonError(format!(
+                    "unexpected character '{}' in rule SQL",
+                    c
+                )))
+            }
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Reads a quoted string starting at `start`. The quote is escaped by
+/// doubling it, and backslash escapes `\n`, `\t`, `\\` and the quotes.
+fn read_string(chars: &[char], start: usize) -> Result<(String, usize), CommonError> {
+    let quote = chars[start];
+    let mut value = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                value.push(quote);
+                i += 2;
+                continue;
+            }
+            return Ok((value, i + 1));
+        }
+        if c == '\\' {
+            match chars.get(i + 1) {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(escaped @ ('\\' | '\'' | '"')) => value.push(*escaped),
+                _ => value.push('\\'),
+            }
+            i += if i + 1 < chars.len() { 2 } else { 1 };
+            continue;
+        }
+        value.push(c);
+        i += 1;
+    }
+    Err(CommonError::CommonError(
+        "unterminated string in rule SQL".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, Token};
+
+    #[test]
+    fn tokenize_select() {
+        let tokens =
+            tokenize(r#"SELECT payload.temp AS t FROM "sensors/+/data" WHERE t >= 4.5e1"#).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("SELECT".to_string()),
+                Token::Ident("payload".to_string()),
+                Token::Dot,
+                Token::Ident("temp".to_string()),
+                Token::Ident("AS".to_string()),
+                Token::Ident("t".to_string()),
+                Token::Ident("FROM".to_string()),
+                Token::Str("sensors/+/data".to_string()),
+                Token::Ident("WHERE".to_string()),
+                Token::Ident("t".to_string()),
+                Token::GtEq,
+                Token::Float(45.0),
+            ]
+        );
+
+        assert_eq!(
+            tokenize("'it''s' <> 1 -- comment").unwrap(),
+            vec![Token::Str("it's".to_string()), Token::NotEq, Token::Int(1)]
+        );
+        assert!(tokenize("'open").is_err());
+        assert!(tokenize("a ; b").is_err());
+    }
+}
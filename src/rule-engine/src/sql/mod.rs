@@ -0,0 +1,235 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rules written as SQL, e.g.
+//! `SELECT payload.temp AS t, clientid FROM "sensors/+/data" WHERE payload.temp > 40`.
+//! FROM lists the topic filters the rule listens on, WHERE decides whether a
+//! message is picked up and SELECT builds the object the actions send on.
+
+use bytes::Bytes;
+use common_base::error::common::CommonError;
+use eval::eval_expr;
+use parser::{parse_select, SelectField, SelectStatement};
+use serde_json::{Map, Value};
+use topic::topic_filter_match;
+
+pub mod eval;
+pub mod functions;
+pub mod lexer;
+pub mod parser;
+pub mod topic;
+
+/// Fields of a message a rule can refer to.
+pub const MESSAGE_FIELDS: &[&str] = &[
+    "payload",
+    "topic",
+    "clientid",
+    "username",
+    "qos",
+    "tenant",
+    "protocol",
+    "timestamp",
+];
+
+/// A message published on MQTT, NATS or Kafka, as rules see it.
+#[derive(Clone, Debug, Default)]
+pub struct RuleMessage {
+    pub tenant: String,
+    pub protocol: String,
+    pub topic: String,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub qos: u8,
+    pub payload: Bytes,
+    // Milliseconds.
+    pub timestamp: u64,
+}
+
+impl RuleMessage {
+    /// `payload` is the decoded JSON if the payload is JSON, or else its text.
+    pub fn context(&self) -> Map<String, Value> {
+        let payload = serde_json::from_slice(&self.payload)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&self.payload).into_owned()));
+        let mut context = Map::new();
+        context.insert("payload".to_string(), payload);
+        context.insert("topic".to_string(), Value::String(self.topic.clone()));
+        context.insert(
+            "clientid".to_string(),
+            Value::String(self.client_id.clone()),
+        );
+        context.insert(
+            "username".to_string(),
+            self.username.clone().map(Value::String).unwrap_or_default(),
+        );
+        context.insert("qos".to_string(), Value::from(self.qos));
+        context.insert("tenant".to_string(), Value::String(self.tenant.clone()));
+        context.insert("protocol".to_string(), Value::String(self.protocol.clone()));
+        context.insert("timestamp".to_string(), Value::from(self.timestamp));
+        context
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SqlRule {
+    statement: SelectStatement,
+}
+
+impl SqlRule {
+    pub fn compile(sql: &str) -> Result<Self, CommonError> {
+        Ok(SqlRule {
+            statement: parse_select(sql)?,
+        })
+    }
+
+    pub fn topic_filters(&self) -> &[String] {
+        &self.statement.from
+    }
+
+    pub fn is_match_topic(&self, topic: &str) -> bool {
+        self.statement
+            .from
+            .iter()
+            .any(|filter| topic_filter_match(filter, topic))
+    }
+
+    /// The selected fields, or `None` if WHERE filters the message out.
+    pub fn apply(&self, message: &RuleMessage) -> Result<Option<Map<String, Value>>, CommonError> {
+        let context = message.context();
+        if let Some(filter) = &self.statement.filter {
+            if eval_expr(filter, &context)? != Value::Bool(true) {
+                return Ok(None);
+            }
+        }
+
+        let mut output = Map::new();
+        for field in self.statement.fields.iter() {
+            match field {
+                SelectField::All => output.extend(context.clone()),
+                SelectField::Expr { expr, alias } => {
+                    output.insert(alias.clone(), eval_expr(expr, &context)?);
+                }
+            }
+        }
+        Ok(Some(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RuleMessage, SqlRule};
+    use bytes::Bytes;
+    use serde_json::{json, Value};
+
+    fn message(topic: &str, payload: &str) -> RuleMessage {
+        RuleMessage {
+            tenant: "default".to_string(),
+            protocol: "mqtt".to_string(),
+            topic: topic.to_string(),
+            client_id: "c1".to_string(),
+            username: None,
+            qos: 1,
+            payload: Bytes::from(payload.to_string()),
+            timestamp: 1700000000000,
+        }
+    }
+
+    #[test]
+    fn select_where() {
+        let rule = SqlRule::compile(
+            r#"SELECT payload.temp AS t, clientid FROM "sensors/+/data" WHERE payload.temp > 40"#,
+        )
+        .unwrap();
+        assert!(rule.is_match_topic("sensors/s1/data"));
+        assert!(!rule.is_match_topic("sensors/s1/status"));
+
+        let output = rule
+            .apply(&message("sensors/s1/data", r#"{"temp": 41.5}"#))
+            .unwrap()
+            .unwrap();
+        assert_eq!(Value::Object(output), json!({"t": 41.5, "clientid": "c1"}));
+
+        assert!(rule
+            .apply(&message("sensors/s1/data", r#"{"temp": 40}"#))
+            .unwrap()
+            .is_none());
+        // A missing field is null, and null never passes WHERE.
+        assert!(rule
+            .apply(&message("sensors/s1/data", r#"{"humidity": 80}"#))
+            .unwrap()
+            .is_none());
+        assert!(rule
+            .apply(&message("sensors/s1/data", "not json"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn select_expressions() {
+        let rule = SqlRule::compile(
+            "SELECT upper(clientid) AS client, payload.values[1] * 2 AS doubled, \
+             split(topic, '/')[1] AS device, unix_ts_to_rfc3339(timestamp, 'millisecond') AS time, \
+             payload.tags['site id'] AS site \
+             FROM 'sensors/#' \
+             WHERE (qos IN (1, 2) AND username IS NULL) OR payload.force = true",
+        )
+        .unwrap();
+        let output = rule
+            .apply(&message(
+                "sensors/s9/data",
+                r#"{"values": [1, 2.5], "tags": {"site id": "north"}}"#,
+            ))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Value::Object(output),
+            json!({
+                "client": "C1",
+                "doubled": 5.0,
+                "device": "s9",
+                "time": "2023-11-14T22:13:20.000Z",
+                "site": "north"
+            })
+        );
+    }
+
+    #[test]
+    fn select_all() {
+        let rule = SqlRule::compile("SELECT *, payload AS raw FROM 'a'").unwrap();
+        let output = rule.apply(&message("a", "plain text")).unwrap().unwrap();
+        assert_eq!(output.get("raw"), Some(&json!("plain text")));
+        assert_eq!(output.get("topic"), Some(&json!("a")));
+        assert_eq!(output.get("username"), Some(&Value::Null));
+        assert_eq!(output.get("qos"), Some(&json!(1)));
+    }
+
+    #[test]
+    fn eval_errors() {
+        let rule = SqlRule::compile("SELECT payload.a / payload.b AS r FROM 'a'").unwrap();
+        assert!(rule.apply(&message("a", r#"{"a": 1, "b": 0}"#)).is_err());
+        assert_eq!(
+            rule.apply(&message("a", r#"{"a": 1, "b": 4}"#))
+                .unwrap()
+                .unwrap()
+                .get("r"),
+            Some(&json!(0.25))
+        );
+
+        let rule = SqlRule::compile("SELECT payload.a + 1 AS r FROM 'a'").unwrap();
+        assert!(rule.apply(&message("a", r#"{"a": "x"}"#)).is_err());
+        assert_eq!(
+            rule.apply(&message("a", "{}")).unwrap().unwrap().get("r"),
+            Some(&Value::Null)
+        );
+    }
+}
@@ -0,0 +1,539 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::functions::check_function;
+use super::lexer::{tokenize, Token};
+use super::topic::check_topic_filter;
+use super::MESSAGE_FIELDS;
+use common_base::error::common::CommonError;
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectStatement {
+    pub fields: Vec<SelectField>,
+    pub from: Vec<String>,
+    pub filter: Option<Expr>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectField {
+    // `*`: every field of the message.
+    All,
+    Expr { expr: Expr, alias: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    // A message field, e.g. `payload` or `clientid`.
+    Field(String),
+    // `expr.name`, `expr[0]` or `expr['name']`.
+    Get(Box<Expr>, Accessor),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    Like {
+        expr: Box<Expr>,
+        pattern: Box<Expr>,
+        negated: bool,
+    },
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Accessor {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+pub fn parse_select(sql: &str) -> Result<SelectStatement, CommonError> {
+    let tokens = tokenize(sql)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+    };
+    let statement = parser.select()?;
+    if let Some(token) = parser.peek() {
+        return Err(parse_error(format!(
+            "unexpected {:?} after the statement",
+            token
+        )));
+    }
+    Ok(statement)
+}
+
+fn parse_error(message: String) -> CommonError {
+    CommonError::CommonError(format!("invalid rule SQL: {}", message))
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), CommonError> {
+        if self.eat(token) {
+            return Ok(());
+        }
+        Err(parse_error(format!(
+            "expected {:?}, found {:?}",
+            token,
+            self.peek()
+        )))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.is_keyword_at(self.pos, keyword)
+    }
+
+    fn is_keyword_at(&self, pos: usize, keyword: &str) -> bool {
+        matches!(self.tokens.get(pos), Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), CommonError> {
+        if self.eat_keyword(keyword) {
+            return Ok(());
+        }
+        Err(parse_error(format!(
+            "expected {}, found {:?}",
+            keyword,
+            self.peek()
+        )))
+    }
+
+    fn select(&mut self) -> Result<SelectStatement, CommonError> {
+        self.expect_keyword("SELECT")?;
+        let mut fields = Vec::new();
+        loop {
+            fields.push(self.select_field()?);
+            if !self.eat(&Token::Comma) {
+                break;
+            }
+        }
+
+        self.expect_keyword("FROM")?;
+        let mut from = Vec::new();
+        loop {
+            match self.next() {
+                Some(Token::Str(filter)) => {
+                    check_topic_filter(&filter)?;
+                    from.push(filter);
+                }
+                token => {
+                    return Err(parse_error(format!(
+                        "FROM expects quoted topic filters, found {:?}",
+                        token
+                    )))
+                }
+            }
+            if !self.eat(&Token::Comma) {
+                break;
+            }
+        }
+
+        let filter = if self.eat_keyword("WHERE") {
+            Some(self.expr()?)
+        } else {
+            None
+        };
+        Ok(SelectStatement {
+            fields,
+            from,
+            filter,
+        })
+    }
+
+    fn select_field(&mut self) -> Result<SelectField, CommonError> {
+        if self.eat(&Token::Star) {
+            return Ok(SelectField::All);
+        }
+        let expr = self.expr()?;
+        let alias = if self.eat_keyword("AS") {
+            match self.next() {
+                Some(Token::Ident(alias)) | Some(Token::Str(alias)) => alias,
+                token => return Err(parse_error(format!("expected an alias, found {:?}", token))),
+            }
+        } else {
+            default_alias(&expr).ok_or_else(|| {
+                parse_error(
+                    "a selected expression needs an alias, e.g. `a + b AS total`".to_string(),
+                )
+            })?
+        };
+        Ok(SelectField::Expr { expr, alias })
+    }
+
+    fn expr(&mut self) -> Result<Expr, CommonError> {
+        self.or()
+    }
+
+    fn or(&mut self) -> Result<Expr, CommonError> {
+        let mut left = self.and()?;
+        while self.eat_keyword("OR") {
+            let right = self.and()?;
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, CommonError> {
+        let mut left = self.not()?;
+        while self.eat_keyword("AND") {
+            let right = self.not()?;
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, CommonError> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Unary(UnaryOp::Not, Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, CommonError> {
+        let left = self.additive()?;
+
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull {
+                expr: Box::new(left),
+                negated,
+            });
+        }
+
+        let negated = self.is_keyword("NOT")
+            && (self.is_keyword_at(self.pos + 1, "IN") || self.is_keyword_at(self.pos + 1, "LIKE"));
+        if negated {
+            self.pos += 1;
+        }
+        if self.eat_keyword("IN") {
+            self.expect(&Token::LParen)?;
+            let list = self.expr_list(&Token::RParen)?;
+            return Ok(Expr::InList {
+                expr: Box::new(left),
+                list,
+                negated,
+            });
+        }
+        if self.eat_keyword("LIKE") {
+            let pattern = self.additive()?;
+            return Ok(Expr::Like {
+                expr: Box::new(left),
+                pattern: Box::new(pattern),
+                negated,
+            });
+        }
+
+        let op = match self.peek() {
+            Some(Token::Eq) => BinaryOp::Eq,
+            Some(Token::NotEq) => BinaryOp::NotEq,
+            Some(Token::Lt) => BinaryOp::Lt,
+            Some(Token::LtEq) => BinaryOp::LtEq,
+            Some(Token::Gt) => BinaryOp::Gt,
+            Some(Token::GtEq) => BinaryOp::GtEq,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.additive()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, CommonError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => BinaryOp::Add,
+                Some(Token::Minus) => BinaryOp::Sub,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.multiplicative()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, CommonError> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => BinaryOp::Mul,
+                Some(Token::Slash) => BinaryOp::Div,
+                Some(Token::Percent) => BinaryOp::Mod,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, CommonError> {
+        if self.eat(&Token::Minus) {
+            return Ok(Expr::Unary(UnaryOp::Neg, Box::new(self.unary()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, CommonError> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(&Token::Dot) {
+                match self.next() {
+                    Some(Token::Ident(key)) | Some(Token::Str(key)) => {
+                        expr = Expr::Get(Box::new(expr), Accessor::Key(key));
+                    }
+                    token => {
+                        return Err(parse_error(format!(
+                            "expected a field name after '.', found {:?}",
+                            token
+                        )))
+                    }
+                }
+            } else if self.eat(&Token::LBracket) {
+                let accessor = match self.next() {
+                    Some(Token::Int(index)) if index >= 0 => Accessor::Index(index as usize),
+                    Some(Token::Str(key)) => Accessor::Key(key),
+                    token => {
+                        return Err(parse_error(format!(
+                            "expected an index or a quoted key in [], found {:?}",
+                            token
+                        )))
+                    }
+                };
+                self.expect(&Token::RBracket)?;
+                expr = Expr::Get(Box::new(expr), accessor);
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, CommonError> {
+        match self.next() {
+            Some(Token::Int(value)) => Ok(Expr::Literal(Value::from(value))),
+            Some(Token::Float(value)) => Ok(Expr::Literal(Value::from(value))),
+            Some(Token::Str(value)) => Ok(Expr::Literal(Value::String(value))),
+            Some(Token::LParen) => {
+                let expr = self.expr()?;
+                self.expect(&Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(ident)) => {
+                if self.eat(&Token::LParen) {
+                    let name = ident.to_lowercase();
+                    let args = self.expr_list(&Token::RParen)?;
+                    check_function(&name, args.len())?;
+                    return Ok(Expr::Call { name, args });
+                }
+                if ident.eq_ignore_ascii_case("true") {
+                    return Ok(Expr::Literal(Value::Bool(true)));
+                }
+                if ident.eq_ignore_ascii_case("false") {
+                    return Ok(Expr::Literal(Value::Bool(false)));
+                }
+                if ident.eq_ignore_ascii_case("null") {
+                    return Ok(Expr::Literal(Value::Null));
+                }
+                if !MESSAGE_FIELDS.contains(&ident.as_str()) {
+                    return Err(parse_error(format!(
+                        "unknown field {}, expected one of {}",
+                        ident,
+                        MESSAGE_FIELDS.join(", ")
+                    )));
+                }
+                Ok(Expr::Field(ident))
+            }
+            token => Err(parse_error(format!("unexpected {:?}", token))),
+        }
+    }
+
+    fn expr_list(&mut self, close: &Token) -> Result<Vec<Expr>, CommonError> {
+        let mut list = Vec::new();
+        if self.eat(close) {
+            return Ok(list);
+        }
+        loop {
+            list.push(self.expr()?);
+            if self.eat(close) {
+                return Ok(list);
+            }
+            self.expect(&Token::Comma)?;
+        }
+    }
+}
+
+/// Output name of a selected expression without `AS`: the last field name of
+/// a path (`payload.temp` is `temp`) or the function name of a call.
+fn default_alias(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Field(name) => Some(name.clone()),
+        Expr::Get(_, Accessor::Key(key)) => Some(key.clone()),
+        Expr::Get(parent, Accessor::Index(_)) => default_alias(parent),
+        Expr::Call { name, .. } => Some(name.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_select_statement() {
+        let statement = parse_select(
+            r#"SELECT payload.temp AS t, clientid, payload.list[0] FROM "sensors/+/data", 'a/#' WHERE payload.temp > 40 AND NOT topic LIKE '%/test'"#,
+        )
+        .unwrap();
+        assert_eq!(
+            statement.from,
+            vec!["sensors/+/data".to_string(), "a/#".to_string()]
+        );
+        let aliases: Vec<&str> = statement
+            .fields
+            .iter()
+            .map(|f| match f {
+                SelectField::Expr { alias, .. } => alias.as_str(),
+                SelectField::All => "*",
+            })
+            .collect();
+        assert_eq!(aliases, vec!["t", "clientid", "list"]);
+
+        let Some(Expr::Binary(BinaryOp::And, left, right)) = statement.filter else {
+            panic!("expected AND");
+        };
+        assert_eq!(
+            *left,
+            Expr::Binary(
+                BinaryOp::Gt,
+                Box::new(Expr::Get(
+                    Box::new(Expr::Field("payload".to_string())),
+                    Accessor::Key("temp".to_string())
+                )),
+                Box::new(Expr::Literal(Value::from(40)))
+            )
+        );
+        assert!(matches!(*right, Expr::Unary(UnaryOp::Not, _)));
+    }
+
+    #[test]
+    fn parse_precedence() {
+        let statement = parse_select("SELECT 1 + 2 * 3 AS x FROM 't'").unwrap();
+        let SelectField::Expr { expr, .. } = &statement.fields[0] else {
+            panic!("expected an expression");
+        };
+        assert_eq!(
+            *expr,
+            Expr::Binary(
+                BinaryOp::Add,
+                Box::new(Expr::Literal(Value::from(1))),
+                Box::new(Expr::Binary(
+                    BinaryOp::Mul,
+                    Box::new(Expr::Literal(Value::from(2))),
+                    Box::new(Expr::Literal(Value::from(3)))
+                ))
+            )
+        );
+
+        let statement =
+            parse_select("SELECT * FROM 't' WHERE qos NOT IN (0, 1) OR username IS NOT NULL")
+                .unwrap();
+        assert_eq!(statement.fields, vec![SelectField::All]);
+        let Some(Expr::Binary(BinaryOp::Or, left, right)) = statement.filter else {
+            panic!("expected OR");
+        };
+        assert!(matches!(*left, Expr::InList { negated: true, .. }));
+        assert!(matches!(*right, Expr::IsNull { negated: true, .. }));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for sql in [
+            "SELECT FROM 't'",
+            "SELECT a FROM 't'",
+            "SELECT clientid FROM t",
+            "SELECT clientid FROM 'a/#/b'",
+            "SELECT 1 + 1 FROM 't'",
+            "SELECT upper(clientid, 1) FROM 't'",
+            "SELECT no_such_function(clientid) FROM 't'",
+            "SELECT clientid FROM 't' WHERE",
+            "SELECT clientid FROM 't' LIMIT 1",
+        ] {
+            assert!(parse_select(sql).is_err(), "{}", sql);
+        }
+    }
+}
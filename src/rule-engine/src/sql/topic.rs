@@ -0,0 +1,77 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+
+/// Checks an MQTT-style topic filter: `+` matches one level and `#` the rest,
+/// and both have to take up a whole level.
+pub fn check_topic_filter(filter: &str) -> Result<(), CommonError> {
+    if filter.is_empty() {
+        return Err(CommonError::CommonError(
+            "topic filter must not be empty".to_string(),
+        ));
+    }
+    let levels: Vec<&str> = filter.split('/').collect();
+    for (i, level) in levels.iter().enumerate() {
+        let valid = match *level {
+            "+" => true,
+            "#" => i == levels.len() - 1,
+            _ => !level.contains('+') && !level.contains('#'),
+        };
+        if !valid {
+            return Err(CommonError::CommonError(format!(
+                "invalid topic filter {}",
+                filter
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn topic_filter_match(filter: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    for level in filter.split('/') {
+        if level == "#" {
+            return true;
+        }
+        match topic_levels.next() {
+            Some(topic_level) if level == "+" || level == topic_level => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_topic_filter, topic_filter_match};
+
+    #[test]
+    fn topic_filter() {
+        assert!(topic_filter_match("sensors/+/data", "sensors/s1/data"));
+        assert!(!topic_filter_match("sensors/+/data", "sensors/s1/s2/data"));
+        assert!(!topic_filter_match("sensors/+/data", "sensors/s1"));
+        assert!(topic_filter_match("sensors/#", "sensors"));
+        assert!(topic_filter_match("sensors/#", "sensors/s1/data"));
+        assert!(topic_filter_match("#", "any/topic"));
+        assert!(topic_filter_match("orders", "orders"));
+        assert!(!topic_filter_match("orders", "orders/1"));
+
+        assert!(check_topic_filter("sensors/+/data").is_ok());
+        assert!(check_topic_filter("sensors/#").is_ok());
+        assert!(check_topic_filter("sensors/#/data").is_err());
+        assert!(check_topic_filter("sensors/s+").is_err());
+        assert!(check_topic_filter("").is_err());
+    }
+}
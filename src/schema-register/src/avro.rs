@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use apache_avro::Schema;
+use apache_avro::{from_avro_datum, Schema};
 use common_base::error::common::CommonError;
 
 pub fn avro_validate(schema: &str, data: &[u8]) -> Result<bool, CommonError> {
@@ -28,12 +28,22 @@ pub fn avro_validate(schema: &str, data: &[u8]) -> Result<bool, CommonError> {
     Ok(res)
 }
 
+/// Validates a single binary-encoded datum, the form Confluent serializers
+/// write after the wire-format header. Trailing bytes make it invalid.
+pub fn avro_datum_validate(schema: &str, data: &[u8]) -> Result<bool, CommonError> {
+    let schema = Schema::parse_str(schema)?;
+
+    let mut reader = data;
+    let value = from_avro_datum(&schema, &mut reader, None)?;
+    Ok(reader.is_empty() && value.validate(&schema))
+}
+
 #[cfg(test)]
 mod test {
     use apache_avro::{from_value, Schema, Writer};
     use serde::{Deserialize, Serialize};
 
-    use crate::avro::avro_validate;
+    use crate::avro::{avro_datum_validate, avro_validate};
 
     #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
     struct TestData {
@@ -100,6 +110,32 @@ pub fn avro_validate_test() {
         assert!(result.is_err());
     }
 
+    #[test]
+    pub fn avro_datum_validate_test() {
+        let raw_schema = r#"
+                {
+                    "type": "record",
+                    "name": "test",
+                    "fields": [
+                        {"name": "a", "type": "long"},
+                        {"name": "b", "type": "string"}
+                    ]
+                }
+                "#;
+        let schema = Schema::parse_str(raw_schema).unwrap();
+        let value = apache_avro::to_value(TestData {
+            a: 1,
+            b: "test".to_string(),
+        })
+        .unwrap();
+        let mut datum = apache_avro::to_avro_datum(&schema, value).unwrap();
+        assert!(avro_datum_validate(raw_schema, &datum).unwrap());
+
+        datum.push(0);
+        assert!(!avro_datum_validate(raw_schema, &datum).unwrap());
+        assert!(avro_datum_validate(raw_schema, b"\x02").is_err());
+    }
+
     #[test]
     pub fn avro_encode_validator_test() {
         let raw_schema = r#"
@@ -0,0 +1,518 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use apache_avro::schema_compatibility::SchemaCompatibility as AvroCompatibility;
+use apache_avro::Schema as AvroSchema;
+use common_base::error::common::CommonError;
+use metadata_struct::schema::{SchemaCompatibility, SchemaType, SchemaVersion};
+use prost_reflect::Kind;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use valico::json_schema;
+
+use crate::proto_parser::proto_tokens;
+use crate::protobuf::ProtobufSchema;
+
+/// Fails if `schema` is not a valid schema of its type.
+pub fn parse_schema(schema_type: &SchemaType, schema: &str) -> Result<(), CommonError> {
+    match schema_type {
+        SchemaType::AVRO => {
+            AvroSchema::parse_str(schema)?;
+        }
+        SchemaType::JSON => {
+            let value: Value = serde_json::from_str(schema)?;
+            let mut scope = json_schema::Scope::new();
+            scope.compile_and_return(value, false)?;
+        }
+        SchemaType::PROTOBUF => {
+            ProtobufSchema::parse(schema)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether two schemas are the same apart from formatting, comments and, for
+/// Avro, attributes that don't change the encoding.
+pub fn is_same_schema(schema_type: &SchemaType, a: &str, b: &str) -> bool {
+    match schema_type {
+        SchemaType::AVRO => match (AvroSchema::parse_str(a), AvroSchema::parse_str(b)) {
+            (Ok(a), Ok(b)) => a.canonical_form() == b.canonical_form(),
+            _ => a == b,
+        },
+        SchemaType::JSON => match (
+            serde_json::from_str::<Value>(a),
+            serde_json::from_str::<Value>(b),
+        ) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => a == b,
+        },
+        SchemaType::PROTOBUF => proto_tokens(a) == proto_tokens(b),
+    }
+}
+
+/// Checks `schema` against the versions of its subject, oldest first, as the
+/// compatibility level requires. Returns one message per incompatibility;
+/// an empty list means the schema can be registered.
+pub fn check_compatibility(
+    schema_type: &SchemaType,
+    schema: &str,
+    previous: &[SchemaVersion],
+    level: SchemaCompatibility,
+) -> Result<Vec<String>, CommonError> {
+    if level == SchemaCompatibility::None {
+        return Ok(Vec::new());
+    }
+    let checked = if level.is_transitive() {
+        previous
+    } else {
+        &previous[previous.len().saturating_sub(1)..]
+    };
+
+    let mut errors = Vec::new();
+    for old in checked {
+        if old.schema_type != *schema_type {
+            errors.push(format!(
+                "version {} is a {} schema, not {}",
+                old.version, old.schema_type, schema_type
+            ));
+            continue;
+        }
+        if level.is_backward() {
+            for e in can_read(schema_type, schema, &old.schema)? {
+                errors.push(format!(
+                    "the new schema cannot read data written with version {}: {}",
+                    old.version, e
+                ));
+            }
+        }
+        if level.is_forward() {
+            for e in can_read(schema_type, &old.schema, schema)? {
+                errors.push(format!(
+                    "version {} cannot read data written with the new schema: {}",
+                    old.version, e
+                ));
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// Whether data written with `writer` can be read with `reader`.
+fn can_read(
+    schema_type: &SchemaType,
+    reader: &str,
+    writer: &str,
+) -> Result<Vec<String>, CommonError> {
+    match schema_type {
+        SchemaType::AVRO => {
+            let reader = AvroSchema::parse_str(reader)?;
+            let writer = AvroSchema::parse_str(writer)?;
+            Ok(AvroCompatibility::can_read(&writer, &reader)
+                .err()
+                .map(|e| vec![e.to_string()])
+                .unwrap_or_default())
+        }
+        SchemaType::JSON => {
+            let reader: Value = serde_json::from_str(reader)?;
+            let writer: Value = serde_json::from_str(writer)?;
+            let mut errors = Vec::new();
+            json_can_read(&reader, &writer, "#", &mut errors);
+            Ok(errors)
+        }
+        SchemaType::PROTOBUF => proto_can_read(&proto_messages(reader)?, &proto_messages(writer)?),
+    }
+}
+
+// JSON Schema: a structural check of the keywords that decide whether a
+// document accepted by the writer is accepted by the reader.
+fn json_can_read(reader: &Value, writer: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(reader) = reader.as_object() else {
+        // `true` accepts everything; `false` would reject everything.
+        if reader == &Value::Bool(false) {
+            errors.push(format!("{path}: the reader rejects every document"));
+        }
+        return;
+    };
+    let empty = serde_json::Map::new();
+    let writer = writer.as_object().unwrap_or(&empty);
+
+    if let Some(reader_types) = json_types(reader) {
+        match json_types(writer) {
+            Some(writer_types) => {
+                for t in &writer_types {
+                    let accepted = reader_types.contains(t)
+                        || (t == "integer" && reader_types.contains("number"));
+                    if !accepted {
+                        errors.push(format!("{path}: type {t} is not accepted by the reader"));
+                    }
+                }
+            }
+            None => errors.push(format!(
+                "{path}: the reader requires type {}, the writer accepts any type",
+                reader_types.iter().cloned().collect::<Vec<_>>().join("|")
+            )),
+        }
+    }
+
+    if let Some(reader_enum) = reader.get("enum").and_then(|e| e.as_array()) {
+        match writer.get("enum").and_then(|e| e.as_array()) {
+            Some(writer_enum) => {
+                for value in writer_enum {
+                    if !reader_enum.contains(value) {
+                        errors.push(format!("{path}: enum value {value} was removed"));
+                    }
+                }
+            }
+            None => errors.push(format!("{path}: the reader added an enum restriction")),
+        }
+    }
+
+    let reader_props = reader.get("properties").and_then(|p| p.as_object());
+    let writer_props = writer.get("properties").and_then(|p| p.as_object());
+    let writer_required = json_required(writer);
+    for name in json_required(reader) {
+        if !writer_required.contains(&name) {
+            errors.push(format!(
+                "{path}: property {name} is required by the reader but not by the writer"
+            ));
+        }
+    }
+    if let (Some(reader_props), Some(writer_props)) = (reader_props, writer_props) {
+        for (name, reader_prop) in reader_props {
+            if let Some(writer_prop) = writer_props.get(name) {
+                json_can_read(
+                    reader_prop,
+                    writer_prop,
+                    &format!("{path}/properties/{name}"),
+                    errors,
+                );
+            }
+        }
+    }
+    if reader.get("additionalProperties") == Some(&Value::Bool(false)) {
+        for name in writer_props.into_iter().flat_map(|p| p.keys()) {
+            if !reader_props.is_some_and(|p| p.contains_key(name)) {
+                errors.push(format!(
+                    "{path}: property {name} is not allowed by the reader"
+                ));
+            }
+        }
+        if writer.get("additionalProperties") != Some(&Value::Bool(false)) {
+            errors.push(format!(
+                "{path}: the reader does not allow additional properties, the writer does"
+            ));
+        }
+    }
+
+    if let (Some(reader_items), Some(writer_items)) = (reader.get("items"), writer.get("items")) {
+        json_can_read(reader_items, writer_items, &format!("{path}/items"), errors);
+    }
+}
+
+fn json_types(schema: &serde_json::Map<String, Value>) -> Option<HashSet<String>> {
+    match schema.get("type")? {
+        Value::String(t) => Some(HashSet::from([t.clone()])),
+        Value::Array(types) => Some(
+            types
+                .iter()
+                .filter_map(|t| t.as_str().map(|t| t.to_string()))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn json_required(schema: &serde_json::Map<String, Value>) -> Vec<String> {
+    schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| {
+            r.iter()
+                .filter_map(|name| name.as_str().map(|name| name.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Protobuf: fields are matched by number, so renaming or removing fields is
+// fine while changing the wire type of a number is not.
+#[derive(Debug, PartialEq)]
+struct ProtoField {
+    name: String,
+    type_name: String,
+    repeated: bool,
+}
+
+type ProtoMessages = BTreeMap<String, BTreeMap<u32, ProtoField>>;
+
+fn proto_can_read(
+    reader: &ProtoMessages,
+    writer: &ProtoMessages,
+) -> Result<Vec<String>, CommonError> {
+    let mut errors = Vec::new();
+    for (message, reader_fields) in reader {
+        let Some(writer_fields) = writer.get(message) else {
+            continue;
+        };
+        for (number, reader_field) in reader_fields {
+            let Some(writer_field) = writer_fields.get(number) else {
+                continue;
+            };
+            if reader_field.repeated != writer_field.repeated {
+                errors.push(format!(
+                    "{message}: field {number} ({}) changed between repeated and singular",
+                    reader_field.name
+                ));
+            } else if proto_wire_group(&reader_field.type_name)
+                != proto_wire_group(&writer_field.type_name)
+            {
+                errors.push(format!(
+                    "{message}: field {number} ({}) changed type from {} to {}",
+                    reader_field.name, writer_field.type_name, reader_field.type_name
+                ));
+            }
+        }
+    }
+    Ok(errors)
+}
+
+// Types that share a wire encoding can be read as one another.
+fn proto_wire_group(type_name: &str) -> String {
+    match type_name {
+        "int32" | "int64" | "uint32" | "uint64" | "bool" => "varint".to_string(),
+        "sint32" | "sint64" => "zigzag".to_string(),
+        "fixed32" | "sfixed32" => "fixed32".to_string(),
+        "fixed64" | "sfixed64" => "fixed64".to_string(),
+        "string" | "bytes" => "bytes".to_string(),
+        // Message and enum types, matched on their name without the package.
+        other => other.rsplit('.').next().unwrap_or(other).to_string(),
+    }
+}
+
+fn proto_messages(schema: &str) -> Result<ProtoMessages, CommonError> {
+    let mut messages = ProtoMessages::new();
+    for message in ProtobufSchema::parse(schema)?.messages() {
+        let fields = message
+            .fields()
+            .map(|field| {
+                let field_info = ProtoField {
+                    name: field.name().to_string(),
+                    type_name: proto_type_name(&field.kind()),
+                    // map fields are repeated entry messages on the wire
+                    repeated: field.is_list() || field.is_map(),
+                };
+                (field.number(), field_info)
+            })
+            .collect();
+        messages.insert(message.full_name().to_string(), fields);
+    }
+    Ok(messages)
+}
+
+fn proto_type_name(kind: &Kind) -> String {
+    let name = match kind {
+        Kind::Double => "double",
+        Kind::Float => "float",
+        Kind::Int32 => "int32",
+        Kind::Int64 => "int64",
+        Kind::Uint32 => "uint32",
+        Kind::Uint64 => "uint64",
+        Kind::Sint32 => "sint32",
+        Kind::Sint64 => "sint64",
+        Kind::Fixed32 => "fixed32",
+        Kind::Fixed64 => "fixed64",
+        Kind::Sfixed32 => "sfixed32",
+        Kind::Sfixed64 => "sfixed64",
+        Kind::Bool => "bool",
+        Kind::String => "string",
+        Kind::Bytes => "bytes",
+        Kind::Message(message) => message.full_name(),
+        Kind::Enum(enum_type) => enum_type.full_name(),
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn version(version: u32, schema_type: SchemaType, schema: &str) -> SchemaVersion {
+        SchemaVersion {
+            tenant: "default".to_string(),
+            subject: "orders-value".to_string(),
+            version,
+            id: version,
+            schema_type,
+            schema: schema.to_string(),
+            create_time: 0,
+        }
+    }
+
+    #[test]
+    fn avro_backward_and_forward() {
+        let v1 = r#"{"type":"record","name":"Order","fields":[{"name":"id","type":"long"}]}"#;
+        // Adding a field with a default keeps both directions working.
+        let v2 = r#"{"type":"record","name":"Order","fields":[
+            {"name":"id","type":"long"},
+            {"name":"note","type":"string","default":""}]}"#;
+        // Without a default, new readers can't fill it in for old data.
+        let v3 = r#"{"type":"record","name":"Order","fields":[
+            {"name":"id","type":"long"},
+            {"name":"note","type":"string"}]}"#;
+        let previous = [version(1, SchemaType::AVRO, v1)];
+
+        for level in [SchemaCompatibility::Backward, SchemaCompatibility::Full] {
+            assert!(check_compatibility(&SchemaType::AVRO, v2, &previous, level)
+                .unwrap()
+                .is_empty());
+        }
+        assert!(!check_compatibility(
+            &SchemaType::AVRO,
+            v3,
+            &previous,
+            SchemaCompatibility::Backward
+        )
+        .unwrap()
+        .is_empty());
+        assert!(check_compatibility(
+            &SchemaType::AVRO,
+            v3,
+            &previous,
+            SchemaCompatibility::Forward
+        )
+        .unwrap()
+        .is_empty());
+        assert!(
+            check_compatibility(&SchemaType::AVRO, v3, &previous, SchemaCompatibility::None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn transitive_checks_every_version() {
+        let v1 = r#"{"type":"record","name":"Order","fields":[{"name":"id","type":"long"}]}"#;
+        let v2 = r#"{"type":"record","name":"Order","fields":[
+            {"name":"id","type":"long"},
+            {"name":"note","type":"string","default":""}]}"#;
+        let v3 = r#"{"type":"record","name":"Order","fields":[
+            {"name":"id","type":"long"},
+            {"name":"note","type":"string","default":""},
+            {"name":"qty","type":"int"}]}"#;
+        // v3's qty has no default, and v2 is no different from v1 there.
+        let previous = [
+            version(1, SchemaType::AVRO, v1),
+            version(2, SchemaType::AVRO, v2),
+        ];
+        let errors = check_compatibility(
+            &SchemaType::AVRO,
+            v3,
+            &previous,
+            SchemaCompatibility::BackwardTransitive,
+        )
+        .unwrap();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn json_required_and_types() {
+        let v1 = r#"{"type":"object","properties":{"id":{"type":"integer"}},"required":["id"]}"#;
+        let v2 = r#"{"type":"object","properties":{
+            "id":{"type":"number"},"note":{"type":"string"}},"required":["id","note"]}"#;
+        let previous = [version(1, SchemaType::JSON, v1)];
+
+        // Old documents have no note, which v2 requires.
+        let backward = check_compatibility(
+            &SchemaType::JSON,
+            v2,
+            &previous,
+            SchemaCompatibility::Backward,
+        )
+        .unwrap();
+        assert_eq!(backward.len(), 1);
+        assert!(backward[0].contains("note"));
+
+        // v1 readers can't take the non-integer ids v2 allows.
+        let forward = check_compatibility(
+            &SchemaType::JSON,
+            v2,
+            &previous,
+            SchemaCompatibility::Forward,
+        )
+        .unwrap();
+        assert_eq!(forward.len(), 1);
+        assert!(forward[0].contains("number"));
+    }
+
+    #[test]
+    fn protobuf_field_numbers() {
+        let v1 = r#"
+            syntax = "proto3";
+            package shop;
+            // An order.
+            message Order {
+                int64 id = 1;
+                string note = 2;
+                message Line { string sku = 1; }
+                repeated Line lines = 3;
+                oneof payment { string card = 4; string iban = 5; }
+            }
+        "#;
+        let renamed = v1.replace("string note = 2", "bytes comment = 2");
+        let retyped = v1.replace("string sku = 1", "double sku = 1");
+        let previous = [version(1, SchemaType::PROTOBUF, v1)];
+
+        assert!(check_compatibility(
+            &SchemaType::PROTOBUF,
+            &renamed,
+            &previous,
+            SchemaCompatibility::Full
+        )
+        .unwrap()
+        .is_empty());
+        let errors = check_compatibility(
+            &SchemaType::PROTOBUF,
+            &retyped,
+            &previous,
+            SchemaCompatibility::Backward,
+        )
+        .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("shop.Order.Line"));
+
+        let messages = proto_messages(v1).unwrap();
+        assert_eq!(messages["shop.Order"].len(), 5);
+        assert!(messages["shop.Order"][&3].repeated);
+    }
+
+    #[test]
+    fn same_schema_ignores_formatting() {
+        assert!(is_same_schema(
+            &SchemaType::JSON,
+            r#"{"type": "string"}"#,
+            r#"{ "type":"string" }"#
+        ));
+        assert!(is_same_schema(
+            &SchemaType::AVRO,
+            r#"{"type":"record","name":"A","doc":"x","fields":[{"name":"a","type":"int"}]}"#,
+            r#"{"type":"record","name":"A","fields":[{"name":"a","type":"int"}]}"#
+        ));
+        assert!(is_same_schema(
+            &SchemaType::PROTOBUF,
+            "message A { int32 a = 1; } // old",
+            "message A {\n  int32 a = 1;\n}"
+        ));
+        assert!(parse_schema(&SchemaType::AVRO, r#"{"type":"recor"}"#).is_err());
+    }
+}
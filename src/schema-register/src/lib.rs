@@ -14,6 +14,9 @@
 
 #![allow(clippy::result_large_err)]
 pub mod avro;
+pub mod compatibility;
 pub mod json;
+pub mod proto_parser;
 pub mod protobuf;
 pub mod schema;
+pub mod wire;
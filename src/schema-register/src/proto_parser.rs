@@ -0,0 +1,610 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Turns `.proto` source into a `FileDescriptorProto`, so text schemas load
+//! into the same descriptor pool as uploaded descriptor sets. Services,
+//! extensions and options other than `default`, `packed` and `allow_alias`
+//! are skipped: they don't change how a payload decodes.
+
+use common_base::error::common::CommonError;
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumOptions, EnumValueDescriptorProto,
+    FieldDescriptorProto, FieldOptions, FileDescriptorProto, MessageOptions, OneofDescriptorProto,
+};
+
+pub fn parse_proto_file(name: &str, source: &str) -> Result<FileDescriptorProto, CommonError> {
+    let tokens = proto_tokens(source);
+    let mut parser = ProtoParser {
+        file: name,
+        tokens: &tokens,
+        pos: 0,
+        proto3: false,
+    };
+    let mut file = FileDescriptorProto {
+        name: Some(name.to_string()),
+        ..Default::default()
+    };
+
+    while let Some(token) = parser.peek() {
+        match token {
+            "syntax" => {
+                parser.pos += 1;
+                parser.expect("=")?;
+                let syntax = parser.string()?;
+                if syntax != "proto2" && syntax != "proto3" {
+                    return Err(parser.error(&format!("unsupported syntax {syntax}")));
+                }
+                parser.proto3 = syntax == "proto3";
+                parser.expect(";")?;
+                file.syntax = Some(syntax);
+            }
+            "edition" => return Err(parser.error("editions are not supported")),
+            "package" => {
+                parser.pos += 1;
+                file.package = Some(parser.ident()?);
+                parser.expect(";")?;
+            }
+            "import" => {
+                parser.pos += 1;
+                let index = file.dependency.len() as i32;
+                if parser.eat("public") {
+                    file.public_dependency.push(index);
+                } else if parser.eat("weak") {
+                    file.weak_dependency.push(index);
+                }
+                file.dependency.push(parser.string()?);
+                parser.expect(";")?;
+            }
+            "message" => file.message_type.push(parser.message()?),
+            "enum" => file.enum_type.push(parser.enum_type()?),
+            "option" => parser.skip_statement(),
+            "service" | "extend" => parser.skip_block(),
+            ";" => parser.pos += 1,
+            _ => return Err(parser.unexpected()),
+        }
+    }
+    Ok(file)
+}
+
+struct ProtoParser<'a> {
+    file: &'a str,
+    tokens: &'a [String],
+    pos: usize,
+    proto3: bool,
+}
+
+impl<'a> ProtoParser<'a> {
+    fn message(&mut self) -> Result<DescriptorProto, CommonError> {
+        self.pos += 1;
+        let mut message = DescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        };
+        self.expect("{")?;
+
+        loop {
+            match self.peek() {
+                Some("}") => {
+                    self.pos += 1;
+                    break;
+                }
+                Some("message") => {
+                    let nested = self.message()?;
+                    message.nested_type.push(nested);
+                }
+                Some("enum") => {
+                    let nested = self.enum_type()?;
+                    message.enum_type.push(nested);
+                }
+                Some("oneof") => self.oneof(&mut message)?,
+                Some("option" | "reserved" | "extensions") => self.skip_statement(),
+                Some("extend") => self.skip_block(),
+                Some(";") => self.pos += 1,
+                Some(_) => {
+                    let field = self.field(&mut message, None)?;
+                    message.field.push(field);
+                }
+                None => return Err(self.unexpected()),
+            }
+        }
+
+        // proto3 `optional` fields each get a synthetic oneof, declared after
+        // the real ones.
+        for i in 0..message.field.len() {
+            if message.field[i].proto3_optional() {
+                message.field[i].oneof_index = Some(message.oneof_decl.len() as i32);
+                message.oneof_decl.push(OneofDescriptorProto {
+                    name: Some(format!("_{}", message.field[i].name())),
+                    ..Default::default()
+                });
+            }
+        }
+        Ok(message)
+    }
+
+    fn oneof(&mut self, message: &mut DescriptorProto) -> Result<(), CommonError> {
+        self.pos += 1;
+        let index = message.oneof_decl.len() as i32;
+        message.oneof_decl.push(OneofDescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        });
+        self.expect("{")?;
+
+        loop {
+            match self.peek() {
+                Some("}") => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some("option") => self.skip_statement(),
+                Some(";") => self.pos += 1,
+                Some(_) => {
+                    let field = self.field(message, Some(index))?;
+                    message.field.push(field);
+                }
+                None => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    // `[label] <type> <name> = <number> [options];` or
+    // `map<<key>, <value>> <name> = <number> [options];`
+    fn field(
+        &mut self,
+        message: &mut DescriptorProto,
+        oneof_index: Option<i32>,
+    ) -> Result<FieldDescriptorProto, CommonError> {
+        let (label, proto3_optional) = match self.peek() {
+            Some("repeated") => (Label::Repeated, false),
+            Some("required") => (Label::Required, false),
+            Some("optional") => (Label::Optional, self.proto3),
+            _ => (Label::Optional, false),
+        };
+        if matches!(self.peek(), Some("repeated" | "required" | "optional")) {
+            self.pos += 1;
+        }
+
+        let type_name = self.ident()?;
+        if type_name == "group" {
+            return Err(self.error("groups are not supported"));
+        }
+        let mut field = if type_name == "map" && self.eat("<") {
+            let key = self.ident()?;
+            self.expect(",")?;
+            let value = self.ident()?;
+            self.expect(">")?;
+            let name = self.ident()?;
+            self.expect("=")?;
+            let number = self.number()?;
+
+            let entry = map_entry_name(&name);
+            message.nested_type.push(DescriptorProto {
+                name: Some(entry.clone()),
+                field: vec![
+                    typed_field("key", 1, Label::Optional, &key),
+                    typed_field("value", 2, Label::Optional, &value),
+                ],
+                options: Some(MessageOptions {
+                    map_entry: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+            typed_field(&name, number, Label::Repeated, &entry)
+        } else {
+            let name = self.ident()?;
+            self.expect("=")?;
+            let number = self.number()?;
+            typed_field(&name, number, label, &type_name)
+        };
+        field.oneof_index = oneof_index;
+        if proto3_optional {
+            field.proto3_optional = Some(true);
+        }
+
+        if self.eat("[") {
+            for (name, value) in self.options()? {
+                match name.as_str() {
+                    "default" => field.default_value = Some(value),
+                    "packed" => {
+                        field
+                            .options
+                            .get_or_insert_with(FieldOptions::default)
+                            .packed = Some(value == "true");
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.expect(";")?;
+        Ok(field)
+    }
+
+    fn enum_type(&mut self) -> Result<EnumDescriptorProto, CommonError> {
+        self.pos += 1;
+        let mut enum_type = EnumDescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        };
+        self.expect("{")?;
+
+        loop {
+            match self.peek() {
+                Some("}") => {
+                    self.pos += 1;
+                    return Ok(enum_type);
+                }
+                Some("option") => {
+                    if self.tokens[self.pos..].starts_with(&[
+                        "option".to_string(),
+                        "allow_alias".to_string(),
+                        "=".to_string(),
+                        "true".to_string(),
+                    ]) {
+                        enum_type.options = Some(EnumOptions {
+                            allow_alias: Some(true),
+                            ..Default::default()
+                        });
+                    }
+                    self.skip_statement();
+                }
+                Some("reserved") => self.skip_statement(),
+                Some(";") => self.pos += 1,
+                Some(_) => {
+                    let name = self.ident()?;
+                    self.expect("=")?;
+                    let negative = self.eat("-");
+                    let number = self.number()?;
+                    if self.eat("[") {
+                        self.options()?;
+                    }
+                    self.expect(";")?;
+                    enum_type.value.push(EnumValueDescriptorProto {
+                        name: Some(name),
+                        number: Some(if negative { -number } else { number }),
+                        options: None,
+                    });
+                }
+                None => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    // `name = value, ...]` after the opening bracket. Values are the tokens
+    // joined back together, with string quotes removed.
+    fn options(&mut self) -> Result<Vec<(String, String)>, CommonError> {
+        let mut options = Vec::new();
+        loop {
+            let start = self.pos;
+            let mut depth = 0;
+            while let Some(token) = self.peek() {
+                match token {
+                    "{" | "(" | "[" => depth += 1,
+                    "}" | ")" => depth -= 1,
+                    "]" if depth == 0 => break,
+                    "]" => depth -= 1,
+                    "," if depth == 0 => break,
+                    _ => {}
+                }
+                self.pos += 1;
+            }
+            let option = &self.tokens[start..self.pos];
+            if let Some(eq) = option.iter().position(|t| t == "=") {
+                let value = option[eq + 1..]
+                    .iter()
+                    .map(|t| unquote(t).unwrap_or(t))
+                    .collect::<String>();
+                options.push((option[..eq].concat(), value));
+            }
+            match self.peek() {
+                Some(",") => self.pos += 1,
+                Some("]") => {
+                    self.pos += 1;
+                    return Ok(options);
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(|t| t.as_str())
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), CommonError> {
+        if self.eat(token) {
+            return Ok(());
+        }
+        Err(self.error(&format!(
+            "expected {token}, found {}",
+            self.peek().unwrap_or("end of file")
+        )))
+    }
+
+    fn ident(&mut self) -> Result<String, CommonError> {
+        match self.peek() {
+            Some(token)
+                if token
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '.') =>
+            {
+                self.pos += 1;
+                Ok(token.to_string())
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // Adjacent string literals are concatenated, as in C.
+    fn string(&mut self) -> Result<String, CommonError> {
+        let mut value = String::new();
+        let mut found = false;
+        while let Some(part) = self.peek().and_then(unquote) {
+            value.push_str(part);
+            found = true;
+            self.pos += 1;
+        }
+        if !found {
+            return Err(self.unexpected());
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<i32, CommonError> {
+        let number = self
+            .peek()
+            .and_then(parse_int)
+            .ok_or_else(|| self.unexpected())?;
+        self.pos += 1;
+        Ok(number)
+    }
+
+    fn skip_statement(&mut self) {
+        self.pos = skip_statement(self.tokens, self.pos);
+    }
+
+    fn skip_block(&mut self) {
+        self.pos = skip_block(self.tokens, self.pos);
+    }
+
+    fn unexpected(&self) -> CommonError {
+        match self.peek() {
+            Some(token) => self.error(&format!("unexpected {token}")),
+            None => self.error("unexpected end of file"),
+        }
+    }
+
+    fn error(&self, message: &str) -> CommonError {
+        CommonError::CommonError(format!(
+            "invalid protobuf schema {}: {}",
+            self.file, message
+        ))
+    }
+}
+
+fn typed_field(name: &str, number: i32, label: Label, type_name: &str) -> FieldDescriptorProto {
+    let mut field = FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(number),
+        label: Some(label as i32),
+        ..Default::default()
+    };
+    match scalar_type(type_name) {
+        Some(scalar) => field.r#type = Some(scalar as i32),
+        // A message or an enum; the pool tells which when it resolves the name.
+        None => field.type_name = Some(type_name.to_string()),
+    }
+    field
+}
+
+fn scalar_type(type_name: &str) -> Option<Type> {
+    Some(match type_name {
+        "double" => Type::Double,
+        "float" => Type::Float,
+        "int32" => Type::Int32,
+        "int64" => Type::Int64,
+        "uint32" => Type::Uint32,
+        "uint64" => Type::Uint64,
+        "sint32" => Type::Sint32,
+        "sint64" => Type::Sint64,
+        "fixed32" => Type::Fixed32,
+        "fixed64" => Type::Fixed64,
+        "sfixed32" => Type::Sfixed32,
+        "sfixed64" => Type::Sfixed64,
+        "bool" => Type::Bool,
+        "string" => Type::String,
+        "bytes" => Type::Bytes,
+        _ => return None,
+    })
+}
+
+// protoc's name for the entry message of a map field: `tag_counts` gets
+// `TagCountsEntry`.
+fn map_entry_name(field: &str) -> String {
+    let mut name = String::new();
+    let mut upper = true;
+    for c in field.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            name.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            name.push(c);
+        }
+    }
+    name + "Entry"
+}
+
+fn unquote(token: &str) -> Option<&str> {
+    let quote = token.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    token
+        .strip_prefix(quote)
+        .map(|t| t.strip_suffix(quote).unwrap_or(t))
+}
+
+fn parse_int(token: &str) -> Option<i32> {
+    if let Some(hex) = token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+    {
+        i32::from_str_radix(hex, 16).ok()
+    } else if token.len() > 1 && token.starts_with('0') {
+        i32::from_str_radix(&token[1..], 8).ok()
+    } else {
+        token.parse().ok()
+    }
+}
+
+fn skip_statement(tokens: &[String], mut i: usize) -> usize {
+    while i < tokens.len() && tokens[i] != ";" && tokens[i] != "{" {
+        i += 1;
+    }
+    if tokens.get(i).is_some_and(|t| t == "{") {
+        return skip_block(tokens, i);
+    }
+    i + 1
+}
+
+fn skip_block(tokens: &[String], mut i: usize) -> usize {
+    let mut depth = 0;
+    while i < tokens.len() {
+        match tokens[i].as_str() {
+            "{" => depth += 1,
+            "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+// Identifiers, numbers, string literals and single-character symbols, with
+// comments and whitespace dropped.
+pub(crate) fn proto_tokens(schema: &str) -> Vec<String> {
+    let chars: Vec<char> = schema.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' || c == '\'' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(chars[start..i.min(chars.len())].iter().collect());
+        } else if c.is_alphanumeric() || c == '_' || c == '.' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_messages_enums_and_maps() {
+        let file = parse_proto_file(
+            "shop/order.proto",
+            r#"
+            syntax = "proto3";
+            package shop;
+            import public "google/protobuf/timestamp.proto";
+
+            message Order {
+                enum Status { option allow_alias = true; NEW = 0; CREATED = 0; GONE = -1; }
+                int64 id = 0x1;
+                optional string note = 2 [json_name = "memo"];
+                map<string, int32> tag_counts = 3;
+                oneof payment { string card = 4; string iban = 5; }
+                repeated .google.protobuf.Timestamp updates = 6 [packed = false];
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(file.package(), "shop");
+        assert_eq!(file.dependency, vec!["google/protobuf/timestamp.proto"]);
+        assert_eq!(file.public_dependency, vec![0]);
+
+        let order = &file.message_type[0];
+        assert_eq!(order.field.len(), 6);
+        assert_eq!(order.field[0].number(), 1);
+        assert_eq!(order.field[0].r#type(), Type::Int64);
+        assert!(order.field[1].proto3_optional());
+        assert_eq!(order.field[1].oneof_index, Some(1));
+        assert_eq!(order.oneof_decl[1].name(), "_note");
+        assert_eq!(order.field[2].type_name(), "TagCountsEntry");
+        assert_eq!(order.field[2].label(), Label::Repeated);
+        assert!(order.nested_type[0].options.as_ref().unwrap().map_entry());
+        assert_eq!(order.field[3].oneof_index, Some(0));
+        assert_eq!(order.field[5].type_name(), ".google.protobuf.Timestamp");
+
+        let status = &order.enum_type[0];
+        assert_eq!(status.value[2].number(), -1);
+        assert!(status.options.as_ref().unwrap().allow_alias());
+    }
+
+    #[test]
+    fn reject_invalid_source() {
+        assert!(parse_proto_file("a.proto", "message A { int32 a = ; }").is_err());
+        assert!(parse_proto_file("a.proto", "message A { int32 a = 1; ").is_err());
+        assert!(parse_proto_file("a.proto", "edition = \"2023\";").is_err());
+        assert!(parse_proto_file("a.proto", "message A { group B = 1 {} }").is_err());
+    }
+}
@@ -12,16 +12,239 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! A protobuf schema is the source of one `.proto` file, or a JSON bundle of
+//! several: either `files`, each file's source keyed by the path other files
+//! import it by, or `descriptor_set`, a base64 `FileDescriptorSet` as written
+//! by `protoc --include_imports --descriptor_set_out`. The `google/protobuf/*`
+//! well-known types can be imported without being uploaded.
+
+use crate::proto_parser::parse_proto_file;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use common_base::error::common::CommonError;
 use metadata_struct::schema::SchemaData;
-use protofish::{decode::Value, prelude::Context};
+use prost::Message;
+use prost_reflect::{
+    Cardinality, DescriptorPool, DynamicMessage, MessageDescriptor, ReflectMessage, Value,
+};
+use prost_types::{FileDescriptorProto, FileDescriptorSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+// The file name a single `.proto` schema is loaded under.
+const SCHEMA_FILE_NAME: &str = "schema.proto";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProtobufBundle {
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    #[serde(default)]
+    pub descriptor_set: Option<String>,
+    /// The file whose first message is used when the binding names none.
+    /// Defaults to the file no other file imports, or for a descriptor set
+    /// to its last file.
+    #[serde(default)]
+    pub main: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProtobufValidateOptions {
+    /// Reject payloads with fields the message doesn't declare.
+    pub strict_unknown_fields: bool,
+    /// Reject payloads missing a `required` field.
+    pub strict_required_fields: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtobufSchema {
+    pool: DescriptorPool,
+    // the schema's own files, without the well-known types
+    files: Vec<String>,
+    main: String,
+}
+
+impl ProtobufSchema {
+    pub fn parse(schema: &str) -> Result<Self, CommonError> {
+        if !schema.trim_start().starts_with('{') {
+            let file = parse_proto_file(SCHEMA_FILE_NAME, schema)?;
+            return ProtobufSchema::build(vec![file], SCHEMA_FILE_NAME.to_string());
+        }
+
+        let bundle: ProtobufBundle = serde_json::from_str(schema)?;
+        let (files, default_main) = match (&bundle.descriptor_set, bundle.files.is_empty()) {
+            (Some(descriptor_set), true) => {
+                let bytes = STANDARD.decode(descriptor_set.trim()).map_err(|e| {
+                    proto_error(&format!("descriptor_set is not valid base64: {e}"))
+                })?;
+                let files = FileDescriptorSet::decode(bytes.as_slice())
+                    .map_err(|e| proto_error(&format!("invalid descriptor_set: {e}")))?
+                    .file;
+                let main = files.last().map(|f| f.name().to_string());
+                (files, main)
+            }
+            (None, false) => {
+                let files = bundle
+                    .files
+                    .iter()
+                    .map(|(name, source)| parse_proto_file(name, source))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let imported: HashSet<&String> =
+                    files.iter().flat_map(|f| f.dependency.iter()).collect();
+                let main = files
+                    .iter()
+                    .map(|f| f.name().to_string())
+                    .find(|name| !imported.contains(name));
+                (files, main)
+            }
+            _ => return Err(proto_error("a bundle has either files or a descriptor_set")),
+        };
+
+        let main = bundle
+            .main
+            .or(default_main)
+            .ok_or_else(|| proto_error("the bundle has no files"))?;
+        if !files.iter().any(|f| f.name() == main) {
+            return Err(proto_error(&format!(
+                "main file {main} is not in the bundle"
+            )));
+        }
+        ProtobufSchema::build(files, main)
+    }
+
+    fn build(files: Vec<FileDescriptorProto>, main: String) -> Result<Self, CommonError> {
+        let mut pool = DescriptorPool::global();
+        let names = files
+            .iter()
+            .map(|f| f.name().to_string())
+            .filter(|name| pool.get_file_by_name(name).is_none())
+            .collect();
+        pool.add_file_descriptor_protos(files)
+            .map_err(|e| proto_error(&e.to_string()))?;
+        Ok(ProtobufSchema {
+            pool,
+            files: names,
+            main,
+        })
+    }
+
+    /// Every message declared by the schema's files, nested ones included.
+    pub fn messages(&self) -> Vec<MessageDescriptor> {
+        self.pool
+            .all_messages()
+            .filter(|m| self.files.iter().any(|f| f == m.parent_file().name()))
+            .collect()
+    }
+
+    /// Looks a message up by its full name, or by its name relative to the
+    /// main file's package. An empty name is the first message of the main
+    /// file.
+    pub fn message(&self, name: &str) -> Result<MessageDescriptor, CommonError> {
+        let main = self
+            .pool
+            .get_file_by_name(&self.main)
+            .ok_or_else(|| proto_error(&format!("main file {} not found", self.main)))?;
+        if name.is_empty() {
+            return main
+                .messages()
+                .next()
+                .ok_or_else(|| proto_error(&format!("{} declares no messages", self.main)));
+        }
+
+        let name = name.trim_start_matches('.');
+        let package = main.package_name();
+        self.pool
+            .get_message_by_name(name)
+            .or_else(|| {
+                (!package.is_empty())
+                    .then(|| self.pool.get_message_by_name(&format!("{package}.{name}")))
+                    .flatten()
+            })
+            .ok_or_else(|| proto_error(&format!("message {name} not found")))
+    }
+
+    /// The message Confluent message indexes point at: the first index picks
+    /// a top-level message of the main file in declaration order, each
+    /// following one a message nested in the previous.
+    pub fn message_by_indexes(&self, indexes: &[usize]) -> Option<MessageDescriptor> {
+        let (first, rest) = indexes.split_first()?;
+        let main = self.pool.get_file_by_name(&self.main)?;
+        let mut message = main.messages().nth(*first)?;
+        for index in rest {
+            let child = message.child_messages().nth(*index)?;
+            message = child;
+        }
+        Some(message)
+    }
+
+    /// Decodes `data` as `message`. The error says why it doesn't match.
+    pub fn check(
+        &self,
+        message: &MessageDescriptor,
+        data: &[u8],
+        options: &ProtobufValidateOptions,
+    ) -> Result<(), CommonError> {
+        let decoded = DynamicMessage::decode(message.clone(), data).map_err(|e| {
+            CommonError::CommonError(format!("cannot decode {}: {e}", message.full_name()))
+        })?;
+        if options.strict_unknown_fields || options.strict_required_fields {
+            check_message(&decoded, options)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_message(
+    message: &DynamicMessage,
+    options: &ProtobufValidateOptions,
+) -> Result<(), CommonError> {
+    let descriptor = message.descriptor();
+    if options.strict_unknown_fields {
+        if let Some(field) = message.unknown_fields().next() {
+            return Err(CommonError::CommonError(format!(
+                "{} has unknown field {}",
+                descriptor.full_name(),
+                field.number()
+            )));
+        }
+    }
+    if options.strict_required_fields {
+        for field in descriptor.fields() {
+            if field.cardinality() == Cardinality::Required && !message.has_field(&field) {
+                return Err(CommonError::CommonError(format!(
+                    "{} is missing required field {}",
+                    descriptor.full_name(),
+                    field.name()
+                )));
+            }
+        }
+    }
+    for (_, value) in message.fields() {
+        check_value(value, options)?;
+    }
+    Ok(())
+}
+
+fn check_value(value: &Value, options: &ProtobufValidateOptions) -> Result<(), CommonError> {
+    match value {
+        Value::Message(message) => check_message(message, options),
+        Value::List(values) => values.iter().try_for_each(|v| check_value(v, options)),
+        Value::Map(entries) => entries.values().try_for_each(|v| check_value(v, options)),
+        _ => Ok(()),
+    }
+}
 
+fn proto_error(message: &str) -> CommonError {
+    CommonError::CommonError(format!("invalid protobuf schema: {message}"))
+}
+
+/// Whether `data` decodes as `message_name`, or as the schema's first message
+/// when the name is empty.
 pub fn protobuf_validate(
     schema_data: &SchemaData,
     data: &[u8],
     message_name: &str,
+    options: &ProtobufValidateOptions,
 ) -> Result<bool, CommonError> {
-    let context = Context::parse([schema_data.schema.as_str()]).map_err(|err| {
+    let schema = ProtobufSchema::parse(&schema_data.schema).map_err(|err| {
         CommonError::CommonError(format!(
             "Failed to parse schema {}: {}",
             schema_data.name.as_str(),
@@ -29,7 +252,7 @@ pub fn protobuf_validate(
         ))
     })?;
 
-    let message = context.get_message(message_name).ok_or_else(|| {
+    let message = schema.message(message_name).map_err(|_| {
         CommonError::CommonError(format!(
             "Message {} not found in schema {}",
             message_name,
@@ -37,26 +260,26 @@ pub fn protobuf_validate(
         ))
     })?;
 
-    let decoded = message.decode(data, &context);
-
-    // Check if there are any unknown or incomplete fields
-    for field in decoded.fields {
-        match &field.value {
-            Value::Unknown(_) | Value::Incomplete(_, _) => {
-                return Ok(false); // Invalid if unknown or incomplete fields exist
-            }
-            _ => {} // Otherwise, it's fine
-        }
-    }
-
-    Ok(true)
+    Ok(schema.check(&message, data, options).is_ok())
 }
 
 #[cfg(test)]
 mod test {
-    use crate::protobuf::protobuf_validate;
+    use crate::protobuf::{
+        protobuf_validate, ProtobufBundle, ProtobufSchema, ProtobufValidateOptions,
+    };
     use metadata_struct::schema::{SchemaData, SchemaType};
 
+    fn schema_data(schema: &str) -> SchemaData {
+        SchemaData {
+            tenant: "test".to_string(),
+            name: "Proto".to_string(),
+            schema_type: SchemaType::PROTOBUF,
+            desc: "".to_string(),
+            schema: schema.to_string(),
+        }
+    }
+
     #[test]
     pub fn protobuf_validate_test() {
         protofish_example_test();
@@ -75,19 +298,14 @@ pub fn protofish_example_test() {
             }
         "#;
 
-        let schema_data = SchemaData {
-            tenant: "test".to_string(),
-            name: "Proto".to_string(),
-            schema_type: SchemaType::PROTOBUF,
-            desc: "".to_string(),
-            schema: schema.to_string(),
-        };
+        let schema_data = schema_data(schema);
+        let options = ProtobufValidateOptions::default();
 
-        let res = protobuf_validate(&schema_data, b"\x0a\x05Perch", "Proto.Request");
+        let res = protobuf_validate(&schema_data, b"\x0a\x05Perch", "Proto.Request", &options);
         assert!(res.is_ok());
         assert!(res.unwrap());
 
-        let res = protobuf_validate(&schema_data, b"\x08\xa9\x46", "Proto.Response");
+        let res = protobuf_validate(&schema_data, b"\x08\xa9\x46", "Proto.Response", &options);
         assert!(res.is_ok());
         assert!(res.unwrap());
 
@@ -95,9 +313,12 @@ pub fn protofish_example_test() {
             &schema_data,
             b"\x12\x07Unknown\x0a\x0fAtlantic ",
             "Proto.Request",
+            &options,
         );
         assert!(res.is_ok());
         assert!(!res.unwrap());
+
+        assert!(protobuf_validate(&schema_data, b"", "Proto.Missing", &options).is_err());
     }
 
     pub fn protofish_schema_test() {
@@ -120,13 +341,8 @@ pub fn protofish_schema_test() {
             }
         "#;
 
-        let schema_data = SchemaData {
-            tenant: "test".to_string(),
-            name: "MyPackage".to_string(),
-            schema_type: SchemaType::PROTOBUF,
-            desc: "".to_string(),
-            schema: schema.to_string(),
-        };
+        let schema_data = schema_data(schema);
+        let options = ProtobufValidateOptions::default();
 
         // ----- Experience -----
         // Experience {
@@ -139,7 +355,8 @@ pub fn protofish_schema_test() {
         let res = protobuf_validate(
             &schema_data,
             b"\x0a\x06Google\x12\x11Software Engineer\x18\xe5\x0f\x20\xe7\x0f\x2a\x04Java\x2a\x0cGoogle Cloud\x2a\x10Software Testing",
-            "MyPackage.Experience"
+            "MyPackage.Experience",
+            &options,
         );
 
         assert!(res.is_ok());
@@ -154,10 +371,178 @@ pub fn protofish_schema_test() {
         let res = protobuf_validate(
             &schema_data,
             b"\x0a\x04John\x10\x1e\x1a\x47\x0a\x06Google\x12\x11Software Engineer\x18\xe5\x0f\x20\xe7\x0f\x2a\x04Java\x2a\x0cGoogle Cloud\x2a\x10Software Testing",
-            "MyPackage.Person"
+            "Person",
+            &options,
         );
 
         assert!(res.is_ok());
         assert!(res.unwrap());
     }
+
+    #[test]
+    fn imports_and_well_known_types() {
+        let bundle = ProtobufBundle {
+            files: [
+                (
+                    "shop/common.proto".to_string(),
+                    r#"
+                    syntax = "proto3";
+                    package shop.common;
+                    message Money { string currency = 1; int64 units = 2; }
+                    "#
+                    .to_string(),
+                ),
+                (
+                    "shop/order.proto".to_string(),
+                    r#"
+                    syntax = "proto3";
+                    package shop;
+                    import "shop/common.proto";
+                    import "google/protobuf/timestamp.proto";
+                    message Order {
+                        string id = 1;
+                        common.Money total = 2;
+                        google.protobuf.Timestamp created = 3;
+                        map<string, common.Money> fees = 4;
+                    }
+                    "#
+                    .to_string(),
+                ),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        let schema = ProtobufSchema::parse(&serde_json::to_string(&bundle).unwrap()).unwrap();
+
+        // shop/order.proto is the only file nothing imports.
+        let order = schema.message("").unwrap();
+        assert_eq!(order.full_name(), "shop.Order");
+        assert_eq!(schema.message("Order").unwrap(), order);
+        assert_eq!(
+            schema.message(".shop.common.Money").unwrap().name(),
+            "Money"
+        );
+        assert!(schema
+            .messages()
+            .iter()
+            .all(|m| !m.full_name().starts_with("google.")));
+
+        // Order { id: "o1", total: { currency: "EUR", units: 5 }, created: { seconds: 1 } }
+        let data = b"\x0a\x02o1\x12\x07\x0a\x03EUR\x10\x05\x1a\x02\x08\x01";
+        let options = ProtobufValidateOptions::default();
+        assert!(schema.check(&order, data, &options).is_ok());
+        assert!(schema
+            .check(&order, b"\x12\x07\x0a\x03EU", &options)
+            .is_err());
+
+        let missing = ProtobufBundle {
+            files: [(
+                "a.proto".to_string(),
+                "import \"b.proto\"; message A { B b = 1; }".to_string(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        assert!(ProtobufSchema::parse(&serde_json::to_string(&missing).unwrap()).is_err());
+    }
+
+    #[test]
+    fn descriptor_set() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use prost::Message;
+
+        let file = crate::proto_parser::parse_proto_file(
+            "event.proto",
+            "syntax = \"proto3\"; package ev; message Event { string id = 1; message Inner { int32 n = 1; } }",
+        )
+        .unwrap();
+        let set = prost_types::FileDescriptorSet { file: vec![file] };
+        let bundle = ProtobufBundle {
+            descriptor_set: Some(STANDARD.encode(set.encode_to_vec())),
+            ..Default::default()
+        };
+        let schema = ProtobufSchema::parse(&serde_json::to_string(&bundle).unwrap()).unwrap();
+        assert_eq!(schema.message("").unwrap().full_name(), "ev.Event");
+        assert_eq!(
+            schema.message_by_indexes(&[0, 0]).unwrap().full_name(),
+            "ev.Event.Inner"
+        );
+
+        let invalid = r#"{"descriptor_set": "not base64!"}"#;
+        assert!(ProtobufSchema::parse(invalid).is_err());
+    }
+
+    #[test]
+    fn message_indexes() {
+        let schema = ProtobufSchema::parse(
+            r#"
+            syntax = "proto3";
+            package shop;
+
+            message Order {
+                string id = 1;
+                message Line {
+                    string sku = 1;
+                    message Price { int64 cents = 1; }
+                }
+                repeated Line lines = 2;
+            }
+
+            enum Status { UNKNOWN = 0; }
+
+            message Refund { string order_id = 1; }
+            "#,
+        )
+        .unwrap();
+
+        let name = |indexes: &[usize]| {
+            schema
+                .message_by_indexes(indexes)
+                .map(|m| m.full_name().to_string())
+        };
+        assert_eq!(name(&[0]).unwrap(), "shop.Order");
+        assert_eq!(name(&[1]).unwrap(), "shop.Refund");
+        assert_eq!(name(&[0, 0]).unwrap(), "shop.Order.Line");
+        assert_eq!(name(&[0, 0, 0]).unwrap(), "shop.Order.Line.Price");
+        assert!(name(&[2]).is_none());
+        assert!(name(&[0, 1]).is_none());
+        assert!(name(&[]).is_none());
+    }
+
+    #[test]
+    fn strict_modes() {
+        let schema = ProtobufSchema::parse(
+            r#"
+            syntax = "proto2";
+            message Item { required string sku = 1; optional int32 qty = 2; }
+            message Cart { repeated Item items = 1; }
+            "#,
+        )
+        .unwrap();
+        let cart = schema.message("Cart").unwrap();
+        let lenient = ProtobufValidateOptions::default();
+        let strict_unknown = ProtobufValidateOptions {
+            strict_unknown_fields: true,
+            ..Default::default()
+        };
+        let strict_required = ProtobufValidateOptions {
+            strict_required_fields: true,
+            ..Default::default()
+        };
+
+        // Cart { items: [{ sku: "a", qty: 1 }] }
+        let valid = b"\x0a\x05\x0a\x01a\x10\x01";
+        // an item with field 3, which Item doesn't declare
+        let unknown = b"\x0a\x07\x0a\x01a\x18\x01\x10\x01";
+        // an item without its sku
+        let no_sku = b"\x0a\x02\x10\x01";
+
+        for options in [lenient, strict_unknown, strict_required] {
+            assert!(schema.check(&cart, valid, &options).is_ok());
+        }
+        assert!(schema.check(&cart, unknown, &lenient).is_ok());
+        assert!(schema.check(&cart, unknown, &strict_unknown).is_err());
+        assert!(schema.check(&cart, no_sku, &lenient).is_ok());
+        assert!(schema.check(&cart, no_sku, &strict_required).is_err());
+    }
 }
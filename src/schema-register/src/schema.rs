@@ -17,9 +17,13 @@
 
 use common_base::error::common::CommonError;
 use dashmap::DashMap;
-use metadata_struct::schema::{SchemaData, SchemaResourceBind, SchemaType};
+use metadata_struct::adapter::adapter_record::{AdapterWriteRecord, RecordHeader};
+use metadata_struct::schema::{SchemaData, SchemaResourceBind, SchemaType, SchemaVersion};
 
-use crate::{avro::avro_validate, json::json_validate};
+use crate::avro::{avro_datum_validate, avro_validate};
+use crate::json::json_validate;
+use crate::protobuf::{ProtobufSchema, ProtobufValidateOptions};
+use crate::wire::{parse_confluent_header, parse_message_indexes};
 
 struct TenantSchemas {
     // schema_name -> SchemaData
@@ -28,6 +32,10 @@ struct TenantSchemas {
     resource_schema_list: DashMap<String, Vec<String>>,
     // schema_name -> [resource_name]
     schema_resource_list: DashMap<String, Vec<String>>,
+    // (resource_name, schema_name) -> SchemaResourceBind
+    bind_list: DashMap<(String, String), SchemaResourceBind>,
+    // subject -> [SchemaVersion], ordered by version
+    subject_versions: DashMap<String, Vec<SchemaVersion>>,
 }
 
 impl TenantSchemas {
@@ -36,6 +44,8 @@ fn new() -> Self {
             schema_list: DashMap::new(),
             resource_schema_list: DashMap::new(),
             schema_resource_list: DashMap::new(),
+            bind_list: DashMap::new(),
+            subject_versions: DashMap::new(),
         }
     }
 }
@@ -44,12 +54,18 @@ fn new() -> Self {
 pub struct SchemaRegisterManager {
     // tenant -> TenantSchemas
     tenants: DashMap<String, Arc<TenantSchemas>>,
+    // schema id -> a version registered with that id
+    schema_ids: DashMap<u32, SchemaVersion>,
+    // protobuf schema text -> its parsed descriptors
+    protobuf_schemas: DashMap<String, ProtobufSchema>,
 }
 
 impl SchemaRegisterManager {
     pub fn new() -> Self {
         SchemaRegisterManager {
             tenants: DashMap::new(),
+            schema_ids: DashMap::new(),
+            protobuf_schemas: DashMap::new(),
         }
     }
 
@@ -82,7 +98,9 @@ pub fn validate(&self, tenant: &str, resource: &str, data: &[u8]) -> Result<bool
                     if let Some(schema) = t.schema_list.get(schema_name.as_str()) {
                         match schema.schema_type {
                             SchemaType::JSON => {
-                                let raw = from_utf8(data).unwrap();
+                                let Ok(raw) = from_utf8(data) else {
+                                    return Ok(false);
+                                };
                                 return json_validate(&schema.schema, raw);
                             }
                             SchemaType::PROTOBUF => {}
@@ -97,15 +115,84 @@ pub fn validate(&self, tenant: &str, resource: &str, data: &[u8]) -> Result<bool
         Ok(true)
     }
 
+    /// Checks a payload published to `resource` against every schema bound to
+    /// it and, once the resource's `<resource>-value` subject has versions,
+    /// against the version whose id the Confluent wire-format header carries.
+    /// Unframed payloads are checked against the subject's latest version
+    /// unless `require_wire_format` is set, in which case they are rejected.
+    /// The error says why the payload was rejected.
+    pub fn validate_payload(
+        &self,
+        tenant: &str,
+        resource: &str,
+        data: &[u8],
+        require_wire_format: bool,
+    ) -> Result<(), CommonError> {
+        let bound = self.get_bind_schema_by_resource(tenant, resource);
+        let versions = self.get_subject_versions(tenant, &value_subject(resource));
+        if bound.is_empty() && versions.is_empty() {
+            return Ok(());
+        }
+
+        let mut payload = data;
+        let mut message_indexes = vec![0];
+        if let Some(latest) = versions.last() {
+            match parse_confluent_header(data) {
+                Some((id, body)) => {
+                    let version = versions.iter().find(|v| v.id == id).ok_or_else(|| {
+                        CommonError::CommonError(format!(
+                            "schema id {id} is not registered under subject {}",
+                            latest.subject
+                        ))
+                    })?;
+                    let (indexes, body) = match version.schema_type {
+                        SchemaType::PROTOBUF => parse_message_indexes(body).ok_or_else(|| {
+                            CommonError::CommonError("invalid protobuf message indexes".to_string())
+                        })?,
+                        _ => (vec![0], body),
+                    };
+                    self.check_payload(&version_schema(version), body, true, &indexes, None)?;
+                    payload = body;
+                    message_indexes = indexes;
+                }
+                None if require_wire_format => {
+                    return Err(CommonError::CommonError(format!(
+                        "payload is not in the Confluent wire format required by subject {}",
+                        latest.subject
+                    )));
+                }
+                None => self.check_payload(&version_schema(latest), data, true, &[0], None)?,
+            }
+        }
+
+        // Once the resource has a subject its payloads are Confluent-style
+        // bare Avro datums, for the bound schemas as well.
+        for schema in bound.iter() {
+            let bind = self.get_bind(tenant, resource, &schema.name);
+            self.check_payload(
+                schema,
+                payload,
+                !versions.is_empty(),
+                &message_indexes,
+                bind.as_ref(),
+            )?;
+        }
+        Ok(())
+    }
+
     // Schema
     pub fn add_schema(&self, schema: SchemaData) {
         let t = self.get_or_create_tenant(&schema.tenant.clone());
-        t.schema_list.insert(schema.name.clone(), schema);
+        if let Some(old) = t.schema_list.insert(schema.name.clone(), schema) {
+            self.protobuf_schemas.remove(&old.schema);
+        }
     }
 
     pub fn remove_schema(&self, tenant: &str, schema_name: &str) {
         if let Some(t) = self.get_tenant(tenant) {
-            t.schema_list.remove(schema_name);
+            if let Some((_, old)) = t.schema_list.remove(schema_name) {
+                self.protobuf_schemas.remove(&old.schema);
+            }
         }
     }
 
@@ -137,11 +224,73 @@ pub fn get_all_schema_all_tenants(&self) -> Vec<(String, SchemaData)> {
             .collect()
     }
 
+    // Schema Version
+    pub fn add_schema_version(&self, schema_version: SchemaVersion) {
+        let t = self.get_or_create_tenant(&schema_version.tenant);
+        self.schema_ids
+            .insert(schema_version.id, schema_version.clone());
+
+        let mut versions = t
+            .subject_versions
+            .entry(schema_version.subject.clone())
+            .or_default();
+        versions.retain(|v| v.version != schema_version.version);
+        versions.push(schema_version);
+        versions.sort_by_key(|v| v.version);
+    }
+
+    pub fn remove_schema_version(&self, tenant: &str, subject: &str, version: u32) {
+        let Some(t) = self.get_tenant(tenant) else {
+            return;
+        };
+        let mut removed = Vec::new();
+        if let Some(mut versions) = t.subject_versions.get_mut(subject) {
+            versions.retain(|v| {
+                if v.version == version {
+                    removed.push(v.id);
+                    self.protobuf_schemas.remove(&v.schema);
+                }
+                v.version != version
+            });
+        }
+        t.subject_versions.remove_if(subject, |_, v| v.is_empty());
+
+        // An id stays resolvable while any subject still has a version with it.
+        for id in removed {
+            let other = self.tenants.iter().find_map(|tenant| {
+                tenant
+                    .subject_versions
+                    .iter()
+                    .find_map(|versions| versions.iter().find(|v| v.id == id).cloned())
+            });
+            match other {
+                Some(other) => {
+                    self.schema_ids.insert(id, other);
+                }
+                None => {
+                    self.schema_ids.remove(&id);
+                }
+            }
+        }
+    }
+
+    pub fn get_schema_by_id(&self, id: u32) -> Option<SchemaVersion> {
+        self.schema_ids.get(&id).map(|v| v.clone())
+    }
+
+    pub fn get_subject_versions(&self, tenant: &str, subject: &str) -> Vec<SchemaVersion> {
+        self.get_tenant(tenant)
+            .and_then(|t| t.subject_versions.get(subject).map(|v| v.clone()))
+            .unwrap_or_default()
+    }
+
     // Schema Resource Bind
     pub fn add_bind(&self, bind: &SchemaResourceBind) {
         let t = self.get_or_create_tenant(&bind.tenant);
         let schema_name = bind.schema_name.clone();
         let resource_name = bind.resource_name.clone();
+        t.bind_list
+            .insert((resource_name.clone(), schema_name.clone()), bind.clone());
 
         t.resource_schema_list
             .entry(resource_name.clone())
@@ -166,9 +315,24 @@ pub fn remove_bind(&self, bind: &SchemaResourceBind) {
         if let Some(t) = self.get_tenant(&bind.tenant) {
             t.resource_schema_list.remove(&bind.resource_name);
             t.schema_resource_list.remove(&bind.schema_name);
+            t.bind_list
+                .retain(|(resource_name, _), _| resource_name != &bind.resource_name);
         }
     }
 
+    fn get_bind(
+        &self,
+        tenant: &str,
+        resource_name: &str,
+        schema_name: &str,
+    ) -> Option<SchemaResourceBind> {
+        self.get_tenant(tenant).and_then(|t| {
+            t.bind_list
+                .get(&(resource_name.to_string(), schema_name.to_string()))
+                .map(|b| b.clone())
+        })
+    }
+
     pub fn get_bind_schema_by_resource(
         &self,
         tenant: &str,
@@ -196,4 +360,248 @@ pub fn get_bind_resource_by_schema(&self, tenant: &str, schema_name: &str) -> Ve
         }
         vec![]
     }
+
+    // `avro_datum` is set for payloads from Confluent serializers, which write
+    // a bare datum rather than an object container file. Protobuf payloads
+    // are checked as the message the binding names, or else as the one the
+    // message indexes point at.
+    fn check_payload(
+        &self,
+        schema: &SchemaData,
+        data: &[u8],
+        avro_datum: bool,
+        message_indexes: &[usize],
+        bind: Option<&SchemaResourceBind>,
+    ) -> Result<(), CommonError> {
+        let valid = match schema.schema_type {
+            SchemaType::JSON => match from_utf8(data) {
+                Ok(raw) => json_validate(&schema.schema, raw),
+                Err(_) => Ok(false),
+            },
+            SchemaType::AVRO if avro_datum => avro_datum_validate(&schema.schema, data),
+            SchemaType::AVRO => avro_validate(&schema.schema, data),
+            SchemaType::PROTOBUF => self.check_protobuf(schema, data, message_indexes, bind),
+        };
+        match valid {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(CommonError::CommonError(format!(
+                "payload does not match {} schema {}",
+                schema.schema_type, schema.name
+            ))),
+            Err(e) => Err(CommonError::CommonError(format!(
+                "payload does not match {} schema {}: {}",
+                schema.schema_type, schema.name, e
+            ))),
+        }
+    }
+
+    fn check_protobuf(
+        &self,
+        schema: &SchemaData,
+        data: &[u8],
+        message_indexes: &[usize],
+        bind: Option<&SchemaResourceBind>,
+    ) -> Result<bool, CommonError> {
+        let parsed = self.protobuf_schema(&schema.schema)?;
+        let message = match bind {
+            Some(bind) if !bind.message_name.is_empty() => parsed.message(&bind.message_name)?,
+            _ => parsed.message_by_indexes(message_indexes).ok_or_else(|| {
+                CommonError::CommonError(format!(
+                    "message indexes {message_indexes:?} not found in schema {}",
+                    schema.name
+                ))
+            })?,
+        };
+        let options = bind
+            .map(|bind| ProtobufValidateOptions {
+                strict_unknown_fields: bind.strict_unknown_fields,
+                strict_required_fields: bind.strict_required_fields,
+            })
+            .unwrap_or_default();
+        parsed.check(&message, data, &options)?;
+        Ok(true)
+    }
+
+    // Parsing builds a descriptor pool, so it is done once per schema text.
+    fn protobuf_schema(&self, schema: &str) -> Result<ProtobufSchema, CommonError> {
+        if let Some(parsed) = self.protobuf_schemas.get(schema) {
+            return Ok(parsed.clone());
+        }
+        let parsed = ProtobufSchema::parse(schema)?;
+        self.protobuf_schemas
+            .insert(schema.to_string(), parsed.clone());
+        Ok(parsed)
+    }
+}
+
+/// The subject Confluent serializers register a resource's values under.
+pub fn value_subject(resource: &str) -> String {
+    format!("{resource}-value")
+}
+
+/// Wraps a payload rejected by [`SchemaRegisterManager::validate_payload`] for
+/// the dead-letter topic. The headers say where it was published and why it
+/// was rejected.
+pub fn dead_letter_record(
+    dead_letter_topic: &str,
+    protocol: &str,
+    resource: &str,
+    reason: &str,
+    data: &[u8],
+) -> AdapterWriteRecord {
+    AdapterWriteRecord::new(dead_letter_topic, data.to_vec()).with_header(vec![
+        RecordHeader {
+            name: "schema-error".to_string(),
+            value: reason.to_string(),
+        },
+        RecordHeader {
+            name: "schema-resource".to_string(),
+            value: resource.to_string(),
+        },
+        RecordHeader {
+            name: "schema-protocol".to_string(),
+            value: protocol.to_string(),
+        },
+    ])
+}
+
+fn version_schema(version: &SchemaVersion) -> SchemaData {
+    SchemaData {
+        tenant: version.tenant.clone(),
+        name: version.subject.clone(),
+        schema_type: version.schema_type.clone(),
+        desc: String::new(),
+        schema: version.schema.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const JSON_SCHEMA: &str = r#"{"type": "object", "required": ["id"]}"#;
+
+    fn json_version(id: u32) -> SchemaVersion {
+        SchemaVersion {
+            tenant: "t1".to_string(),
+            subject: value_subject("orders"),
+            version: 1,
+            id,
+            schema_type: SchemaType::JSON,
+            schema: JSON_SCHEMA.to_string(),
+            create_time: 0,
+        }
+    }
+
+    fn framed(id: u32, payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![0];
+        data.extend_from_slice(&id.to_be_bytes());
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn validate_payload_against_bound_schema() {
+        let manager = SchemaRegisterManager::new();
+        assert!(manager
+            .validate_payload("t1", "orders", b"not json", true)
+            .is_ok());
+
+        manager.add_schema(SchemaData {
+            tenant: "t1".to_string(),
+            name: "order".to_string(),
+            schema_type: SchemaType::JSON,
+            desc: String::new(),
+            schema: JSON_SCHEMA.to_string(),
+        });
+        manager.add_bind(&SchemaResourceBind {
+            tenant: "t1".to_string(),
+            schema_name: "order".to_string(),
+            resource_name: "orders".to_string(),
+            message_name: String::new(),
+            strict_unknown_fields: false,
+            strict_required_fields: false,
+        });
+
+        assert!(manager
+            .validate_payload("t1", "orders", br#"{"id": 1}"#, false)
+            .is_ok());
+        assert!(manager
+            .validate_payload("t1", "orders", br#"{"name": "a"}"#, false)
+            .is_err());
+        assert!(manager
+            .validate_payload("t1", "orders", b"\xff\xfe", false)
+            .is_err());
+    }
+
+    #[test]
+    fn validate_payload_wire_format() {
+        let manager = SchemaRegisterManager::new();
+        manager.add_schema_version(json_version(7));
+
+        assert!(manager
+            .validate_payload("t1", "orders", &framed(7, br#"{"id": 1}"#), true)
+            .is_ok());
+        assert!(manager
+            .validate_payload("t1", "orders", &framed(7, br#"{}"#), true)
+            .is_err());
+        // an id registered under another subject
+        assert!(manager
+            .validate_payload("t1", "orders", &framed(8, br#"{"id": 1}"#), true)
+            .is_err());
+
+        // unframed payloads only pass where the wire format is optional
+        assert!(manager
+            .validate_payload("t1", "orders", br#"{"id": 1}"#, true)
+            .is_err());
+        assert!(manager
+            .validate_payload("t1", "orders", br#"{"id": 1}"#, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_payload_protobuf_bind_options() {
+        let manager = SchemaRegisterManager::new();
+        manager.add_schema(SchemaData {
+            tenant: "t1".to_string(),
+            name: "order".to_string(),
+            schema_type: SchemaType::PROTOBUF,
+            desc: String::new(),
+            schema: r#"
+                syntax = "proto2";
+                message Header { optional string trace = 1; }
+                message Order { required string id = 1; }
+            "#
+            .to_string(),
+        });
+        let mut bind = SchemaResourceBind {
+            tenant: "t1".to_string(),
+            schema_name: "order".to_string(),
+            resource_name: "orders".to_string(),
+            message_name: "Order".to_string(),
+            strict_unknown_fields: false,
+            strict_required_fields: true,
+        };
+        manager.add_bind(&bind);
+
+        // Order { id: "o1" }
+        assert!(manager
+            .validate_payload("t1", "orders", b"\x0a\x02o1", false)
+            .is_ok());
+        // an empty Order is a valid Header, but the binding names Order
+        assert!(manager
+            .validate_payload("t1", "orders", b"", false)
+            .is_err());
+        // Order { id: "o1" } with field 2
+        let extra = b"\x0a\x02o1\x10\x01";
+        assert!(manager
+            .validate_payload("t1", "orders", extra, false)
+            .is_ok());
+
+        bind.strict_unknown_fields = true;
+        manager.add_bind(&bind);
+        assert!(manager
+            .validate_payload("t1", "orders", extra, false)
+            .is_err());
+    }
 }
@@ -0,0 +1,94 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Confluent wire format: a zero magic byte and the 4-byte big-endian schema
+//! id, then for protobuf the indexes of the message type, then the payload.
+
+pub const CONFLUENT_MAGIC_BYTE: u8 = 0;
+
+const CONFLUENT_HEADER_LEN: usize = 5;
+
+/// Splits the schema id off a payload. `None` if it doesn't start with the
+/// wire-format header.
+pub fn parse_confluent_header(data: &[u8]) -> Option<(u32, &[u8])> {
+    if data.len() < CONFLUENT_HEADER_LEN || data[0] != CONFLUENT_MAGIC_BYTE {
+        return None;
+    }
+    let id = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+    Some((id, &data[CONFLUENT_HEADER_LEN..]))
+}
+
+/// Splits the message indexes off a protobuf payload: a zigzag varint count
+/// followed by that many zigzag varint indexes. A count of zero is the
+/// shorthand for the first message in the schema.
+pub fn parse_message_indexes(data: &[u8]) -> Option<(Vec<usize>, &[u8])> {
+    let (count, mut rest) = read_zigzag_varint(data)?;
+    if count == 0 {
+        return Some((vec![0], rest));
+    }
+    let count = usize::try_from(count).ok()?;
+    if count > rest.len() {
+        return None;
+    }
+    let mut indexes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (index, next) = read_zigzag_varint(rest)?;
+        indexes.push(usize::try_from(index).ok()?);
+        rest = next;
+    }
+    Some((indexes, rest))
+}
+
+fn read_zigzag_varint(data: &[u8]) -> Option<(i64, &[u8])> {
+    let mut value: u64 = 0;
+    for (i, byte) in data.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            let decoded = (value >> 1) as i64 ^ -((value & 1) as i64);
+            return Some((decoded, &data[i + 1..]));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn confluent_header() {
+        let (id, payload) = parse_confluent_header(b"\x00\x00\x00\x01\x02data").unwrap();
+        assert_eq!(id, 258);
+        assert_eq!(payload, b"data");
+
+        assert!(parse_confluent_header(b"\x01\x00\x00\x00\x01data").is_none());
+        assert!(parse_confluent_header(b"\x00\x00\x01").is_none());
+    }
+
+    #[test]
+    fn message_indexes() {
+        assert_eq!(
+            parse_message_indexes(b"\x00rest").unwrap(),
+            (vec![0], &b"rest"[..])
+        );
+        // count 2, then 1 and 0
+        assert_eq!(
+            parse_message_indexes(b"\x04\x02\x00rest").unwrap(),
+            (vec![1, 0], &b"rest"[..])
+        );
+        // a negative count
+        assert!(parse_message_indexes(b"\x01").is_none());
+        assert!(parse_message_indexes(b"\x04\x02").is_none());
+    }
+}
@@ -159,6 +159,35 @@ pub async fn commit(&self) -> Result<(), CommonError> {
         Ok(())
     }
 
+    /// Persist explicit per-shard next-read offsets without touching the consume position.
+    ///
+    /// For callers that read ahead of what they acknowledge (e.g. a connector
+    /// buffering records into larger files): the read position moves with `advance`,
+    /// while only the offsets that are actually durable get persisted here.
+    pub async fn commit_shard_offsets(
+        &self,
+        tenant: &str,
+        topic: &str,
+        next_offsets: &HashMap<String, u64>,
+    ) -> Result<(), CommonError> {
+        if next_offsets.is_empty() {
+            return Ok(());
+        }
+
+        let offsets: Vec<AdapterCommitOffset> = next_offsets
+            .iter()
+            .map(|(shard, offset)| AdapterCommitOffset {
+                shard_name: shard.clone(),
+                topic_name: topic.to_string(),
+                partition: 0,
+                offset: *offset,
+            })
+            .collect();
+        self.driver
+            .commit_group_offset(tenant, &self.group_name, &offsets)
+            .await
+    }
+
     /// Stage a single shard offset directly into pending_offsets.
     ///
     /// Useful when the caller knows the exact target offset (e.g. ACK with msg_id,
@@ -17,7 +17,8 @@
     cache::NodeCacheManager,
     inner_topic::{
         AGENT_REPORT_INFO_TOPIC, DELAY_QUEUE_INDEX_TOPIC, DELAY_QUEUE_MESSAGE_TOPIC,
-        DELAY_TASK_INDEX_TOPIC, LAST_WILL_MESSAGE_TOPIC, QOS2_INNER_TOPIC, RETAIN_MESSAGE_TOPIC,
+        DELAY_TASK_INDEX_TOPIC, KAFKA_TRANSACTION_LOG_TOPIC, LAST_WILL_MESSAGE_TOPIC,
+        QOS2_INNER_TOPIC, RETAIN_MESSAGE_TOPIC,
     },
 };
 use common_base::error::common::CommonError;
@@ -168,6 +169,7 @@ pub async fn init_inner_topics(
         DELAY_QUEUE_INDEX_TOPIC,
         AGENT_REPORT_INFO_TOPIC,
         QOS2_INNER_TOPIC,
+        KAFKA_TRANSACTION_LOG_TOPIC,
     ] {
         init_single_inner_topic(
             broker_cache,
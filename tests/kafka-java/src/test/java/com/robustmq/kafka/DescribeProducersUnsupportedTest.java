@@ -31,12 +31,11 @@ import org.junit.jupiter.api.Test;
 import org.junit.jupiter.api.function.Executable;
 
 /**
- * Transactions are not implemented, so the transaction admin APIs and
- * DescribeProducers are deliberately not advertised. They must therefore fail
- * fast with UnsupportedVersion rather than hang (the old behaviour was a handler
- * returning no response, leaving the client waiting until timeout).
+ * DescribeProducers is deliberately not advertised, so it must fail fast with
+ * UnsupportedVersion rather than hang (the old behaviour was a handler returning
+ * no response, leaving the client waiting until timeout).
  */
-class TransactionUnsupportedTest {
+class DescribeProducersUnsupportedTest {
 
     private static Admin fastAdmin() {
         return Support.newAdmin(Map.of(
@@ -50,21 +49,6 @@ class TransactionUnsupportedTest {
                 "unadvertised API should fail fast with UnsupportedVersion, got " + ex.getCause());
     }
 
-    @Test
-    void listTransactionsIsUnsupported() throws Exception {
-        try (Admin admin = fastAdmin()) {
-            assertUnsupported(() -> admin.listTransactions().all().get());
-        }
-    }
-
-    @Test
-    void describeTransactionsIsUnsupported() throws Exception {
-        try (Admin admin = fastAdmin()) {
-            assertUnsupported(() -> admin.describeTransactions(List.of("txn-" + UUID.randomUUID()))
-                    .all().get());
-        }
-    }
-
     @Test
     void describeProducersIsUnsupported() throws Exception {
         String topic = "it-dp-" + UUID.randomUUID();
@@ -33,9 +33,9 @@ import org.apache.kafka.clients.producer.RecordMetadata;
 import org.junit.jupiter.api.Test;
 
 /**
- * Phase 5 (idempotence only, no transactions): the default idempotent producer
+ * Phase 5 (idempotence): the default idempotent producer
  * (enable.idempotence=true) must obtain a producer id via InitProducerId(22) and
- * produce successfully. Transactions remain unimplemented.
+ * produce successfully. Transactions are covered by {@link TransactionTest}.
  */
 class IdempotentProduceTest {
 
@@ -0,0 +1,146 @@
+/*
+ * Copyright 2023 RobustMQ Team
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.robustmq.kafka;
+
+import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertTrue;
+
+import java.time.Duration;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Map;
+import java.util.UUID;
+
+import org.apache.kafka.clients.admin.Admin;
+import org.apache.kafka.clients.admin.NewTopic;
+import org.apache.kafka.clients.admin.TransactionDescription;
+import org.apache.kafka.clients.admin.TransactionListing;
+import org.apache.kafka.clients.admin.TransactionState;
+import org.apache.kafka.clients.consumer.ConsumerConfig;
+import org.apache.kafka.clients.consumer.ConsumerRecord;
+import org.apache.kafka.clients.consumer.KafkaConsumer;
+import org.apache.kafka.clients.producer.KafkaProducer;
+import org.apache.kafka.clients.producer.ProducerConfig;
+import org.apache.kafka.clients.producer.ProducerRecord;
+import org.apache.kafka.common.TopicPartition;
+import org.junit.jupiter.api.Test;
+
+/**
+ * Transactional produce: InitProducerId with a transactional id, commit/abort
+ * markers, and read_committed fetch hiding aborted records.
+ */
+class TransactionTest {
+
+    private static String name() {
+        return "it-txn-" + UUID.randomUUID();
+    }
+
+    private static void createTopic(String topic, int partitions) throws Exception {
+        try (Admin admin = Support.newAdmin()) {
+            admin.createTopics(List.of(new NewTopic(topic, partitions, (short) 1))).all().get();
+        }
+    }
+
+    private static KafkaProducer<byte[], byte[]> transactionalProducer(String transactionalId) {
+        return Support.newProducer(Map.of(
+                ProducerConfig.ENABLE_IDEMPOTENCE_CONFIG, true,
+                ProducerConfig.TRANSACTIONAL_ID_CONFIG, transactionalId));
+    }
+
+    /** Values visible to a read_committed consumer, drained until `expected` arrive or the deadline passes. */
+    private static List<String> readCommitted(String topic, int expected) {
+        try (KafkaConsumer<byte[], byte[]> consumer = Support.newConsumer(null,
+                Map.of(ConsumerConfig.ISOLATION_LEVEL_CONFIG, "read_committed"))) {
+            TopicPartition tp = new TopicPartition(topic, 0);
+            consumer.assign(List.of(tp));
+            consumer.seekToBeginning(List.of(tp));
+            List<String> out = new ArrayList<>();
+            long deadline = System.nanoTime() + Duration.ofSeconds(10).toNanos();
+            while (out.size() < expected && System.nanoTime() < deadline) {
+                for (ConsumerRecord<byte[], byte[]> r : consumer.poll(Duration.ofMillis(300))) {
+                    out.add(new String(r.value()));
+                }
+            }
+            return out;
+        }
+    }
+
+    @Test
+    void committedRecordsAreVisibleAndAbortedAreNot() throws Exception {
+        String topic = name();
+        createTopic(topic, 1);
+
+        try (KafkaProducer<byte[], byte[]> producer = transactionalProducer(name())) {
+            producer.initTransactions();
+
+            producer.beginTransaction();
+            producer.send(new ProducerRecord<>(topic, 0, null, "c1".getBytes()));
+            producer.send(new ProducerRecord<>(topic, 0, null, "c2".getBytes()));
+            producer.commitTransaction();
+
+            producer.beginTransaction();
+            producer.send(new ProducerRecord<>(topic, 0, null, "a1".getBytes()));
+            producer.flush();
+            producer.abortTransaction();
+
+            producer.beginTransaction();
+            producer.send(new ProducerRecord<>(topic, 0, null, "c3".getBytes()));
+            producer.commitTransaction();
+        }
+
+        assertEquals(List.of("c1", "c2", "c3"), readCommitted(topic, 3),
+                "read_committed should skip the aborted record and the control markers");
+
+        // read_uncommitted still sees the aborted record; markers are never returned.
+        List<String> all = new ArrayList<>();
+        for (ConsumerRecord<byte[], byte[]> r : Support.consumeAllFromBeginning(topic, 4)) {
+            all.add(new String(r.value()));
+        }
+        assertEquals(List.of("c1", "c2", "a1", "c3"), all);
+    }
+
+    @Test
+    void listAndDescribeTransactions() throws Exception {
+        String topic = name();
+        String transactionalId = name();
+        createTopic(topic, 1);
+
+        try (KafkaProducer<byte[], byte[]> producer = transactionalProducer(transactionalId);
+                Admin admin = Support.newAdmin()) {
+            producer.initTransactions();
+            producer.beginTransaction();
+            producer.send(new ProducerRecord<>(topic, 0, null, "v".getBytes())).get();
+
+            TransactionDescription ongoing =
+                    admin.describeTransactions(List.of(transactionalId)).description(transactionalId).get();
+            assertEquals(TransactionState.ONGOING, ongoing.state());
+            assertTrue(ongoing.topicPartitions().contains(new TopicPartition(topic, 0)));
+
+            producer.commitTransaction();
+
+            TransactionDescription done =
+                    admin.describeTransactions(List.of(transactionalId)).description(transactionalId).get();
+            assertEquals(TransactionState.COMPLETE_COMMIT, done.state());
+            assertTrue(done.topicPartitions().isEmpty());
+
+            boolean listed = false;
+            for (TransactionListing listing : admin.listTransactions().all().get()) {
+                listed |= listing.transactionalId().equals(transactionalId);
+            }
+            assertTrue(listed, "listTransactions should include " + transactionalId);
+        }
+    }
+}